@@ -0,0 +1,468 @@
+// Compiler passes checked against the tree engine. They live in `sl-api` because `sl-compiler`
+// must not depend on `sl-runtime`.
+use super::*;
+use regex::Regex;
+use sl_compiler::{lower_to_linear, merge_bundles};
+use sl_core::{ChoiceEntry, LinearInstruction, LinearProgram, ScriptNode, ScriptTarget};
+use std::collections::BTreeSet;
+
+fn map(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+    entries
+        .iter()
+        .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+        .collect()
+}
+
+/// Run `entry` on the tree engine, taking `picks` at successive choices.
+///
+/// Choices render as `choices[prompt]: a|b`; the run stops at a choice once `picks` is used up and
+/// otherwise ends with `end`.
+fn run_tree(bundle: &CompileProjectBundleResult, entry: &str, picks: &[usize]) -> Vec<String> {
+    let bundle = bundle.clone();
+    let mut engine = ScriptLangEngine::new(ScriptLangEngineOptions {
+        scripts: bundle.scripts,
+        global_data: bundle.global_data,
+        module_var_declarations: bundle.module_var_declarations,
+        module_var_init_order: bundle.module_var_init_order,
+        module_const_declarations: bundle.module_const_declarations,
+        module_const_init_order: bundle.module_const_init_order,
+        ..Default::default()
+    })
+    .expect("engine");
+    engine.start(entry, None).expect("start");
+    let mut picks = picks.to_vec();
+    let mut outputs = Vec::new();
+    loop {
+        match engine.next_output().expect("output") {
+            EngineOutput::Text { text, .. } => outputs.push(text),
+            EngineOutput::Choices {
+                items, prompt_text, ..
+            } => {
+                let texts = items.into_iter().map(|item| item.text).collect::<Vec<_>>();
+                outputs.push(format!(
+                    "choices[{}]: {}",
+                    prompt_text.unwrap_or_default(),
+                    texts.join("|")
+                ));
+                if picks.is_empty() {
+                    return outputs;
+                }
+                engine.choose(picks.remove(0)).expect("choose");
+            }
+            other => {
+                assert_eq!(other, EngineOutput::End, "unexpected output");
+                outputs.push("end".to_string());
+                return outputs;
+            }
+        }
+    }
+}
+
+enum Flow {
+    Returned,
+    Ended,
+    Stuck,
+}
+
+/// Reference VM for the linear form, just enough for the examples below: plain Rhai vars,
+/// `${...}` interpolation, static options and literal `<call>` targets.
+struct LinearVm {
+    programs: BTreeMap<String, LinearProgram>,
+    rhai: rhai::Engine,
+    picks: Vec<usize>,
+    once_seen: BTreeSet<String>,
+    select_counts: BTreeMap<String, u32>,
+    outputs: Vec<String>,
+}
+
+impl LinearVm {
+    fn eval(&self, scope: &mut rhai::Scope, expr: &str) -> rhai::Dynamic {
+        self.rhai
+            .eval_with_scope::<rhai::Dynamic>(scope, expr)
+            .expect("eval")
+    }
+
+    fn render(&self, scope: &mut rhai::Scope, template: &str) -> String {
+        let pattern = Regex::new(r"\$\{([^}]*)\}").expect("regex");
+        let mut out = String::new();
+        let mut last = 0;
+        for captures in pattern.captures_iter(template) {
+            let whole = captures.get(0).expect("match");
+            out.push_str(&template[last..whole.start()]);
+            let value = self.eval(scope, &captures[1]);
+            out.push_str(&value.to_string());
+            last = whole.end();
+        }
+        out.push_str(&template[last..]);
+        out
+    }
+
+    fn run(&mut self, script_name: &str, scope: &mut rhai::Scope) -> Flow {
+        let program = self.programs[script_name].clone();
+        let mut marks = Vec::new();
+        let mut loop_counts = BTreeMap::<usize, usize>::new();
+        let mut pc = 0;
+        loop {
+            match &program.instructions[pc] {
+                LinearInstruction::Node { node } => match node {
+                    ScriptNode::Text {
+                        id, value, once, ..
+                    } => {
+                        if !*once || self.once_seen.insert(format!("text:{}", id)) {
+                            let text = self.render(scope, value);
+                            self.outputs.push(text);
+                        }
+                    }
+                    ScriptNode::Code { code, .. } => {
+                        self.rhai.run_with_scope(scope, code).expect("run");
+                    }
+                    ScriptNode::Var { declaration, .. } => {
+                        let value = match &declaration.initial_value_expr {
+                            Some(expr) => self.eval(scope, expr),
+                            None => rhai::Dynamic::from(0_i64),
+                        };
+                        scope.push_dynamic(declaration.name.clone(), value);
+                    }
+                    ScriptNode::Call {
+                        target_script: ScriptTarget::Literal { script_name },
+                        args,
+                        ..
+                    } => {
+                        let params = self.programs[script_name].params.clone();
+                        let mut callee = rhai::Scope::new();
+                        for (param, arg) in params.iter().zip(args) {
+                            let value = self.eval(scope, &arg.value_expr);
+                            callee.push_dynamic(param.name.clone(), value);
+                        }
+                        match self.run(script_name, &mut callee) {
+                            Flow::Returned => {}
+                            other => return other,
+                        }
+                        for (param, arg) in params.iter().zip(args) {
+                            if arg.is_ref {
+                                let value = callee.get_value::<rhai::Dynamic>(&param.name);
+                                scope.set_value(arg.value_expr.clone(), value.expect("ref"));
+                            }
+                        }
+                    }
+                    other => {
+                        assert!(
+                            matches!(other, ScriptNode::End { .. }),
+                            "reference VM does not run {:?}",
+                            other
+                        );
+                        return Flow::Ended;
+                    }
+                },
+                LinearInstruction::EnterScope => marks.push(scope.len()),
+                LinearInstruction::ExitScope => {
+                    scope.rewind(marks.pop().expect("open scope"));
+                }
+                LinearInstruction::Jump { target } => {
+                    pc = *target;
+                    continue;
+                }
+                LinearInstruction::BranchUnless {
+                    when_expr, target, ..
+                } => {
+                    if !self.eval(scope, when_expr).as_bool().expect("bool") {
+                        pc = *target;
+                        continue;
+                    }
+                }
+                LinearInstruction::WhileHead {
+                    when_expr,
+                    max_iterations,
+                    exit,
+                    ..
+                } => {
+                    let condition = self.eval(scope, when_expr).as_bool().expect("bool");
+                    if !condition {
+                        pc = *exit;
+                        continue;
+                    }
+                    let count = loop_counts.entry(pc).or_default();
+                    assert!(max_iterations.is_none_or(|max| *count < max));
+                    *count += 1;
+                }
+                LinearInstruction::LoopEnd { head } => {
+                    loop_counts.remove(head);
+                }
+                LinearInstruction::Choice {
+                    prompt_text,
+                    entries,
+                    ..
+                } => {
+                    let mut regular = Vec::new();
+                    let mut fall_over = None;
+                    let mut pinned = Vec::new();
+                    assert!(
+                        entries
+                            .iter()
+                            .all(|entry| matches!(entry.entry, ChoiceEntry::Static { .. })),
+                        "reference VM does not run dynamic options"
+                    );
+                    for entry in entries {
+                        let ChoiceEntry::Static { option } = &entry.entry else {
+                            continue;
+                        };
+                        let visible = option
+                            .when_expr
+                            .as_ref()
+                            .is_none_or(|expr| self.eval(scope, expr).as_bool().expect("bool"))
+                            && !(option.once
+                                && self.once_seen.contains(&format!("option:{}", option.id)))
+                            && option.max_select.is_none_or(|limit| {
+                                self.select_counts.get(&option.id).copied().unwrap_or(0) < limit
+                            });
+                        match (visible, option.fall_over, option.pinned) {
+                            (false, _, _) => {}
+                            (true, true, _) => fall_over = Some((option, entry.target)),
+                            (true, _, true) => pinned.push((option, entry.target)),
+                            (true, false, false) => regular.push((option, entry.target)),
+                        }
+                    }
+                    if regular.is_empty() {
+                        regular.extend(fall_over);
+                    }
+                    regular.extend(pinned);
+                    if !regular.is_empty() {
+                        let texts = regular
+                            .iter()
+                            .map(|(option, _)| self.render(scope, &option.text))
+                            .collect::<Vec<_>>();
+                        let prompt = self.render(scope, prompt_text);
+                        self.outputs
+                            .push(format!("choices[{}]: {}", prompt, texts.join("|")));
+                        if self.picks.is_empty() {
+                            return Flow::Stuck;
+                        }
+                        let (option, target) = regular[self.picks.remove(0)];
+                        if option.once {
+                            self.once_seen.insert(format!("option:{}", option.id));
+                        }
+                        if option.max_select.is_some() {
+                            *self.select_counts.entry(option.id.clone()).or_default() += 1;
+                        }
+                        marks.push(scope.len());
+                        pc = target;
+                        continue;
+                    }
+                }
+                LinearInstruction::Return => return Flow::Returned,
+            }
+            pc += 1;
+        }
+    }
+}
+
+fn run_linear(bundle: &CompileProjectBundleResult, picks: &[usize]) -> Vec<String> {
+    let mut vm = LinearVm {
+        programs: bundle
+            .scripts
+            .iter()
+            .map(|(name, script)| (name.clone(), lower_to_linear(script).expect("lower")))
+            .collect(),
+        rhai: rhai::Engine::new(),
+        picks: picks.to_vec(),
+        once_seen: BTreeSet::new(),
+        select_counts: BTreeMap::new(),
+        outputs: Vec::new(),
+    };
+    match vm.run("main.main", &mut rhai::Scope::new()) {
+        Flow::Returned | Flow::Ended => vm.outputs.push("end".to_string()),
+        Flow::Stuck => {}
+    }
+    vm.outputs
+}
+
+fn assert_same_outputs(source: &str, picks: &[usize]) -> Vec<String> {
+    let bundle =
+        compile_project_bundle_from_xml_map(&map(&[("main.xml", source)])).expect("compile");
+    let tree = run_tree(&bundle, "main.main", picks);
+    assert_eq!(run_linear(&bundle, picks), tree);
+    tree
+}
+
+#[test]
+fn lowered_loops_branches_and_calls_match_tree_engine() {
+    let outputs = assert_same_outputs(
+        r#"<module name="main" export="script:main">
+<script name="main">
+  <temp name="i" type="int">0</temp>
+  <temp name="total" type="int">0</temp>
+  <while when="i LT 10">
+<code>i = i + 1;</code>
+<if when="i == 2"><continue/></if>
+<if when="i > 4"><break/></if>
+<temp name="sq" type="int">i * i</temp>
+<if when="sq % 2 == 0"><text>even ${sq}</text><else><text>odd ${sq}</text></else></if>
+<code>total = total + sq;</code>
+  </while>
+  <text>total=${total}</text>
+  <call script="@main.award" args="total, ref:i"/>
+  <call script="@main.award" args="500, ref:i"/>
+  <text>i=${i}</text>
+  <end/>
+</script>
+<script name="award" kind="call" args="int:points,ref:int:out">
+  <if when="points > 100"><return/></if>
+  <code>out = points;</code>
+  <text>awarded ${points}</text>
+</script>
+</module>"#,
+        &[],
+    );
+    assert_eq!(
+        outputs,
+        vec![
+            "odd 1",
+            "odd 9",
+            "even 16",
+            "total=26",
+            "awarded 26",
+            "i=26",
+            "end"
+        ]
+    );
+}
+
+#[test]
+fn lowered_choices_match_tree_engine_with_once_max_select_continue_and_break() {
+    let outputs = assert_same_outputs(
+        r#"<module name="main" export="script:main">
+<script name="main">
+  <temp name="n" type="int">0</temp>
+  <while when="true">
+<code>n = n + 1;</code>
+<choice text="Round ${n}">
+  <option text="More" when="n LT 3"><temp name="m" type="int">n * 10</temp><text>more ${m}</text></option>
+  <option text="Peek" once="true"><text>peeked</text><continue/></option>
+  <option text="Quit"><break/></option>
+  <option text="Fallback" fall_over="true"><text>fallback</text></option>
+</choice>
+  </while>
+  <text>n=${n}</text>
+  <choice text="Extra">
+<option text="Again" max_select="2"><text>again</text><continue/></option>
+<option text="Done"><text>done</text></option>
+  </choice>
+  <end/>
+</script>
+</module>"#,
+        &[1, 0, 0, 0, 0, 0],
+    );
+    assert_eq!(
+        outputs,
+        vec![
+            "choices[Round 1]: More|Peek|Quit",
+            "peeked",
+            "choices[Round 2]: More|Quit",
+            "more 20",
+            "choices[Round 3]: Quit",
+            "n=3",
+            "choices[Extra]: Again|Done",
+            "again",
+            "choices[Extra]: Again|Done",
+            "again",
+            "choices[Extra]: Done",
+            "done",
+            "end",
+        ]
+    );
+}
+
+#[test]
+fn folded_expressions_match_tree_engine() {
+    let files = map(&[(
+        "main.xml",
+        r#"<module name="main" export="script:main;var:base">
+  <var name="base" type="int">1 + 2 * 3</var>
+  <const name="ratio" type="float">7 / 2.0</const>
+  <script name="main">
+    <temp name="sum" type="int">(1 + 2) * 4 - 10 % 3</temp>
+    <temp name="flag" type="boolean">3 >= 2 AND !(1 == 2)</temp>
+    <if when="2 > 1"><text>then</text><else><text>else</text></else></if>
+    <if when="sum > 1"><text>${sum}</text></if>
+    <while when="1 > 2"><text>never</text></while>
+    <choice text="Pick">
+      <option text="hidden" when="1 == 2"><text>x</text></option>
+      <option text="shown" when="2 LTE 2"><text>${flag} ${base} ${ratio}</text></option>
+    </choice>
+    <end/>
+  </script>
+</module>"#,
+    )]);
+    let compile = |fold_constants| {
+        compile_project_bundle_from_xml_map_with_options(
+            &files,
+            CompileOptions {
+                fold_constants,
+                ..CompileOptions::default()
+            },
+        )
+        .expect("compile")
+    };
+
+    let folded = run_tree(&compile(true), "main.main", &[0]);
+    assert_eq!(folded, run_tree(&compile(false), "main.main", &[0]));
+    assert_eq!(
+        folded,
+        vec!["then", "11", "choices[Pick]: shown", "true 7 3.5", "end"]
+    );
+}
+
+fn merge_input(label: &str) -> CompileProjectBundleResult {
+    let main = format!(
+        r#"
+<!-- import shared from shared.xml -->
+<module name="main" export="script:main;var:count;function:bump">
+  <var name="count" type="int">0</var>
+  <function name="bump" args="int:n" return_type="int">
+    count = count + n;
+    return count;
+  </function>
+  <script name="main">
+    <temp name="next" type="script">@shared.hello</temp>
+    <code>bump(2);</code>
+    <text>{label} ${{count}} ${{__script__}}</text>
+    <call script="@shared.hello"/>
+    <call script="next"/>
+    <end/>
+  </script>
+</module>"#
+    );
+    let shared = format!(
+        r#"<module name="shared" export="script:hello"><script name="hello" kind="call"><text>{label} hello</text></script></module>"#
+    );
+    compile_project_bundle_from_xml_map(&map(&[("main.xml", &main), ("shared.xml", &shared)]))
+        .expect("compile")
+}
+
+#[test]
+fn merged_bundles_run_like_the_bundles_they_came_from() {
+    let first = merge_input("first");
+    let merged = merge_bundles(vec![
+        ("a".to_string(), first.clone()),
+        ("b".to_string(), merge_input("second")),
+    ])
+    .expect("merge");
+
+    assert_eq!(
+        run_tree(&merged, "a.main.main", &[]),
+        vec!["first 2 a.main.main", "first hello", "first hello", "end"]
+    );
+    assert_eq!(
+        run_tree(&merged, "b.main.main", &[]),
+        vec![
+            "second 2 b.main.main",
+            "second hello",
+            "second hello",
+            "end"
+        ]
+    );
+    assert_eq!(
+        run_tree(&first, "main.main", &[]),
+        vec!["first 2 main.main", "first hello", "first hello", "end"]
+    );
+}
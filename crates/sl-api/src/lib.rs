@@ -3,20 +3,44 @@ use std::sync::Arc;
 
 use sl_compiler::{
     compile_artifact_from_xml_map as compile_compiled_artifact_from_xml_map,
-    compile_project_bundle_from_xml_map, compile_project_scripts_from_xml_map,
+    compile_artifact_from_xml_map_with_options, compile_project_bundle_from_xml_map,
+    compile_project_bundle_from_xml_map_with_options, compile_project_scripts_from_xml_map,
     validate_terminal_structure_from_xml_map, CompileProjectBundleResult,
 };
 use sl_core::{CompileProjectResult, CompiledProjectArtifact};
 use sl_runtime::{HostFunctionRegistry, ScriptLangEngineOptions};
 
+#[cfg(test)]
+mod compiler_pass_tests;
+
 pub use sl_compiler::write_artifact_json;
-pub use sl_compiler::DEFAULT_COMPILER_VERSION;
-pub use sl_core::{ChoiceItem, EngineOutput, PendingBoundary, ScriptLangError, SlValue, Snapshot};
-pub use sl_runtime::{RandomStateView, ScriptLangEngine};
+pub use sl_compiler::{CompileOptions, DEFAULT_COMPILER_VERSION};
+pub use sl_core::{
+    slvalue_from_json, slvalue_to_json, ChoiceItem, DebugState, EngineOutput, ErrorCode,
+    PendingBoundary, ScriptIr, ScriptLangError, SlValue, Snapshot,
+};
+pub use sl_runtime::{
+    inspect_snapshot, ChoiceIndexPolicy, EngineBehavior, EngineLimits, IntPrecisionPolicy,
+    RandomStateView, RhaiResourceLimits, ScriptLangEngine, SnapshotReport, TextFilter,
+};
+
+/// Engine tunables passed through to `ScriptLangEngineOptions` unchanged.
+///
+/// Resuming applies them like creating does, so a session should be resumed with the tuning it
+/// was created with (limits, policies, locale, `random_override`).
+#[derive(Clone, Default)]
+pub struct EngineTuning {
+    pub limits: EngineLimits,
+    pub behavior: EngineBehavior,
+    pub host_constants: BTreeMap<String, SlValue>,
+    pub random_override: Option<Vec<u32>>,
+    pub clock: Option<i64>,
+}
 
 #[derive(Clone)]
 pub struct CreateEngineFromXmlOptions {
     pub scripts_xml: BTreeMap<String, String>,
+    pub compile_options: CompileOptions,
     pub entry_script: Option<String>,
     pub entry_args: Option<BTreeMap<String, SlValue>>,
     pub host_functions: Option<Arc<dyn HostFunctionRegistry>>,
@@ -24,6 +48,7 @@ pub struct CreateEngineFromXmlOptions {
     pub random_sequence: Option<Vec<u32>>,
     pub random_sequence_index: Option<usize>,
     pub compiler_version: Option<String>,
+    pub tuning: EngineTuning,
 }
 
 #[derive(Clone)]
@@ -35,16 +60,19 @@ pub struct CreateEngineFromArtifactOptions {
     pub random_sequence: Option<Vec<u32>>,
     pub random_sequence_index: Option<usize>,
     pub compiler_version: Option<String>,
+    pub tuning: EngineTuning,
 }
 
 #[derive(Clone)]
 pub struct ResumeEngineFromXmlOptions {
     pub scripts_xml: BTreeMap<String, String>,
+    pub compile_options: CompileOptions,
     pub snapshot: Snapshot,
     pub host_functions: Option<Arc<dyn HostFunctionRegistry>>,
     pub random_sequence: Option<Vec<u32>>,
     pub random_sequence_index: Option<usize>,
     pub compiler_version: Option<String>,
+    pub tuning: EngineTuning,
 }
 
 #[derive(Clone)]
@@ -55,6 +83,7 @@ pub struct ResumeEngineFromArtifactOptions {
     pub random_sequence: Option<Vec<u32>>,
     pub random_sequence_index: Option<usize>,
     pub compiler_version: Option<String>,
+    pub tuning: EngineTuning,
 }
 
 pub fn compile_scripts_from_xml_map(
@@ -76,6 +105,7 @@ pub fn compile_project_from_xml_map(
         module_var_init_order,
         module_const_declarations,
         module_const_init_order,
+        ..
     } = compile_project_bundle_from_xml_map(xml_by_path)?;
     validate_terminal_structure_from_xml_map(xml_by_path)?;
 
@@ -106,7 +136,7 @@ pub fn create_engine_from_artifact(
 ) -> Result<ScriptLangEngine, ScriptLangError> {
     let Some(entry_script) = options.artifact.scripts.get(&options.artifact.entry_script) else {
         return Err(ScriptLangError::new(
-            "API_ARTIFACT_ENTRY_NOT_FOUND",
+            ErrorCode::ApiArtifactEntryNotFound,
             format!(
                 "Artifact entry script \"{}\" is not registered.",
                 options.artifact.entry_script
@@ -116,12 +146,12 @@ pub fn create_engine_from_artifact(
     validate_entry_script_access(
         entry_script,
         &options.artifact.entry_script,
-        "API_ARTIFACT_ENTRY_PRIVATE",
+        ErrorCode::ApiArtifactEntryPrivate,
     )?;
     validate_entry_script_kind(
         entry_script,
         &options.artifact.entry_script,
-        "API_ARTIFACT_ENTRY_KIND",
+        ErrorCode::ApiArtifactEntryKind,
     )?;
 
     let compiler_version = options
@@ -140,6 +170,12 @@ pub fn create_engine_from_artifact(
         random_sequence: options.random_sequence,
         random_sequence_index: options.random_sequence_index,
         compiler_version,
+        clock: options.tuning.clock,
+        host_constants: options.tuning.host_constants,
+        random_override: options.tuning.random_override,
+        limits: options.tuning.limits,
+        behavior: options.tuning.behavior,
+        ..Default::default()
     })?;
 
     engine.start(&options.artifact.entry_script, options.entry_args)?;
@@ -161,10 +197,15 @@ pub fn resume_engine_from_artifact(
         module_const_declarations: options.artifact.module_const_declarations,
         module_const_init_order: options.artifact.module_const_init_order,
         host_functions: options.host_functions,
-        random_seed: None,
         random_sequence: options.random_sequence,
         random_sequence_index: options.random_sequence_index,
         compiler_version,
+        clock: options.tuning.clock,
+        host_constants: options.tuning.host_constants,
+        random_override: options.tuning.random_override,
+        limits: options.tuning.limits,
+        behavior: options.tuning.behavior,
+        ..Default::default()
     })?;
 
     engine.resume(options.snapshot)?;
@@ -174,7 +215,12 @@ pub fn resume_engine_from_artifact(
 pub fn create_engine_from_xml(
     options: CreateEngineFromXmlOptions,
 ) -> Result<ScriptLangEngine, ScriptLangError> {
-    let artifact = compile_artifact_from_xml_map(&options.scripts_xml, options.entry_script)?;
+    let artifact = compile_artifact_from_xml_map_with_options(
+        &options.scripts_xml,
+        options.entry_script,
+        options.compile_options,
+    )?;
+    validate_terminal_structure_from_xml_map(&options.scripts_xml)?;
     create_engine_from_artifact(CreateEngineFromArtifactOptions {
         artifact,
         entry_args: options.entry_args,
@@ -183,18 +229,27 @@ pub fn create_engine_from_xml(
         random_sequence: options.random_sequence,
         random_sequence_index: options.random_sequence_index,
         compiler_version: options.compiler_version,
+        tuning: options.tuning,
     })
 }
 
 pub fn resume_engine_from_xml(
     options: ResumeEngineFromXmlOptions,
 ) -> Result<ScriptLangEngine, ScriptLangError> {
-    let compiled = compile_project_bundle_from_xml_map(&options.scripts_xml)?;
+    let compiled = compile_project_bundle_from_xml_map_with_options(
+        &options.scripts_xml,
+        options.compile_options,
+    )?;
     validate_terminal_structure_from_xml_map(&options.scripts_xml)?;
+    // Mirror the artifact compiler: an optimized layout gets its own compiler version.
+    let compiler_version = match &compiled.bundle_fingerprint {
+        Some(fingerprint) => format!("{}+{}", DEFAULT_COMPILER_VERSION, fingerprint),
+        None => DEFAULT_COMPILER_VERSION.to_string(),
+    };
     resume_engine_from_artifact(ResumeEngineFromArtifactOptions {
         artifact: CompiledProjectArtifact {
             schema_version: sl_core::COMPILED_PROJECT_SCHEMA.to_string(),
-            compiler_version: sl_compiler::DEFAULT_COMPILER_VERSION.to_string(),
+            compiler_version,
             entry_script: "main.main".to_string(),
             scripts: compiled.scripts,
             global_data: compiled.global_data,
@@ -208,6 +263,7 @@ pub fn resume_engine_from_xml(
         random_sequence: options.random_sequence,
         random_sequence_index: options.random_sequence_index,
         compiler_version: options.compiler_version,
+        tuning: options.tuning,
     })
 }
 
@@ -218,12 +274,12 @@ fn resolve_entry_script(
     if let Some(entry) = explicit {
         let Some(script) = scripts.get(&entry) else {
             return Err(ScriptLangError::new(
-                "API_ENTRY_SCRIPT_NOT_FOUND",
+                ErrorCode::ApiEntryScriptNotFound,
                 format!("Entry script \"{}\" is not registered.", entry),
             ));
         };
-        validate_entry_script_access(script, &entry, "API_ENTRY_SCRIPT_PRIVATE")?;
-        validate_entry_script_kind(script, &entry, "API_ENTRY_SCRIPT_KIND")?;
+        validate_entry_script_access(script, &entry, ErrorCode::ApiEntryScriptPrivate)?;
+        validate_entry_script_kind(script, &entry, ErrorCode::ApiEntryScriptKind)?;
         return Ok(entry);
     }
 
@@ -232,13 +288,13 @@ fn resolve_entry_script(
         let script = scripts
             .get(&entry)
             .expect("main.main existence should be checked before retrieval");
-        validate_entry_script_access(script, &entry, "API_ENTRY_SCRIPT_PRIVATE")?;
-        validate_entry_script_kind(script, &entry, "API_ENTRY_SCRIPT_KIND")?;
+        validate_entry_script_access(script, &entry, ErrorCode::ApiEntryScriptPrivate)?;
+        validate_entry_script_kind(script, &entry, ErrorCode::ApiEntryScriptKind)?;
         return Ok(entry);
     }
 
     Err(ScriptLangError::new(
-        "API_ENTRY_MAIN_NOT_FOUND",
+        ErrorCode::ApiEntryMainNotFound,
         "Expected script with name=\"main.main\" as default entry.",
     ))
 }
@@ -246,7 +302,7 @@ fn resolve_entry_script(
 fn validate_entry_script_access(
     script: &sl_core::ScriptIr,
     entry: &str,
-    code: &'static str,
+    code: ErrorCode,
 ) -> Result<(), ScriptLangError> {
     if script.access != sl_core::AccessLevel::Private {
         return Ok(());
@@ -263,7 +319,7 @@ fn validate_entry_script_access(
 fn validate_entry_script_kind(
     script: &sl_core::ScriptIr,
     entry: &str,
-    code: &'static str,
+    code: ErrorCode,
 ) -> Result<(), ScriptLangError> {
     if script.kind == sl_core::ScriptKind::Goto {
         return Ok(());
@@ -284,6 +340,7 @@ mod tests {
             EngineOutput::Debug { .. } => "debug",
             EngineOutput::Choices { .. } => "choices",
             EngineOutput::Input { .. } => "input",
+            EngineOutput::Checkpoint { .. } => "checkpoint",
             EngineOutput::End => "end",
         }
     }
@@ -797,6 +854,7 @@ mod tests {
             random_sequence: None,
             random_sequence_index: None,
             compiler_version: None,
+            tuning: EngineTuning::default(),
         })
         .expect("engine should build");
         let output = engine.next_output().expect("next output");
@@ -821,6 +879,7 @@ mod tests {
             random_sequence: None,
             random_sequence_index: None,
             compiler_version: None,
+            tuning: EngineTuning::default(),
         })
         .err()
         .expect("missing artifact entry should fail");
@@ -852,6 +911,7 @@ mod tests {
             random_sequence: None,
             random_sequence_index: None,
             compiler_version: None,
+            tuning: EngineTuning::default(),
         })
         .err()
         .expect("private artifact entry should fail");
@@ -881,6 +941,7 @@ mod tests {
             random_sequence: None,
             random_sequence_index: None,
             compiler_version: None,
+            tuning: EngineTuning::default(),
         })
         .expect("engine should build");
         let output = engine.next_output().expect("next output");
@@ -894,6 +955,7 @@ mod tests {
             random_sequence: None,
             random_sequence_index: None,
             compiler_version: None,
+            tuning: EngineTuning::default(),
         })
         .expect("resume from artifact");
         resumed.choose(0).expect("choose should succeed");
@@ -926,6 +988,8 @@ mod tests {
             random_sequence: None,
             random_sequence_index: None,
             compiler_version: Some("player".to_string()),
+            compile_options: CompileOptions::default(),
+            tuning: EngineTuning::default(),
         })
         .expect("engine should build");
 
@@ -958,6 +1022,8 @@ mod tests {
             random_sequence: Some(vec![12]),
             random_sequence_index: Some(0),
             compiler_version: Some("player".to_string()),
+            compile_options: CompileOptions::default(),
+            tuning: EngineTuning::default(),
         })
         .expect("engine should build");
 
@@ -965,14 +1031,16 @@ mod tests {
             engine.next_output().expect("first output"),
             EngineOutput::Text {
                 text: "2".to_string(),
-                tag: None
+                tag: None,
+                speaker: None,
             }
         );
         assert_eq!(
             engine.next_output().expect("second output"),
             EngineOutput::Text {
                 text: "0".to_string(),
-                tag: None
+                tag: None,
+                speaker: None,
             }
         );
     }
@@ -1002,6 +1070,8 @@ mod tests {
             random_sequence: None,
             random_sequence_index: None,
             compiler_version: Some("player".to_string()),
+            compile_options: CompileOptions::default(),
+            tuning: EngineTuning::default(),
         })
         .expect("engine should build");
         let first = engine.next_output().expect("next should succeed");
@@ -1015,6 +1085,8 @@ mod tests {
             random_sequence: None,
             random_sequence_index: None,
             compiler_version: Some("player".to_string()),
+            compile_options: CompileOptions::default(),
+            tuning: EngineTuning::default(),
         })
         .expect("resume should succeed");
         resumed.choose(0).expect("choose should succeed");
@@ -1022,6 +1094,93 @@ mod tests {
         assert_eq!(output_kind(&next), "text");
     }
 
+    #[test]
+    fn create_and_resume_engine_from_xml_apply_compile_options_and_tuning() {
+        let strict = CompileOptions {
+            strict_attributes: true,
+            ..CompileOptions::default()
+        };
+        let unknown_attr = map(&[(
+            "main.xml",
+            r#"<module name="main" export="script:main"><script name="main"><text colour="red">x</text></script></module>"#,
+        )]);
+        let error = create_engine_from_xml(CreateEngineFromXmlOptions {
+            scripts_xml: unknown_attr,
+            entry_script: None,
+            entry_args: None,
+            host_functions: None,
+            random_seed: None,
+            random_sequence: None,
+            random_sequence_index: None,
+            compiler_version: None,
+            compile_options: strict,
+            tuning: EngineTuning::default(),
+        })
+        .err()
+        .expect("strict attributes should reject unknown attributes");
+        assert_eq!(error.code, "XML_UNKNOWN_ATTR");
+
+        let scripts = map(&[(
+            "main.xml",
+            r#"
+<module name="main" export="script:main">
+<script name="main">
+  <choice text="Pick">
+    <option text="A"><text>A</text></option>
+    <option text="B"><text>B</text></option>
+  </choice>
+  <end/>
+</script>
+</module>
+"#,
+        )]);
+        let tuning = EngineTuning {
+            behavior: EngineBehavior {
+                choice_index_policy: ChoiceIndexPolicy::Clamp,
+                ..EngineBehavior::default()
+            },
+            ..EngineTuning::default()
+        };
+        let mut engine = create_engine_from_xml(CreateEngineFromXmlOptions {
+            scripts_xml: scripts.clone(),
+            entry_script: None,
+            entry_args: None,
+            host_functions: None,
+            random_seed: Some(1),
+            random_sequence: None,
+            random_sequence_index: None,
+            compiler_version: None,
+            compile_options: strict,
+            tuning: tuning.clone(),
+        })
+        .expect("engine should build");
+        engine.next_output().expect("next should succeed");
+        let snapshot = engine.snapshot().expect("snapshot should succeed");
+
+        let mut resumed = resume_engine_from_xml(ResumeEngineFromXmlOptions {
+            scripts_xml: scripts,
+            snapshot,
+            host_functions: None,
+            random_sequence: None,
+            random_sequence_index: None,
+            compiler_version: None,
+            compile_options: strict,
+            tuning,
+        })
+        .expect("resume should succeed");
+        resumed
+            .choose(9)
+            .expect("clamp policy should survive resume");
+        assert_eq!(
+            resumed.next_output().expect("next should succeed"),
+            EngineOutput::Text {
+                text: "B".to_string(),
+                tag: None,
+                speaker: None,
+            }
+        );
+    }
+
     #[test]
     fn resume_engine_from_xml_fails_on_compile_error() {
         // Line 192: compile fails in resume_engine_from_xml
@@ -1042,11 +1201,22 @@ mod tests {
                 },
                 module_vars: BTreeMap::new(),
                 once_state_by_script: BTreeMap::new(),
+                global_once_state: Vec::new(),
+                select_count_by_script: BTreeMap::new(),
+                visit_counts: BTreeMap::new(),
+                coverage: None,
+                clock: None,
+                entry_script: String::new(),
+                entry_args: BTreeMap::new(),
+                node_hash: None,
+                random_override_index: None,
             },
             host_functions: None,
             random_sequence: None,
             random_sequence_index: None,
             compiler_version: None,
+            compile_options: CompileOptions::default(),
+            tuning: EngineTuning::default(),
         });
         // Must fail due to compile error
         assert!(
@@ -1078,11 +1248,22 @@ mod tests {
                 },
                 module_vars: BTreeMap::new(),
                 once_state_by_script: BTreeMap::new(),
+                global_once_state: Vec::new(),
+                select_count_by_script: BTreeMap::new(),
+                visit_counts: BTreeMap::new(),
+                coverage: None,
+                clock: None,
+                entry_script: String::new(),
+                entry_args: BTreeMap::new(),
+                node_hash: None,
+                random_override_index: None,
             },
             host_functions: None,
             random_sequence: None,
             random_sequence_index: None,
             compiler_version: None,
+            compile_options: CompileOptions::default(),
+            tuning: EngineTuning::default(),
         });
         // Must fail due to terminal validation error
         assert!(
@@ -1123,6 +1304,8 @@ mod tests {
             random_sequence: None,
             random_sequence_index: None,
             compiler_version: Some("player".to_string()),
+            compile_options: CompileOptions::default(),
+            tuning: EngineTuning::default(),
         })
         .expect("module engine should build");
         let first = engine.next_output().expect("input output");
@@ -1136,6 +1319,8 @@ mod tests {
             random_sequence: None,
             random_sequence_index: None,
             compiler_version: Some("player".to_string()),
+            compile_options: CompileOptions::default(),
+            tuning: EngineTuning::default(),
         })
         .expect("resume should succeed");
         resumed.submit_input("go").expect("input should succeed");
@@ -1144,6 +1329,7 @@ mod tests {
             EngineOutput::Text {
                 text: "2".to_string(),
                 tag: None,
+                speaker: None,
             }
         );
     }
@@ -1173,6 +1359,8 @@ mod tests {
             random_sequence: None,
             random_sequence_index: None,
             compiler_version: Some("player".to_string()),
+            compile_options: CompileOptions::default(),
+            tuning: EngineTuning::default(),
         })
         .err()
         .expect("reserved host function should fail create");
@@ -1187,6 +1375,8 @@ mod tests {
             random_sequence: None,
             random_sequence_index: None,
             compiler_version: Some("player".to_string()),
+            compile_options: CompileOptions::default(),
+            tuning: EngineTuning::default(),
         })
         .expect("engine should build");
         let output = ok_engine.next_output().expect("choice output");
@@ -1204,6 +1394,8 @@ mod tests {
             random_sequence: None,
             random_sequence_index: None,
             compiler_version: Some("player".to_string()),
+            compile_options: CompileOptions::default(),
+            tuning: EngineTuning::default(),
         })
         .err()
         .expect("reserved host function should fail resume");
@@ -1240,6 +1432,7 @@ mod tests {
             random_sequence: None,
             random_sequence_index: None,
             compiler_version: None,
+            tuning: EngineTuning::default(),
         })
         .err()
         .expect("start arg type mismatch should fail");
@@ -1253,6 +1446,7 @@ mod tests {
             random_sequence: None,
             random_sequence_index: None,
             compiler_version: None,
+            tuning: EngineTuning::default(),
         })
         .expect("engine");
         let out = ok_engine.next_output().expect("next");
@@ -1273,6 +1467,15 @@ mod tests {
             },
             module_vars: BTreeMap::new(),
             once_state_by_script: BTreeMap::new(),
+            global_once_state: Vec::new(),
+            select_count_by_script: BTreeMap::new(),
+            visit_counts: BTreeMap::new(),
+            coverage: None,
+            clock: None,
+            entry_script: String::new(),
+            entry_args: BTreeMap::new(),
+            node_hash: None,
+            random_override_index: None,
         };
         let resume_error = resume_engine_from_artifact(ResumeEngineFromArtifactOptions {
             artifact: artifact.clone(),
@@ -1281,6 +1484,7 @@ mod tests {
             random_sequence: None,
             random_sequence_index: None,
             compiler_version: None,
+            tuning: EngineTuning::default(),
         })
         .err()
         .expect("resume should fail");
@@ -1295,6 +1499,8 @@ mod tests {
             random_sequence: None,
             random_sequence_index: None,
             compiler_version: None,
+            compile_options: CompileOptions::default(),
+            tuning: EngineTuning::default(),
         })
         .err()
         .expect("create from xml should fail");
@@ -1316,11 +1522,22 @@ mod tests {
                 },
                 module_vars: BTreeMap::new(),
                 once_state_by_script: BTreeMap::new(),
+                global_once_state: Vec::new(),
+                select_count_by_script: BTreeMap::new(),
+                visit_counts: BTreeMap::new(),
+                coverage: None,
+                clock: None,
+                entry_script: String::new(),
+                entry_args: BTreeMap::new(),
+                node_hash: None,
+                random_override_index: None,
             },
             host_functions: None,
             random_sequence: None,
             random_sequence_index: None,
             compiler_version: None,
+            compile_options: CompileOptions::default(),
+            tuning: EngineTuning::default(),
         })
         .err()
         .expect("resume from xml should fail");
@@ -1350,6 +1567,8 @@ mod tests {
             random_sequence: None,
             random_sequence_index: None,
             compiler_version: None,
+            compile_options: CompileOptions::default(),
+            tuning: EngineTuning::default(),
         })
         .expect("engine should build");
         let out = engine.next_output().expect("input output");
@@ -1373,6 +1592,7 @@ mod tests {
             random_sequence: None,
             random_sequence_index: None,
             compiler_version: None,
+            tuning: EngineTuning::default(),
         })
         .err()
         .expect("call kind entry should fail");
@@ -1,15 +1,21 @@
+use std::collections::BTreeMap;
+use std::fs;
 use std::path::Path;
+use std::time::Instant;
 
 use sl_api::EngineOutput;
+use sl_api::ErrorCode;
 use sl_api::ScriptLangEngine;
 use sl_api::ScriptLangError;
 use sl_api::DEFAULT_COMPILER_VERSION;
 
 use crate::{
     create_engine_for_scenario, emit_boundary_with_saved_state, load_player_state,
-    load_source_by_ref, load_source_by_scripts_dir, parse_rand_sequence, resume_engine_for_state,
-    run_to_boundary, AgentArgs, AgentCommand, ChooseArgs, CompileArgs, InputArgs, RandConfig,
-    ReplayArgs, StartArgs,
+    load_replay_trace, load_source_by_ref, load_source_by_scripts_dir, map_cli_init_write,
+    map_cli_inputs_read, parse_rand_sequence, resume_engine_for_state, run_to_boundary,
+    save_replay_trace, AgentArgs, AgentCommand, BenchArgs, BenchFormat, ChooseArgs, CompileArgs,
+    InitArgs, InputArgs, ListArgs, RandConfig, ReplayArgs, ReplayTrace, StartArgs,
+    REPLAY_TRACE_SCHEMA,
 };
 
 pub(super) fn run_agent(args: AgentArgs) -> Result<i32, ScriptLangError> {
@@ -18,14 +24,13 @@ pub(super) fn run_agent(args: AgentArgs) -> Result<i32, ScriptLangError> {
         AgentCommand::Choose(args) => run_choose(args),
         AgentCommand::Input(args) => run_input(args),
         AgentCommand::Replay(args) => run_replay(args),
+        AgentCommand::Bench(args) => run_bench(args),
+        AgentCommand::Init(args) => run_init(args),
     }
 }
 
 pub(super) fn run_start(args: StartArgs) -> Result<i32, ScriptLangError> {
-    let scenario = load_source_by_scripts_dir(
-        &args.scripts_dir,
-        args.entry_script.as_deref().unwrap_or("main.main"),
-    )?;
+    let scenario = load_source_by_scripts_dir(&args.scripts_dir, args.entry_script.as_deref())?;
     let random_sequence = parse_rand_sequence(args.rand.as_deref())?;
     let mut engine = create_engine_for_scenario(
         &scenario,
@@ -44,6 +49,7 @@ pub(super) fn run_start(args: StartArgs) -> Result<i32, ScriptLangError> {
         &args.state_out,
         &scenario.id,
         DEFAULT_COMPILER_VERSION,
+        None,
     )
 }
 
@@ -54,7 +60,8 @@ pub(super) fn run_choose(args: ChooseArgs) -> Result<i32, ScriptLangError> {
         &args.state_out,
         random_sequence,
         args.show_debug,
-        |engine| engine.choose(args.choice),
+        args.action_id.as_deref(),
+        |engine| engine.choose(args.choice).map(drop),
     )
 }
 
@@ -65,6 +72,7 @@ pub(super) fn run_input(args: InputArgs) -> Result<i32, ScriptLangError> {
         &args.state_out,
         random_sequence,
         args.show_debug,
+        args.action_id.as_deref(),
         |engine| engine.submit_input(&args.text),
     )
 }
@@ -74,10 +82,7 @@ pub(super) fn run_compile(args: CompileArgs) -> Result<i32, ScriptLangError> {
     use sl_api::write_artifact_json;
 
     // 1. 加载源文件
-    let scenario = load_source_by_scripts_dir(
-        &args.scripts_dir,
-        args.entry_script.as_deref().unwrap_or("main.main"),
-    )?;
+    let scenario = load_source_by_scripts_dir(&args.scripts_dir, args.entry_script.as_deref())?;
 
     // 2. 编译（在内存中进行）
     let artifact =
@@ -97,7 +102,7 @@ pub(super) fn run_compile(args: CompileArgs) -> Result<i32, ScriptLangError> {
         // 正常模式：写入文件
         let output_path = args.output.ok_or_else(|| {
             ScriptLangError::new(
-                "CLI_OUTPUT_REQUIRED",
+                ErrorCode::CliOutputRequired,
                 "--output is required when not using --dry-run".to_string(),
             )
         })?;
@@ -108,6 +113,89 @@ pub(super) fn run_compile(args: CompileArgs) -> Result<i32, ScriptLangError> {
     Ok(0)
 }
 
+pub(super) fn run_list(args: ListArgs) -> Result<i32, ScriptLangError> {
+    let scenario = load_source_by_scripts_dir(&args.scripts_dir, None)?;
+    let scripts = sl_api::compile_scripts_from_xml_map(&scenario.scripts_xml)?;
+    println!("RESULT:OK");
+    for line in list_lines(&scripts, args.tag.as_deref()) {
+        println!("{}", line);
+    }
+    Ok(0)
+}
+
+pub(super) fn run_init(args: InitArgs) -> Result<i32, ScriptLangError> {
+    let root = Path::new(&args.dir);
+    let files = init_files(&args.name);
+    // Check everything first so a refused init leaves the directory untouched.
+    for (file_name, _) in &files {
+        let path = root.join(file_name);
+        if path.exists() {
+            return Err(ScriptLangError::new(
+                ErrorCode::CliInitExists,
+                format!(
+                    "{} already exists; init never overwrites files.",
+                    path.display()
+                ),
+            ));
+        }
+    }
+    fs::create_dir_all(root).map_err(map_cli_init_write)?;
+    println!("RESULT:OK");
+    println!("MODE:INIT");
+    for (file_name, content) in files {
+        let path = root.join(file_name);
+        fs::write(&path, content).map_err(map_cli_init_write)?;
+        println!("FILE:{}", path.display());
+    }
+    Ok(0)
+}
+
+/// Scaffold contents; `name` is escaped for XML text and kept literal in `${` positions.
+fn init_files(name: &str) -> Vec<(&'static str, String)> {
+    let xml_name = name
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace("${", "\\${");
+    let main = format!(
+        r#"<module name="main" export="script:main">
+  <script name="main">
+    <text>Welcome to {xml_name}!</text>
+    <choice text="What do you do?">
+      <option text="Look around">
+        <text>You take in the scenery.</text>
+      </option>
+      <option text="Move on">
+        <text>You set off down the road.</text>
+      </option>
+    </choice>
+    <text>The end.</text>
+    <end/>
+  </script>
+</module>
+"#
+    );
+    let shared = "<module name=\"shared\">\n</module>\n".to_string();
+    let game = format!(
+        "{}\n",
+        serde_json::to_string_pretty(&serde_json::json!({ "title": name }))
+            .unwrap_or_else(|_| "{}".to_string())
+    );
+    vec![
+        ("main.script.xml", main),
+        ("shared.defs.xml", shared),
+        ("game.json", game),
+    ]
+}
+
+fn list_lines(scripts: &BTreeMap<String, sl_api::ScriptIr>, tag: Option<&str>) -> Vec<String> {
+    scripts
+        .iter()
+        .filter(|(_, script)| tag.is_none_or(|tag| script.tags.iter().any(|item| item == tag)))
+        .map(|(name, script)| format!("SCRIPT:{}|{}", name, script.tags.join(",")))
+        .collect()
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum ReplayAction {
     Choose(usize),
@@ -137,23 +225,57 @@ struct ReplayResult {
     actions_used: usize,
     actions_total: usize,
     stop_at: ReplayStopAt,
+    next_calls: usize,
 }
 
 pub(super) fn run_replay(args: ReplayArgs) -> Result<i32, ScriptLangError> {
-    let entry_script = args.entry_script.unwrap_or("main.main".to_string());
-    let scenario = load_source_by_scripts_dir(&args.scripts_dir, &entry_script)?;
-    let random_sequence = parse_rand_sequence(args.rand.as_deref())?;
+    let scenario = load_source_by_scripts_dir(&args.scripts_dir, args.entry_script.as_deref())?;
+    let trace = args
+        .trace
+        .as_deref()
+        .map(|path| load_replay_trace(Path::new(path)))
+        .transpose()?;
+    if trace.is_some() && !args.step.is_empty() {
+        return Err(ScriptLangError::new(
+            ErrorCode::CliReplayTraceConflict,
+            "--trace cannot be combined with --step.",
+        ));
+    }
+    let steps = trace
+        .as_ref()
+        .map(|trace| trace.steps.clone())
+        .unwrap_or(args.step);
+    let random_sequence = match parse_rand_sequence(args.rand.as_deref())? {
+        Some(sequence) => Some(sequence),
+        None => trace
+            .as_ref()
+            .and_then(|trace| trace.random_sequence.clone()),
+    };
     let mut engine = create_engine_for_scenario(
         &scenario,
-        &entry_script,
+        &scenario.entry_script,
         RandConfig {
-            sequence: random_sequence,
+            sequence: random_sequence.clone(),
             sequence_index: Some(0),
             seed_state: None,
         },
     )?;
-    let actions = parse_replay_steps(&args.step)?;
+    let actions = parse_replay_steps(&steps)?;
     let result = run_replay_sequence(&mut engine, &actions, args.show_debug)?;
+    if let Some(trace) = &trace {
+        check_replay_divergence(&trace.lines, &result.lines)?;
+    }
+    if let Some(trace_out) = &args.trace_out {
+        save_replay_trace(
+            Path::new(trace_out),
+            &ReplayTrace {
+                schema_version: REPLAY_TRACE_SCHEMA.to_string(),
+                steps,
+                random_sequence,
+                lines: result.lines.clone(),
+            },
+        )?;
+    }
 
     println!("RESULT:OK");
     println!("MODE:REPLAY");
@@ -163,15 +285,39 @@ pub(super) fn run_replay(args: ReplayArgs) -> Result<i32, ScriptLangError> {
     println!("ACTIONS_USED: {}", result.actions_used);
     println!("ACTIONS_TOTAL: {}", result.actions_total);
     println!("STOP_AT: {}", result.stop_at.as_label());
+    if trace.is_some() {
+        println!("TRACE: MATCH");
+    }
     Ok(0)
 }
 
+fn check_replay_divergence(expected: &[String], actual: &[String]) -> Result<(), ScriptLangError> {
+    let Some(index) = (0..expected.len().max(actual.len()))
+        .find(|index| expected.get(*index) != actual.get(*index))
+    else {
+        return Ok(());
+    };
+    let describe = |line: Option<&String>| match line {
+        Some(line) => format!("\"{}\"", line),
+        None => "<none>".to_string(),
+    };
+    Err(ScriptLangError::new(
+        ErrorCode::CliReplayDivergence,
+        format!(
+            "Replay diverged from trace at line {}: expected {}, got {}.",
+            index + 1,
+            describe(expected.get(index)),
+            describe(actual.get(index))
+        ),
+    ))
+}
+
 fn parse_replay_steps(steps: &[String]) -> Result<Vec<ReplayAction>, ScriptLangError> {
     let mut actions = Vec::with_capacity(steps.len());
     for step in steps {
         let (kind, payload) = step.split_once(':').ok_or_else(|| {
             ScriptLangError::new(
-                "CLI_REPLAY_STEP_INVALID",
+                ErrorCode::CliReplayStepInvalid,
                 format!(
                     "Invalid replay step \"{}\". Expected format \"choose:<index>\" or \"input:<text>\".",
                     step
@@ -182,7 +328,7 @@ fn parse_replay_steps(steps: &[String]) -> Result<Vec<ReplayAction>, ScriptLangE
             "choose" => {
                 let index = payload.parse::<usize>().map_err(|_| {
                     ScriptLangError::new(
-                        "CLI_REPLAY_STEP_INVALID",
+                        ErrorCode::CliReplayStepInvalid,
                         format!("Invalid choose index in replay step \"{}\".", step),
                     )
                 })?;
@@ -191,7 +337,7 @@ fn parse_replay_steps(steps: &[String]) -> Result<Vec<ReplayAction>, ScriptLangE
             "input" => actions.push(ReplayAction::Input(payload.to_string())),
             _ => {
                 return Err(ScriptLangError::new(
-                    "CLI_REPLAY_STEP_INVALID",
+                    ErrorCode::CliReplayStepInvalid,
                     format!(
                         "Unsupported replay step kind \"{}\" in \"{}\". Expected choose/input.",
                         kind, step
@@ -210,24 +356,35 @@ fn run_replay_sequence(
 ) -> Result<ReplayResult, ScriptLangError> {
     let mut lines = Vec::new();
     let mut action_index = 0usize;
+    let mut next_calls = 0usize;
 
     loop {
+        next_calls += 1;
         match engine.next_output()? {
-            EngineOutput::Text { text, tag } => {
+            EngineOutput::Text { text, tag, speaker } => {
                 lines.push(format!("TEXT: {}", text));
                 if let Some(tag) = tag {
                     lines.push(format!("TEXT_TAG: {}", tag));
                 }
+                if let Some(speaker) = speaker {
+                    lines.push(format!("TEXT_SPEAKER: {}", speaker));
+                }
             }
             EngineOutput::Debug { text } => {
                 if show_debug {
                     lines.push(format!("DEBUG: {}", text));
                 }
             }
-            EngineOutput::Choices { items, prompt_text } => {
+            EngineOutput::Checkpoint { id, .. } => {
+                lines.push(format!("CHECKPOINT: {}", id));
+            }
+            EngineOutput::Choices {
+                items, prompt_text, ..
+            } => {
                 lines.push(format!("CHOICES: {}", prompt_text.unwrap_or_default()));
                 for item in items {
-                    lines.push(format!("- [{}] {}", item.index, item.text));
+                    let disabled = if item.enabled { "" } else { " (disabled)" };
+                    lines.push(format!("- [{}] {}{}", item.index, item.text, disabled));
                 }
                 let Some(action) = actions.get(action_index) else {
                     return Ok(ReplayResult {
@@ -235,6 +392,7 @@ fn run_replay_sequence(
                         actions_used: action_index,
                         actions_total: actions.len(),
                         stop_at: ReplayStopAt::Choices,
+                        next_calls,
                     });
                 };
                 match action {
@@ -245,7 +403,7 @@ fn run_replay_sequence(
                     }
                     ReplayAction::Input(text) => {
                         return Err(ScriptLangError::new(
-                            "CLI_REPLAY_ACTION_KIND_MISMATCH",
+                            ErrorCode::CliReplayActionKindMismatch,
                             format!(
                                 "Replay action mismatch at index {}. Expected choose action, got input:{}.",
                                 action_index, text
@@ -267,6 +425,7 @@ fn run_replay_sequence(
                         actions_used: action_index,
                         actions_total: actions.len(),
                         stop_at: ReplayStopAt::Input,
+                        next_calls,
                     });
                 };
                 match action {
@@ -277,7 +436,7 @@ fn run_replay_sequence(
                     }
                     ReplayAction::Choose(index) => {
                         return Err(ScriptLangError::new(
-                            "CLI_REPLAY_ACTION_KIND_MISMATCH",
+                            ErrorCode::CliReplayActionKindMismatch,
                             format!(
                                 "Replay action mismatch at index {}. Expected input action, got choose:{}.",
                                 action_index, index
@@ -290,7 +449,7 @@ fn run_replay_sequence(
                 lines.push("END".to_string());
                 if action_index != actions.len() {
                     return Err(ScriptLangError::new(
-                        "CLI_REPLAY_UNUSED_ACTIONS",
+                        ErrorCode::CliReplayUnusedActions,
                         format!(
                             "Replay ended with unused actions. used={} total={}",
                             action_index,
@@ -303,23 +462,123 @@ fn run_replay_sequence(
                     actions_used: action_index,
                     actions_total: actions.len(),
                     stop_at: ReplayStopAt::End,
+                    next_calls,
                 });
             }
         }
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+struct BenchReport {
+    iterations: usize,
+    next_calls: usize,
+    stop_at: ReplayStopAt,
+    wall_ms: Vec<f64>,
+}
+
+pub(super) fn run_bench(args: BenchArgs) -> Result<i32, ScriptLangError> {
+    if args.iterations == 0 {
+        return Err(ScriptLangError::new(
+            ErrorCode::CliBenchIterationsInvalid,
+            "--iterations must be greater than 0.",
+        ));
+    }
+    let scenario = load_source_by_scripts_dir(&args.scripts_dir, args.entry_script.as_deref())?;
+    let steps = match &args.inputs_file {
+        Some(path) => parse_inputs_file(&fs::read_to_string(path).map_err(map_cli_inputs_read)?),
+        None => Vec::new(),
+    };
+    let actions = parse_replay_steps(&steps)?;
+    let random_sequence = parse_rand_sequence(args.rand.as_deref())?;
+
+    let mut report = BenchReport {
+        iterations: args.iterations,
+        next_calls: 0,
+        stop_at: ReplayStopAt::End,
+        wall_ms: Vec::with_capacity(args.iterations),
+    };
+    for _ in 0..args.iterations {
+        let mut engine = create_engine_for_scenario(
+            &scenario,
+            &scenario.entry_script,
+            RandConfig {
+                sequence: random_sequence.clone(),
+                sequence_index: Some(0),
+                seed_state: None,
+            },
+        )?;
+        let started = Instant::now();
+        let result = run_replay_sequence(&mut engine, &actions, false)?;
+        report
+            .wall_ms
+            .push(started.elapsed().as_secs_f64() * 1000.0);
+        report.next_calls += result.next_calls;
+        report.stop_at = result.stop_at;
+    }
+
+    for line in bench_lines(&report, args.format) {
+        println!("{}", line);
+    }
+    Ok(0)
+}
+
+/// One replay step per line; blank lines and `#` comments are skipped.
+fn parse_inputs_file(raw: &str) -> Vec<String> {
+    raw.lines()
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .filter(|line| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+fn bench_lines(report: &BenchReport, format: BenchFormat) -> Vec<String> {
+    let mut sorted = report.wall_ms.clone();
+    sorted.sort_by(f64::total_cmp);
+    let middle = sorted.len() / 2;
+    let median = if sorted.len().is_multiple_of(2) {
+        (sorted[middle - 1] + sorted[middle]) / 2.0
+    } else {
+        sorted[middle]
+    };
+    let (min, max) = (sorted[0], sorted[sorted.len() - 1]);
+    match format {
+        BenchFormat::Json => vec![serde_json::json!({
+            "iterations": report.iterations,
+            "nextCalls": report.next_calls,
+            "stopAt": report.stop_at.as_label(),
+            "wallMs": { "min": min, "median": median, "max": max },
+        })
+        .to_string()],
+        BenchFormat::Text => vec![
+            "RESULT:OK".to_string(),
+            "MODE:BENCH".to_string(),
+            format!("ITERATIONS: {}", report.iterations),
+            format!("NEXT_CALLS: {}", report.next_calls),
+            format!("STOP_AT: {}", report.stop_at.as_label()),
+            format!("WALL_MS_MIN: {:.3}", min),
+            format!("WALL_MS_MEDIAN: {:.3}", median),
+            format!("WALL_MS_MAX: {:.3}", max),
+        ],
+    }
+}
+
 fn run_state_transition(
     state_in: &str,
     state_out: &str,
     random_sequence: Option<Vec<u32>>,
     show_debug: bool,
+    action_id: Option<&str>,
     transition: impl FnOnce(&mut sl_api::ScriptLangEngine) -> Result<(), ScriptLangError>,
 ) -> Result<i32, ScriptLangError> {
     let state = load_player_state(Path::new(state_in))?;
     let scenario = load_source_by_ref(&state.scenario_id)?;
     let mut engine = resume_engine_for_state(&scenario, &state, random_sequence)?;
-    transition(&mut engine)?;
+    // A retried command whose action was already applied re-emits the pending boundary.
+    let already_applied = action_id.is_some() && action_id == state.last_action_id.as_deref();
+    if !already_applied {
+        transition(&mut engine)?;
+    }
     let boundary = run_to_boundary(&mut engine, show_debug)?;
     emit_boundary_with_saved_state(
         &engine,
@@ -327,6 +586,7 @@ fn run_state_transition(
         state_out,
         &state.scenario_id,
         &state.compiler_version,
+        action_id,
     )
 }
 
@@ -356,16 +616,151 @@ mod agent_tests {
             command: AgentCommand::Input(InputArgs {
                 state_in: state_in.to_string_lossy().to_string(),
                 text: "Guild".to_string(),
+                action_id: None,
                 state_out: state_out.to_string_lossy().to_string(),
                 rand: None,
                 show_debug: false,
             }),
+            quiet: false,
         })
         .expect("input dispatch should pass");
 
         assert_eq!(code, 0);
     }
 
+    #[test]
+    fn run_list_filters_scripts_by_tag() {
+        let root = temp_path("agent-list-tags");
+        fs::create_dir_all(&root).expect("root should be created");
+        write_file(
+            &root.join("main.xml"),
+            r#"
+<module name="main" export="script:main,duel">
+<script name="main"><goto script="@duel"/></script>
+<script name="duel" tags="combat,boss"><end/></script>
+</module>"#,
+        );
+        let scenario = load_source_by_scripts_dir(&root.to_string_lossy(), None)
+            .expect("scenario should load");
+        let scripts = sl_api::compile_scripts_from_xml_map(&scenario.scripts_xml)
+            .expect("scripts should compile");
+
+        assert_eq!(
+            list_lines(&scripts, None),
+            vec!["SCRIPT:main.duel|combat,boss", "SCRIPT:main.main|"]
+        );
+        assert_eq!(
+            list_lines(&scripts, Some("combat")),
+            vec!["SCRIPT:main.duel|combat,boss"]
+        );
+        assert!(list_lines(&scripts, Some("shop")).is_empty());
+        let code = run_list(ListArgs {
+            scripts_dir: root.to_string_lossy().to_string(),
+            tag: Some("boss".to_string()),
+        })
+        .expect("list should pass");
+        assert_eq!(code, 0);
+    }
+
+    #[test]
+    fn run_init_scaffolds_a_scenario_that_compiles_and_plays() {
+        let root = temp_path("agent-init").join("story");
+        let dir = root.to_string_lossy().to_string();
+        let args = || InitArgs {
+            dir: dir.clone(),
+            name: "Rin & <Co> ${x}".to_string(),
+        };
+        assert_eq!(run_init(args()).expect("init should pass"), 0);
+        for file_name in ["main.script.xml", "shared.defs.xml", "game.json"] {
+            assert!(root.join(file_name).is_file(), "{file_name}");
+        }
+        let game: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(root.join("game.json")).expect("game.json"))
+                .expect("json");
+        assert_eq!(game["title"], "Rin & <Co> ${x}");
+
+        let compile = CompileArgs {
+            scripts_dir: dir.clone(),
+            entry_script: None,
+            output: None,
+            dry_run: true,
+            rand: None,
+        };
+        assert_eq!(run_compile(compile).expect("scaffold should compile"), 0);
+        let scenario = load_source_by_scripts_dir(&dir, None).expect("load");
+        let mut engine = create_engine_for_scenario(
+            &scenario,
+            &scenario.entry_script,
+            RandConfig {
+                sequence: None,
+                sequence_index: Some(0),
+                seed_state: None,
+            },
+        )
+        .expect("engine");
+        let result = run_replay_sequence(&mut engine, &[ReplayAction::Choose(1)], false)
+            .expect("scaffold should play");
+        assert_eq!(result.stop_at, ReplayStopAt::End);
+        assert!(result
+            .lines
+            .iter()
+            .any(|line| line.contains("Welcome to Rin & <Co> ${x}!")));
+
+        let main_path = root.join("main.script.xml");
+        write_file(&main_path, "<module name=\"main\"/>");
+        let error = run_init(args()).expect_err("existing files should be kept");
+        assert_eq!(error.code, "CLI_INIT_EXISTS");
+        assert_eq!(
+            fs::read_to_string(main_path).expect("main"),
+            "<module name=\"main\"/>"
+        );
+    }
+
+    #[test]
+    fn run_bench_autoplays_and_reports_wall_time() {
+        let root = temp_path("agent-bench");
+        fs::create_dir_all(&root).expect("root should be created");
+        let inputs_path = root.join("inputs.txt");
+        write_file(&inputs_path, "# name prompt\ninput:Rin\n\n");
+        let args = |iterations: usize| BenchArgs {
+            scripts_dir: example_scripts_dir("16-input-name"),
+            entry_script: Some("main.main".to_string()),
+            inputs_file: Some(inputs_path.to_string_lossy().to_string()),
+            iterations,
+            format: BenchFormat::Json,
+            rand: None,
+        };
+        assert_eq!(run_bench(args(3)).expect("bench should pass"), 0);
+        let error = run_bench(args(0)).expect_err("zero iterations should fail");
+        assert_eq!(error.code, "CLI_BENCH_ITERATIONS_INVALID");
+
+        assert_eq!(
+            parse_inputs_file("choose:0\r\n  # skip\ninput: two words \n"),
+            ["choose:0", "input: two words "]
+        );
+        let report = BenchReport {
+            iterations: 4,
+            next_calls: 20,
+            stop_at: ReplayStopAt::End,
+            wall_ms: vec![4.0, 1.0, 3.0, 2.0],
+        };
+        let lines = bench_lines(&report, BenchFormat::Text);
+        assert_eq!(lines[1], "MODE:BENCH");
+        assert_eq!(lines[3], "NEXT_CALLS: 20");
+        assert_eq!(
+            lines[5..],
+            [
+                "WALL_MS_MIN: 1.000",
+                "WALL_MS_MEDIAN: 2.500",
+                "WALL_MS_MAX: 4.000"
+            ]
+        );
+        let json: serde_json::Value =
+            serde_json::from_str(&bench_lines(&report, BenchFormat::Json)[0]).expect("json");
+        assert_eq!(json["stopAt"], "END");
+        assert_eq!(json["wallMs"]["median"], 2.5);
+    }
+
     #[test]
     fn run_replay_text_only_to_end() {
         let scripts_dir = example_scripts_dir("01-text-code");
@@ -373,6 +768,8 @@ mod agent_tests {
             scripts_dir,
             entry_script: Some("main.main".to_string()),
             step: Vec::new(),
+            trace: None,
+            trace_out: None,
             rand: None,
             show_debug: false,
         };
@@ -380,6 +777,78 @@ mod agent_tests {
         assert_eq!(code, 0);
     }
 
+    #[test]
+    fn run_replay_records_trace_and_detects_divergence() {
+        let root = temp_path("agent-replay-trace");
+        fs::create_dir_all(&root).expect("root should be created");
+        let script = |right_text: &str| {
+            format!(
+                r#"<module name="main" export="script:main">
+<script name="main">
+  <choice text="Door">
+    <option text="Left"><text>cave</text></option>
+    <option text="Right"><text>{}</text></option>
+  </choice>
+  <end/>
+</script>
+</module>"#,
+                right_text
+            )
+        };
+        write_file(&root.join("main.xml"), &script("river"));
+        let scripts_dir = root.to_string_lossy().to_string();
+        let trace_path = root.join("trace").join("run.json");
+        let replay_args =
+            |step: Vec<String>, trace: Option<String>, trace_out: Option<String>| ReplayArgs {
+                scripts_dir: scripts_dir.clone(),
+                entry_script: Some("main.main".to_string()),
+                step,
+                trace,
+                trace_out,
+                rand: None,
+                show_debug: false,
+            };
+
+        run_replay(replay_args(
+            vec!["choose:1".to_string()],
+            None,
+            Some(trace_path.to_string_lossy().to_string()),
+        ))
+        .expect("record should pass");
+        let trace = load_replay_trace(&trace_path).expect("trace should load");
+        assert_eq!(trace.steps, vec!["choose:1".to_string()]);
+        assert!(trace.lines.iter().any(|line| line == "TEXT: river"));
+
+        let trace_arg = Some(trace_path.to_string_lossy().to_string());
+        assert_eq!(
+            run_replay(replay_args(Vec::new(), trace_arg.clone(), None)).expect("replay"),
+            0
+        );
+        assert_eq!(
+            run_replay(replay_args(
+                vec!["choose:0".to_string()],
+                trace_arg.clone(),
+                None
+            ))
+            .expect_err("trace and steps conflict")
+            .code,
+            "CLI_REPLAY_TRACE_CONFLICT"
+        );
+
+        write_file(&root.join("main.xml"), &script("bridge"));
+        let error = run_replay(replay_args(Vec::new(), trace_arg, None))
+            .expect_err("edited script should diverge");
+        assert_eq!(error.code, "CLI_REPLAY_DIVERGENCE");
+        assert!(error.message.contains("\"TEXT: river\""));
+        assert!(error.message.contains("\"TEXT: bridge\""));
+        assert_eq!(
+            check_replay_divergence(&["END".to_string()], &[])
+                .expect_err("missing line")
+                .message,
+            "Replay diverged from trace at line 1: expected \"END\", got <none>."
+        );
+    }
+
     #[test]
     fn run_replay_hides_or_shows_debug_lines_by_flag() {
         let root = temp_path("agent-replay-debug-flag");
@@ -390,8 +859,9 @@ mod agent_tests {
 <script name="main"><debug>dbg=${1+1}</debug><text>ok</text><end/></script>
 </module>"#,
         );
-        let scenario = load_source_by_scripts_dir(root.to_string_lossy().as_ref(), "main.main")
-            .expect("scenario");
+        let scenario =
+            load_source_by_scripts_dir(root.to_string_lossy().as_ref(), Some("main.main"))
+                .expect("scenario");
         let mut engine = create_engine_for_scenario(&scenario, "main.main", RandConfig::default())
             .expect("engine");
         let hidden = run_replay_sequence(&mut engine, &[], false).expect("hidden replay");
@@ -424,7 +894,7 @@ mod agent_tests {
         );
 
         let mut engine = create_engine_for_scenario(
-            &load_source_by_scripts_dir(root.to_string_lossy().as_ref(), "main.main")
+            &load_source_by_scripts_dir(root.to_string_lossy().as_ref(), Some("main.main"))
                 .expect("scenario should load"),
             "main.main",
             RandConfig::default(),
@@ -459,8 +929,9 @@ mod agent_tests {
 </script>
 </module>"#,
         );
-        let scenario = load_source_by_scripts_dir(root.to_string_lossy().as_ref(), "main.main")
-            .expect("scenario");
+        let scenario =
+            load_source_by_scripts_dir(root.to_string_lossy().as_ref(), Some("main.main"))
+                .expect("scenario");
         let mut engine = create_engine_for_scenario(&scenario, "main.main", RandConfig::default())
             .expect("engine");
         let actions = parse_replay_steps(&["choose:0".to_string()]).expect("steps should parse");
@@ -483,7 +954,8 @@ mod agent_tests {
     #[test]
     fn run_replay_reports_action_kind_mismatch() {
         let scripts_dir = example_scripts_dir("16-input-name");
-        let scenario = load_source_by_scripts_dir(&scripts_dir, "main.main").expect("scenario");
+        let scenario =
+            load_source_by_scripts_dir(&scripts_dir, Some("main.main")).expect("scenario");
         let mut engine = create_engine_for_scenario(&scenario, "main.main", RandConfig::default())
             .expect("engine");
         let actions = parse_replay_steps(&["choose:0".to_string()]).expect("steps should parse");
@@ -495,7 +967,8 @@ mod agent_tests {
     #[test]
     fn run_replay_reports_unused_actions_if_end_reached_early() {
         let scripts_dir = example_scripts_dir("01-text-code");
-        let scenario = load_source_by_scripts_dir(&scripts_dir, "main.main").expect("scenario");
+        let scenario =
+            load_source_by_scripts_dir(&scripts_dir, Some("main.main")).expect("scenario");
         let mut engine = create_engine_for_scenario(&scenario, "main.main", RandConfig::default())
             .expect("engine");
         let actions = parse_replay_steps(&["input:Guild".to_string()]).expect("steps should parse");
@@ -540,6 +1013,7 @@ mod agent_tests {
         run_choose(ChooseArgs {
             state_in: state_1.to_string_lossy().to_string(),
             choice: 0,
+            action_id: None,
             state_out: state_2.to_string_lossy().to_string(),
             rand: None,
             show_debug: false,
@@ -552,6 +1026,75 @@ mod agent_tests {
         assert_eq!(state.random_sequence_index, Some(1));
     }
 
+    #[test]
+    fn choose_with_repeated_action_id_is_a_no_op() {
+        let root = temp_path("agent-action-id");
+        fs::create_dir_all(&root).expect("root should be created");
+        write_file(
+            &root.join("main.xml"),
+            r#"
+<module name="main" export="script:main">
+<script name="main">
+  <choice text="First">
+    <option text="A">
+      <choice text="Second">
+        <option text="B">
+          <temp name="name" type="string">"Traveler"</temp>
+          <input var="name" text="Name"/>
+          <end/>
+        </option>
+      </choice>
+    </option>
+  </choice>
+</script>
+</module>"#,
+        );
+
+        let state = temp_path("agent-action-id-state.json");
+        let state_arg = state.to_string_lossy().to_string();
+        run_start(StartArgs {
+            scripts_dir: root.to_string_lossy().to_string(),
+            entry_script: Some("main.main".to_string()),
+            state_out: state_arg.clone(),
+            rand: None,
+            show_debug: false,
+        })
+        .expect("start should pass");
+
+        let choose = |action_id: &str| {
+            run_choose(ChooseArgs {
+                state_in: state_arg.clone(),
+                choice: 0,
+                action_id: Some(action_id.to_string()),
+                state_out: state_arg.clone(),
+                rand: None,
+                show_debug: false,
+            })
+        };
+        choose("a1").expect("first choose should pass");
+        let after_first = fs::read_to_string(&state).expect("state should read");
+        assert_eq!(
+            load_player_state(&state)
+                .expect("state should load")
+                .last_action_id,
+            Some("a1".to_string())
+        );
+
+        choose("a1").expect("retried choose should be a no-op");
+        assert_eq!(
+            fs::read_to_string(&state).expect("state should read"),
+            after_first
+        );
+
+        choose("a2").expect("new action id should apply");
+        let loaded = load_player_state(&state).expect("state should load");
+        assert_eq!(loaded.last_action_id, Some("a2".to_string()));
+        assert!(matches!(
+            loaded.snapshot.pending_boundary,
+            sl_api::PendingBoundary::Input { .. }
+        ));
+    }
+
     #[test]
     fn choose_rand_argument_overrides_state_random_sequence() {
         let root = temp_path("agent-rand-override");
@@ -588,6 +1131,7 @@ mod agent_tests {
         run_choose(ChooseArgs {
             state_in: state_1.to_string_lossy().to_string(),
             choice: 0,
+            action_id: None,
             state_out: state_2.to_string_lossy().to_string(),
             rand: Some("9,8,7".to_string()),
             show_debug: false,
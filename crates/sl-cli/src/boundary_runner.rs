@@ -14,15 +14,19 @@ pub(crate) fn run_to_boundary(
 
     loop {
         match engine.next_output()? {
-            EngineOutput::Text { text, tag } => {
-                outputs.push(OutputEvent::Text(TextEvent { text, tag }))
+            EngineOutput::Text { text, tag, speaker } => {
+                outputs.push(OutputEvent::Text(TextEvent { text, tag, speaker }))
             }
             EngineOutput::Debug { text } => {
                 if show_debug {
                     outputs.push(OutputEvent::Debug(DebugEvent { text }));
                 }
             }
-            EngineOutput::Choices { items, prompt_text } => {
+            // Session state is saved on demand by the CLI, so script checkpoints are not kept.
+            EngineOutput::Checkpoint { .. } => {}
+            EngineOutput::Choices {
+                items, prompt_text, ..
+            } => {
                 return Ok(BoundaryResult {
                     event: BoundaryEvent::Choices,
                     outputs,
@@ -78,6 +82,9 @@ pub(crate) fn emit_boundary(boundary: BoundaryResult, state_out: Option<String>)
                 if let Some(tag) = text_event.tag {
                     println!("TEXT_TAG_JSON:{}", json_string(&tag));
                 }
+                if let Some(speaker) = text_event.speaker {
+                    println!("TEXT_SPEAKER_JSON:{}", json_string(&speaker));
+                }
             }
             OutputEvent::Debug(debug_event) => {
                 println!("DEBUG_JSON:{}", json_string(&debug_event.text));
@@ -113,13 +120,15 @@ mod boundary_runner_tests {
     use crate::cli_test_support::*;
     use crate::{load_source_by_ref, load_source_by_scripts_dir};
     use sl_api::DEFAULT_COMPILER_VERSION;
-    use sl_api::{create_engine_from_xml, CreateEngineFromXmlOptions};
+    use sl_api::{
+        create_engine_from_xml, CompileOptions, CreateEngineFromXmlOptions, EngineTuning,
+    };
 
     #[test]
     fn run_to_boundary_and_load_source_helpers_work_with_examples() {
         let scripts_dir = example_scripts_dir("06-snapshot-flow");
-        let loaded =
-            load_source_by_scripts_dir(&scripts_dir, "main.main").expect("source should be loaded");
+        let loaded = load_source_by_scripts_dir(&scripts_dir, Some("main.main"))
+            .expect("source should be loaded");
         assert!(loaded.id.starts_with("scripts-dir:"));
         assert_eq!(loaded.entry_script, "main.main");
 
@@ -132,6 +141,8 @@ mod boundary_runner_tests {
             random_sequence: None,
             random_sequence_index: None,
             compiler_version: Some(DEFAULT_COMPILER_VERSION.to_string()),
+            compile_options: CompileOptions::default(),
+            tuning: EngineTuning::default(),
         })
         .expect("engine should build");
 
@@ -152,6 +163,7 @@ mod boundary_runner_tests {
                     OutputEvent::Text(TextEvent {
                         text: "plain".to_string(),
                         tag: None,
+                        speaker: None,
                     }),
                     OutputEvent::Debug(DebugEvent {
                         text: "dbg".to_string(),
@@ -159,6 +171,7 @@ mod boundary_runner_tests {
                     OutputEvent::Text(TextEvent {
                         text: "sfx/path.ogg".to_string(),
                         tag: Some("sound".to_string()),
+                        speaker: Some("Ann".to_string()),
                     }),
                 ],
                 choices: Vec::new(),
@@ -187,6 +200,8 @@ mod boundary_runner_tests {
             random_sequence: None,
             random_sequence_index: None,
             compiler_version: Some(DEFAULT_COMPILER_VERSION.to_string()),
+            compile_options: CompileOptions::default(),
+            tuning: EngineTuning::default(),
         })
         .expect("engine should build");
         let hidden_boundary = run_to_boundary(&mut hidden, false).expect("boundary hidden");
@@ -208,6 +223,8 @@ mod boundary_runner_tests {
             random_sequence: None,
             random_sequence_index: None,
             compiler_version: Some(DEFAULT_COMPILER_VERSION.to_string()),
+            compile_options: CompileOptions::default(),
+            tuning: EngineTuning::default(),
         })
         .expect("engine should build");
         let shown_boundary = run_to_boundary(&mut shown, true).expect("boundary shown");
@@ -1,4 +1,4 @@
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
 #[derive(Debug, Parser)]
 #[command(name = "sl-cli")]
@@ -19,6 +19,8 @@ pub(crate) enum Mode {
         long_about = "Compile scripts and output artifact JSON.\n\nUse --dry-run to compile only in memory without writing output (useful for debugging compilation errors)."
     )]
     Compile(CompileArgs),
+    #[command(about = "List compiled scripts, optionally filtered by tag")]
+    List(ListArgs),
     Tui(TuiArgs),
 }
 
@@ -26,6 +28,9 @@ pub(crate) enum Mode {
 pub(crate) struct AgentArgs {
     #[command(subcommand)]
     pub(crate) command: AgentCommand,
+    #[arg(long = "quiet", global = true)]
+    #[arg(help = "Suppress RESULT:ERROR/ERROR_MSG_JSON lines; rely on ERROR_CODE and exit code")]
+    pub(crate) quiet: bool,
 }
 
 #[derive(Debug, Subcommand)]
@@ -38,9 +43,19 @@ pub(crate) enum AgentCommand {
     Input(InputArgs),
     #[command(about = "Run from a fresh start with queued --step actions")]
     #[command(
-        long_about = "Run from a fresh start with queued --step actions.\n\nEach `--step` is consumed when a matching boundary appears:\n- choose:<index>\n- input:<text>\n\nWhen steps are exhausted, replay continues until the next boundary (CHOICES/INPUT/END), then exits successfully with a summary."
+        long_about = "Run from a fresh start with queued --step actions.\n\nEach `--step` is consumed when a matching boundary appears:\n- choose:<index>\n- input:<text>\n\nWhen steps are exhausted, replay continues until the next boundary (CHOICES/INPUT/END), then exits successfully with a summary.\n\nUse --trace-out to record the steps and transcript, and --trace to re-drive a recorded run and fail with CLI_REPLAY_DIVERGENCE if the transcript changed."
     )]
     Replay(ReplayArgs),
+    #[command(about = "Autoplay a scenario repeatedly and report next() calls and wall time")]
+    #[command(
+        long_about = "Autoplay a scenario repeatedly and report next() calls and wall time.\n\nEach iteration starts a fresh engine and drives it like `replay`, consuming the steps from --inputs-file. Wall time is reported as min/median/max in milliseconds; use --format json for CI ingestion."
+    )]
+    Bench(BenchArgs),
+    #[command(about = "Scaffold a minimal runnable scenario into a directory")]
+    #[command(
+        long_about = "Scaffold a minimal runnable scenario into a directory.\n\nWrites main.script.xml (a greeting and a two-option choice), an empty shared.defs.xml and game.json. Existing files are never overwritten: if any of them is already present, nothing is written and the command fails with CLI_INIT_EXISTS."
+    )]
+    Init(InitArgs),
 }
 
 #[derive(Debug, Args)]
@@ -49,7 +64,7 @@ pub(crate) struct StartArgs {
     #[arg(help = "Directory containing *.xml")]
     pub(crate) scripts_dir: String,
     #[arg(long = "entry-script")]
-    #[arg(help = "Entry script name (default: main.main)")]
+    #[arg(help = "Entry script name (default: scenario.json entry, then main.main)")]
     pub(crate) entry_script: Option<String>,
     #[arg(long = "state-out")]
     #[arg(help = "Path to write player state json")]
@@ -70,6 +85,9 @@ pub(crate) struct ChooseArgs {
     #[arg(long = "choice")]
     #[arg(help = "Visible choice index to submit")]
     pub(crate) choice: usize,
+    #[arg(long = "action-id")]
+    #[arg(help = "Idempotency token; a retry with the last applied id is a no-op")]
+    pub(crate) action_id: Option<String>,
     #[arg(long = "state-out")]
     #[arg(help = "Path to output player state json")]
     pub(crate) state_out: String,
@@ -89,6 +107,9 @@ pub(crate) struct InputArgs {
     #[arg(long = "text")]
     #[arg(help = "Input text to submit")]
     pub(crate) text: String,
+    #[arg(long = "action-id")]
+    #[arg(help = "Idempotency token; a retry with the last applied id is a no-op")]
+    pub(crate) action_id: Option<String>,
     #[arg(long = "state-out")]
     #[arg(help = "Path to output player state json")]
     pub(crate) state_out: String,
@@ -109,11 +130,17 @@ pub(crate) struct ReplayArgs {
     #[arg(help = "Directory containing *.xml")]
     pub(crate) scripts_dir: String,
     #[arg(long = "entry-script")]
-    #[arg(help = "Entry script name (default: main.main)")]
+    #[arg(help = "Entry script name (default: scenario.json entry, then main.main)")]
     pub(crate) entry_script: Option<String>,
     #[arg(long = "step")]
     #[arg(help = "Replay action: choose:<index> or input:<text>. Repeat to build a queue")]
     pub(crate) step: Vec<String>,
+    #[arg(long = "trace")]
+    #[arg(help = "Replay steps from a recorded trace json and fail if the transcript diverges")]
+    pub(crate) trace: Option<String>,
+    #[arg(long = "trace-out")]
+    #[arg(help = "Path to write the replayed steps and transcript as a trace json")]
+    pub(crate) trace_out: Option<String>,
     #[arg(long = "rand")]
     #[arg(help = "Comma-separated random sequence, e.g. 12,3,1")]
     pub(crate) rand: Option<String>,
@@ -122,6 +149,46 @@ pub(crate) struct ReplayArgs {
     pub(crate) show_debug: bool,
 }
 
+#[derive(Debug, Args)]
+pub(crate) struct InitArgs {
+    #[arg(long = "dir")]
+    #[arg(help = "Directory to write the scenario into (created if missing)")]
+    pub(crate) dir: String,
+    #[arg(long = "name")]
+    #[arg(help = "Scenario name shown in the greeting and game.json title")]
+    pub(crate) name: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum BenchFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, Args)]
+pub(crate) struct BenchArgs {
+    #[arg(long = "scripts-dir")]
+    #[arg(help = "Directory containing *.xml")]
+    pub(crate) scripts_dir: String,
+    #[arg(long = "entry-script")]
+    #[arg(help = "Entry script name (default: scenario.json entry, then main.main)")]
+    pub(crate) entry_script: Option<String>,
+    #[arg(long = "inputs-file")]
+    #[arg(
+        help = "File with one replay step per line (choose:<index> or input:<text>); blank lines and # comments are skipped"
+    )]
+    pub(crate) inputs_file: Option<String>,
+    #[arg(long = "iterations", default_value_t = 10)]
+    #[arg(help = "Number of full playthroughs to time")]
+    pub(crate) iterations: usize,
+    #[arg(long = "format", value_enum, default_value_t = BenchFormat::Text)]
+    #[arg(help = "Report format")]
+    pub(crate) format: BenchFormat,
+    #[arg(long = "rand")]
+    #[arg(help = "Comma-separated random sequence, e.g. 12,3,1")]
+    pub(crate) rand: Option<String>,
+}
+
 #[derive(Debug, Args)]
 #[command(about = "Interactive TUI mode (auto-fallback to line mode in non-TTY/test env)")]
 pub(crate) struct TuiArgs {
@@ -129,7 +196,7 @@ pub(crate) struct TuiArgs {
     #[arg(help = "Directory containing *.xml")]
     pub(crate) scripts_dir: String,
     #[arg(long = "entry-script")]
-    #[arg(help = "Entry script name (default: main.main)")]
+    #[arg(help = "Entry script name (default: scenario.json entry, then main.main)")]
     pub(crate) entry_script: Option<String>,
     #[arg(long = "state-file")]
     #[arg(help = "Path to save/load state (default: .scriptlang/save.json)")]
@@ -142,13 +209,23 @@ pub(crate) struct TuiArgs {
     pub(crate) show_debug: bool,
 }
 
+#[derive(Debug, Args)]
+pub(crate) struct ListArgs {
+    #[arg(long = "scripts-dir")]
+    #[arg(help = "Directory containing *.xml")]
+    pub(crate) scripts_dir: String,
+    #[arg(long = "tag")]
+    #[arg(help = "Only list scripts declaring this tag in <script tags>")]
+    pub(crate) tag: Option<String>,
+}
+
 #[derive(Debug, Args)]
 pub(crate) struct CompileArgs {
     #[arg(long = "scripts-dir")]
     #[arg(help = "Directory containing *.xml")]
     pub(crate) scripts_dir: String,
     #[arg(long = "entry-script")]
-    #[arg(help = "Entry script name (default: main.main)")]
+    #[arg(help = "Entry script name (default: scenario.json entry, then main.main)")]
     pub(crate) entry_script: Option<String>,
     #[arg(long = "output", short = 'o')]
     #[arg(help = "Output path for artifact JSON (required if not --dry-run)")]
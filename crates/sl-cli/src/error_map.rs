@@ -1,26 +1,26 @@
-use sl_api::ScriptLangError;
+use sl_api::{ErrorCode, ScriptLangError};
 use std::fmt::Display;
 
-fn map_error(code: &'static str, error: impl Display) -> ScriptLangError {
+fn map_error(code: ErrorCode, error: impl Display) -> ScriptLangError {
     ScriptLangError::new(code, error.to_string())
 }
 
 fn hint_for_error(code: &str, message: &str) -> Option<&'static str> {
-    if code == "XML_PARSE_ERROR" && message.contains("invalid name token") {
+    if code == ErrorCode::XmlParseError.as_str() && message.contains("invalid name token") {
         return Some(
             "Hint: ScriptLang expressions no longer use XML escape operators. Write LT/LTE/AND instead of <, <=, &&. In XML attributes use 'text'; in <code>/<function>/<var>/<temp> bodies use \"text\".",
         );
     }
 
     if code.starts_with("RHAI_PREPROCESS_FORBIDDEN_")
-        || code == "RHAI_PREPROCESS_STRING_UNTERMINATED"
+        || code == ErrorCode::RhaiPreprocessStringUnterminated.as_str()
     {
         return Some(
             "Hint: use ScriptLang expr keywords LT/LTE/AND. In XML attributes, write strings with single quotes like 'text'; in <code>/<function>/<var>/<temp> bodies, use double quotes like \"text\". Raw <, <=, && are forbidden.",
         );
     }
 
-    if code == "TYPE_UNKNOWN" && message.contains("Unknown custom type") {
+    if code == ErrorCode::TypeUnknown.as_str() && message.contains("Unknown custom type") {
         return Some(
             "Hint: custom types are visible by import-closure, not auto inheritance. Add the required `<!-- import ... from ... -->` directive in each module that references the type.",
         );
@@ -35,62 +35,187 @@ fn hint_for_error(code: &str, message: &str) -> Option<&'static str> {
     None
 }
 
-fn with_hint(error: ScriptLangError) -> ScriptLangError {
-    let mut message = error.message;
-    if let Some(hint) = hint_for_error(&error.code, &message) {
-        message.push('\n');
-        message.push_str(hint);
+fn with_hint(mut error: ScriptLangError) -> ScriptLangError {
+    if let Some(hint) = hint_for_error(&error.code, &error.message) {
+        error.message.push('\n');
+        error.message.push_str(hint);
     }
-    ScriptLangError::new(error.code, message)
+    error
 }
 
-pub(crate) fn emit_error(error: ScriptLangError) -> i32 {
+pub(crate) const EXIT_CODE_GENERIC: i32 = 1;
+pub(crate) const EXIT_CODE_COMPILE: i32 = 3;
+pub(crate) const EXIT_CODE_RUNTIME: i32 = 4;
+pub(crate) const EXIT_CODE_IO: i32 = 5;
+
+const COMPILE_ERROR_FAMILIES: [&str; 15] = [
+    "XML", "TYPE", "NAME", "ENUM", "RHAI", "IMPORT", "ALIAS", "SCRIPT", "MODULE", "FUNCTION",
+    "ARTIFACT", "API", "SOURCE", "GLOBAL", "CALL",
+];
+
+pub(crate) fn exit_code_for_error(code: &str) -> i32 {
+    let family = code.split('_').next().unwrap_or_default();
+    match family {
+        "CLI" | "TUI" => EXIT_CODE_IO,
+        "ENGINE" | "SNAPSHOT" | "RUNTIME" => EXIT_CODE_RUNTIME,
+        _ if code == ErrorCode::ArtifactIoError.as_str() => EXIT_CODE_IO,
+        _ if COMPILE_ERROR_FAMILIES.contains(&family) => EXIT_CODE_COMPILE,
+        _ => EXIT_CODE_GENERIC,
+    }
+}
+
+fn error_lines(error: &ScriptLangError, quiet: bool) -> Vec<String> {
+    let mut lines = Vec::new();
+    if !quiet {
+        lines.push("RESULT:ERROR".to_string());
+    }
+    lines.push(format!("ERROR_CODE:{}", error.code));
+    if !quiet {
+        lines.push(format!(
+            "ERROR_MSG_JSON:{}",
+            serde_json::Value::String(error.message.clone())
+        ));
+        if let Some((file, location)) = error.source_location() {
+            lines.push(format!(
+                "ERROR_LOC_JSON:{}",
+                serde_json::json!({
+                    "file": file,
+                    "line": location.line,
+                    "col": location.column,
+                })
+            ));
+        }
+    }
+    lines
+}
+
+pub(crate) fn emit_error(error: ScriptLangError, quiet: bool) -> i32 {
     let error = with_hint(error);
-    println!("RESULT:ERROR");
-    println!("ERROR_CODE:{}", error.code);
-    println!(
-        "ERROR_MSG_JSON:{}",
-        serde_json::Value::String(error.message)
-    );
-    1
+    for line in error_lines(&error, quiet) {
+        println!("{}", line);
+    }
+    exit_code_for_error(&error.code)
 }
 
 pub(crate) fn map_tui_io(error: std::io::Error) -> ScriptLangError {
-    map_error("TUI_IO", error)
+    map_error(ErrorCode::TuiIo, error)
 }
 
 pub(crate) fn map_cli_source_path(error: std::io::Error) -> ScriptLangError {
-    map_error("CLI_SOURCE_PATH", error)
+    map_error(ErrorCode::CliSourcePath, error)
 }
 
 pub(crate) fn map_cli_source_scan(error: std::path::StripPrefixError) -> ScriptLangError {
-    map_error("CLI_SOURCE_SCAN", error)
+    map_error(ErrorCode::CliSourceScan, error)
 }
 
 pub(crate) fn map_cli_source_read(error: std::io::Error) -> ScriptLangError {
-    map_error("CLI_SOURCE_READ", error)
+    map_error(ErrorCode::CliSourceRead, error)
+}
+
+pub(crate) fn map_cli_init_write(error: std::io::Error) -> ScriptLangError {
+    map_error(ErrorCode::CliInitWrite, error)
 }
 
 pub(crate) fn map_cli_state_write(error: std::io::Error) -> ScriptLangError {
-    map_error("CLI_STATE_WRITE", error)
+    map_error(ErrorCode::CliStateWrite, error)
 }
 
 pub(crate) fn map_cli_state_read(error: std::io::Error) -> ScriptLangError {
-    map_error("CLI_STATE_READ", error)
+    map_error(ErrorCode::CliStateRead, error)
 }
 
 pub(crate) fn map_cli_state_invalid(error: serde_json::Error) -> ScriptLangError {
-    map_error("CLI_STATE_INVALID", error)
+    map_error(ErrorCode::CliStateInvalid, error)
+}
+
+pub(crate) fn map_cli_trace_write(error: std::io::Error) -> ScriptLangError {
+    map_error(ErrorCode::CliTraceWrite, error)
+}
+
+pub(crate) fn map_cli_trace_read(error: std::io::Error) -> ScriptLangError {
+    map_error(ErrorCode::CliTraceRead, error)
+}
+
+pub(crate) fn map_cli_trace_invalid(error: serde_json::Error) -> ScriptLangError {
+    map_error(ErrorCode::CliTraceInvalid, error)
+}
+
+pub(crate) fn map_cli_inputs_read(error: std::io::Error) -> ScriptLangError {
+    map_error(ErrorCode::CliInputsRead, error)
+}
+
+pub(crate) fn map_cli_manifest_invalid(error: serde_json::Error) -> ScriptLangError {
+    map_error(ErrorCode::CliManifestInvalid, error)
 }
 
 #[cfg(test)]
 mod error_map_tests {
     use super::*;
+    use sl_api::{
+        create_engine_from_xml, CompileOptions, CreateEngineFromXmlOptions, EngineTuning,
+    };
+    use std::collections::BTreeMap;
 
     #[test]
     fn emit_error_returns_non_zero_exit_code() {
-        let code = emit_error(ScriptLangError::new("ERR", "failed"));
-        assert_eq!(code, 1);
+        let code = emit_error(ScriptLangError::new("ERR", "failed"), false);
+        assert_eq!(code, EXIT_CODE_GENERIC);
+        let code = emit_error(ScriptLangError::new("ENGINE_EVAL_ERROR", "failed"), true);
+        assert_eq!(code, EXIT_CODE_RUNTIME);
+    }
+
+    #[test]
+    fn error_lines_include_location_of_compile_errors() {
+        let scripts = BTreeMap::from([(
+            "main.xml".to_string(),
+            "<module name=\"main\">\n<script name=\"main\">\n  <text>hi</txt>\n</script>\n</module>"
+                .to_string(),
+        )]);
+        let error = create_engine_from_xml(CreateEngineFromXmlOptions {
+            scripts_xml: scripts,
+            entry_script: None,
+            entry_args: None,
+            host_functions: None,
+            random_seed: None,
+            random_sequence: None,
+            random_sequence_index: None,
+            compiler_version: None,
+            compile_options: CompileOptions::default(),
+            tuning: EngineTuning::default(),
+        })
+        .err()
+        .map(with_hint)
+        .expect("broken script should not compile");
+        assert_eq!(error.code, "XML_PARSE_ERROR");
+
+        let lines = error_lines(&error, false);
+        assert_eq!(
+            lines.last().map(String::as_str),
+            Some(r#"ERROR_LOC_JSON:{"col":11,"file":"main.xml","line":3}"#)
+        );
+        assert!(!error_lines(&error, true)
+            .iter()
+            .any(|line| line.starts_with("ERROR_LOC_JSON:")));
+        assert!(!error_lines(&ScriptLangError::new("ERR", "failed"), false)
+            .iter()
+            .any(|line| line.starts_with("ERROR_LOC_JSON:")));
+    }
+
+    #[test]
+    fn exit_code_for_error_maps_code_families() {
+        assert_eq!(exit_code_for_error("XML_PARSE_ERROR"), EXIT_CODE_COMPILE);
+        assert_eq!(exit_code_for_error("TYPE_UNKNOWN"), EXIT_CODE_COMPILE);
+        assert_eq!(exit_code_for_error("ENGINE_EVAL_ERROR"), EXIT_CODE_RUNTIME);
+        assert_eq!(exit_code_for_error("SNAPSHOT_SCHEMA"), EXIT_CODE_RUNTIME);
+        assert_eq!(exit_code_for_error("CLI_STATE_READ"), EXIT_CODE_IO);
+        assert_eq!(exit_code_for_error("TUI_IO"), EXIT_CODE_IO);
+        assert_eq!(exit_code_for_error("ARTIFACT_IO_ERROR"), EXIT_CODE_IO);
+        assert_eq!(
+            exit_code_for_error("ARTIFACT_PARSE_ERROR"),
+            EXIT_CODE_COMPILE
+        );
+        assert_eq!(exit_code_for_error("UNKNOWN"), EXIT_CODE_GENERIC);
     }
 
     #[test]
@@ -121,6 +246,12 @@ mod error_map_tests {
 
         let invalid = serde_json::from_str::<serde_json::Value>("{").expect_err("invalid json");
         assert_eq!(map_cli_state_invalid(invalid).code, "CLI_STATE_INVALID");
+
+        let invalid = serde_json::from_str::<serde_json::Value>("[").expect_err("invalid json");
+        assert_eq!(
+            map_cli_manifest_invalid(invalid).code,
+            "CLI_MANIFEST_INVALID"
+        );
     }
 
     #[test]
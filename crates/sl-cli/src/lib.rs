@@ -5,7 +5,7 @@ use sl_api::ScriptLangError;
 #[cfg(test)]
 use sl_api::DEFAULT_COMPILER_VERSION;
 #[cfg(test)]
-use sl_api::{create_engine_from_xml, CreateEngineFromXmlOptions};
+use sl_api::{create_engine_from_xml, CompileOptions, CreateEngineFromXmlOptions, EngineTuning};
 
 mod agent;
 mod boundary_runner;
@@ -23,19 +23,22 @@ mod tui_state;
 
 pub(crate) use boundary_runner::{emit_boundary, run_to_boundary};
 pub(crate) use cli_args::{
-    AgentArgs, AgentCommand, ChooseArgs, Cli, CompileArgs, InputArgs, Mode, ReplayArgs, StartArgs,
-    TuiArgs,
+    AgentArgs, AgentCommand, BenchArgs, BenchFormat, ChooseArgs, Cli, CompileArgs, InitArgs,
+    InputArgs, ListArgs, Mode, ReplayArgs, StartArgs, TuiArgs,
 };
 pub(crate) use error_map::{
-    emit_error, map_cli_source_path, map_cli_source_read, map_cli_source_scan,
-    map_cli_state_invalid, map_cli_state_read, map_cli_state_write, map_tui_io,
+    emit_error, map_cli_init_write, map_cli_inputs_read, map_cli_manifest_invalid,
+    map_cli_source_path, map_cli_source_read, map_cli_source_scan, map_cli_state_invalid,
+    map_cli_state_read, map_cli_state_write, map_cli_trace_invalid, map_cli_trace_read,
+    map_cli_trace_write, map_tui_io,
 };
 pub(crate) use line_tui::run_tui_line_mode;
 #[cfg(test)]
 pub(crate) use line_tui::{handle_line_cmd, handle_tui_command};
 pub(crate) use models::{
     BoundaryEvent, BoundaryResult, DebugEvent, LoadedScenario, OutputEvent, PlayerRandomMode,
-    PlayerState, TextEvent, TuiCommandAction, TuiCommandContext, PLAYER_STATE_SCHEMA,
+    PlayerState, ReplayTrace, ScenarioManifest, TextEvent, TuiCommandAction, TuiCommandContext,
+    PLAYER_STATE_SCHEMA, REPLAY_TRACE_SCHEMA, SCENARIO_MANIFEST_FILE,
 };
 pub(crate) use session_ops::{
     create_engine_for_scenario, emit_boundary_with_saved_state, load_engine_from_state_for_ref,
@@ -43,7 +46,9 @@ pub(crate) use session_ops::{
     save_engine_state, RandConfig,
 };
 pub(crate) use source_loader::{load_source_by_ref, load_source_by_scripts_dir};
-pub(crate) use state_store::{load_player_state, save_player_state};
+pub(crate) use state_store::{
+    load_player_state, load_replay_trace, save_player_state, save_replay_trace,
+};
 
 pub fn run_cli_from_args<I, T>(args: I) -> i32
 where
@@ -64,9 +69,10 @@ where
             return code;
         }
     };
+    let quiet = matches!(&cli.command, Mode::Agent(args) if args.quiet);
     match run(cli) {
         Ok(code) => code,
-        Err(error) => emit_error(error),
+        Err(error) => emit_error(error, quiet),
     }
 }
 
@@ -135,12 +141,24 @@ fn print_full_help() {
         println!();
         println!("{}", render_help(compile.clone()));
     }
+
+    let list = root
+        .get_subcommands()
+        .find(|cmd| cmd.get_name() == "list")
+        .cloned();
+    if let Some(list) = list {
+        println!();
+        println!("---");
+        println!();
+        println!("{}", render_help(list.clone()));
+    }
 }
 
 fn run(cli: Cli) -> Result<i32, ScriptLangError> {
     match cli.command {
         Mode::Agent(args) => run_agent(args),
         Mode::Compile(args) => run_compile(args),
+        Mode::List(args) => agent::run_list(args),
         Mode::Tui(args) => run_tui(args),
     }
 }
@@ -154,15 +172,14 @@ fn run_compile(args: CompileArgs) -> Result<i32, ScriptLangError> {
 }
 
 fn run_tui(args: TuiArgs) -> Result<i32, ScriptLangError> {
-    let entry_script = args.entry_script.unwrap_or("main.main".to_string());
     let state_file = args
         .state_file
         .unwrap_or(".scriptlang/save.json".to_string());
     let random_sequence = parse_rand_sequence(args.rand.as_deref())?;
-    let scenario = load_source_by_scripts_dir(&args.scripts_dir, &entry_script)?;
+    let scenario = load_source_by_scripts_dir(&args.scripts_dir, args.entry_script.as_deref())?;
     let mut engine = create_engine_for_scenario(
         &scenario,
-        &entry_script,
+        &scenario.entry_script,
         RandConfig {
             sequence: random_sequence.clone(),
             sequence_index: Some(0),
@@ -173,7 +190,7 @@ fn run_tui(args: TuiArgs) -> Result<i32, ScriptLangError> {
     tui::run_tui_ratatui_mode(
         &state_file,
         &scenario,
-        &entry_script,
+        &scenario.entry_script,
         random_sequence,
         args.show_debug,
         &mut engine,
@@ -282,6 +299,7 @@ mod lib_tests {
                     rand: None,
                     show_debug: false,
                 }),
+                quiet: false,
             }),
         })
         .expect("agent start should pass");
@@ -291,10 +309,12 @@ mod lib_tests {
             command: AgentCommand::Choose(ChooseArgs {
                 state_in: start_state.to_string_lossy().to_string(),
                 choice: 0,
+                action_id: None,
                 state_out: choose_state.to_string_lossy().to_string(),
                 rand: None,
                 show_debug: false,
             }),
+            quiet: false,
         })
         .expect("agent choose should pass");
         assert_eq!(choose_code, 0);
@@ -312,6 +332,7 @@ mod lib_tests {
         let input_code = agent::run_input(InputArgs {
             state_in: input_state_1.to_string_lossy().to_string(),
             text: "Guild".to_string(),
+            action_id: None,
             state_out: input_state_2.to_string_lossy().to_string(),
             rand: None,
             show_debug: false,
@@ -324,9 +345,12 @@ mod lib_tests {
                 scripts_dir: input_scenario.clone(),
                 entry_script: Some("main.main".to_string()),
                 step: vec!["input:Guild".to_string()],
+                trace: None,
+                trace_out: None,
                 rand: None,
                 show_debug: false,
             }),
+            quiet: false,
         })
         .expect("agent replay should pass");
         assert_eq!(replay_code, 0);
@@ -342,7 +366,7 @@ mod lib_tests {
         assert_eq!(tui_code, 0);
 
         let loaded =
-            load_source_by_scripts_dir(&choice_scenario, "main.main").expect("load source");
+            load_source_by_scripts_dir(&choice_scenario, Some("main.main")).expect("load source");
         let mut engine = create_engine_from_xml(CreateEngineFromXmlOptions {
             scripts_xml: loaded.scripts_xml.clone(),
             entry_script: Some("main.main".to_string()),
@@ -352,6 +376,8 @@ mod lib_tests {
             random_sequence: None,
             random_sequence_index: None,
             compiler_version: Some(DEFAULT_COMPILER_VERSION.to_string()),
+            compile_options: CompileOptions::default(),
+            tuning: EngineTuning::default(),
         })
         .expect("engine should build");
         let _ = run_to_boundary(&mut engine, false).expect("boundary");
@@ -470,6 +496,42 @@ mod lib_tests {
         assert_eq!(compile_ok, 0);
     }
 
+    #[test]
+    fn run_cli_from_args_maps_error_families_to_exit_codes() {
+        let broken_dir = temp_path("run-cli-broken-scripts");
+        write_file(&broken_dir.join("main.xml"), "<module name=\"main\">");
+        let state_out = temp_path("run-cli-broken-state.json");
+        let compile_error = run_cli_from_args([
+            "sl-cli",
+            "agent",
+            "start",
+            "--scripts-dir",
+            broken_dir.to_string_lossy().as_ref(),
+            "--state-out",
+            state_out.to_string_lossy().as_ref(),
+        ]);
+        assert_eq!(compile_error, error_map::EXIT_CODE_COMPILE);
+
+        let missing_state = temp_path("run-cli-missing-state.json");
+        for quiet in [false, true] {
+            let mut argv = vec![
+                "sl-cli".to_string(),
+                "agent".to_string(),
+                "choose".to_string(),
+                "--state-in".to_string(),
+                missing_state.to_string_lossy().to_string(),
+                "--choice".to_string(),
+                "0".to_string(),
+                "--state-out".to_string(),
+                state_out.to_string_lossy().to_string(),
+            ];
+            if quiet {
+                argv.push("--quiet".to_string());
+            }
+            assert_eq!(run_cli_from_args(argv), error_map::EXIT_CODE_IO);
+        }
+    }
+
     #[test]
     fn run_cli_from_args_returns_zero_on_top_level_help() {
         let code = run_cli_from_args(["sl-cli", "--help"]);
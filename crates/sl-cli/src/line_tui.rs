@@ -2,7 +2,7 @@ use std::io::{self, BufRead, Write};
 use std::path::Path;
 
 use sl_api::DEFAULT_COMPILER_VERSION;
-use sl_api::{EngineOutput, ScriptLangError};
+use sl_api::{EngineOutput, ErrorCode, ScriptLangError};
 
 use crate::{
     create_engine_for_scenario, load_engine_from_state_for_ref, map_tui_io, save_engine_state,
@@ -47,9 +47,12 @@ pub(crate) fn run_tui_line_mode_with_io(
 
     loop {
         match engine.next_output()? {
-            EngineOutput::Text { text, tag } => {
+            EngineOutput::Text { text, tag, speaker } => {
                 println!();
-                println!("{}", text);
+                match speaker {
+                    Some(speaker) => println!("{}: {}", speaker, text),
+                    None => println!("{}", text),
+                }
                 if let Some(tag) = tag {
                     println!("[tag: {}]", tag);
                 }
@@ -60,13 +63,18 @@ pub(crate) fn run_tui_line_mode_with_io(
                     println!("[debug] {}", text);
                 }
             }
-            EngineOutput::Choices { items, prompt_text } => {
+            // `:save` snapshots at the next boundary; script checkpoints are not persisted.
+            EngineOutput::Checkpoint { .. } => {}
+            EngineOutput::Choices {
+                items, prompt_text, ..
+            } => {
                 println!();
                 if let Some(prompt_text) = prompt_text {
                     println!("{}", prompt_text);
                 }
                 for item in &items {
-                    println!("  [{}] {}", item.index, item.text);
+                    let disabled = if item.enabled { "" } else { " (disabled)" };
+                    println!("  [{}] {}{}", item.index, item.text, disabled);
                 }
                 loop {
                     let raw = prompt_input_from("> ", reader, writer)?;
@@ -80,7 +88,7 @@ pub(crate) fn run_tui_line_mode_with_io(
                     }
                     let choice = raw.parse::<usize>().map_err(|_| {
                         ScriptLangError::new(
-                            "TUI_CHOICE_PARSE",
+                            ErrorCode::TuiChoiceParse,
                             format!("Invalid choice index: {}", raw),
                         )
                     })?;
@@ -212,7 +220,8 @@ mod line_tui_tests {
         let scripts_dir = temp_path(name);
         write_file(&scripts_dir.join("main.xml"), script_xml);
         let scripts_dir_str = scripts_dir.to_string_lossy().to_string();
-        load_source_by_scripts_dir(&scripts_dir_str, "main.main").expect("scenario should load")
+        load_source_by_scripts_dir(&scripts_dir_str, Some("main.main"))
+            .expect("scenario should load")
     }
 
     fn create_engine_for_tests(scenario: &LoadedScenario) -> sl_api::ScriptLangEngine {
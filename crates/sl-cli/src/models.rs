@@ -12,10 +12,22 @@ pub(crate) enum PlayerRandomMode {
     Sequence,
 }
 
+pub(crate) const SCENARIO_MANIFEST_FILE: &str = "scenario.json";
+pub(crate) const REPLAY_TRACE_SCHEMA: &str = "replay-trace";
+
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub(crate) struct ScenarioManifest {
+    pub(crate) entry: Option<String>,
+    pub(crate) title: Option<String>,
+    pub(crate) version: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct LoadedScenario {
     pub(crate) id: String,
     pub(crate) title: String,
+    pub(crate) version: Option<String>,
     pub(crate) scripts_xml: BTreeMap<String, String>,
     pub(crate) entry_script: String,
 }
@@ -32,6 +44,20 @@ pub(crate) struct PlayerState {
     #[serde(default)]
     pub(crate) random_sequence: Vec<u32>,
     pub(crate) random_sequence_index: Option<usize>,
+    /// `--action-id` of the last applied `choose`/`input`, used to ignore retried commands.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) last_action_id: Option<String>,
+}
+
+/// Steps and transcript lines recorded by `agent replay --trace-out`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReplayTrace {
+    pub(crate) schema_version: String,
+    pub(crate) steps: Vec<String>,
+    #[serde(default)]
+    pub(crate) random_sequence: Option<Vec<u32>>,
+    pub(crate) lines: Vec<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -45,6 +71,7 @@ pub(crate) enum BoundaryEvent {
 pub(crate) struct TextEvent {
     pub(crate) text: String,
     pub(crate) tag: Option<String>,
+    pub(crate) speaker: Option<String>,
 }
 
 #[derive(Debug, Clone)]
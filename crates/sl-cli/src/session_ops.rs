@@ -1,11 +1,12 @@
 use std::path::Path;
 
+use sl_api::ErrorCode;
 use sl_api::RandomStateView;
 use sl_api::ScriptLangError;
 use sl_api::DEFAULT_COMPILER_VERSION;
 use sl_api::{
-    create_engine_from_xml, resume_engine_from_xml, CreateEngineFromXmlOptions,
-    ResumeEngineFromXmlOptions,
+    create_engine_from_xml, resume_engine_from_xml, CompileOptions, CreateEngineFromXmlOptions,
+    EngineTuning, ResumeEngineFromXmlOptions,
 };
 
 use crate::{
@@ -26,7 +27,7 @@ pub(crate) fn parse_rand_sequence(raw: Option<&str>) -> Result<Option<Vec<u32>>,
     };
     if raw.trim().is_empty() {
         return Err(ScriptLangError::new(
-            "CLI_RAND_INVALID",
+            ErrorCode::CliRandInvalid,
             "Random sequence cannot be empty.",
         ));
     }
@@ -36,13 +37,13 @@ pub(crate) fn parse_rand_sequence(raw: Option<&str>) -> Result<Option<Vec<u32>>,
         let item = part.trim();
         if item.is_empty() {
             return Err(ScriptLangError::new(
-                "CLI_RAND_INVALID",
+                ErrorCode::CliRandInvalid,
                 format!("Invalid --rand item in \"{}\".", raw),
             ));
         }
         let value = item.parse::<u32>().map_err(|_| {
             ScriptLangError::new(
-                "CLI_RAND_INVALID",
+                ErrorCode::CliRandInvalid,
                 format!("Invalid --rand item \"{}\" in \"{}\".", item, raw),
             )
         })?;
@@ -65,6 +66,8 @@ pub(crate) fn create_engine_for_scenario(
         random_sequence: rand.sequence,
         random_sequence_index: rand.sequence_index,
         compiler_version: Some(DEFAULT_COMPILER_VERSION.to_string()),
+        compile_options: CompileOptions::default(),
+        tuning: EngineTuning::default(),
     })
 }
 
@@ -90,6 +93,8 @@ pub(crate) fn resume_engine_for_state(
         random_sequence,
         random_sequence_index,
         compiler_version: Some(state.compiler_version.clone()),
+        compile_options: CompileOptions::default(),
+        tuning: EngineTuning::default(),
     })
 }
 
@@ -98,6 +103,16 @@ pub(crate) fn save_engine_state(
     engine: &sl_api::ScriptLangEngine,
     scenario_id: &str,
     compiler_version: &str,
+) -> Result<(), ScriptLangError> {
+    save_engine_state_with_action(path, engine, scenario_id, compiler_version, None)
+}
+
+pub(crate) fn save_engine_state_with_action(
+    path: &Path,
+    engine: &sl_api::ScriptLangEngine,
+    scenario_id: &str,
+    compiler_version: &str,
+    last_action_id: Option<&str>,
 ) -> Result<(), ScriptLangError> {
     let snapshot = engine.snapshot()?;
     let (random_mode, random_seed_state, random_sequence, random_sequence_index) =
@@ -119,6 +134,7 @@ pub(crate) fn save_engine_state(
         random_seed_state,
         random_sequence,
         random_sequence_index,
+        last_action_id: last_action_id.map(str::to_string),
     };
     save_player_state(path, &state)
 }
@@ -139,7 +155,7 @@ pub(crate) fn load_engine_from_state_for_scenario(
     let state = load_player_state(path)?;
     if state.scenario_id != scenario.id {
         return Err(ScriptLangError::new(
-            "TUI_STATE_SCENARIO_MISMATCH",
+            ErrorCode::TuiStateScenarioMismatch,
             format!(
                 "State scenario mismatch. expected={} actual={}",
                 scenario.id, state.scenario_id
@@ -156,12 +172,19 @@ pub(crate) fn emit_boundary_with_saved_state(
     state_out: &str,
     scenario_id: &str,
     compiler_version: &str,
+    last_action_id: Option<&str>,
 ) -> Result<i32, ScriptLangError> {
     if matches!(
         boundary.event,
         BoundaryEvent::Choices | BoundaryEvent::Input
     ) {
-        save_engine_state(Path::new(state_out), engine, scenario_id, compiler_version)?;
+        save_engine_state_with_action(
+            Path::new(state_out),
+            engine,
+            scenario_id,
+            compiler_version,
+            last_action_id,
+        )?;
         emit_boundary(boundary, Some(state_out.to_string()));
         return Ok(0);
     }
@@ -179,7 +202,7 @@ mod session_ops_tests {
     #[test]
     fn session_helpers_cover_create_save_load_and_emit_paths() {
         let scripts_dir = example_scripts_dir("06-snapshot-flow");
-        let scenario = crate::load_source_by_scripts_dir(&scripts_dir, "main.main")
+        let scenario = crate::load_source_by_scripts_dir(&scripts_dir, Some("main.main"))
             .expect("scenario should load");
 
         let mut engine = create_engine_for_scenario(&scenario, "main.main", RandConfig::default())
@@ -213,6 +236,7 @@ mod session_ops_tests {
             state_file.to_string_lossy().as_ref(),
             &scenario.id,
             DEFAULT_COMPILER_VERSION,
+            None,
         )
         .expect("emit with save should pass");
         assert_eq!(emit_code, 0);
@@ -222,10 +246,10 @@ mod session_ops_tests {
     fn load_engine_from_state_for_scenario_rejects_mismatch() {
         let scenario_path = example_scripts_dir("06-snapshot-flow");
         let other_path = example_scripts_dir("16-input-name");
-        let scenario =
-            crate::load_source_by_scripts_dir(&scenario_path, "main.main").expect("scenario load");
+        let scenario = crate::load_source_by_scripts_dir(&scenario_path, Some("main.main"))
+            .expect("scenario load");
         let other =
-            crate::load_source_by_scripts_dir(&other_path, "main.main").expect("other load");
+            crate::load_source_by_scripts_dir(&other_path, Some("main.main")).expect("other load");
 
         let mut engine = create_engine_for_scenario(&other, "main.main", RandConfig::default())
             .expect("engine build");
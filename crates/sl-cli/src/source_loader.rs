@@ -2,45 +2,81 @@ use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use sl_api::ErrorCode;
 use sl_api::ScriptLangError;
 use walkdir::WalkDir;
 
-use crate::{map_cli_source_path, map_cli_source_read, map_cli_source_scan, LoadedScenario};
+use crate::{
+    map_cli_manifest_invalid, map_cli_source_path, map_cli_source_read, map_cli_source_scan,
+    LoadedScenario, ScenarioManifest, SCENARIO_MANIFEST_FILE,
+};
 
 pub(crate) fn load_source_by_scripts_dir(
     scripts_dir: &str,
-    entry_script: &str,
+    entry_script: Option<&str>,
 ) -> Result<LoadedScenario, ScriptLangError> {
     let scripts_root = resolve_scripts_dir(scripts_dir)?;
     let scripts_xml = read_scripts_xml_from_dir(&scripts_root)?;
+    let manifest = read_scenario_manifest(&scripts_root)?.unwrap_or_default();
     let scenario_id = make_scripts_dir_scenario_id(&scripts_root);
-    let title = format!(
-        "Scripts {}",
-        scripts_root
-            .file_name()
-            .and_then(|name| name.to_str())
-            .unwrap_or("unknown")
-    );
+    let title = manifest.title.unwrap_or_else(|| {
+        format!(
+            "Scripts {}",
+            scripts_root
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("unknown")
+        )
+    });
+    let entry_script = entry_script
+        .map(str::to_string)
+        .or(manifest.entry)
+        .unwrap_or_else(|| "main.main".to_string());
 
     Ok(LoadedScenario {
         id: scenario_id,
         title,
+        version: manifest.version,
         scripts_xml,
-        entry_script: entry_script.to_string(),
+        entry_script,
     })
 }
 
+pub(crate) fn read_scenario_manifest(
+    scripts_dir: &Path,
+) -> Result<Option<ScenarioManifest>, ScriptLangError> {
+    let path = scripts_dir.join(SCENARIO_MANIFEST_FILE);
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    let raw = fs::read_to_string(&path).map_err(map_cli_source_read)?;
+    let manifest: ScenarioManifest =
+        serde_json::from_str(&raw).map_err(map_cli_manifest_invalid)?;
+    if manifest
+        .entry
+        .as_deref()
+        .is_some_and(|entry| entry.trim().is_empty())
+    {
+        return Err(ScriptLangError::new(
+            ErrorCode::CliManifestInvalid,
+            format!("{} entry must not be empty.", path.display()),
+        ));
+    }
+    Ok(Some(manifest))
+}
+
 pub(crate) fn load_source_by_ref(scenario_ref: &str) -> Result<LoadedScenario, ScriptLangError> {
     let prefix = "scripts-dir:";
     if !scenario_ref.starts_with(prefix) {
         return Err(ScriptLangError::new(
-            "CLI_SOURCE_REF_INVALID",
+            ErrorCode::CliSourceRefInvalid,
             format!("Unsupported scenario ref: {}", scenario_ref),
         ));
     }
 
     let raw = scenario_ref.trim_start_matches(prefix);
-    load_source_by_scripts_dir(raw, "main.main")
+    load_source_by_scripts_dir(raw, None)
 }
 
 pub(crate) fn resolve_scripts_dir(scripts_dir: &str) -> Result<PathBuf, ScriptLangError> {
@@ -55,14 +91,14 @@ pub(crate) fn resolve_scripts_dir(scripts_dir: &str) -> Result<PathBuf, ScriptLa
 
     if !absolute.exists() {
         return Err(ScriptLangError::new(
-            "CLI_SOURCE_NOT_FOUND",
+            ErrorCode::CliSourceNotFound,
             format!("scripts-dir does not exist: {}", absolute.display()),
         ));
     }
 
     if !absolute.is_dir() {
         return Err(ScriptLangError::new(
-            "CLI_SOURCE_NOT_DIR",
+            ErrorCode::CliSourceNotDir,
             format!("scripts-dir is not a directory: {}", absolute.display()),
         ));
     }
@@ -103,7 +139,7 @@ pub(crate) fn read_scripts_xml_from_dir(
 
     if scripts.is_empty() {
         return Err(ScriptLangError::new(
-            "CLI_SOURCE_EMPTY",
+            ErrorCode::CliSourceEmpty,
             format!("No .xml files under {}", scripts_dir.display()),
         ));
     }
@@ -195,7 +231,7 @@ mod source_loader_tests {
             r#"<module name="main" export="script:main"><script name="main"><text>Hello</text></script></module>"#,
         );
 
-        let loaded = load_source_by_scripts_dir(&root.to_string_lossy(), "main.main")
+        let loaded = load_source_by_scripts_dir(&root.to_string_lossy(), Some("main.main"))
             .expect("load should pass");
         assert!(loaded.id.starts_with("scripts-dir:"));
         assert_eq!(loaded.entry_script, "main.main");
@@ -211,11 +247,85 @@ mod source_loader_tests {
             r#"<module name="game" export="script:game"><script name="game"><text>Game</text></script></module>"#,
         );
 
-        let loaded = load_source_by_scripts_dir(&root.to_string_lossy(), "game.game")
+        let loaded = load_source_by_scripts_dir(&root.to_string_lossy(), Some("game.game"))
             .expect("load should pass");
         assert_eq!(loaded.entry_script, "game.game");
     }
 
+    #[test]
+    fn read_scenario_manifest_parses_optional_fields() {
+        let root = temp_path("scenario-manifest-parse");
+        fs::create_dir_all(&root).expect("root");
+        assert_eq!(read_scenario_manifest(&root).expect("no manifest"), None);
+
+        write_file(
+            &root.join("scenario.json"),
+            r#"{"entry":"game.game","title":"Demo","version":"1.2.0"}"#,
+        );
+        let manifest = read_scenario_manifest(&root)
+            .expect("manifest should parse")
+            .expect("manifest should exist");
+        assert_eq!(
+            manifest,
+            ScenarioManifest {
+                entry: Some("game.game".to_string()),
+                title: Some("Demo".to_string()),
+                version: Some("1.2.0".to_string()),
+            }
+        );
+
+        write_file(
+            &root.join("scenario.json"),
+            r#"{"entry":"game.game","extra":1}"#,
+        );
+        let error = read_scenario_manifest(&root).expect_err("unknown field should fail");
+        assert_eq!(error.code, "CLI_MANIFEST_INVALID");
+
+        write_file(&root.join("scenario.json"), r#"{"entry":"  "}"#);
+        let error = read_scenario_manifest(&root).expect_err("empty entry should fail");
+        assert_eq!(error.code, "CLI_MANIFEST_INVALID");
+    }
+
+    #[test]
+    fn load_source_by_scripts_dir_resolves_entry_from_manifest() {
+        let root = temp_path("scenario-manifest-entry");
+        fs::create_dir_all(&root).expect("root");
+        write_file(
+            &root.join("game.xml"),
+            r#"<module name="game" export="script:game"><script name="game"><text>Game</text></script></module>"#,
+        );
+        write_file(
+            &root.join("scenario.json"),
+            r#"{"entry":"game.game","title":"Demo","version":"1.2.0"}"#,
+        );
+
+        let loaded =
+            load_source_by_scripts_dir(&root.to_string_lossy(), None).expect("load should pass");
+        assert_eq!(loaded.entry_script, "game.game");
+        assert_eq!(loaded.title, "Demo");
+        assert_eq!(loaded.version.as_deref(), Some("1.2.0"));
+        assert!(!loaded.scripts_xml.contains_key("scenario.json"));
+
+        let overridden = load_source_by_scripts_dir(&root.to_string_lossy(), Some("other.main"))
+            .expect("load should pass");
+        assert_eq!(overridden.entry_script, "other.main");
+
+        let by_ref = load_source_by_ref(&loaded.id).expect("load by ref should pass");
+        assert_eq!(by_ref.entry_script, "game.game");
+
+        let plain = temp_path("scenario-manifest-missing");
+        fs::create_dir_all(&plain).expect("plain root");
+        write_file(
+            &plain.join("main.xml"),
+            r#"<module name="main" export="script:main"><script name="main"><text>Hello</text></script></module>"#,
+        );
+        let loaded =
+            load_source_by_scripts_dir(&plain.to_string_lossy(), None).expect("load should pass");
+        assert_eq!(loaded.entry_script, "main.main");
+        assert_eq!(loaded.version, None);
+        assert!(loaded.title.starts_with("Scripts "));
+    }
+
     #[test]
     fn load_source_by_ref_validates_prefix() {
         let error = load_source_by_ref("invalid").expect_err("no prefix should fail");
@@ -1,11 +1,13 @@
 use std::fs;
 use std::path::Path;
 
+use sl_api::ErrorCode;
 use sl_api::ScriptLangError;
 
 use crate::{
-    map_cli_state_invalid, map_cli_state_read, map_cli_state_write, PlayerState,
-    PLAYER_STATE_SCHEMA,
+    map_cli_state_invalid, map_cli_state_read, map_cli_state_write, map_cli_trace_invalid,
+    map_cli_trace_read, map_cli_trace_write, PlayerState, ReplayTrace, PLAYER_STATE_SCHEMA,
+    REPLAY_TRACE_SCHEMA,
 };
 
 pub(crate) fn save_player_state(path: &Path, state: &PlayerState) -> Result<(), ScriptLangError> {
@@ -22,7 +24,7 @@ pub(crate) fn save_player_state(path: &Path, state: &PlayerState) -> Result<(),
 pub(crate) fn load_player_state(path: &Path) -> Result<PlayerState, ScriptLangError> {
     if !path.exists() {
         return Err(ScriptLangError::new(
-            "CLI_STATE_NOT_FOUND",
+            ErrorCode::CliStateNotFound,
             format!("State file does not exist: {}", path.display()),
         ));
     }
@@ -33,7 +35,9 @@ pub(crate) fn load_player_state(path: &Path) -> Result<PlayerState, ScriptLangEr
     let schema_version = value
         .get("schemaVersion")
         .and_then(|v| v.as_str())
-        .ok_or_else(|| ScriptLangError::new("CLI_STATE_SCHEMA", "Missing state schemaVersion."))?;
+        .ok_or_else(|| {
+            ScriptLangError::new(ErrorCode::CliStateSchema, "Missing state schemaVersion.")
+        })?;
 
     if schema_version == PLAYER_STATE_SCHEMA {
         let state: PlayerState = serde_json::from_value(value).map_err(map_cli_state_invalid)?;
@@ -41,11 +45,41 @@ pub(crate) fn load_player_state(path: &Path) -> Result<PlayerState, ScriptLangEr
     }
 
     Err(ScriptLangError::new(
-        "CLI_STATE_SCHEMA",
+        ErrorCode::CliStateSchema,
         format!("Unsupported player state schema: {}", schema_version),
     ))
 }
 
+pub(crate) fn save_replay_trace(path: &Path, trace: &ReplayTrace) -> Result<(), ScriptLangError> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    fs::create_dir_all(parent).map_err(map_cli_trace_write)?;
+
+    let payload = serde_json::to_string_pretty(trace).map_err(map_cli_trace_invalid)?;
+    fs::write(path, payload).map_err(map_cli_trace_write)
+}
+
+pub(crate) fn load_replay_trace(path: &Path) -> Result<ReplayTrace, ScriptLangError> {
+    if !path.exists() {
+        return Err(ScriptLangError::new(
+            ErrorCode::CliTraceNotFound,
+            format!("Trace file does not exist: {}", path.display()),
+        ));
+    }
+
+    let raw = fs::read_to_string(path).map_err(map_cli_trace_read)?;
+    let trace: ReplayTrace = serde_json::from_str(&raw).map_err(map_cli_trace_invalid)?;
+    if trace.schema_version != REPLAY_TRACE_SCHEMA {
+        return Err(ScriptLangError::new(
+            ErrorCode::CliTraceSchema,
+            format!("Unsupported replay trace schema: {}", trace.schema_version),
+        ));
+    }
+    Ok(trace)
+}
+
 #[cfg(test)]
 mod state_store_tests {
     use super::*;
@@ -77,10 +111,20 @@ mod state_store_tests {
                 },
                 module_vars: BTreeMap::new(),
                 once_state_by_script: BTreeMap::new(),
+                global_once_state: Vec::new(),
+                select_count_by_script: BTreeMap::new(),
+                visit_counts: BTreeMap::new(),
+                coverage: None,
+                clock: None,
+                entry_script: String::new(),
+                entry_args: BTreeMap::new(),
+                node_hash: None,
+                random_override_index: None,
             },
             random_mode: PlayerRandomMode::Seeded,
             random_seed_state: Some(1),
             random_sequence: Vec::new(),
+            last_action_id: None,
             random_sequence_index: None,
         };
         save_player_state(&state_path, &state).expect("save should pass");
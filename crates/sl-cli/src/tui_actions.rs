@@ -1,6 +1,7 @@
 use std::path::Path;
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use sl_api::ErrorCode;
 use sl_api::ScriptLangError;
 use sl_api::DEFAULT_COMPILER_VERSION;
 
@@ -143,22 +144,20 @@ pub(crate) fn handle_key(
                 ui.status = "no pending choice".to_string();
                 return Ok(false);
             }
-            let selected = ui
-                .choices
-                .get(ui.selected_choice_index)
-                .ok_or_else(|| ScriptLangError::new("TUI_CHOICE_PARSE", "No choices available"))?;
+            let selected = ui.choices.get(ui.selected_choice_index).ok_or_else(|| {
+                ScriptLangError::new(ErrorCode::TuiChoiceParse, "No choices available")
+            })?;
             let boundary = choose_current(engine, selected.index, context.show_debug)?;
             ui.append_boundary(boundary);
             ui.status = format!("chose {}", ui.selected_choice_index);
             return Ok(false);
         }
-        KeyCode::Char(ch) => {
+        KeyCode::Char(ch)
             if input_pending
                 && !key.modifiers.contains(KeyModifiers::CONTROL)
-                && !key.modifiers.contains(KeyModifiers::ALT)
-            {
-                ui.input_buffer.push(ch);
-            }
+                && !key.modifiers.contains(KeyModifiers::ALT) =>
+        {
+            ui.input_buffer.push(ch);
         }
         _ => {}
     }
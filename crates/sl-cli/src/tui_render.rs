@@ -128,10 +128,11 @@ pub(crate) fn render_tui(
         " ".to_string()
     };
 
-    let header_text = truncate_to_width(
-        format!("{} | {}", scenario.id, scenario.title).as_str(),
-        content_width,
-    );
+    let header_title = match &scenario.version {
+        Some(version) => format!("{} | {} ({})", scenario.id, scenario.title, version),
+        None => format!("{} | {}", scenario.id, scenario.title),
+    };
+    let header_text = truncate_to_width(header_title.as_str(), content_width);
     let state_text = truncate_to_width(format!("state: {}", state_file).as_str(), content_width);
     let status_text = truncate_to_width(format!("status: {}", ui.status).as_str(), content_width);
     let divider_line = "─".repeat(content_width);
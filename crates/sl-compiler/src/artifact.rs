@@ -8,6 +8,14 @@ pub const DEFAULT_COMPILER_VERSION: &str = "player";
 pub fn compile_artifact_from_xml_map(
     xml_by_path: &BTreeMap<String, String>,
     entry_script: Option<String>,
+) -> Result<CompiledProjectArtifact, ScriptLangError> {
+    compile_artifact_from_xml_map_with_options(xml_by_path, entry_script, CompileOptions::default())
+}
+
+pub fn compile_artifact_from_xml_map_with_options(
+    xml_by_path: &BTreeMap<String, String>,
+    entry_script: Option<String>,
+    options: CompileOptions,
 ) -> Result<CompiledProjectArtifact, ScriptLangError> {
     let CompileProjectBundleResult {
         scripts,
@@ -16,13 +24,19 @@ pub fn compile_artifact_from_xml_map(
         module_var_init_order,
         module_const_declarations,
         module_const_init_order,
-    } = compile_project_bundle_from_xml_map(xml_by_path)?;
+        bundle_fingerprint,
+    } = compile_project_bundle_from_xml_map_with_options(xml_by_path, options)?;
 
     let entry_script = resolve_entry_script(&scripts, entry_script)?;
+    // Snapshots record node positions, so an optimized layout gets its own compiler version.
+    let compiler_version = match bundle_fingerprint {
+        Some(fingerprint) => format!("{}+{}", DEFAULT_COMPILER_VERSION, fingerprint),
+        None => DEFAULT_COMPILER_VERSION.to_string(),
+    };
 
     Ok(CompiledProjectArtifact {
         schema_version: COMPILED_PROJECT_SCHEMA.to_string(),
-        compiler_version: DEFAULT_COMPILER_VERSION.to_string(),
+        compiler_version,
         entry_script,
         scripts,
         global_data,
@@ -39,7 +53,7 @@ pub fn write_artifact_json(
 ) -> Result<(), ScriptLangError> {
     if artifact.schema_version != COMPILED_PROJECT_SCHEMA {
         return Err(ScriptLangError::new(
-            "ARTIFACT_SCHEMA_UNSUPPORTED",
+            ErrorCode::ArtifactSchemaUnsupported,
             format!(
                 "Unsupported compiled artifact schema \"{}\", expected \"{}\".",
                 artifact.schema_version, COMPILED_PROJECT_SCHEMA
@@ -50,18 +64,18 @@ pub fn write_artifact_json(
     let encoded =
         serde_json::to_string_pretty(artifact).expect("compiled artifact should serialize");
     fs::write(path, encoded)
-        .map_err(|error| ScriptLangError::new("ARTIFACT_IO_ERROR", error.to_string()))
+        .map_err(|error| ScriptLangError::new(ErrorCode::ArtifactIoError, error.to_string()))
 }
 
 pub fn read_artifact_json(path: &Path) -> Result<CompiledProjectArtifact, ScriptLangError> {
     let raw = fs::read_to_string(path)
-        .map_err(|error| ScriptLangError::new("ARTIFACT_IO_ERROR", error.to_string()))?;
+        .map_err(|error| ScriptLangError::new(ErrorCode::ArtifactIoError, error.to_string()))?;
     let artifact: CompiledProjectArtifact = serde_json::from_str(&raw)
-        .map_err(|error| ScriptLangError::new("ARTIFACT_PARSE_ERROR", error.to_string()))?;
+        .map_err(|error| ScriptLangError::new(ErrorCode::ArtifactParseError, error.to_string()))?;
 
     if artifact.schema_version != COMPILED_PROJECT_SCHEMA {
         return Err(ScriptLangError::new(
-            "ARTIFACT_SCHEMA_UNSUPPORTED",
+            ErrorCode::ArtifactSchemaUnsupported,
             format!(
                 "Unsupported compiled artifact schema \"{}\", expected \"{}\".",
                 artifact.schema_version, COMPILED_PROJECT_SCHEMA
@@ -79,7 +93,7 @@ fn resolve_entry_script(
     if let Some(entry) = explicit {
         let Some(script) = scripts.get(&entry) else {
             return Err(ScriptLangError::new(
-                "ARTIFACT_ENTRY_SCRIPT_NOT_FOUND",
+                ErrorCode::ArtifactEntryScriptNotFound,
                 format!("Entry script \"{}\" is not registered.", entry),
             ));
         };
@@ -97,7 +111,7 @@ fn resolve_entry_script(
     }
 
     Err(ScriptLangError::new(
-        "ARTIFACT_ENTRY_MAIN_NOT_FOUND",
+        ErrorCode::ArtifactEntryMainNotFound,
         "Expected script with name=\"main.main\" as default entry.",
     ))
 }
@@ -107,7 +121,7 @@ fn validate_entry_script_access(script: &ScriptIr, entry: &str) -> Result<(), Sc
         return Ok(());
     }
     Err(ScriptLangError::new(
-        "ARTIFACT_ENTRY_SCRIPT_PRIVATE",
+        ErrorCode::ArtifactEntryScriptPrivate,
         format!(
             "Entry script \"{}\" is private and cannot be started by host.",
             entry
@@ -145,6 +159,30 @@ mod artifact_tests {
         assert!(artifact.scripts.contains_key("main.main"));
     }
 
+    #[test]
+    fn compile_artifact_with_inlining_tags_compiler_version_with_fingerprint() {
+        let files = compiler_test_support::map(&[(
+            "main.xml",
+            r#"<module name="main" export="script:main">
+<script name="tiny" kind="call"><text>Hi</text></script>
+<script name="main"><call script="@tiny"/></script>
+</module>"#,
+        )]);
+
+        let artifact = compile_artifact_from_xml_map_with_options(
+            &files,
+            None,
+            CompileOptions {
+                inline_small_calls: true,
+                ..CompileOptions::default()
+            },
+        )
+        .expect("compile artifact");
+        assert!(artifact
+            .compiler_version
+            .starts_with(&format!("{}+inline-", DEFAULT_COMPILER_VERSION)));
+    }
+
     #[test]
     fn compile_artifact_from_xml_map_validates_entry_script() {
         let files = compiler_test_support::map(&[(
@@ -0,0 +1,245 @@
+use crate::*;
+
+/// Upper bound on Rhai operations spent folding a single expression.
+const CONST_FOLD_MAX_OPERATIONS: u64 = 10_000;
+
+/// Replace provably-pure literal expressions with the literal they evaluate to.
+///
+/// Folds `<if>`/`<while>`/`<option>` conditions, `<temp>` initializers and module
+/// `<var>`/`<const>` initializers. Node layout is untouched (a constant `when` keeps its branch),
+/// so snapshots and node hashes stay valid. Expressions that fail to evaluate are left for the
+/// runtime to report.
+pub(crate) fn fold_constant_expressions(
+    scripts: &mut BTreeMap<String, ScriptIr>,
+    module_var_declarations: &mut BTreeMap<String, ModuleVarDecl>,
+    module_const_declarations: &mut BTreeMap<String, ModuleConstDecl>,
+) {
+    let folder = ConstFolder::new();
+    for script in scripts.values_mut() {
+        for group in script.groups.values_mut() {
+            for node in &mut group.nodes {
+                folder.fold_node(node);
+            }
+        }
+        for decl in script.visible_module_vars.values_mut() {
+            folder.fold_optional(&mut decl.initial_value_expr);
+        }
+        for decl in script.visible_module_consts.values_mut() {
+            folder.fold_optional(&mut decl.initial_value_expr);
+        }
+    }
+    for decl in module_var_declarations.values_mut() {
+        folder.fold_optional(&mut decl.initial_value_expr);
+    }
+    for decl in module_const_declarations.values_mut() {
+        folder.fold_optional(&mut decl.initial_value_expr);
+    }
+}
+
+struct ConstFolder {
+    engine: rhai::Engine,
+}
+
+impl ConstFolder {
+    fn new() -> Self {
+        // A raw engine only has the built-in operators: no packages, functions or variables.
+        let mut engine = rhai::Engine::new_raw();
+        engine.set_max_operations(CONST_FOLD_MAX_OPERATIONS);
+        Self { engine }
+    }
+
+    fn fold_node(&self, node: &mut ScriptNode) {
+        match node {
+            ScriptNode::If { when_expr, .. } | ScriptNode::While { when_expr, .. } => {
+                self.fold(when_expr);
+            }
+            ScriptNode::Var { declaration, .. } => {
+                self.fold_optional(&mut declaration.initial_value_expr);
+            }
+            ScriptNode::Choice { entries, .. } => {
+                for entry in entries {
+                    if let ChoiceEntry::Static { option } = entry {
+                        self.fold_optional(&mut option.when_expr);
+                        self.fold_optional(&mut option.enabled_when_expr);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn fold_optional(&self, expr: &mut Option<String>) {
+        if let Some(expr) = expr {
+            self.fold(expr);
+        }
+    }
+
+    fn fold(&self, expr: &mut String) {
+        if let Some(literal) = self.evaluate(expr) {
+            if literal != expr.trim() {
+                *expr = literal;
+            }
+        }
+    }
+
+    /// Literal text for `expr`, or `None` unless it is pure and yields a bool or number.
+    fn evaluate(&self, expr: &str) -> Option<String> {
+        if !is_pure_literal_expression(expr) {
+            return None;
+        }
+        let value = self.engine.eval_expression::<rhai::Dynamic>(expr).ok()?;
+        let literal = if let Ok(flag) = value.as_bool() {
+            flag.to_string()
+        } else if let Ok(int) = value.as_int() {
+            int.to_string()
+        } else if let Ok(float) = value.as_float() {
+            if !float.is_finite() {
+                return None;
+            }
+            format!("{:?}", float)
+        } else {
+            return None;
+        };
+        // Only keep the literal if Rhai reads it back as the same value.
+        let reparsed = self
+            .engine
+            .eval_expression::<rhai::Dynamic>(&literal)
+            .ok()?;
+        (reparsed.to_string() == value.to_string() && reparsed.type_name() == value.type_name())
+            .then_some(literal)
+    }
+}
+
+/// Whether `expr` references nothing but literals and operators.
+///
+/// Any identifier other than `true`/`false` (variables, globals, functions, keywords) and any
+/// block, map, index, string or interpolation syntax makes the expression impure.
+fn is_pure_literal_expression(expr: &str) -> bool {
+    let mut chars = expr.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch.is_ascii_alphabetic() || ch == '_' {
+            let mut word = String::from(ch);
+            while let Some(next) = chars.peek().copied() {
+                if !(next.is_ascii_alphanumeric() || next == '_') {
+                    break;
+                }
+                word.push(next);
+                chars.next();
+            }
+            if word != "true" && word != "false" {
+                return false;
+            }
+        } else if !(ch.is_ascii_digit() || ch.is_whitespace() || "+-*/%<>=!&|^().".contains(ch)) {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod const_fold_tests {
+    use super::*;
+
+    fn compile(
+        files: &BTreeMap<String, String>,
+        fold_constants: bool,
+    ) -> CompileProjectBundleResult {
+        compile_project_bundle_from_xml_map_with_options(
+            files,
+            CompileOptions {
+                fold_constants,
+                ..CompileOptions::default()
+            },
+        )
+        .expect("compile")
+    }
+
+    fn when_exprs(bundle: &CompileProjectBundleResult) -> Vec<String> {
+        let main = &bundle.scripts["main.main"];
+        let mut exprs = Vec::new();
+        for node in &main.groups[&main.root_group_id].nodes {
+            match node {
+                ScriptNode::If { when_expr, .. } | ScriptNode::While { when_expr, .. } => {
+                    exprs.push(when_expr.clone())
+                }
+                ScriptNode::Var { declaration, .. } => {
+                    exprs.extend(declaration.initial_value_expr.clone())
+                }
+                _ => {}
+            }
+        }
+        exprs
+    }
+
+    #[test]
+    fn folding_rewrites_pure_expressions_and_keeps_node_hashes() {
+        let files = compiler_test_support::map(&[(
+            "main.xml",
+            r#"<module name="main" export="script:main;var:base">
+  <var name="base" type="int">1 + 2 * 3</var>
+  <const name="ratio" type="float">7 / 2.0</const>
+  <script name="main">
+    <temp name="sum" type="int">(1 + 2) * 4 - 10 % 3</temp>
+    <temp name="flag" type="boolean">3 >= 2 AND !(1 == 2)</temp>
+    <if when="2 > 1"><text>then</text><else><text>else</text></else></if>
+    <if when="sum > 1"><text>${sum}</text></if>
+    <while when="1 > 2"><text>never</text></while>
+    <choice text="Pick">
+      <option text="hidden" when="1 == 2"><text>x</text></option>
+      <option text="shown" when="2 LTE 2"><text>${flag} ${base} ${ratio}</text></option>
+    </choice>
+    <end/>
+  </script>
+</module>"#,
+        )]);
+
+        let plain = compile(&files, false);
+        let folded = compile(&files, true);
+        assert_eq!(
+            when_exprs(&folded),
+            vec!["11", "true", "true", "sum > 1", "false"]
+        );
+        assert_eq!(
+            folded.module_var_declarations["main.base"]
+                .initial_value_expr
+                .as_deref(),
+            Some("7")
+        );
+        assert_eq!(
+            folded.module_const_declarations["main.ratio"]
+                .initial_value_expr
+                .as_deref(),
+            Some("3.5")
+        );
+        let main = &folded.scripts["main.main"];
+        assert_eq!(
+            main.groups[&main.root_group_id].node_hashes,
+            plain.scripts["main.main"].groups[&main.root_group_id].node_hashes,
+            "folding must not invalidate snapshots"
+        );
+    }
+
+    #[test]
+    fn impure_or_failing_expressions_are_left_untouched() {
+        let exprs = [
+            "hp + 1",
+            "random(6) > 2",
+            "\"a\" + \"b\"",
+            "[1, 2].len() > 1",
+            "#{a: 1}.a == 1",
+            "1 / 0",
+            "9223372036854775807 + 1",
+            "if true { 1 } else { 2 }",
+        ];
+        let folder = ConstFolder::new();
+        for expr in exprs {
+            let mut folded = expr.to_string();
+            folder.fold(&mut folded);
+            assert_eq!(folded, expr, "{expr} should not fold");
+        }
+
+        let mut literal = "1.0 + 2.0".to_string();
+        folder.fold(&mut literal);
+        assert_eq!(literal, "3.0");
+    }
+}
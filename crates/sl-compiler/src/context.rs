@@ -2,6 +2,8 @@ use crate::*;
 
 pub const INTERNAL_RESERVED_NAME_PREFIX: &str = "__";
 pub(crate) const FOR_FIRST_TEMP_VAR_PREFIX: &str = "__sl_for_";
+pub(crate) const REPEAT_FIRST_TEMP_VAR_PREFIX: &str = "__sl_repeat_";
+pub(crate) const INTERNAL_CODE_ATTR: &str = "__sl_internal";
 
 #[derive(Debug, Clone)]
 pub struct CompileProjectBundleResult {
@@ -11,6 +13,29 @@ pub struct CompileProjectBundleResult {
     pub module_var_init_order: Vec<String>,
     pub module_const_declarations: BTreeMap<String, ModuleConstDecl>,
     pub module_const_init_order: Vec<String>,
+    /// Set when an IR optimization changed node layout; snapshots from other layouts must not resume.
+    pub bundle_fingerprint: Option<String>,
+}
+
+impl CompileProjectBundleResult {
+    /// Names of the scripts declaring `tag` in their `tags` attribute, in name order.
+    pub fn scripts_with_tag(&self, tag: &str) -> Vec<&str> {
+        self.scripts
+            .iter()
+            .filter(|(_, script)| script.tags.iter().any(|item| item == tag))
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompileOptions {
+    /// Reject attributes outside each script node's allowlist with `XML_UNKNOWN_ATTR`.
+    pub strict_attributes: bool,
+    /// Inline static calls to small param-less call scripts into the caller group.
+    pub inline_small_calls: bool,
+    /// Pre-evaluate pure literal conditions and initializers (e.g. `2 > 1`) into literals.
+    pub fold_constants: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -64,6 +89,7 @@ pub(crate) struct ParsedModuleVarDecl {
     pub(crate) initial_value_format: InitializerFormat,
     pub(crate) initial_value_expr: Option<String>,
     pub(crate) location: SourceSpan,
+    pub(crate) session: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -146,6 +172,7 @@ pub(crate) struct CompileScriptOptions<'a> {
     pub(crate) visible_module_consts: &'a BTreeMap<String, ModuleConstDecl>,
     pub(crate) all_script_access: &'a BTreeMap<String, AccessLevel>,
     pub(crate) invoke_all_functions: &'a BTreeMap<String, FunctionDecl>,
+    pub(crate) strict_attributes: bool,
 }
 
 pub(crate) type VisibleTypeMap = BTreeMap<String, ScriptType>;
@@ -170,6 +197,7 @@ pub(crate) struct GroupBuilder {
     pub(crate) node_counter: usize,
     pub(crate) choice_counter: usize,
     pub(crate) groups: BTreeMap<String, ImplicitGroup>,
+    pub(crate) node_labels: BTreeMap<String, String>,
 }
 
 impl GroupBuilder {
@@ -180,6 +208,7 @@ impl GroupBuilder {
             node_counter: 0,
             choice_counter: 0,
             groups: BTreeMap::new(),
+            node_labels: BTreeMap::new(),
         }
     }
 
@@ -0,0 +1,289 @@
+use crate::*;
+
+/// Variables one script reads and writes, as far as static inspection can tell.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScriptDataFlow {
+    pub reads: BTreeSet<String>,
+    pub writes: BTreeSet<String>,
+}
+
+/// Compute per-script read/write sets over compiled script IR, keyed by script name.
+///
+/// Tracked names are script params, `<temp>` declarations, visible globals and visible module
+/// globals (reported by qualified name). Reads come from `${...}` interpolations, `when`
+/// conditions, initializers, call args and `<code>`; writes come from declarations, `<input>`
+/// targets, ref args and `lhs = ...` / `lhs += ...` statements in `<code>`.
+///
+/// This is best-effort: writes through function bodies, map handles or code built at runtime are
+/// not seen, and a plain identifier match is counted even when a local `let` shadows it.
+pub fn analyze_script_data_flow(
+    scripts: &BTreeMap<String, ScriptIr>,
+) -> BTreeMap<String, ScriptDataFlow> {
+    scripts
+        .iter()
+        .map(|(name, script)| (name.clone(), analyze_script(script)))
+        .collect()
+}
+
+/// `(name as written in source, name reported in the result)`
+type TrackedNames = Vec<(String, String)>;
+
+fn analyze_script(script: &ScriptIr) -> ScriptDataFlow {
+    let tracked = tracked_names(script);
+    let mut flow = ScriptDataFlow::default();
+    for group in script.groups.values() {
+        for node in &group.nodes {
+            match node {
+                ScriptNode::Text { value, speaker, .. } => {
+                    record_template_reads(&tracked, value, &mut flow);
+                    if let Some(speaker) = speaker {
+                        record_template_reads(&tracked, speaker, &mut flow);
+                    }
+                }
+                ScriptNode::Debug { value, .. } => {
+                    record_template_reads(&tracked, value, &mut flow);
+                }
+                ScriptNode::Code { code, .. } => record_code(&tracked, code, &mut flow),
+                ScriptNode::Var { declaration, .. } => {
+                    if !declaration.name.starts_with(INTERNAL_RESERVED_NAME_PREFIX) {
+                        flow.writes.insert(declaration.name.clone());
+                    }
+                    if let Some(expr) = &declaration.initial_value_expr {
+                        record_expression_reads(&tracked, expr, &mut flow);
+                    }
+                }
+                ScriptNode::If { when_expr, .. } | ScriptNode::While { when_expr, .. } => {
+                    record_expression_reads(&tracked, when_expr, &mut flow);
+                }
+                ScriptNode::Choice {
+                    prompt_text,
+                    entries,
+                    ..
+                } => {
+                    record_template_reads(&tracked, prompt_text, &mut flow);
+                    for entry in entries {
+                        let (text, when_expr) = match entry {
+                            ChoiceEntry::Static { option } => {
+                                if let Some(expr) = &option.enabled_when_expr {
+                                    record_expression_reads(&tracked, expr, &mut flow);
+                                }
+                                (&option.text, &option.when_expr)
+                            }
+                            ChoiceEntry::Dynamic { block } => {
+                                record_expression_reads(&tracked, &block.array_expr, &mut flow);
+                                (&block.template.text, &block.template.when_expr)
+                            }
+                        };
+                        record_template_reads(&tracked, text, &mut flow);
+                        if let Some(expr) = when_expr {
+                            record_expression_reads(&tracked, expr, &mut flow);
+                        }
+                    }
+                }
+                ScriptNode::Input {
+                    target_var,
+                    prompt_text,
+                    ..
+                } => {
+                    record_template_reads(&tracked, prompt_text, &mut flow);
+                    record_write_target(&tracked, target_var, &mut flow);
+                }
+                ScriptNode::Call {
+                    target_script,
+                    args,
+                    ..
+                }
+                | ScriptNode::Goto {
+                    target_script,
+                    args,
+                    ..
+                } => {
+                    if let ScriptTarget::Variable { var_name } = target_script {
+                        record_expression_reads(&tracked, var_name, &mut flow);
+                    }
+                    for arg in args {
+                        record_expression_reads(&tracked, &arg.value_expr, &mut flow);
+                        if arg.is_ref {
+                            record_write_target(&tracked, arg.value_expr.trim(), &mut flow);
+                        }
+                    }
+                }
+                ScriptNode::Fail { message, .. } => {
+                    record_template_reads(&tracked, message, &mut flow);
+                }
+                ScriptNode::Break { .. }
+                | ScriptNode::Continue { .. }
+                | ScriptNode::End { .. }
+                | ScriptNode::Checkpoint { .. }
+                | ScriptNode::Return { .. } => {}
+            }
+        }
+    }
+    flow
+}
+
+fn tracked_names(script: &ScriptIr) -> TrackedNames {
+    let mut tracked = TrackedNames::new();
+    let mut push = |source: &str, reported: &str| {
+        if !tracked.iter().any(|(known, _)| known == source) {
+            tracked.push((source.to_string(), reported.to_string()));
+        }
+    };
+    let declared = script.params.iter().map(|param| &param.name).chain(
+        script
+            .groups
+            .values()
+            .flat_map(|group| &group.nodes)
+            .filter_map(|node| match node {
+                ScriptNode::Var { declaration, .. } => Some(&declaration.name),
+                _ => None,
+            }),
+    );
+    for name in declared {
+        if !name.starts_with(INTERNAL_RESERVED_NAME_PREFIX) {
+            push(name, name);
+        }
+    }
+    for (name, decl) in &script.visible_module_vars {
+        // `<code>` bodies are stored with module access already rewritten to the namespace symbol.
+        let rewritten = format!("{}.{}", module_namespace_symbol(&decl.namespace), decl.name);
+        push(&rewritten, &decl.qualified_name);
+        push(name, &decl.qualified_name);
+    }
+    for name in &script.visible_globals {
+        push(name, name);
+    }
+    tracked
+}
+
+fn record_template_reads(tracked: &TrackedNames, template: &str, flow: &mut ScriptDataFlow) {
+    for captures in template_expr_regex().captures_iter(template) {
+        if let Some(expr) = captures.get(1) {
+            record_expression_reads(tracked, expr.as_str(), flow);
+        }
+    }
+}
+
+fn record_expression_reads(tracked: &TrackedNames, expr: &str, flow: &mut ScriptDataFlow) {
+    record_sanitized_reads(tracked, &sanitize_rhai_source(expr), flow);
+}
+
+fn record_sanitized_reads(tracked: &TrackedNames, sanitized: &str, flow: &mut ScriptDataFlow) {
+    for (source, reported) in tracked {
+        if contains_root_identifier(sanitized, source) {
+            flow.reads.insert(reported.clone());
+        }
+    }
+}
+
+fn record_write_target(tracked: &TrackedNames, target: &str, flow: &mut ScriptDataFlow) {
+    let reported = tracked
+        .iter()
+        .filter(|(source, _)| {
+            target == source
+                || target
+                    .strip_prefix(source.as_str())
+                    .is_some_and(|rest| rest.starts_with('.') || rest.starts_with('['))
+        })
+        .max_by_key(|(source, _)| source.len())
+        .map(|(_, reported)| reported.clone());
+    flow.writes
+        .insert(reported.unwrap_or_else(|| target.to_string()));
+}
+
+/// Split `<code>` into statements and treat `lhs = rhs` / `lhs op= rhs` as a write of the root of
+/// `lhs`; `let` bindings are Rhai locals and only contribute reads.
+fn record_code(tracked: &TrackedNames, code: &str, flow: &mut ScriptDataFlow) {
+    let sanitized = sanitize_rhai_source(code);
+    for statement in sanitized.split([';', '{', '}']) {
+        let Some(captures) = code_assignment_regex().captures(statement) else {
+            record_sanitized_reads(tracked, statement, flow);
+            continue;
+        };
+        let whole = captures.get(0).expect("whole match");
+        let lhs = captures.get(1).expect("assignment target").as_str();
+        let root = lhs.split('[').next().unwrap_or(lhs).trim();
+        if tracked.iter().any(|(source, _)| {
+            root == source
+                || root
+                    .strip_prefix(source.as_str())
+                    .is_some_and(|rest| rest.starts_with('.'))
+        }) {
+            record_write_target(tracked, root, flow);
+        }
+        let compound = captures.get(2).is_some_and(|op| !op.as_str().is_empty());
+        let index_exprs = &lhs[root.len()..];
+        if compound {
+            record_sanitized_reads(tracked, lhs, flow);
+        } else {
+            record_sanitized_reads(tracked, index_exprs, flow);
+        }
+        record_sanitized_reads(tracked, &statement[whole.end()..], flow);
+    }
+}
+
+fn code_assignment_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| {
+        Regex::new(
+            r"^\s*([A-Za-z_][A-Za-z0-9_]*(?:\s*(?:\.[A-Za-z_][A-Za-z0-9_]*|\[[^\]]*\]))*)\s*(\*\*|<<|>>|[-+*/%&|^])?=(?:[^=]|$)",
+        )
+        .expect("code assignment regex")
+    })
+}
+
+#[cfg(test)]
+mod dataflow_tests {
+    use super::*;
+
+    fn names(values: &[&str]) -> BTreeSet<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn analyze_script_data_flow_collects_reads_and_writes() {
+        let files = compiler_test_support::map(&[
+            (
+                "shared.xml",
+                r#"<module name="shared" export="var:hp">
+<var name="hp" type="int">10</var>
+</module>"#,
+            ),
+            (
+                "main.xml",
+                r##"<!-- import shared from shared.xml -->
+<module name="main" export="script:main,heal">
+<script name="heal" kind="call" args="ref:int:target,int:amount">
+  <code>target += amount;</code>
+</script>
+<script name="main" args="int:bonus">
+  <temp name="gold" type="int">bonus * 2</temp>
+  <temp name="name" type="string">""</temp>
+  <temp name="bag" type="#{int}">#{}</temp>
+  <text>Gold: ${gold}</text>
+  <code>
+    let tmp = shared.hp;
+    shared.hp = tmp - 1;
+    bag["coins"] = gold; // unseen = 1
+    gold = 0;
+  </code>
+  <input var="name" text="Name?"/>
+  <if when="name == ''"><text>empty</text></if>
+  <call script="@heal" args="ref:gold,bonus"/>
+  <end/>
+</script>
+</module>"##,
+            ),
+        ]);
+        let bundle = compile_project_bundle_from_xml_map(&files).expect("compile should pass");
+        let flows = analyze_script_data_flow(&bundle.scripts);
+
+        let main = &flows["main.main"];
+        assert_eq!(main.reads, names(&["bonus", "gold", "name", "shared.hp"]));
+        assert_eq!(main.writes, names(&["bag", "gold", "name", "shared.hp"]));
+
+        let heal = &flows["main.heal"];
+        assert_eq!(heal.reads, names(&["amount", "target"]));
+        assert_eq!(heal.writes, names(&["target"]));
+    }
+}
@@ -2,23 +2,7 @@ use crate::*;
 use sl_core::default_value_from_type;
 
 #[cfg(test)]
-pub(crate) fn slvalue_from_json(value: JsonValue) -> SlValue {
-    match value {
-        JsonValue::Null => SlValue::String("null".to_string()),
-        JsonValue::Bool(value) => SlValue::Bool(value),
-        JsonValue::Number(value) => SlValue::Number(value.as_f64().unwrap_or(0.0)),
-        JsonValue::String(value) => SlValue::String(value),
-        JsonValue::Array(values) => {
-            SlValue::Array(values.into_iter().map(slvalue_from_json).collect())
-        }
-        JsonValue::Object(values) => SlValue::Map(
-            values
-                .into_iter()
-                .map(|(key, value)| (key, slvalue_from_json(value)))
-                .collect(),
-        ),
-    }
-}
+pub(crate) use sl_core::{slvalue_from_json, slvalue_to_json};
 
 #[cfg(test)]
 pub fn default_values_from_script_params(params: &[ScriptParam]) -> BTreeMap<String, SlValue> {
@@ -77,7 +61,8 @@ mod defaults_tests {
         let json_str = r#"null"#;
         let json_value: JsonValue = serde_json::from_str(json_str).unwrap();
         let sl_value = slvalue_from_json(json_value);
-        assert_eq!(sl_value, SlValue::String("null".to_string()));
+        assert_eq!(sl_value, SlValue::Null);
+        assert_eq!(slvalue_to_json(&sl_value), JsonValue::Null);
     }
 
     #[test]
@@ -7,7 +7,7 @@ pub(crate) fn validate_import_graph(
         for import in &source.imports {
             if !sources.contains_key(import) {
                 return Err(ScriptLangError::new(
-                    "IMPORT_NOT_FOUND",
+                    ErrorCode::ImportNotFound,
                     format!(
                         "Import \"{}\" referenced by \"{}\" not found.",
                         import, file_path
@@ -34,7 +34,7 @@ pub(crate) fn validate_import_graph(
                 stack.push(node.to_string());
                 let cycle = stack.join(" -> ");
                 return Err(ScriptLangError::new(
-                    "IMPORT_CYCLE",
+                    ErrorCode::ImportCycle,
                     format!("Import cycle detected: {}", cycle),
                 ));
             }
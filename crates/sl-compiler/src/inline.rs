@@ -0,0 +1,253 @@
+use crate::*;
+
+/// Maximum callee body size (excluding a trailing `<return/>`) eligible for inlining.
+pub(crate) const INLINE_CALL_MAX_NODES: usize = 8;
+
+struct InlinableBody {
+    script_path: String,
+    nodes: Vec<ScriptNode>,
+}
+
+/// Replace static `<call>` nodes targeting tiny call scripts with the callee body.
+///
+/// Only callees whose execution cannot observe the missing frame are inlined: `kind="call"`,
+/// no params, same source file as the caller (identical visible symbols), and a single root
+/// group holding at most [`INLINE_CALL_MAX_NODES`] non-once `<text>`/`<debug>` nodes with an
+/// optional trailing `<return/>`. Such bodies contain no calls, so recursion cannot occur.
+/// Labeled callees and labeled call sites are kept, since `sl_visited` counts their node ids.
+///
+/// Returns a fingerprint of the inlined call sites, or `None` when nothing was inlined.
+pub(crate) fn inline_small_calls(scripts: &mut BTreeMap<String, ScriptIr>) -> Option<String> {
+    let inlinable = scripts
+        .values()
+        .filter_map(|script| {
+            collect_inlinable_body(script).map(|nodes| {
+                (
+                    script.script_name.clone(),
+                    InlinableBody {
+                        script_path: script.script_path.clone(),
+                        nodes,
+                    },
+                )
+            })
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    let mut inlined_call_sites = Vec::new();
+    for script in scripts.values_mut() {
+        for group in script.groups.values_mut() {
+            let mut changed = false;
+            let mut nodes = Vec::with_capacity(group.nodes.len());
+            // New index of each original node, used to re-anchor `<comment>`s.
+            let mut new_index = Vec::with_capacity(group.nodes.len() + 1);
+            for node in std::mem::take(&mut group.nodes) {
+                new_index.push(nodes.len());
+                let labeled = script
+                    .node_labels
+                    .values()
+                    .any(|id| id.as_str() == node.id());
+                let call_id = node.id();
+                let inlined = match &node {
+                    ScriptNode::Call {
+                        target_script: ScriptTarget::Literal { script_name },
+                        args,
+                        ..
+                    } if args.is_empty() && !labeled => inlinable
+                        .get(script_name)
+                        .filter(|body| body.script_path == script.script_path)
+                        .and_then(|body| {
+                            body.nodes
+                                .iter()
+                                .map(|inner| with_inlined_node_id(inner, call_id))
+                                .collect::<Option<Vec<_>>>()
+                        }),
+                    _ => None,
+                };
+                let Some(inlined) = inlined else {
+                    nodes.push(node);
+                    continue;
+                };
+                nodes.extend(inlined);
+                inlined_call_sites.push(format!("{}#{}", script.script_name, call_id));
+                changed = true;
+            }
+            if changed {
+                new_index.push(nodes.len());
+                group.entry_node_id = nodes.first().map(|node| node.id().to_string());
+                for comment in &mut group.comments {
+                    comment.node_index = new_index[comment.node_index];
+                }
+            }
+            group.nodes = nodes;
+        }
+    }
+
+    if inlined_call_sites.is_empty() {
+        return None;
+    }
+    Some(format!("inline-{:016x}", fnv1a_64(&inlined_call_sites)))
+}
+
+fn collect_inlinable_body(script: &ScriptIr) -> Option<Vec<ScriptNode>> {
+    if script.kind != ScriptKind::Call
+        || !script.params.is_empty()
+        || script.groups.len() != 1
+        || !script.node_labels.is_empty()
+    {
+        return None;
+    }
+    let root = script.groups.get(&script.root_group_id)?;
+    let mut nodes = root.nodes.as_slice();
+    if let Some((ScriptNode::Return { .. }, rest)) = nodes.split_last() {
+        nodes = rest;
+    }
+    if nodes.len() > INLINE_CALL_MAX_NODES {
+        return None;
+    }
+    nodes
+        .iter()
+        .all(|node| {
+            matches!(
+                node,
+                ScriptNode::Text { once: false, .. } | ScriptNode::Debug { .. }
+            )
+        })
+        .then(|| nodes.to_vec())
+}
+
+/// Copy of an inlinable `<text>`/`<debug>` node with its id namespaced under the call site, or
+/// `None` for any other node kind (the call is then kept as is).
+fn with_inlined_node_id(node: &ScriptNode, call_id: &str) -> Option<ScriptNode> {
+    let mut node = node.clone();
+    match &mut node {
+        ScriptNode::Text { id, .. } | ScriptNode::Debug { id, .. } => {
+            *id = format!("{}::inline::{}", call_id, id);
+        }
+        _ => return None,
+    }
+    Some(node)
+}
+
+pub(crate) fn fnv1a_64(parts: &[String]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    for part in parts {
+        for byte in part.bytes().chain(std::iter::once(0)) {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    hash
+}
+
+#[cfg(test)]
+mod inline_tests {
+    use super::*;
+
+    fn compile(files: &BTreeMap<String, String>, inline: bool) -> CompileProjectBundleResult {
+        compile_project_bundle_from_xml_map_with_options(
+            files,
+            CompileOptions {
+                inline_small_calls: inline,
+                ..CompileOptions::default()
+            },
+        )
+        .expect("compile should pass")
+    }
+
+    fn root_nodes<'a>(bundle: &'a CompileProjectBundleResult, script: &str) -> &'a [ScriptNode] {
+        let script = bundle.scripts.get(script).expect("script should exist");
+        &script.groups[&script.root_group_id].nodes
+    }
+
+    #[test]
+    fn inline_small_calls_replaces_eligible_call_sites() {
+        let files = compiler_test_support::map(&[(
+            "main.xml",
+            r#"<module name="main" export="script:main">
+<script name="greet" kind="call"><text>Hi</text><debug>d</debug><return/></script>
+<script name="once" kind="call"><text once="true">Once</text></script>
+<script name="withArg" kind="call" args="int:n"><text>${n}</text></script>
+<script name="main">
+  <call script="@greet"/>
+  <call script="@once"/>
+  <comment>not inlined: takes an arg</comment>
+  <call script="@withArg" args="1"/>
+  <if when="true"><call script="@greet"/></if>
+  <end/>
+</script>
+</module>"#,
+        )]);
+
+        let plain = compile(&files, false);
+        assert_eq!(plain.bundle_fingerprint, None);
+        assert!(matches!(
+            root_nodes(&plain, "main.main")[0],
+            ScriptNode::Call { .. }
+        ));
+
+        let inlined = compile(&files, true);
+        let nodes = root_nodes(&inlined, "main.main");
+        assert!(matches!(&nodes[0], ScriptNode::Text { value, .. } if value == "Hi"));
+        assert!(matches!(&nodes[1], ScriptNode::Debug { value, .. } if value == "d"));
+        assert!(matches!(&nodes[2], ScriptNode::Call { .. }));
+        assert!(matches!(&nodes[3], ScriptNode::Call { .. }));
+        let main = &inlined.scripts["main.main"];
+        assert_eq!(
+            main.groups[&main.root_group_id].entry_node_id.as_deref(),
+            Some(nodes[0].id())
+        );
+        assert!(nodes[0].id().contains("::inline::"));
+        assert_eq!(main.groups[&main.root_group_id].comments[0].node_index, 3);
+        assert_eq!(
+            root_nodes(&plain, "main.main").len() + 1,
+            nodes.len(),
+            "comments never become nodes"
+        );
+
+        let if_group = main
+            .groups
+            .values()
+            .find(|group| group.group_id != main.root_group_id && !group.nodes.is_empty())
+            .expect("then group should exist");
+        assert!(matches!(&if_group.nodes[0], ScriptNode::Text { value, .. } if value == "Hi"));
+        assert_ne!(if_group.nodes[0].id(), nodes[0].id());
+
+        let fingerprint = inlined.bundle_fingerprint.expect("fingerprint");
+        assert!(fingerprint.starts_with("inline-"));
+        assert_eq!(compile(&files, true).bundle_fingerprint, Some(fingerprint));
+    }
+
+    #[test]
+    fn inline_small_calls_skips_oversized_and_cross_file_callees() {
+        let big_body = "<text>x</text>".repeat(INLINE_CALL_MAX_NODES + 1);
+        let main = format!(
+            r#"<!-- import shared from shared.xml -->
+<module name="main" export="script:main">
+<script name="big" kind="call">{big_body}</script>
+<script name="main"><call script="@big"/><call script="@shared.tiny"/><end/></script>
+</module>"#
+        );
+        let files = compiler_test_support::map(&[
+            ("main.xml", main.as_str()),
+            (
+                "shared.xml",
+                r#"<module name="shared" export="script:tiny"><script name="tiny" kind="call"><text>t</text></script></module>"#,
+            ),
+        ]);
+
+        let bundle = compile(&files, true);
+        assert_eq!(bundle.bundle_fingerprint, None);
+        let nodes = root_nodes(&bundle, "main.main");
+        assert!(matches!(nodes[0], ScriptNode::Call { .. }));
+        assert!(matches!(nodes[1], ScriptNode::Call { .. }));
+    }
+
+    #[test]
+    fn with_inlined_node_id_only_accepts_text_and_debug_nodes() {
+        let node = ScriptNode::Return {
+            id: "r1".to_string(),
+            location: SourceSpan::synthetic(),
+        };
+        assert!(with_inlined_node_id(&node, "c1").is_none());
+    }
+}
@@ -9,10 +9,10 @@ pub(crate) use sl_core::{
     module_namespace_symbol, preprocess_scriptlang_rhai_input, rewrite_function_calls,
     rewrite_module_global_qualified_access, rhai_function_symbol, AccessLevel, CallArgument,
     ChoiceEntry, ChoiceOption, CompiledProjectArtifact, ContinueTarget, DynamicChoiceBlock,
-    DynamicChoiceTemplate, FunctionDecl, FunctionParam, FunctionReturn, ImplicitGroup, MapKeyType,
-    ModuleConstDecl, ModuleVarDecl, RhaiInputMode, ScriptIr, ScriptKind, ScriptLangError,
-    ScriptNode, ScriptParam, ScriptTarget, ScriptType, SlValue, SourceSpan, VarDeclaration,
-    COMPILED_PROJECT_SCHEMA,
+    DynamicChoiceTemplate, ErrorCode, FunctionDecl, FunctionParam, FunctionReturn, GroupComment,
+    ImplicitGroup, InputFormat, MapKeyType, ModuleConstDecl, ModuleVarDecl, RhaiInputMode,
+    ScriptIr, ScriptKind, ScriptLangError, ScriptNode, ScriptParam, ScriptTarget, ScriptType,
+    SlValue, SourceSpan, VarDeclaration, COMPILED_PROJECT_SCHEMA,
 };
 pub(crate) use sl_parser::{
     parse_alias_directives, parse_import_directives, parse_xml_document,
@@ -21,33 +21,53 @@ pub(crate) use sl_parser::{
 };
 
 mod artifact;
+mod const_fold;
 mod context;
+mod dataflow;
 #[cfg(test)]
 mod defaults;
 mod error_context;
 mod import_graph;
+mod inline;
+mod linear;
 mod macro_expand;
+mod merge;
 mod module_resolver;
+mod node_hash;
 mod pipeline;
 mod sanitize;
 mod script_compile;
 mod source_parse;
+mod source_provider;
 mod type_expr;
 mod xml_utils;
 
 pub use artifact::{
-    compile_artifact_from_xml_map, read_artifact_json, write_artifact_json,
-    DEFAULT_COMPILER_VERSION,
+    compile_artifact_from_xml_map, compile_artifact_from_xml_map_with_options, read_artifact_json,
+    write_artifact_json, DEFAULT_COMPILER_VERSION,
+};
+pub use context::{CompileOptions, CompileProjectBundleResult};
+pub use dataflow::{analyze_script_data_flow, ScriptDataFlow};
+pub use linear::lower_to_linear;
+pub use merge::merge_bundles;
+pub use pipeline::{
+    compile_project_bundle_from_xml_map, compile_project_bundle_from_xml_map_with_options,
+    compile_project_scripts_from_xml_map,
 };
-pub use context::CompileProjectBundleResult;
-pub use pipeline::{compile_project_bundle_from_xml_map, compile_project_scripts_from_xml_map};
 pub use script_compile::validate_terminal_structure_from_xml_map;
+pub use source_provider::{
+    compile_project_bundle_from_provider, compile_project_bundle_from_provider_with_options,
+    SourceProvider,
+};
 
+pub(crate) use const_fold::*;
 pub(crate) use context::*;
 pub(crate) use error_context::with_file_context_shared;
 pub(crate) use import_graph::*;
+pub(crate) use inline::*;
 pub(crate) use macro_expand::*;
 pub(crate) use module_resolver::*;
+pub(crate) use node_hash::*;
 pub(crate) use sanitize::*;
 pub(crate) use script_compile::*;
 pub(crate) use source_parse::*;
@@ -69,6 +89,30 @@ pub(crate) mod compiler_test_support {
             .collect()
     }
 
+    /// Value of a `<text>` node, `None` for any other node.
+    pub(crate) fn text_value(node: &ScriptNode) -> Option<&str> {
+        match node {
+            ScriptNode::Text { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Entries of a `<choice>` node, `None` for any other node.
+    pub(crate) fn choice_entries(node: &ScriptNode) -> Option<&[ChoiceEntry]> {
+        match node {
+            ScriptNode::Choice { entries, .. } => Some(entries),
+            _ => None,
+        }
+    }
+
+    /// Declaration of a `<temp>` node, `None` for any other node.
+    pub(crate) fn var_declaration(node: &ScriptNode) -> Option<&VarDeclaration> {
+        match node {
+            ScriptNode::Var { declaration, .. } => Some(declaration),
+            _ => None,
+        }
+    }
+
     fn normalize_test_source_path(path: &str) -> String {
         path.replace(".script.xml", ".xml")
             .replace(".module.xml", ".xml")
@@ -0,0 +1,365 @@
+use crate::*;
+use sl_core::{LinearChoiceEntry, LinearInstruction, LinearProgram};
+
+/// Lower a script's group tree to a flat instruction list with explicit jumps.
+///
+/// Every nested group (if/else branch, loop body, option body) becomes a scoped block: loop and
+/// branch bodies are wrapped in `EnterScope`/`ExitScope`, while option bodies get their scope from
+/// the `Choice` instruction and close it themselves. `<break>`/`<continue>` become `ExitScope`s for
+/// the blocks they leave followed by a `Jump`; one without an enclosing target is kept as a node so
+/// the VM reports it the way the tree engine does. A group id missing from the script fails with
+/// `LINEAR_GROUP_NOT_FOUND`.
+pub fn lower_to_linear(script: &ScriptIr) -> Result<LinearProgram, ScriptLangError> {
+    let mut lowering = Lowering {
+        script,
+        instructions: Vec::new(),
+        contexts: Vec::new(),
+        breaks: Vec::new(),
+        depth: 0,
+    };
+    lowering.lower_group(&script.root_group_id)?;
+    lowering.instructions.push(LinearInstruction::Return);
+    Ok(LinearProgram {
+        script_name: script.script_name.clone(),
+        kind: script.kind,
+        params: script.params.clone(),
+        instructions: lowering.instructions,
+    })
+}
+
+#[derive(Clone, Copy)]
+enum ControlContext {
+    Loop { head: usize, depth: usize },
+    ChoiceOption { choice: usize, depth: usize },
+}
+
+/// A forward jump whose target is filled in by [`Lowering::patch`]. It keeps the parts of the
+/// instruction that are already known, so patching writes the whole instruction back.
+enum PendingJump {
+    Jump {
+        index: usize,
+    },
+    BranchUnless {
+        index: usize,
+        node_id: String,
+        when_expr: String,
+    },
+    WhileHead {
+        index: usize,
+        node_id: String,
+        when_expr: String,
+        max_iterations: Option<usize>,
+    },
+}
+
+struct Lowering<'a> {
+    script: &'a ScriptIr,
+    instructions: Vec<LinearInstruction>,
+    contexts: Vec<ControlContext>,
+    /// `<break>` jumps of the loops being lowered; each loop takes back the ones pushed after it
+    /// started.
+    breaks: Vec<PendingJump>,
+    depth: usize,
+}
+
+impl Lowering<'_> {
+    fn emit(&mut self, instruction: LinearInstruction) -> usize {
+        self.instructions.push(instruction);
+        self.instructions.len() - 1
+    }
+
+    fn here(&self) -> usize {
+        self.instructions.len()
+    }
+
+    fn emit_jump(&mut self) -> PendingJump {
+        PendingJump::Jump {
+            index: self.emit(LinearInstruction::Jump { target: 0 }),
+        }
+    }
+
+    fn patch(&mut self, pending: PendingJump, to: usize) {
+        let (index, instruction) = match pending {
+            PendingJump::Jump { index } => (index, LinearInstruction::Jump { target: to }),
+            PendingJump::BranchUnless {
+                index,
+                node_id,
+                when_expr,
+            } => (
+                index,
+                LinearInstruction::BranchUnless {
+                    node_id,
+                    when_expr,
+                    target: to,
+                },
+            ),
+            PendingJump::WhileHead {
+                index,
+                node_id,
+                when_expr,
+                max_iterations,
+            } => (
+                index,
+                LinearInstruction::WhileHead {
+                    node_id,
+                    when_expr,
+                    max_iterations,
+                    exit: to,
+                },
+            ),
+        };
+        self.instructions[index] = instruction;
+    }
+
+    fn lower_scoped_group(&mut self, group_id: &str) -> Result<(), ScriptLangError> {
+        self.emit(LinearInstruction::EnterScope);
+        self.depth += 1;
+        self.lower_group(group_id)?;
+        self.depth -= 1;
+        self.emit(LinearInstruction::ExitScope);
+        Ok(())
+    }
+
+    fn exit_scopes_to(&mut self, depth: usize) {
+        for _ in depth..self.depth {
+            self.emit(LinearInstruction::ExitScope);
+        }
+    }
+
+    fn lower_group(&mut self, group_id: &str) -> Result<(), ScriptLangError> {
+        let group = self.script.groups.get(group_id).ok_or_else(|| {
+            ScriptLangError::new(
+                ErrorCode::LinearGroupNotFound,
+                format!(
+                    "Group \"{}\" not found in script \"{}\".",
+                    group_id, self.script.script_name
+                ),
+            )
+        })?;
+        for node in &group.nodes {
+            self.lower_node(node)?;
+        }
+        Ok(())
+    }
+
+    fn lower_node(&mut self, node: &ScriptNode) -> Result<(), ScriptLangError> {
+        match node {
+            ScriptNode::If {
+                id,
+                when_expr,
+                then_group_id,
+                else_group_id,
+                ..
+            } => {
+                let branch = PendingJump::BranchUnless {
+                    index: self.emit(LinearInstruction::BranchUnless {
+                        node_id: id.clone(),
+                        when_expr: when_expr.clone(),
+                        target: 0,
+                    }),
+                    node_id: id.clone(),
+                    when_expr: when_expr.clone(),
+                };
+                self.lower_scoped_group(then_group_id)?;
+                match else_group_id {
+                    Some(else_group_id) => {
+                        let skip_else = self.emit_jump();
+                        self.patch(branch, self.here());
+                        self.lower_scoped_group(else_group_id)?;
+                        self.patch(skip_else, self.here());
+                    }
+                    None => self.patch(branch, self.here()),
+                }
+            }
+            ScriptNode::While {
+                id,
+                when_expr,
+                body_group_id,
+                max_iterations,
+                ..
+            } => {
+                let head = self.emit(LinearInstruction::WhileHead {
+                    node_id: id.clone(),
+                    when_expr: when_expr.clone(),
+                    max_iterations: *max_iterations,
+                    exit: 0,
+                });
+                let outer_breaks = self.breaks.len();
+                self.contexts.push(ControlContext::Loop {
+                    head,
+                    depth: self.depth,
+                });
+                self.lower_scoped_group(body_group_id)?;
+                self.contexts.pop();
+                self.emit(LinearInstruction::Jump { target: head });
+                let end = self.emit(LinearInstruction::LoopEnd { head });
+                let exit = PendingJump::WhileHead {
+                    index: head,
+                    node_id: id.clone(),
+                    when_expr: when_expr.clone(),
+                    max_iterations: *max_iterations,
+                };
+                self.patch(exit, end);
+                for jump in self.breaks.split_off(outer_breaks) {
+                    self.patch(jump, end);
+                }
+            }
+            ScriptNode::Choice {
+                id,
+                prompt_text,
+                entries,
+                timeout_default_option_id,
+                ..
+            } => {
+                // Placeholder until the option bodies are lowered and their targets are known.
+                let choice = self.emit(LinearInstruction::Return);
+                let mut to_after = vec![self.emit_jump()];
+                let mut lowered = Vec::with_capacity(entries.len());
+                for entry in entries {
+                    let group_id = match entry {
+                        ChoiceEntry::Static { option } => &option.group_id,
+                        ChoiceEntry::Dynamic { block } => &block.template.group_id,
+                    };
+                    lowered.push(LinearChoiceEntry {
+                        entry: entry.clone(),
+                        target: self.here(),
+                    });
+                    self.contexts.push(ControlContext::ChoiceOption {
+                        choice,
+                        depth: self.depth,
+                    });
+                    self.depth += 1;
+                    self.lower_group(group_id)?;
+                    self.depth -= 1;
+                    self.contexts.pop();
+                    self.emit(LinearInstruction::ExitScope);
+                    to_after.push(self.emit_jump());
+                }
+                self.instructions[choice] = LinearInstruction::Choice {
+                    node_id: id.clone(),
+                    prompt_text: prompt_text.clone(),
+                    entries: lowered,
+                    timeout_default_option_id: timeout_default_option_id.clone(),
+                };
+                let after = self.here();
+                for jump in to_after {
+                    self.patch(jump, after);
+                }
+            }
+            ScriptNode::Break { .. } => {
+                let depth = self
+                    .contexts
+                    .iter()
+                    .rev()
+                    .find_map(|context| match context {
+                        ControlContext::Loop { depth, .. } => Some(*depth),
+                        ControlContext::ChoiceOption { .. } => None,
+                    });
+                let Some(depth) = depth else {
+                    self.emit(LinearInstruction::Node { node: node.clone() });
+                    return Ok(());
+                };
+                self.exit_scopes_to(depth);
+                let jump = self.emit_jump();
+                self.breaks.push(jump);
+            }
+            ScriptNode::Continue { target, .. } => {
+                let resolved = self
+                    .contexts
+                    .iter()
+                    .rev()
+                    .find_map(|context| match *context {
+                        ControlContext::Loop { head, depth }
+                            if *target == ContinueTarget::While =>
+                        {
+                            Some((head, depth))
+                        }
+                        ControlContext::ChoiceOption { choice, depth }
+                            if *target == ContinueTarget::Choice =>
+                        {
+                            Some((choice, depth))
+                        }
+                        _ => None,
+                    });
+                let Some((jump_target, depth)) = resolved else {
+                    self.emit(LinearInstruction::Node { node: node.clone() });
+                    return Ok(());
+                };
+                self.exit_scopes_to(depth);
+                self.emit(LinearInstruction::Jump {
+                    target: jump_target,
+                });
+            }
+            ScriptNode::Return { .. } => {
+                self.emit(LinearInstruction::Return);
+            }
+            _ => {
+                self.emit(LinearInstruction::Node { node: node.clone() });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod linear_tests {
+    use super::*;
+
+    #[test]
+    fn lowering_patches_jump_targets_and_serializes() {
+        let files = compiler_test_support::map(&[(
+            "main.xml",
+            r#"<module name="main" export="script:main">
+<script name="main">
+  <if when="true"><text>a</text></if>
+  <text>b</text>
+</script>
+</module>"#,
+        )]);
+        let bundle = compile_project_bundle_from_xml_map(&files).expect("compile");
+        let program = lower_to_linear(&bundle.scripts["main.main"]).expect("lower");
+        assert!(matches!(
+            &program.instructions[0],
+            LinearInstruction::BranchUnless { target: 4, .. }
+        ));
+        assert!(matches!(
+            program.instructions[1],
+            LinearInstruction::EnterScope
+        ));
+        assert!(matches!(
+            program.instructions[3],
+            LinearInstruction::ExitScope
+        ));
+        assert!(matches!(
+            &program.instructions[4],
+            LinearInstruction::Node { node: ScriptNode::Text { value, .. } } if value == "b"
+        ));
+        assert_eq!(
+            program.instructions.last(),
+            Some(&LinearInstruction::Return)
+        );
+
+        let json = serde_json::to_value(&program).expect("serialize");
+        assert_eq!(json["instructions"][0]["op"], "branchUnless");
+        let decoded: LinearProgram = serde_json::from_value(json).expect("deserialize");
+        assert_eq!(decoded, program);
+    }
+
+    #[test]
+    fn lowering_reports_missing_groups() {
+        let files = compiler_test_support::map(&[(
+            "main.xml",
+            r#"<module name="main" export="script:main">
+<script name="main">
+  <if when="true"><text>a</text></if>
+</script>
+</module>"#,
+        )]);
+        let bundle = compile_project_bundle_from_xml_map(&files).expect("compile");
+        let mut script = bundle.scripts["main.main"].clone();
+        let root = script.root_group_id.clone();
+        script.groups.retain(|group_id, _| *group_id == root);
+        let error = lower_to_linear(&script).expect_err("missing branch group should fail");
+        assert_eq!(error.code, "LINEAR_GROUP_NOT_FOUND");
+    }
+}
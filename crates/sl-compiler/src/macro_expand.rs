@@ -65,6 +65,11 @@ pub(crate) fn validate_reserved_prefix_in_user_var_declarations(
             }
         }
     }
+    if node.name == "code" {
+        for key in node.attributes.keys() {
+            assert_name_not_reserved(key, "code attribute", node.location.clone())?;
+        }
+    }
 
     for child in element_children(node) {
         validate_reserved_prefix_in_user_var_declarations(child)?;
@@ -95,12 +100,21 @@ pub(crate) fn expand_element_with_macros(
     node: &XmlElementNode,
     context: &mut MacroExpansionContext,
 ) -> Result<Vec<XmlElementNode>, ScriptLangError> {
+    if node.name == "disabled" {
+        return Ok(Vec::new());
+    }
     if node.name == "for" {
         return Ok(vec![expand_for_macro(node, context)?]);
     }
+    if node.name == "repeat" {
+        return Ok(vec![expand_repeat_macro(node, context)?]);
+    }
     if node.name == "temp-input" {
         return expand_temp_input_macro(node);
     }
+    if node.name == "option" && node.attributes.contains_key("set") {
+        return Ok(vec![expand_option_set_macro(node, context)?]);
+    }
 
     Ok(vec![XmlElementNode {
         name: node.name.clone(),
@@ -110,11 +124,43 @@ pub(crate) fn expand_element_with_macros(
     }])
 }
 
+/// Rewrite `<option set="...">` into an option whose body starts with an implicit `<code>`.
+fn expand_option_set_macro(
+    node: &XmlElementNode,
+    context: &mut MacroExpansionContext,
+) -> Result<XmlElementNode, ScriptLangError> {
+    let mut attributes = node.attributes.clone();
+    let set_code = attributes.remove("set").unwrap_or_default();
+    if set_code.trim().is_empty() {
+        return Err(ScriptLangError::with_span(
+            ErrorCode::XmlOptionSetEmpty,
+            "Attribute \"set\" on <option> cannot be empty.",
+            node.location.clone(),
+        ));
+    }
+    let mut children = vec![XmlNode::Element(XmlElementNode {
+        name: "code".to_string(),
+        attributes: BTreeMap::new(),
+        children: vec![XmlNode::Text(XmlTextNode {
+            value: set_code,
+            location: node.location.clone(),
+        })],
+        location: node.location.clone(),
+    })];
+    children.extend(expand_children(&node.children, context)?);
+    Ok(XmlElementNode {
+        name: node.name.clone(),
+        attributes,
+        children,
+        location: node.location.clone(),
+    })
+}
+
 fn expand_temp_input_macro(node: &XmlElementNode) -> Result<Vec<XmlElementNode>, ScriptLangError> {
     validate_temp_input_attributes(node)?;
     if let Some(child) = element_children(node).next() {
         return Err(ScriptLangError::with_span(
-            "XML_TEMP_INPUT_CONTENT_FORBIDDEN",
+            ErrorCode::XmlTempInputContentForbidden,
             "<temp-input> cannot contain child elements. Use inline text only.",
             child.location.clone(),
         ));
@@ -126,7 +172,7 @@ fn expand_temp_input_macro(node: &XmlElementNode) -> Result<Vec<XmlElementNode>,
     let type_name = get_required_non_empty_attr(node, "type")?;
     if type_name.trim() != "string" {
         return Err(ScriptLangError::with_span(
-            "XML_TEMP_INPUT_TYPE_UNSUPPORTED",
+            ErrorCode::XmlTempInputTypeUnsupported,
             format!(
                 "Attribute \"type\" on <temp-input> only supports \"string\", got \"{}\".",
                 type_name
@@ -179,7 +225,7 @@ fn validate_temp_input_attributes(node: &XmlElementNode) -> Result<(), ScriptLan
             continue;
         }
         return Err(ScriptLangError::with_span(
-            "XML_ATTR_NOT_ALLOWED",
+            ErrorCode::XmlAttrNotAllowed,
             format!(
                 "Attribute \"{}\" is not allowed on <temp-input>. Supported attributes: name, type, text, max_length.",
                 key
@@ -226,7 +272,7 @@ fn expand_for_macro(
         )?;
         if !temp_names.insert(decl.name.clone()) {
             return Err(ScriptLangError::with_span(
-                "XML_FOR_TEMPS_DUPLICATE",
+                ErrorCode::XmlForTempsDuplicate,
                 format!(
                     "Attribute \"temps\" on <for> contains duplicated temp name \"{}\".",
                     decl.name
@@ -265,7 +311,7 @@ fn expand_for_macro(
 
     let clear_first_flag_code = XmlElementNode {
         name: "code".to_string(),
-        attributes: BTreeMap::new(),
+        attributes: BTreeMap::from([(INTERNAL_CODE_ATTR.to_string(), "true".to_string())]),
         children: vec![XmlNode::Text(XmlTextNode {
             value: format!("{} = false;", first_flag_name),
             location: node.location.clone(),
@@ -321,6 +367,86 @@ fn expand_for_macro(
     })
 }
 
+/// Rewrite `<repeat until="...">` into a `<while>` whose first pass skips the condition.
+///
+/// The body stays the `<while>` body, so `<break/>`/`<continue/>` target it and `continue`
+/// re-checks `until` like the end of a pass does.
+fn expand_repeat_macro(
+    node: &XmlElementNode,
+    context: &mut MacroExpansionContext,
+) -> Result<XmlElementNode, ScriptLangError> {
+    validate_repeat_attributes(node)?;
+    let until_expr = get_required_non_empty_attr(node, "until")?;
+
+    let first_flag_name = next_first_flag_var_name(context, REPEAT_FIRST_TEMP_VAR_PREFIX);
+    let first_flag_temp = XmlElementNode {
+        name: "temp".to_string(),
+        attributes: BTreeMap::from([
+            ("name".to_string(), first_flag_name.clone()),
+            ("type".to_string(), "boolean".to_string()),
+        ]),
+        children: vec![XmlNode::Text(XmlTextNode {
+            value: "true".to_string(),
+            location: node.location.clone(),
+        })],
+        location: node.location.clone(),
+    };
+    let clear_first_flag_code = XmlElementNode {
+        name: "code".to_string(),
+        attributes: BTreeMap::from([(INTERNAL_CODE_ATTR.to_string(), "true".to_string())]),
+        children: vec![XmlNode::Text(XmlTextNode {
+            value: format!("{} = false;", first_flag_name),
+            location: node.location.clone(),
+        })],
+        location: node.location.clone(),
+    };
+
+    let mut while_children = vec![XmlNode::Element(clear_first_flag_code)];
+    while_children.extend(expand_children(&node.children, context)?);
+
+    let mut while_attrs = BTreeMap::new();
+    while_attrs.insert(
+        "when".to_string(),
+        format!("{} || !({})", first_flag_name, until_expr),
+    );
+    if let Some(max) = get_optional_attr(node, "max") {
+        while_attrs.insert("max".to_string(), max);
+    }
+
+    Ok(XmlElementNode {
+        name: "group".to_string(),
+        attributes: BTreeMap::new(),
+        children: vec![
+            XmlNode::Element(first_flag_temp),
+            XmlNode::Element(XmlElementNode {
+                name: "while".to_string(),
+                attributes: while_attrs,
+                children: while_children,
+                location: node.location.clone(),
+            }),
+        ],
+        location: node.location.clone(),
+    })
+}
+
+fn validate_repeat_attributes(node: &XmlElementNode) -> Result<(), ScriptLangError> {
+    if let Some(key) = node
+        .attributes
+        .keys()
+        .find(|key| !matches!(key.as_str(), "until" | "max"))
+    {
+        return Err(ScriptLangError::with_span(
+            ErrorCode::XmlAttrNotAllowed,
+            format!(
+                "Attribute \"{}\" is not allowed on <repeat>. Supported attributes: until, max.",
+                key
+            ),
+            node.location.clone(),
+        ));
+    }
+    Ok(())
+}
+
 fn get_for_iteration_expr(node: &XmlElementNode) -> Result<String, ScriptLangError> {
     let Some(raw) = get_optional_attr(node, "iteration") else {
         return Ok("true;".to_string());
@@ -328,7 +454,7 @@ fn get_for_iteration_expr(node: &XmlElementNode) -> Result<String, ScriptLangErr
 
     if raw.trim().is_empty() {
         return Err(ScriptLangError::with_span(
-            "XML_EMPTY_ATTR",
+            ErrorCode::XmlEmptyAttr,
             "Attribute \"iteration\" on <for> cannot be empty.",
             node.location.clone(),
         ));
@@ -487,7 +613,7 @@ fn split_top_level_for_temps_entries(raw: &str) -> Vec<String> {
 
 fn invalid_for_temps_error(node: &XmlElementNode, message: &str) -> ScriptLangError {
     ScriptLangError::with_span(
-        "XML_FOR_TEMPS_INVALID",
+        ErrorCode::XmlForTempsInvalid,
         message.to_string(),
         node.location.clone(),
     )
@@ -499,7 +625,7 @@ fn validate_for_attributes(node: &XmlElementNode) -> Result<(), ScriptLangError>
             continue;
         }
         return Err(ScriptLangError::with_span(
-            "XML_ATTR_NOT_ALLOWED",
+            ErrorCode::XmlAttrNotAllowed,
             format!(
                 "Attribute \"{}\" is not allowed on <for>. Supported attributes: temps, condition, iteration.",
                 key
@@ -511,8 +637,12 @@ fn validate_for_attributes(node: &XmlElementNode) -> Result<(), ScriptLangError>
 }
 
 pub(crate) fn next_for_first_flag_var_name(context: &mut MacroExpansionContext) -> String {
+    next_first_flag_var_name(context, FOR_FIRST_TEMP_VAR_PREFIX)
+}
+
+fn next_first_flag_var_name(context: &mut MacroExpansionContext, prefix: &str) -> String {
     loop {
-        let candidate = format!("{}{}_first", FOR_FIRST_TEMP_VAR_PREFIX, context.for_counter);
+        let candidate = format!("{}{}_first", prefix, context.for_counter);
         context.for_counter += 1;
         if context.used_var_names.insert(candidate.clone()) {
             return candidate;
@@ -576,6 +706,80 @@ mod macro_expand_tests {
         assert!(!while_group.nodes.is_empty());
     }
 
+    #[test]
+    fn disabled_blocks_produce_no_nodes_but_must_be_well_formed() {
+        let files = map(&[(
+            "main.xml",
+            r#"
+    <module name="main" export="script:main">
+    <script name="main">
+      <text>kept</text>
+      <disabled>
+        <text>skipped</text>
+        <disabled><choice text="old"><option text="x"/></choice></disabled>
+      </disabled>
+      <end/>
+      <disabled><text>after end</text></disabled>
+    </script>
+    </module>
+    "#,
+        )]);
+        let result = compile_project_bundle_from_xml_map(&files).expect("project should compile");
+        let main = result.scripts.get("main.main").expect("main script");
+        let root = main.groups.get(&main.root_group_id).expect("root group");
+        assert_eq!(root.nodes.len(), 2);
+        assert!(matches!(&root.nodes[0], ScriptNode::Text { value, .. } if value == "kept"));
+        assert!(matches!(&root.nodes[1], ScriptNode::End { .. }));
+
+        let malformed = map(&[(
+            "main.xml",
+            r#"<module name="main" export="script:main"><script name="main"><disabled><text></disabled><end/></script></module>"#,
+        )]);
+        assert_eq!(
+            compile_project_bundle_from_xml_map(&malformed)
+                .expect_err("malformed disabled content should fail")
+                .code,
+            "XML_PARSE_ERROR"
+        );
+    }
+
+    #[test]
+    fn option_set_macro_prepends_code_to_option_body() {
+        let option = xml_element(
+            "option",
+            &[("text", "Yes"), ("set", "agreed = true;")],
+            vec![XmlNode::Element(xml_element(
+                "text",
+                &[],
+                vec![xml_text("ok")],
+            ))],
+        );
+        let expanded = expand_element_with_macros(
+            &option,
+            &mut MacroExpansionContext {
+                used_var_names: BTreeSet::new(),
+                for_counter: 0,
+            },
+        )
+        .expect("option set should expand");
+        assert!(!expanded[0].attributes.contains_key("set"));
+        let names = element_children(&expanded[0])
+            .map(|child| child.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["code", "text"]);
+
+        let empty = xml_element("option", &[("text", "Yes"), ("set", " ")], vec![]);
+        let error = expand_element_with_macros(
+            &empty,
+            &mut MacroExpansionContext {
+                used_var_names: BTreeSet::new(),
+                for_counter: 0,
+            },
+        )
+        .expect_err("empty set should fail");
+        assert_eq!(error.code, "XML_OPTION_SET_EMPTY");
+    }
+
     #[test]
     fn for_macro_guards_iteration_with_first_flag() {
         let for_node = xml_element(
@@ -861,6 +1065,15 @@ mod macro_expand_tests {
             .expect_err("reserved temp-input name should fail");
         assert_eq!(error.code, "NAME_RESERVED_PREFIX");
 
+        let forged_internal_code = xml_element(
+            "code",
+            &[(INTERNAL_CODE_ATTR, "true")],
+            vec![xml_text("__sl_for_0_first = true;")],
+        );
+        let error = validate_reserved_prefix_in_user_var_declarations(&forged_internal_code)
+            .expect_err("user code cannot claim to be macro-emitted");
+        assert_eq!(error.code, "NAME_RESERVED_PREFIX");
+
         let bad_temps = xml_element(
             "for",
             &[
@@ -1174,6 +1387,48 @@ mod macro_expand_tests {
         assert!(chosen.starts_with(FOR_FIRST_TEMP_VAR_PREFIX));
     }
 
+    #[test]
+    fn repeat_macro_expands_to_while_with_first_pass_flag() {
+        let repeat_node = xml_element(
+            "repeat",
+            &[("until", "hp LTE 0"), ("max", "10")],
+            vec![XmlNode::Element(xml_element(
+                "text",
+                &[],
+                vec![xml_text("x")],
+            ))],
+        );
+        let mut context = MacroExpansionContext {
+            used_var_names: BTreeSet::new(),
+            for_counter: 0,
+        };
+        let expanded =
+            expand_element_with_macros(&repeat_node, &mut context).expect("repeat should expand");
+        let group = expanded.first().expect("expanded group");
+        let temp = find_child_by_name(&group.children, "temp").expect("first pass flag");
+        assert_eq!(temp.attributes["name"], "__sl_repeat_0_first");
+        let while_node = find_child_by_name(&group.children, "while").expect("while");
+        assert_eq!(
+            while_node.attributes["when"],
+            "__sl_repeat_0_first || !(hp LTE 0)"
+        );
+        assert_eq!(while_node.attributes["max"], "10");
+        let clear = find_child_by_name(&while_node.children, "code").expect("flag reset");
+        assert!(clear.attributes.contains_key(INTERNAL_CODE_ATTR));
+        assert!(find_child_by_name(&while_node.children, "text").is_some());
+
+        let error = expand_element_with_macros(
+            &xml_element("repeat", &[("while", "true")], Vec::new()),
+            &mut context,
+        )
+        .expect_err("unknown attribute");
+        assert_eq!(error.code, "XML_ATTR_NOT_ALLOWED");
+        let error =
+            expand_element_with_macros(&xml_element("repeat", &[], Vec::new()), &mut context)
+                .expect_err("missing until");
+        assert_eq!(error.code, "XML_MISSING_ATTR");
+    }
+
     #[test]
     fn for_macro_empty_temps_attribute_fails() {
         let for_node = xml_element(
@@ -0,0 +1,802 @@
+use crate::*;
+
+/// Combine independently compiled bundles into one, moving each under its own prefix.
+///
+/// Every script name becomes `<prefix>.name`, module or not (`main` -> `a.main`, `main.main` ->
+/// `a.main.main`), and so does every module namespace `ns`, so function, module var/const and type
+/// names gain the prefix too. Group and node ids and `script_path` gain `<prefix>/`, and host
+/// `global_data` symbols become `<prefix>_symbol`. Compiled expressions are rewritten to match:
+/// module namespace and function symbols, `"@script"` / `"*function"` literals and `__script__`
+/// values. Node hashes are recomputed because they cover expression text.
+///
+/// Prefixes must be identifiers and unique. A static `<call>`/`<goto>` naming a script that is
+/// not part of its own bundle fails with `MERGE_REFERENCE_UNRESOLVED`, and a renamed script,
+/// global, module var/const or labeled node that is already in the merged bundle fails with
+/// `MERGE_NAME_CONFLICT` instead of replacing it.
+pub fn merge_bundles(
+    bundles: Vec<(String, CompileProjectBundleResult)>,
+) -> Result<CompileProjectBundleResult, ScriptLangError> {
+    let mut merged = CompileProjectBundleResult {
+        scripts: BTreeMap::new(),
+        global_data: BTreeMap::new(),
+        module_var_declarations: BTreeMap::new(),
+        module_var_init_order: Vec::new(),
+        module_const_declarations: BTreeMap::new(),
+        module_const_init_order: Vec::new(),
+        bundle_fingerprint: None,
+    };
+    let mut prefixes = BTreeSet::new();
+    let mut fingerprints = Vec::new();
+    // Rhai symbol -> the prefixed name it was derived from, to catch `a`+`b_c` vs `a_b`+`c`.
+    let mut symbols = BTreeMap::<String, String>::new();
+    let mut labeled_nodes = BTreeSet::new();
+
+    for (prefix, bundle) in bundles {
+        if !decl_name_regex().is_match(&prefix) {
+            return Err(ScriptLangError::new(
+                ErrorCode::MergePrefixInvalid,
+                format!("Merge prefix \"{}\" must be an identifier.", prefix),
+            ));
+        }
+        if !prefixes.insert(prefix.clone()) {
+            return Err(ScriptLangError::new(
+                ErrorCode::MergePrefixDuplicate,
+                format!("Merge prefix \"{}\" is used more than once.", prefix),
+            ));
+        }
+
+        let renamer = BundleRenamer::new(&prefix, &bundle);
+        for (symbol, origin) in renamer.symbol_origins() {
+            if let Some(existing) = symbols.insert(symbol.clone(), origin.clone()) {
+                if existing != *origin {
+                    return Err(ScriptLangError::new(
+                        ErrorCode::MergeSymbolConflict,
+                        format!(
+                            "\"{}\" and \"{}\" map to the same Rhai symbol \"{}\".",
+                            existing, origin, symbol
+                        ),
+                    ));
+                }
+            }
+        }
+
+        for script in bundle.scripts.into_values() {
+            let script = renamer.rename_script(script)?;
+            for node_id in script.node_labels.values() {
+                if !labeled_nodes.insert(node_id.clone()) {
+                    return Err(name_conflict("labeled node", node_id));
+                }
+            }
+            insert_unique(
+                &mut merged.scripts,
+                script.script_name.clone(),
+                script,
+                "script",
+            )?;
+        }
+        for (symbol, value) in bundle.global_data {
+            insert_unique(
+                &mut merged.global_data,
+                renamer.global_symbol(&symbol),
+                value,
+                "global",
+            )?;
+        }
+        for decl in bundle.module_var_declarations.into_values() {
+            let decl = renamer.rename_module_var(decl);
+            insert_unique(
+                &mut merged.module_var_declarations,
+                decl.qualified_name.clone(),
+                decl,
+                "module var",
+            )?;
+        }
+        merged.module_var_init_order.extend(
+            bundle
+                .module_var_init_order
+                .iter()
+                .map(|name| renamer.qualified(name)),
+        );
+        for decl in bundle.module_const_declarations.into_values() {
+            let decl = renamer.rename_module_const(decl);
+            insert_unique(
+                &mut merged.module_const_declarations,
+                decl.qualified_name.clone(),
+                decl,
+                "module const",
+            )?;
+        }
+        merged.module_const_init_order.extend(
+            bundle
+                .module_const_init_order
+                .iter()
+                .map(|name| renamer.qualified(name)),
+        );
+        if let Some(fingerprint) = bundle.bundle_fingerprint {
+            fingerprints.push(format!("{}={}", prefix, fingerprint));
+        }
+    }
+
+    assign_node_hashes(&mut merged.scripts);
+    if !fingerprints.is_empty() {
+        merged.bundle_fingerprint = Some(format!("merge-{:016x}", fnv1a_64(&fingerprints)));
+    }
+    Ok(merged)
+}
+
+fn insert_unique<V>(
+    map: &mut BTreeMap<String, V>,
+    key: String,
+    value: V,
+    kind: &str,
+) -> Result<(), ScriptLangError> {
+    if map.contains_key(&key) {
+        return Err(name_conflict(kind, &key));
+    }
+    map.insert(key, value);
+    Ok(())
+}
+
+fn name_conflict(kind: &str, name: &str) -> ScriptLangError {
+    ScriptLangError::new(
+        ErrorCode::MergeNameConflict,
+        format!(
+            "Merged bundles define {} \"{}\" more than once.",
+            kind, name
+        ),
+    )
+}
+
+struct BundleRenamer<'a> {
+    prefix: &'a str,
+    /// First segments of the bundle's module namespaces; a dotted name starting with one is
+    /// qualified and gets the prefix.
+    roots: BTreeSet<String>,
+    script_names: BTreeSet<String>,
+    function_names: BTreeSet<String>,
+    /// Rhai identifiers in compiled expressions and their prefixed replacements.
+    identifiers: BTreeMap<String, String>,
+    /// Prefixed replacement symbol -> the prefixed name it stands for, for conflict messages.
+    origins: BTreeMap<String, String>,
+}
+
+impl<'a> BundleRenamer<'a> {
+    fn new(prefix: &'a str, bundle: &CompileProjectBundleResult) -> Self {
+        let mut namespaces = BTreeSet::new();
+        let mut dotted_functions = BTreeSet::new();
+        for script in bundle.scripts.values() {
+            namespaces.extend(script.module_name.clone());
+            namespaces.extend(
+                script
+                    .visible_module_vars
+                    .values()
+                    .map(|decl| decl.namespace.clone()),
+            );
+            namespaces.extend(
+                script
+                    .visible_module_consts
+                    .values()
+                    .map(|decl| decl.namespace.clone()),
+            );
+            dotted_functions.extend(
+                script
+                    .visible_functions
+                    .keys()
+                    .chain(script.invoke_all_functions.keys())
+                    .filter(|name| name.contains('.'))
+                    .cloned(),
+            );
+        }
+        namespaces.extend(
+            bundle
+                .module_var_declarations
+                .values()
+                .map(|decl| decl.namespace.clone()),
+        );
+        namespaces.extend(
+            bundle
+                .module_const_declarations
+                .values()
+                .map(|decl| decl.namespace.clone()),
+        );
+        // `navigation.get` next to `m.navigation.get` is a parent's short name for a child
+        // module function, not a qualified name.
+        let function_names = dotted_functions
+            .iter()
+            .filter(|name| {
+                !dotted_functions
+                    .iter()
+                    .any(|other| other.ends_with(&format!(".{}", name)))
+            })
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        namespaces.extend(
+            function_names
+                .iter()
+                .filter_map(|name| name.rsplit_once('.').map(|(ns, _)| ns.to_string())),
+        );
+
+        let roots = namespaces
+            .iter()
+            .map(|ns| ns.split('.').next().unwrap_or(ns).to_string())
+            .collect();
+        let mut identifiers = BTreeMap::new();
+        let mut origins = BTreeMap::new();
+        for ns in &namespaces {
+            let prefixed = format!("{}.{}", prefix, ns);
+            let symbol = module_namespace_symbol(&prefixed);
+            identifiers.insert(module_namespace_symbol(ns), symbol.clone());
+            origins.insert(symbol, format!("module {}", prefixed));
+        }
+        for name in &function_names {
+            let prefixed = format!("{}.{}", prefix, name);
+            let symbol = rhai_function_symbol(&prefixed);
+            identifiers.insert(rhai_function_symbol(name), symbol.clone());
+            origins.insert(symbol, format!("function {}", prefixed));
+        }
+        for name in bundle.global_data.keys().chain(
+            bundle
+                .scripts
+                .values()
+                .flat_map(|script| script.visible_globals.iter()),
+        ) {
+            let symbol = format!("{}_{}", prefix, name);
+            identifiers.insert(name.clone(), symbol.clone());
+            origins.insert(symbol, format!("global {}.{}", prefix, name));
+        }
+
+        Self {
+            prefix,
+            roots,
+            script_names: bundle.scripts.keys().cloned().collect(),
+            function_names,
+            identifiers,
+            origins,
+        }
+    }
+
+    /// `(renamed Rhai symbol, prefixed name)` pairs that must stay unique across bundles.
+    fn symbol_origins(&self) -> &BTreeMap<String, String> {
+        &self.origins
+    }
+
+    fn qualified(&self, name: &str) -> String {
+        match name.split_once('.') {
+            Some((root, _)) if self.roots.contains(root) => format!("{}.{}", self.prefix, name),
+            _ => name.to_string(),
+        }
+    }
+
+    fn script_name(&self, name: &str) -> String {
+        format!("{}.{}", self.prefix, name)
+    }
+
+    fn namespace(&self, ns: &str) -> String {
+        format!("{}.{}", self.prefix, ns)
+    }
+
+    fn id(&self, id: &str) -> String {
+        format!("{}/{}", self.prefix, id)
+    }
+
+    fn global_symbol(&self, symbol: &str) -> String {
+        format!("{}_{}", self.prefix, symbol)
+    }
+
+    fn rename_script(&self, mut script: ScriptIr) -> Result<ScriptIr, ScriptLangError> {
+        script.script_path = self.id(&script.script_path);
+        script.script_name = self.script_name(&script.script_name);
+        script.module_name = script.module_name.map(|ns| self.namespace(&ns));
+        script.root_group_id = self.id(&script.root_group_id);
+        for param in &mut script.params {
+            self.rename_type(&mut param.r#type);
+        }
+        let mut groups = BTreeMap::new();
+        for (_, mut group) in std::mem::take(&mut script.groups) {
+            group.group_id = self.id(&group.group_id);
+            group.parent_group_id = group.parent_group_id.map(|id| self.id(&id));
+            group.entry_node_id = group.entry_node_id.map(|id| self.id(&id));
+            for node in &mut group.nodes {
+                self.rename_node(&script.script_name, node)?;
+            }
+            groups.insert(group.group_id.clone(), group);
+        }
+        script.groups = groups;
+        for id in script.node_labels.values_mut() {
+            *id = self.id(id);
+        }
+        script.visible_globals = script
+            .visible_globals
+            .iter()
+            .map(|symbol| self.global_symbol(symbol))
+            .collect();
+        script.visible_functions = self.rename_functions(script.visible_functions);
+        script.invoke_all_functions = self.rename_functions(script.invoke_all_functions);
+        script.visible_module_vars = script
+            .visible_module_vars
+            .into_iter()
+            .map(|(key, decl)| (self.qualified(&key), self.rename_module_var(decl)))
+            .collect();
+        script.visible_module_consts = script
+            .visible_module_consts
+            .into_iter()
+            .map(|(key, decl)| (self.qualified(&key), self.rename_module_const(decl)))
+            .collect();
+        Ok(script)
+    }
+
+    fn rename_node(&self, script_name: &str, node: &mut ScriptNode) -> Result<(), ScriptLangError> {
+        match node {
+            ScriptNode::Text {
+                id,
+                value,
+                expr,
+                speaker,
+                ..
+            } => {
+                *id = self.id(id);
+                *value = self.template(value);
+                self.expr_optional(expr);
+                *speaker = speaker.as_deref().map(|speaker| self.template(speaker));
+            }
+            ScriptNode::Debug { id, value, .. } => {
+                *id = self.id(id);
+                *value = self.template(value);
+            }
+            ScriptNode::Code { id, code, .. } => {
+                *id = self.id(id);
+                *code = self.expr(code);
+            }
+            ScriptNode::Var {
+                id, declaration, ..
+            } => {
+                *id = self.id(id);
+                self.rename_type(&mut declaration.r#type);
+                self.expr_optional(&mut declaration.initial_value_expr);
+            }
+            ScriptNode::If {
+                id,
+                when_expr,
+                then_group_id,
+                else_group_id,
+                ..
+            } => {
+                *id = self.id(id);
+                *when_expr = self.expr(when_expr);
+                *then_group_id = self.id(then_group_id);
+                *else_group_id = else_group_id.as_deref().map(|group| self.id(group));
+            }
+            ScriptNode::While {
+                id,
+                when_expr,
+                body_group_id,
+                ..
+            } => {
+                *id = self.id(id);
+                *when_expr = self.expr(when_expr);
+                *body_group_id = self.id(body_group_id);
+            }
+            ScriptNode::Choice {
+                id,
+                prompt_text,
+                entries,
+                timeout_default_option_id,
+                ..
+            } => {
+                *id = self.id(id);
+                *prompt_text = self.template(prompt_text);
+                *timeout_default_option_id = timeout_default_option_id
+                    .as_deref()
+                    .map(|option| self.id(option));
+                for entry in entries {
+                    match entry {
+                        ChoiceEntry::Static { option } => {
+                            option.id = self.id(&option.id);
+                            option.text = self.template(&option.text);
+                            self.expr_optional(&mut option.when_expr);
+                            self.expr_optional(&mut option.enabled_when_expr);
+                            option.group_id = self.id(&option.group_id);
+                        }
+                        ChoiceEntry::Dynamic { block } => {
+                            block.id = self.id(&block.id);
+                            block.array_expr = self.expr(&block.array_expr);
+                            block.template.text = self.template(&block.template.text);
+                            self.expr_optional(&mut block.template.when_expr);
+                            block.template.group_id = self.id(&block.template.group_id);
+                        }
+                    }
+                }
+            }
+            ScriptNode::Input {
+                id,
+                target_var,
+                prompt_text,
+                ..
+            } => {
+                *id = self.id(id);
+                *target_var = self.expr(target_var);
+                *prompt_text = self.template(prompt_text);
+            }
+            ScriptNode::Call {
+                id,
+                target_script,
+                args,
+                location,
+            }
+            | ScriptNode::Goto {
+                id,
+                target_script,
+                args,
+                location,
+            } => {
+                *id = self.id(id);
+                if let ScriptTarget::Literal {
+                    script_name: target,
+                } = target_script
+                {
+                    if !self.script_names.contains(target.as_str()) {
+                        return Err(ScriptLangError::with_span(
+                            ErrorCode::MergeReferenceUnresolved,
+                            format!(
+                                "Script \"{}\" references \"{}\", which is not in bundle \"{}\".",
+                                script_name, target, self.prefix
+                            ),
+                            location.clone(),
+                        ));
+                    }
+                    *target = self.script_name(target);
+                }
+                for arg in args {
+                    arg.value_expr = self.expr(&arg.value_expr);
+                }
+            }
+            ScriptNode::Fail { id, message, .. } => {
+                *id = self.id(id);
+                *message = self.template(message);
+            }
+            ScriptNode::Break { id, .. }
+            | ScriptNode::Continue { id, .. }
+            | ScriptNode::End { id, .. }
+            | ScriptNode::Checkpoint { id, .. }
+            | ScriptNode::Return { id, .. } => {
+                *id = self.id(id);
+            }
+        }
+        Ok(())
+    }
+
+    fn rename_functions(
+        &self,
+        functions: BTreeMap<String, FunctionDecl>,
+    ) -> BTreeMap<String, FunctionDecl> {
+        functions
+            .into_iter()
+            .map(|(key, mut decl)| {
+                decl.name = self.qualified(&decl.name);
+                for param in &mut decl.params {
+                    self.rename_type(&mut param.r#type);
+                }
+                self.rename_type(&mut decl.return_binding.r#type);
+                decl.code = self.expr(&decl.code);
+                (self.qualified(&key), decl)
+            })
+            .collect()
+    }
+
+    fn rename_module_var(&self, mut decl: ModuleVarDecl) -> ModuleVarDecl {
+        decl.namespace = self.namespace(&decl.namespace);
+        decl.qualified_name = self.qualified(&decl.qualified_name);
+        self.rename_type(&mut decl.r#type);
+        self.expr_optional(&mut decl.initial_value_expr);
+        decl
+    }
+
+    fn rename_module_const(&self, mut decl: ModuleConstDecl) -> ModuleConstDecl {
+        decl.namespace = self.namespace(&decl.namespace);
+        decl.qualified_name = self.qualified(&decl.qualified_name);
+        self.rename_type(&mut decl.r#type);
+        self.expr_optional(&mut decl.initial_value_expr);
+        decl
+    }
+
+    fn rename_type(&self, script_type: &mut ScriptType) {
+        match script_type {
+            ScriptType::Enum { type_name, .. } => *type_name = self.qualified(type_name),
+            ScriptType::Object { type_name, fields } => {
+                *type_name = self.qualified(type_name);
+                for field in fields.values_mut() {
+                    self.rename_type(field);
+                }
+            }
+            ScriptType::Array { element_type } => self.rename_type(element_type),
+            ScriptType::Map {
+                key_type,
+                value_type,
+            } => {
+                if let MapKeyType::Enum { type_name, .. } = key_type {
+                    *type_name = self.qualified(type_name);
+                }
+                self.rename_type(value_type);
+            }
+            ScriptType::Primitive { .. } | ScriptType::Script | ScriptType::Function => {}
+        }
+    }
+
+    fn template(&self, template: &str) -> String {
+        template_expr_regex()
+            .replace_all(template, |captures: &regex::Captures<'_>| {
+                match captures.get(1) {
+                    Some(expr) => format!("${{{}}}", self.expr(expr.as_str())),
+                    None => captures[0].to_string(),
+                }
+            })
+            .into_owned()
+    }
+
+    fn expr_optional(&self, expr: &mut Option<String>) {
+        if let Some(expr) = expr {
+            *expr = self.expr(expr);
+        }
+    }
+
+    /// Rewrite identifiers outside of strings and comments, and name literals inside strings.
+    fn expr(&self, source: &str) -> String {
+        let chars = source.chars().collect::<Vec<_>>();
+        let mut out = String::with_capacity(source.len());
+        let mut index = 0;
+        while index < chars.len() {
+            let ch = chars[index];
+            if ch == '"' || ch == '`' {
+                let start = index + 1;
+                let mut end = start;
+                while end < chars.len() && chars[end] != ch {
+                    end += if chars[end] == '\\' { 2 } else { 1 };
+                }
+                let end = end.min(chars.len());
+                let content = chars[start..end].iter().collect::<String>();
+                out.push(ch);
+                out.push_str(&self.literal(&content));
+                if end < chars.len() {
+                    out.push(ch);
+                }
+                index = end + 1;
+            } else if ch == '/' && chars.get(index + 1) == Some(&'/') {
+                while index < chars.len() && chars[index] != '\n' {
+                    out.push(chars[index]);
+                    index += 1;
+                }
+            } else if ch == '/' && chars.get(index + 1) == Some(&'*') {
+                let close = (index + 2..chars.len().saturating_sub(1))
+                    .find(|at| chars[*at] == '*' && chars[*at + 1] == '/')
+                    .map_or(chars.len(), |at| at + 2);
+                out.extend(&chars[index..close]);
+                index = close;
+            } else if ch.is_ascii_alphanumeric() || ch == '_' {
+                let start = index;
+                while index < chars.len()
+                    && (chars[index].is_ascii_alphanumeric() || chars[index] == '_')
+                {
+                    index += 1;
+                }
+                let word = chars[start..index].iter().collect::<String>();
+                let after_dot = chars[..start]
+                    .iter()
+                    .rev()
+                    .find(|ch| !ch.is_whitespace())
+                    .is_some_and(|ch| *ch == '.');
+                match self.identifiers.get(&word) {
+                    Some(renamed) if !after_dot && !ch.is_ascii_digit() => out.push_str(renamed),
+                    _ => out.push_str(&word),
+                }
+            } else {
+                out.push(ch);
+                index += 1;
+            }
+        }
+        out
+    }
+
+    fn literal(&self, content: &str) -> String {
+        if let Some(script) = content.strip_prefix('@') {
+            if self.script_names.contains(script) {
+                return format!("@{}", self.script_name(script));
+            }
+        } else if let Some(function) = content.strip_prefix('*') {
+            if self.function_names.contains(function) {
+                return format!("*{}", self.qualified(function));
+            }
+        } else if self.script_names.contains(content) {
+            return self.script_name(content);
+        }
+        content.to_string()
+    }
+}
+
+#[cfg(test)]
+mod merge_tests {
+    use super::*;
+
+    fn bundle(label: &str) -> CompileProjectBundleResult {
+        let main = format!(
+            r#"
+<!-- import shared from shared.xml -->
+<module name="main" export="script:main;var:count;function:bump">
+  <var name="count" type="int">0</var>
+  <function name="bump" args="int:n" return_type="int">
+    count = count + n;
+    return count;
+  </function>
+  <script name="main">
+    <temp name="next" type="script">@shared.hello</temp>
+    <code>bump(2);</code>
+    <text>{label} ${{count}} ${{__script__}}</text>
+    <call script="@shared.hello"/>
+    <call script="next"/>
+    <end/>
+  </script>
+</module>"#
+        );
+        let shared = format!(
+            r#"<module name="shared" export="script:hello"><script name="hello" kind="call"><text>{label} hello</text></script></module>"#
+        );
+        compile_project_bundle_from_xml_map(&compiler_test_support::map(&[
+            ("main.xml", &main),
+            ("shared.xml", &shared),
+        ]))
+        .expect("compile")
+    }
+
+    #[test]
+    fn merged_bundles_keep_each_main_and_its_references_apart() {
+        let merged = merge_bundles(vec![
+            ("a".to_string(), bundle("first")),
+            ("b".to_string(), bundle("second")),
+        ])
+        .expect("merge");
+
+        assert_eq!(
+            merged.scripts.keys().collect::<Vec<_>>(),
+            vec![
+                "a.main.main",
+                "a.shared.hello",
+                "b.main.main",
+                "b.shared.hello"
+            ]
+        );
+        assert_eq!(
+            merged.module_var_init_order,
+            vec!["a.main.count", "b.main.count"]
+        );
+        let main = &merged.scripts["a.main.main"];
+        assert_eq!(main.script_path, "a/main.xml");
+        assert!(main.groups.contains_key(&main.root_group_id));
+        assert!(main.root_group_id.starts_with("a/"));
+        let code = main.groups[&main.root_group_id]
+            .nodes
+            .iter()
+            .find_map(|node| match node {
+                ScriptNode::Code { code, .. } => Some(code.clone()),
+                _ => None,
+            })
+            .expect("code node");
+        assert!(
+            code.contains(&rhai_function_symbol("a.main.bump")),
+            "{code}"
+        );
+        let bump = &main.visible_functions["a.main.bump"];
+        assert!(
+            bump.code.contains(&module_namespace_symbol("a.main")),
+            "{}",
+            bump.code
+        );
+    }
+
+    /// A bundle of scripts outside any module, as hand-built IR has them: `main` calls `helper`.
+    fn plain_bundle() -> CompileProjectBundleResult {
+        let mut bundle = compile_project_bundle_from_xml_map(&compiler_test_support::map(&[(
+            "main.xml",
+            r#"<module name="main" export="script:main;script:helper">
+<script name="main"><temp name="next" type="script">@helper</temp><call script="@helper"/><call script="next"/><end/></script>
+<script name="helper" kind="call"><text label="hi">hi</text></script>
+</module>"#,
+        )]))
+        .expect("compile");
+        let scripts = std::mem::take(&mut bundle.scripts);
+        for mut script in scripts.into_values() {
+            script.script_name = script.local_script_name.clone().expect("local name");
+            script.module_name = None;
+            for group in script.groups.values_mut() {
+                for node in &mut group.nodes {
+                    match node {
+                        ScriptNode::Call {
+                            target_script: ScriptTarget::Literal { script_name },
+                            ..
+                        } => *script_name = "helper".to_string(),
+                        ScriptNode::Var { declaration, .. } => {
+                            declaration.initial_value_expr = declaration
+                                .initial_value_expr
+                                .as_ref()
+                                .map(|expr| expr.replace("main.helper", "helper"));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            bundle.scripts.insert(script.script_name.clone(), script);
+        }
+        bundle
+    }
+
+    #[test]
+    fn merge_prefixes_scripts_outside_modules() {
+        let first = plain_bundle();
+        assert_eq!(
+            first.scripts.keys().collect::<Vec<_>>(),
+            vec!["helper", "main"]
+        );
+        let merged = merge_bundles(vec![
+            ("a".to_string(), first),
+            ("b".to_string(), plain_bundle()),
+        ])
+        .expect("merge");
+
+        assert_eq!(
+            merged.scripts.keys().collect::<Vec<_>>(),
+            vec!["a.helper", "a.main", "b.helper", "b.main"]
+        );
+        let main = &merged.scripts["b.main"];
+        let nodes = &main.groups[&main.root_group_id].nodes;
+        let initial = nodes
+            .iter()
+            .find_map(|node| match node {
+                ScriptNode::Var { declaration, .. } => declaration.initial_value_expr.clone(),
+                _ => None,
+            })
+            .expect("temp initializer");
+        assert!(initial.contains("b.helper"), "{initial}");
+        let targets = nodes
+            .iter()
+            .filter_map(|node| match node {
+                ScriptNode::Call { target_script, .. } => Some(target_script.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            targets[0],
+            ScriptTarget::Literal {
+                script_name: "b.helper".to_string()
+            }
+        );
+        assert!(matches!(&targets[1], ScriptTarget::Variable { .. }));
+        assert!(merged.scripts["a.helper"].node_labels["hi"].starts_with("a/"));
+    }
+
+    #[test]
+    fn merge_rejects_names_already_in_the_merged_bundle() {
+        let mut duplicated = plain_bundle();
+        let helper = duplicated.scripts["helper"].clone();
+        duplicated.scripts.insert("helper copy".to_string(), helper);
+        let error = merge_bundles(vec![("a".to_string(), duplicated)]).expect_err("conflict");
+        assert_eq!(error.code, "MERGE_NAME_CONFLICT");
+        assert!(error.message.contains("a/"), "{}", error.message);
+    }
+
+    #[test]
+    fn merge_rejects_bad_prefixes_and_unresolved_references() {
+        let error = merge_bundles(vec![("a.b".to_string(), bundle("x"))]).expect_err("invalid");
+        assert_eq!(error.code, "MERGE_PREFIX_INVALID");
+
+        let error = merge_bundles(vec![
+            ("a".to_string(), bundle("x")),
+            ("a".to_string(), bundle("y")),
+        ])
+        .expect_err("duplicate");
+        assert_eq!(error.code, "MERGE_PREFIX_DUPLICATE");
+
+        let mut partial = bundle("x");
+        partial.scripts.remove("shared.hello");
+        let error = merge_bundles(vec![("a".to_string(), partial)]).expect_err("unresolved");
+        assert_eq!(error.code, "MERGE_REFERENCE_UNRESOLVED");
+        assert!(error.message.contains("shared.hello"), "{}", error.message);
+    }
+}
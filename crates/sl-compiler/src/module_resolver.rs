@@ -71,7 +71,7 @@ fn parse_module_source(
 
     if root.name != "module" {
         return Err(ScriptLangError::with_span(
-            "XML_ROOT_INVALID",
+            ErrorCode::XmlRootInvalid,
             format!(
                 "Expected <module> root in file \"{}\", got <{}>.",
                 file_path, root.name
@@ -159,7 +159,7 @@ fn parse_module_child(
         }
         _ => Err(with_file_context(
             ScriptLangError::with_span(
-                "XML_MODULE_CHILD_INVALID",
+                ErrorCode::XmlModuleChildInvalid,
                 format!("Unsupported child <{}> under <module>.", child.name),
                 child.location.clone(),
             ),
@@ -258,7 +258,7 @@ fn validate_module_segment_name(name: &str, span: SourceSpan) -> Result<(), Scri
         return Ok(());
     }
     Err(ScriptLangError::with_span(
-        "NAME_IDENTIFIER_INVALID",
+        ErrorCode::NameIdentifierInvalid,
         format!(
             "Name \"{}\" for module must be a single identifier segment (letters/digits/underscore/hyphen, no dot).",
             name
@@ -397,14 +397,14 @@ fn parse_module_export_targets(
         let group = group.trim();
         if group.is_empty() {
             return Err(ScriptLangError::with_span(
-                "XML_EXPORT_INVALID",
+                ErrorCode::XmlExportInvalid,
                 "Attribute \"export\" contains an empty group.",
                 root.location.clone(),
             ));
         }
         let Some((kind_raw, names_raw)) = group.split_once(':') else {
             return Err(ScriptLangError::with_span(
-                "XML_EXPORT_INVALID",
+                ErrorCode::XmlExportInvalid,
                 format!(
                     "Attribute \"export\" group \"{}\" must be in \"kind:name1,name2\" format.",
                     group
@@ -416,7 +416,7 @@ fn parse_module_export_targets(
         let names_raw = names_raw.trim();
         if names_raw.is_empty() {
             return Err(ScriptLangError::with_span(
-                "XML_EXPORT_INVALID",
+                ErrorCode::XmlExportInvalid,
                 format!(
                     "Attribute \"export\" group \"{}\" must include at least one name.",
                     group
@@ -428,7 +428,7 @@ fn parse_module_export_targets(
             let name = name.trim();
             if name.is_empty() {
                 return Err(ScriptLangError::with_span(
-                    "XML_EXPORT_INVALID",
+                    ErrorCode::XmlExportInvalid,
                     format!(
                         "Attribute \"export\" group \"{}\" contains an empty name.",
                         group
@@ -446,7 +446,7 @@ fn parse_module_export_targets(
                 "enum" => targets.enums.insert(name.to_string()),
                 _ => {
                     return Err(ScriptLangError::with_span(
-                        "XML_EXPORT_KIND_INVALID",
+                        ErrorCode::XmlExportKindInvalid,
                         format!(
                             "Unsupported export kind \"{}\". Allowed kinds: module/script/function/var/const/type/enum.",
                             kind
@@ -457,7 +457,7 @@ fn parse_module_export_targets(
             };
             if !inserted {
                 return Err(ScriptLangError::with_span(
-                    "XML_EXPORT_DUPLICATE",
+                    ErrorCode::XmlExportDuplicate,
                     format!(
                         "Duplicate export entry \"{}:{}\" in module \"export\".",
                         kind, name
@@ -594,7 +594,7 @@ fn validate_export_names(
             continue;
         }
         return Err(ScriptLangError::with_span(
-            "XML_EXPORT_TARGET_NOT_FOUND",
+            ErrorCode::XmlExportTargetNotFound,
             format!(
                 "Export target \"{}:{}\" does not exist in this module.",
                 kind, name
@@ -662,7 +662,7 @@ fn collect_module_explicit_visible_symbol_aliases(
                     continue;
                 }
                 return Err(ScriptLangError::new(
-                    "ALIAS_NAME_CONFLICT",
+                    ErrorCode::AliasNameConflict,
                     format!(
                         "Alias \"{}\" points to both \"{}\" and \"{}\".",
                         alias, existing_target, target
@@ -691,7 +691,7 @@ fn merge_namespace_module_symbol_aliases(
                     continue;
                 }
                 return Err(ScriptLangError::new(
-                    "ALIAS_NAME_CONFLICT",
+                    ErrorCode::AliasNameConflict,
                     format!(
                         "Alias \"{}\" points to both \"{}\" and \"{}\".",
                         alias, existing_target, target
@@ -916,7 +916,7 @@ fn validate_xml_object_initializer_fields(
 ) -> Result<(), ScriptLangError> {
     let Some(entries) = parse_static_map_literal_entries(expr) else {
         return Err(ScriptLangError::with_span(
-            "XML_INIT_XML_OBJECT_INVALID",
+            ErrorCode::XmlInitXmlObjectInvalid,
             "Object initializer in xml format must compile to a static map literal.",
             span.clone(),
         ));
@@ -925,14 +925,14 @@ fn validate_xml_object_initializer_fields(
     for entry in entries {
         let Some(key_expr) = extract_map_literal_key_expr(&entry) else {
             return Err(ScriptLangError::with_span(
-                "XML_INIT_XML_OBJECT_INVALID",
+                ErrorCode::XmlInitXmlObjectInvalid,
                 "Object initializer entry must be in \"field: expr\" format.",
                 span.clone(),
             ));
         };
         let Some(key) = decode_static_map_key(key_expr) else {
             return Err(ScriptLangError::with_span(
-                "XML_INIT_XML_OBJECT_INVALID",
+                ErrorCode::XmlInitXmlObjectInvalid,
                 format!(
                     "Object field key \"{}\" must be a static identifier.",
                     key_expr
@@ -942,14 +942,14 @@ fn validate_xml_object_initializer_fields(
         };
         if !fields.contains_key(&key) {
             return Err(ScriptLangError::with_span(
-                "XML_INIT_XML_FIELD_UNKNOWN",
+                ErrorCode::XmlInitXmlFieldUnknown,
                 format!("Field \"{}\" does not exist on target object type.", key),
                 span.clone(),
             ));
         }
         if !seen.insert(key.clone()) {
             return Err(ScriptLangError::with_span(
-                "XML_INIT_XML_FIELD_DUPLICATE",
+                ErrorCode::XmlInitXmlFieldDuplicate,
                 format!("Field \"{}\" appears more than once.", key),
                 span.clone(),
             ));
@@ -960,7 +960,7 @@ fn validate_xml_object_initializer_fields(
             continue;
         }
         return Err(ScriptLangError::with_span(
-            "XML_INIT_XML_FIELD_MISSING",
+            ErrorCode::XmlInitXmlFieldMissing,
             format!(
                 "Missing field \"{}\" in xml object initializer.",
                 field_name
@@ -980,7 +980,7 @@ fn normalize_xml_enum_map_initializer_keys(
 ) -> Result<String, ScriptLangError> {
     let Some(entries) = parse_static_map_literal_entries(expr) else {
         return Err(ScriptLangError::with_span(
-            "XML_INIT_XML_ENUM_MAP_INVALID",
+            ErrorCode::XmlInitXmlEnumMapInvalid,
             "Enum map initializer in xml format must compile to a static map literal.",
             span.clone(),
         ));
@@ -989,7 +989,7 @@ fn normalize_xml_enum_map_initializer_keys(
     for entry in entries {
         let Some(key_expr_raw) = extract_map_literal_key_expr(&entry) else {
             return Err(ScriptLangError::with_span(
-                "XML_INIT_XML_ENUM_MAP_INVALID",
+                ErrorCode::XmlInitXmlEnumMapInvalid,
                 "Enum map initializer entry must be in \"key: expr\" format.",
                 span.clone(),
             ));
@@ -997,7 +997,7 @@ fn normalize_xml_enum_map_initializer_keys(
         let key_expr = key_expr_raw.trim();
         let Some((_, value_expr)) = entry.split_once(':') else {
             return Err(ScriptLangError::with_span(
-                "XML_INIT_XML_ENUM_MAP_INVALID",
+                ErrorCode::XmlInitXmlEnumMapInvalid,
                 "Enum map initializer entry must be in \"key: expr\" format.",
                 span.clone(),
             ));
@@ -1076,7 +1076,7 @@ fn validate_module_initializer_visibility(
         }
 
         return Err(ScriptLangError::with_span(
-            "MODULE_SYMBOL_NOT_VISIBLE",
+            ErrorCode::ModuleSymbolNotVisible,
             format!(
                 "Module global \"{}\" is not visible in namespace \"{}\".",
                 token, visibility.local_namespace
@@ -1096,7 +1096,7 @@ fn normalize_module_initializer(
     let Some(expr) = expr.as_ref() else {
         if matches!(resolved_type, ScriptType::Enum { .. }) {
             return Err(ScriptLangError::with_span(
-                "ENUM_INIT_REQUIRED",
+                ErrorCode::EnumInitRequired,
                 "Enum declaration requires explicit Type.Member initializer.",
                 context.span.clone(),
             ));
@@ -1194,6 +1194,7 @@ pub(crate) fn parse_module_var_declaration(
         initial_value_format: parsed.initial_value_format,
         initial_value_expr: parsed.initial_value_expr,
         location: parsed.location,
+        session: parse_bool_attr(node, "session", false)?,
     })
 }
 
@@ -1231,7 +1232,7 @@ fn parse_module_binding_declaration(
         InitializerFormat::Inline => {
             if let Some(child) = element_children(node).next() {
                 return Err(ScriptLangError::with_span(
-                    "XML_VAR_CHILD_INVALID",
+                    ErrorCode::XmlVarChildInvalid,
                     format!(
                         "<{}> cannot contain child element <{}>. Use inline expression text only.",
                         tag_name, child.name
@@ -1260,6 +1261,7 @@ fn parse_module_binding_declaration(
         initial_value_format,
         initial_value_expr,
         location: node.location.clone(),
+        session: false,
     })
 }
 
@@ -1268,14 +1270,14 @@ pub(crate) fn parse_global_data_symbol(file_path: &str) -> Result<String, Script
     let path = Path::new(file_path);
     let Some(stem) = path.file_stem().and_then(|value| value.to_str()) else {
         return Err(ScriptLangError::new(
-            "GLOBAL_DATA_SYMBOL_INVALID",
+            ErrorCode::GlobalDataSymbolInvalid,
             format!("Invalid global data file name: {}", file_path),
         ));
     };
 
     if !global_data_symbol_regex().is_match(stem) {
         return Err(ScriptLangError::new(
-            "GLOBAL_DATA_SYMBOL_INVALID",
+            ErrorCode::GlobalDataSymbolInvalid,
             format!(
                 "global data basename \"{}\" is not a valid identifier.",
                 stem
@@ -1353,7 +1355,7 @@ pub(crate) fn resolve_visible_module_symbols_with_aliases_and_module_scoped_type
             }
             if type_decls_map.contains_key(&decl.qualified_name) {
                 return Err(ScriptLangError::with_span(
-                    "TYPE_DECL_DUPLICATE",
+                    ErrorCode::TypeDeclDuplicate,
                     format!("Duplicate type declaration \"{}\".", decl.qualified_name),
                     decl.location.clone(),
                 ));
@@ -1482,7 +1484,7 @@ pub(crate) fn resolve_visible_module_symbols_with_aliases_and_module_scoped_type
         for decl in &module.function_decls {
             if !visible_function_names.insert(decl.qualified_name.clone()) {
                 return Err(ScriptLangError::with_span(
-                    "FUNCTION_DECL_DUPLICATE",
+                    ErrorCode::FunctionDeclDuplicate,
                     format!(
                         "Duplicate function declaration \"{}\".",
                         decl.qualified_name
@@ -1695,7 +1697,7 @@ pub(crate) fn resolve_visible_module_symbols_with_aliases_and_module_scoped_type
             );
             if module_vars_qualified.contains_key(&decl.qualified_name) {
                 return Err(ScriptLangError::with_span(
-                    "MODULE_GLOBAL_VAR_DUPLICATE",
+                    ErrorCode::ModuleGlobalVarDuplicate,
                     format!(
                         "Duplicate module global variable declaration \"{}\".",
                         decl.qualified_name
@@ -1741,6 +1743,7 @@ pub(crate) fn resolve_visible_module_symbols_with_aliases_and_module_scoped_type
                     r#type: resolved_type,
                     initial_value_expr,
                     location: decl.location.clone(),
+                    session: decl.session,
                 }
             });
             if is_local {
@@ -1804,7 +1807,7 @@ pub(crate) fn resolve_visible_module_symbols_with_aliases_and_module_scoped_type
             );
             if module_consts_qualified.contains_key(&decl.qualified_name) {
                 return Err(ScriptLangError::with_span(
-                    "MODULE_GLOBAL_CONST_DUPLICATE",
+                    ErrorCode::ModuleGlobalConstDuplicate,
                     format!(
                         "Duplicate module global const declaration \"{}\".",
                         decl.qualified_name
@@ -1956,7 +1959,7 @@ fn collect_explicit_visible_type_aliases(
                 continue;
             }
             return Err(ScriptLangError::new(
-                "ALIAS_NAME_CONFLICT",
+                ErrorCode::AliasNameConflict,
                 format!(
                     "Alias \"{}\" points to both \"{}\" and \"{}\".",
                     alias, existing_target, target
@@ -1987,7 +1990,7 @@ fn apply_explicit_alias_directives(
                 continue;
             }
             return Err(ScriptLangError::new(
-                "ALIAS_NAME_CONFLICT",
+                ErrorCode::AliasNameConflict,
                 format!(
                     "Alias \"{}\" points to both \"{}\" and \"{}\".",
                     alias, existing_target, target
@@ -1998,7 +2001,7 @@ fn apply_explicit_alias_directives(
         if let Some(target_type) = visible_types.get(target).cloned() {
             if visible_types.contains_key(alias) {
                 return Err(ScriptLangError::new(
-                    "ALIAS_NAME_CONFLICT",
+                    ErrorCode::AliasNameConflict,
                     format!(
                         "Alias name \"{}\" conflicts with existing visible type.",
                         alias
@@ -2013,7 +2016,7 @@ fn apply_explicit_alias_directives(
         if let Some(target_var) = visible_module_vars.get(target).cloned() {
             if visible_module_vars.contains_key(alias) {
                 return Err(ScriptLangError::new(
-                    "ALIAS_NAME_CONFLICT",
+                    ErrorCode::AliasNameConflict,
                     format!(
                         "Alias name \"{}\" conflicts with existing visible module variable.",
                         alias
@@ -2028,7 +2031,7 @@ fn apply_explicit_alias_directives(
         if let Some(target_const) = visible_module_consts.get(target).cloned() {
             if visible_module_consts.contains_key(alias) {
                 return Err(ScriptLangError::new(
-                    "ALIAS_NAME_CONFLICT",
+                    ErrorCode::AliasNameConflict,
                     format!(
                         "Alias name \"{}\" conflicts with existing visible module constant.",
                         alias
@@ -2042,7 +2045,7 @@ fn apply_explicit_alias_directives(
 
         if visible_functions.contains_key(target) {
             return Err(ScriptLangError::new(
-                "ALIAS_TARGET_KIND_UNSUPPORTED",
+                ErrorCode::AliasTargetKindUnsupported,
                 format!(
                     "Alias target \"{}\" is a function. Alias only supports type/module var/module const.",
                     target
@@ -2051,7 +2054,7 @@ fn apply_explicit_alias_directives(
         }
 
         return Err(ScriptLangError::new(
-            "ALIAS_TARGET_NOT_FOUND",
+            ErrorCode::AliasTargetNotFound,
             format!(
                 "Alias target \"{}\" is not visible in current module closure.",
                 target
@@ -2073,7 +2076,7 @@ pub(crate) fn collect_functions_for_bundle_with_aliases(
         for decl in &module.type_decls {
             if type_decls_map.contains_key(&decl.qualified_name) {
                 return Err(ScriptLangError::with_span(
-                    "TYPE_DECL_DUPLICATE",
+                    ErrorCode::TypeDeclDuplicate,
                     format!("Duplicate type declaration \"{}\".", decl.qualified_name),
                     decl.location.clone(),
                 ));
@@ -2165,7 +2168,7 @@ pub(crate) fn collect_functions_for_bundle_with_aliases(
         for decl in &module.function_decls {
             if !visible_function_names.insert(decl.qualified_name.clone()) {
                 return Err(ScriptLangError::with_span(
-                    "FUNCTION_DECL_DUPLICATE",
+                    ErrorCode::FunctionDeclDuplicate,
                     format!(
                         "Duplicate function declaration \"{}\".",
                         decl.qualified_name
@@ -2305,7 +2308,7 @@ pub(crate) fn collect_module_vars_for_bundle_with_aliases(
         for decl in &module.type_decls {
             if type_decls_map.contains_key(&decl.qualified_name) {
                 return Err(ScriptLangError::with_span(
-                    "TYPE_DECL_DUPLICATE",
+                    ErrorCode::TypeDeclDuplicate,
                     format!("Duplicate type declaration \"{}\".", decl.qualified_name),
                     decl.location.clone(),
                 ));
@@ -2389,7 +2392,7 @@ pub(crate) fn collect_module_vars_for_bundle_with_aliases(
         for decl in &module.module_global_var_decls {
             if module_vars.contains_key(&decl.qualified_name) {
                 return Err(ScriptLangError::with_span(
-                    "MODULE_GLOBAL_VAR_DUPLICATE",
+                    ErrorCode::ModuleGlobalVarDuplicate,
                     format!(
                         "Duplicate module global variable declaration \"{}\".",
                         decl.qualified_name
@@ -2445,6 +2448,7 @@ pub(crate) fn collect_module_vars_for_bundle_with_aliases(
                     r#type: resolved_type,
                     initial_value_expr,
                     location: decl.location.clone(),
+                    session: decl.session,
                 }
             });
             init_order.push(decl.qualified_name.clone());
@@ -2469,7 +2473,7 @@ pub(crate) fn collect_module_consts_for_bundle_with_aliases(
         for decl in &module.type_decls {
             if type_decls_map.contains_key(&decl.qualified_name) {
                 return Err(ScriptLangError::with_span(
-                    "TYPE_DECL_DUPLICATE",
+                    ErrorCode::TypeDeclDuplicate,
                     format!("Duplicate type declaration \"{}\".", decl.qualified_name),
                     decl.location.clone(),
                 ));
@@ -2553,7 +2557,7 @@ pub(crate) fn collect_module_consts_for_bundle_with_aliases(
         for decl in &module.module_global_const_decls {
             if module_consts.contains_key(&decl.qualified_name) {
                 return Err(ScriptLangError::with_span(
-                    "MODULE_GLOBAL_CONST_DUPLICATE",
+                    ErrorCode::ModuleGlobalConstDuplicate,
                     format!(
                         "Duplicate module global const declaration \"{}\".",
                         decl.qualified_name
@@ -2674,7 +2678,7 @@ pub(crate) fn validate_module_var_init_order(
                 }
                 if !initialized.contains(target_qualified) {
                     return Err(ScriptLangError::with_span(
-                        "MODULE_GLOBAL_INIT_ORDER",
+                        ErrorCode::ModuleGlobalInitOrder,
                         format!(
                             "Module global \"{}\" initializer references \"{}\" before initialization.",
                             qualified, name
@@ -2764,7 +2768,7 @@ pub(crate) fn validate_module_const_init_rules(
             for (name, target_qualified) in &var_name_to_qualified {
                 if contains_module_global_ref(&sanitized, name, target_qualified) {
                     return Err(ScriptLangError::with_span(
-                        "MODULE_CONST_INIT_REF_NON_CONST",
+                        ErrorCode::ModuleConstInitRefNonConst,
                         format!(
                             "Module const \"{}\" initializer references mutable module global \"{}\".",
                             qualified, name
@@ -2779,7 +2783,7 @@ pub(crate) fn validate_module_const_init_rules(
                 }
                 if !initialized.contains(target_qualified) {
                     return Err(ScriptLangError::with_span(
-                        "MODULE_CONST_INIT_ORDER",
+                        ErrorCode::ModuleConstInitOrder,
                         format!(
                             "Module const \"{}\" initializer references \"{}\" before initialization.",
                             qualified, name
@@ -2956,6 +2960,7 @@ mod module_resolver_tests {
                 initial_value_format: InitializerFormat::Inline,
                 initial_value_expr: Some("Status.Unknown".to_string()),
                 location: span.clone(),
+                session: false,
             }],
             module_global_const_decls: Vec::new(),
         };
@@ -3212,6 +3217,7 @@ mod module_resolver_tests {
             initial_value_format: InitializerFormat::Inline,
             initial_value_expr: None,
             location: span.clone(),
+            session: false,
         };
 
         let unique_modules = BTreeMap::from([(
@@ -3325,6 +3331,7 @@ mod module_resolver_tests {
                         initial_value_format: InitializerFormat::Inline,
                         initial_value_expr: Some("1".to_string()),
                         location: span.clone(),
+                        session: false,
                     },
                     ParsedModuleVarDecl {
                         namespace: "shared".to_string(),
@@ -3335,6 +3342,7 @@ mod module_resolver_tests {
                         initial_value_format: InitializerFormat::Inline,
                         initial_value_expr: Some("2".to_string()),
                         location: span.clone(),
+                        session: false,
                     },
                 ],
                 module_global_const_decls: vec![ParsedModuleConstDecl {
@@ -3913,6 +3921,7 @@ mod module_resolver_tests {
             initial_value_format: InitializerFormat::Inline,
             initial_value_expr: Some("1".to_string()),
             location: span.clone(),
+            session: false,
         };
         let module_by_path = BTreeMap::from([
             (
@@ -4299,6 +4308,7 @@ mod module_resolver_tests {
                     initial_value_format: InitializerFormat::Inline,
                     initial_value_expr: None,
                     location: span.clone(),
+                    session: false,
                 }],
                 module_global_const_decls: Vec::new(),
             },
@@ -4346,6 +4356,7 @@ mod module_resolver_tests {
                     initial_value_format: InitializerFormat::Inline,
                     initial_value_expr: None,
                     location: span.clone(),
+                    session: false,
                 }],
                 module_global_const_decls: Vec::new(),
             },
@@ -4548,6 +4559,7 @@ mod module_resolver_tests {
                     initial_value_format: InitializerFormat::Inline,
                     initial_value_expr: None,
                     location: span.clone(),
+                    session: false,
                 }],
                 module_global_const_decls: Vec::new(),
             },
@@ -4938,6 +4950,7 @@ mod module_resolver_tests {
                 },
                 initial_value_expr: Some("1".to_string()),
                 location: span.clone(),
+                session: false,
             },
         )]);
         let duplicate = BTreeMap::from([
@@ -5312,6 +5325,7 @@ mod module_resolver_tests {
                 },
                 initial_value_expr: Some("100".to_string()),
                 location: span.clone(),
+                session: false,
             },
         )]);
 
@@ -5487,6 +5501,7 @@ mod module_resolver_tests {
                     initial_value_format: InitializerFormat::Inline,
                     initial_value_expr: None,
                     location: span.clone(),
+                    session: false,
                 },
                 ParsedModuleVarDecl {
                     namespace: "shared".to_string(),
@@ -5497,6 +5512,7 @@ mod module_resolver_tests {
                     initial_value_format: InitializerFormat::Inline,
                     initial_value_expr: None,
                     location: span.clone(),
+                    session: false,
                 },
             ],
             module_global_const_decls: vec![],
@@ -5635,6 +5651,7 @@ mod module_resolver_tests {
                 initial_value_format: InitializerFormat::Inline,
                 initial_value_expr: Some("FollowupPhase.Phase2".to_string()),
                 location: span.clone(),
+                session: false,
             }],
             module_global_const_decls: Vec::new(),
         };
@@ -5652,6 +5669,7 @@ mod module_resolver_tests {
                 initial_value_format: InitializerFormat::Inline,
                 initial_value_expr: Some("FollowupPhase.Phase3".to_string()),
                 location: span.clone(),
+                session: false,
             }],
             module_global_const_decls: Vec::new(),
         };
@@ -6543,6 +6561,7 @@ mod module_resolver_tests {
                     initial_value_format: InitializerFormat::Inline,
                     initial_value_expr: None,
                     location: span.clone(),
+                    session: false,
                 },
                 ParsedModuleVarDecl {
                     namespace: "shared".to_string(),
@@ -6553,6 +6572,7 @@ mod module_resolver_tests {
                     initial_value_format: InitializerFormat::Inline,
                     initial_value_expr: None,
                     location: span.clone(),
+                    session: false,
                 },
             ],
             module_global_const_decls: vec![],
@@ -6608,6 +6628,7 @@ mod module_resolver_tests {
                     initial_value_format: InitializerFormat::Inline,
                     initial_value_expr: None,
                     location: span.clone(),
+                    session: false,
                 },
                 ParsedModuleVarDecl {
                     namespace: "shared".to_string(),
@@ -6618,6 +6639,7 @@ mod module_resolver_tests {
                     initial_value_format: InitializerFormat::Inline,
                     initial_value_expr: None,
                     location: span.clone(),
+                    session: false,
                 },
             ],
             module_global_const_decls: vec![],
@@ -6666,6 +6688,7 @@ mod module_resolver_tests {
                 initial_value_format: InitializerFormat::Inline,
                 initial_value_expr: None,
                 location: span.clone(),
+                session: false,
             }],
             module_global_const_decls: vec![],
         };
@@ -6722,6 +6745,7 @@ mod module_resolver_tests {
                 initial_value_format: InitializerFormat::Inline,
                 initial_value_expr: None,
                 location: span.clone(),
+                session: false,
             }],
             module_global_const_decls: vec![],
         };
@@ -6949,6 +6973,7 @@ mod module_resolver_tests {
                 initial_value_format: InitializerFormat::Inline,
                 initial_value_expr: None,
                 location: span.clone(),
+                session: false,
             }],
             // This creates namespace alias: shared -> other -> shared.other
             module_global_const_decls: vec![ParsedModuleConstDecl {
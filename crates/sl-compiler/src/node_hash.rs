@@ -0,0 +1,148 @@
+use crate::*;
+
+/// Fill `ImplicitGroup::node_hashes` for every group once the script IR is final.
+///
+/// A node hash covers the node kind, its attributes and expressions, and (recursively) the
+/// contents of the groups it owns. Node ids, group ids and source spans are left out, so adding
+/// or moving unrelated nodes does not change it, while editing the node's own text or the body of
+/// one of its branches/options does.
+pub(crate) fn assign_node_hashes(scripts: &mut BTreeMap<String, ScriptIr>) {
+    for script in scripts.values_mut() {
+        let mut memo = BTreeMap::new();
+        let hashes = script
+            .groups
+            .iter()
+            .map(|(group_id, group)| {
+                let node_hashes = group
+                    .nodes
+                    .iter()
+                    .map(|node| format!("{:016x}", node_hash(node, &script.groups, &mut memo)))
+                    .collect::<Vec<_>>();
+                (group_id.clone(), node_hashes)
+            })
+            .collect::<BTreeMap<_, _>>();
+        for (group_id, node_hashes) in hashes {
+            if let Some(group) = script.groups.get_mut(&group_id) {
+                group.node_hashes = node_hashes;
+            }
+        }
+    }
+}
+
+fn group_hash(
+    groups: &BTreeMap<String, ImplicitGroup>,
+    group_id: &str,
+    memo: &mut BTreeMap<String, u64>,
+) -> u64 {
+    if let Some(hash) = memo.get(group_id) {
+        return *hash;
+    }
+    let parts = groups
+        .get(group_id)
+        .map(|group| {
+            group
+                .nodes
+                .iter()
+                .map(|node| format!("{:016x}", node_hash(node, groups, memo)))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let hash = fnv1a_64(&parts);
+    memo.insert(group_id.to_string(), hash);
+    hash
+}
+
+fn node_hash(
+    node: &ScriptNode,
+    groups: &BTreeMap<String, ImplicitGroup>,
+    memo: &mut BTreeMap<String, u64>,
+) -> u64 {
+    let mut value = serde_json::to_value(node).expect("script node should serialize");
+    canonicalize(&mut value, groups, memo);
+    fnv1a_64(&[value.to_string()])
+}
+
+/// Drop ids and spans and replace owned group ids with the hash of the group's contents.
+fn canonicalize(
+    value: &mut serde_json::Value,
+    groups: &BTreeMap<String, ImplicitGroup>,
+    memo: &mut BTreeMap<String, u64>,
+) {
+    match value {
+        serde_json::Value::Object(fields) => {
+            fields.retain(|key, _| key != "id" && key != "location");
+            for (key, field) in fields.iter_mut() {
+                if key.ends_with("group_id") {
+                    if let serde_json::Value::String(group_id) = field {
+                        *field = serde_json::Value::String(format!(
+                            "{:016x}",
+                            group_hash(groups, group_id, memo)
+                        ));
+                    }
+                } else {
+                    canonicalize(field, groups, memo);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                canonicalize(item, groups, memo);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod node_hash_tests {
+    use super::*;
+
+    fn root_hashes(source: &str) -> Vec<String> {
+        let files = compiler_test_support::map(&[("main.xml", source)]);
+        let bundle = compile_project_bundle_from_xml_map(&files).expect("compile should pass");
+        let script = &bundle.scripts["main.main"];
+        script.groups[&script.root_group_id].node_hashes.clone()
+    }
+
+    #[test]
+    fn node_hashes_follow_content_not_position() {
+        let base = root_hashes(
+            r#"<module name="main" export="script:main">
+<script name="main">
+  <text>A</text>
+  <choice text="Pick">
+    <option text="Go"><text>went</text></option>
+  </choice>
+</script>
+</module>"#,
+        );
+        assert_eq!(base.len(), 2);
+        assert_ne!(base[0], base[1]);
+
+        let shifted = root_hashes(
+            r#"<module name="main" export="script:main">
+<script name="main">
+  <text>new</text>
+  <text>A</text>
+  <choice text="Pick">
+    <option text="Go"><text>went</text></option>
+  </choice>
+</script>
+</module>"#,
+        );
+        assert_eq!(shifted[1..], base[..]);
+
+        let edited_body = root_hashes(
+            r#"<module name="main" export="script:main">
+<script name="main">
+  <text>A</text>
+  <choice text="Pick">
+    <option text="Go"><text>ran</text></option>
+  </choice>
+</script>
+</module>"#,
+        );
+        assert_eq!(edited_body[0], base[0]);
+        assert_ne!(edited_body[1], base[1]);
+    }
+}
@@ -8,6 +8,13 @@ pub fn compile_project_scripts_from_xml_map(
 
 pub fn compile_project_bundle_from_xml_map(
     xml_by_path: &BTreeMap<String, String>,
+) -> Result<CompileProjectBundleResult, ScriptLangError> {
+    compile_project_bundle_from_xml_map_with_options(xml_by_path, CompileOptions::default())
+}
+
+pub fn compile_project_bundle_from_xml_map_with_options(
+    xml_by_path: &BTreeMap<String, String>,
+    options: CompileOptions,
 ) -> Result<CompileProjectBundleResult, ScriptLangError> {
     let sources = parse_sources(xml_by_path)?;
     validate_import_graph(&sources)?;
@@ -28,13 +35,13 @@ pub fn compile_project_bundle_from_xml_map(
         &module_by_path,
         &module_alias_directives_by_namespace,
     )?;
-    let (module_var_declarations, module_var_init_order) =
+    let (mut module_var_declarations, module_var_init_order) =
         collect_module_vars_for_bundle_with_aliases(
             &module_by_path,
             &invoke_all_functions,
             &module_alias_directives_by_namespace,
         )?;
-    let (module_const_declarations, module_const_init_order) =
+    let (mut module_const_declarations, module_const_init_order) =
         collect_module_consts_for_bundle_with_aliases(
             &module_by_path,
             &module_var_declarations,
@@ -78,11 +85,12 @@ pub fn compile_project_bundle_from_xml_map(
                 visible_module_consts: &visible_module_consts,
                 all_script_access: &all_script_access,
                 invoke_all_functions: &script_invoke_all_functions,
+                strict_attributes: options.strict_attributes,
             })
             .map_err(|error| with_file_context(error, file_path))?;
             if scripts.contains_key(&ir.script_name) {
                 return Err(ScriptLangError::with_span(
-                    "SCRIPT_NAME_DUPLICATE",
+                    ErrorCode::ScriptNameDuplicate,
                     format!("Duplicate script name \"{}\".", ir.script_name),
                     script_decl.root.location.clone(),
                 ));
@@ -92,6 +100,22 @@ pub fn compile_project_bundle_from_xml_map(
         }
     }
     validate_static_literal_script_target_rules(&scripts)?;
+    validate_unconditional_call_recursion(&scripts)?;
+    validate_session_var_shadowing(&scripts)?;
+    let bundle_fingerprint = if options.inline_small_calls {
+        inline_small_calls(&mut scripts)
+    } else {
+        None
+    };
+    assign_node_hashes(&mut scripts);
+    // Folded after hashing so saves stay compatible between folded and unfolded builds.
+    if options.fold_constants {
+        fold_constant_expressions(
+            &mut scripts,
+            &mut module_var_declarations,
+            &mut module_const_declarations,
+        );
+    }
 
     Ok(CompileProjectBundleResult {
         scripts,
@@ -100,6 +124,7 @@ pub fn compile_project_bundle_from_xml_map(
         module_var_init_order,
         module_const_declarations,
         module_const_init_order,
+        bundle_fingerprint,
     })
 }
 
@@ -202,7 +227,15 @@ fn validate_static_literal_script_target_rules(
                             script_name,
                             args.len(),
                             location,
-                            "SCRIPT_CALL_ARGS_COUNT_MISMATCH",
+                            ErrorCode::ScriptCallArgsCountMismatch,
+                            "call",
+                        )?;
+                        validate_single_literal_call_arg_types(
+                            scripts,
+                            script_name,
+                            args,
+                            location,
+                            ErrorCode::ScriptCallArgTypeMismatch,
                             "call",
                         )?;
                         validate_single_literal_target_script_kind(
@@ -210,7 +243,7 @@ fn validate_static_literal_script_target_rules(
                             script_name,
                             ScriptKind::Call,
                             location,
-                            "SCRIPT_CALL_TARGET_KIND_MISMATCH",
+                            ErrorCode::ScriptCallTargetKindMismatch,
                             "call",
                         )?;
                     }
@@ -225,7 +258,15 @@ fn validate_static_literal_script_target_rules(
                             script_name,
                             args.len(),
                             location,
-                            "SCRIPT_GOTO_ARGS_COUNT_MISMATCH",
+                            ErrorCode::ScriptGotoArgsCountMismatch,
+                            "goto",
+                        )?;
+                        validate_single_literal_call_arg_types(
+                            scripts,
+                            script_name,
+                            args,
+                            location,
+                            ErrorCode::ScriptGotoArgTypeMismatch,
                             "goto",
                         )?;
                         validate_single_literal_target_script_kind(
@@ -233,7 +274,7 @@ fn validate_static_literal_script_target_rules(
                             script_name,
                             ScriptKind::Goto,
                             location,
-                            "SCRIPT_GOTO_TARGET_KIND_MISMATCH",
+                            ErrorCode::ScriptGotoTargetKindMismatch,
                             "goto",
                         )?;
                     }
@@ -245,12 +286,117 @@ fn validate_static_literal_script_target_rules(
     Ok(())
 }
 
+/// Reject scripts whose root group reaches a static `<call>` back into the same call chain before
+/// any boundary, branch or terminator; such a chain can only end at the runtime depth guard.
+fn validate_unconditional_call_recursion(
+    scripts: &BTreeMap<String, ScriptIr>,
+) -> Result<(), ScriptLangError> {
+    let first_calls = scripts
+        .iter()
+        .filter_map(|(name, script)| {
+            first_unconditional_call(script).map(|call| (name.as_str(), call))
+        })
+        .collect::<BTreeMap<_, _>>();
+    for (&start, &(_, location)) in &first_calls {
+        let mut chain = vec![start];
+        let mut current = start;
+        while let Some(&(next, _)) = first_calls.get(current) {
+            if next == start {
+                chain.push(next);
+                return Err(ScriptLangError::with_span(
+                    ErrorCode::CallInfiniteRecursion,
+                    format!(
+                        "Script \"{}\" calls itself unconditionally ({}) before any choice, input, branch or return.",
+                        start,
+                        chain.join(" -> ")
+                    ),
+                    location.clone(),
+                ));
+            }
+            if chain.contains(&next) {
+                break;
+            }
+            chain.push(next);
+            current = next;
+        }
+    }
+    Ok(())
+}
+
+fn first_unconditional_call(script: &ScriptIr) -> Option<(&str, &SourceSpan)> {
+    let root = script.groups.get(&script.root_group_id)?;
+    for node in &root.nodes {
+        match node {
+            ScriptNode::Text { .. }
+            | ScriptNode::Debug { .. }
+            | ScriptNode::Code { .. }
+            | ScriptNode::Var { .. }
+            | ScriptNode::Checkpoint { .. } => {}
+            ScriptNode::Call {
+                target_script: ScriptTarget::Literal { script_name },
+                location,
+                ..
+            } => return Some((script_name.as_str(), location)),
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Session module vars must resolve to the engine-level store, so no param or `<temp>` may take
+/// a name under which a session var is visible to the script.
+fn validate_session_var_shadowing(
+    scripts: &BTreeMap<String, ScriptIr>,
+) -> Result<(), ScriptLangError> {
+    for script in scripts.values() {
+        let params = script
+            .params
+            .iter()
+            .map(|param| (&param.name, &param.location));
+        let temps = script
+            .groups
+            .values()
+            .flat_map(|group| &group.nodes)
+            .filter_map(|node| match node {
+                ScriptNode::Var {
+                    declaration,
+                    location,
+                    ..
+                } => Some((&declaration.name, location)),
+                _ => None,
+            });
+        for (name, location) in params.chain(temps) {
+            // Reachable by bare name through an alias key or as a var of the script's own module.
+            let Some(decl) = script.visible_module_vars.iter().find_map(|(key, decl)| {
+                let bare = key == name
+                    || (script.module_name.as_deref() == Some(decl.namespace.as_str())
+                        && &decl.name == name);
+                (decl.session && bare).then_some(decl)
+            }) else {
+                continue;
+            };
+            return Err(with_file_context(
+                ScriptLangError::with_span(
+                    ErrorCode::VarSessionShadow,
+                    format!(
+                        "Local \"{}\" in script \"{}\" shadows session var \"{}\".",
+                        name, script.script_name, decl.qualified_name
+                    ),
+                    location.clone(),
+                ),
+                &script.script_path,
+            ));
+        }
+    }
+    Ok(())
+}
+
 fn validate_single_literal_target_script_kind(
     scripts: &BTreeMap<String, ScriptIr>,
     target_script_name: &str,
     expected_kind: ScriptKind,
     location: &SourceSpan,
-    error_code: &str,
+    error_code: ErrorCode,
     label: &str,
 ) -> Result<(), ScriptLangError> {
     let target = scripts
@@ -278,7 +424,7 @@ fn validate_single_literal_call_arg_count(
     target_script_name: &str,
     arg_count: usize,
     location: &SourceSpan,
-    error_code: &str,
+    error_code: ErrorCode,
     label: &str,
 ) -> Result<(), ScriptLangError> {
     let target = scripts
@@ -298,6 +444,73 @@ fn validate_single_literal_call_arg_count(
     Ok(())
 }
 
+/// Reject literal args (`1`, `1.5`, `'text'`, `true`) whose primitive type cannot match the
+/// target param; non-literal args are left to the runtime scope check.
+fn validate_single_literal_call_arg_types(
+    scripts: &BTreeMap<String, ScriptIr>,
+    target_script_name: &str,
+    args: &[CallArgument],
+    location: &SourceSpan,
+    error_code: ErrorCode,
+    label: &str,
+) -> Result<(), ScriptLangError> {
+    let target = scripts
+        .get(target_script_name)
+        .expect("target script must exist");
+    for (arg, param) in args.iter().zip(&target.params) {
+        let ScriptType::Primitive { name: expected } = &param.r#type else {
+            continue;
+        };
+        let Some(actual) = literal_primitive_type(&arg.value_expr) else {
+            continue;
+        };
+        if arg.is_ref || actual == expected || (actual == "int" && expected == "float") {
+            continue;
+        }
+        return Err(ScriptLangError::with_span(
+            error_code,
+            format!(
+                "{} target script \"{}\" param \"{}\" expects {}, got {} literal \"{}\".",
+                label,
+                target_script_name,
+                param.name,
+                expected,
+                actual,
+                arg.value_expr.trim()
+            ),
+            location.clone(),
+        ));
+    }
+    Ok(())
+}
+
+fn literal_primitive_type(expr: &str) -> Option<&'static str> {
+    let expr = expr.trim();
+    let digits = expr.strip_prefix('-').unwrap_or(expr);
+    let is_digits = |value: &str| !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit());
+    if is_digits(digits) {
+        return Some("int");
+    }
+    if let Some((whole, fraction)) = digits.split_once('.') {
+        if is_digits(whole) && is_digits(fraction) {
+            return Some("float");
+        }
+    }
+    if expr == "true" || expr == "false" {
+        return Some("boolean");
+    }
+    let quoted = |quote: char| {
+        expr.len() >= 2
+            && expr.starts_with(quote)
+            && expr.ends_with(quote)
+            && !expr[1..expr.len() - 1].contains(quote)
+    };
+    if quoted('\'') || quoted('"') {
+        return Some("string");
+    }
+    None
+}
+
 pub(crate) fn with_file_context(error: ScriptLangError, file_path: &str) -> ScriptLangError {
     crate::with_file_context_shared(error, file_path)
 }
@@ -330,6 +543,96 @@ mod pipeline_tests {
         assert!(!main.groups.is_empty());
     }
 
+    #[test]
+    fn compile_bundle_with_options_applies_strict_attributes() {
+        let files = map(&[(
+            "main.xml",
+            r#"
+    <module name="main" export="script:main">
+    <script name="main">
+      <choice text="Pick">
+        <option txt="A" text="A"><text>A1</text></option>
+      </choice>
+    </script>
+    </module>
+    "#,
+        )]);
+
+        compile_project_bundle_from_xml_map(&files).expect("default mode ignores extra attrs");
+        let error = compile_project_bundle_from_xml_map_with_options(
+            &files,
+            CompileOptions {
+                strict_attributes: true,
+                ..CompileOptions::default()
+            },
+        )
+        .expect_err("strict mode should reject unknown attrs");
+        assert_eq!(error.code, "XML_UNKNOWN_ATTR");
+    }
+
+    #[test]
+    fn compile_bundle_exposes_script_tags_for_filtering() {
+        let files = map(&[(
+            "main.xml",
+            r#"
+    <module name="main" export="script:main,duel,shop">
+    <script name="main"><goto script="@duel"/></script>
+    <script name="duel" tags="combat, boss,combat"><end/></script>
+    <script name="shop" tags="shop"><end/></script>
+    </module>
+    "#,
+        )]);
+
+        let bundle = compile_project_bundle_from_xml_map_with_options(
+            &files,
+            CompileOptions {
+                strict_attributes: true,
+                ..CompileOptions::default()
+            },
+        )
+        .expect("tagged scripts should compile in strict mode");
+        assert_eq!(bundle.scripts["main.duel"].tags, vec!["combat", "boss"]);
+        assert!(bundle.scripts["main.main"].tags.is_empty());
+        assert_eq!(bundle.scripts_with_tag("combat"), vec!["main.duel"]);
+        assert_eq!(bundle.scripts_with_tag("shop"), vec!["main.shop"]);
+        assert!(bundle.scripts_with_tag("missing").is_empty());
+    }
+
+    #[test]
+    fn compile_bundle_records_host_function_uses_per_script() {
+        let files = map(&[(
+            "main.xml",
+            r#"
+    <module name="main" export="script:main,duel">
+    <script name="main"><goto script="@duel"/></script>
+    <script name="duel">
+      <uses functions="roll, vec_add"/>
+      <uses functions="roll"/>
+      <temp name="hp" type="int">roll(1, 6)</temp>
+      <end/>
+    </script>
+    </module>
+    "#,
+        )]);
+
+        let bundle = compile_project_bundle_from_xml_map_with_options(
+            &files,
+            CompileOptions {
+                strict_attributes: true,
+                ..CompileOptions::default()
+            },
+        )
+        .expect("uses declarations should compile in strict mode");
+        assert_eq!(
+            bundle.scripts["main.duel"].host_functions,
+            vec!["roll", "vec_add"]
+        );
+        assert!(bundle.scripts["main.main"].host_functions.is_empty());
+        let duel_root =
+            &bundle.scripts["main.duel"].groups[&bundle.scripts["main.duel"].root_group_id];
+        assert_eq!(duel_root.nodes.len(), 2);
+    }
+
     #[test]
     fn compile_bundle_supports_mixed_sources_without_filesystem_examples() {
         let files = map(&[
@@ -506,6 +809,113 @@ mod pipeline_tests {
         assert_eq!(error.code, "SCRIPT_GOTO_ARGS_COUNT_MISMATCH");
     }
 
+    #[test]
+    fn compile_bundle_rejects_too_many_and_mistyped_literal_call_args() {
+        let files = |call_args: &str| {
+            map(&[
+                (
+                    "callee.xml",
+                    r#"<module name="callee" export="script:callee"><script name="callee" kind="call" args="int:x,float:y"><return/></script></module>"#,
+                ),
+                (
+                    "main.xml",
+                    &format!(
+                        r#"<module name="main" export="script:main"><script name="main"><temp name="n" type="int">1</temp><call script="@callee.callee" args="{}"/><end/></script></module>"#,
+                        call_args
+                    ),
+                ),
+            ])
+        };
+
+        let too_many = compile_project_bundle_from_xml_map(&files("1,2,3"))
+            .expect_err("extra arg should fail");
+        assert_eq!(too_many.code, "SCRIPT_CALL_ARGS_COUNT_MISMATCH");
+        assert!(too_many.message.contains("expects 2 args, got 3"));
+
+        let mistyped = compile_project_bundle_from_xml_map(&files("'one',2"))
+            .expect_err("string literal for int should fail");
+        assert_eq!(mistyped.code, "SCRIPT_CALL_ARG_TYPE_MISMATCH");
+        assert!(mistyped
+            .message
+            .contains("param \"x\" expects int, got string"));
+
+        compile_project_bundle_from_xml_map(&files("n,2")).expect("int literal widens to float");
+        compile_project_bundle_from_xml_map(&files("n + 1,2.5")).expect("expressions are skipped");
+    }
+
+    #[test]
+    fn compile_bundle_rejects_unconditional_call_recursion_only() {
+        let files = |body: &str| {
+            map(&[(
+                "main.xml",
+                &format!(
+                    r#"<module name="main" export="script:main"><script name="main"><call script="@spin" args="0"/><end/></script><script name="spin" kind="call" args="int:n">{}<return/></script><script name="other" kind="call"><call script="@spin" args="1"/><return/></script></module>"#,
+                    body
+                ),
+            )])
+        };
+
+        let direct = compile_project_bundle_from_xml_map(&files(
+            r#"<text>${n}</text><call script="@spin" args="n + 1"/>"#,
+        ))
+        .expect_err("unconditional self call should fail");
+        assert_eq!(direct.code, "CALL_INFINITE_RECURSION");
+        assert!(direct.message.contains("main.spin -> main.spin"));
+
+        let mutual = compile_project_bundle_from_xml_map(&files(r#"<call script="@other"/>"#))
+            .expect_err("unconditional mutual call should fail");
+        assert_eq!(mutual.code, "CALL_INFINITE_RECURSION");
+        assert!(mutual
+            .message
+            .contains("main.other -> main.spin -> main.other"));
+
+        compile_project_bundle_from_xml_map(&files(
+            r#"<if when="n > 0"><call script="@spin" args="n - 1"/></if>"#,
+        ))
+        .expect("conditional recursion is allowed");
+    }
+
+    #[test]
+    fn compile_bundle_marks_session_vars_and_rejects_shadowing_them() {
+        let files = |script: &str| {
+            map(&[
+                (
+                    "shared.xml",
+                    r#"<module name="shared" export="var:visits"><var name="visits" type="int" session="true">0</var></module>"#,
+                ),
+                (
+                    "main.xml",
+                    &format!(
+                        r#"<!-- import shared from shared.xml -->
+<module name="main" export="script:main"><var name="turns" type="int" session="true">0</var><var name="hp" type="int">1</var>{}</module>"#,
+                        script
+                    ),
+                ),
+            ])
+        };
+
+        let bundle = compile_project_bundle_from_xml_map(&files(
+            r#"<script name="main"><temp name="hp" type="int">2</temp><temp name="visits" type="int">shared.visits</temp><text>${turns} ${hp} ${visits}</text></script>"#,
+        ))
+        .expect("plain module vars and qualified-only session vars can share a local name");
+        assert!(bundle.module_var_declarations["main.turns"].session);
+        assert!(bundle.module_var_declarations["shared.visits"].session);
+        assert!(!bundle.module_var_declarations["main.hp"].session);
+
+        let temp = compile_project_bundle_from_xml_map(&files(
+            r#"<script name="main"><temp name="turns" type="int">2</temp></script>"#,
+        ))
+        .expect_err("temp shadowing a session var should fail");
+        assert_eq!(temp.code, "VAR_SESSION_SHADOW");
+        assert!(temp.message.contains("main.turns"));
+
+        let param = compile_project_bundle_from_xml_map(&files(
+            r#"<script name="main" args="int:turns"><end/></script>"#,
+        ))
+        .expect_err("param shadowing a session var should fail");
+        assert_eq!(param.code, "VAR_SESSION_SHADOW");
+    }
+
     #[test]
     fn compile_bundle_rejects_literal_call_when_target_is_goto_script() {
         let files = map(&[
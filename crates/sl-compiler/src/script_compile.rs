@@ -46,14 +46,21 @@ fn script_literal_name_regex() -> &'static Regex {
     })
 }
 
+fn fail_code_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(r"^[A-Z][A-Z0-9_]*$").expect("fail code regex"))
+}
+
 fn function_ref_var_regex() -> &'static Regex {
     static REGEX: OnceLock<Regex> = OnceLock::new();
     REGEX.get_or_init(|| Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*$").expect("function ref var regex"))
 }
 
-fn template_expr_regex() -> &'static Regex {
+/// Matches `${expr}` interpolations, or an escaped `\${` delimiter (no capture group 1) that is
+/// kept verbatim so the runtime renders it as a literal `${`.
+pub(crate) fn template_expr_regex() -> &'static Regex {
     static REGEX: OnceLock<Regex> = OnceLock::new();
-    REGEX.get_or_init(|| Regex::new(r"\$\{([^{}]+)\}").expect("template expression regex"))
+    REGEX.get_or_init(|| Regex::new(r"\\\$\{|\$\{([^{}]+)\}").expect("template expression regex"))
 }
 
 #[derive(Clone, Copy)]
@@ -83,7 +90,7 @@ fn compile_rhai_source_for_target(
     };
     result.map_err(|error| {
         ScriptLangError::with_span(
-            "XML_RHAI_SYNTAX_INVALID",
+            ErrorCode::XmlRhaiSyntaxInvalid,
             format!("Invalid Rhai {}: {}", context, error),
             span.clone(),
         )
@@ -126,9 +133,9 @@ fn preprocess_and_compile_template_expressions(
         let full = captures
             .get(0)
             .expect("capture group 0 must exist for each template capture");
-        let expr = captures
-            .get(1)
-            .expect("capture group 1 must exist for each template capture");
+        let Some(expr) = captures.get(1) else {
+            continue;
+        };
         out.push_str(&template[last_index..full.start()]);
         let preprocessed = preprocess_and_compile_rhai_source(
             expr.as_str(),
@@ -174,23 +181,34 @@ fn build_runtime_module_global_rewrite_map(
 fn build_runtime_function_symbol_map(
     visible_functions: &BTreeMap<String, FunctionDecl>,
     module_name: Option<&str>,
+    current_script_name: Option<&str>,
 ) -> BTreeMap<String, String> {
     let mut out = visible_functions
         .keys()
         .map(|name| (name.clone(), rhai_function_symbol(name)))
         .collect::<BTreeMap<_, _>>();
-    if let Some(module_name) = module_name {
-        let root_name = module_root_name(module_name).to_string();
-        for short_name in visible_functions.keys().filter(|name| !name.contains('.')) {
-            let local_candidate = format!("{module_name}.{short_name}");
-            if visible_functions.contains_key(&local_candidate) {
-                out.insert(short_name.clone(), rhai_function_symbol(&local_candidate));
+    for short_name in visible_functions.keys().filter(|name| !name.contains('.')) {
+        if let Some(script_name) = current_script_name {
+            let script_local_candidate = format!("{script_name}.{short_name}");
+            if visible_functions.contains_key(&script_local_candidate) {
+                out.insert(
+                    short_name.clone(),
+                    rhai_function_symbol(&script_local_candidate),
+                );
                 continue;
             }
-            let root_candidate = format!("{root_name}.{short_name}");
-            if visible_functions.contains_key(&root_candidate) {
-                out.insert(short_name.clone(), rhai_function_symbol(&root_candidate));
-            }
+        }
+        let Some(module_name) = module_name else {
+            continue;
+        };
+        let local_candidate = format!("{module_name}.{short_name}");
+        if visible_functions.contains_key(&local_candidate) {
+            out.insert(short_name.clone(), rhai_function_symbol(&local_candidate));
+            continue;
+        }
+        let root_candidate = format!("{}.{short_name}", module_root_name(module_name));
+        if visible_functions.contains_key(&root_candidate) {
+            out.insert(short_name.clone(), rhai_function_symbol(&root_candidate));
         }
     }
     out
@@ -291,9 +309,9 @@ fn rewrite_script_context_macro_in_template(template: &str, script_name: Option<
         let full = captures
             .get(0)
             .expect("capture group 0 must exist for each template capture");
-        let expr = captures
-            .get(1)
-            .expect("capture group 1 must exist for each template capture");
+        let Some(expr) = captures.get(1) else {
+            continue;
+        };
         out.push_str(&template[last_index..full.start()]);
         let rewritten = rewrite_script_context_macro_in_expression(
             expr.as_str(),
@@ -321,7 +339,7 @@ fn qualify_script_literal_name(
         return Ok(format!("{}.{}", module_name, literal_name));
     }
     Err(ScriptLangError::with_span(
-        "XML_SCRIPT_TARGET_INVALID",
+        ErrorCode::XmlScriptTargetInvalid,
         format!(
             "Short script literal \"@{}\" requires module context.",
             literal_name
@@ -342,7 +360,7 @@ fn validate_script_literal_access(
 ) -> Result<(), ScriptLangError> {
     let Some(access) = all_script_access.get(qualified).copied() else {
         return Err(ScriptLangError::with_span(
-            "XML_SCRIPT_TARGET_NOT_FOUND",
+            ErrorCode::XmlScriptTargetNotFound,
             format!("Script target \"{}\" not found.", qualified),
             span.clone(),
         ));
@@ -352,7 +370,7 @@ fn validate_script_literal_access(
         let target_module = qualified.rsplit_once('.').map(|(ns, _)| ns).unwrap_or("");
         if module_name != Some(target_module) {
             return Err(ScriptLangError::with_span(
-                "XML_SCRIPT_TARGET_ACCESS_DENIED",
+                ErrorCode::XmlScriptTargetAccessDenied,
                 format!(
                     "Script target \"{}\" is private and cannot be referenced here.",
                     qualified
@@ -482,7 +500,7 @@ fn qualify_function_literal_name(
         return Ok(format!("{}.{}", module_name, literal_name));
     }
     Err(ScriptLangError::with_span(
-        "XML_FUNCTION_LITERAL_INVALID",
+        ErrorCode::XmlFunctionLiteralInvalid,
         format!(
             "Short function literal \"*{}\" requires module context.",
             literal_name
@@ -600,7 +618,7 @@ where
                 };
                 if !has_visible_function(&qualified) {
                     return Err(ScriptLangError::with_span(
-                        "XML_FUNCTION_LITERAL_NOT_FOUND",
+                        ErrorCode::XmlFunctionLiteralNotFound,
                         format!(
                             "Function literal target \"{}\" not found or not visible.",
                             qualified
@@ -614,7 +632,7 @@ where
                 }
                 if chars.get(lookahead) == Some(&'(') {
                     return Err(ScriptLangError::with_span(
-                        "XML_FUNCTION_LITERAL_CALL_FORBIDDEN",
+                        ErrorCode::XmlFunctionLiteralCallForbidden,
                         "Function literal cannot be called directly. Use method(...) or module.method(...).",
                         span.clone(),
                     ));
@@ -741,7 +759,7 @@ fn validate_invoke_first_arg(
         let first_arg = extract_first_invoke_arg(&chars, open_index).unwrap_or_default();
         if !function_ref_var_regex().is_match(first_arg.trim()) {
             return Err(ScriptLangError::with_span(
-                "XML_INVOKE_TARGET_VAR_REQUIRED",
+                ErrorCode::XmlInvokeTargetVarRequired,
                 "invoke first argument must be a function variable name.",
                 span.clone(),
             ));
@@ -753,7 +771,7 @@ fn validate_invoke_first_arg(
             .or_else(|| visible_module_consts.get(var_name).map(|decl| &decl.r#type));
         if !matches!(declared_type, Some(ScriptType::Function)) {
             return Err(ScriptLangError::with_span(
-                "XML_INVOKE_TARGET_VAR_TYPE",
+                ErrorCode::XmlInvokeTargetVarType,
                 format!(
                     "invoke first argument \"{}\" must declare type=\"function\".",
                     var_name
@@ -815,8 +833,11 @@ fn normalize_expression_literals(
     )?;
     let runtime_module_global_rewrite_map =
         build_runtime_module_global_rewrite_map(ctx.visible_module_vars, ctx.visible_module_consts);
-    let runtime_function_symbol_map =
-        build_runtime_function_symbol_map(ctx.visible_functions, ctx.module_name);
+    let runtime_function_symbol_map = build_runtime_function_symbol_map(
+        ctx.visible_functions,
+        ctx.module_name,
+        ctx.current_script_name,
+    );
     preprocess_and_compile_rhai_source(
         &rewritten,
         span,
@@ -875,8 +896,11 @@ fn normalize_attribute_expression_literals(
     )?;
     let runtime_module_global_rewrite_map =
         build_runtime_module_global_rewrite_map(ctx.visible_module_vars, ctx.visible_module_consts);
-    let runtime_function_symbol_map =
-        build_runtime_function_symbol_map(ctx.visible_functions, ctx.module_name);
+    let runtime_function_symbol_map = build_runtime_function_symbol_map(
+        ctx.visible_functions,
+        ctx.module_name,
+        ctx.current_script_name,
+    );
     preprocess_and_compile_rhai_source(
         &rewritten,
         span,
@@ -929,8 +953,11 @@ fn normalize_template_literals(
     )?;
     let runtime_module_global_rewrite_map =
         build_runtime_module_global_rewrite_map(ctx.visible_module_vars, ctx.visible_module_consts);
-    let runtime_function_symbol_map =
-        build_runtime_function_symbol_map(ctx.visible_functions, ctx.module_name);
+    let runtime_function_symbol_map = build_runtime_function_symbol_map(
+        ctx.visible_functions,
+        ctx.module_name,
+        ctx.current_script_name,
+    );
     preprocess_and_compile_template_expressions(
         &rewritten,
         span,
@@ -951,7 +978,7 @@ fn parse_script_target_attr(
     let target = raw_target.trim();
     if target.contains("${") {
         return Err(ScriptLangError::with_span(
-            "XML_SCRIPT_TARGET_TEMPLATE_REMOVED",
+            ErrorCode::XmlScriptTargetTemplateRemoved,
             "Attribute \"script\" no longer supports ${...}; use @literal or script variable name.",
             node.location.clone(),
         ));
@@ -961,7 +988,7 @@ fn parse_script_target_attr(
         let script_name = stripped.trim();
         if script_name.is_empty() || !script_literal_name_regex().is_match(script_name) {
             return Err(ScriptLangError::with_span(
-                "XML_SCRIPT_TARGET_INVALID",
+                ErrorCode::XmlScriptTargetInvalid,
                 format!("Invalid script literal \"{}\".", target),
                 node.location.clone(),
             ));
@@ -988,7 +1015,7 @@ fn parse_script_target_attr(
 
     if !script_target_var_regex().is_match(target) {
         return Err(ScriptLangError::with_span(
-            "XML_SCRIPT_TARGET_INVALID",
+            ErrorCode::XmlScriptTargetInvalid,
             format!(
                 "script=\"{}\" is invalid. Use @module.script or script variable name.",
                 target
@@ -1003,7 +1030,7 @@ fn parse_script_target_attr(
         .or_else(|| visible_module_consts.get(target).map(|decl| &decl.r#type))
         .ok_or_else(|| {
             ScriptLangError::with_span(
-                "XML_SCRIPT_TARGET_VAR_UNKNOWN",
+                ErrorCode::XmlScriptTargetVarUnknown,
                 format!("Script target variable \"{}\" is not declared.", target),
                 node.location.clone(),
             )
@@ -1011,7 +1038,7 @@ fn parse_script_target_attr(
 
     if !matches!(declared_type, ScriptType::Script) {
         return Err(ScriptLangError::with_span(
-            "XML_SCRIPT_TARGET_VAR_TYPE",
+            ErrorCode::XmlScriptTargetVarType,
             format!(
                 "Script target variable \"{}\" must declare type=\"script\".",
                 target
@@ -1025,6 +1052,132 @@ fn parse_script_target_attr(
     })
 }
 
+/// Best-effort check that a `ref:` call argument starts at a declared variable. Only the root
+/// (`x` in `x.y`, or a qualified `module.var`) is checked; nested map fields stay dynamic.
+fn validate_ref_arg_root(
+    path: &str,
+    node: &XmlElementNode,
+    local_var_types: &BTreeMap<String, ScriptType>,
+    visible_module_vars: &BTreeMap<String, ModuleVarDecl>,
+) -> Result<(), ScriptLangError> {
+    let parts = path
+        .split('.')
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>();
+    let is_declared =
+        |name: &str| local_var_types.contains_key(name) || visible_module_vars.contains_key(name);
+    let qualified_declared = parts.len() >= 2 && is_declared(&format!("{}.{}", parts[0], parts[1]));
+    if qualified_declared || parts.first().is_some_and(|root| is_declared(root)) {
+        return Ok(());
+    }
+
+    Err(ScriptLangError::with_span(
+        ErrorCode::CallRefRootUnknown,
+        format!(
+            "Ref argument \"{}\" does not start at a declared variable in scope.",
+            path
+        ),
+        node.location.clone(),
+    ))
+}
+
+/// Parse the `scope` attribute of a once-able node: `script` (default) or `global`.
+fn parse_once_scope(node: &XmlElementNode, once: bool) -> Result<bool, ScriptLangError> {
+    let Some(scope) = get_optional_attr(node, "scope") else {
+        return Ok(false);
+    };
+    let global = match scope.trim() {
+        "script" => false,
+        "global" => true,
+        other => {
+            return Err(ScriptLangError::with_span(
+                ErrorCode::XmlOnceScopeInvalid,
+                format!(
+                    "Attribute \"scope\" must be \"script\" or \"global\", got \"{}\".",
+                    other
+                ),
+                node.location.clone(),
+            ));
+        }
+    };
+    if !once {
+        return Err(ScriptLangError::with_span(
+            ErrorCode::XmlOnceScopeWithoutOnce,
+            "Attribute \"scope\" requires once=\"true\".",
+            node.location.clone(),
+        ));
+    }
+    Ok(global)
+}
+
+/// Check that an `<input var>` target is a declared variable in scope and, where its declared
+/// type can be followed through object fields and map values, that it matches the input format
+/// (`string` for text, `boolean` for boolean; `json` accepts any type).
+fn validate_input_target(
+    path: &str,
+    format: InputFormat,
+    node: &XmlElementNode,
+    local_var_types: &BTreeMap<String, ScriptType>,
+    visible_module_vars: &BTreeMap<String, ModuleVarDecl>,
+) -> Result<(), ScriptLangError> {
+    let parts = path
+        .split('.')
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>();
+    let declared_type = |name: &str| {
+        local_var_types
+            .get(name)
+            .or_else(|| visible_module_vars.get(name).map(|decl| &decl.r#type))
+    };
+    let qualified = (parts.len() >= 2)
+        .then(|| declared_type(&format!("{}.{}", parts[0], parts[1])))
+        .flatten();
+    let (root_type, consumed) = match qualified {
+        Some(ty) => (ty, 2),
+        None => match parts.first().and_then(|root| declared_type(root)) {
+            Some(ty) => (ty, 1),
+            None => {
+                return Err(ScriptLangError::with_span(
+                    ErrorCode::XmlInputVarUnknown,
+                    format!(
+                        "Input target var \"{}\" is not a declared variable in scope.",
+                        path
+                    ),
+                    node.location.clone(),
+                ));
+            }
+        },
+    };
+
+    let mut target_type = Some(root_type);
+    for part in &parts[consumed..] {
+        target_type = match target_type {
+            Some(ScriptType::Object { fields, .. }) => fields.get(*part),
+            Some(ScriptType::Map { value_type, .. }) => Some(value_type.as_ref()),
+            _ => None,
+        };
+    }
+    let expected = match format {
+        InputFormat::Text => "string",
+        InputFormat::Boolean => "boolean",
+        InputFormat::Json => return Ok(()),
+    };
+    match target_type {
+        Some(ScriptType::Primitive { name }) if name == expected => Ok(()),
+        None => Ok(()),
+        Some(_) => Err(ScriptLangError::with_span(
+            ErrorCode::XmlInputVarType,
+            format!(
+                "Input target var \"{}\" must be declared as {}.",
+                path, expected
+            ),
+            node.location.clone(),
+        )),
+    }
+}
+
 pub(crate) fn compile_script(
     options: CompileScriptOptions<'_>,
 ) -> Result<ScriptIr, ScriptLangError> {
@@ -1040,14 +1193,18 @@ pub(crate) fn compile_script(
         visible_module_consts,
         all_script_access,
         invoke_all_functions,
+        strict_attributes,
     } = options;
     if root.name != "script" {
         return Err(ScriptLangError::with_span(
-            "XML_ROOT_INVALID",
+            ErrorCode::XmlRootInvalid,
             "Script file root must be <script>.",
             root.location.clone(),
         ));
     }
+    if strict_attributes {
+        validate_strict_attributes(root)?;
+    }
 
     let local_script_name = get_required_non_empty_attr(root, "name")?;
     assert_decl_name_not_reserved_or_rhai_keyword(
@@ -1060,7 +1217,11 @@ pub(crate) fn compile_script(
         .to_string();
 
     let script_kind = parse_script_kind(root)?;
+    let tags = parse_script_tags(root)?;
     let params = parse_script_args(root, visible_types, script_kind)?;
+    let (body_root, host_functions) = split_script_host_function_uses(root, strict_attributes)?;
+    let (body_root, function_nodes) = split_script_local_functions(&body_root);
+    let root = &body_root;
     validate_reserved_prefix_in_user_var_declarations(root)?;
 
     let mut reserved_names = params
@@ -1078,14 +1239,33 @@ pub(crate) fn compile_script(
     for param in &params {
         visible_var_types.insert(param.name.clone(), param.r#type.clone());
     }
+    let (visible_functions, local_functions) = compile_script_local_functions(
+        &function_nodes,
+        &script_name,
+        &ExpressionNormalizeContext {
+            all_script_access,
+            module_name,
+            current_script_name: Some(script_name.as_str()),
+            visible_types,
+            visible_functions,
+            local_var_types: &BTreeMap::new(),
+            visible_module_vars,
+            visible_module_consts,
+        },
+    )?;
+    let mut invoke_all_functions = invoke_all_functions.clone();
+    for decl in local_functions {
+        invoke_all_functions.insert(decl.name.clone(), decl);
+    }
     let scope = CompileVisibility {
         visible_types,
-        visible_functions,
+        visible_functions: &visible_functions,
         visible_module_vars,
         visible_module_consts,
         all_script_access,
         module_name,
         current_script_name: Some(script_name.as_str()),
+        strict_attributes,
     };
 
     compile_group_with_context(
@@ -1104,17 +1284,160 @@ pub(crate) fn compile_script(
         module_name: module_name.map(|value| value.to_string()),
         local_script_name: module_name.map(|_| local_script_name.clone()),
         kind: script_kind,
+        tags,
         params,
         root_group_id,
         groups: builder.groups,
+        node_labels: builder.node_labels,
         visible_globals: Vec::new(),
-        visible_functions: visible_functions.clone(),
+        visible_functions,
         visible_module_vars: visible_module_vars.clone(),
         visible_module_consts: visible_module_consts.clone(),
-        invoke_all_functions: invoke_all_functions.clone(),
+        invoke_all_functions,
+        host_functions,
     })
 }
 
+/// Collect the host function names declared by `<uses functions="...">` directly under
+/// `<script>`, removing those declarations from the executable body.
+fn split_script_host_function_uses(
+    root: &XmlElementNode,
+    strict_attributes: bool,
+) -> Result<(XmlElementNode, Vec<String>), ScriptLangError> {
+    let mut body_root = root.clone();
+    let mut names = Vec::new();
+    for uses in element_children(root).filter(|child| child.name == "uses") {
+        if strict_attributes {
+            validate_strict_attributes(uses)?;
+        }
+        let raw = get_required_non_empty_attr(uses, "functions")?;
+        for item in raw.split(',') {
+            let name = item.trim();
+            if !decl_name_regex().is_match(name) {
+                return Err(ScriptLangError::with_span(
+                    ErrorCode::XmlUsesFunctionInvalid,
+                    format!(
+                        "Invalid host function name \"{}\" in <uses functions=\"{}\">.",
+                        name, raw
+                    ),
+                    uses.location.clone(),
+                ));
+            }
+            if !names.iter().any(|existing| existing == name) {
+                names.push(name.to_string());
+            }
+        }
+    }
+    body_root
+        .children
+        .retain(|child| !matches!(child, XmlNode::Element(element) if element.name == "uses"));
+    Ok((body_root, names))
+}
+
+/// Separate `<function>` children declared directly under `<script>` from the executable body.
+fn split_script_local_functions(root: &XmlElementNode) -> (XmlElementNode, Vec<XmlElementNode>) {
+    let mut body_root = root.clone();
+    let mut function_nodes = Vec::new();
+    body_root.children.retain(|child| match child {
+        XmlNode::Element(element) if element.name == "function" => {
+            function_nodes.push(element.clone());
+            false
+        }
+        _ => true,
+    });
+    (body_root, function_nodes)
+}
+
+/// Compile script-local `<function>` declarations under `<script name>.<function name>`.
+///
+/// Returns the script's visible functions extended with each local function (by short and
+/// qualified name), plus the local declarations themselves. Local functions are never exported,
+/// so sibling scripts cannot see them.
+fn compile_script_local_functions(
+    function_nodes: &[XmlElementNode],
+    script_name: &str,
+    ctx: &ExpressionNormalizeContext,
+) -> Result<(BTreeMap<String, FunctionDecl>, Vec<FunctionDecl>), ScriptLangError> {
+    let mut visible_functions = ctx.visible_functions.clone();
+    let mut parsed = Vec::new();
+    let mut local_names = BTreeSet::new();
+    for node in function_nodes {
+        let decl = parse_function_declaration_node_with_namespace(
+            node,
+            script_name,
+            AccessLevel::Private,
+        )?;
+        if !local_names.insert(decl.name.clone()) {
+            return Err(ScriptLangError::with_span(
+                ErrorCode::FunctionDeclDuplicate,
+                format!(
+                    "Duplicate function declaration \"{}\".",
+                    decl.qualified_name
+                ),
+                decl.location.clone(),
+            ));
+        }
+        let mut params = Vec::new();
+        for param in &decl.params {
+            params.push(FunctionParam {
+                name: param.name.clone(),
+                r#type: resolve_type_expr(&param.type_expr, ctx.visible_types, &param.location)?,
+                location: param.location.clone(),
+            });
+        }
+        let return_binding = FunctionReturn {
+            r#type: resolve_type_expr(
+                &decl.return_decl.type_expr,
+                ctx.visible_types,
+                &decl.return_decl.location,
+            )?,
+            location: decl.return_decl.location.clone(),
+        };
+        let shell = FunctionDecl {
+            name: decl.qualified_name.clone(),
+            params,
+            return_binding,
+            code: String::new(),
+            location: decl.location.clone(),
+        };
+        visible_functions.insert(decl.name.clone(), shell.clone());
+        visible_functions.insert(decl.qualified_name.clone(), shell.clone());
+        let param_types = shell
+            .params
+            .iter()
+            .map(|param| (param.name.clone(), param.r#type.clone()))
+            .collect::<BTreeMap<_, _>>();
+        parsed.push((decl, shell, param_types));
+    }
+
+    let mut local_functions = Vec::new();
+    for (decl, mut shell, param_types) in parsed {
+        let body_ctx = ExpressionNormalizeContext {
+            visible_functions: &visible_functions,
+            local_var_types: &param_types,
+            ..*ctx
+        };
+        shell.code = normalize_expression_literals(
+            &decl.code,
+            &decl.location,
+            "function body",
+            RhaiCompileTarget::CodeBlock,
+            &body_ctx,
+        )?;
+        local_functions.push(shell);
+    }
+    for decl in &local_functions {
+        let short_name = decl
+            .name
+            .rsplit_once('.')
+            .map(|(_, short_name)| short_name)
+            .unwrap_or(&decl.name);
+        visible_functions.insert(short_name.to_string(), decl.clone());
+        visible_functions.insert(decl.name.clone(), decl.clone());
+    }
+    Ok((visible_functions, local_functions))
+}
+
 pub fn validate_terminal_structure_from_xml_map(
     xml_by_path: &BTreeMap<String, String>,
 ) -> Result<(), ScriptLangError> {
@@ -1144,7 +1467,8 @@ fn validate_script_terminal_structure_for_root(
 ) -> Result<(), ScriptLangError> {
     let script_name = get_required_non_empty_attr(root, "name")?;
     let script_kind = parse_script_kind(root)?;
-    let expanded_root = expand_script_macros(root, &[])?;
+    let (body_root, _) = split_script_local_functions(root);
+    let expanded_root = expand_script_macros(&body_root, &[])?;
     validate_script_terminal_structure(&expanded_root, script_kind, &script_name)
 }
 
@@ -1157,8 +1481,8 @@ fn kind_terminal_labels(script_kind: ScriptKind) -> &'static str {
 
 fn is_kind_terminal_node(name: &str, script_kind: ScriptKind) -> bool {
     match script_kind {
-        ScriptKind::Goto => name == "goto" || name == "end",
-        ScriptKind::Call => name == "return",
+        ScriptKind::Goto => name == "goto" || name == "end" || name == "fail",
+        ScriptKind::Call => name == "return" || name == "fail",
     }
 }
 
@@ -1173,7 +1497,7 @@ fn terminal_structure_error(
         ScriptKind::Call => "call",
     };
     ScriptLangError::with_span(
-        "XML_SCRIPT_TERMINATOR_REQUIRED",
+        ErrorCode::XmlScriptTerminatorRequired,
         format!(
             "{} script \"{}\" terminal structure invalid: {}. Expected terminal: {}.",
             kind_label,
@@ -1198,7 +1522,9 @@ fn validate_container_terminal_tail(
     script_kind: ScriptKind,
     script_name: &str,
 ) -> Result<(), ScriptLangError> {
-    let children = element_children(container).collect::<Vec<_>>();
+    let children = element_children(container)
+        .filter(|child| child.name != "comment")
+        .collect::<Vec<_>>();
     let Some(tail) = children.last() else {
         return Err(terminal_structure_error(
             script_name,
@@ -1321,6 +1647,7 @@ pub(crate) fn compile_group(
         all_script_access: &BTreeMap::new(),
         module_name: None,
         current_script_name: None,
+        strict_attributes: false,
     };
     compile_group_with_context(
         group_id,
@@ -1341,6 +1668,7 @@ struct CompileVisibility<'a> {
     all_script_access: &'a BTreeMap<String, AccessLevel>,
     module_name: Option<&'a str>,
     current_script_name: Option<&'a str>,
+    strict_attributes: bool,
 }
 
 fn compile_group_with_context(
@@ -1362,6 +1690,8 @@ fn compile_group_with_context(
             parent_group_id: parent_group_id.map(|value| value.to_string()),
             entry_node_id: None,
             nodes: Vec::new(),
+            node_hashes: Vec::new(),
+            comments: Vec::new(),
         },
     );
 
@@ -1375,7 +1705,7 @@ fn compile_group_with_context(
         &mut nodes,
     )?;
 
-    let entry_node_id = nodes.first().map(|node| node_id(node).to_string());
+    let entry_node_id = nodes.first().map(|node| node.id().to_string());
     let group = builder.groups.get_mut(group_id).expect("group must exist");
     group.entry_node_id = entry_node_id;
     group.nodes = nodes;
@@ -1421,18 +1751,33 @@ fn compile_group_nodes(
     let current_script_name = scope.current_script_name;
 
     for child in element_children(container) {
+        if scope.strict_attributes {
+            validate_strict_attributes_allowing(child, &["label"])?;
+        }
+        let label = parse_node_label(child, builder)?;
         if has_attr(child, "once") && child.name != "text" {
             return Err(ScriptLangError::with_span(
-                "XML_ATTR_NOT_ALLOWED",
+                ErrorCode::XmlAttrNotAllowed,
                 "Attribute \"once\" is only allowed on <text> and <option>.",
                 child.location.clone(),
             ));
         }
 
         let node = match child.name.as_str() {
+            "comment" => {
+                let comment = parse_comment(child, nodes.len())?;
+                builder
+                    .groups
+                    .get_mut(group_id)
+                    .expect("group must exist")
+                    .comments
+                    .push(comment);
+                continue;
+            }
             "group" => {
                 let body_group_id = builder.next_group_id();
-                let else_group_id = builder.next_group_id();
+                // Reserved for stable group numbering; `<group>` never takes an else branch.
+                builder.next_group_id();
 
                 compile_group_with_context(
                     &body_group_id,
@@ -1445,26 +1790,26 @@ fn compile_group_nodes(
                         .with_script_kind(mode.script_kind),
                 )?;
 
-                builder.groups.insert(
-                    else_group_id.clone(),
-                    ImplicitGroup {
-                        group_id: else_group_id.clone(),
-                        parent_group_id: Some(group_id.to_string()),
-                        entry_node_id: None,
-                        nodes: Vec::new(),
-                    },
-                );
-
                 ScriptNode::If {
                     id: builder.next_node_id("if"),
                     when_expr: "true".to_string(),
                     then_group_id: body_group_id,
-                    else_group_id: Some(else_group_id),
+                    else_group_id: None,
                     location: child.location.clone(),
                 }
             }
             "temp" => {
                 let mut declaration = parse_var_declaration(child, visible_types)?;
+                if local_var_types.contains_key(&declaration.name) {
+                    return Err(ScriptLangError::with_span(
+                        ErrorCode::XmlVarDuplicate,
+                        format!(
+                            "Variable \"{}\" is already declared in this scope.",
+                            declaration.name
+                        ),
+                        child.location.clone(),
+                    ));
+                }
                 if let Some(expr) = declaration.initial_value_expr.as_mut() {
                     let raw_expr_quoted = {
                         let trimmed = expr.trim_start();
@@ -1489,14 +1834,14 @@ fn compile_group_nodes(
                     )?;
                     if matches!(declaration.r#type, ScriptType::Script) && raw_expr_quoted {
                         return Err(ScriptLangError::with_span(
-                            "XML_SCRIPT_ASSIGN_STRING_FORBIDDEN",
+                            ErrorCode::XmlScriptAssignStringForbidden,
                             "script type does not accept plain string literal; use @module.script.",
                             child.location.clone(),
                         ));
                     }
                     if matches!(declaration.r#type, ScriptType::Function) && raw_expr_quoted {
                         return Err(ScriptLangError::with_span(
-                            "XML_FUNCTION_ASSIGN_STRING_FORBIDDEN",
+                            ErrorCode::XmlFunctionAssignStringForbidden,
                             "function type does not accept plain string literal; use *module.function.",
                             child.location.clone(),
                         ));
@@ -1509,35 +1854,76 @@ fn compile_group_nodes(
                     location: child.location.clone(),
                 }
             }
-            "text" => ScriptNode::Text {
-                id: builder.next_node_id("text"),
-                value: {
-                    let ctx = ExpressionNormalizeContext {
-                        all_script_access,
-                        module_name,
-                        current_script_name,
-                        visible_types,
-                        visible_functions,
-                        local_var_types,
-                        visible_module_vars,
-                        visible_module_consts,
-                    };
-                    normalize_template_literals(
-                        &parse_inline_required(child)?,
-                        &child.location,
-                        &ctx,
-                    )?
-                },
-                tag: get_optional_attr(child, "tag")
-                    .map(|value| value.trim().to_string())
-                    .filter(|value| !value.is_empty()),
-                once: parse_bool_attr(child, "once", false)?,
-                location: child.location.clone(),
-            },
+            "text" => {
+                let once = parse_bool_attr(child, "once", false)?;
+                let key = parse_text_key(child)?;
+                let expr = parse_text_expr(child)?
+                    .map(|expr| {
+                        let ctx = ExpressionNormalizeContext {
+                            all_script_access,
+                            module_name,
+                            current_script_name,
+                            visible_types,
+                            visible_functions,
+                            local_var_types,
+                            visible_module_vars,
+                            visible_module_consts,
+                        };
+                        normalize_attribute_expression_literals(&expr, &child.location, &ctx)
+                    })
+                    .transpose()?;
+                ScriptNode::Text {
+                    id: builder.next_node_id("text"),
+                    value: if key.is_some() || expr.is_some() {
+                        String::new()
+                    } else {
+                        let ctx = ExpressionNormalizeContext {
+                            all_script_access,
+                            module_name,
+                            current_script_name,
+                            visible_types,
+                            visible_functions,
+                            local_var_types,
+                            visible_module_vars,
+                            visible_module_consts,
+                        };
+                        normalize_template_literals(
+                            &parse_inline_required(child)?,
+                            &child.location,
+                            &ctx,
+                        )?
+                    },
+                    key,
+                    expr,
+                    tag: get_optional_attr(child, "tag")
+                        .map(|value| value.trim().to_string())
+                        .filter(|value| !value.is_empty()),
+                    speaker: get_optional_attr(child, "speaker")
+                        .map(|value| value.trim().to_string())
+                        .filter(|value| !value.is_empty())
+                        .map(|value| {
+                            let ctx = ExpressionNormalizeContext {
+                                all_script_access,
+                                module_name,
+                                current_script_name,
+                                visible_types,
+                                visible_functions,
+                                local_var_types,
+                                visible_module_vars,
+                                visible_module_consts,
+                            };
+                            normalize_template_literals(&value, &child.location, &ctx)
+                        })
+                        .transpose()?,
+                    once,
+                    global_once: parse_once_scope(child, once)?,
+                    location: child.location.clone(),
+                }
+            }
             "debug" => {
                 if !child.attributes.is_empty() {
                     return Err(ScriptLangError::with_span(
-                        "XML_ATTR_NOT_ALLOWED",
+                        ErrorCode::XmlAttrNotAllowed,
                         "<debug> does not support attributes. Use inline content only.",
                         child.location.clone(),
                     ));
@@ -1585,11 +1971,14 @@ fn compile_group_nodes(
                 ScriptNode::Code {
                     id: builder.next_node_id("code"),
                     code,
+                    internal: has_attr(child, INTERNAL_CODE_ATTR),
                     location: child.location.clone(),
                 }
             }
             "if" => {
                 let then_group_id = builder.next_group_id();
+                // The else id is reserved even without `<else>` so that later group ids (and the
+                // frames of previously saved snapshots) keep their numbering.
                 let else_group_id = builder.next_group_id();
 
                 let else_node = element_children(child).find(|candidate| candidate.name == "else");
@@ -1621,28 +2010,21 @@ fn compile_group_nodes(
                 );
                 then_result?;
 
-                if let Some(else_child) = else_node {
-                    let else_result = compile_child_group(
-                        group_id,
-                        &else_group_id,
-                        else_child,
-                        builder,
-                        scope,
-                        local_var_types,
-                        group_mode,
-                    );
-                    else_result?;
-                } else {
-                    builder.groups.insert(
-                        else_group_id.clone(),
-                        ImplicitGroup {
-                            group_id: else_group_id.clone(),
-                            parent_group_id: Some(group_id.to_string()),
-                            entry_node_id: None,
-                            nodes: Vec::new(),
-                        },
-                    );
-                }
+                let else_group_id = match else_node {
+                    Some(else_child) => {
+                        compile_child_group(
+                            group_id,
+                            &else_group_id,
+                            else_child,
+                            builder,
+                            scope,
+                            local_var_types,
+                            group_mode,
+                        )?;
+                        Some(else_group_id)
+                    }
+                    None => None,
+                };
 
                 ScriptNode::If {
                     id: builder.next_node_id("if"),
@@ -1664,7 +2046,7 @@ fn compile_group_nodes(
                         )?
                     },
                     then_group_id,
-                    else_group_id: Some(else_group_id),
+                    else_group_id,
                     location: child.location.clone(),
                 }
             }
@@ -1702,6 +2084,7 @@ fn compile_group_nodes(
                         )?
                     },
                     body_group_id,
+                    max_iterations: parse_while_max_iterations(child)?,
                     location: child.location.clone(),
                 }
             }
@@ -1724,12 +2107,39 @@ fn compile_group_nodes(
                 let mut entries = Vec::new();
                 let mut fall_over_seen = 0usize;
                 let mut fall_over_entry_index = None;
+                let mut pinned_seen = false;
+                let mut static_option_ids = Vec::new();
 
                 for choice_child in element_children(child) {
+                    if scope.strict_attributes {
+                        validate_strict_attributes(choice_child)?;
+                    }
                     match choice_child.name.as_str() {
                         "option" => {
                             let once = parse_bool_attr(choice_child, "once", false)?;
+                            let max_select = parse_option_max_select(choice_child)?;
+                            if once && max_select.is_some() {
+                                return Err(ScriptLangError::with_span(
+                                    ErrorCode::XmlOptionMaxSelectOnceConflict,
+                                    "An option cannot declare both once and max_select.",
+                                    choice_child.location.clone(),
+                                ));
+                            }
+                            // `max_select="1"` is exactly `once`, including its once-key.
+                            let once = once || max_select == Some(1);
+                            let max_select = max_select.filter(|limit| *limit > 1);
                             let fall_over = parse_bool_attr(choice_child, "fall_over", false)?;
+                            let pinned = parse_bool_attr(choice_child, "pinned", false)?;
+                            let scene = match get_optional_attr(choice_child, "scene") {
+                                Some(value) if value.trim().is_empty() => {
+                                    return Err(ScriptLangError::with_span(
+                                        ErrorCode::XmlOptionSceneEmpty,
+                                        "option scene cannot be empty.",
+                                        choice_child.location.clone(),
+                                    ));
+                                }
+                                value => value.map(|value| value.trim().to_string()),
+                            };
                             let when_expr = get_optional_attr(choice_child, "when")
                                 .map(|expr| {
                                     let ctx = ExpressionNormalizeContext {
@@ -1749,16 +2159,65 @@ fn compile_group_nodes(
                                     )
                                 })
                                 .transpose()?;
+                            let enabled_when_expr = get_optional_attr(choice_child, "enabled_when")
+                                .map(|expr| {
+                                    let ctx = ExpressionNormalizeContext {
+                                        all_script_access,
+                                        module_name,
+                                        current_script_name,
+                                        visible_types,
+                                        visible_functions,
+                                        local_var_types,
+                                        visible_module_vars,
+                                        visible_module_consts,
+                                    };
+                                    normalize_attribute_expression_literals(
+                                        &expr,
+                                        &choice_child.location,
+                                        &ctx,
+                                    )
+                                })
+                                .transpose()?;
+                            if pinned {
+                                if fall_over {
+                                    return Err(ScriptLangError::with_span(
+                                        ErrorCode::XmlOptionPinnedFallOverConflict,
+                                        "An option cannot be both pinned and fall_over.",
+                                        choice_child.location.clone(),
+                                    ));
+                                }
+                                if when_expr.is_some() {
+                                    return Err(ScriptLangError::with_span(
+                                        ErrorCode::XmlOptionPinnedWhenForbidden,
+                                        "pinned option cannot declare when.",
+                                        choice_child.location.clone(),
+                                    ));
+                                }
+                                pinned_seen = true;
+                            } else if pinned_seen {
+                                return Err(ScriptLangError::with_span(
+                                    ErrorCode::XmlOptionPinnedNotLast,
+                                    "pinned options must come after all other options.",
+                                    choice_child.location.clone(),
+                                ));
+                            }
                             if fall_over {
                                 fall_over_seen += 1;
                                 fall_over_entry_index = Some(entries.len());
                                 if when_expr.is_some() {
                                     return Err(ScriptLangError::with_span(
-                                        "XML_OPTION_FALL_OVER_WHEN_FORBIDDEN",
+                                        ErrorCode::XmlOptionFallOverWhenForbidden,
                                         "fall_over option cannot declare when.",
                                         choice_child.location.clone(),
                                     ));
                                 }
+                                if enabled_when_expr.is_some() {
+                                    return Err(ScriptLangError::with_span(
+                                        ErrorCode::XmlOptionFallOverWhenForbidden,
+                                        "fall_over option cannot declare enabled_when.",
+                                        choice_child.location.clone(),
+                                    ));
+                                }
                             }
 
                             let option_group_id = builder.next_group_id();
@@ -1775,9 +2234,11 @@ fn compile_group_nodes(
                             );
                             option_result?;
 
+                            let option_id = builder.next_choice_id();
+                            static_option_ids.push(option_id.clone());
                             entries.push(ChoiceEntry::Static {
                                 option: ChoiceOption {
-                                    id: builder.next_choice_id(),
+                                    id: option_id,
                                     text: {
                                         let ctx = ExpressionNormalizeContext {
                                             all_script_access,
@@ -1796,14 +2257,26 @@ fn compile_group_nodes(
                                         )?
                                     },
                                     when_expr,
+                                    enabled_when_expr,
                                     once,
+                                    global_once: parse_once_scope(choice_child, once)?,
+                                    max_select,
                                     fall_over,
+                                    pinned,
+                                    scene,
                                     group_id: option_group_id,
                                     location: choice_child.location.clone(),
                                 },
                             });
                         }
                         "dynamic-options" => {
+                            if pinned_seen {
+                                return Err(ScriptLangError::with_span(
+                                    ErrorCode::XmlOptionPinnedNotLast,
+                                    "pinned options must come after all other options.",
+                                    choice_child.location.clone(),
+                                ));
+                            }
                             let ctx = ExpressionNormalizeContext {
                                 all_script_access,
                                 module_name,
@@ -1836,25 +2309,28 @@ fn compile_group_nodes(
                             let templates = element_children(choice_child).collect::<Vec<_>>();
                             if templates.is_empty() {
                                 return Err(ScriptLangError::with_span(
-                                    "XML_DYNAMIC_OPTIONS_TEMPLATE_REQUIRED",
+                                    ErrorCode::XmlDynamicOptionsTemplateRequired,
                                     "<dynamic-options> must contain exactly one <option> template child.",
                                     choice_child.location.clone(),
                                 ));
                             }
                             if templates.len() != 1 || templates[0].name != "option" {
                                 return Err(ScriptLangError::with_span(
-                                    "XML_DYNAMIC_OPTIONS_CHILD_INVALID",
+                                    ErrorCode::XmlDynamicOptionsChildInvalid,
                                     "<dynamic-options> only supports exactly one direct <option> template child.",
                                     choice_child.location.clone(),
                                 ));
                             }
 
                             let template_option = templates[0];
+                            if scope.strict_attributes {
+                                validate_strict_attributes(template_option)?;
+                            }
                             let has_once = parse_bool_attr(template_option, "once", false)?;
-                            if has_once {
+                            if has_once || has_attr(template_option, "max_select") {
                                 return Err(ScriptLangError::with_span(
-                                    "XML_DYNAMIC_OPTION_ONCE_UNSUPPORTED",
-                                    "<dynamic-options> template <option> does not support once.",
+                                    ErrorCode::XmlDynamicOptionOnceUnsupported,
+                                    "<dynamic-options> template <option> does not support once or max_select.",
                                     template_option.location.clone(),
                                 ));
                             }
@@ -1862,11 +2338,32 @@ fn compile_group_nodes(
                                 parse_bool_attr(template_option, "fall_over", false)?;
                             if has_fall_over {
                                 return Err(ScriptLangError::with_span(
-                                    "XML_DYNAMIC_OPTION_FALL_OVER_UNSUPPORTED",
+                                    ErrorCode::XmlDynamicOptionFallOverUnsupported,
                                     "<dynamic-options> template <option> does not support fall_over.",
                                     template_option.location.clone(),
                                 ));
                             }
+                            if parse_bool_attr(template_option, "pinned", false)? {
+                                return Err(ScriptLangError::with_span(
+                                    ErrorCode::XmlDynamicOptionPinnedUnsupported,
+                                    "<dynamic-options> template <option> does not support pinned.",
+                                    template_option.location.clone(),
+                                ));
+                            }
+                            if has_attr(template_option, "enabled_when") {
+                                return Err(ScriptLangError::with_span(
+                                    ErrorCode::XmlDynamicOptionEnabledWhenUnsupported,
+                                    "<dynamic-options> template <option> does not support enabled_when.",
+                                    template_option.location.clone(),
+                                ));
+                            }
+                            if has_attr(template_option, "scene") {
+                                return Err(ScriptLangError::with_span(
+                                    ErrorCode::XmlDynamicOptionSceneUnsupported,
+                                    "<dynamic-options> template <option> does not support scene.",
+                                    template_option.location.clone(),
+                                ));
+                            }
 
                             let option_group_id = builder.next_group_id();
                             let option_mode = CompileGroupMode::new(mode.while_depth, true)
@@ -1943,7 +2440,7 @@ fn compile_group_nodes(
                         }
                         _ => {
                             return Err(ScriptLangError::with_span(
-                                "XML_CHOICE_CHILD_INVALID",
+                                ErrorCode::XmlChoiceChildInvalid,
                                 format!(
                                     "Unsupported child <{}> under <choice>.",
                                     choice_child.name
@@ -1956,58 +2453,78 @@ fn compile_group_nodes(
 
                 if fall_over_seen > 1 {
                     return Err(ScriptLangError::with_span(
-                        "XML_OPTION_FALL_OVER_DUPLICATE",
+                        ErrorCode::XmlOptionFallOverDuplicate,
                         "At most one fall_over option is allowed per choice.",
                         child.location.clone(),
                     ));
                 }
 
                 if let Some(index) = fall_over_entry_index {
-                    if index != entries.len().saturating_sub(1) {
+                    let pinned_count = entries
+                        .iter()
+                        .filter(|entry| {
+                            matches!(entry, ChoiceEntry::Static { option } if option.pinned)
+                        })
+                        .count();
+                    if index + pinned_count != entries.len().saturating_sub(1) {
                         return Err(ScriptLangError::with_span(
-                            "XML_OPTION_FALL_OVER_NOT_LAST",
-                            "fall_over option must be the last option.",
+                            ErrorCode::XmlOptionFallOverNotLast,
+                            "fall_over option must be the last option before any pinned options.",
                             child.location.clone(),
                         ));
                     }
                 }
 
+                let timeout_default_option_id =
+                    parse_choice_timeout_default(child, &static_option_ids)?;
+
                 ScriptNode::Choice {
                     id: builder.next_node_id("choice"),
                     prompt_text,
                     entries,
+                    timeout_default_option_id,
                     location: child.location.clone(),
                 }
             }
             "input" => {
                 if has_attr(child, "default") {
                     return Err(ScriptLangError::with_span(
-                        "XML_INPUT_DEFAULT_UNSUPPORTED",
+                        ErrorCode::XmlInputDefaultUnsupported,
                         "Attribute \"default\" is not supported on <input>.",
                         child.location.clone(),
                     ));
                 }
                 if has_any_child_content(child) {
                     return Err(ScriptLangError::with_span(
-                        "XML_INPUT_CONTENT_FORBIDDEN",
+                        ErrorCode::XmlInputContentForbidden,
                         "<input> cannot contain child nodes or inline text.",
                         child.location.clone(),
                     ));
                 }
                 let max_length = parse_input_max_length(child)?;
+                let format = parse_input_format(child)?;
+                let target_var = get_required_non_empty_attr(child, "var")?;
+                validate_input_target(
+                    &target_var,
+                    format,
+                    child,
+                    local_var_types,
+                    visible_module_vars,
+                )?;
 
                 ScriptNode::Input {
                     id: builder.next_node_id("input"),
-                    target_var: get_required_non_empty_attr(child, "var")?,
+                    target_var,
                     prompt_text: get_required_non_empty_attr(child, "text")?,
                     max_length,
+                    format,
                     location: child.location.clone(),
                 }
             }
             "break" => {
                 if mode.while_depth == 0 {
                     return Err(ScriptLangError::with_span(
-                        "XML_BREAK_OUTSIDE_WHILE",
+                        ErrorCode::XmlBreakOutsideWhile,
                         "<break/> is only valid inside <while>.",
                         child.location.clone(),
                     ));
@@ -2024,7 +2541,7 @@ fn compile_group_nodes(
                     ContinueTarget::Choice
                 } else {
                     return Err(ScriptLangError::with_span(
-                        "XML_CONTINUE_OUTSIDE_WHILE_OR_OPTION",
+                        ErrorCode::XmlContinueOutsideWhileOrOption,
                         "<continue/> is only valid inside <while> or as direct child of <option>.",
                         child.location.clone(),
                     ));
@@ -2067,6 +2584,13 @@ fn compile_group_nodes(
                                     &child.location,
                                     &ctx,
                                 )?;
+                            } else {
+                                validate_ref_arg_root(
+                                    &arg.value_expr,
+                                    child,
+                                    local_var_types,
+                                    visible_module_vars,
+                                )?;
                             }
                             Ok::<_, ScriptLangError>(arg)
                         })
@@ -2077,7 +2601,7 @@ fn compile_group_nodes(
             "goto" => {
                 if mode.script_kind != ScriptKind::Goto {
                     return Err(ScriptLangError::with_span(
-                        "XML_CALL_SCRIPT_GOTO_FORBIDDEN",
+                        ErrorCode::XmlCallScriptGotoForbidden,
                         "Call script does not support <goto/>.",
                         child.location.clone(),
                     ));
@@ -2107,7 +2631,7 @@ fn compile_group_nodes(
                     .collect::<Result<Vec<_>, _>>()?;
                 if args.iter().any(|arg| arg.is_ref) {
                     return Err(ScriptLangError::with_span(
-                        "XML_GOTO_REF_UNSUPPORTED",
+                        ErrorCode::XmlGotoRefUnsupported,
                         "Goto args do not support ref mode.",
                         child.location.clone(),
                     ));
@@ -2131,21 +2655,21 @@ fn compile_group_nodes(
             "return" => {
                 if mode.script_kind != ScriptKind::Call {
                     return Err(ScriptLangError::with_span(
-                        "XML_GOTO_SCRIPT_RETURN_FORBIDDEN",
+                        ErrorCode::XmlGotoScriptReturnForbidden,
                         "Goto script does not support <return/>.",
                         child.location.clone(),
                     ));
                 }
                 if has_attr(child, "script") || has_attr(child, "args") {
                     return Err(ScriptLangError::with_span(
-                        "XML_RETURN_ATTR_NOT_ALLOWED",
+                        ErrorCode::XmlReturnAttrNotAllowed,
                         "<return/> in call script does not support script/args attributes.",
                         child.location.clone(),
                     ));
                 }
                 if has_any_child_content(child) {
                     return Err(ScriptLangError::with_span(
-                        "XML_RETURN_CONTENT_FORBIDDEN",
+                        ErrorCode::XmlReturnContentForbidden,
                         "<return/> cannot contain child nodes or inline text.",
                         child.location.clone(),
                     ));
@@ -2159,21 +2683,21 @@ fn compile_group_nodes(
             "end" => {
                 if mode.script_kind != ScriptKind::Goto {
                     return Err(ScriptLangError::with_span(
-                        "XML_CALL_SCRIPT_END_FORBIDDEN",
+                        ErrorCode::XmlCallScriptEndForbidden,
                         "Call script does not support <end/>.",
                         child.location.clone(),
                     ));
                 }
                 if !child.attributes.is_empty() {
                     return Err(ScriptLangError::with_span(
-                        "XML_END_ATTR_NOT_ALLOWED",
+                        ErrorCode::XmlEndAttrNotAllowed,
                         "<end/> does not support attributes.",
                         child.location.clone(),
                     ));
                 }
                 if has_any_child_content(child) {
                     return Err(ScriptLangError::with_span(
-                        "XML_END_CONTENT_FORBIDDEN",
+                        ErrorCode::XmlEndContentForbidden,
                         "<end/> cannot contain child nodes or inline text.",
                         child.location.clone(),
                     ));
@@ -2183,66 +2707,191 @@ fn compile_group_nodes(
                     location: child.location.clone(),
                 }
             }
+            "checkpoint" => {
+                if has_any_child_content(child) {
+                    return Err(ScriptLangError::with_span(
+                        ErrorCode::XmlCheckpointContentForbidden,
+                        "<checkpoint/> cannot contain child nodes or inline text.",
+                        child.location.clone(),
+                    ));
+                }
+                ScriptNode::Checkpoint {
+                    id: builder.next_node_id("checkpoint"),
+                    checkpoint_id: get_required_non_empty_attr(child, "id")?,
+                    location: child.location.clone(),
+                }
+            }
+            "fail" => {
+                let code = get_required_non_empty_attr(child, "code")?;
+                if !fail_code_regex().is_match(&code) {
+                    return Err(ScriptLangError::with_span(
+                        ErrorCode::XmlFailCodeInvalid,
+                        format!(
+                            "<fail> code \"{}\" must be SCREAMING_SNAKE_CASE (A-Z, 0-9, _), starting with a letter.",
+                            code
+                        ),
+                        child.location.clone(),
+                    ));
+                }
+                if has_any_child_content(child) {
+                    return Err(ScriptLangError::with_span(
+                        ErrorCode::XmlFailContentForbidden,
+                        "<fail/> cannot contain child nodes or inline text.",
+                        child.location.clone(),
+                    ));
+                }
+                let ctx = ExpressionNormalizeContext {
+                    all_script_access,
+                    module_name,
+                    current_script_name,
+                    visible_types,
+                    visible_functions,
+                    local_var_types,
+                    visible_module_vars,
+                    visible_module_consts,
+                };
+                ScriptNode::Fail {
+                    id: builder.next_node_id("fail"),
+                    message: match get_optional_attr(child, "message") {
+                        Some(message) => {
+                            normalize_template_literals(&message, &child.location, &ctx)?
+                        }
+                        None => format!("Script failed with {}.", code),
+                    },
+                    code,
+                    location: child.location.clone(),
+                }
+            }
             "for" => {
                 return Err(ScriptLangError::with_span(
-                    "XML_FOR_INTERNAL",
+                    ErrorCode::XmlForInternal,
                     "<for> must be expanded before compile phase.",
                     child.location.clone(),
                 ))
             }
             "temp-input" => {
                 return Err(ScriptLangError::with_span(
-                    "XML_TEMP_INPUT_INTERNAL",
+                    ErrorCode::XmlTempInputInternal,
                     "<temp-input> must be expanded before compile phase.",
                     child.location.clone(),
                 ))
             }
             "else" => {
                 return Err(ScriptLangError::with_span(
-                    "XML_ELSE_POSITION",
+                    ErrorCode::XmlElsePosition,
                     "<else> can only appear inside <if>.",
                     child.location.clone(),
                 ))
             }
             removed @ ("loop" | "var" | "vars" | "step" | "set" | "push" | "remove") => {
                 return Err(ScriptLangError::with_span(
-                    "XML_REMOVED_NODE",
+                    ErrorCode::XmlRemovedNode,
                     format!("<{}> is removed in ScriptLang.", removed),
                     child.location.clone(),
                 ))
             }
             _ => {
                 return Err(ScriptLangError::with_span(
-                    "XML_NODE_UNSUPPORTED",
+                    ErrorCode::XmlNodeUnsupported,
                     format!("Unsupported node <{}> in <script> body.", child.name),
                     child.location.clone(),
                 ))
             }
         };
 
+        if let Some(label) = label {
+            builder.node_labels.insert(label, node.id().to_string());
+        }
         nodes.push(node);
     }
 
     Ok(())
 }
 
-pub(crate) fn node_id(node: &ScriptNode) -> &str {
-    match node {
-        ScriptNode::Text { id, .. }
-        | ScriptNode::Debug { id, .. }
-        | ScriptNode::Code { id, .. }
-        | ScriptNode::Var { id, .. }
-        | ScriptNode::If { id, .. }
-        | ScriptNode::While { id, .. }
-        | ScriptNode::Choice { id, .. }
-        | ScriptNode::Input { id, .. }
-        | ScriptNode::Break { id, .. }
-        | ScriptNode::Continue { id, .. }
-        | ScriptNode::Call { id, .. }
-        | ScriptNode::Goto { id, .. }
-        | ScriptNode::End { id, .. }
-        | ScriptNode::Return { id, .. } => id,
+/// Read the optional `label="..."` of a statement, rejecting invalid, duplicate and comment
+/// labels. Labels are unique per script so `sl_visited` can resolve them without qualification.
+fn parse_node_label(
+    child: &XmlElementNode,
+    builder: &GroupBuilder,
+) -> Result<Option<String>, ScriptLangError> {
+    let Some(label) = child.attributes.get("label") else {
+        return Ok(None);
+    };
+    let label = label.trim().to_string();
+    if child.name == "comment" || !decl_name_regex().is_match(&label) {
+        return Err(ScriptLangError::with_span(
+            ErrorCode::XmlNodeLabelInvalid,
+            format!("Invalid label \"{}\" on <{}>.", label, child.name),
+            child.location.clone(),
+        ));
+    }
+    if builder.node_labels.contains_key(&label) {
+        return Err(ScriptLangError::with_span(
+            ErrorCode::XmlNodeLabelDuplicate,
+            format!("Duplicate label \"{}\" in script.", label),
+            child.location.clone(),
+        ));
+    }
+    Ok(Some(label))
+}
+
+fn strict_attribute_allowlist(element_name: &str) -> Option<&'static [&'static str]> {
+    let allowlist: &'static [&'static str] = match element_name {
+        "script" => &["name", "kind", "tags", "args"],
+        "group" | "else" | "debug" | "break" | "continue" | "return" | "end" | "comment" => &[],
+        "code" => &[INTERNAL_CODE_ATTR],
+        "temp" => &["name", "type", "format"],
+        "text" => &["key", "expr", "tag", "speaker", "once", "scope"],
+        "if" => &["when"],
+        "while" => &["when", "max"],
+        "choice" => &["text", "timeout_default"],
+        "option" => &[
+            "text",
+            "when",
+            "enabled_when",
+            "once",
+            "max_select",
+            "scope",
+            "fall_over",
+            "pinned",
+            "scene",
+        ],
+        "dynamic-options" => &["array", "item", "index"],
+        "input" => &["var", "text", "max_length", "format"],
+        "call" | "goto" => &["script", "args"],
+        "fail" => &["code", "message"],
+        "checkpoint" => &["id"],
+        "uses" => &["functions"],
+        _ => return None,
+    };
+    Some(allowlist)
+}
+
+fn validate_strict_attributes(node: &XmlElementNode) -> Result<(), ScriptLangError> {
+    validate_strict_attributes_allowing(node, &[])
+}
+
+/// Like [`validate_strict_attributes`], but also accepts `extra` keys (such as the statement
+/// `label`) on top of the element's own allowlist.
+fn validate_strict_attributes_allowing(
+    node: &XmlElementNode,
+    extra: &[&str],
+) -> Result<(), ScriptLangError> {
+    let Some(allowlist) = strict_attribute_allowlist(&node.name) else {
+        return Ok(());
+    };
+    if let Some(key) = node
+        .attributes
+        .keys()
+        .find(|key| !allowlist.contains(&key.as_str()) && !extra.contains(&key.as_str()))
+    {
+        return Err(ScriptLangError::with_span(
+            ErrorCode::XmlUnknownAttr,
+            format!("Unknown attribute \"{}\" on <{}>.", key, node.name),
+            node.location.clone(),
+        ));
     }
+    Ok(())
 }
 
 pub(crate) fn parse_var_declaration(
@@ -2258,7 +2907,7 @@ pub(crate) fn parse_var_declaration(
 
     if has_attr(node, "value") {
         return Err(ScriptLangError::with_span(
-            "XML_ATTR_NOT_ALLOWED",
+            ErrorCode::XmlAttrNotAllowed,
             "Attribute \"value\" is not allowed on <temp>. Use inline content instead.",
             node.location.clone(),
         ));
@@ -2268,7 +2917,7 @@ pub(crate) fn parse_var_declaration(
         InitializerFormat::Inline => {
             if let Some(child) = element_children(node).next() {
                 return Err(ScriptLangError::with_span(
-                    "XML_VAR_CHILD_INVALID",
+                    ErrorCode::XmlVarChildInvalid,
                     format!(
                         "<temp> cannot contain child element <{}>. Use inline expression text only.",
                         child.name
@@ -2281,7 +2930,7 @@ pub(crate) fn parse_var_declaration(
             if inline.trim().is_empty() {
                 if matches!(ty, ScriptType::Enum { .. }) {
                     return Err(ScriptLangError::with_span(
-                        "ENUM_INIT_REQUIRED",
+                        ErrorCode::EnumInitRequired,
                         format!(
                             "<temp name=\"{}\"> with enum type requires explicit Type.Member initializer.",
                             name
@@ -2329,7 +2978,7 @@ pub(crate) fn parse_var_declaration(
 
 pub(crate) fn parse_type_name_segment<'a>(
     segment: &'a str,
-    parse_error_code: &'static str,
+    parse_error_code: ErrorCode,
     parse_error_label: &'static str,
     span: &SourceSpan,
 ) -> Result<(&'a str, &'a str), ScriptLangError> {
@@ -2377,7 +3026,7 @@ pub(crate) fn parse_script_args(
         let is_ref = segment.starts_with("ref:");
         if is_ref && script_kind == ScriptKind::Goto {
             return Err(ScriptLangError::with_span(
-                "SCRIPT_GOTO_ARGS_REF_UNSUPPORTED",
+                ErrorCode::ScriptGotoArgsRefUnsupported,
                 "Goto script params do not support ref mode.",
                 root.location.clone(),
             ));
@@ -2389,7 +3038,7 @@ pub(crate) fn parse_script_args(
         };
         let (type_raw, name) = parse_type_name_segment(
             normalized,
-            "SCRIPT_ARGS_PARSE_ERROR",
+            ErrorCode::ScriptArgsParseError,
             "script args",
             &root.location,
         )?;
@@ -2397,7 +3046,7 @@ pub(crate) fn parse_script_args(
         assert_decl_name_not_reserved_or_rhai_keyword(name, "script arg", root.location.clone())?;
         if !names.insert(name.to_string()) {
             return Err(ScriptLangError::with_span(
-                "SCRIPT_ARGS_DUPLICATE",
+                ErrorCode::ScriptArgsDuplicate,
                 format!("Script arg \"{}\" is declared more than once.", name),
                 root.location.clone(),
             ));
@@ -2417,6 +3066,31 @@ pub(crate) fn parse_script_args(
     Ok(params)
 }
 
+/// Parse `<script tags="combat, shop">` into identifier-like tags, keeping declaration order.
+fn parse_script_tags(root: &XmlElementNode) -> Result<Vec<String>, ScriptLangError> {
+    let Some(raw) = get_optional_attr(root, "tags") else {
+        return Ok(Vec::new());
+    };
+    let mut tags = Vec::new();
+    for item in raw.split(',') {
+        let tag = item.trim();
+        if !decl_name_regex().is_match(tag) {
+            return Err(ScriptLangError::with_span(
+                ErrorCode::XmlScriptTagInvalid,
+                format!(
+                    "Invalid tag \"{}\" in <script tags=\"{}\">. Tags must be identifiers (letters/digits/underscore).",
+                    tag, raw
+                ),
+                root.location.clone(),
+            ));
+        }
+        if !tags.iter().any(|existing| existing == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    Ok(tags)
+}
+
 fn parse_script_kind(root: &XmlElementNode) -> Result<ScriptKind, ScriptLangError> {
     let Some(raw) = get_optional_attr(root, "kind") else {
         return Ok(ScriptKind::Goto);
@@ -2424,7 +3098,7 @@ fn parse_script_kind(root: &XmlElementNode) -> Result<ScriptKind, ScriptLangErro
     let value = raw.trim();
     if value.is_empty() {
         return Err(ScriptLangError::with_span(
-            "XML_SCRIPT_KIND_INVALID",
+            ErrorCode::XmlScriptKindInvalid,
             "Attribute \"kind\" on <script> must be \"call\" or \"goto\".",
             root.location.clone(),
         ));
@@ -2433,7 +3107,7 @@ fn parse_script_kind(root: &XmlElementNode) -> Result<ScriptKind, ScriptLangErro
         "call" => Ok(ScriptKind::Call),
         "goto" => Ok(ScriptKind::Goto),
         _ => Err(ScriptLangError::with_span(
-            "XML_SCRIPT_KIND_INVALID",
+            ErrorCode::XmlScriptKindInvalid,
             format!(
                 "Unsupported <script kind=\"{}\">. Allowed values: call, goto.",
                 value
@@ -2459,14 +3133,14 @@ pub(crate) fn parse_function_args(
     for segment in split_by_top_level_comma(&raw) {
         if segment.starts_with("ref:") {
             return Err(ScriptLangError::with_span(
-                "XML_FUNCTION_ARGS_REF_UNSUPPORTED",
+                ErrorCode::XmlFunctionArgsRefUnsupported,
                 format!("Function arg \"{}\" cannot use ref mode.", segment),
                 node.location.clone(),
             ));
         }
         let (type_raw, name) = parse_type_name_segment(
             &segment,
-            "FUNCTION_ARGS_PARSE_ERROR",
+            ErrorCode::FunctionArgsParseError,
             "function args",
             &node.location,
         )?;
@@ -2474,7 +3148,7 @@ pub(crate) fn parse_function_args(
 
         if !names.insert(name.to_string()) {
             return Err(ScriptLangError::with_span(
-                "FUNCTION_ARGS_DUPLICATE",
+                ErrorCode::FunctionArgsDuplicate,
                 format!("Function arg \"{}\" is declared more than once.", name),
                 node.location.clone(),
             ));
@@ -2495,7 +3169,7 @@ pub(crate) fn parse_function_return(
 ) -> Result<ParsedFunctionReturnDecl, ScriptLangError> {
     if has_attr(node, "return") {
         return Err(ScriptLangError::with_span(
-            "FUNCTION_RETURN_ATTR_INVALID",
+            ErrorCode::FunctionReturnAttrInvalid,
             "Attribute \"return\" is not allowed on <function>.",
             node.location.clone(),
         ));
@@ -2513,7 +3187,7 @@ fn parse_input_max_length(node: &XmlElementNode) -> Result<Option<usize>, Script
     };
     let parsed = raw.trim().parse::<usize>().map_err(|_| {
         ScriptLangError::with_span(
-            "XML_INPUT_MAX_LENGTH_INVALID",
+            ErrorCode::XmlInputMaxLengthInvalid,
             format!(
                 "Attribute \"max_length\" on <input> must be a non-negative integer, got \"{}\".",
                 raw
@@ -2524,6 +3198,99 @@ fn parse_input_max_length(node: &XmlElementNode) -> Result<Option<usize>, Script
     Ok(Some(parsed))
 }
 
+fn parse_comment(
+    node: &XmlElementNode,
+    node_index: usize,
+) -> Result<GroupComment, ScriptLangError> {
+    if element_children(node).next().is_some() {
+        return Err(ScriptLangError::with_span(
+            ErrorCode::XmlCommentContentInvalid,
+            "<comment> can only contain text.",
+            node.location.clone(),
+        ));
+    }
+    Ok(GroupComment {
+        node_index,
+        text: inline_text_content(node).trim().to_string(),
+        location: node.location.clone(),
+    })
+}
+
+fn parse_while_max_iterations(node: &XmlElementNode) -> Result<Option<usize>, ScriptLangError> {
+    let Some(raw) = get_optional_attr(node, "max") else {
+        return Ok(None);
+    };
+    match raw.trim().parse::<usize>() {
+        Ok(parsed) if parsed > 0 => Ok(Some(parsed)),
+        _ => Err(ScriptLangError::with_span(
+            ErrorCode::XmlWhileMaxInvalid,
+            format!(
+                "Attribute \"max\" on <while> must be a positive integer, got \"{}\".",
+                raw
+            ),
+            node.location.clone(),
+        )),
+    }
+}
+
+fn parse_option_max_select(node: &XmlElementNode) -> Result<Option<u32>, ScriptLangError> {
+    let Some(raw) = get_optional_attr(node, "max_select") else {
+        return Ok(None);
+    };
+    match raw.trim().parse::<u32>() {
+        Ok(parsed) if parsed > 0 => Ok(Some(parsed)),
+        _ => Err(ScriptLangError::with_span(
+            ErrorCode::XmlOptionMaxSelectInvalid,
+            format!(
+                "Attribute \"max_select\" on <option> must be a positive integer, got \"{}\".",
+                raw
+            ),
+            node.location.clone(),
+        )),
+    }
+}
+
+/// Resolve `timeout_default` (0-based index among static `<option>` children) to an option id.
+fn parse_choice_timeout_default(
+    node: &XmlElementNode,
+    static_option_ids: &[String],
+) -> Result<Option<String>, ScriptLangError> {
+    let Some(raw) = get_optional_attr(node, "timeout_default") else {
+        return Ok(None);
+    };
+    raw.trim()
+        .parse::<usize>()
+        .ok()
+        .and_then(|index| static_option_ids.get(index))
+        .map(|id| Some(id.clone()))
+        .ok_or_else(|| {
+            ScriptLangError::with_span(
+                ErrorCode::XmlChoiceTimeoutDefaultUnknown,
+                format!(
+                    "Attribute \"timeout_default\" on <choice> must be the index of an <option>, got \"{}\".",
+                    raw
+                ),
+                node.location.clone(),
+            )
+        })
+}
+
+fn parse_input_format(node: &XmlElementNode) -> Result<InputFormat, ScriptLangError> {
+    match get_optional_attr(node, "format").as_deref().map(str::trim) {
+        None | Some("text") => Ok(InputFormat::Text),
+        Some("json") => Ok(InputFormat::Json),
+        Some("boolean") => Ok(InputFormat::Boolean),
+        Some(raw) => Err(ScriptLangError::with_span(
+            ErrorCode::XmlInputFormatInvalid,
+            format!(
+                "Attribute \"format\" on <input> must be \"text\", \"json\" or \"boolean\", got \"{}\".",
+                raw
+            ),
+            node.location.clone(),
+        )),
+    }
+}
+
 pub(crate) fn contains_return_statement(code: &str) -> bool {
     let bytes = code.as_bytes();
     let mut idx = 0usize;
@@ -2848,6 +3615,7 @@ mod script_compile_tests {
             visible_module_consts: &BTreeMap::new(),
             all_script_access: &BTreeMap::new(),
             invoke_all_functions: &BTreeMap::new(),
+            strict_attributes: false,
         })
         .expect_err("empty kind should fail");
         assert_eq!(error.code, "XML_SCRIPT_KIND_INVALID");
@@ -2869,6 +3637,7 @@ mod script_compile_tests {
             visible_module_consts: &BTreeMap::new(),
             all_script_access: &BTreeMap::new(),
             invoke_all_functions: &BTreeMap::new(),
+            strict_attributes: false,
         });
         // Explicit goto kind should compile successfully
         assert!(result.is_ok());
@@ -2890,6 +3659,7 @@ mod script_compile_tests {
             visible_module_consts: &BTreeMap::new(),
             all_script_access: &BTreeMap::new(),
             invoke_all_functions: &BTreeMap::new(),
+            strict_attributes: false,
         })
         .expect_err("invalid kind should fail");
         assert_eq!(error.code, "XML_SCRIPT_KIND_INVALID");
@@ -2912,6 +3682,7 @@ mod script_compile_tests {
             visible_module_consts: &BTreeMap::new(),
             all_script_access: &BTreeMap::new(),
             invoke_all_functions: &BTreeMap::new(),
+            strict_attributes: false,
         })
         .expect_err("goto in call script should fail");
         assert_eq!(error.code, "XML_CALL_SCRIPT_GOTO_FORBIDDEN");
@@ -2932,6 +3703,7 @@ mod script_compile_tests {
             visible_module_consts: &BTreeMap::new(),
             all_script_access: &BTreeMap::new(),
             invoke_all_functions: &BTreeMap::new(),
+            strict_attributes: false,
         })
         .expect_err("return in goto script should fail");
         assert_eq!(error.code, "XML_GOTO_SCRIPT_RETURN_FORBIDDEN");
@@ -2952,6 +3724,7 @@ mod script_compile_tests {
             visible_module_consts: &BTreeMap::new(),
             all_script_access: &BTreeMap::new(),
             invoke_all_functions: &BTreeMap::new(),
+            strict_attributes: false,
         })
         .expect_err("end in call script should fail");
         assert_eq!(error.code, "XML_CALL_SCRIPT_END_FORBIDDEN");
@@ -2973,6 +3746,7 @@ mod script_compile_tests {
             visible_module_consts: &BTreeMap::new(),
             all_script_access: &BTreeMap::new(),
             invoke_all_functions: &BTreeMap::new(),
+            strict_attributes: false,
         })
         .expect_err("return with content should fail");
         assert_eq!(error.code, "XML_RETURN_CONTENT_FORBIDDEN");
@@ -2993,6 +3767,7 @@ mod script_compile_tests {
             visible_module_consts: &BTreeMap::new(),
             all_script_access: &BTreeMap::new(),
             invoke_all_functions: &BTreeMap::new(),
+            strict_attributes: false,
         })
         .expect_err("end with attr should fail");
         assert_eq!(error.code, "XML_END_ATTR_NOT_ALLOWED");
@@ -3013,6 +3788,7 @@ mod script_compile_tests {
             visible_module_consts: &BTreeMap::new(),
             all_script_access: &BTreeMap::new(),
             invoke_all_functions: &BTreeMap::new(),
+            strict_attributes: false,
         })
         .expect_err("end with content should fail");
         assert_eq!(error.code, "XML_END_CONTENT_FORBIDDEN");
@@ -3035,6 +3811,7 @@ mod script_compile_tests {
             visible_module_consts: &BTreeMap::new(),
             all_script_access: &BTreeMap::new(),
             invoke_all_functions: &BTreeMap::new(),
+            strict_attributes: false,
         })
         .expect_err("ref args in goto script should fail");
         assert_eq!(error.code, "SCRIPT_GOTO_ARGS_REF_UNSUPPORTED");
@@ -3055,6 +3832,7 @@ mod script_compile_tests {
             visible_module_consts: &BTreeMap::new(),
             all_script_access: &BTreeMap::new(),
             invoke_all_functions: &BTreeMap::new(),
+            strict_attributes: false,
         })
         .expect_err("empty script attr should fail");
         assert_eq!(error.code, "XML_EMPTY_ATTR");
@@ -3446,6 +4224,7 @@ mod script_compile_tests {
                 r#type: ScriptType::Function,
                 initial_value_expr: None,
                 location: span.clone(),
+                session: false,
             },
         );
         validate_invoke_first_arg(
@@ -3868,43 +4647,300 @@ mod script_compile_tests {
     }
 
     #[test]
-    fn function_temp_rejects_plain_string_initializer() {
-        let root = parse_xml_document(
-            r#"<script name="main"><temp name="fnRef" type="function">"main.add"</temp></script>"#,
-        )
-        .expect("xml")
-        .root;
-        let mut visible_functions = BTreeMap::new();
-        visible_functions.insert(
-            "main.add".to_string(),
-            FunctionDecl {
-                name: "main.add".to_string(),
-                params: Vec::new(),
-                return_binding: FunctionReturn {
-                    r#type: ScriptType::Primitive {
-                        name: "int".to_string(),
-                    },
-                    location: SourceSpan::synthetic(),
-                },
-                code: "out = 1;".to_string(),
-                location: SourceSpan::synthetic(),
-            },
-        );
-        let error = compile_script(CompileScriptOptions {
-            script_path: "main.xml",
-            root: &root,
-            script_access: AccessLevel::Public,
-            qualified_script_name: Some("main.main"),
-            module_name: Some("main"),
-            visible_types: &BTreeMap::new(),
-            visible_functions: &visible_functions,
-            visible_module_vars: &BTreeMap::new(),
-            visible_module_consts: &BTreeMap::new(),
-            all_script_access: &BTreeMap::new(),
-            invoke_all_functions: &BTreeMap::new(),
-        })
-        .expect_err("function temp with string should fail");
-        assert_eq!(error.code, "XML_FUNCTION_ASSIGN_STRING_FORBIDDEN");
+    fn function_temp_rejects_plain_string_initializer() {
+        let root = parse_xml_document(
+            r#"<script name="main"><temp name="fnRef" type="function">"main.add"</temp></script>"#,
+        )
+        .expect("xml")
+        .root;
+        let mut visible_functions = BTreeMap::new();
+        visible_functions.insert(
+            "main.add".to_string(),
+            FunctionDecl {
+                name: "main.add".to_string(),
+                params: Vec::new(),
+                return_binding: FunctionReturn {
+                    r#type: ScriptType::Primitive {
+                        name: "int".to_string(),
+                    },
+                    location: SourceSpan::synthetic(),
+                },
+                code: "out = 1;".to_string(),
+                location: SourceSpan::synthetic(),
+            },
+        );
+        let error = compile_script(CompileScriptOptions {
+            script_path: "main.xml",
+            root: &root,
+            script_access: AccessLevel::Public,
+            qualified_script_name: Some("main.main"),
+            module_name: Some("main"),
+            visible_types: &BTreeMap::new(),
+            visible_functions: &visible_functions,
+            visible_module_vars: &BTreeMap::new(),
+            visible_module_consts: &BTreeMap::new(),
+            all_script_access: &BTreeMap::new(),
+            invoke_all_functions: &BTreeMap::new(),
+            strict_attributes: false,
+        })
+        .expect_err("function temp with string should fail");
+        assert_eq!(error.code, "XML_FUNCTION_ASSIGN_STRING_FORBIDDEN");
+    }
+
+    #[test]
+    fn duplicate_temp_in_scope_is_rejected_at_compile_time() {
+        let compile = |body: &str| {
+            let xml = format!(
+                r#"<module name="main" export="script:main">
+<script name="main">{body}</script>
+<script name="other" kind="call"><temp name="x" type="int">3</temp></script>
+</module>"#
+            );
+            compile_project_bundle_from_xml_map(&compiler_test_support::map(&[(
+                "main.xml",
+                xml.as_str(),
+            )]))
+        };
+
+        let error = compile(
+            r#"<temp name="x" type="int">1</temp>
+<temp name="x" type="int">2</temp>"#,
+        )
+        .expect_err("same-group duplicate should fail");
+        assert_eq!(error.code, "XML_VAR_DUPLICATE");
+        assert_eq!(error.span.expect("span").start.line, 3);
+
+        let error = compile(
+            r#"<temp name="x" type="int">1</temp>
+<if when="true"><temp name="x" type="int">2</temp></if>"#,
+        )
+        .expect_err("redeclaring a parent-scope var should fail");
+        assert_eq!(error.code, "XML_VAR_DUPLICATE");
+
+        compile(
+            r#"<if when="true"><temp name="y" type="int">1</temp></if>
+<if when="true"><temp name="y" type="int">2</temp></if>
+<temp name="x" type="int">1</temp>
+<call script="@other"/>"#,
+        )
+        .expect("sibling groups and other scripts may reuse names");
+    }
+
+    #[test]
+    fn input_target_must_be_declared_with_matching_type() {
+        let compile = |body: &str| {
+            let xml = format!(
+                r##"<module name="main" export="script:main">
+<type name="Hero">
+  <field name="name" type="string"/>
+  <field name="hp" type="int"/>
+</type>
+<var name="title" type="string">""</var>
+<script name="main" args="string:nick">{body}</script>
+</module>"##
+            );
+            compile_project_bundle_from_xml_map(&compiler_test_support::map(&[(
+                "main.xml",
+                xml.as_str(),
+            )]))
+        };
+
+        compile(
+            r##"<temp name="hero" type="Hero">#{name: "", hp: 1}</temp>
+<temp name="ok" type="boolean">false</temp>
+<temp name="bag" type="#{string}">#{}</temp>
+<input var="nick" text="p"/>
+<input var="title" text="p"/>
+<input var="hero.name" text="p"/>
+<input var="bag.key" text="p"/>
+<input var="ok" text="p" format="boolean"/>
+<input var="hero" text="p" format="json"/>"##,
+        )
+        .expect("declared targets of matching type should compile");
+
+        let error = compile(r##"<input var="nmae" text="p"/>"##).expect_err("typo should fail");
+        assert_eq!(error.code, "XML_INPUT_VAR_UNKNOWN");
+        let error = compile(
+            r##"<if when="true"><temp name="inner" type="string">""</temp></if>
+<input var="inner" text="p"/>"##,
+        )
+        .expect_err("out-of-scope target should fail");
+        assert_eq!(error.code, "XML_INPUT_VAR_UNKNOWN");
+
+        let error = compile(
+            r##"<temp name="hero" type="Hero">#{name: "", hp: 1}</temp>
+<input var="hero.hp" text="p"/>"##,
+        )
+        .expect_err("int field should fail");
+        assert_eq!(error.code, "XML_INPUT_VAR_TYPE");
+        let error = compile(r##"<input var="nick" text="p" format="boolean"/>"##)
+            .expect_err("string target for boolean input should fail");
+        assert_eq!(error.code, "XML_INPUT_VAR_TYPE");
+    }
+
+    #[test]
+    fn option_max_select_validates_and_folds_the_single_case_into_once() {
+        let compile = |options: &str| {
+            let xml = format!(
+                r#"<module name="main" export="script:main"><script name="main"><choice text="Pick">{options}</choice></script></module>"#
+            );
+            compile_project_bundle_from_xml_map(&compiler_test_support::map(&[(
+                "main.xml",
+                xml.as_str(),
+            )]))
+        };
+
+        let bundle = compile(
+            r#"<option text="A" max_select="3"><text>a</text></option><option text="B" max_select="1"><text>b</text></option>"#,
+        )
+        .expect("compile");
+        let script = &bundle.scripts["main.main"];
+        let entries =
+            compiler_test_support::choice_entries(&script.groups[&script.root_group_id].nodes[0])
+                .expect("choice expected");
+        assert!(matches!(
+            &entries[0],
+            ChoiceEntry::Static { option } if option.max_select == Some(3) && !option.once
+        ));
+        assert!(matches!(
+            &entries[1],
+            ChoiceEntry::Static { option } if option.max_select.is_none() && option.once
+        ));
+
+        for raw in ["0", "-1", "two", ""] {
+            let error = compile(&format!(
+                r#"<option text="A" max_select="{raw}"><text>a</text></option>"#
+            ))
+            .expect_err("invalid max_select should fail");
+            assert_eq!(error.code, "XML_OPTION_MAX_SELECT_INVALID", "{raw}");
+        }
+        let error =
+            compile(r#"<option text="A" once="true" max_select="2"><text>a</text></option>"#)
+                .expect_err("once with max_select should fail");
+        assert_eq!(error.code, "XML_OPTION_MAX_SELECT_ONCE_CONFLICT");
+    }
+
+    #[test]
+    fn option_enabled_when_is_rejected_on_fall_over_and_dynamic_templates() {
+        let compile = |options: &str| {
+            let xml = format!(
+                r#"<module name="main" export="script:main"><script name="main"><temp name="xs" type="int[]">[1]</temp><choice text="Pick">{options}</choice></script></module>"#
+            );
+            compile_project_bundle_from_xml_map(&compiler_test_support::map(&[(
+                "main.xml",
+                xml.as_str(),
+            )]))
+        };
+
+        let bundle =
+            compile(r#"<option text="A" enabled_when="xs.len() > 0"><text>a</text></option>"#)
+                .expect("compile");
+        let script = &bundle.scripts["main.main"];
+        let entries =
+            compiler_test_support::choice_entries(&script.groups[&script.root_group_id].nodes[1])
+                .expect("choice expected");
+        assert!(matches!(
+            &entries[0],
+            ChoiceEntry::Static { option }
+                if option.enabled_when_expr.as_deref() == Some("xs.len() > 0")
+        ));
+
+        let error = compile(
+            r#"<option text="A"><text>a</text></option><option text="B" fall_over="true" enabled_when="true"><text>b</text></option>"#,
+        )
+        .expect_err("fall_over enabled_when should fail");
+        assert_eq!(error.code, "XML_OPTION_FALL_OVER_WHEN_FORBIDDEN");
+        let error = compile(
+            r#"<dynamic-options array="xs" item="x"><option text="${x}" enabled_when="true"><text>x</text></option></dynamic-options>"#,
+        )
+        .expect_err("template enabled_when should fail");
+        assert_eq!(error.code, "XML_DYNAMIC_OPTION_ENABLED_WHEN_UNSUPPORTED");
+    }
+
+    #[test]
+    fn once_scope_attribute_selects_global_once_state() {
+        let compile = |body: &str| {
+            let xml = format!(
+                r#"<module name="main" export="script:main"><script name="main">{body}</script></module>"#
+            );
+            compile_project_bundle_from_xml_map(&compiler_test_support::map(&[(
+                "main.xml",
+                xml.as_str(),
+            )]))
+        };
+
+        let bundle = compile(
+            r#"<text once="true" scope="global">tip</text>
+<text once="true" scope="script">intro</text>
+<choice text="Pick"><option text="A" once="true" scope="global"><text>a</text></option></choice>"#,
+        )
+        .expect("compile");
+        let script = &bundle.scripts["main.main"];
+        let nodes = &script.groups[&script.root_group_id].nodes;
+        assert!(matches!(
+            &nodes[0],
+            ScriptNode::Text {
+                global_once: true,
+                ..
+            }
+        ));
+        assert!(matches!(
+            &nodes[1],
+            ScriptNode::Text {
+                once: true,
+                global_once: false,
+                ..
+            }
+        ));
+        let entries = compiler_test_support::choice_entries(&nodes[2]).expect("choice expected");
+        assert!(matches!(
+            &entries[0],
+            ChoiceEntry::Static { option } if option.global_once
+        ));
+
+        let error = compile(r#"<text once="true" scope="world">x</text>"#)
+            .expect_err("unknown scope should fail");
+        assert_eq!(error.code, "XML_ONCE_SCOPE_INVALID");
+        let error = compile(r#"<text scope="global">x</text>"#)
+            .expect_err("scope without once should fail");
+        assert_eq!(error.code, "XML_ONCE_SCOPE_WITHOUT_ONCE");
+    }
+
+    #[test]
+    fn if_without_else_emits_no_else_group_and_keeps_group_numbering() {
+        let files = compiler_test_support::map(&[(
+            "main.xml",
+            r#"<module name="main" export="script:main">
+<script name="main">
+  <if when="true"><text>A</text></if>
+  <group><text>G</text></group>
+  <if when="false"><text>B</text><else><text>C</text></else></if>
+</script>
+</module>"#,
+        )]);
+        let bundle = compile_project_bundle_from_xml_map(&files).expect("compile");
+        let main = &bundle.scripts["main.main"];
+        assert!(main.groups.values().all(|group| !group.nodes.is_empty()));
+
+        let root = &main.groups[&main.root_group_id].nodes;
+        assert!(root
+            .iter()
+            .all(|node| matches!(node, ScriptNode::If { .. })));
+        let ifs = root
+            .iter()
+            .filter_map(|node| match node {
+                ScriptNode::If {
+                    then_group_id,
+                    else_group_id,
+                    ..
+                } => Some((then_group_id.clone(), else_group_id.clone())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let base = "main.xml__main.main";
+        assert_eq!(ifs[0], (format!("{base}::g1"), None));
+        assert_eq!(ifs[1], (format!("{base}::g3"), None));
+        assert_eq!(ifs[2], (format!("{base}::g5"), Some(format!("{base}::g6"))));
     }
 
     #[test]
@@ -4083,11 +5119,17 @@ mod script_compile_tests {
                 )),
                 XmlNode::Element(xml_element(
                     "input",
-                    &[("var", "name"), ("text", "prompt")],
+                    &[("var", "answer"), ("text", "prompt")],
                     Vec::new(),
                 )),
             ],
         );
+        let visible_var_types = BTreeMap::from([(
+            "answer".to_string(),
+            ScriptType::Primitive {
+                name: "string".to_string(),
+            },
+        )]);
 
         compile_group(
             &root_group,
@@ -4095,7 +5137,7 @@ mod script_compile_tests {
             &container,
             &mut builder,
             &BTreeMap::new(),
-            &BTreeMap::new(),
+            &visible_var_types,
             CompileGroupMode::new(0, false),
         )
         .expect("group container should compile");
@@ -4639,6 +5681,54 @@ mod script_compile_tests {
                     )]),
                     "XML_OPTION_FALL_OVER_NOT_LAST",
                 ),
+                (
+                    "choice pinned fall_over conflict",
+                    map(&[(
+                        "main.xml",
+                        "<script name=\"main\"><choice text=\"c\"><option text=\"a\"/><option text=\"b\" pinned=\"true\" fall_over=\"true\"/></choice></script>",
+                    )]),
+                    "XML_OPTION_PINNED_FALL_OVER_CONFLICT",
+                ),
+                (
+                    "choice pinned with when forbidden",
+                    map(&[(
+                        "main.xml",
+                        "<script name=\"main\"><choice text=\"c\"><option text=\"a\"/><option text=\"b\" pinned=\"true\" when=\"true\"/></choice></script>",
+                    )]),
+                    "XML_OPTION_PINNED_WHEN_FORBIDDEN",
+                ),
+                (
+                    "choice pinned not last",
+                    map(&[(
+                        "main.xml",
+                        "<script name=\"main\"><choice text=\"c\"><option text=\"a\" pinned=\"true\"/><option text=\"b\" fall_over=\"true\"/></choice></script>",
+                    )]),
+                    "XML_OPTION_PINNED_NOT_LAST",
+                ),
+                (
+                    "choice dynamic options after pinned",
+                    map(&[(
+                        "main.xml",
+                        "<script name=\"main\"><choice text=\"c\"><option text=\"a\" pinned=\"true\"/><dynamic-options array=\"arr\" item=\"it\"><option text=\"b\"/></dynamic-options></choice></script>",
+                    )]),
+                    "XML_OPTION_PINNED_NOT_LAST",
+                ),
+                (
+                    "dynamic option pinned unsupported",
+                    map(&[(
+                        "main.xml",
+                        "<script name=\"main\"><choice text=\"c\"><dynamic-options array=\"arr\" item=\"it\"><option text=\"a\" pinned=\"true\"/></dynamic-options></choice></script>",
+                    )]),
+                    "XML_DYNAMIC_OPTION_PINNED_UNSUPPORTED",
+                ),
+                (
+                    "dynamic option scene unsupported",
+                    map(&[(
+                        "main.xml",
+                        "<script name=\"main\"><choice text=\"c\"><dynamic-options array=\"arr\" item=\"it\"><option text=\"a\" scene=\"x\"/></dynamic-options></choice></script>",
+                    )]),
+                    "XML_DYNAMIC_OPTION_SCENE_UNSUPPORTED",
+                ),
                 (
                     "dynamic options template required",
                     map(&[(
@@ -4727,6 +5817,54 @@ mod script_compile_tests {
                     )]),
                     "XML_INPUT_MAX_LENGTH_INVALID",
                 ),
+                (
+                    "comment with element content",
+                    map(&[(
+                        "main.xml",
+                        "<script name=\"main\"><comment><text>x</text></comment><end/></script>",
+                    )]),
+                    "XML_COMMENT_CONTENT_INVALID",
+                ),
+                (
+                    "while max zero invalid",
+                    map(&[(
+                        "main.xml",
+                        "<script name=\"main\"><while when=\"true\" max=\"0\"><break/></while></script>",
+                    )]),
+                    "XML_WHILE_MAX_INVALID",
+                ),
+                (
+                    "while max text invalid",
+                    map(&[(
+                        "main.xml",
+                        "<script name=\"main\"><while when=\"true\" max=\"lots\"><break/></while></script>",
+                    )]),
+                    "XML_WHILE_MAX_INVALID",
+                ),
+                (
+                    "choice timeout_default out of range",
+                    map(&[(
+                        "main.xml",
+                        "<script name=\"main\"><choice text=\"c\" timeout_default=\"1\"><option text=\"a\"/></choice></script>",
+                    )]),
+                    "XML_CHOICE_TIMEOUT_DEFAULT_UNKNOWN",
+                ),
+                (
+                    "choice timeout_default not an index",
+                    map(&[(
+                        "main.xml",
+                        "<script name=\"main\"><choice text=\"c\" timeout_default=\"a\"><option text=\"a\"/></choice></script>",
+                    )]),
+                    "XML_CHOICE_TIMEOUT_DEFAULT_UNKNOWN",
+                ),
+                (
+                    "input format invalid",
+                    map(&[(
+                        "main.xml",
+                        "<script name=\"main\"><input var=\"x\" text=\"p\" format=\"yaml\"/></script>",
+                    )]),
+                    "XML_INPUT_FORMAT_INVALID",
+                ),
                 (
                     "goto ref unsupported",
                     map(&[(
@@ -4775,6 +5913,54 @@ mod script_compile_tests {
                     )]),
                     "CALL_ARGS_PARSE_ERROR",
                 ),
+                (
+                    "text key with inline content",
+                    map(&[(
+                        "main.xml",
+                        "<script name=\"main\"><text key=\"intro\">Hello</text></script>",
+                    )]),
+                    "XML_TEXT_KEY_CONFLICT",
+                ),
+                (
+                    "text expr with inline content",
+                    map(&[(
+                        "main.xml",
+                        "<script name=\"main\"><text expr=\"1 + 1\">Hello</text></script>",
+                    )]),
+                    "XML_TEXT_EXPR_AND_INLINE",
+                ),
+                (
+                    "text expr with key",
+                    map(&[(
+                        "main.xml",
+                        "<script name=\"main\"><text key=\"intro\" expr=\"1 + 1\"/></script>",
+                    )]),
+                    "XML_TEXT_KEY_CONFLICT",
+                ),
+                (
+                    "text expr empty",
+                    map(&[(
+                        "main.xml",
+                        "<script name=\"main\"><text expr=\" \"/></script>",
+                    )]),
+                    "XML_TEXT_EXPR_EMPTY",
+                ),
+                (
+                    "text key empty",
+                    map(&[(
+                        "main.xml",
+                        "<script name=\"main\"><text key=\" \"/></script>",
+                    )]),
+                    "XML_TEXT_KEY_EMPTY",
+                ),
+                (
+                    "call ref arg unknown root",
+                    map(&[(
+                        "main.xml",
+                        "<script name=\"main\"><temp name=\"hp\" type=\"int\">1</temp><call script=\"@main.main\" args=\"ref:hpp.value\"/></script>",
+                    )]),
+                    "CALL_REF_ROOT_UNKNOWN",
+                ),
                 (
                     "script args reserved prefix",
                     map(&[(
@@ -4791,6 +5977,30 @@ mod script_compile_tests {
                     )]),
                     "NAME_RHAI_KEYWORD_RESERVED",
                 ),
+                (
+                    "option scene empty",
+                    map(&[(
+                        "main.xml",
+                        "<script name=\"main\"><choice text=\"c\"><option text=\"a\" scene=\" \"><text>x</text></option></choice></script>",
+                    )]),
+                    "XML_OPTION_SCENE_EMPTY",
+                ),
+                (
+                    "script tag invalid",
+                    map(&[(
+                        "main.xml",
+                        "<script name=\"main\" tags=\"combat,two words\"><end/></script>",
+                    )]),
+                    "XML_SCRIPT_TAG_INVALID",
+                ),
+                (
+                    "uses function invalid",
+                    map(&[(
+                        "main.xml",
+                        "<script name=\"main\"><uses functions=\"roll,two words\"/><end/></script>",
+                    )]),
+                    "XML_USES_FUNCTION_INVALID",
+                ),
                 (
                     "loop removed",
                     map(&[(
@@ -5073,6 +6283,7 @@ mod script_compile_tests {
             visible_module_consts: &BTreeMap::new(),
             all_script_access: &BTreeMap::new(),
             invoke_all_functions: &BTreeMap::new(),
+            strict_attributes: false,
         })
         .expect_err("compile_script should require script root");
         assert_eq!(compile_root_error.code, "XML_ROOT_INVALID");
@@ -5090,6 +6301,7 @@ mod script_compile_tests {
             visible_module_consts: &BTreeMap::new(),
             all_script_access: &BTreeMap::new(),
             invoke_all_functions: &BTreeMap::new(),
+            strict_attributes: false,
         })
         .expect_err("compile_script should require script name");
         assert_eq!(missing_name_error.code, "XML_MISSING_ATTR");
@@ -5107,6 +6319,7 @@ mod script_compile_tests {
             visible_module_consts: &BTreeMap::new(),
             all_script_access: &BTreeMap::new(),
             invoke_all_functions: &BTreeMap::new(),
+            strict_attributes: false,
         })
         .expect_err("compile_script should reject reserved name");
         assert_eq!(reserved_name_error.code, "NAME_RESERVED_PREFIX");
@@ -5123,6 +6336,7 @@ mod script_compile_tests {
             visible_module_consts: &BTreeMap::new(),
             all_script_access: &BTreeMap::new(),
             invoke_all_functions: &BTreeMap::new(),
+            strict_attributes: false,
         })
         .expect_err("compile_script should reject keyword name");
         assert_eq!(keyword_name_error.code, "NAME_RHAI_KEYWORD_RESERVED");
@@ -5148,6 +6362,7 @@ mod script_compile_tests {
             visible_module_consts: &BTreeMap::new(),
             all_script_access: &BTreeMap::new(),
             invoke_all_functions: &BTreeMap::new(),
+            strict_attributes: false,
         })
         .expect_err("compile_script should reject reserved var names");
         assert_eq!(reserved_var_error.code, "NAME_RESERVED_PREFIX");
@@ -5169,6 +6384,7 @@ mod script_compile_tests {
             visible_module_consts: &BTreeMap::new(),
             all_script_access: &no_module_scripts,
             invoke_all_functions: &BTreeMap::new(),
+            strict_attributes: false,
         })
         .expect("compile without module name");
         let root_group = no_module_ir
@@ -5365,18 +6581,20 @@ mod script_compile_tests {
             id: "w1".to_string(),
             when_expr: "true".to_string(),
             body_group_id: "g".to_string(),
+            max_iterations: None,
             location: SourceSpan::synthetic(),
         };
-        let while_id = node_id(&while_node);
+        let while_id = while_node.id();
         assert_eq!(while_id, "w1");
         let input_node = ScriptNode::Input {
             id: "i1".to_string(),
             target_var: "name".to_string(),
             prompt_text: "p".to_string(),
             max_length: None,
+            format: InputFormat::Text,
             location: SourceSpan::synthetic(),
         };
-        let input_id = node_id(&input_node);
+        let input_id = input_node.id();
         assert_eq!(input_id, "i1");
         let call_node = ScriptNode::Call {
             id: "c1".to_string(),
@@ -5386,28 +6604,29 @@ mod script_compile_tests {
             args: Vec::new(),
             location: SourceSpan::synthetic(),
         };
-        let call_id = node_id(&call_node);
+        let call_id = call_node.id();
         assert_eq!(call_id, "c1");
         let choice_node = ScriptNode::Choice {
             id: "ch1".to_string(),
             prompt_text: "Pick".to_string(),
             entries: Vec::new(),
+            timeout_default_option_id: None,
             location: SourceSpan::synthetic(),
         };
-        let choice_id = node_id(&choice_node);
+        let choice_id = choice_node.id();
         assert_eq!(choice_id, "ch1");
         let break_node = ScriptNode::Break {
             id: "b1".to_string(),
             location: SourceSpan::synthetic(),
         };
-        let break_id = node_id(&break_node);
+        let break_id = break_node.id();
         assert_eq!(break_id, "b1");
         let continue_node = ScriptNode::Continue {
             id: "k1".to_string(),
             target: ContinueTarget::Choice,
             location: SourceSpan::synthetic(),
         };
-        let continue_id = node_id(&continue_node);
+        let continue_id = continue_node.id();
         assert_eq!(continue_id, "k1");
 
         let mut choice_builder = GroupBuilder::new("choice.xml");
@@ -5625,7 +6844,7 @@ mod script_compile_tests {
 
         assert_eq!(
             crate::defaults::slvalue_from_json(JsonValue::Null),
-            SlValue::String("null".to_string())
+            SlValue::Null
         );
 
         // Test build_runtime_module_global_rewrite_map: qualified_name without namespace (no '.')
@@ -5642,6 +6861,7 @@ mod script_compile_tests {
                 },
                 initial_value_expr: None,
                 location: span.clone(),
+                session: false,
             },
         )]);
         let no_namespace_map =
@@ -5652,7 +6872,7 @@ mod script_compile_tests {
         );
 
         // Test build_runtime_function_symbol_map with empty map
-        let empty_fn_map = build_runtime_function_symbol_map(&BTreeMap::new(), None);
+        let empty_fn_map = build_runtime_function_symbol_map(&BTreeMap::new(), None, None);
         assert!(empty_fn_map.is_empty());
 
         // Test build_runtime_function_symbol_map with module_name and short names (lines 178-182)
@@ -5693,18 +6913,18 @@ mod script_compile_tests {
         );
         // Test: with module_name="main", short_name="add", local_candidate="main.add" exists
         // So it should override with local_candidate (line 176-177 continue, line 178)
-        let fn_map_with_local = build_runtime_function_symbol_map(&fn_map, Some("main"));
+        let fn_map_with_local = build_runtime_function_symbol_map(&fn_map, Some("main"), None);
         // rhai_function_symbol("main.add") = "main_add"
         assert_eq!(fn_map_with_local.get("add"), Some(&"main_add".to_string()));
 
         // Test case 2: module_name="util", local_candidate="util.add" exists
-        let fn_map2_result = build_runtime_function_symbol_map(&fn_map, Some("util"));
+        let fn_map2_result = build_runtime_function_symbol_map(&fn_map, Some("util"), None);
         // local_candidate doesn't exist (no "util.add"), so no override happens
         // Short name "add" keeps its original symbol "add"
         assert_eq!(fn_map2_result.get("add"), Some(&"add".to_string()));
 
         // Test case 3: module_name=None should not process short names
-        let fn_map_no_module = build_runtime_function_symbol_map(&fn_map, None);
+        let fn_map_no_module = build_runtime_function_symbol_map(&fn_map, None, None);
         assert_eq!(fn_map_no_module.get("add"), Some(&"add".to_string()));
 
         // Test case 4: test root_candidate branch (lines 180-181)
@@ -5745,11 +6965,137 @@ mod script_compile_tests {
         // With module_name="main.sub", root_name="main"
         // local_candidate = "main.sub.add" doesn't exist
         // root_candidate = "main.add" exists, should override
-        let fn_map3_result = build_runtime_function_symbol_map(&fn_map3, Some("main.sub"));
+        let fn_map3_result = build_runtime_function_symbol_map(&fn_map3, Some("main.sub"), None);
         // Should use root_candidate "main.add"
         assert_eq!(fn_map3_result.get("add"), Some(&"main_add".to_string()));
     }
 
+    #[test]
+    fn escaped_interpolation_delimiter_is_kept_verbatim_and_not_compiled() {
+        let files = compiler_test_support::map(&[(
+            "main.xml",
+            r#"<module name="main" export="script:main">
+<script name="main"><text>Price: \${not rhai ((} = ${1 + 1}</text></script>
+</module>"#,
+        )]);
+        let bundle = compile_project_bundle_from_xml_map(&files).expect("escape should compile");
+        let main = &bundle.scripts["main.main"];
+        let node = &main.groups[&main.root_group_id].nodes[0];
+        assert_eq!(
+            compiler_test_support::text_value(node),
+            Some("Price: \\${not rhai ((} = ${1 + 1}")
+        );
+    }
+
+    #[test]
+    fn script_local_functions_are_visible_only_in_their_script() {
+        let files = compiler_test_support::map(&[(
+            "main.xml",
+            r#"<module name="main" export="script:main,other">
+<script name="main">
+  <function name="double" args="int:x" return_type="int">return x * 2;</function>
+  <function name="quad" args="int:x" return_type="int">return double(double(x));</function>
+  <temp name="n" type="int">quad(3)</temp>
+  <end/>
+</script>
+<script name="other"><end/></script>
+</module>"#,
+        )]);
+        let bundle = compile_project_bundle_from_xml_map(&files).expect("local functions compile");
+        let main = &bundle.scripts["main.main"];
+        assert!(main.visible_functions.contains_key("double"));
+        assert!(main.invoke_all_functions.contains_key("main.main.quad"));
+        assert!(!main.invoke_all_functions.contains_key("quad"));
+        assert!(main.invoke_all_functions["main.main.quad"]
+            .code
+            .contains("main_main_double"));
+        let declaration =
+            compiler_test_support::var_declaration(&main.groups[&main.root_group_id].nodes[0])
+                .expect("temp node expected");
+        assert!(declaration
+            .initial_value_expr
+            .as_deref()
+            .is_some_and(|expr| expr.contains("main_main_quad")));
+        let other = &bundle.scripts["main.other"];
+        assert!(!other.visible_functions.contains_key("double"));
+        assert!(!other.invoke_all_functions.contains_key("main.main.double"));
+
+        let ref_error = compile_project_bundle_from_xml_map(&compiler_test_support::map(&[(
+            "main.xml",
+            r#"<script name="main"><function name="f" args="ref:int:x" return_type="int">return x;</function><end/></script>"#,
+        )]))
+        .expect_err("ref args are rejected");
+        assert_eq!(ref_error.code, "XML_FUNCTION_ARGS_REF_UNSUPPORTED");
+    }
+
+    #[test]
+    fn fail_node_compiles_as_terminal_and_validates_code() {
+        let bundle = compile_project_bundle_from_xml_map(&compiler_test_support::map(&[(
+            "main.xml",
+            r#"<script name="main"><if when="true"><end/><else><fail code="BAD_STATE"/></else></if></script>"#,
+        )]))
+        .expect("fail should terminate a branch");
+        let main = &bundle.scripts["main.main"];
+        let else_group = main
+            .groups
+            .values()
+            .find(|group| matches!(group.nodes.first(), Some(ScriptNode::Fail { .. })))
+            .expect("else group should hold the fail node");
+        assert!(matches!(
+            &else_group.nodes[0],
+            ScriptNode::Fail { code, message, .. }
+                if code == "BAD_STATE" && message == "Script failed with BAD_STATE."
+        ));
+
+        let compile_error = |xml: &str| {
+            compile_project_bundle_from_xml_map(&compiler_test_support::map(&[("main.xml", xml)]))
+                .expect_err("fail should be rejected")
+                .code
+        };
+        assert_eq!(
+            compile_error(r#"<script name="main"><fail code="bad-state"/></script>"#),
+            "XML_FAIL_CODE_INVALID"
+        );
+        assert_eq!(
+            compile_error(r#"<script name="main"><fail code=" "/></script>"#),
+            "XML_EMPTY_ATTR"
+        );
+        assert_eq!(
+            compile_error(r#"<script name="main"><fail code="X">oops</fail></script>"#),
+            "XML_FAIL_CONTENT_FORBIDDEN"
+        );
+    }
+
+    #[test]
+    fn checkpoint_node_requires_id_and_no_content() {
+        let bundle = compile_project_bundle_from_xml_map(&compiler_test_support::map(&[(
+            "main.xml",
+            r#"<script name="main"><checkpoint id="camp"/><end/></script>"#,
+        )]))
+        .expect("checkpoint should compile");
+        let main = &bundle.scripts["main.main"];
+        assert!(matches!(
+            &main.groups[&main.root_group_id].nodes[0],
+            ScriptNode::Checkpoint { checkpoint_id, .. } if checkpoint_id == "camp"
+        ));
+
+        let compile_error = |xml: &str| {
+            compile_project_bundle_from_xml_map(&compiler_test_support::map(&[("main.xml", xml)]))
+                .expect_err("checkpoint should be rejected")
+                .code
+        };
+        assert_eq!(
+            compile_error(r#"<script name="main"><checkpoint/><end/></script>"#),
+            "XML_MISSING_ATTR"
+        );
+        assert_eq!(
+            compile_error(
+                r#"<script name="main"><checkpoint id="a">x</checkpoint><end/></script>"#
+            ),
+            "XML_CHECKPOINT_CONTENT_FORBIDDEN"
+        );
+    }
+
     #[test]
     fn normalize_template_literals_error_paths_are_covered() {
         // Test lines 135-136: normalize_template_literals error propagation
@@ -5778,6 +7124,7 @@ mod script_compile_tests {
                 visible_module_consts: &BTreeMap::new(),
                 all_script_access: &known_scripts,
                 invoke_all_functions: &BTreeMap::new(),
+                strict_attributes: false,
             })
             .expect_err("compile should fail");
             assert_eq!(error.code, expected_code);
@@ -5823,6 +7170,7 @@ mod script_compile_tests {
                 visible_module_consts: &BTreeMap::new(),
                 all_script_access: &all_scripts,
                 invoke_all_functions: &BTreeMap::new(),
+                strict_attributes: false,
             })
             .expect_err("compile should fail");
             assert_eq!(error.code, expected_code);
@@ -6157,6 +7505,7 @@ mod script_compile_tests {
             visible_module_consts: &BTreeMap::new(),
             all_script_access: &all_scripts,
             invoke_all_functions: &BTreeMap::new(),
+            strict_attributes: false,
         })
         .expect("compile should pass");
 
@@ -6200,6 +7549,7 @@ mod script_compile_tests {
             visible_module_consts: &BTreeMap::new(),
             all_script_access: &all_scripts,
             invoke_all_functions: &BTreeMap::new(),
+            strict_attributes: false,
         })
         .expect("compile should pass");
         let root_group = compiled
@@ -6246,6 +7596,7 @@ mod script_compile_tests {
                 visible_module_consts: &BTreeMap::new(),
                 all_script_access: &known_scripts,
                 invoke_all_functions: &BTreeMap::new(),
+                strict_attributes: false,
             })
             .expect_err("compile should fail");
             assert_eq!(error.code, expected_code);
@@ -6324,6 +7675,7 @@ mod script_compile_tests {
                 visible_module_consts: &BTreeMap::new(),
                 all_script_access: &known_scripts,
                 invoke_all_functions: &BTreeMap::new(),
+                strict_attributes: false,
             })
             .expect_err("compile should fail");
             assert_eq!(error.code, expected_code);
@@ -6351,6 +7703,7 @@ mod script_compile_tests {
                 visible_module_consts: &BTreeMap::new(),
                 all_script_access: &known_scripts,
                 invoke_all_functions: &BTreeMap::new(),
+                strict_attributes: false,
             })
             .expect("compile should succeed");
         };
@@ -6414,4 +7767,114 @@ mod script_compile_tests {
         .expect_err("invalid expression should fail");
         assert_eq!(error2.code, "XML_RHAI_SYNTAX_INVALID");
     }
+
+    #[test]
+    fn strict_attributes_reject_unknown_attributes_on_known_nodes() {
+        fn compile_with_strict(
+            xml: &str,
+            strict_attributes: bool,
+        ) -> Result<ScriptIr, ScriptLangError> {
+            let root = parse_xml_document(xml).expect("xml").root;
+            compile_script(CompileScriptOptions {
+                script_path: "main.xml",
+                root: &root,
+                script_access: AccessLevel::Public,
+                qualified_script_name: Some("main.main"),
+                module_name: Some("main"),
+                visible_types: &BTreeMap::new(),
+                visible_functions: &BTreeMap::new(),
+                visible_module_vars: &BTreeMap::new(),
+                visible_module_consts: &BTreeMap::new(),
+                all_script_access: &BTreeMap::new(),
+                invoke_all_functions: &BTreeMap::new(),
+                strict_attributes,
+            })
+        }
+
+        let text_xml = r#"<script name="main"><text tg="hint">A</text></script>"#;
+        let error = compile_with_strict(text_xml, true).expect_err("unknown text attr");
+        assert_eq!(error.code, "XML_UNKNOWN_ATTR");
+        assert!(error.message.contains("\"tg\""));
+        assert!(error.message.contains("<text>"));
+        compile_with_strict(text_xml, false).expect("extra attrs are ignored by default");
+
+        let option_xml = r#"<script name="main"><choice text="Pick"><option txt="A" text="A"><text>A</text></option></choice></script>"#;
+        let error = compile_with_strict(option_xml, true).expect_err("unknown option attr");
+        assert_eq!(error.code, "XML_UNKNOWN_ATTR");
+        assert!(error.message.contains("\"txt\""));
+        assert!(error.message.contains("<option>"));
+        compile_with_strict(option_xml, false).expect("extra attrs are ignored by default");
+
+        let dynamic_xml = r#"<script name="main"><choice text="Pick"><dynamic-options array="[1]" item="it"><option text="${it}" label="x"><text>A</text></option></dynamic-options></choice></script>"#;
+        let error = compile_with_strict(dynamic_xml, true).expect_err("unknown template attr");
+        assert_eq!(error.code, "XML_UNKNOWN_ATTR");
+        assert!(error.message.contains("\"label\""));
+
+        let while_xml =
+            r#"<script name="main"><while when="false" limit="3"><text>W</text></while></script>"#;
+        let error = compile_with_strict(while_xml, true).expect_err("unknown while attr");
+        assert_eq!(error.code, "XML_UNKNOWN_ATTR");
+        assert!(error.message.contains("\"limit\""));
+        assert!(error.message.contains("<while>"));
+        compile_with_strict(while_xml, false).expect("extra attrs are ignored by default");
+
+        let root_xml = r#"<script name="main" title="x"><text>A</text></script>"#;
+        let error = compile_with_strict(root_xml, true).expect_err("unknown script attr");
+        assert_eq!(error.code, "XML_UNKNOWN_ATTR");
+
+        compile_with_strict(
+            r#"<script name="main"><temp name="n" type="int">1</temp><for temps="i:int:0" condition="i LT 1" iteration="i = i + 1"><text tag="t" once="true">${i}</text></for><temp-input name="s" type="string" text="Name" max_length="3">x</temp-input><choice text="Pick"><option text="A" when="true" once="true"><text>A</text></option><option text="B" fall_over="true"><text>B</text></option></choice></script>"#,
+            true,
+        )
+        .expect("known attributes and macro expansions should pass strict mode");
+    }
+
+    #[test]
+    fn node_labels_map_to_node_ids_and_reject_invalid_or_duplicate_names() {
+        fn compile(xml: &str) -> Result<ScriptIr, ScriptLangError> {
+            let root = parse_xml_document(xml).expect("xml").root;
+            compile_script(CompileScriptOptions {
+                script_path: "main.xml",
+                root: &root,
+                script_access: AccessLevel::Public,
+                qualified_script_name: Some("main.main"),
+                module_name: Some("main"),
+                visible_types: &BTreeMap::new(),
+                visible_functions: &BTreeMap::new(),
+                visible_module_vars: &BTreeMap::new(),
+                visible_module_consts: &BTreeMap::new(),
+                all_script_access: &BTreeMap::new(),
+                invoke_all_functions: &BTreeMap::new(),
+                strict_attributes: true,
+            })
+        }
+
+        let script = compile(
+            r#"<script name="main"><text label="intro">A</text><group label="forest"><text label="tree">B</text></group></script>"#,
+        )
+        .expect("labels are allowed on statements in strict mode");
+        let root = &script.groups[&script.root_group_id];
+        assert_eq!(script.node_labels["intro"], root.nodes[0].id());
+        assert_eq!(script.node_labels["forest"], root.nodes[1].id());
+        assert!(script.node_labels["forest"].ends_with(":if"));
+        assert_eq!(script.node_labels.len(), 3);
+
+        for (xml, code) in [
+            (
+                r#"<script name="main"><text label="a b">A</text></script>"#,
+                "XML_NODE_LABEL_INVALID",
+            ),
+            (
+                r#"<script name="main"><comment label="c">note</comment></script>"#,
+                "XML_NODE_LABEL_INVALID",
+            ),
+            (
+                r#"<script name="main"><text label="a">A</text><if when="true"><text label="a">B</text></if></script>"#,
+                "XML_NODE_LABEL_DUPLICATE",
+            ),
+        ] {
+            let error = compile(xml).expect_err("label should be rejected");
+            assert_eq!(error.code, code, "{xml}");
+        }
+    }
 }
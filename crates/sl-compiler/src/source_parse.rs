@@ -92,7 +92,7 @@ pub(crate) fn detect_source_kind(path: &str) -> Result<SourceKind, ScriptLangErr
         Ok(SourceKind::ModuleXml)
     } else {
         Err(ScriptLangError::new(
-            "SOURCE_KIND_UNSUPPORTED",
+            ErrorCode::SourceKindUnsupported,
             format!("Unsupported source extension: {}", path),
         ))
     }
@@ -114,7 +114,7 @@ pub(crate) fn resolve_import_path(current_path: &str, import_path: &str) -> Stri
 fn extract_module_name(root: &XmlElementNode) -> Result<String, ScriptLangError> {
     if root.name != "module" {
         return Err(ScriptLangError::with_span(
-            "XML_ROOT_INVALID",
+            ErrorCode::XmlRootInvalid,
             "Module file root must be <module>.",
             root.location.clone(),
         ));
@@ -122,14 +122,14 @@ fn extract_module_name(root: &XmlElementNode) -> Result<String, ScriptLangError>
 
     let Some(module_name) = root.attributes.get("name").map(String::as_str) else {
         return Err(ScriptLangError::with_span(
-            "XML_MODULE_NAME_MISSING",
+            ErrorCode::XmlModuleNameMissing,
             "Module root requires non-empty name attribute.",
             root.location.clone(),
         ));
     };
     if module_name.trim().is_empty() {
         return Err(ScriptLangError::with_span(
-            "XML_MODULE_NAME_MISSING",
+            ErrorCode::XmlModuleNameMissing,
             "Module root requires non-empty name attribute.",
             root.location.clone(),
         ));
@@ -144,7 +144,7 @@ fn reject_duplicate_alias_directives(directives: &[AliasDirective]) -> Result<()
         if targets_by_alias.contains_key(&directive.alias_name) {
             // Same alias name already exists - this is always ALIAS_DUPLICATE
             return Err(ScriptLangError::new(
-                "ALIAS_DUPLICATE",
+                ErrorCode::AliasDuplicate,
                 format!(
                     "Duplicate alias \"{}\" for target \"{}\".",
                     directive.alias_name, directive.target_qualified_name
@@ -177,7 +177,7 @@ fn resolve_import_directives(
                 let resolved = resolve_import_path(current_path, from_path);
                 if !available_paths.contains(&resolved) {
                     return Err(ScriptLangError::new(
-                        "IMPORT_FILE_NOT_FOUND",
+                        ErrorCode::ImportFileNotFound,
                         format!(
                             "Import target file \"{}\" resolved to \"{}\" in \"{}\" but was not found.",
                             from_path, resolved, current_path
@@ -186,7 +186,7 @@ fn resolve_import_directives(
                 }
                 let Some(actual_module_name) = module_names_by_path.get(&resolved) else {
                     return Err(ScriptLangError::new(
-                        "IMPORT_TARGET_INVALID",
+                        ErrorCode::ImportTargetInvalid,
                         format!(
                             "Import target file \"{}\" resolved to \"{}\" in \"{}\" but is not a module source.",
                             from_path, resolved, current_path
@@ -195,7 +195,7 @@ fn resolve_import_directives(
                 };
                 if actual_module_name != module_name {
                     return Err(ScriptLangError::new(
-                        "IMPORT_MODULE_MISMATCH",
+                        ErrorCode::ImportModuleMismatch,
                         format!(
                             "Import requires module \"{}\" from \"{}\", but that file declares module \"{}\".",
                             module_name, resolved, actual_module_name
@@ -204,7 +204,7 @@ fn resolve_import_directives(
                 }
                 if !seen.insert(resolved.clone()) {
                     return Err(ScriptLangError::new(
-                        "IMPORT_DUPLICATE",
+                        ErrorCode::ImportDuplicate,
                         format!(
                             "Duplicate import target \"{}\" in \"{}\".",
                             resolved, current_path
@@ -219,7 +219,7 @@ fn resolve_import_directives(
             } => {
                 if !from_path.ends_with('/') {
                     return Err(ScriptLangError::new(
-                        "IMPORT_DIR_PATH_INVALID",
+                        ErrorCode::ImportDirPathInvalid,
                         format!("Directory import from \"{}\" must end with '/'.", from_path),
                     ));
                 }
@@ -232,7 +232,7 @@ fn resolve_import_directives(
                 .map_err(|error| with_file_context(error, current_path))?;
                 if module_paths.is_empty() {
                     return Err(ScriptLangError::new(
-                        "IMPORT_DIR_EMPTY",
+                        ErrorCode::ImportDirEmpty,
                         format!(
                             "Import directory \"{}\" resolved to \"{}\" in \"{}\" but matched no module sources.",
                             from_path, prefix, current_path
@@ -243,7 +243,7 @@ fn resolve_import_directives(
                 for module_name in module_names {
                     let Some(resolved) = module_paths.get(module_name) else {
                         return Err(ScriptLangError::new(
-                            "IMPORT_MODULE_NOT_FOUND",
+                            ErrorCode::ImportModuleNotFound,
                             format!(
                                 "Import directory \"{}\" in \"{}\" does not contain module \"{}\".",
                                 from_path, current_path, module_name
@@ -252,7 +252,7 @@ fn resolve_import_directives(
                     };
                     if !seen.insert(resolved.clone()) {
                         return Err(ScriptLangError::new(
-                            "IMPORT_DUPLICATE",
+                            ErrorCode::ImportDuplicate,
                             format!(
                                 "Duplicate import target \"{}\" in \"{}\".",
                                 resolved, current_path
@@ -268,7 +268,7 @@ fn resolve_import_directives(
     Ok(imports)
 }
 
-fn resolve_import_directory_prefix(current_path: &str, import_path: &str) -> String {
+pub(crate) fn resolve_import_directory_prefix(current_path: &str, import_path: &str) -> String {
     let trimmed = import_path.trim_end_matches('/');
     if trimmed.is_empty() && import_path.starts_with('/') {
         return String::new();
@@ -294,7 +294,7 @@ fn collect_directory_import_modules(
         };
         if let Some(existing_path) = modules.insert(module_name.clone(), path.clone()) {
             return Err(ScriptLangError::new(
-                "IMPORT_MODULE_DUPLICATE",
+                ErrorCode::ImportModuleDuplicate,
                 format!(
                     "Directory import prefix \"{}\" contains duplicate module name \"{}\" in \"{}\" and \"{}\".",
                     directory_prefix, module_name, existing_path, path
@@ -310,11 +310,11 @@ fn collect_directory_import_modules(
     Ok(modules)
 }
 
-fn is_supported_import_path(path: &str) -> bool {
+pub(crate) fn is_supported_import_path(path: &str) -> bool {
     matches!(detect_source_kind(path), Ok(SourceKind::ModuleXml))
 }
 
-fn is_path_within_directory(path: &str, directory_prefix: &str) -> bool {
+pub(crate) fn is_path_within_directory(path: &str, directory_prefix: &str) -> bool {
     if directory_prefix.is_empty() {
         return true;
     }
@@ -0,0 +1,200 @@
+use crate::*;
+use std::collections::VecDeque;
+
+/// Read-only view of a project's source files, keyed by virtual path (e.g. `main.xml`).
+///
+/// Lets hosts compile from zip archives, network backends or other stores without reading every
+/// file up front: `compile_project_bundle_from_provider` only calls `read` for the entry files and
+/// the files their imports reach.
+pub trait SourceProvider {
+    /// Text of the file at `path`, or `None` if it does not exist.
+    fn read(&self, path: &str) -> Option<String>;
+
+    /// Every available path; consulted for directory imports and when no entry is given.
+    fn list(&self) -> Vec<String>;
+}
+
+impl SourceProvider for BTreeMap<String, String> {
+    fn read(&self, path: &str) -> Option<String> {
+        self.get(path).cloned()
+    }
+
+    fn list(&self) -> Vec<String> {
+        self.keys().cloned().collect()
+    }
+}
+
+pub fn compile_project_bundle_from_provider(
+    provider: &dyn SourceProvider,
+    entry_paths: &[&str],
+) -> Result<CompileProjectBundleResult, ScriptLangError> {
+    compile_project_bundle_from_provider_with_options(
+        provider,
+        entry_paths,
+        CompileOptions::default(),
+    )
+}
+
+/// Compile the files reachable from `entry_paths` through import directives.
+///
+/// With no entries, every listed file is compiled, matching `compile_project_bundle_from_xml_map`.
+pub fn compile_project_bundle_from_provider_with_options(
+    provider: &dyn SourceProvider,
+    entry_paths: &[&str],
+    options: CompileOptions,
+) -> Result<CompileProjectBundleResult, ScriptLangError> {
+    let xml_by_path = collect_provider_sources(provider, entry_paths)?;
+    compile_project_bundle_from_xml_map_with_options(&xml_by_path, options)
+}
+
+/// Walk the import graph from the entries, reading each reached file once.
+///
+/// Unresolvable imports are left out rather than reported here, so the regular pipeline raises
+/// the same `IMPORT_*` errors it would for a map missing those files.
+fn collect_provider_sources(
+    provider: &dyn SourceProvider,
+    entry_paths: &[&str],
+) -> Result<BTreeMap<String, String>, ScriptLangError> {
+    let listed = provider
+        .list()
+        .into_iter()
+        .map(|path| normalize_virtual_path(&path))
+        .collect::<BTreeSet<_>>();
+    let mut pending = if entry_paths.is_empty() {
+        listed.iter().cloned().collect::<VecDeque<_>>()
+    } else {
+        entry_paths
+            .iter()
+            .map(|path| normalize_virtual_path(path))
+            .collect()
+    };
+
+    let mut xml_by_path = BTreeMap::new();
+    while let Some(path) = pending.pop_front() {
+        if xml_by_path.contains_key(&path) {
+            continue;
+        }
+        let Some(source_text) = provider.read(&path) else {
+            if entry_paths
+                .iter()
+                .any(|entry| normalize_virtual_path(entry) == path)
+            {
+                return Err(ScriptLangError::new(
+                    ErrorCode::SourceEntryNotFound,
+                    format!("Entry source \"{}\" was not found.", path),
+                ));
+            }
+            continue;
+        };
+        for directive in parse_import_directives(&source_text) {
+            match directive {
+                ImportDirective::File { from_path, .. } => {
+                    pending.push_back(resolve_import_path(&path, &from_path));
+                }
+                ImportDirective::Directory { from_path, .. } => {
+                    let prefix = resolve_import_directory_prefix(&path, &from_path);
+                    pending.extend(
+                        listed
+                            .iter()
+                            .filter(|candidate| {
+                                is_supported_import_path(candidate)
+                                    && is_path_within_directory(candidate, &prefix)
+                            })
+                            .cloned(),
+                    );
+                }
+            }
+        }
+        xml_by_path.insert(path, source_text);
+    }
+    Ok(xml_by_path)
+}
+
+#[cfg(test)]
+mod source_provider_tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    struct RecordingProvider {
+        files: BTreeMap<String, String>,
+        reads: RefCell<Vec<String>>,
+    }
+
+    impl SourceProvider for RecordingProvider {
+        fn read(&self, path: &str) -> Option<String> {
+            self.reads.borrow_mut().push(path.to_string());
+            self.files.read(path)
+        }
+
+        fn list(&self) -> Vec<String> {
+            self.files.list()
+        }
+    }
+
+    #[test]
+    fn provider_compile_reads_only_files_reachable_from_entries() {
+        let provider = RecordingProvider {
+            files: compiler_test_support::map(&[
+                (
+                    "main.xml",
+                    r#"
+<!-- import shared from shared.xml -->
+<!-- import { battle } from battle/ -->
+<module name="main" export="script:main"><script name="main"><call script="@shared.hello"/></script></module>"#,
+                ),
+                (
+                    "shared.xml",
+                    r#"<module name="shared" export="script:hello"><script name="hello" kind="call"><text>hi</text></script></module>"#,
+                ),
+                (
+                    "battle/fight.xml",
+                    r#"<module name="battle" export="script:fight"><script name="fight"><text>go</text></script></module>"#,
+                ),
+                (
+                    "unused.xml",
+                    r#"<module name="unused" export="script:main"><script name="main"><text>x</text></script></module>"#,
+                ),
+            ]),
+            reads: RefCell::new(Vec::new()),
+        };
+
+        let bundle =
+            compile_project_bundle_from_provider(&provider, &["main.xml"]).expect("compile");
+        assert!(bundle.scripts.contains_key("main.main"));
+        assert!(bundle.scripts.contains_key("shared.hello"));
+        assert!(bundle.scripts.contains_key("battle.fight"));
+        assert!(!bundle.scripts.contains_key("unused.main"));
+        let reads = provider.reads.borrow().clone();
+        assert_eq!(reads, vec!["main.xml", "shared.xml", "battle/fight.xml"]);
+
+        provider.reads.borrow_mut().clear();
+        let everything = compile_project_bundle_from_provider(&provider, &[]).expect("compile");
+        assert_eq!(
+            everything.scripts.keys().collect::<Vec<_>>(),
+            compile_project_bundle_from_xml_map(&provider.files)
+                .expect("compile")
+                .scripts
+                .keys()
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(provider.reads.borrow().len(), 4);
+
+        let error = compile_project_bundle_from_provider(&provider, &["missing.xml"])
+            .expect_err("missing entry should fail");
+        assert_eq!(error.code, "SOURCE_ENTRY_NOT_FOUND");
+    }
+
+    #[test]
+    fn provider_compile_reports_missing_imports_like_the_map_api() {
+        let files = compiler_test_support::map(&[(
+            "main.xml",
+            r#"
+<!-- import shared from shared.xml -->
+<module name="main" export="script:main"><script name="main"><text>x</text></script></module>"#,
+        )]);
+        let error =
+            compile_project_bundle_from_provider(&files, &["main.xml"]).expect_err("should fail");
+        let expected = compile_project_bundle_from_xml_map(&files).expect_err("should fail");
+        assert_eq!(error.code, expected.code);
+    }
+}
@@ -24,7 +24,7 @@ pub(crate) fn resolve_named_type_with_aliases(
         qualified.clone()
     } else {
         return Err(ScriptLangError::new(
-            "TYPE_UNKNOWN",
+            ErrorCode::TypeUnknown,
             format!("Unknown type \"{}\".", name),
         ));
     };
@@ -35,7 +35,7 @@ pub(crate) fn resolve_named_type_with_aliases(
 
     if !visiting.insert(lookup_name.clone()) {
         return Err(ScriptLangError::new(
-            "TYPE_DECL_RECURSIVE",
+            ErrorCode::TypeDeclRecursive,
             format!("Recursive type declaration detected for \"{}\".", name),
         ));
     }
@@ -43,7 +43,7 @@ pub(crate) fn resolve_named_type_with_aliases(
     let Some(decl) = type_decls_map.get(&lookup_name) else {
         visiting.remove(&lookup_name);
         return Err(ScriptLangError::new(
-            "TYPE_UNKNOWN",
+            ErrorCode::TypeUnknown,
             format!("Unknown type \"{}\".", name),
         ));
     };
@@ -60,7 +60,7 @@ pub(crate) fn resolve_named_type_with_aliases(
             if fields.contains_key(&field.name) {
                 visiting.remove(&lookup_name);
                 return Err(ScriptLangError::with_span(
-                    "TYPE_FIELD_DUPLICATE",
+                    ErrorCode::TypeFieldDuplicate,
                     format!("Duplicate field \"{}\" in type \"{}\".", field.name, name),
                     field.location.clone(),
                 ));
@@ -165,7 +165,7 @@ pub(crate) fn resolve_type_expr_with_lookup_with_aliases(
             ) {
                 Ok(value) => Ok(value),
                 Err(_) => Err(ScriptLangError::with_span(
-                    "TYPE_UNKNOWN",
+                    ErrorCode::TypeUnknown,
                     format!("Unknown custom type \"{}\".", name),
                     span.clone(),
                 )),
@@ -199,7 +199,7 @@ pub(crate) fn resolve_type_expr(
         ParsedTypeExpr::Custom(name) => match resolved_types.get(name).cloned() {
             Some(value) => Ok(value),
             None => Err(ScriptLangError::with_span(
-                "TYPE_UNKNOWN",
+                ErrorCode::TypeUnknown,
                 format!("Unknown custom type \"{}\".", name),
                 span.clone(),
             )),
@@ -210,13 +210,14 @@ pub(crate) fn resolve_type_expr(
 fn resolve_map_key_type(ty: &ScriptType, span: &SourceSpan) -> Result<MapKeyType, ScriptLangError> {
     match ty {
         ScriptType::Primitive { name } if name == "string" => Ok(MapKeyType::String),
+        ScriptType::Primitive { name } if name == "int" => Ok(MapKeyType::Int),
         ScriptType::Enum { type_name, members } => Ok(MapKeyType::Enum {
             type_name: type_name.clone(),
             members: members.clone(),
         }),
         _ => Err(ScriptLangError::with_span(
-            "TYPE_MAP_KEY_UNSUPPORTED",
-            "Map key type must be string or enum type.",
+            ErrorCode::TypeMapKeyUnsupported,
+            "Map key type must be string, int or enum type.",
             span.clone(),
         )),
     }
@@ -281,7 +282,7 @@ pub(crate) fn parse_type_declaration_node_with_namespace(
     assert_decl_name_not_reserved_or_rhai_keyword(&name, "type", node.location.clone())?;
     if has_attr(node, "access") {
         return Err(ScriptLangError::with_span(
-            "XML_ATTR_NOT_ALLOWED",
+            ErrorCode::XmlAttrNotAllowed,
             "Attribute \"access\" is removed. Use module \"export\".",
             node.location.clone(),
         ));
@@ -293,7 +294,7 @@ pub(crate) fn parse_type_declaration_node_with_namespace(
     for child in element_children(node) {
         if child.name != "field" {
             return Err(ScriptLangError::with_span(
-                "XML_TYPE_CHILD_INVALID",
+                ErrorCode::XmlTypeChildInvalid,
                 format!("Unsupported child <{}> under <type>.", child.name),
                 child.location.clone(),
             ));
@@ -307,7 +308,7 @@ pub(crate) fn parse_type_declaration_node_with_namespace(
         )?;
         if !seen.insert(field_name.clone()) {
             return Err(ScriptLangError::with_span(
-                "TYPE_FIELD_DUPLICATE",
+                ErrorCode::TypeFieldDuplicate,
                 format!("Duplicate field \"{}\" in type \"{}\".", field_name, name),
                 child.location.clone(),
             ));
@@ -342,7 +343,7 @@ pub(crate) fn parse_enum_declaration_node_with_namespace(
     assert_decl_name_not_reserved_or_rhai_keyword(&name, "enum", node.location.clone())?;
     if has_attr(node, "access") {
         return Err(ScriptLangError::with_span(
-            "XML_ATTR_NOT_ALLOWED",
+            ErrorCode::XmlAttrNotAllowed,
             "Attribute \"access\" is removed. Use module \"export\".",
             node.location.clone(),
         ));
@@ -353,7 +354,7 @@ pub(crate) fn parse_enum_declaration_node_with_namespace(
     for child in element_children(node) {
         if child.name != "member" {
             return Err(ScriptLangError::with_span(
-                "XML_ENUM_CHILD_INVALID",
+                ErrorCode::XmlEnumChildInvalid,
                 format!("Unsupported child <{}> under <enum>.", child.name),
                 child.location.clone(),
             ));
@@ -366,7 +367,7 @@ pub(crate) fn parse_enum_declaration_node_with_namespace(
         )?;
         if !seen.insert(member_name.clone()) {
             return Err(ScriptLangError::with_span(
-                "ENUM_MEMBER_DUPLICATE",
+                ErrorCode::EnumMemberDuplicate,
                 format!(
                     "Duplicate enum member \"{}\" in enum \"{}\".",
                     member_name, name
@@ -376,7 +377,7 @@ pub(crate) fn parse_enum_declaration_node_with_namespace(
         }
         if has_any_child_content(child) {
             return Err(ScriptLangError::with_span(
-                "XML_ENUM_MEMBER_CONTENT_FORBIDDEN",
+                ErrorCode::XmlEnumMemberContentForbidden,
                 "<member> cannot contain child nodes or inline text.",
                 child.location.clone(),
             ));
@@ -385,7 +386,7 @@ pub(crate) fn parse_enum_declaration_node_with_namespace(
     }
     if members.is_empty() {
         return Err(ScriptLangError::with_span(
-            "ENUM_DECL_EMPTY",
+            ErrorCode::EnumDeclEmpty,
             format!("Enum \"{}\" must declare at least one <member>.", name),
             node.location.clone(),
         ));
@@ -418,7 +419,7 @@ pub(crate) fn parse_function_declaration_node_with_namespace(
     assert_decl_name_not_reserved_or_rhai_keyword(&name, "function", node.location.clone())?;
     if has_attr(node, "access") {
         return Err(ScriptLangError::with_span(
-            "XML_ATTR_NOT_ALLOWED",
+            ErrorCode::XmlAttrNotAllowed,
             "Attribute \"access\" is removed. Use module \"export\".",
             node.location.clone(),
         ));
@@ -429,7 +430,7 @@ pub(crate) fn parse_function_declaration_node_with_namespace(
     let code = parse_inline_required_no_element_children(node)?;
     if !contains_return_statement(&code) {
         return Err(ScriptLangError::with_span(
-            "FUNCTION_RETURN_STATEMENT_REQUIRED",
+            ErrorCode::FunctionReturnStatementRequired,
             format!(
                 "<function name=\"{}\"> body must contain at least one return statement.",
                 name
@@ -555,11 +556,11 @@ mod type_expr_tests {
         .expect_err("unknown map value type should fail");
         assert_eq!(map_err.code, "TYPE_UNKNOWN");
 
-        // Test invalid map key type (int) via resolve_type_expr_with_lookup_with_aliases
+        // Test invalid map key type (boolean) via resolve_type_expr_with_lookup_with_aliases
         // This covers line 153 (resolve_map_key_type error path)
         let map_invalid_key = resolve_type_expr_with_lookup(
             &ParsedTypeExpr::Map {
-                key_type: Box::new(ParsedTypeExpr::Primitive("int".to_string())),
+                key_type: Box::new(ParsedTypeExpr::Primitive("boolean".to_string())),
                 value_type: Box::new(ParsedTypeExpr::Primitive("string".to_string())),
             },
             &type_map,
@@ -567,7 +568,7 @@ mod type_expr_tests {
             &mut visiting,
             &span,
         )
-        .expect_err("map key int should be rejected in resolve_type_expr_with_lookup");
+        .expect_err("map key boolean should be rejected in resolve_type_expr_with_lookup");
         assert_eq!(map_invalid_key.code, "TYPE_MAP_KEY_UNSUPPORTED");
 
         // Test map key type that fails to resolve (unknown custom type) - covers line 143
@@ -648,16 +649,16 @@ mod type_expr_tests {
         .expect("enum key map should resolve");
         assert_eq!(script_type_kind(&enum_key_map), "map");
 
-        // Test invalid map key type (int) - covers resolve_map_key_type error path (line 193)
+        // Test invalid map key type (boolean) - covers resolve_map_key_type error path (line 193)
         let invalid_key = resolve_type_expr(
             &ParsedTypeExpr::Map {
-                key_type: Box::new(ParsedTypeExpr::Primitive("int".to_string())),
+                key_type: Box::new(ParsedTypeExpr::Primitive("boolean".to_string())),
                 value_type: Box::new(ParsedTypeExpr::Primitive("int".to_string())),
             },
             &resolved_types,
             &span,
         )
-        .expect_err("map key int should be rejected");
+        .expect_err("map key boolean should be rejected");
         assert_eq!(invalid_key.code, "TYPE_MAP_KEY_UNSUPPORTED");
 
         // Test map key type that fails to resolve (unknown custom type) - covers line 191
@@ -894,13 +895,13 @@ mod type_expr_tests {
         let span = SourceSpan::synthetic();
         let resolved_types = BTreeMap::new();
 
-        // Test invalid map key type (int) through resolve_type_expr_in_namespace
+        // Test invalid map key type (boolean) through resolve_type_expr_in_namespace
         let map_expr = ParsedTypeExpr::Map {
-            key_type: Box::new(ParsedTypeExpr::Primitive("int".to_string())),
+            key_type: Box::new(ParsedTypeExpr::Primitive("boolean".to_string())),
             value_type: Box::new(ParsedTypeExpr::Primitive("string".to_string())),
         };
         let error = resolve_type_expr_in_namespace(&map_expr, &resolved_types, "test", &span)
-            .expect_err("map with int key should be rejected");
+            .expect_err("map with boolean key should be rejected");
         assert_eq!(error.code, "TYPE_MAP_KEY_UNSUPPORTED");
     }
 
@@ -27,7 +27,7 @@ pub(crate) fn parse_type_expr(
     {
         if value.trim().is_empty() {
             return Err(ScriptLangError::with_span(
-                "TYPE_PARSE_ERROR",
+                ErrorCode::TypeParseError,
                 format!("Unsupported type syntax: \"{}\".", raw),
                 span.clone(),
             ));
@@ -52,7 +52,7 @@ pub(crate) fn parse_type_expr(
     }
 
     Err(ScriptLangError::with_span(
-        "TYPE_PARSE_ERROR",
+        ErrorCode::TypeParseError,
         format!("Unsupported type syntax: \"{}\".", raw),
         span.clone(),
     ))
@@ -146,7 +146,7 @@ pub(crate) fn parse_args(raw: Option<String>) -> Result<Vec<CallArgument>, Scrip
         };
         if normalized.is_empty() {
             return Err(ScriptLangError::new(
-                "CALL_ARGS_PARSE_ERROR",
+                ErrorCode::CallArgsParseError,
                 format!("Invalid call arg segment: \"{}\".", part),
             ));
         }
@@ -163,7 +163,7 @@ pub(crate) fn parse_args(raw: Option<String>) -> Result<Vec<CallArgument>, Scrip
 pub(crate) fn parse_inline_required(node: &XmlElementNode) -> Result<String, ScriptLangError> {
     if has_attr(node, "value") {
         return Err(ScriptLangError::with_span(
-            "XML_ATTR_NOT_ALLOWED",
+            ErrorCode::XmlAttrNotAllowed,
             format!(
                 "Attribute \"value\" is not allowed on <{}>. Use inline content instead.",
                 node.name
@@ -175,7 +175,7 @@ pub(crate) fn parse_inline_required(node: &XmlElementNode) -> Result<String, Scr
     let content = inline_text_content(node);
     if content.trim().is_empty() {
         return Err(ScriptLangError::with_span(
-            "XML_EMPTY_NODE_CONTENT",
+            ErrorCode::XmlEmptyNodeContent,
             format!("<{}> requires non-empty inline content.", node.name),
             node.location.clone(),
         ));
@@ -184,6 +184,58 @@ pub(crate) fn parse_inline_required(node: &XmlElementNode) -> Result<String, Scr
     Ok(content.trim().to_string())
 }
 
+/// Read the optional `key` attribute of `<text>`; keyed text must not also carry inline content.
+pub(crate) fn parse_text_key(node: &XmlElementNode) -> Result<Option<String>, ScriptLangError> {
+    let Some(raw) = get_optional_attr(node, "key") else {
+        return Ok(None);
+    };
+    let key = raw.trim();
+    if key.is_empty() {
+        return Err(ScriptLangError::with_span(
+            ErrorCode::XmlTextKeyEmpty,
+            "Attribute \"key\" on <text> must not be empty.",
+            node.location.clone(),
+        ));
+    }
+    if !inline_text_content(node).trim().is_empty() {
+        return Err(ScriptLangError::with_span(
+            ErrorCode::XmlTextKeyConflict,
+            "<text> cannot combine attribute \"key\" with inline content.",
+            node.location.clone(),
+        ));
+    }
+    Ok(Some(key.to_string()))
+}
+
+/// Read `<text expr="...">`, which replaces inline content with a single expression.
+pub(crate) fn parse_text_expr(node: &XmlElementNode) -> Result<Option<String>, ScriptLangError> {
+    let Some(raw) = get_optional_attr(node, "expr") else {
+        return Ok(None);
+    };
+    if raw.trim().is_empty() {
+        return Err(ScriptLangError::with_span(
+            ErrorCode::XmlTextExprEmpty,
+            "Attribute \"expr\" on <text> must not be empty.",
+            node.location.clone(),
+        ));
+    }
+    if has_attr(node, "key") {
+        return Err(ScriptLangError::with_span(
+            ErrorCode::XmlTextKeyConflict,
+            "<text> cannot combine attribute \"key\" with attribute \"expr\".",
+            node.location.clone(),
+        ));
+    }
+    if !inline_text_content(node).trim().is_empty() {
+        return Err(ScriptLangError::with_span(
+            ErrorCode::XmlTextExprAndInline,
+            "<text> cannot combine attribute \"expr\" with inline content.",
+            node.location.clone(),
+        ));
+    }
+    Ok(Some(raw))
+}
+
 pub(crate) fn parse_initializer_format(
     node: &XmlElementNode,
 ) -> Result<InitializerFormat, ScriptLangError> {
@@ -194,7 +246,7 @@ pub(crate) fn parse_initializer_format(
         "inline" => Ok(InitializerFormat::Inline),
         "xml" => Ok(InitializerFormat::Xml),
         other => Err(ScriptLangError::with_span(
-            "XML_INIT_FORMAT_INVALID",
+            ErrorCode::XmlInitFormatInvalid,
             format!(
                 "Attribute \"format\" on <{}> only supports \"inline\" or \"xml\", got \"{}\".",
                 node.name, other
@@ -250,7 +302,7 @@ fn build_initializer_expr_from_xml_untyped(
     let has_elements = element_children(node).next().is_some();
     if has_non_whitespace_text_child(node) && has_elements {
         return Err(ScriptLangError::with_span(
-            "XML_INIT_XML_MIXED_CONTENT",
+            ErrorCode::XmlInitXmlMixedContent,
             format!(
                 "<{} format=\"xml\"> cannot mix non-empty inline text with child elements.",
                 node.name
@@ -260,7 +312,7 @@ fn build_initializer_expr_from_xml_untyped(
     }
     if has_non_whitespace_text_child(node) {
         return Err(ScriptLangError::with_span(
-            "XML_INIT_XML_CHILD_INVALID",
+            ErrorCode::XmlInitXmlChildInvalid,
             format!(
                 "<{} format=\"xml\"> requires structural child nodes and does not accept inline text.",
                 node.name
@@ -280,7 +332,7 @@ fn build_initializer_expr_from_xml_untyped(
             for child in children {
                 if child.name != "field" {
                     return Err(ScriptLangError::with_span(
-                        "XML_INIT_XML_CHILD_INVALID",
+                        ErrorCode::XmlInitXmlChildInvalid,
                         format!(
                             "<{} format=\"xml\"> only allows one child kind (field/item/tuple).",
                             node.name
@@ -304,7 +356,7 @@ fn build_initializer_expr_from_xml_untyped(
             for child in children {
                 if child.name != "item" {
                     return Err(ScriptLangError::with_span(
-                        "XML_INIT_XML_CHILD_INVALID",
+                        ErrorCode::XmlInitXmlChildInvalid,
                         format!(
                             "<{} format=\"xml\"> only allows one child kind (field/item/tuple).",
                             node.name
@@ -321,7 +373,7 @@ fn build_initializer_expr_from_xml_untyped(
             for child in children {
                 if child.name != "tuple" {
                     return Err(ScriptLangError::with_span(
-                        "XML_INIT_XML_CHILD_INVALID",
+                        ErrorCode::XmlInitXmlChildInvalid,
                         format!(
                             "<{} format=\"xml\"> only allows one child kind (field/item/tuple).",
                             node.name
@@ -336,7 +388,7 @@ fn build_initializer_expr_from_xml_untyped(
             Ok(format!("#{{{}}}", pairs.join(", ")))
         }
         _ => Err(ScriptLangError::with_span(
-            "XML_INIT_XML_CHILD_INVALID",
+            ErrorCode::XmlInitXmlChildInvalid,
             format!(
                 "<{} format=\"xml\"> only supports child nodes <field>/<item>/<tuple>.",
                 node.name
@@ -353,7 +405,7 @@ pub(crate) fn build_initializer_expr_from_xml_for_type_expr(
     let has_elements = element_children(node).next().is_some();
     if has_non_whitespace_text_child(node) && has_elements {
         return Err(ScriptLangError::with_span(
-            "XML_INIT_XML_MIXED_CONTENT",
+            ErrorCode::XmlInitXmlMixedContent,
             format!(
                 "<{} format=\"xml\"> cannot mix non-empty inline text with child elements.",
                 node.name
@@ -363,7 +415,7 @@ pub(crate) fn build_initializer_expr_from_xml_for_type_expr(
     }
     if has_non_whitespace_text_child(node) {
         return Err(ScriptLangError::with_span(
-            "XML_INIT_XML_CHILD_INVALID",
+            ErrorCode::XmlInitXmlChildInvalid,
             format!(
                 "<{} format=\"xml\"> requires structural child nodes and does not accept inline text.",
                 node.name
@@ -378,7 +430,7 @@ pub(crate) fn build_initializer_expr_from_xml_for_type_expr(
             for child in element_children(node) {
                 if child.name != "field" {
                     return Err(ScriptLangError::with_span(
-                        "XML_INIT_XML_CHILD_INVALID",
+                        ErrorCode::XmlInitXmlChildInvalid,
                         format!(
                             "<{} format=\"xml\"> for object type only allows <field> children.",
                             node.name
@@ -397,7 +449,7 @@ pub(crate) fn build_initializer_expr_from_xml_for_type_expr(
             }
             if fields.is_empty() {
                 return Err(ScriptLangError::with_span(
-                    "XML_INIT_XML_CHILD_INVALID",
+                    ErrorCode::XmlInitXmlChildInvalid,
                     format!(
                         "<{} format=\"xml\"> for object type requires at least one <field> child.",
                         node.name
@@ -412,7 +464,7 @@ pub(crate) fn build_initializer_expr_from_xml_for_type_expr(
             for child in element_children(node) {
                 if child.name != "item" {
                     return Err(ScriptLangError::with_span(
-                        "XML_INIT_XML_CHILD_INVALID",
+                        ErrorCode::XmlInitXmlChildInvalid,
                         format!(
                             "<{} format=\"xml\"> for array type only allows <item> children.",
                             node.name
@@ -435,7 +487,7 @@ pub(crate) fn build_initializer_expr_from_xml_for_type_expr(
             for child in element_children(node) {
                 if child.name != "tuple" {
                     return Err(ScriptLangError::with_span(
-                        "XML_INIT_XML_CHILD_INVALID",
+                        ErrorCode::XmlInitXmlChildInvalid,
                         format!(
                             "<{} format=\"xml\"> for map type only allows <tuple> children.",
                             node.name
@@ -451,7 +503,7 @@ pub(crate) fn build_initializer_expr_from_xml_for_type_expr(
             Ok(format!("#{{{}}}", pairs.join(", ")))
         }
         _ => Err(ScriptLangError::with_span(
-            "XML_INIT_XML_TYPE_UNSUPPORTED",
+            ErrorCode::XmlInitXmlTypeUnsupported,
             format!(
                 "<{} format=\"xml\"> is only supported for object/array/map types.",
                 node.name
@@ -469,7 +521,7 @@ pub(crate) fn build_initializer_expr_from_xml(
     let has_elements = element_children(node).next().is_some();
     if has_non_whitespace_text_child(node) && has_elements {
         return Err(ScriptLangError::with_span(
-            "XML_INIT_XML_MIXED_CONTENT",
+            ErrorCode::XmlInitXmlMixedContent,
             format!(
                 "<{} format=\"xml\"> cannot mix non-empty inline text with child elements.",
                 node.name
@@ -479,7 +531,7 @@ pub(crate) fn build_initializer_expr_from_xml(
     }
     if has_non_whitespace_text_child(node) {
         return Err(ScriptLangError::with_span(
-            "XML_INIT_XML_CHILD_INVALID",
+            ErrorCode::XmlInitXmlChildInvalid,
             format!(
                 "<{} format=\"xml\"> requires structural child nodes and does not accept inline text.",
                 node.name
@@ -495,7 +547,7 @@ pub(crate) fn build_initializer_expr_from_xml(
             for child in element_children(node) {
                 if child.name != "field" {
                     return Err(ScriptLangError::with_span(
-                        "XML_INIT_XML_CHILD_INVALID",
+                        ErrorCode::XmlInitXmlChildInvalid,
                         format!(
                             "<{} format=\"xml\"> for object type only allows <field> children.",
                             node.name
@@ -506,7 +558,7 @@ pub(crate) fn build_initializer_expr_from_xml(
                 let field_name = get_required_non_empty_attr(child, "name")?;
                 if !fields.contains_key(&field_name) {
                     return Err(ScriptLangError::with_span(
-                        "XML_INIT_XML_FIELD_UNKNOWN",
+                        ErrorCode::XmlInitXmlFieldUnknown,
                         format!(
                             "Field \"{}\" does not exist on target object type.",
                             field_name
@@ -516,7 +568,7 @@ pub(crate) fn build_initializer_expr_from_xml(
                 }
                 if !seen.insert(field_name.clone()) {
                     return Err(ScriptLangError::with_span(
-                        "XML_INIT_XML_FIELD_DUPLICATE",
+                        ErrorCode::XmlInitXmlFieldDuplicate,
                         format!("Field \"{}\" appears more than once.", field_name),
                         child.location.clone(),
                     ));
@@ -533,7 +585,7 @@ pub(crate) fn build_initializer_expr_from_xml(
                     continue;
                 }
                 return Err(ScriptLangError::with_span(
-                    "XML_INIT_XML_FIELD_MISSING",
+                    ErrorCode::XmlInitXmlFieldMissing,
                     format!(
                         "Missing field \"{}\" in <{} format=\"xml\"> initializer.",
                         field_name, node.name
@@ -548,7 +600,7 @@ pub(crate) fn build_initializer_expr_from_xml(
             for child in element_children(node) {
                 if child.name != "item" {
                     return Err(ScriptLangError::with_span(
-                        "XML_INIT_XML_CHILD_INVALID",
+                        ErrorCode::XmlInitXmlChildInvalid,
                         format!(
                             "<{} format=\"xml\"> for array type only allows <item> children.",
                             node.name
@@ -572,7 +624,7 @@ pub(crate) fn build_initializer_expr_from_xml(
             for child in element_children(node) {
                 if child.name != "tuple" {
                     return Err(ScriptLangError::with_span(
-                        "XML_INIT_XML_CHILD_INVALID",
+                        ErrorCode::XmlInitXmlChildInvalid,
                         format!(
                             "<{} format=\"xml\"> for map type only allows <tuple> children.",
                             node.name
@@ -594,6 +646,16 @@ pub(crate) fn build_initializer_expr_from_xml(
                             value_expr
                         ));
                     }
+                    MapKeyType::Int => {
+                        let Ok(number) = raw_key.trim().parse::<i64>() else {
+                            return Err(ScriptLangError::with_span(
+                                ErrorCode::XmlInitMapKeyInvalid,
+                                format!("Map key \"{}\" is not an int.", raw_key),
+                                child.location.clone(),
+                            ));
+                        };
+                        pairs.push(format!("\"{}\": {}", number, value_expr));
+                    }
                     MapKeyType::Enum {
                         type_name, members, ..
                     } => {
@@ -615,7 +677,7 @@ pub(crate) fn build_initializer_expr_from_xml(
             Ok(format!("#{{{}}}", pairs.join(", ")))
         }
         _ => Err(ScriptLangError::with_span(
-            "XML_INIT_XML_TYPE_UNSUPPORTED",
+            ErrorCode::XmlInitXmlTypeUnsupported,
             format!(
                 "<{} format=\"xml\"> is only supported for object/array/map types.",
                 node.name
@@ -630,7 +692,7 @@ pub(crate) fn parse_inline_required_no_element_children(
 ) -> Result<String, ScriptLangError> {
     if let Some(element) = element_children(node).next() {
         return Err(ScriptLangError::with_span(
-            "XML_FUNCTION_CHILD_NODE_INVALID",
+            ErrorCode::XmlFunctionChildNodeInvalid,
             format!(
                 "<{}> cannot contain child elements. Only inline code text is allowed.",
                 node.name
@@ -666,7 +728,7 @@ pub(crate) fn parse_bool_attr(
         "true" => Ok(true),
         "false" => Ok(false),
         _ => Err(ScriptLangError::with_span(
-            "XML_ATTR_BOOL_INVALID",
+            ErrorCode::XmlAttrBoolInvalid,
             format!(
                 "Attribute \"{}\" on <{}> must be \"true\" or \"false\".",
                 name, node.name
@@ -734,7 +796,7 @@ pub(crate) fn assert_name_not_reserved(
     }
 
     Err(ScriptLangError::with_span(
-        "NAME_RESERVED_PREFIX",
+        ErrorCode::NameReservedPrefix,
         format!(
             "Name \"{}\" for {} cannot start with \"{}\" because that prefix is reserved.",
             name, label, INTERNAL_RESERVED_NAME_PREFIX
@@ -751,7 +813,7 @@ pub(crate) fn assert_decl_name_not_reserved_or_rhai_keyword(
     assert_name_not_reserved(name, label, span.clone())?;
     if !decl_name_regex().is_match(name) {
         return Err(ScriptLangError::with_span(
-            "NAME_IDENTIFIER_INVALID",
+            ErrorCode::NameIdentifierInvalid,
             format!(
                 "Name \"{}\" for {} must be a single identifier (letters/digits/underscore only, no dot).",
                 name, label
@@ -764,7 +826,7 @@ pub(crate) fn assert_decl_name_not_reserved_or_rhai_keyword(
     }
 
     Err(ScriptLangError::with_span(
-        "NAME_RHAI_KEYWORD_RESERVED",
+        ErrorCode::NameRhaiKeywordReserved,
         format!(
             "Name \"{}\" for {} conflicts with Rhai keyword or reserved identifier.",
             name, label
@@ -872,7 +934,7 @@ pub(crate) fn get_required_non_empty_attr(
 ) -> Result<String, ScriptLangError> {
     let Some(raw) = node.attributes.get(name) else {
         return Err(ScriptLangError::with_span(
-            "XML_MISSING_ATTR",
+            ErrorCode::XmlMissingAttr,
             format!(
                 "Missing required attribute \"{}\" on <{}>.",
                 name, node.name
@@ -883,7 +945,7 @@ pub(crate) fn get_required_non_empty_attr(
 
     if raw.trim().is_empty() {
         return Err(ScriptLangError::with_span(
-            "XML_EMPTY_ATTR",
+            ErrorCode::XmlEmptyAttr,
             format!("Attribute \"{}\" on <{}> cannot be empty.", name, node.name),
             node.location.clone(),
         ));
@@ -898,7 +960,9 @@ pub(crate) fn has_attr(node: &XmlElementNode, name: &str) -> bool {
 
 fn enum_template_regex() -> &'static Regex {
     static REGEX: OnceLock<Regex> = OnceLock::new();
-    REGEX.get_or_init(|| Regex::new(r"\$\{([^{}]+)\}").expect("enum template regex must compile"))
+    REGEX.get_or_init(|| {
+        Regex::new(r"\\\$\{|\$\{([^{}]+)\}").expect("enum template regex must compile")
+    })
 }
 
 fn is_expr_ident_start(ch: char) -> bool {
@@ -1063,9 +1127,9 @@ pub(crate) fn rewrite_module_symbol_aliases_in_template(
         let full = captures
             .get(0)
             .expect("capture group 0 must exist for each template capture");
-        let expr = captures
-            .get(1)
-            .expect("capture group 1 must exist for each template capture");
+        let Some(expr) = captures.get(1) else {
+            continue;
+        };
         out.push_str(&template[last_index..full.start()]);
         let rewritten =
             rewrite_module_symbol_aliases_in_expression(expr.as_str(), alias_to_qualified);
@@ -1152,7 +1216,7 @@ fn rewrite_and_validate_enum_literals_with_quote(
                 if let Some(members) = enum_lookup.get(type_name) {
                     if !members.iter().any(|member| member == member_name) {
                         return Err(ScriptLangError::with_span(
-                            "ENUM_LITERAL_MEMBER_UNKNOWN",
+                            ErrorCode::EnumLiteralMemberUnknown,
                             format!(
                                 "Unknown enum member \"{}\" for type \"{}\".",
                                 member_name, type_name
@@ -1188,9 +1252,9 @@ pub(crate) fn rewrite_and_validate_enum_literals_in_template(
         let full = captures
             .get(0)
             .expect("capture group 0 must exist for each template capture");
-        let expr = captures
-            .get(1)
-            .expect("capture group 1 must exist for each template capture");
+        let Some(expr) = captures.get(1) else {
+            continue;
+        };
         out.push_str(&template[last_index..full.start()]);
         let rewritten =
             rewrite_and_validate_enum_literals_in_expression(expr.as_str(), visible_types, span)?;
@@ -1213,7 +1277,7 @@ pub(crate) fn parse_enum_literal_initializer(
     let trimmed = expr.trim();
     if trimmed.starts_with('"') || trimmed.starts_with('\'') {
         return Err(ScriptLangError::with_span(
-            "ENUM_LITERAL_REQUIRED",
+            ErrorCode::EnumLiteralRequired,
             format!(
                 "Enum \"{}\" initializer must use Type.Member literal, not string literal.",
                 enum_type_name
@@ -1224,7 +1288,7 @@ pub(crate) fn parse_enum_literal_initializer(
 
     let Some((type_name, member_name)) = trimmed.rsplit_once('.') else {
         return Err(ScriptLangError::with_span(
-            "ENUM_LITERAL_REQUIRED",
+            ErrorCode::EnumLiteralRequired,
             format!(
                 "Enum \"{}\" initializer must use Type.Member literal.",
                 enum_type_name
@@ -1235,7 +1299,7 @@ pub(crate) fn parse_enum_literal_initializer(
 
     if !enum_members.iter().any(|member| member == member_name) {
         return Err(ScriptLangError::with_span(
-            "ENUM_LITERAL_MEMBER_UNKNOWN",
+            ErrorCode::EnumLiteralMemberUnknown,
             format!(
                 "Unknown enum member \"{}\" for type \"{}\".",
                 member_name, type_name
@@ -1256,7 +1320,7 @@ pub(crate) fn parse_enum_literal_initializer(
     });
     if !type_matches {
         return Err(ScriptLangError::with_span(
-            "ENUM_LITERAL_REQUIRED",
+            ErrorCode::EnumLiteralRequired,
             format!(
                 "Enum \"{}\" initializer must use Type.Member literal of the same enum type.",
                 enum_type_name
@@ -1294,7 +1358,7 @@ pub(crate) fn validate_enum_map_initializer_keys_if_static(
         };
         if !enum_members.iter().any(|member| member == &key) {
             return Err(ScriptLangError::with_span(
-                "ENUM_MAP_KEY_UNKNOWN",
+                ErrorCode::EnumMapKeyUnknown,
                 format!(
                     "Unknown map key \"{}\" for enum key type \"{}\".",
                     key, enum_type_name
@@ -1615,6 +1679,7 @@ mod xml_utils_tests {
             },
             initial_value_expr: None,
             location: SourceSpan::synthetic(),
+            session: false,
         };
         let base_decl = ModuleConstDecl {
             namespace: "main".to_string(),
@@ -1645,6 +1710,7 @@ mod xml_utils_tests {
                     },
                     initial_value_expr: None,
                     location: SourceSpan::synthetic(),
+                    session: false,
                 },
             ),
         ]);
@@ -2742,6 +2808,46 @@ mod xml_utils_tests {
                 .expect_err("unknown enum member should fail");
         assert_eq!(enum_map_bad_key_error.code, "ENUM_LITERAL_MEMBER_UNKNOWN");
 
+        let int_map_type = ScriptType::Map {
+            key_type: MapKeyType::Int,
+            value_type: Box::new(ScriptType::Primitive {
+                name: "string".to_string(),
+            }),
+        };
+        let int_map_node = xml_element(
+            "temp",
+            &[("format", "xml")],
+            vec![
+                XmlNode::Element(xml_element(
+                    "tuple",
+                    &[("key", "07")],
+                    vec![xml_text("\"a\"")],
+                )),
+                XmlNode::Element(xml_element(
+                    "tuple",
+                    &[("key", "-3")],
+                    vec![xml_text("\"b\"")],
+                )),
+            ],
+        );
+        let int_map_expr =
+            build_initializer_expr_from_xml(&int_map_node, &int_map_type, &visible_types)
+                .expect("int map xml should pass");
+        assert_eq!(int_map_expr, "#{\"7\": \"a\", \"-3\": \"b\"}");
+        let int_map_bad_key = xml_element(
+            "temp",
+            &[("format", "xml")],
+            vec![XmlNode::Element(xml_element(
+                "tuple",
+                &[("key", "1.5")],
+                vec![xml_text("\"a\"")],
+            ))],
+        );
+        let int_map_bad_key_error =
+            build_initializer_expr_from_xml(&int_map_bad_key, &int_map_type, &visible_types)
+                .expect_err("non-int key should fail");
+        assert_eq!(int_map_bad_key_error.code, "XML_INIT_MAP_KEY_INVALID");
+
         // Test line 378-389: missing field in object xml initializer
         // Create object type with fields but provide only partial fields in XML
         let object_with_required_fields = ScriptType::Object {
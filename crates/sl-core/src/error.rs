@@ -1,4 +1,5 @@
-use crate::types::SourceSpan;
+use crate::error_code::ErrorCode;
+use crate::types::{SourceLocation, SourceSpan};
 use thiserror::Error;
 
 #[derive(Debug, Error, Clone)]
@@ -7,6 +8,8 @@ pub struct ScriptLangError {
     pub code: String,
     pub message: String,
     pub span: Option<SourceSpan>,
+    /// Source file the span points into, set when the compiler attaches file context.
+    pub file: Option<String>,
 }
 
 impl ScriptLangError {
@@ -15,6 +18,7 @@ impl ScriptLangError {
             code: code.into(),
             message: message.into(),
             span: None,
+            file: None,
         }
     }
 
@@ -27,8 +31,25 @@ impl ScriptLangError {
             code: code.into(),
             message: message.into(),
             span: Some(span),
+            file: None,
         }
     }
+
+    /// Structured form of `code`, for exhaustive matching.
+    pub fn kind(&self) -> ErrorCode {
+        ErrorCode::from_code(&self.code)
+    }
+
+    /// File and start position of the error, or `None` when either is unknown or the span is only
+    /// the synthetic placeholder.
+    pub fn source_location(&self) -> Option<(&str, &SourceLocation)> {
+        let file = self.file.as_deref()?;
+        let span = self.span.as_ref()?;
+        if *span == SourceSpan::synthetic() {
+            return None;
+        }
+        Some((file, &span.start))
+    }
 }
 
 #[cfg(test)]
@@ -41,9 +62,21 @@ mod tests {
         assert_eq!(error.code, "E_CODE");
         assert_eq!(error.message, "message");
         assert!(error.span.is_none());
+        assert!(error.source_location().is_none());
         assert_eq!(format!("{}", error), "E_CODE: message");
     }
 
+    #[test]
+    fn enum_codes_build_errors_and_map_back_to_their_variant() {
+        let error = ScriptLangError::new(ErrorCode::SnapshotSchema, "bad schema");
+        assert_eq!(error.code, "SNAPSHOT_SCHEMA");
+        assert_eq!(error.kind(), ErrorCode::SnapshotSchema);
+        assert_eq!(
+            ScriptLangError::new("QUEST_LOCKED", "locked").kind(),
+            ErrorCode::Other("QUEST_LOCKED".to_string())
+        );
+    }
+
     #[test]
     fn with_span_builds_error_with_span() {
         let span = SourceSpan::synthetic();
@@ -51,5 +84,21 @@ mod tests {
         assert_eq!(error.code, "E_SPAN");
         assert_eq!(error.message, "has-span");
         assert_eq!(error.span, Some(span));
+        assert!(error.source_location().is_none());
+    }
+
+    #[test]
+    fn source_location_requires_file_and_real_span() {
+        let span = SourceSpan {
+            start: SourceLocation { line: 3, column: 5 },
+            end: SourceLocation { line: 3, column: 9 },
+        };
+        let mut error = ScriptLangError::with_span("E_SPAN", "has-span", span);
+        assert!(error.source_location().is_none());
+        error.file = Some("main.xml".to_string());
+        assert_eq!(
+            error.source_location(),
+            Some(("main.xml", &SourceLocation { line: 3, column: 5 }))
+        );
     }
 }
@@ -0,0 +1,384 @@
+/// Defines [`ErrorCode`] with one variant per stable code plus its string form.
+macro_rules! error_codes {
+    ($($variant:ident => $code:literal,)*) => {
+        /// Stable error codes raised by the compiler, runtime and tooling crates.
+        ///
+        /// `ScriptLangError::code` keeps the string form for compatibility; use
+        /// [`ScriptLangError::kind`](crate::ScriptLangError::kind) to match exhaustively. Codes
+        /// without a variant (e.g. author codes from `<fail code="...">`) map to `Other`.
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        #[non_exhaustive]
+        pub enum ErrorCode {
+            $($variant,)*
+            Other(String),
+        }
+
+        impl ErrorCode {
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(Self::$variant => $code,)*
+                    Self::Other(code) => code,
+                }
+            }
+
+            pub fn from_code(code: &str) -> Self {
+                match code {
+                    $($code => Self::$variant,)*
+                    other => Self::Other(other.to_string()),
+                }
+            }
+        }
+    };
+}
+
+error_codes! {
+    AliasDuplicate => "ALIAS_DUPLICATE",
+    AliasNameConflict => "ALIAS_NAME_CONFLICT",
+    AliasTargetKindUnsupported => "ALIAS_TARGET_KIND_UNSUPPORTED",
+    AliasTargetNotFound => "ALIAS_TARGET_NOT_FOUND",
+    ApiArtifactEntryKind => "API_ARTIFACT_ENTRY_KIND",
+    ApiArtifactEntryNotFound => "API_ARTIFACT_ENTRY_NOT_FOUND",
+    ApiArtifactEntryPrivate => "API_ARTIFACT_ENTRY_PRIVATE",
+    ApiEntryMainNotFound => "API_ENTRY_MAIN_NOT_FOUND",
+    ApiEntryScriptKind => "API_ENTRY_SCRIPT_KIND",
+    ApiEntryScriptNotFound => "API_ENTRY_SCRIPT_NOT_FOUND",
+    ApiEntryScriptPrivate => "API_ENTRY_SCRIPT_PRIVATE",
+    ArtifactEntryMainNotFound => "ARTIFACT_ENTRY_MAIN_NOT_FOUND",
+    ArtifactEntryScriptNotFound => "ARTIFACT_ENTRY_SCRIPT_NOT_FOUND",
+    ArtifactEntryScriptPrivate => "ARTIFACT_ENTRY_SCRIPT_PRIVATE",
+    ArtifactIoError => "ARTIFACT_IO_ERROR",
+    ArtifactParseError => "ARTIFACT_PARSE_ERROR",
+    ArtifactSchemaUnsupported => "ARTIFACT_SCHEMA_UNSUPPORTED",
+    CallArgsParseError => "CALL_ARGS_PARSE_ERROR",
+    CallInfiniteRecursion => "CALL_INFINITE_RECURSION",
+    CallRefRootUnknown => "CALL_REF_ROOT_UNKNOWN",
+    CliBenchIterationsInvalid => "CLI_BENCH_ITERATIONS_INVALID",
+    CliInitExists => "CLI_INIT_EXISTS",
+    CliInitWrite => "CLI_INIT_WRITE",
+    CliInputsRead => "CLI_INPUTS_READ",
+    CliManifestInvalid => "CLI_MANIFEST_INVALID",
+    CliOutputRequired => "CLI_OUTPUT_REQUIRED",
+    CliRandInvalid => "CLI_RAND_INVALID",
+    CliReplayActionKindMismatch => "CLI_REPLAY_ACTION_KIND_MISMATCH",
+    CliReplayDivergence => "CLI_REPLAY_DIVERGENCE",
+    CliReplayStepInvalid => "CLI_REPLAY_STEP_INVALID",
+    CliReplayTraceConflict => "CLI_REPLAY_TRACE_CONFLICT",
+    CliReplayUnusedActions => "CLI_REPLAY_UNUSED_ACTIONS",
+    CliSourceEmpty => "CLI_SOURCE_EMPTY",
+    CliSourceNotDir => "CLI_SOURCE_NOT_DIR",
+    CliSourceNotFound => "CLI_SOURCE_NOT_FOUND",
+    CliSourcePath => "CLI_SOURCE_PATH",
+    CliSourceRead => "CLI_SOURCE_READ",
+    CliSourceRefInvalid => "CLI_SOURCE_REF_INVALID",
+    CliSourceScan => "CLI_SOURCE_SCAN",
+    CliStateInvalid => "CLI_STATE_INVALID",
+    CliStateNotFound => "CLI_STATE_NOT_FOUND",
+    CliStateRead => "CLI_STATE_READ",
+    CliStateSchema => "CLI_STATE_SCHEMA",
+    CliStateWrite => "CLI_STATE_WRITE",
+    CliTraceInvalid => "CLI_TRACE_INVALID",
+    CliTraceNotFound => "CLI_TRACE_NOT_FOUND",
+    CliTraceRead => "CLI_TRACE_READ",
+    CliTraceSchema => "CLI_TRACE_SCHEMA",
+    CliTraceWrite => "CLI_TRACE_WRITE",
+    EngineArithmetic => "ENGINE_ARITHMETIC",
+    EngineBooleanExpected => "ENGINE_BOOLEAN_EXPECTED",
+    EngineCallArgMissing => "ENGINE_CALL_ARG_MISSING",
+    EngineCallArgUnknown => "ENGINE_CALL_ARG_UNKNOWN",
+    EngineCallNoFrame => "ENGINE_CALL_NO_FRAME",
+    EngineCallRefMismatch => "ENGINE_CALL_REF_MISMATCH",
+    EngineCallTarget => "ENGINE_CALL_TARGET",
+    EngineCallTargetEmpty => "ENGINE_CALL_TARGET_EMPTY",
+    EngineCallTargetKind => "ENGINE_CALL_TARGET_KIND",
+    EngineChoiceArrayNotArray => "ENGINE_CHOICE_ARRAY_NOT_ARRAY",
+    EngineChoiceContinueTargetMissing => "ENGINE_CHOICE_CONTINUE_TARGET_MISSING",
+    EngineChoiceDisabled => "ENGINE_CHOICE_DISABLED",
+    EngineChoiceFrameMissing => "ENGINE_CHOICE_FRAME_MISSING",
+    EngineChoiceIndex => "ENGINE_CHOICE_INDEX",
+    EngineChoiceNodeMissing => "ENGINE_CHOICE_NODE_MISSING",
+    EngineChoiceNotFound => "ENGINE_CHOICE_NOT_FOUND",
+    EngineChoiceStale => "ENGINE_CHOICE_STALE",
+    EngineConstReadonly => "ENGINE_CONST_READONLY",
+    EngineDivByZero => "ENGINE_DIV_BY_ZERO",
+    EngineEntryScriptKind => "ENGINE_ENTRY_SCRIPT_KIND",
+    EngineEntryScriptPrivate => "ENGINE_ENTRY_SCRIPT_PRIVATE",
+    EngineEnumInitRequired => "ENGINE_ENUM_INIT_REQUIRED",
+    EngineEvalError => "ENGINE_EVAL_ERROR",
+    EngineExplicitEndRequired => "ENGINE_EXPLICIT_END_REQUIRED",
+    EngineGlobalReadonly => "ENGINE_GLOBAL_READONLY",
+    EngineGotoArgUnknown => "ENGINE_GOTO_ARG_UNKNOWN",
+    EngineGotoGroupMismatch => "ENGINE_GOTO_GROUP_MISMATCH",
+    EngineGotoTarget => "ENGINE_GOTO_TARGET",
+    EngineGotoTargetEmpty => "ENGINE_GOTO_TARGET_EMPTY",
+    EngineGotoTargetKind => "ENGINE_GOTO_TARGET_KIND",
+    EngineGroupNotFound => "ENGINE_GROUP_NOT_FOUND",
+    EngineGuardExceeded => "ENGINE_GUARD_EXCEEDED",
+    EngineHostConstantConflict => "ENGINE_HOST_CONSTANT_CONFLICT",
+    EngineHostFunctionConflict => "ENGINE_HOST_FUNCTION_CONFLICT",
+    EngineHostFunctionMissing => "ENGINE_HOST_FUNCTION_MISSING",
+    EngineHostFunctionReserved => "ENGINE_HOST_FUNCTION_RESERVED",
+    EngineHostFunctionUnsupported => "ENGINE_HOST_FUNCTION_UNSUPPORTED",
+    EngineInputBooleanInvalid => "ENGINE_INPUT_BOOLEAN_INVALID",
+    EngineInputFrameMissing => "ENGINE_INPUT_FRAME_MISSING",
+    EngineInputJsonInvalid => "ENGINE_INPUT_JSON_INVALID",
+    EngineInputTooLong => "ENGINE_INPUT_TOO_LONG",
+    EngineInputVarType => "ENGINE_INPUT_VAR_TYPE",
+    EngineIntPrecision => "ENGINE_INT_PRECISION",
+    EngineLocaleKeyMissing => "ENGINE_LOCALE_KEY_MISSING",
+    EngineMaxTextCharsInvalid => "ENGINE_MAX_TEXT_CHARS_INVALID",
+    EngineMaxWhileIterationsInvalid => "ENGINE_MAX_WHILE_ITERATIONS_INVALID",
+    EngineModuleConstDeclMissing => "ENGINE_MODULE_CONST_DECL_MISSING",
+    EngineModuleConstMissing => "ENGINE_MODULE_CONST_MISSING",
+    EngineModuleFunctionReserved => "ENGINE_MODULE_FUNCTION_RESERVED",
+    EngineModuleFunctionSymbolConflict => "ENGINE_MODULE_FUNCTION_SYMBOL_CONFLICT",
+    EngineModuleFunctionSymbolMissing => "ENGINE_MODULE_FUNCTION_SYMBOL_MISSING",
+    EngineModuleGlobalDeclMissing => "ENGINE_MODULE_GLOBAL_DECL_MISSING",
+    EngineModuleGlobalMissing => "ENGINE_MODULE_GLOBAL_MISSING",
+    EngineModuleGlobalNamespaceType => "ENGINE_MODULE_GLOBAL_NAMESPACE_TYPE",
+    EngineNoFrame => "ENGINE_NO_FRAME",
+    EngineNoHistory => "ENGINE_NO_HISTORY",
+    EngineNoPendingChoice => "ENGINE_NO_PENDING_CHOICE",
+    EngineNoPendingInput => "ENGINE_NO_PENDING_INPUT",
+    EngineOnceStateInvalid => "ENGINE_ONCE_STATE_INVALID",
+    EngineRefPath => "ENGINE_REF_PATH",
+    EngineRefPathRead => "ENGINE_REF_PATH_READ",
+    EngineRefPathWrite => "ENGINE_REF_PATH_WRITE",
+    EngineRefValueMissing => "ENGINE_REF_VALUE_MISSING",
+    EngineReservedVarWrite => "ENGINE_RESERVED_VAR_WRITE",
+    EngineRootFrame => "ENGINE_ROOT_FRAME",
+    EngineScriptAccessDenied => "ENGINE_SCRIPT_ACCESS_DENIED",
+    EngineScriptMissing => "ENGINE_SCRIPT_MISSING",
+    EngineScriptNotFound => "ENGINE_SCRIPT_NOT_FOUND",
+    EngineTailRefUnsupported => "ENGINE_TAIL_REF_UNSUPPORTED",
+    EngineTargetVarType => "ENGINE_TARGET_VAR_TYPE",
+    EngineTextEmptyInterp => "ENGINE_TEXT_EMPTY_INTERP",
+    EngineTypeMismatch => "ENGINE_TYPE_MISMATCH",
+    EngineValueUnsupported => "ENGINE_VALUE_UNSUPPORTED",
+    EngineVarDuplicate => "ENGINE_VAR_DUPLICATE",
+    EngineVarFrame => "ENGINE_VAR_FRAME",
+    EngineVarRead => "ENGINE_VAR_READ",
+    EngineVarWrite => "ENGINE_VAR_WRITE",
+    EngineWhileControlTargetMissing => "ENGINE_WHILE_CONTROL_TARGET_MISSING",
+    EngineWhileLimitExceeded => "ENGINE_WHILE_LIMIT_EXCEEDED",
+    EnumDeclEmpty => "ENUM_DECL_EMPTY",
+    EnumInitRequired => "ENUM_INIT_REQUIRED",
+    EnumLiteralMemberUnknown => "ENUM_LITERAL_MEMBER_UNKNOWN",
+    EnumLiteralRequired => "ENUM_LITERAL_REQUIRED",
+    EnumMapKeyUnknown => "ENUM_MAP_KEY_UNKNOWN",
+    EnumMemberDuplicate => "ENUM_MEMBER_DUPLICATE",
+    FunctionArgsDuplicate => "FUNCTION_ARGS_DUPLICATE",
+    FunctionArgsParseError => "FUNCTION_ARGS_PARSE_ERROR",
+    FunctionDeclDuplicate => "FUNCTION_DECL_DUPLICATE",
+    FunctionReturnAttrInvalid => "FUNCTION_RETURN_ATTR_INVALID",
+    FunctionReturnStatementRequired => "FUNCTION_RETURN_STATEMENT_REQUIRED",
+    GlobalDataSymbolInvalid => "GLOBAL_DATA_SYMBOL_INVALID",
+    ImportCycle => "IMPORT_CYCLE",
+    ImportDirectiveUnsupported => "IMPORT_DIRECTIVE_UNSUPPORTED",
+    ImportDirEmpty => "IMPORT_DIR_EMPTY",
+    ImportDirPathInvalid => "IMPORT_DIR_PATH_INVALID",
+    ImportDuplicate => "IMPORT_DUPLICATE",
+    ImportFileNotFound => "IMPORT_FILE_NOT_FOUND",
+    ImportModuleDuplicate => "IMPORT_MODULE_DUPLICATE",
+    ImportModuleMismatch => "IMPORT_MODULE_MISMATCH",
+    ImportModuleNotFound => "IMPORT_MODULE_NOT_FOUND",
+    ImportNotFound => "IMPORT_NOT_FOUND",
+    ImportTargetInvalid => "IMPORT_TARGET_INVALID",
+    LinearGroupNotFound => "LINEAR_GROUP_NOT_FOUND",
+    LintSourceEmpty => "LINT_SOURCE_EMPTY",
+    LintSourceNotDir => "LINT_SOURCE_NOT_DIR",
+    LintSourceNotFound => "LINT_SOURCE_NOT_FOUND",
+    LintSourceRead => "LINT_SOURCE_READ",
+    LintSourceScan => "LINT_SOURCE_SCAN",
+    MergeNameConflict => "MERGE_NAME_CONFLICT",
+    MergePrefixDuplicate => "MERGE_PREFIX_DUPLICATE",
+    MergePrefixInvalid => "MERGE_PREFIX_INVALID",
+    MergeReferenceUnresolved => "MERGE_REFERENCE_UNRESOLVED",
+    MergeSymbolConflict => "MERGE_SYMBOL_CONFLICT",
+    ModuleConstInitOrder => "MODULE_CONST_INIT_ORDER",
+    ModuleConstInitRefNonConst => "MODULE_CONST_INIT_REF_NON_CONST",
+    ModuleGlobalConstDuplicate => "MODULE_GLOBAL_CONST_DUPLICATE",
+    ModuleGlobalInitOrder => "MODULE_GLOBAL_INIT_ORDER",
+    ModuleGlobalVarDuplicate => "MODULE_GLOBAL_VAR_DUPLICATE",
+    ModuleSymbolNotVisible => "MODULE_SYMBOL_NOT_VISIBLE",
+    NameIdentifierInvalid => "NAME_IDENTIFIER_INVALID",
+    NameReservedPrefix => "NAME_RESERVED_PREFIX",
+    NameRhaiKeywordReserved => "NAME_RHAI_KEYWORD_RESERVED",
+    RhaiPreprocessForbiddenAnd => "RHAI_PREPROCESS_FORBIDDEN_AND",
+    RhaiPreprocessForbiddenDoubleQuote => "RHAI_PREPROCESS_FORBIDDEN_DOUBLE_QUOTE",
+    RhaiPreprocessForbiddenLt => "RHAI_PREPROCESS_FORBIDDEN_LT",
+    RhaiPreprocessForbiddenLte => "RHAI_PREPROCESS_FORBIDDEN_LTE",
+    RhaiPreprocessForbiddenSingleQuote => "RHAI_PREPROCESS_FORBIDDEN_SINGLE_QUOTE",
+    RhaiPreprocessFunctionLiteralCallForbidden => "RHAI_PREPROCESS_FUNCTION_LITERAL_CALL_FORBIDDEN",
+    RhaiPreprocessStringUnterminated => "RHAI_PREPROCESS_STRING_UNTERMINATED",
+    ScriptArgsDuplicate => "SCRIPT_ARGS_DUPLICATE",
+    ScriptArgsParseError => "SCRIPT_ARGS_PARSE_ERROR",
+    ScriptCallArgsCountMismatch => "SCRIPT_CALL_ARGS_COUNT_MISMATCH",
+    ScriptCallArgTypeMismatch => "SCRIPT_CALL_ARG_TYPE_MISMATCH",
+    ScriptCallTargetKindMismatch => "SCRIPT_CALL_TARGET_KIND_MISMATCH",
+    ScriptGotoArgsCountMismatch => "SCRIPT_GOTO_ARGS_COUNT_MISMATCH",
+    ScriptGotoArgsRefUnsupported => "SCRIPT_GOTO_ARGS_REF_UNSUPPORTED",
+    ScriptGotoArgTypeMismatch => "SCRIPT_GOTO_ARG_TYPE_MISMATCH",
+    ScriptGotoTargetKindMismatch => "SCRIPT_GOTO_TARGET_KIND_MISMATCH",
+    ScriptNameDuplicate => "SCRIPT_NAME_DUPLICATE",
+    SnapshotCompilerVersion => "SNAPSHOT_COMPILER_VERSION",
+    SnapshotEmpty => "SNAPSHOT_EMPTY",
+    SnapshotEntryScriptUnknown => "SNAPSHOT_ENTRY_SCRIPT_UNKNOWN",
+    SnapshotGroupMissing => "SNAPSHOT_GROUP_MISSING",
+    SnapshotModuleGlobalType => "SNAPSHOT_MODULE_GLOBAL_TYPE",
+    SnapshotModuleGlobalUnknown => "SNAPSHOT_MODULE_GLOBAL_UNKNOWN",
+    SnapshotNodeChanged => "SNAPSHOT_NODE_CHANGED",
+    SnapshotNodeIndexOob => "SNAPSHOT_NODE_INDEX_OOB",
+    SnapshotNotAllowed => "SNAPSHOT_NOT_ALLOWED",
+    SnapshotPendingBoundary => "SNAPSHOT_PENDING_BOUNDARY",
+    SnapshotSchema => "SNAPSHOT_SCHEMA",
+    SourceEntryNotFound => "SOURCE_ENTRY_NOT_FOUND",
+    SourceKindUnsupported => "SOURCE_KIND_UNSUPPORTED",
+    TuiChoiceParse => "TUI_CHOICE_PARSE",
+    TuiIo => "TUI_IO",
+    TuiStateScenarioMismatch => "TUI_STATE_SCENARIO_MISMATCH",
+    TypeDeclDuplicate => "TYPE_DECL_DUPLICATE",
+    TypeDeclRecursive => "TYPE_DECL_RECURSIVE",
+    TypeFieldDuplicate => "TYPE_FIELD_DUPLICATE",
+    TypeMapKeyUnsupported => "TYPE_MAP_KEY_UNSUPPORTED",
+    TypeParseError => "TYPE_PARSE_ERROR",
+    TypeUnknown => "TYPE_UNKNOWN",
+    VarSessionShadow => "VAR_SESSION_SHADOW",
+    XmlAttrBoolInvalid => "XML_ATTR_BOOL_INVALID",
+    XmlAttrNotAllowed => "XML_ATTR_NOT_ALLOWED",
+    XmlBreakOutsideWhile => "XML_BREAK_OUTSIDE_WHILE",
+    XmlCallScriptEndForbidden => "XML_CALL_SCRIPT_END_FORBIDDEN",
+    XmlCallScriptGotoForbidden => "XML_CALL_SCRIPT_GOTO_FORBIDDEN",
+    XmlCheckpointContentForbidden => "XML_CHECKPOINT_CONTENT_FORBIDDEN",
+    XmlChoiceChildInvalid => "XML_CHOICE_CHILD_INVALID",
+    XmlChoiceTimeoutDefaultUnknown => "XML_CHOICE_TIMEOUT_DEFAULT_UNKNOWN",
+    XmlCommentContentInvalid => "XML_COMMENT_CONTENT_INVALID",
+    XmlContinueOutsideWhileOrOption => "XML_CONTINUE_OUTSIDE_WHILE_OR_OPTION",
+    XmlDynamicOptionsChildInvalid => "XML_DYNAMIC_OPTIONS_CHILD_INVALID",
+    XmlDynamicOptionsTemplateRequired => "XML_DYNAMIC_OPTIONS_TEMPLATE_REQUIRED",
+    XmlDynamicOptionEnabledWhenUnsupported => "XML_DYNAMIC_OPTION_ENABLED_WHEN_UNSUPPORTED",
+    XmlDynamicOptionFallOverUnsupported => "XML_DYNAMIC_OPTION_FALL_OVER_UNSUPPORTED",
+    XmlDynamicOptionOnceUnsupported => "XML_DYNAMIC_OPTION_ONCE_UNSUPPORTED",
+    XmlDynamicOptionPinnedUnsupported => "XML_DYNAMIC_OPTION_PINNED_UNSUPPORTED",
+    XmlDynamicOptionSceneUnsupported => "XML_DYNAMIC_OPTION_SCENE_UNSUPPORTED",
+    XmlElsePosition => "XML_ELSE_POSITION",
+    XmlEmptyAttr => "XML_EMPTY_ATTR",
+    XmlEmptyNodeContent => "XML_EMPTY_NODE_CONTENT",
+    XmlEndAttrNotAllowed => "XML_END_ATTR_NOT_ALLOWED",
+    XmlEndContentForbidden => "XML_END_CONTENT_FORBIDDEN",
+    XmlEnumChildInvalid => "XML_ENUM_CHILD_INVALID",
+    XmlEnumMemberContentForbidden => "XML_ENUM_MEMBER_CONTENT_FORBIDDEN",
+    XmlExportDuplicate => "XML_EXPORT_DUPLICATE",
+    XmlExportInvalid => "XML_EXPORT_INVALID",
+    XmlExportKindInvalid => "XML_EXPORT_KIND_INVALID",
+    XmlExportTargetNotFound => "XML_EXPORT_TARGET_NOT_FOUND",
+    XmlFailCodeInvalid => "XML_FAIL_CODE_INVALID",
+    XmlFailContentForbidden => "XML_FAIL_CONTENT_FORBIDDEN",
+    XmlForInternal => "XML_FOR_INTERNAL",
+    XmlForTempsDuplicate => "XML_FOR_TEMPS_DUPLICATE",
+    XmlForTempsInvalid => "XML_FOR_TEMPS_INVALID",
+    XmlFunctionArgsRefUnsupported => "XML_FUNCTION_ARGS_REF_UNSUPPORTED",
+    XmlFunctionAssignStringForbidden => "XML_FUNCTION_ASSIGN_STRING_FORBIDDEN",
+    XmlFunctionChildNodeInvalid => "XML_FUNCTION_CHILD_NODE_INVALID",
+    XmlFunctionLiteralCallForbidden => "XML_FUNCTION_LITERAL_CALL_FORBIDDEN",
+    XmlFunctionLiteralInvalid => "XML_FUNCTION_LITERAL_INVALID",
+    XmlFunctionLiteralNotFound => "XML_FUNCTION_LITERAL_NOT_FOUND",
+    XmlGotoRefUnsupported => "XML_GOTO_REF_UNSUPPORTED",
+    XmlGotoScriptReturnForbidden => "XML_GOTO_SCRIPT_RETURN_FORBIDDEN",
+    XmlInitFormatInvalid => "XML_INIT_FORMAT_INVALID",
+    XmlInitMapKeyInvalid => "XML_INIT_MAP_KEY_INVALID",
+    XmlInitXmlChildInvalid => "XML_INIT_XML_CHILD_INVALID",
+    XmlInitXmlEnumMapInvalid => "XML_INIT_XML_ENUM_MAP_INVALID",
+    XmlInitXmlFieldDuplicate => "XML_INIT_XML_FIELD_DUPLICATE",
+    XmlInitXmlFieldMissing => "XML_INIT_XML_FIELD_MISSING",
+    XmlInitXmlFieldUnknown => "XML_INIT_XML_FIELD_UNKNOWN",
+    XmlInitXmlMixedContent => "XML_INIT_XML_MIXED_CONTENT",
+    XmlInitXmlObjectInvalid => "XML_INIT_XML_OBJECT_INVALID",
+    XmlInitXmlTypeUnsupported => "XML_INIT_XML_TYPE_UNSUPPORTED",
+    XmlInputContentForbidden => "XML_INPUT_CONTENT_FORBIDDEN",
+    XmlInputDefaultUnsupported => "XML_INPUT_DEFAULT_UNSUPPORTED",
+    XmlInputFormatInvalid => "XML_INPUT_FORMAT_INVALID",
+    XmlInputMaxLengthInvalid => "XML_INPUT_MAX_LENGTH_INVALID",
+    XmlInputVarType => "XML_INPUT_VAR_TYPE",
+    XmlInputVarUnknown => "XML_INPUT_VAR_UNKNOWN",
+    XmlInvokeTargetVarRequired => "XML_INVOKE_TARGET_VAR_REQUIRED",
+    XmlInvokeTargetVarType => "XML_INVOKE_TARGET_VAR_TYPE",
+    XmlMissingAttr => "XML_MISSING_ATTR",
+    XmlModuleChildInvalid => "XML_MODULE_CHILD_INVALID",
+    XmlModuleNameMissing => "XML_MODULE_NAME_MISSING",
+    XmlNodeLabelDuplicate => "XML_NODE_LABEL_DUPLICATE",
+    XmlNodeLabelInvalid => "XML_NODE_LABEL_INVALID",
+    XmlNodeUnsupported => "XML_NODE_UNSUPPORTED",
+    XmlOnceScopeInvalid => "XML_ONCE_SCOPE_INVALID",
+    XmlOnceScopeWithoutOnce => "XML_ONCE_SCOPE_WITHOUT_ONCE",
+    XmlOptionFallOverDuplicate => "XML_OPTION_FALL_OVER_DUPLICATE",
+    XmlOptionFallOverNotLast => "XML_OPTION_FALL_OVER_NOT_LAST",
+    XmlOptionFallOverWhenForbidden => "XML_OPTION_FALL_OVER_WHEN_FORBIDDEN",
+    XmlOptionMaxSelectInvalid => "XML_OPTION_MAX_SELECT_INVALID",
+    XmlOptionMaxSelectOnceConflict => "XML_OPTION_MAX_SELECT_ONCE_CONFLICT",
+    XmlOptionPinnedFallOverConflict => "XML_OPTION_PINNED_FALL_OVER_CONFLICT",
+    XmlOptionPinnedNotLast => "XML_OPTION_PINNED_NOT_LAST",
+    XmlOptionPinnedWhenForbidden => "XML_OPTION_PINNED_WHEN_FORBIDDEN",
+    XmlOptionSceneEmpty => "XML_OPTION_SCENE_EMPTY",
+    XmlOptionSetEmpty => "XML_OPTION_SET_EMPTY",
+    XmlParseError => "XML_PARSE_ERROR",
+    XmlRemovedNode => "XML_REMOVED_NODE",
+    XmlReturnAttrNotAllowed => "XML_RETURN_ATTR_NOT_ALLOWED",
+    XmlReturnContentForbidden => "XML_RETURN_CONTENT_FORBIDDEN",
+    XmlRhaiSyntaxInvalid => "XML_RHAI_SYNTAX_INVALID",
+    XmlRootInvalid => "XML_ROOT_INVALID",
+    XmlScriptAssignStringForbidden => "XML_SCRIPT_ASSIGN_STRING_FORBIDDEN",
+    XmlScriptKindInvalid => "XML_SCRIPT_KIND_INVALID",
+    XmlScriptTagInvalid => "XML_SCRIPT_TAG_INVALID",
+    XmlScriptTargetAccessDenied => "XML_SCRIPT_TARGET_ACCESS_DENIED",
+    XmlScriptTargetInvalid => "XML_SCRIPT_TARGET_INVALID",
+    XmlScriptTargetNotFound => "XML_SCRIPT_TARGET_NOT_FOUND",
+    XmlScriptTargetTemplateRemoved => "XML_SCRIPT_TARGET_TEMPLATE_REMOVED",
+    XmlScriptTargetVarType => "XML_SCRIPT_TARGET_VAR_TYPE",
+    XmlScriptTargetVarUnknown => "XML_SCRIPT_TARGET_VAR_UNKNOWN",
+    XmlScriptTerminatorRequired => "XML_SCRIPT_TERMINATOR_REQUIRED",
+    XmlTempInputContentForbidden => "XML_TEMP_INPUT_CONTENT_FORBIDDEN",
+    XmlTempInputInternal => "XML_TEMP_INPUT_INTERNAL",
+    XmlTempInputTypeUnsupported => "XML_TEMP_INPUT_TYPE_UNSUPPORTED",
+    XmlTextExprAndInline => "XML_TEXT_EXPR_AND_INLINE",
+    XmlTextExprEmpty => "XML_TEXT_EXPR_EMPTY",
+    XmlTextKeyConflict => "XML_TEXT_KEY_CONFLICT",
+    XmlTextKeyEmpty => "XML_TEXT_KEY_EMPTY",
+    XmlTypeChildInvalid => "XML_TYPE_CHILD_INVALID",
+    XmlUnknownAttr => "XML_UNKNOWN_ATTR",
+    XmlUsesFunctionInvalid => "XML_USES_FUNCTION_INVALID",
+    XmlVarChildInvalid => "XML_VAR_CHILD_INVALID",
+    XmlVarDuplicate => "XML_VAR_DUPLICATE",
+    XmlWhileMaxInvalid => "XML_WHILE_MAX_INVALID",
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<ErrorCode> for String {
+    fn from(code: ErrorCode) -> Self {
+        match code {
+            ErrorCode::Other(code) => code,
+            known => known.as_str().to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_round_trip_through_their_string_form() {
+        let code = ErrorCode::from_code("ENGINE_TYPE_MISMATCH");
+        assert_eq!(code, ErrorCode::EngineTypeMismatch);
+        assert_eq!(code.as_str(), "ENGINE_TYPE_MISMATCH");
+        assert_eq!(String::from(code), "ENGINE_TYPE_MISMATCH");
+
+        let custom = ErrorCode::from_code("QUEST_LOCKED");
+        assert_eq!(custom, ErrorCode::Other("QUEST_LOCKED".to_string()));
+        assert_eq!(custom.to_string(), "QUEST_LOCKED");
+    }
+}
@@ -1,9 +1,11 @@
 pub mod error;
+pub mod error_code;
 pub mod rhai;
 pub mod types;
 pub mod value;
 
 pub use error::ScriptLangError;
+pub use error_code::ErrorCode;
 pub use rhai::*;
 pub use types::*;
 pub use value::*;
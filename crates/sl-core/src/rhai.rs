@@ -3,7 +3,7 @@ use std::collections::BTreeMap;
 
 use regex::Regex;
 
-use crate::ScriptLangError;
+use crate::{ErrorCode, ScriptLangError};
 
 pub fn rhai_function_symbol(name: &str) -> String {
     let mut out = String::with_capacity(name.len());
@@ -83,7 +83,7 @@ pub fn rewrite_module_global_qualified_access(
     }
 
     let mut names = qualified_to_expr.iter().collect::<Vec<_>>();
-    names.sort_by(|(left, _), (right, _)| right.len().cmp(&left.len()));
+    names.sort_by_key(|(left, _)| std::cmp::Reverse(left.len()));
 
     let mut rewritten = source.to_string();
     for (qualified_name, target_expr) in names {
@@ -149,7 +149,7 @@ pub fn preprocess_scriptlang_rhai_input(
                 }
                 RhaiInputMode::TextInterpolationExpr => {
                     return Err(preprocess_error(
-                        "RHAI_PREPROCESS_FORBIDDEN_SINGLE_QUOTE",
+                        ErrorCode::RhaiPreprocessForbiddenSingleQuote,
                         context,
                         "single-quoted strings are forbidden in text interpolation expressions",
                         "Use double-quoted strings like \"text\" inside ${...}.",
@@ -157,7 +157,7 @@ pub fn preprocess_scriptlang_rhai_input(
                 }
                 RhaiInputMode::CodeBlock => {
                     return Err(preprocess_error(
-                        "RHAI_PREPROCESS_FORBIDDEN_SINGLE_QUOTE",
+                        ErrorCode::RhaiPreprocessForbiddenSingleQuote,
                         context,
                         "single-quoted strings are forbidden in code-style expressions",
                         "Use double-quoted strings like \"text\" in <code>, <function>, and <var> initializer bodies.",
@@ -167,7 +167,7 @@ pub fn preprocess_scriptlang_rhai_input(
             '"' => match mode {
                 RhaiInputMode::AttributeExpr => {
                     return Err(preprocess_error(
-                        "RHAI_PREPROCESS_FORBIDDEN_DOUBLE_QUOTE",
+                        ErrorCode::RhaiPreprocessForbiddenDoubleQuote,
                         context,
                         "double-quoted strings are forbidden in attribute expressions",
                         "Use single-quoted strings like 'text' in XML attributes.",
@@ -189,7 +189,7 @@ pub fn preprocess_scriptlang_rhai_input(
                                 out.push('\\');
                                 let Some(next) = chars.get(index + 1).copied() else {
                                     return Err(preprocess_error(
-                                            "RHAI_PREPROCESS_STRING_UNTERMINATED",
+                                            ErrorCode::RhaiPreprocessStringUnterminated,
                                             context,
                                             "unterminated escape in double-quoted string",
                                             "Close the string with \" and keep escapes inside the string body.",
@@ -206,7 +206,7 @@ pub fn preprocess_scriptlang_rhai_input(
                     }
                     if !closed {
                         return Err(preprocess_error(
-                            "RHAI_PREPROCESS_STRING_UNTERMINATED",
+                            ErrorCode::RhaiPreprocessStringUnterminated,
                             context,
                             "unterminated double-quoted string",
                             "Close the string with \".",
@@ -216,11 +216,11 @@ pub fn preprocess_scriptlang_rhai_input(
             },
             '<' => {
                 let code = if chars.get(index + 1) == Some(&'=') {
-                    "RHAI_PREPROCESS_FORBIDDEN_LTE"
+                    ErrorCode::RhaiPreprocessForbiddenLte
                 } else {
-                    "RHAI_PREPROCESS_FORBIDDEN_LT"
+                    ErrorCode::RhaiPreprocessForbiddenLt
                 };
-                let replacement = if code.ends_with("LTE") {
+                let replacement = if code == ErrorCode::RhaiPreprocessForbiddenLte {
                     "Use LTE instead of <=."
                 } else {
                     "Use LT instead of <."
@@ -234,7 +234,7 @@ pub fn preprocess_scriptlang_rhai_input(
             }
             '&' if chars.get(index + 1) == Some(&'&') => {
                 return Err(preprocess_error(
-                    "RHAI_PREPROCESS_FORBIDDEN_AND",
+                    ErrorCode::RhaiPreprocessForbiddenAnd,
                     context,
                     "raw logical operator && is forbidden",
                     "Use AND instead of &&.",
@@ -262,7 +262,7 @@ pub fn preprocess_scriptlang_rhai_input(
                     }
                     if chars.get(lookahead) == Some(&'(') {
                         return Err(preprocess_error(
-                            "RHAI_PREPROCESS_FUNCTION_LITERAL_CALL_FORBIDDEN",
+                            ErrorCode::RhaiPreprocessFunctionLiteralCallForbidden,
                             context,
                             "function literal cannot be called directly",
                             "Use method(...) or module.method(...), not *module.method(...).",
@@ -289,6 +289,8 @@ pub fn preprocess_scriptlang_rhai_input(
                     "LTE" => out.push_str("<="),
                     "LT" => out.push('<'),
                     "AND" => out.push_str("&&"),
+                    // Rhai reserves `null` without giving it a value; it means unit `()` here.
+                    "null" => out.push_str("()"),
                     _ => out.push_str(&token),
                 }
             }
@@ -404,7 +406,7 @@ fn parse_single_quoted_string(
             '\\' => {
                 let Some(next) = chars.get(index + 1).copied() else {
                     return Err(preprocess_error(
-                        "RHAI_PREPROCESS_STRING_UNTERMINATED",
+                        ErrorCode::RhaiPreprocessStringUnterminated,
                         context,
                         "unterminated escape in single-quoted string",
                         "Close the string and escape inner apostrophes as \\\\'",
@@ -437,7 +439,7 @@ fn parse_single_quoted_string(
     }
 
     Err(preprocess_error(
-        "RHAI_PREPROCESS_STRING_UNTERMINATED",
+        ErrorCode::RhaiPreprocessStringUnterminated,
         context,
         "unterminated single-quoted string",
         "Close the string with ' and escape inner apostrophes as \\\\'",
@@ -445,7 +447,7 @@ fn parse_single_quoted_string(
 }
 
 fn preprocess_error(
-    code: &'static str,
+    code: ErrorCode,
     context: &str,
     detail: &str,
     recommendation: &str,
@@ -578,6 +580,14 @@ mod rhai_tests {
         .expect("preprocess");
         assert_eq!(rewritten, "hp <= 10 && name == \"Rin\" && slot == SLOT");
 
+        let null_check = preprocess_scriptlang_rhai_input(
+            "nick == null AND 'null' != nullable",
+            "expression",
+            RhaiInputMode::AttributeExpr,
+        )
+        .expect("preprocess");
+        assert_eq!(null_check, "nick == () && \"null\" != nullable");
+
         let escaped = preprocess_scriptlang_rhai_input(
             "'I\\'m \"ok\"'",
             "expression",
@@ -64,6 +64,8 @@ pub enum ScriptType {
 #[serde(tag = "kind", rename_all = "camelCase")]
 pub enum MapKeyType {
     String,
+    /// Keys are stored as canonical decimal strings (`"7"`, `"-3"`).
+    Int,
     Enum {
         type_name: String,
         members: Vec<String>,
@@ -88,6 +90,9 @@ pub struct ModuleVarDecl {
     pub r#type: ScriptType,
     pub initial_value_expr: Option<String>,
     pub location: SourceSpan,
+    /// `<var session="true">`: the value is kept across `start` instead of being re-initialized.
+    #[serde(default)]
+    pub session: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -158,8 +163,24 @@ pub struct ChoiceOption {
     pub id: String,
     pub text: String,
     pub when_expr: Option<String>,
+    /// `enabled_when="..."`: the option stays listed but `choose` rejects it while this is false.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled_when_expr: Option<String>,
     pub once: bool,
+    /// `scope="global"`: the once-key is shared across scripts instead of tracked per script.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub global_once: bool,
+    /// `max_select="N"` with N > 1: hidden once selected N times in the same script.
+    /// `max_select="1"` compiles to `once` instead.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_select: Option<u32>,
     pub fall_over: bool,
+    /// Shown after the regular (or fall-over) options whenever the choice is offered.
+    #[serde(default)]
+    pub pinned: bool,
+    /// Host transition hint from `scene="..."`; copied onto the rendered `ChoiceItem`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scene: Option<String>,
     pub group_id: String,
     pub location: SourceSpan,
 }
@@ -195,9 +216,21 @@ pub enum ScriptNode {
     Text {
         id: String,
         value: String,
+        /// Locale table key; when set, `value` is empty and the template comes from the locale.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        key: Option<String>,
+        /// `<text expr="...">`: when set, `value` is empty and the line is this expression's value.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expr: Option<String>,
         #[serde(default, skip_serializing_if = "Option::is_none")]
         tag: Option<String>,
+        /// Speaker template (may contain `${...}`), rendered alongside the text.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        speaker: Option<String>,
         once: bool,
+        /// `scope="global"`: the once-key is shared across scripts instead of tracked per script.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        global_once: bool,
         location: SourceSpan,
     },
     Debug {
@@ -208,6 +241,9 @@ pub enum ScriptNode {
     Code {
         id: String,
         code: String,
+        /// Emitted by a compiler macro; only internal code may write `__sl_` vars.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        internal: bool,
         location: SourceSpan,
     },
     Var {
@@ -226,12 +262,16 @@ pub enum ScriptNode {
         id: String,
         when_expr: String,
         body_group_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_iterations: Option<usize>,
         location: SourceSpan,
     },
     Choice {
         id: String,
         prompt_text: String,
         entries: Vec<ChoiceEntry>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout_default_option_id: Option<String>,
         location: SourceSpan,
     },
     Input {
@@ -240,6 +280,8 @@ pub enum ScriptNode {
         prompt_text: String,
         #[serde(default, skip_serializing_if = "Option::is_none")]
         max_length: Option<usize>,
+        #[serde(default, skip_serializing_if = "InputFormat::is_text")]
+        format: InputFormat,
         location: SourceSpan,
     },
     Break {
@@ -267,12 +309,49 @@ pub enum ScriptNode {
         id: String,
         location: SourceSpan,
     },
+    /// Script-marked save point: emits `EngineOutput::Checkpoint` and continues.
+    Checkpoint {
+        id: String,
+        checkpoint_id: String,
+        location: SourceSpan,
+    },
+    /// Author-declared failure: executing it ends `next_output` with a `USER_`-prefixed error.
+    Fail {
+        id: String,
+        code: String,
+        message: String,
+        location: SourceSpan,
+    },
     Return {
         id: String,
         location: SourceSpan,
     },
 }
 
+impl ScriptNode {
+    /// Compiler-assigned id of the node, unique within its script.
+    pub fn id(&self) -> &str {
+        match self {
+            ScriptNode::Text { id, .. }
+            | ScriptNode::Debug { id, .. }
+            | ScriptNode::Code { id, .. }
+            | ScriptNode::Var { id, .. }
+            | ScriptNode::If { id, .. }
+            | ScriptNode::While { id, .. }
+            | ScriptNode::Choice { id, .. }
+            | ScriptNode::Input { id, .. }
+            | ScriptNode::Break { id, .. }
+            | ScriptNode::Continue { id, .. }
+            | ScriptNode::Call { id, .. }
+            | ScriptNode::Goto { id, .. }
+            | ScriptNode::End { id, .. }
+            | ScriptNode::Checkpoint { id, .. }
+            | ScriptNode::Fail { id, .. }
+            | ScriptNode::Return { id, .. } => id,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ContinueTarget {
@@ -280,12 +359,43 @@ pub enum ContinueTarget {
     Choice,
 }
 
+/// How submitted `<input>` text is converted before it is written to the target var.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum InputFormat {
+    #[default]
+    Text,
+    Json,
+    Boolean,
+}
+
+impl InputFormat {
+    pub fn is_text(&self) -> bool {
+        *self == Self::Text
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ImplicitGroup {
     pub group_id: String,
     pub parent_group_id: Option<String>,
     pub entry_node_id: Option<String>,
     pub nodes: Vec<ScriptNode>,
+    /// Content hash of each entry in `nodes`, used to detect saves pointing into edited nodes.
+    /// Empty for IR built outside the compiler, which skips that check.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub node_hashes: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub comments: Vec<GroupComment>,
+}
+
+/// Author note from a `<comment>` element: exported with the IR for tooling, never executed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GroupComment {
+    /// Index in `nodes` of the node the comment precedes; `nodes.len()` for a trailing comment.
+    pub node_index: usize,
+    pub text: String,
+    pub location: SourceSpan,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -300,6 +410,9 @@ pub struct ScriptIr {
     pub local_script_name: Option<String>,
     #[serde(default)]
     pub kind: ScriptKind,
+    /// Categories from `<script tags="...">`; metadata only, never read by execution.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
     pub params: Vec<ScriptParam>,
     pub root_group_id: String,
     pub groups: BTreeMap<String, ImplicitGroup>,
@@ -311,6 +424,81 @@ pub struct ScriptIr {
     pub visible_module_consts: BTreeMap<String, ModuleConstDecl>,
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     pub invoke_all_functions: BTreeMap<String, FunctionDecl>,
+    /// Host functions opted into via `<uses functions="...">`; the only ones callable while this
+    /// script evaluates.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub host_functions: Vec<String>,
+    /// Statement labels from `label="..."`, mapped to the labeled node id; `sl_visited(label)`
+    /// reads the visit count of that node.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub node_labels: BTreeMap<String, String>,
+}
+
+/// A script body lowered to a flat instruction list with explicit jumps.
+///
+/// Produced by `sl_compiler::lower_to_linear` for hosts that run scripts on a simple linear VM
+/// instead of walking the group tree; jump targets index into `instructions`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinearProgram {
+    pub script_name: String,
+    #[serde(default)]
+    pub kind: ScriptKind,
+    pub params: Vec<ScriptParam>,
+    pub instructions: Vec<LinearInstruction>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "camelCase")]
+pub enum LinearInstruction {
+    /// Straight-line node (text, debug, code, var, input, call, goto, end, fail), run as in the
+    /// tree engine.
+    Node {
+        node: ScriptNode,
+    },
+    /// Open a block scope for the `<temp>`s that follow; closed by the matching `ExitScope`.
+    EnterScope,
+    ExitScope,
+    Jump {
+        target: usize,
+    },
+    /// `<if>`: fall through when `when_expr` is true, otherwise continue at `target`.
+    BranchUnless {
+        node_id: String,
+        when_expr: String,
+        target: usize,
+    },
+    /// `<while>` loop head: fall through into the body when `when_expr` is true, counting the
+    /// iteration against `max_iterations`; otherwise continue at `exit`.
+    WhileHead {
+        node_id: String,
+        when_expr: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_iterations: Option<usize>,
+        exit: usize,
+    },
+    /// First instruction after a loop (reached on exit or `<break>`); resets the iteration count
+    /// of the `WhileHead` at `head`.
+    LoopEnd {
+        head: usize,
+    },
+    /// `<choice>`: offer the visible entries. Choosing one opens a scope (binding a dynamic
+    /// option's item and index) and continues at its `target`; with nothing visible, falls
+    /// through.
+    Choice {
+        node_id: String,
+        prompt_text: String,
+        entries: Vec<LinearChoiceEntry>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout_default_option_id: Option<String>,
+    },
+    /// `<return/>`, and the implicit end of the script body.
+    Return,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinearChoiceEntry {
+    pub entry: ChoiceEntry,
+    pub target: usize,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -331,6 +519,10 @@ pub struct SnapshotFrame {
     pub completion: SnapshotCompletion,
     pub script_root: bool,
     pub return_continuation: Option<ContinuationFrame>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text_page: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub while_iterations: Option<usize>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -350,6 +542,15 @@ pub struct ChoiceItem {
     pub index: usize,
     pub id: String,
     pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scene: Option<String>,
+    /// False when the option's `enabled_when` failed: show it greyed out; `choose` rejects it.
+    #[serde(default = "default_choice_item_enabled")]
+    pub enabled: bool,
+}
+
+fn default_choice_item_enabled() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -382,6 +583,12 @@ pub enum PendingBoundary {
         #[serde(default, skip_serializing_if = "Option::is_none")]
         max_length: Option<usize>,
     },
+    /// Taken at a `<checkpoint>`; resuming continues with the node after it.
+    #[serde(rename_all = "camelCase")]
+    Checkpoint {
+        node_id: String,
+        checkpoint_id: String,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -395,15 +602,55 @@ pub struct Snapshot {
     #[serde(default)]
     pub module_vars: BTreeMap<String, SlValue>,
     pub once_state_by_script: BTreeMap<String, Vec<String>>,
+    /// Once-keys consumed by `scope="global"` nodes, shared by every script.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub global_once_state: Vec<String>,
+    /// Selection counts of `max_select` options (`option:<id>`), by script name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub select_count_by_script: BTreeMap<String, BTreeMap<String, u32>>,
+    /// Executions of labeled nodes read by `sl_visited`, by node id.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub visit_counts: BTreeMap<String, usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coverage: Option<BTreeMap<String, usize>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub clock: Option<i64>,
+    /// Script the session was started from; empty in snapshots written before it was recorded.
+    #[serde(default)]
+    pub entry_script: String,
+    #[serde(default)]
+    pub entry_args: BTreeMap<String, SlValue>,
+    /// Content hash of the pending node when the snapshot was taken.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node_hash: Option<String>,
+    /// How many `random_override` values were consumed; absent when no override was set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub random_override_index: Option<usize>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Live engine state for diagnostics, taken at any point rather than only at a boundary.
+///
+/// Not a save format: there is no way to resume from it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugState {
+    pub frames: Vec<SnapshotFrame>,
+    pub pending_boundary: Option<PendingBoundary>,
+    pub rng_state: u32,
+    pub module_vars: BTreeMap<String, SlValue>,
+    pub waiting_choice: bool,
+    pub ended: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub enum EngineOutput {
     Text {
         text: String,
         #[serde(default, skip_serializing_if = "Option::is_none")]
         tag: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        speaker: Option<String>,
     },
     Debug {
         text: String,
@@ -411,6 +658,8 @@ pub enum EngineOutput {
     Choices {
         items: Vec<ChoiceItem>,
         prompt_text: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        default_index: Option<usize>,
     },
     Input {
         prompt_text: String,
@@ -418,6 +667,11 @@ pub enum EngineOutput {
         #[serde(default, skip_serializing_if = "Option::is_none")]
         max_length: Option<usize>,
     },
+    /// A `<checkpoint>` was reached; `snapshot` resumes right after it. Execution continues.
+    Checkpoint {
+        id: String,
+        snapshot: Box<Snapshot>,
+    },
     End,
 }
 
@@ -465,6 +719,20 @@ mod tests {
         assert_eq!(span.end.column, 1);
     }
 
+    #[test]
+    fn script_node_id_reads_the_node_id() {
+        let node = ScriptNode::Return {
+            id: "return_1".to_string(),
+            location: SourceSpan::synthetic(),
+        };
+        assert_eq!(node.id(), "return_1");
+        let node = ScriptNode::End {
+            id: "end_1".to_string(),
+            location: SourceSpan::synthetic(),
+        };
+        assert_eq!(node.id(), "end_1");
+    }
+
     #[test]
     fn compiled_project_schema_constant_matches() {
         assert_eq!(COMPILED_PROJECT_SCHEMA, "compiled-project");
@@ -1,6 +1,7 @@
 use std::collections::BTreeMap;
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
 
 use crate::types::{MapKeyType, ScriptType};
 
@@ -12,6 +13,8 @@ pub enum SlValue {
     String(String),
     Array(Vec<SlValue>),
     Map(BTreeMap<String, SlValue>),
+    /// `null` in expressions and JSON; Rhai's unit `()` at runtime.
+    Null,
 }
 
 impl SlValue {
@@ -36,10 +39,58 @@ impl SlValue {
             Self::String(_) => "string",
             Self::Array(_) => "array",
             Self::Map(_) => "map",
+            Self::Null => "null",
         }
     }
 }
 
+/// Convert plain JSON into an `SlValue`. Numbers become `f64`.
+pub fn slvalue_from_json(value: JsonValue) -> SlValue {
+    match value {
+        JsonValue::Null => SlValue::Null,
+        JsonValue::Bool(value) => SlValue::Bool(value),
+        JsonValue::Number(value) => SlValue::Number(value.as_f64().unwrap_or(0.0)),
+        JsonValue::String(value) => SlValue::String(value),
+        JsonValue::Array(values) => {
+            SlValue::Array(values.into_iter().map(slvalue_from_json).collect())
+        }
+        JsonValue::Object(values) => SlValue::Map(
+            values
+                .into_iter()
+                .map(|(key, value)| (key, slvalue_from_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// Convert an `SlValue` into plain JSON, the inverse of [`slvalue_from_json`].
+///
+/// Integral numbers are written as JSON integers so `3` round-trips as `3` rather than `3.0`.
+/// Non-finite numbers have no JSON form and become `null`.
+pub fn slvalue_to_json(value: &SlValue) -> JsonValue {
+    match value {
+        SlValue::Bool(value) => JsonValue::Bool(*value),
+        SlValue::Number(value) => {
+            if is_integral_number(*value) && value.abs() < i64::MAX as f64 {
+                JsonValue::from(*value as i64)
+            } else {
+                serde_json::Number::from_f64(*value)
+                    .map(JsonValue::Number)
+                    .unwrap_or(JsonValue::Null)
+            }
+        }
+        SlValue::String(value) => JsonValue::String(value.clone()),
+        SlValue::Array(values) => JsonValue::Array(values.iter().map(slvalue_to_json).collect()),
+        SlValue::Map(values) => JsonValue::Object(
+            values
+                .iter()
+                .map(|(key, value)| (key.clone(), slvalue_to_json(value)))
+                .collect(),
+        ),
+        SlValue::Null => JsonValue::Null,
+    }
+}
+
 fn is_integral_number(value: f64) -> bool {
     value.is_finite() && value.fract().abs() < f64::EPSILON
 }
@@ -51,8 +102,7 @@ fn is_function_reference(value: &str) -> bool {
     if rest.is_empty() {
         return false;
     }
-    let mut segment_count = 1usize;
-    for segment in rest.split('.') {
+    for (index, segment) in rest.split('.').enumerate() {
         if segment.is_empty() {
             return false;
         }
@@ -64,10 +114,9 @@ fn is_function_reference(value: &str) -> bool {
         if !chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_') {
             return false;
         }
-        if segment_count > 2 {
+        if index > 1 {
             return false;
         }
-        segment_count += 1;
     }
     true
 }
@@ -98,6 +147,13 @@ pub fn default_value_from_type(ty: &ScriptType) -> SlValue {
     }
 }
 
+/// Whether `key` is the string form of an `int` map key: an i64 that round-trips through
+/// `to_string`, so `"7"` and `"-3"` pass while `"07"`, `"+7"` and `"7.0"` do not.
+fn is_canonical_int_key(key: &str) -> bool {
+    key.parse::<i64>()
+        .is_ok_and(|number| number.to_string() == key)
+}
+
 pub fn is_type_compatible(value: &SlValue, ty: &ScriptType) -> bool {
     match ty {
         ScriptType::Primitive { name } => match (name.as_str(), value) {
@@ -132,6 +188,7 @@ pub fn is_type_compatible(value: &SlValue, ty: &ScriptType) -> bool {
             SlValue::Map(values) => values.iter().all(|(key, entry)| {
                 let key_ok = match key_type {
                     MapKeyType::String => true,
+                    MapKeyType::Int => is_canonical_int_key(key),
                     MapKeyType::Enum { members, .. } => members.iter().any(|member| member == key),
                 };
                 key_ok && is_type_compatible(entry, value_type)
@@ -181,6 +238,36 @@ mod tests {
         assert_eq!(SlValue::String("x".to_string()).type_name(), "string");
         assert_eq!(SlValue::Array(Vec::new()).type_name(), "array");
         assert_eq!(SlValue::Map(BTreeMap::new()).type_name(), "map");
+        assert_eq!(SlValue::Null.type_name(), "null");
+    }
+
+    #[test]
+    fn slvalue_json_conversion_round_trips_nested_values() {
+        let json = serde_json::json!({
+            "hp": 3,
+            "ratio": 0.5,
+            "neg": -2,
+            "name": "Rin",
+            "alive": true,
+            "bag": [[1, 2.25], {"coins": [7]}, []],
+            "empty": {},
+            "missing": null
+        });
+        let value = slvalue_from_json(json.clone());
+        assert_eq!(slvalue_to_json(&value), json);
+        assert_eq!(slvalue_from_json(slvalue_to_json(&value)), value);
+
+        assert_eq!(slvalue_from_json(JsonValue::Null), SlValue::Null);
+        assert_eq!(serde_json::to_string(&SlValue::Null).unwrap(), "null");
+        assert_eq!(
+            serde_json::from_str::<SlValue>("[null]").unwrap(),
+            SlValue::Array(vec![SlValue::Null])
+        );
+        assert_eq!(slvalue_to_json(&SlValue::Number(f64::NAN)), JsonValue::Null);
+        assert_eq!(
+            slvalue_to_json(&SlValue::Number(1e300)),
+            serde_json::json!(1e300)
+        );
     }
 
     #[test]
@@ -376,6 +463,29 @@ mod tests {
         ));
         assert!(!is_type_compatible(&SlValue::Bool(true), &map_type));
 
+        let int_map_type = ScriptType::Map {
+            key_type: MapKeyType::Int,
+            value_type: Box::new(ScriptType::Primitive {
+                name: "int".to_string(),
+            }),
+        };
+        assert!(is_type_compatible(
+            &SlValue::Map(BTreeMap::from([
+                ("7".to_string(), SlValue::Number(1.0)),
+                ("-3".to_string(), SlValue::Number(2.0))
+            ])),
+            &int_map_type
+        ));
+        for bad_key in ["07", "+7", "7.0", "x", ""] {
+            assert!(!is_type_compatible(
+                &SlValue::Map(BTreeMap::from([(
+                    bad_key.to_string(),
+                    SlValue::Number(1.0)
+                )])),
+                &int_map_type
+            ));
+        }
+
         let enum_map_type = ScriptType::Map {
             key_type: MapKeyType::Enum {
                 type_name: "State".to_string(),
@@ -4,7 +4,7 @@ use std::path::Path;
 
 use clap::Parser;
 use sl_compiler::compile_project_bundle_from_xml_map;
-use sl_core::ScriptLangError;
+use sl_core::{ErrorCode, ScriptLangError};
 use walkdir::WalkDir;
 
 mod lint;
@@ -53,13 +53,13 @@ fn run(cli: Cli) -> Result<i32, ScriptLangError> {
 fn read_scripts_xml_from_dir(root: &Path) -> Result<BTreeMap<String, String>, ScriptLangError> {
     if !root.exists() {
         return Err(ScriptLangError::new(
-            "LINT_SOURCE_NOT_FOUND",
+            ErrorCode::LintSourceNotFound,
             format!("scripts-dir does not exist: {}", root.display()),
         ));
     }
     if !root.is_dir() {
         return Err(ScriptLangError::new(
-            "LINT_SOURCE_NOT_DIR",
+            ErrorCode::LintSourceNotDir,
             format!("scripts-dir is not a directory: {}", root.display()),
         ));
     }
@@ -79,17 +79,17 @@ fn read_scripts_xml_from_dir(root: &Path) -> Result<BTreeMap<String, String>, Sc
         }
         let relative = path
             .strip_prefix(root)
-            .map_err(|error| ScriptLangError::new("LINT_SOURCE_SCAN", error.to_string()))?
+            .map_err(|error| ScriptLangError::new(ErrorCode::LintSourceScan, error.to_string()))?
             .to_string_lossy()
             .replace('\\', "/");
         let content = std::fs::read_to_string(path)
-            .map_err(|error| ScriptLangError::new("LINT_SOURCE_READ", error.to_string()))?;
+            .map_err(|error| ScriptLangError::new(ErrorCode::LintSourceRead, error.to_string()))?;
         scripts.insert(relative, content);
     }
 
     if scripts.is_empty() {
         return Err(ScriptLangError::new(
-            "LINT_SOURCE_EMPTY",
+            ErrorCode::LintSourceEmpty,
             format!("No .xml files under {}", root.display()),
         ));
     }
@@ -149,6 +149,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn lint_flags_unused_script_params_but_not_ref_params() {
+        let scripts = std::collections::BTreeMap::from([(
+            "main.xml".to_string(),
+            r#"<module name="main" export="script:main">
+<script name="main">
+  <temp name="score" type="int">0</temp>
+  <call script="@main.award" args="1, 'x', ref:score"/>
+  <text>${score}</text>
+</script>
+<script name="award" kind="call" args="int:hp,string:name,ref:int:out">
+  <text>hp=${hp}</text>
+</script>
+</module>"#
+                .to_string(),
+        )]);
+        let bundle = compile_project_bundle_from_xml_map(&scripts).expect("bundle should compile");
+        let report = crate::lint::run_lint(&scripts, &bundle, "main.main");
+        let unused = report
+            .diagnostics
+            .iter()
+            .filter(|item| item.code == "unused-param")
+            .map(|item| item.message.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(unused, vec!["Param \"name\" is never read.".to_string()]);
+    }
+
     #[test]
     fn lint_does_not_flag_root_const_as_unused_when_read_in_submodule_function_path() {
         let dir = example_dir("48-sub-module-complex");
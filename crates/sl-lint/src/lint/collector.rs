@@ -48,6 +48,16 @@ pub(crate) struct UnreachableNode {
     pub(crate) span: SourceSpan,
 }
 
+/// `<choice>`/`<input>` placed after an unconditional terminator in the same group.
+#[derive(Debug, Clone)]
+pub(crate) struct UnreachableBoundary {
+    pub(crate) file: String,
+    pub(crate) script_name: String,
+    pub(crate) span: SourceSpan,
+    pub(crate) element: &'static str,
+    pub(crate) terminator_id: String,
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct ScriptLocals {
     pub(crate) params: Vec<NamedDecl>,
@@ -84,6 +94,9 @@ pub(crate) struct LintContext {
     pub(crate) alias_symbol_targets: HashSet<String>,
     pub(crate) short_name_candidates: Vec<ShortNameCandidate>,
     pub(crate) unreachable_nodes: Vec<UnreachableNode>,
+    pub(crate) unreachable_boundaries: Vec<UnreachableBoundary>,
+    pub(crate) unreachable_fall_overs: Vec<UnreachableNode>,
+    pub(crate) once_inside_loops: Vec<UnreachableNode>,
 }
 
 pub(crate) fn collect_context(
@@ -403,9 +416,11 @@ fn collect_script_usage(bundle: &CompileProjectBundleResult, context: &mut LintC
         let module_name = script.module_name.clone().unwrap_or_default();
         let file = script.script_path.clone();
         let mut locals = ScriptLocals {
+            // Ref params carry results back to the caller, so an unread one is not a leftover.
             params: script
                 .params
                 .iter()
+                .filter(|param| !param.is_ref)
                 .map(|param| NamedDecl {
                     name: param.name.clone(),
                     file: file.clone(),
@@ -423,14 +438,31 @@ fn collect_script_usage(bundle: &CompileProjectBundleResult, context: &mut LintC
         };
 
         for group in script.groups.values() {
-            let mut terminated = false;
+            let mut terminator: Option<&str> = None;
             for node in &group.nodes {
-                if terminated {
-                    context.unreachable_nodes.push(UnreachableNode {
-                        file: file.clone(),
-                        script_name: script_name.clone(),
-                        span: node_span(node).clone(),
-                    });
+                let boundary_element = match node {
+                    ScriptNode::Choice { .. } => Some("choice"),
+                    ScriptNode::Input { .. } => Some("input"),
+                    _ => None,
+                };
+                match (terminator, boundary_element) {
+                    (Some(terminator_id), Some(element)) => {
+                        context.unreachable_boundaries.push(UnreachableBoundary {
+                            file: file.clone(),
+                            script_name: script_name.clone(),
+                            span: node_span(node).clone(),
+                            element,
+                            terminator_id: terminator_id.to_string(),
+                        });
+                    }
+                    (Some(_), None) => {
+                        context.unreachable_nodes.push(UnreachableNode {
+                            file: file.clone(),
+                            script_name: script_name.clone(),
+                            span: node_span(node).clone(),
+                        });
+                    }
+                    (None, _) => {}
                 }
 
                 match node {
@@ -480,7 +512,9 @@ fn collect_script_usage(bundle: &CompileProjectBundleResult, context: &mut LintC
                             );
                         }
                     }
-                    ScriptNode::Return { .. } | ScriptNode::End { .. } => {}
+                    ScriptNode::Return { .. }
+                    | ScriptNode::End { .. }
+                    | ScriptNode::Checkpoint { .. } => {}
                     ScriptNode::If {
                         when_expr,
                         location,
@@ -528,11 +562,29 @@ fn collect_script_usage(bundle: &CompileProjectBundleResult, context: &mut LintC
                             );
                         }
                     }
+                    ScriptNode::Text {
+                        expr: Some(expr),
+                        location,
+                        ..
+                    } => {
+                        collect_expression_usage(
+                            expr,
+                            &usage,
+                            location,
+                            context,
+                            Some(&mut locals),
+                        );
+                    }
                     ScriptNode::Text {
                         value, location, ..
                     }
                     | ScriptNode::Debug {
                         value, location, ..
+                    }
+                    | ScriptNode::Fail {
+                        message: value,
+                        location,
+                        ..
                     } => {
                         for expr in extract_template_expressions(value) {
                             collect_expression_usage(
@@ -562,6 +614,7 @@ fn collect_script_usage(bundle: &CompileProjectBundleResult, context: &mut LintC
                         for entry in entries {
                             collect_choice_entry_usage(entry, &usage, context, &mut locals);
                         }
+                        collect_unreachable_fall_overs(entries, &file, script_name, context);
                     }
                     ScriptNode::Input {
                         prompt_text,
@@ -587,19 +640,83 @@ fn collect_script_usage(bundle: &CompileProjectBundleResult, context: &mut LintC
                     node,
                     ScriptNode::Goto { .. }
                         | ScriptNode::End { .. }
+                        | ScriptNode::Fail { .. }
                         | ScriptNode::Return { .. }
                         | ScriptNode::Break { .. }
                         | ScriptNode::Continue { .. }
-                ) {
-                    terminated = true;
+                ) && terminator.is_none()
+                {
+                    terminator = Some(node.id());
                 }
             }
         }
 
+        collect_once_inside_loops(script, &file, script_name, context);
         context.script_locals.insert(script_name.clone(), locals);
     }
 }
 
+/// A `once` text or option lexically inside a `<while>` body (including `<for>`, which expands to
+/// one) only shows on the first iteration, which is usually not what the author meant.
+fn collect_once_inside_loops(
+    script: &ScriptIr,
+    file: &str,
+    script_name: &str,
+    context: &mut LintContext,
+) {
+    let loop_bodies = script
+        .groups
+        .values()
+        .flat_map(|group| &group.nodes)
+        .filter_map(|node| match node {
+            ScriptNode::While { body_group_id, .. } => Some(body_group_id.as_str()),
+            _ => None,
+        })
+        .collect::<HashSet<_>>();
+    let inside_loop = |group_id: &str| {
+        let mut current = Some(group_id);
+        while let Some(id) = current {
+            if loop_bodies.contains(id) {
+                return true;
+            }
+            current = script
+                .groups
+                .get(id)
+                .and_then(|group| group.parent_group_id.as_deref());
+        }
+        false
+    };
+    for group in script.groups.values() {
+        if !inside_loop(&group.group_id) {
+            continue;
+        }
+        for node in &group.nodes {
+            let spans = match node {
+                ScriptNode::Text {
+                    once: true,
+                    location,
+                    ..
+                } => vec![location],
+                ScriptNode::Choice { entries, .. } => entries
+                    .iter()
+                    .filter_map(|entry| match entry {
+                        ChoiceEntry::Static { option } if option.once => Some(&option.location),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            };
+            for span in spans {
+                context.once_inside_loops.push(UnreachableNode {
+                    file: file.to_string(),
+                    script_name: script_name.to_string(),
+                    span: span.clone(),
+                });
+            }
+        }
+    }
+}
+
 struct UsageScope<'a> {
     module_name: &'a str,
     file: &'a str,
@@ -615,7 +732,7 @@ fn collect_choice_entry_usage(
 ) {
     match entry {
         ChoiceEntry::Static { option } => {
-            if let Some(expr) = &option.when_expr {
+            for expr in option.when_expr.iter().chain(&option.enabled_when_expr) {
                 collect_expression_usage(expr, usage, &option.location, context, Some(locals));
             }
             for expr in extract_template_expressions(&option.text) {
@@ -652,6 +769,42 @@ fn collect_choice_entry_usage(
     }
 }
 
+/// A fall-over option only shows when no regular option is visible, so an unconditional regular
+/// option (no `when`, `once` or `max_select`, not pinned) makes every fall-over option in the same
+/// choice dead.
+fn collect_unreachable_fall_overs(
+    entries: &[ChoiceEntry],
+    file: &str,
+    script_name: &str,
+    context: &mut LintContext,
+) {
+    let has_unconditional_regular = entries.iter().any(|entry| {
+        matches!(
+            entry,
+            ChoiceEntry::Static { option }
+                if !option.fall_over
+                    && !option.pinned
+                    && !option.once
+                    && option.max_select.is_none()
+                    && option.when_expr.is_none()
+        )
+    });
+    if !has_unconditional_regular {
+        return;
+    }
+    for entry in entries {
+        if let ChoiceEntry::Static { option } = entry {
+            if option.fall_over {
+                context.unreachable_fall_overs.push(UnreachableNode {
+                    file: file.to_string(),
+                    script_name: script_name.to_string(),
+                    span: option.location.clone(),
+                });
+            }
+        }
+    }
+}
+
 fn collect_initializer_usage(bundle: &CompileProjectBundleResult, context: &mut LintContext) {
     for decl in bundle.module_var_declarations.values() {
         if let Some(expr) = &decl.initial_value_expr {
@@ -1174,13 +1327,16 @@ fn node_span(node: &ScriptNode) -> &SourceSpan {
         | ScriptNode::Call { location, .. }
         | ScriptNode::Goto { location, .. }
         | ScriptNode::End { location, .. }
+        | ScriptNode::Checkpoint { location, .. }
+        | ScriptNode::Fail { location, .. }
         | ScriptNode::Return { location, .. } => location,
     }
 }
 
 fn template_expr_regex() -> &'static Regex {
     static REGEX: std::sync::OnceLock<Regex> = std::sync::OnceLock::new();
-    REGEX.get_or_init(|| Regex::new(r"\$\{([^}]*)\}").expect("template regex should compile"))
+    REGEX
+        .get_or_init(|| Regex::new(r"\\\$\{|\$\{([^}]*)\}").expect("template regex should compile"))
 }
 
 fn call_name_regex() -> &'static Regex {
@@ -1374,6 +1530,114 @@ mod tests {
         assert!(context.module_consts.contains_key("m.values"));
     }
 
+    #[test]
+    fn collect_context_flags_fall_over_shadowed_by_unconditional_option() {
+        let xml = BTreeMap::from([(
+            "main.xml".to_string(),
+            r#"
+<module name="main" export="script:main">
+  <script name="main">
+    <temp name="hp" type="int">1</temp>
+    <choice text="Always">
+      <option text="Go"><end/></option>
+      <option text="Fallback" fall_over="true"><end/></option>
+    </choice>
+    <choice text="Conditional">
+      <option text="Heal" when="hp LT 5"><end/></option>
+      <option text="Once" once="true"><end/></option>
+      <option text="Twice" max_select="2"><end/></option>
+      <option text="Fallback" fall_over="true"><end/></option>
+    </choice>
+  </script>
+</module>
+"#
+            .to_string(),
+        )]);
+        let bundle = sl_compiler::compile_project_bundle_from_xml_map(&xml)
+            .expect("bundle should compile for lint test");
+        let context = collect_context(&xml, &bundle, "main.main");
+        assert_eq!(context.unreachable_fall_overs.len(), 1);
+        assert_eq!(context.unreachable_fall_overs[0].script_name, "main.main");
+        assert_eq!(context.unreachable_fall_overs[0].span.start.line, 7);
+    }
+
+    #[test]
+    fn collect_context_flags_choice_after_unconditional_return() {
+        let xml = BTreeMap::from([(
+            "main.xml".to_string(),
+            r#"
+<module name="main" export="script:main,helper">
+  <script name="main">
+    <call script="@helper"/>
+    <end/>
+  </script>
+  <script name="helper" kind="call">
+    <text>before</text>
+    <return/>
+    <text>dead text</text>
+    <choice text="Never">
+      <option text="A"><return/></option>
+    </choice>
+  </script>
+</module>
+"#
+            .to_string(),
+        )]);
+        let bundle = sl_compiler::compile_project_bundle_from_xml_map(&xml)
+            .expect("bundle should compile for lint test");
+        let context = collect_context(&xml, &bundle, "main.main");
+        assert_eq!(context.unreachable_nodes.len(), 1);
+        assert_eq!(context.unreachable_boundaries.len(), 1);
+        let boundary = &context.unreachable_boundaries[0];
+        assert_eq!(boundary.script_name, "main.helper");
+        assert_eq!(boundary.element, "choice");
+        assert_eq!(boundary.span.start.line, 11);
+        let helper = &bundle.scripts["main.helper"];
+        let return_node = &helper.groups[&helper.root_group_id].nodes[1];
+        assert_eq!(boundary.terminator_id, return_node.id());
+    }
+
+    #[test]
+    fn collect_context_flags_once_nodes_inside_while_bodies() {
+        let xml = BTreeMap::from([(
+            "main.xml".to_string(),
+            r#"
+<module name="main" export="script:main">
+  <script name="main">
+    <temp name="i" type="int">0</temp>
+    <text once="true">Welcome</text>
+    <while when="i LT 3">
+      <code>i = i + 1;</code>
+      <if when="i == 2">
+        <text once="true">Halfway</text>
+      </if>
+      <choice text="Pick">
+        <option text="Tip" once="true"><text>tip</text></option>
+        <option text="Next"><text>next</text></option>
+      </choice>
+    </while>
+    <end/>
+  </script>
+</module>
+"#
+            .to_string(),
+        )]);
+        let bundle = sl_compiler::compile_project_bundle_from_xml_map(&xml)
+            .expect("bundle should compile for lint test");
+        let context = collect_context(&xml, &bundle, "main.main");
+        let mut lines = context
+            .once_inside_loops
+            .iter()
+            .map(|node| node.span.start.line)
+            .collect::<Vec<_>>();
+        lines.sort();
+        assert_eq!(lines, vec![9, 12]);
+        assert!(context
+            .once_inside_loops
+            .iter()
+            .all(|node| node.script_name == "main.main"));
+    }
+
     #[test]
     fn mark_value_use_resolves_runtime_namespace_symbol() {
         let mut context = LintContext::default();
@@ -14,6 +14,9 @@ pub(crate) fn run_rules(context: &LintContext) -> Vec<LintDiagnostic> {
     collect_prefer_short_name(context, &mut diagnostics);
     collect_unused_import(context, &mut diagnostics);
     collect_unreachable_node(context, &mut diagnostics);
+    collect_choice_unreachable(context, &mut diagnostics);
+    collect_choice_fall_over_unreachable(context, &mut diagnostics);
+    collect_once_inside_loop(context, &mut diagnostics);
     diagnostics
 }
 
@@ -223,11 +226,60 @@ fn collect_unreachable_node(context: &LintContext, diagnostics: &mut Vec<LintDia
     }
 }
 
+fn collect_choice_unreachable(context: &LintContext, diagnostics: &mut Vec<LintDiagnostic>) {
+    for unreachable in &context.unreachable_boundaries {
+        diagnostics.push(LintDiagnostic::warning(
+            "choice-unreachable",
+            unreachable.file.clone(),
+            Some(unreachable.span.clone()),
+            format!(
+                "<{}> in script \"{}\" is never reached because node \"{}\" ends the flow before it.",
+                unreachable.element, unreachable.script_name, unreachable.terminator_id
+            ),
+            Some("Move the terminator after it or remove the dead boundary.".to_string()),
+        ));
+    }
+}
+
+fn collect_choice_fall_over_unreachable(
+    context: &LintContext,
+    diagnostics: &mut Vec<LintDiagnostic>,
+) {
+    for unreachable in &context.unreachable_fall_overs {
+        diagnostics.push(LintDiagnostic::warning(
+            "choice-fallover-unreachable",
+            unreachable.file.clone(),
+            Some(unreachable.span.clone()),
+            format!(
+                "fall_over option in script \"{}\" can never show because the choice has an unconditional regular option.",
+                unreachable.script_name
+            ),
+            Some("Add a when condition or once to the regular options, or remove the fall_over option.".to_string()),
+        ));
+    }
+}
+
+fn collect_once_inside_loop(context: &LintContext, diagnostics: &mut Vec<LintDiagnostic>) {
+    for node in &context.once_inside_loops {
+        diagnostics.push(LintDiagnostic::warning(
+            "once-inside-loop",
+            node.file.clone(),
+            Some(node.span.clone()),
+            format!(
+                "once node in script \"{}\" is inside a <while> and only shows on the first iteration.",
+                node.script_name
+            ),
+            Some("Remove once if it should show every iteration, or move it before the loop.".to_string()),
+        ));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::lint::collector::{
-        ImportDecl, ModuleDecl, NamedDecl, ScriptLocals, ShortNameCandidate, UnreachableNode,
+        ImportDecl, ModuleDecl, NamedDecl, ScriptLocals, ShortNameCandidate, UnreachableBoundary,
+        UnreachableNode,
     };
     use sl_core::SourceSpan;
     use std::collections::HashSet;
@@ -433,6 +485,51 @@ mod tests {
         assert!(result.iter().any(|d| d.code == "unreachable-node"));
     }
 
+    #[test]
+    fn run_rules_emits_choice_unreachable_with_terminator_id() {
+        let mut ctx = base_context();
+        ctx.unreachable_boundaries.push(UnreachableBoundary {
+            file: "main.xml".to_string(),
+            script_name: "main.helper".to_string(),
+            span: SourceSpan::synthetic(),
+            element: "input",
+            terminator_id: "n3".to_string(),
+        });
+        let result = run_rules(&ctx);
+        let diagnostic = result
+            .iter()
+            .find(|d| d.code == "choice-unreachable")
+            .expect("choice-unreachable should be reported");
+        assert!(diagnostic.message.contains("<input>"));
+        assert!(diagnostic.message.contains("\"n3\""));
+    }
+
+    #[test]
+    fn run_rules_emits_choice_fallover_unreachable() {
+        let mut ctx = base_context();
+        ctx.unreachable_fall_overs.push(UnreachableNode {
+            file: "main.xml".to_string(),
+            script_name: "main.main".to_string(),
+            span: SourceSpan::synthetic(),
+        });
+        let result = run_rules(&ctx);
+        assert!(result
+            .iter()
+            .any(|d| d.code == "choice-fallover-unreachable"));
+    }
+
+    #[test]
+    fn run_rules_emits_once_inside_loop() {
+        let mut ctx = base_context();
+        ctx.once_inside_loops.push(UnreachableNode {
+            file: "main.xml".to_string(),
+            script_name: "main.main".to_string(),
+            span: SourceSpan::synthetic(),
+        });
+        let result = run_rules(&ctx);
+        assert!(result.iter().any(|d| d.code == "once-inside-loop"));
+    }
+
     #[test]
     fn run_rules_handles_empty_context() {
         let ctx = base_context();
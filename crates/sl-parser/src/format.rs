@@ -0,0 +1,251 @@
+use sl_core::ScriptLangError;
+
+use crate::xml::{
+    parse_alias_directives, parse_import_directives, parse_xml_document, ImportDirective,
+    XmlDocument, XmlElementNode, XmlNode,
+};
+
+const INDENT: &str = "  ";
+
+/// Attributes printed first, in this order; any other attribute follows alphabetically.
+const ATTRIBUTE_ORDER: [&str; 28] = [
+    "name",
+    "kind",
+    "tags",
+    "type",
+    "export",
+    "var",
+    "script",
+    "args",
+    "text",
+    "when",
+    "enabled_when",
+    "max",
+    "array",
+    "item",
+    "index",
+    "temps",
+    "condition",
+    "iteration",
+    "tag",
+    "once",
+    "max_select",
+    "scope",
+    "fall_over",
+    "pinned",
+    "scene",
+    "format",
+    "max_length",
+    "timeout_default",
+];
+
+/// Re-emit a `.xml` source file in canonical form.
+///
+/// Preserved: `import`/`alias` directives (re-emitted at the top), the element tree, every
+/// attribute value, and inline text content (only leading/trailing whitespace is trimmed, which
+/// the compiler ignores). Lost: other comments, XML declarations, original attribute order and
+/// quoting, and whitespace between elements. Formatting canonical output is a no-op.
+pub fn format_xml_source(source: &str) -> Result<String, ScriptLangError> {
+    let document = parse_xml_document(source)?;
+    let mut out = String::new();
+    for directive in parse_import_directives(source) {
+        match directive {
+            ImportDirective::File {
+                module_name,
+                from_path,
+            } => out.push_str(&format!(
+                "<!-- import {} from {} -->\n",
+                module_name, from_path
+            )),
+            ImportDirective::Directory {
+                module_names,
+                from_path,
+            } => out.push_str(&format!(
+                "<!-- import {{ {} }} from {} -->\n",
+                module_names.join(", "),
+                from_path
+            )),
+        }
+    }
+    for directive in parse_alias_directives(source) {
+        let default_alias = directive
+            .target_qualified_name
+            .rsplit('.')
+            .next()
+            .unwrap_or_default();
+        if directive.alias_name == default_alias {
+            out.push_str(&format!(
+                "<!-- alias {} -->\n",
+                directive.target_qualified_name
+            ));
+        } else {
+            out.push_str(&format!(
+                "<!-- alias {} as {} -->\n",
+                directive.target_qualified_name, directive.alias_name
+            ));
+        }
+    }
+    out.push_str(&format_xml_document(&document));
+    Ok(out)
+}
+
+/// Render a parsed document as canonical, two-space-indented XML ending with a newline.
+pub fn format_xml_document(document: &XmlDocument) -> String {
+    let mut out = String::new();
+    write_element(&mut out, &document.root, 0);
+    out
+}
+
+fn write_element(out: &mut String, node: &XmlElementNode, depth: usize) {
+    let indent = INDENT.repeat(depth);
+    out.push_str(&indent);
+    out.push('<');
+    out.push_str(&node.name);
+    for (name, value) in ordered_attributes(node) {
+        out.push_str(&format!(" {}=\"{}\"", name, escape_attribute(value)));
+    }
+
+    let has_elements = node
+        .children
+        .iter()
+        .any(|child| matches!(child, XmlNode::Element(_)));
+    let texts = node
+        .children
+        .iter()
+        .filter_map(|child| match child {
+            XmlNode::Text(text) if !text.value.trim().is_empty() => Some(text.value.trim()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    if !has_elements {
+        if texts.is_empty() {
+            out.push_str("/>\n");
+        } else {
+            out.push('>');
+            out.push_str(&escape_text(&texts.join("\n")));
+            out.push_str(&format!("</{}>\n", node.name));
+        }
+        return;
+    }
+
+    out.push_str(">\n");
+    for child in &node.children {
+        match child {
+            XmlNode::Element(element) => write_element(out, element, depth + 1),
+            XmlNode::Text(text) if !text.value.trim().is_empty() => {
+                out.push_str(&INDENT.repeat(depth + 1));
+                out.push_str(&escape_text(text.value.trim()));
+                out.push('\n');
+            }
+            XmlNode::Text(_) => {}
+        }
+    }
+    out.push_str(&format!("{}</{}>\n", indent, node.name));
+}
+
+fn ordered_attributes(node: &XmlElementNode) -> Vec<(&str, &str)> {
+    let mut attributes = ATTRIBUTE_ORDER
+        .iter()
+        .filter_map(|name| {
+            node.attributes
+                .get_key_value(*name)
+                .map(|(name, value)| (name.as_str(), value.as_str()))
+        })
+        .collect::<Vec<_>>();
+    attributes.extend(
+        node.attributes
+            .iter()
+            .filter(|(name, _)| !ATTRIBUTE_ORDER.contains(&name.as_str()))
+            .map(|(name, value)| (name.as_str(), value.as_str())),
+    );
+    attributes
+}
+
+fn escape_attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('"', "&quot;")
+}
+
+fn escape_text(value: &str) -> String {
+    value.replace('&', "&amp;").replace('<', "&lt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CANONICAL: &str = r#"<!-- import shared from shared.xml -->
+<!-- import { a, b } from lib/ -->
+<!-- alias shared.helper as help -->
+<module name="main" export="script:main">
+  <script name="main">
+    <temp name="hp" type="int">3</temp>
+    <text tag="intro" once="true">Hello &amp; welcome</text>
+    <if when="hp LT 5">
+      <text>low</text>
+      <else>
+        <text>high</text>
+      </else>
+    </if>
+    <while when="hp > 0">
+      <code>hp = hp - 1;
+      let note = "a&lt;b";</code>
+    </while>
+    <choice text="Pick" timeout_default="0">
+      <option text="A" when="hp == 0" fall_over="false">
+        <call script="@shared.helper" args="1"/>
+      </option>
+    </choice>
+    <input var="name" text="Name" max_length="8"/>
+    <end/>
+  </script>
+</module>
+"#;
+
+    #[test]
+    fn format_xml_source_is_idempotent_on_canonical_input() {
+        let formatted = format_xml_source(CANONICAL).expect("format should pass");
+        assert_eq!(formatted, CANONICAL);
+        assert_eq!(
+            format_xml_source(&formatted).expect("format should pass"),
+            formatted
+        );
+    }
+
+    #[test]
+    fn format_xml_source_normalizes_layout_and_attribute_order() {
+        let messy = r#"<?xml version="1.0"?>
+<!-- alias shared.helper -->
+<!-- a plain comment is dropped -->
+<script name="main"><choice text='Pick' timeout_default="0"><option fall_over="true" text="Go">   <text>
+  go
+</text></option></choice>
+<temp type="string" name="s">  "x"  </temp></script>"#;
+
+        let formatted = format_xml_source(messy).expect("format should pass");
+        assert_eq!(
+            formatted,
+            r#"<!-- alias shared.helper -->
+<script name="main">
+  <choice text="Pick" timeout_default="0">
+    <option text="Go" fall_over="true">
+      <text>go</text>
+    </option>
+  </choice>
+  <temp name="s" type="string">"x"</temp>
+</script>
+"#
+        );
+        assert_eq!(
+            format_xml_source(&formatted).expect("format should pass"),
+            formatted
+        );
+        assert_eq!(
+            format_xml_source("<script>").expect_err("invalid xml").code,
+            "XML_PARSE_ERROR"
+        );
+    }
+}
@@ -1,3 +1,5 @@
+pub mod format;
 pub mod xml;
 
+pub use format::*;
 pub use xml::*;
@@ -3,7 +3,7 @@ use std::sync::OnceLock;
 
 use regex::Regex;
 use roxmltree::{Document, Node, NodeType};
-use sl_core::{ScriptLangError, SourceLocation, SourceSpan};
+use sl_core::{ErrorCode, ScriptLangError, SourceLocation, SourceSpan};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct XmlDocument {
@@ -153,7 +153,7 @@ pub fn reject_non_import_dependency_directives(source: &str) -> Result<(), Scrip
             .trim();
         (keyword == "import").then_some(()).ok_or_else(|| {
             ScriptLangError::new(
-                "IMPORT_DIRECTIVE_UNSUPPORTED",
+                ErrorCode::ImportDirectiveUnsupported,
                 format!(
                     "Unsupported dependency directive \"{}\". Only `import` directives are allowed.",
                     keyword
@@ -214,8 +214,21 @@ fn non_import_dependency_directive_regex() -> &'static Regex {
 }
 
 pub fn parse_xml_document(source: &str) -> Result<XmlDocument, ScriptLangError> {
-    let document = Document::parse(source)
-        .map_err(|error| ScriptLangError::new("XML_PARSE_ERROR", error.to_string()))?;
+    let document = Document::parse(source).map_err(|error| {
+        let pos = error.pos();
+        let location = SourceLocation {
+            line: pos.row as usize,
+            column: pos.col as usize,
+        };
+        ScriptLangError::with_span(
+            ErrorCode::XmlParseError,
+            error.to_string(),
+            SourceSpan {
+                start: location.clone(),
+                end: location,
+            },
+        )
+    })?;
 
     let root = document.root_element();
 
@@ -448,6 +461,11 @@ mod tests {
     fn parse_xml_document_returns_parse_error_for_invalid_xml() {
         let error = parse_xml_document("<script>").expect_err("invalid xml should fail");
         assert_eq!(error.code, "XML_PARSE_ERROR");
+
+        let error = parse_xml_document("<script>\n  <text>hi</txt>\n</script>")
+            .expect_err("mismatched tag should fail");
+        let span = error.span.expect("parse error should carry a span");
+        assert_eq!((span.start.line, span.start.column), (2, 11));
     }
 
     #[test]
@@ -1,27 +1,28 @@
 mod rng;
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::BTreeMap;
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeSet, HashMap, VecDeque};
 use std::rc::Rc;
 use std::sync::{Arc, OnceLock};
 
+use crate::helpers::json_value::{parse_json_input, slvalue_to_json_text};
 use crate::helpers::rhai_bridge::{
     dynamic_to_slvalue, slvalue_to_dynamic, slvalue_to_dynamic_with_type, slvalue_to_rhai_literal,
     slvalue_to_text,
 };
 use crate::helpers::value_path::{assign_nested_path, parse_ref_path};
 use regex::Regex;
-use rhai::{Dynamic, Engine, EvalAltResult, ImmutableString, Position, Scope, INT};
+use rhai::{Dynamic, Engine, EvalAltResult, ImmutableString, Position, Scope, FLOAT, INT};
 use rng::next_random_bounded;
 #[cfg(test)]
 use rng::{next_random_bounded_with, next_random_u32};
 use sl_core::{
     default_value_from_type, is_type_compatible, module_namespace_symbol, rhai_function_symbol,
-    AccessLevel, ChoiceEntry, ChoiceItem, ContinuationFrame, ContinueTarget, EngineOutput,
-    ModuleConstDecl, ModuleVarDecl, PendingDynamicChoiceBinding, ScriptIr, ScriptKind,
-    ScriptLangError, ScriptNode, ScriptTarget, ScriptType, SlValue, Snapshot, SnapshotCompletion,
-    SnapshotFrame,
+    AccessLevel, ChoiceEntry, ChoiceItem, ContinuationFrame, ContinueTarget, DebugState,
+    EngineOutput, ErrorCode, InputFormat, ModuleConstDecl, ModuleVarDecl,
+    PendingDynamicChoiceBinding, ScriptIr, ScriptKind, ScriptLangError, ScriptNode, ScriptTarget,
+    ScriptType, SlValue, Snapshot, SnapshotCompletion, SnapshotFrame,
 };
 
 mod boundary;
@@ -36,9 +37,12 @@ mod snapshot;
 mod step;
 
 pub use lifecycle::{
-    EmptyHostFunctionRegistry, HostFunctionRegistry, RandomStateView, ScriptLangEngine,
-    ScriptLangEngineOptions, DEFAULT_COMPILER_VERSION, SNAPSHOT_SCHEMA,
+    ChoiceIndexPolicy, EmptyHostFunctionRegistry, EngineBehavior, EngineLimits,
+    FnHostFunctionRegistry, HostFunction, HostFunctionRegistry, IntPrecisionPolicy,
+    RandomStateView, RhaiResourceLimits, ScriptLangEngine, ScriptLangEngineOptions, TextFilter,
+    DEFAULT_COMPILER_VERSION, SNAPSHOT_SCHEMA,
 };
+pub use snapshot::{inspect_snapshot, SnapshotReport};
 
 #[cfg(test)]
 pub(super) mod runtime_test_support {
@@ -131,6 +135,28 @@ pub(super) mod runtime_test_support {
         )
     }
 
+    /// The compiler rejects undeclared or wrongly typed `<input>` targets; rewrite every compiled
+    /// input node to keep the runtime guards covered for hand-built bundles.
+    pub(super) fn rewrite_input_nodes(
+        engine: &mut ScriptLangEngine,
+        target: &str,
+        input_format: InputFormat,
+    ) {
+        for script in engine.scripts.values_mut() {
+            for group in script.groups.values_mut() {
+                for node in &mut group.nodes {
+                    if let ScriptNode::Input {
+                        target_var, format, ..
+                    } = node
+                    {
+                        *target_var = target.to_string();
+                        *format = input_format;
+                    }
+                }
+            }
+        }
+    }
+
     pub(super) fn engine_from_sources(files: BTreeMap<String, String>) -> ScriptLangEngine {
         let compiled = compile_project_from_sources(files);
         ScriptLangEngine::new(ScriptLangEngineOptions {
@@ -140,11 +166,8 @@ pub(super) mod runtime_test_support {
             module_var_init_order: compiled.module_var_init_order,
             module_const_declarations: compiled.module_const_declarations,
             module_const_init_order: compiled.module_const_init_order,
-            host_functions: None,
             random_seed: Some(1),
-            random_sequence: None,
-            random_sequence_index: None,
-            compiler_version: None,
+            ..Default::default()
         })
         .expect("engine should build")
     }
@@ -169,11 +192,8 @@ pub(super) mod runtime_test_support {
             module_var_init_order: compiled.module_var_init_order,
             module_const_declarations: compiled.module_const_declarations,
             module_const_init_order: compiled.module_const_init_order,
-            host_functions: None,
             random_seed: Some(1),
-            random_sequence: None,
-            random_sequence_index: None,
-            compiler_version: None,
+            ..Default::default()
         })
         .expect("engine should build")
     }
@@ -216,11 +236,28 @@ pub(super) mod runtime_test_support {
         }
     }
 
+    /// Text of a `Text` output, `None` for any other output.
+    pub(super) fn output_text(output: EngineOutput) -> Option<String> {
+        match output {
+            EngineOutput::Text { text, .. } => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Items of a `Choices` output, `None` for any other output.
+    pub(super) fn choice_items(output: EngineOutput) -> Option<Vec<ChoiceItem>> {
+        match output {
+            EngineOutput::Choices { items, .. } => Some(items),
+            _ => None,
+        }
+    }
+
     pub(super) fn drive_engine_to_end(engine: &mut ScriptLangEngine) {
         for _ in 0..5_000usize {
             match engine.next_output().expect("next should pass") {
                 EngineOutput::Text { .. } => {}
                 EngineOutput::Debug { .. } => {}
+                EngineOutput::Checkpoint { .. } => {}
                 EngineOutput::Choices { items, .. } => {
                     let index = items.first().map(|item| item.index).unwrap_or(0);
                     engine.choose(index).expect("choose should pass");
@@ -1,11 +1,32 @@
-use super::lifecycle::{CompletionKind, PendingBoundary};
+use super::lifecycle::{parse_bool_token, CompletionKind, PendingBoundary};
 use super::*;
 
 impl ScriptLangEngine {
-    pub fn choose(&mut self, index: usize) -> Result<(), ScriptLangError> {
+    /// Choose the offered option whose `ChoiceItem.id` is `id`, regardless of its display index.
+    pub fn choose_id(&mut self, id: &str) -> Result<ChoiceItem, ScriptLangError> {
+        let Some(PendingBoundary::Choice { options, .. }) = &self.pending_boundary else {
+            return Err(ScriptLangError::new(
+                ErrorCode::EngineNoPendingChoice,
+                "No pending choice is available.",
+            ));
+        };
+        let Some(index) = options.iter().position(|option| option.item.id == id) else {
+            return Err(ScriptLangError::new(
+                ErrorCode::EngineChoiceNotFound,
+                format!("Choice option \"{}\" is not offered.", id),
+            ));
+        };
+        self.choose(index)
+    }
+
+    /// Choose the offered option at `index` and return the option taken, which differs from
+    /// `index` when `ChoiceIndexPolicy::Clamp` remapped it.
+    pub fn choose(&mut self, index: usize) -> Result<ChoiceItem, ScriptLangError> {
+        let index = self.resolve_choice_index(index)?;
+        let checkpoint = self.history_checkpoint();
         let Some(pending) = self.pending_boundary.take() else {
             return Err(ScriptLangError::new(
-                "ENGINE_NO_PENDING_CHOICE",
+                ErrorCode::EngineNoPendingChoice,
                 "No pending choice is available.",
             ));
         };
@@ -20,25 +41,12 @@ impl ScriptLangEngine {
             other => {
                 self.pending_boundary = Some(other);
                 return Err(ScriptLangError::new(
-                    "ENGINE_NO_PENDING_CHOICE",
+                    ErrorCode::EngineNoPendingChoice,
                     "No pending choice is available.",
                 ));
             }
         };
 
-        if index >= options.len() {
-            self.pending_boundary = Some(PendingBoundary::Choice {
-                frame_id,
-                node_id,
-                options,
-                prompt_text,
-            });
-            return Err(ScriptLangError::new(
-                "ENGINE_CHOICE_INDEX",
-                format!("Choice index \"{}\" is out of range.", index),
-            ));
-        }
-
         let Some(frame_index) = self.find_frame_index(frame_id) else {
             self.pending_boundary = Some(PendingBoundary::Choice {
                 frame_id,
@@ -47,7 +55,7 @@ impl ScriptLangEngine {
                 prompt_text,
             });
             return Err(ScriptLangError::new(
-                "ENGINE_CHOICE_FRAME_MISSING",
+                ErrorCode::EngineChoiceFrameMissing,
                 "Pending choice frame is missing.",
             ));
         };
@@ -75,7 +83,7 @@ impl ScriptLangEngine {
                 prompt_text,
             });
             return Err(ScriptLangError::new(
-                "ENGINE_CHOICE_NODE_MISSING",
+                ErrorCode::EngineChoiceNodeMissing,
                 "Pending choice node is no longer valid.",
             ));
         };
@@ -86,6 +94,7 @@ impl ScriptLangEngine {
         }
 
         let item = &options[index];
+        let chosen_item = item.item.clone();
         let chosen_target = if let Some(binding) = item.dynamic_binding.clone() {
             ChosenTarget::Dynamic(binding)
         } else {
@@ -100,7 +109,7 @@ impl ScriptLangEngine {
                     prompt_text,
                 });
                 return Err(ScriptLangError::new(
-                    "ENGINE_CHOICE_NOT_FOUND",
+                    ErrorCode::EngineChoiceNotFound,
                     "Choice option no longer exists.",
                 ));
             };
@@ -133,7 +142,14 @@ impl ScriptLangEngine {
         match chosen_target {
             ChosenTarget::Static(option) => {
                 if option.once {
-                    self.mark_once_state(&script_name, &format!("option:{}", option.id));
+                    self.mark_scoped_once_state(
+                        &script_name,
+                        &format!("option:{}", option.id),
+                        option.global_once,
+                    );
+                }
+                if option.max_select.is_some() {
+                    self.record_selection(&script_name, &option.id);
                 }
             }
             ChosenTarget::Dynamic(binding) => {
@@ -155,13 +171,154 @@ impl ScriptLangEngine {
             }
         }
         self.waiting_choice = false;
-        Ok(())
+        self.last_choice = Some(chosen_item.clone());
+        self.push_history(checkpoint);
+        Ok(chosen_item)
+    }
+
+    /// The option taken by the most recent successful `choose`, which differs from the
+    /// requested index when `ChoiceIndexPolicy::Clamp` remapped it.
+    pub fn last_choice(&self) -> Option<&ChoiceItem> {
+        self.last_choice.as_ref()
+    }
+
+    /// Apply `choice_index_policy` to a requested index, returning the offered option to take.
+    ///
+    /// Errors leave the choice pending. Without a pending choice the index is returned as is, so
+    /// `choose` reports the missing choice itself.
+    fn resolve_choice_index(&mut self, index: usize) -> Result<usize, ScriptLangError> {
+        let Some(PendingBoundary::Choice { options, .. }) = &self.pending_boundary else {
+            return Ok(index);
+        };
+        let offered = options
+            .iter()
+            .map(|option| (true, option.item.enabled))
+            .collect::<Vec<_>>();
+        let ids = options
+            .iter()
+            .map(|option| option.item.id.clone())
+            .collect::<Vec<_>>();
+        let states = match self.choice_index_policy {
+            ChoiceIndexPolicy::Strict => offered,
+            ChoiceIndexPolicy::RejectStale | ChoiceIndexPolicy::Clamp => {
+                self.current_choice_states()?.unwrap_or(offered)
+            }
+        };
+
+        if self.choice_index_policy == ChoiceIndexPolicy::Clamp {
+            let target = index.min(states.len().saturating_sub(1));
+            return (0..states.len())
+                .filter(|at| states[*at] == (true, true))
+                .min_by_key(|at| (at.abs_diff(target), *at))
+                .ok_or_else(|| {
+                    ScriptLangError::new(
+                        ErrorCode::EngineChoiceStale,
+                        "No offered choice option is still visible and enabled.",
+                    )
+                });
+        }
+        let Some((visible, enabled)) = states.get(index).copied() else {
+            return Err(ScriptLangError::new(
+                ErrorCode::EngineChoiceIndex,
+                format!("Choice index \"{}\" is out of range.", index),
+            ));
+        };
+        if !visible {
+            return Err(ScriptLangError::new(
+                ErrorCode::EngineChoiceStale,
+                format!("Choice option \"{}\" is no longer visible.", ids[index]),
+            ));
+        }
+        if !enabled {
+            return Err(ScriptLangError::new(
+                ErrorCode::EngineChoiceDisabled,
+                format!("Choice option \"{}\" is disabled.", ids[index]),
+            ));
+        }
+        Ok(index)
+    }
+
+    /// `(visible, enabled)` of each pending option re-evaluated now, in offered order.
+    ///
+    /// Returns `None` when the choice frame or node is gone, which `choose` then reports.
+    fn current_choice_states(&mut self) -> Result<Option<Vec<(bool, bool)>>, ScriptLangError> {
+        let Some(PendingBoundary::Choice {
+            frame_id, options, ..
+        }) = &self.pending_boundary
+        else {
+            return Ok(None);
+        };
+        let options = options.clone();
+        let Some(frame_index) = self.find_frame_index(*frame_id) else {
+            return Ok(None);
+        };
+        let frame = &self.frames[frame_index];
+        let node_index = frame.node_index;
+        let Ok((script_name, group)) = self.lookup_group(&frame.group_id) else {
+            return Ok(None);
+        };
+        let Some(ScriptNode::Choice { entries, .. }) = group.nodes.get(node_index) else {
+            return Ok(None);
+        };
+        let script_name = script_name.to_string();
+        let entries = entries.clone();
+
+        let mut states = Vec::with_capacity(options.len());
+        for pending in &options {
+            let state = match &pending.dynamic_binding {
+                Some(binding) => {
+                    let block = entries.iter().find_map(|entry| match entry {
+                        ChoiceEntry::Dynamic { block }
+                            if block.template.group_id == binding.group_id =>
+                        {
+                            Some(block)
+                        }
+                        _ => None,
+                    });
+                    let visible = match block {
+                        Some(block) => self.dynamic_choice_when(
+                            block,
+                            (&binding.item_value, binding.index_value.unwrap_or(0)),
+                        )?,
+                        None => false,
+                    };
+                    (visible, true)
+                }
+                None => {
+                    let option = entries.iter().find_map(|entry| match entry {
+                        ChoiceEntry::Static { option } if option.id == pending.item.id => {
+                            Some(option)
+                        }
+                        _ => None,
+                    });
+                    match option {
+                        Some(option) if option.fall_over => {
+                            let visible = !option.once
+                                || !self.has_scoped_once_state(
+                                    &script_name,
+                                    &format!("option:{}", option.id),
+                                    option.global_once,
+                                );
+                            (visible, true)
+                        }
+                        Some(option) => {
+                            let visible = self.is_choice_option_visible(&script_name, option)?;
+                            (visible, visible && self.is_choice_option_enabled(option)?)
+                        }
+                        None => (false, false),
+                    }
+                }
+            };
+            states.push(state);
+        }
+        Ok(Some(states))
     }
 
     pub fn submit_input(&mut self, text: &str) -> Result<(), ScriptLangError> {
+        let checkpoint = self.history_checkpoint();
         let Some(pending) = self.pending_boundary.take() else {
             return Err(ScriptLangError::new(
-                "ENGINE_NO_PENDING_INPUT",
+                ErrorCode::EngineNoPendingInput,
                 "No pending input is available.",
             ));
         };
@@ -185,7 +342,7 @@ impl ScriptLangEngine {
             other => {
                 self.pending_boundary = Some(other);
                 return Err(ScriptLangError::new(
-                    "ENGINE_NO_PENDING_INPUT",
+                    ErrorCode::EngineNoPendingInput,
                     "No pending input is available.",
                 ));
             }
@@ -201,11 +358,12 @@ impl ScriptLangEngine {
                 max_length,
             });
             return Err(ScriptLangError::new(
-                "ENGINE_INPUT_FRAME_MISSING",
+                ErrorCode::EngineInputFrameMissing,
                 "Pending input frame is missing.",
             ));
         };
 
+        let text = if self.trim_input { text.trim() } else { text };
         let normalized = if text.trim().is_empty() {
             default_text.clone()
         } else {
@@ -223,13 +381,26 @@ impl ScriptLangEngine {
                     max_length: Some(limit),
                 });
                 return Err(ScriptLangError::new(
-                    "ENGINE_INPUT_TOO_LONG",
+                    ErrorCode::EngineInputTooLong,
                     format!("Input length {} exceeds max_length {}.", actual_len, limit),
                 ));
             }
         }
 
-        if let Err(error) = self.write_path(&target_var, SlValue::String(normalized)) {
+        let value =
+            match self.input_format_at(frame_index) {
+                InputFormat::Text => Ok(SlValue::String(normalized)),
+                InputFormat::Json => parse_json_input(&normalized),
+                InputFormat::Boolean => parse_bool_token(&normalized)
+                    .map(SlValue::Bool)
+                    .ok_or_else(|| {
+                        ScriptLangError::new(
+                            ErrorCode::EngineInputBooleanInvalid,
+                            format!("Input \"{}\" is not a yes/no answer.", normalized),
+                        )
+                    }),
+            };
+        if let Err(error) = value.and_then(|value| self.write_path(&target_var, value)) {
             self.pending_boundary = Some(PendingBoundary::Input {
                 frame_id,
                 node_id,
@@ -243,8 +414,41 @@ impl ScriptLangEngine {
 
         self.frames[frame_index].node_index += 1;
         self.waiting_choice = false;
+        self.push_history(checkpoint);
         Ok(())
     }
+
+    fn input_format_at(&self, frame_index: usize) -> InputFormat {
+        let frame = &self.frames[frame_index];
+        match self
+            .lookup_group(&frame.group_id)
+            .ok()
+            .and_then(|(_, group)| group.nodes.get(frame.node_index))
+        {
+            Some(ScriptNode::Input { format, .. }) => *format,
+            _ => InputFormat::Text,
+        }
+    }
+
+    pub fn pending_choices(&self) -> Option<Vec<ChoiceItem>> {
+        match self.pending_boundary.as_ref()? {
+            PendingBoundary::Choice { options, .. } => {
+                Some(options.iter().map(|option| option.item.clone()).collect())
+            }
+            PendingBoundary::Input { .. } => None,
+        }
+    }
+
+    pub fn pending_input(&self) -> Option<(String, String)> {
+        match self.pending_boundary.as_ref()? {
+            PendingBoundary::Input {
+                prompt_text,
+                default_text,
+                ..
+            } => Some((prompt_text.clone(), default_text.clone())),
+            PendingBoundary::Choice { .. } => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -258,6 +462,7 @@ mod boundary_tests {
             EngineOutput::Debug { .. } => "debug",
             EngineOutput::Choices { .. } => "choices",
             EngineOutput::Input { .. } => "input",
+            EngineOutput::Checkpoint { .. } => "checkpoint",
             EngineOutput::End => "end",
         }
     }
@@ -308,14 +513,16 @@ mod boundary_tests {
         assert_eq!(
             output_kind(&EngineOutput::Text {
                 text: "x".to_string(),
-                tag: None
+                tag: None,
+                speaker: None,
             }),
             "text"
         );
         assert_eq!(
             output_kind(&EngineOutput::Choices {
                 items: Vec::new(),
-                prompt_text: None
+                prompt_text: None,
+                default_index: None,
             }),
             "choices"
         );
@@ -369,6 +576,8 @@ mod boundary_tests {
                     index: 0,
                     id: "id".to_string(),
                     text: "text".to_string(),
+                    scene: None,
+                    enabled: true,
                 },
                 dynamic_binding: None,
             }],
@@ -410,6 +619,449 @@ mod boundary_tests {
         assert_eq!(output_kind(&first), "choices");
         let error = engine.choose(9).expect_err("index out of range");
         assert_eq!(error.code, "ENGINE_CHOICE_INDEX");
+        assert!(engine.last_choice().is_none());
+    }
+
+    #[test]
+    pub(super) fn clamp_policy_remaps_stale_index_to_last_visible_option() {
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <choice text="Pick">
+        <option text="A"><text>took A</text></option>
+        <option text="Hidden" when="false"><text>took Hidden</text></option>
+        <option text="B"><text>took B</text></option>
+      </choice>
+    </script>
+    "#,
+        )]));
+        engine.choice_index_policy = ChoiceIndexPolicy::Clamp;
+        engine.start("main", None).expect("start");
+        let items = choice_items(engine.next_output().expect("next")).expect("choices expected");
+        assert_eq!(items.len(), 2);
+
+        let taken = engine.choose(2).expect("stale index should clamp");
+        assert_eq!(engine.last_choice(), Some(&taken));
+        assert_eq!(taken, items[1]);
+        assert_eq!(taken.text, "B");
+        assert!(matches!(
+            engine.next_output().expect("next"),
+            EngineOutput::Text { text, .. } if text == "took B"
+        ));
+    }
+
+    #[test]
+    pub(super) fn option_scene_hint_is_carried_to_choice_items_and_last_choice() {
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <choice text="Where?">
+        <option text="Stay"><text>stayed</text></option>
+        <option text="Leave" scene="forest"><text>left</text></option>
+      </choice>
+    </script>
+    "#,
+        )]));
+        engine.start("main", None).expect("start");
+        let items = choice_items(engine.next_output().expect("next")).expect("choices expected");
+        assert_eq!(items[0].scene, None);
+        assert_eq!(items[1].scene.as_deref(), Some("forest"));
+
+        engine.choose(1).expect("choose");
+        assert_eq!(
+            engine.last_choice().and_then(|item| item.scene.as_deref()),
+            Some("forest")
+        );
+        assert!(matches!(
+            engine.next_output().expect("next"),
+            EngineOutput::Text { text, .. } if text == "left"
+        ));
+    }
+
+    #[test]
+    pub(super) fn max_select_hides_option_after_limit_and_survives_snapshot() {
+        let files = map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <while when="true">
+        <choice text="Shop">
+          <option text="Buy" max_select="2"><text>bought</text><continue/></option>
+          <option text="Haggle" max_select="1"><text>haggled</text><continue/></option>
+          <option text="Leave"><break/></option>
+        </choice>
+      </while>
+      <end/>
+    </script>
+    "#,
+        )]);
+        let choice_texts = |engine: &mut ScriptLangEngine| loop {
+            let output = engine.next_output().expect("next");
+            if matches!(output, EngineOutput::Text { .. }) {
+                continue;
+            }
+            let items = choice_items(output).expect("choices expected");
+            return items.into_iter().map(|item| item.text).collect::<Vec<_>>();
+        };
+        let mut engine = engine_from_sources(files.clone());
+        engine.start("main", None).expect("start");
+        assert_eq!(choice_texts(&mut engine), vec!["Buy", "Haggle", "Leave"]);
+        engine.choose(0).expect("first buy");
+        assert_eq!(choice_texts(&mut engine), vec!["Buy", "Haggle", "Leave"]);
+        let snapshot = engine.snapshot().expect("snapshot");
+        assert_eq!(
+            snapshot.select_count_by_script["main.main"]
+                .values()
+                .collect::<Vec<_>>(),
+            vec![&1]
+        );
+
+        let mut resumed = engine_from_sources(files);
+        resumed.resume(snapshot).expect("resume");
+        assert_eq!(choice_texts(&mut resumed), vec!["Buy", "Haggle", "Leave"]);
+        resumed.choose(0).expect("second buy");
+        assert_eq!(choice_texts(&mut resumed), vec!["Haggle", "Leave"]);
+        resumed.choose(0).expect("haggle");
+        assert_eq!(choice_texts(&mut resumed), vec!["Leave"]);
+        assert!(resumed.once_state()["main.main"]
+            .iter()
+            .all(|key| key.starts_with("option:")));
+        assert_eq!(
+            resumed.select_counts()["main.main"]
+                .values()
+                .collect::<Vec<_>>(),
+            vec![&2]
+        );
+    }
+
+    #[test]
+    pub(super) fn enabled_when_keeps_option_listed_but_rejects_choosing_it() {
+        let files = map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <temp name="gold" type="int">3</temp>
+      <choice text="Shop">
+        <option text="Secret" when="gold > 100"><text>secret</text></option>
+        <option text="Sword" enabled_when="gold >= 10"><text>sword</text></option>
+        <option text="Bread" enabled_when="gold >= 2"><text>bread</text></option>
+      </choice>
+    </script>
+    "#,
+        )]);
+        let mut engine = engine_from_sources(files.clone());
+        engine.start("main", None).expect("start");
+        let items = choice_items(engine.next_output().expect("next")).expect("choices expected");
+        assert_eq!(
+            items
+                .iter()
+                .map(|item| (item.text.as_str(), item.enabled))
+                .collect::<Vec<_>>(),
+            vec![("Sword", false), ("Bread", true)]
+        );
+
+        let error = engine.choose(0).expect_err("disabled option");
+        assert_eq!(error.code, "ENGINE_CHOICE_DISABLED");
+        let sword_id = items[0].id.clone();
+        let error = engine.choose_id(&sword_id).expect_err("disabled option");
+        assert_eq!(error.code, "ENGINE_CHOICE_DISABLED");
+
+        let snapshot = engine
+            .snapshot()
+            .expect("pending choice survives rejection");
+        let mut resumed = engine_from_sources(files);
+        resumed.resume(snapshot).expect("resume");
+        let items = choice_items(resumed.next_output().expect("next")).expect("choices expected");
+        assert!(!items[0].enabled && items[1].enabled);
+        resumed.choose(1).expect("enabled option");
+        assert!(matches!(
+            resumed.next_output().expect("next"),
+            EngineOutput::Text { text, .. } if text == "bread"
+        ));
+    }
+
+    #[test]
+    fn choice_indices_follow_declaration_order_and_ids_survive_hidden_options() {
+        let files = map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <temp name="show_a" type="boolean">true</temp>
+      <choice text="Pick">
+        <option text="A" when="show_a"><text>took A</text></option>
+        <option text="Once" once="true"><text>took Once</text></option>
+        <option text="B"><text>took B</text></option>
+        <option text="C"><text>took C</text></option>
+      </choice>
+      <end/>
+    </script>
+    "#,
+        )]);
+        let offered = |engine: &mut ScriptLangEngine| {
+            let items =
+                choice_items(engine.next_output().expect("next")).expect("choices expected");
+            assert!(items
+                .iter()
+                .enumerate()
+                .all(|(index, item)| item.index == index));
+            items
+        };
+
+        let mut engine = engine_from_sources(files.clone());
+        engine.start("main", None).expect("start");
+        let first = offered(&mut engine);
+        let texts = first
+            .iter()
+            .map(|item| item.text.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["A", "Once", "B", "C"]);
+        engine.choose_id(&first[1].id).expect("choose once");
+        assert_eq!(engine.last_choice(), Some(&first[1]));
+
+        // The consumed once option drops out and later options close the gap, keeping their ids.
+        engine.start("main", None).expect("restart");
+        let second = offered(&mut engine);
+        let ids = second
+            .iter()
+            .map(|item| item.id.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ids,
+            vec![
+                first[0].id.clone(),
+                first[2].id.clone(),
+                first[3].id.clone()
+            ]
+        );
+
+        // Hiding A after the boundary was offered does not move the option the player picked.
+        let mut snapshot = engine.snapshot().expect("snapshot");
+        let items = match &mut snapshot.pending_boundary {
+            sl_core::PendingBoundary::Choice { items, .. } => Some(items),
+            _ => None,
+        }
+        .expect("pending choice expected");
+        items.remove(0);
+        let mut resumed = engine_from_sources(files);
+        resumed.resume(snapshot).expect("resume");
+        resumed
+            .set_variable("show_a", SlValue::Bool(false))
+            .expect("hide A");
+        let resumed_items = offered(&mut resumed);
+        assert_eq!(resumed_items[1].id, first[3].id);
+        resumed.choose_id(&first[3].id).expect("choose C by id");
+        assert!(matches!(
+            resumed.next_output().expect("next"),
+            EngineOutput::Text { text, .. } if text == "took C"
+        ));
+
+        let error = resumed
+            .choose_id(&first[3].id)
+            .expect_err("no pending choice");
+        assert_eq!(error.code, "ENGINE_NO_PENDING_CHOICE");
+        engine.start("main", None).expect("restart");
+        offered(&mut engine);
+        let error = engine
+            .choose_id(&first[1].id)
+            .expect_err("consumed option is not offered");
+        assert_eq!(error.code, "ENGINE_CHOICE_NOT_FOUND");
+    }
+
+    #[test]
+    pub(super) fn clamp_policy_keeps_in_range_index() {
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <choice text="Pick">
+        <option text="A"><text>took A</text></option>
+        <option text="B"><text>took B</text></option>
+      </choice>
+    </script>
+    "#,
+        )]));
+        engine.choice_index_policy = ChoiceIndexPolicy::Clamp;
+        engine.start("main", None).expect("start");
+        engine.next_output().expect("choices");
+        engine.choose(0).expect("choose");
+        assert_eq!(engine.last_choice().map(|item| item.index), Some(0));
+    }
+
+    fn stale_choice_engine(policy: ChoiceIndexPolicy) -> ScriptLangEngine {
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <temp name="show_a" type="boolean">true</temp>
+      <temp name="gold" type="int">3</temp>
+      <choice text="Pick">
+        <option text="A" when="show_a"><text>took A</text></option>
+        <option text="Sword" enabled_when="gold >= 10"><text>took Sword</text></option>
+        <option text="B"><text>took B</text></option>
+        <option text="C"><text>took C</text></option>
+      </choice>
+    </script>
+    "#,
+        )]));
+        engine.choice_index_policy = policy;
+        engine.start("main", None).expect("start");
+        let items = choice_items(engine.next_output().expect("next")).expect("choices expected");
+        assert_eq!(items.len(), 4);
+        engine
+    }
+
+    #[test]
+    pub(super) fn clamp_policy_moves_hidden_target_to_nearest_visible_option() {
+        let mut engine = stale_choice_engine(ChoiceIndexPolicy::Clamp);
+        engine
+            .set_variable("show_a", SlValue::Bool(false))
+            .expect("hide A");
+        // A (0) is hidden and Sword (1) disabled, so B (2) is the nearest selectable option.
+        let taken = engine.choose(0).expect("hidden target should remap");
+        assert_eq!((taken.index, taken.text.as_str()), (2, "B"));
+        assert!(matches!(
+            engine.next_output().expect("next"),
+            EngineOutput::Text { text, .. } if text == "took B"
+        ));
+
+        let mut engine = stale_choice_engine(ChoiceIndexPolicy::Clamp);
+        engine
+            .set_variable("show_a", SlValue::Bool(false))
+            .expect("hide A");
+        assert_eq!(engine.choose(9).expect("out of range").text, "C");
+    }
+
+    #[test]
+    pub(super) fn clamp_policy_snaps_disabled_target_to_nearest_enabled_option() {
+        // Sword (1) is one step from both A (0) and B (2); the earlier option wins the tie.
+        let mut engine = stale_choice_engine(ChoiceIndexPolicy::Clamp);
+        assert_eq!(engine.choose(1).expect("snap to enabled").text, "A");
+
+        // An option enabled since it was offered is taken as is.
+        let mut engine = stale_choice_engine(ChoiceIndexPolicy::Clamp);
+        engine
+            .set_variable("gold", SlValue::Number(20.0))
+            .expect("gold");
+        assert_eq!(engine.choose(1).expect("now enabled").text, "Sword");
+    }
+
+    #[test]
+    pub(super) fn reject_stale_policy_errors_on_hidden_and_disabled_targets() {
+        let mut engine = stale_choice_engine(ChoiceIndexPolicy::RejectStale);
+        engine
+            .set_variable("show_a", SlValue::Bool(false))
+            .expect("hide A");
+        let error = engine.choose(0).expect_err("hidden target");
+        assert_eq!(error.code, "ENGINE_CHOICE_STALE");
+        let error = engine.choose(1).expect_err("disabled target");
+        assert_eq!(error.code, "ENGINE_CHOICE_DISABLED");
+        let error = engine.choose(4).expect_err("out of range");
+        assert_eq!(error.code, "ENGINE_CHOICE_INDEX");
+        assert!(engine.last_choice().is_none());
+        assert_eq!(engine.choose(2).expect("still valid").text, "B");
+    }
+
+    #[test]
+    pub(super) fn clamp_policy_errors_when_no_offered_option_remains() {
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <temp name="open" type="boolean">true</temp>
+      <choice text="Pick">
+        <option text="A" when="open"><text>took A</text></option>
+      </choice>
+    </script>
+    "#,
+        )]));
+        engine.choice_index_policy = ChoiceIndexPolicy::Clamp;
+        engine.start("main", None).expect("start");
+        engine.next_output().expect("choices");
+        engine
+            .set_variable("open", SlValue::Bool(false))
+            .expect("hide A");
+        let error = engine.choose(0).expect_err("nothing left");
+        assert_eq!(error.code, "ENGINE_CHOICE_STALE");
+        assert!(matches!(
+            engine.next_output().expect("still pending"),
+            EngineOutput::Choices { .. }
+        ));
+    }
+
+    #[test]
+    pub(super) fn choose_runs_option_set_before_option_body() {
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <temp name="agreed" type="boolean">false</temp>
+      <choice text="Agree?">
+        <option text="Yes" set="agreed = true;"/>
+        <option text="No"><text>no</text></option>
+      </choice>
+      <text>agreed=${agreed}</text>
+      <end/>
+    </script>
+    "#,
+        )]));
+        engine.start("main", None).expect("start");
+        assert_eq!(output_kind(&engine.next_output().expect("next")), "choices");
+        engine.choose(0).expect("choose");
+        assert!(matches!(
+            engine.next_output().expect("text"),
+            EngineOutput::Text { text, .. } if text == "agreed=true"
+        ));
+    }
+
+    #[test]
+    pub(super) fn pending_choices_and_input_read_boundary_after_resume() {
+        let files = map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <temp name="heroName" type="string">"Traveler"</temp>
+      <choice text="Pick">
+        <option text="A"><text>A</text></option>
+        <option text="B"><text>B</text></option>
+      </choice>
+      <input var="heroName" text="Name your hero"/>
+      <text>Hello ${heroName}</text>
+    </script>
+    "#,
+        )]);
+        let mut engine = engine_from_sources(files.clone());
+        engine.start("main", None).expect("start");
+        assert_eq!(engine.pending_choices(), None);
+        assert_eq!(engine.pending_input(), None);
+        let _ = engine.next_output().expect("choices");
+        let snapshot = engine.snapshot().expect("choice snapshot");
+
+        let mut resumed = engine_from_sources(files.clone());
+        resumed.resume(snapshot).expect("resume choice");
+        let items = resumed.pending_choices().expect("pending choices");
+        assert_eq!(
+            items
+                .iter()
+                .map(|item| item.text.as_str())
+                .collect::<Vec<_>>(),
+            vec!["A", "B"]
+        );
+        assert_eq!(resumed.pending_input(), None);
+        resumed.choose(items[1].index).expect("choose");
+        assert_eq!(resumed.pending_choices(), None);
+        let _ = resumed.next_output().expect("text");
+        let _ = resumed.next_output().expect("input");
+        let snapshot = resumed.snapshot().expect("input snapshot");
+
+        let mut resumed_input = engine_from_sources(files);
+        resumed_input.resume(snapshot).expect("resume input");
+        assert_eq!(
+            resumed_input.pending_input(),
+            Some(("Name your hero".to_string(), "Traveler".to_string()))
+        );
+        assert_eq!(resumed_input.pending_choices(), None);
     }
 
     #[test]
@@ -436,6 +1088,42 @@ mod boundary_tests {
         ));
     }
 
+    #[test]
+    pub(super) fn submit_input_trims_ends_unless_disabled() {
+        let files = map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <temp name="heroName" type="string">"Traveler"</temp>
+      <input var="heroName" text="Name your hero"/>
+      <text>[${heroName}]</text>
+    </script>
+    "#,
+        )]);
+        let rendered_after_input = |engine: &mut ScriptLangEngine, input: &str| {
+            engine.start("main", None).expect("start");
+            let first = engine.next_output().expect("next");
+            assert_eq!(output_kind(&first), "input");
+            engine.submit_input(input).expect("submit input");
+            output_text(engine.next_output().expect("next")).expect("text expected")
+        };
+
+        let mut engine = engine_from_sources(files.clone());
+        assert_eq!(rendered_after_input(&mut engine, "Guild\n"), "[Guild]");
+        assert_eq!(
+            rendered_after_input(&mut engine, "  Mary  Ann \r\n"),
+            "[Mary  Ann]"
+        );
+
+        let mut raw_engine = engine_from_sources(files);
+        raw_engine.trim_input = false;
+        assert_eq!(
+            rendered_after_input(&mut raw_engine, "  Mary  Ann \n"),
+            "[  Mary  Ann \n]"
+        );
+        assert_eq!(rendered_after_input(&mut raw_engine, " \n"), "[Traveler]");
+    }
+
     #[test]
     pub(super) fn submit_input_uses_provided_non_empty_value() {
         let mut engine = engine_from_sources(map(&[(
@@ -504,6 +1192,127 @@ mod boundary_tests {
         assert_eq!(output_kind(&pending_again), "input");
     }
 
+    #[test]
+    pub(super) fn submit_input_parses_json_format_into_typed_var() {
+        let files = map(&[(
+            "main.script.xml",
+            r##"
+    <script name="main">
+      <temp name="config" type="#{int}">#{hp: 1}</temp>
+      <input var="config" text="Config" format="json"/>
+      <text>hp=${config.hp}</text>
+      <text>mp=${config.mp}</text>
+    </script>
+    "##,
+        )]);
+        let mut engine = engine_from_sources(files.clone());
+        engine.start("main", None).expect("start");
+        assert!(matches!(
+            engine.next_output().expect("input"),
+            EngineOutput::Input { default_text, .. } if default_text == r#"{"hp":1}"#
+        ));
+
+        let error = engine
+            .submit_input(r#"{"hp": 3"#)
+            .expect_err("malformed json should fail");
+        assert_eq!(error.code, "ENGINE_INPUT_JSON_INVALID");
+        assert_eq!(output_kind(&engine.next_output().expect("input")), "input");
+
+        let error = engine
+            .submit_input(r#"{"hp": "high"}"#)
+            .expect_err("type mismatch should fail");
+        assert_eq!(error.code, "ENGINE_TYPE_MISMATCH");
+        assert_eq!(output_kind(&engine.next_output().expect("input")), "input");
+
+        engine
+            .submit_input(r#"{"hp": 3, "mp": 4}"#)
+            .expect("valid json should submit");
+        assert!(matches!(
+            engine.next_output().expect("text"),
+            EngineOutput::Text { text, .. } if text == "hp=3"
+        ));
+        assert!(matches!(
+            engine.next_output().expect("text"),
+            EngineOutput::Text { text, .. } if text == "mp=4"
+        ));
+
+        let mut blank = engine_from_sources(files);
+        blank.start("main", None).expect("start");
+        assert_eq!(output_kind(&blank.next_output().expect("input")), "input");
+        blank
+            .submit_input("  ")
+            .expect("blank falls back to default");
+        assert!(matches!(
+            blank.next_output().expect("text"),
+            EngineOutput::Text { text, .. } if text == "hp=1"
+        ));
+    }
+
+    #[test]
+    pub(super) fn submit_input_parses_boolean_format_tokens() {
+        let files = map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <temp name="agree" type="boolean">false</temp>
+      <input var="agree" text="Continue?" format="boolean"/>
+      <if when="agree"><text>yes</text><else><text>no</text></else></if>
+    </script>
+    "#,
+        )]);
+        for (answer, expected) in [
+            ("YES", "yes"),
+            ("y", "yes"),
+            ("true", "yes"),
+            ("1", "yes"),
+            ("On", "yes"),
+            ("no", "no"),
+            ("N", "no"),
+            ("False", "no"),
+            ("0", "no"),
+            ("off", "no"),
+            ("  ", "no"),
+        ] {
+            let mut engine = engine_from_sources(files.clone());
+            engine.start("main", None).expect("start");
+            assert!(matches!(
+                engine.next_output().expect("input"),
+                EngineOutput::Input { default_text, .. } if default_text == "false"
+            ));
+            engine
+                .submit_input(answer)
+                .expect("boolean token should submit");
+            assert!(
+                matches!(
+                    engine.next_output().expect("text"),
+                    EngineOutput::Text { ref text, .. } if text == expected
+                ),
+                "answer {:?}",
+                answer
+            );
+        }
+
+        let mut engine = engine_from_sources(files);
+        engine.start("main", None).expect("start");
+        engine.next_output().expect("input");
+        let error = engine
+            .submit_input("maybe")
+            .expect_err("ambiguous answer should fail");
+        assert_eq!(error.code, "ENGINE_INPUT_BOOLEAN_INVALID");
+        assert_eq!(output_kind(&engine.next_output().expect("input")), "input");
+
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"<script name="main"><temp name="name" type="string">""</temp><input var="name" text="?"/></script>"#,
+        )]));
+        rewrite_input_nodes(&mut engine, "name", InputFormat::Boolean);
+        engine.start("main", None).expect("start");
+        let error = engine
+            .next_output()
+            .expect_err("non-boolean target should fail");
+        assert_eq!(error.code, "ENGINE_INPUT_VAR_TYPE");
+    }
+
     #[test]
     pub(super) fn submit_input_rejects_module_const_target() {
         let mut engine = engine_from_sources(map(&[(
@@ -511,10 +1320,12 @@ mod boundary_tests {
             r#"<module name="main" export="script:main;const:heroName">
   <const name="heroName" type="string">"Traveler"</const>
   <script name="main">
-    <input var="heroName" text="Name your hero"/>
+    <temp name="draft" type="string">""</temp>
+    <input var="draft" text="Name your hero"/>
   </script>
 </module>"#,
         )]));
+        rewrite_input_nodes(&mut engine, "heroName", InputFormat::Text);
         engine.start("main.main", None).expect("start");
         let first = engine.next_output().expect("next");
         assert_eq!(output_kind(&first), "input");
@@ -691,6 +1502,8 @@ mod boundary_tests {
                     index: 0,
                     id: "opt".to_string(),
                     text: "A".to_string(),
+                    scene: None,
+                    enabled: true,
                 },
                 dynamic_binding: None,
             }],
@@ -17,7 +17,7 @@ impl ScriptLangEngine {
         }
         let Some(target_module_name) = target.module_name.as_deref() else {
             return Err(ScriptLangError::new(
-                "ENGINE_SCRIPT_ACCESS_DENIED",
+                ErrorCode::EngineScriptAccessDenied,
                 format!(
                     "Script \"{}\" is private and cannot be called from current context.",
                     target_script_name
@@ -26,7 +26,7 @@ impl ScriptLangEngine {
         };
         let Some(current_module_name) = self.resolve_current_module_name() else {
             return Err(ScriptLangError::new(
-                "ENGINE_SCRIPT_ACCESS_DENIED",
+                ErrorCode::EngineScriptAccessDenied,
                 format!(
                     "Script \"{}\" is private and cannot be called from current context.",
                     target_script_name
@@ -35,7 +35,7 @@ impl ScriptLangEngine {
         };
         if current_module_name != target_module_name {
             return Err(ScriptLangError::new(
-                "ENGINE_SCRIPT_ACCESS_DENIED",
+                ErrorCode::EngineScriptAccessDenied,
                 format!(
                     "Script \"{}\" is private and cannot be called from current context.",
                     target_script_name
@@ -48,7 +48,7 @@ impl ScriptLangEngine {
     fn normalize_target_script(
         &self,
         resolved: &str,
-        missing_code: &str,
+        missing_code: ErrorCode,
         missing_message: &str,
     ) -> Result<String, ScriptLangError> {
         let target_script = resolved.trim().to_string();
@@ -61,7 +61,7 @@ impl ScriptLangEngine {
     fn resolve_target_script(
         &mut self,
         target: &ScriptTarget,
-        missing_code: &str,
+        missing_code: ErrorCode,
         missing_message: &str,
     ) -> Result<String, ScriptLangError> {
         match target {
@@ -72,13 +72,13 @@ impl ScriptLangEngine {
                 let value = self.read_variable(var_name)?;
                 let SlValue::String(raw) = value else {
                     return Err(ScriptLangError::new(
-                        "ENGINE_TARGET_VAR_TYPE",
+                        ErrorCode::EngineTargetVarType,
                         format!("Target variable \"{}\" must be script.", var_name),
                     ));
                 };
                 let stripped = raw.trim().strip_prefix('@').ok_or_else(|| {
                     ScriptLangError::new(
-                        "ENGINE_TARGET_VAR_TYPE",
+                        ErrorCode::EngineTargetVarType,
                         format!("Target variable \"{}\" must be script.", var_name),
                     )
                 })?;
@@ -93,7 +93,7 @@ impl ScriptLangEngine {
     ) -> Result<(), ScriptLangError> {
         let frame_index = self.frames.len().checked_sub(1).ok_or_else(|| {
             ScriptLangError::new(
-                "ENGINE_VAR_FRAME",
+                ErrorCode::EngineVarFrame,
                 "No frame available for var declaration.",
             )
         })?;
@@ -101,7 +101,7 @@ impl ScriptLangEngine {
         let duplicate = self.frames[frame_index].scope.contains_key(&decl.name);
         if duplicate {
             return Err(ScriptLangError::new(
-                "ENGINE_VAR_DUPLICATE",
+                ErrorCode::EngineVarDuplicate,
                 format!(
                     "Variable \"{}\" is already declared in current scope.",
                     decl.name
@@ -114,7 +114,7 @@ impl ScriptLangEngine {
             value = self.eval_initializer_expression(expr, "initializer")?;
         } else if matches!(decl.r#type, ScriptType::Enum { .. }) {
             return Err(ScriptLangError::new(
-                "ENGINE_ENUM_INIT_REQUIRED",
+                ErrorCode::EngineEnumInitRequired,
                 format!(
                     "Variable \"{}\" with enum type requires explicit initializer.",
                     decl.name
@@ -124,7 +124,7 @@ impl ScriptLangEngine {
 
         if !is_type_compatible(&value, &decl.r#type) {
             return Err(ScriptLangError::new(
-                "ENGINE_TYPE_MISMATCH",
+                ErrorCode::EngineTypeMismatch,
                 format!("Variable \"{}\" does not match declared type.", decl.name),
             ));
         }
@@ -144,11 +144,14 @@ impl ScriptLangEngine {
     ) -> Result<(), ScriptLangError> {
         let target_script_name = self.resolve_target_script(
             target_script,
-            "ENGINE_CALL_TARGET_EMPTY",
+            ErrorCode::EngineCallTargetEmpty,
             "Call target script cannot resolve to empty.",
         )?;
         let caller_index = self.frames.len().checked_sub(1).ok_or_else(|| {
-            ScriptLangError::new("ENGINE_CALL_NO_FRAME", "No frame available for <call>.")
+            ScriptLangError::new(
+                ErrorCode::EngineCallNoFrame,
+                "No frame available for <call>.",
+            )
         })?;
 
         let caller_group_id = self.frames[caller_index].group_id.clone();
@@ -159,13 +162,13 @@ impl ScriptLangEngine {
 
         let Some(target) = self.scripts.get(&target_script_name).cloned() else {
             return Err(ScriptLangError::new(
-                "ENGINE_CALL_TARGET",
+                ErrorCode::EngineCallTarget,
                 format!("Call target script \"{}\" not found.", target_script_name),
             ));
         };
         if target.kind != ScriptKind::Call {
             return Err(ScriptLangError::new(
-                "ENGINE_CALL_TARGET_KIND",
+                ErrorCode::EngineCallTargetKind,
                 format!(
                     "Call target script \"{}\" must be call kind.",
                     target_script_name
@@ -182,7 +185,7 @@ impl ScriptLangEngine {
         for (index, arg) in args.iter().enumerate() {
             let Some(param) = target.params.get(index) else {
                 return Err(ScriptLangError::new(
-                    "ENGINE_CALL_ARG_UNKNOWN",
+                    ErrorCode::EngineCallArgUnknown,
                     format!(
                         "Call argument at position {} has no matching parameter.",
                         index + 1
@@ -192,13 +195,13 @@ impl ScriptLangEngine {
 
             if param.is_ref && !arg.is_ref {
                 return Err(ScriptLangError::new(
-                    "ENGINE_CALL_REF_MISMATCH",
+                    ErrorCode::EngineCallRefMismatch,
                     format!("Call argument {} must use ref mode.", index + 1),
                 ));
             }
             if !param.is_ref && arg.is_ref {
                 return Err(ScriptLangError::new(
-                    "ENGINE_CALL_REF_MISMATCH",
+                    ErrorCode::EngineCallRefMismatch,
                     format!("Call argument {} cannot use ref mode.", index + 1),
                 ));
             }
@@ -220,7 +223,7 @@ impl ScriptLangEngine {
 
         if is_tail_at_root && !ref_bindings.is_empty() {
             return Err(ScriptLangError::new(
-                "ENGINE_TAIL_REF_UNSUPPORTED",
+                ErrorCode::EngineTailRefUnsupported,
                 "Tail call with ref args is not supported.",
             ));
         }
@@ -255,18 +258,18 @@ impl ScriptLangEngine {
         let inherited = root_frame.return_continuation.clone();
         let target_name = self.resolve_target_script(
             target_script,
-            "ENGINE_GOTO_TARGET_EMPTY",
+            ErrorCode::EngineGotoTargetEmpty,
             "Goto target script cannot resolve to empty.",
         )?;
         let Some(target) = self.scripts.get(&target_name).cloned() else {
             return Err(ScriptLangError::new(
-                "ENGINE_GOTO_TARGET",
+                ErrorCode::EngineGotoTarget,
                 format!("Goto target script \"{}\" not found.", target_name),
             ));
         };
         if target.kind != ScriptKind::Goto {
             return Err(ScriptLangError::new(
-                "ENGINE_GOTO_TARGET_KIND",
+                ErrorCode::EngineGotoTargetKind,
                 format!("Goto target script \"{}\" must be goto kind.", target_name),
             ));
         }
@@ -278,7 +281,7 @@ impl ScriptLangEngine {
         for (index, arg) in args.iter().enumerate() {
             let Some(param) = target.params.get(index) else {
                 return Err(ScriptLangError::new(
-                    "ENGINE_GOTO_ARG_UNKNOWN",
+                    ErrorCode::EngineGotoArgUnknown,
                     format!(
                         "Goto argument at position {} has no target parameter.",
                         index + 1
@@ -362,7 +365,7 @@ impl ScriptLangEngine {
             }
         }
         Err(ScriptLangError::new(
-            "ENGINE_ROOT_FRAME",
+            ErrorCode::EngineRootFrame,
             "No script root frame found.",
         ))
     }
@@ -432,6 +435,97 @@ mod callstack_tests {
         assert!(matches!(output, EngineOutput::Text { text, .. } if text == "Dynamic hi"));
     }
 
+    fn engine_from_artifact(artifact: sl_core::CompiledProjectArtifact) -> ScriptLangEngine {
+        let mut engine = ScriptLangEngine::new(ScriptLangEngineOptions {
+            scripts: artifact.scripts,
+            global_data: artifact.global_data,
+            module_var_declarations: artifact.module_var_declarations,
+            module_var_init_order: artifact.module_var_init_order,
+            module_const_declarations: artifact.module_const_declarations,
+            module_const_init_order: artifact.module_const_init_order,
+            random_seed: Some(1),
+            compiler_version: Some(artifact.compiler_version),
+            ..Default::default()
+        })
+        .expect("engine should build");
+        engine.start(&artifact.entry_script, None).expect("start");
+        engine
+    }
+
+    #[test]
+    pub(super) fn inlined_small_calls_produce_identical_outputs() {
+        let files = map(&[(
+            "main.xml",
+            r#"<module name="main" export="script:main">
+<var name="hp" type="int">3</var>
+<script name="status" kind="call"><text>hp=${hp}</text><debug>tick</debug><return/></script>
+<script name="main">
+  <temp name="i" type="int">0</temp>
+  <while when="i LT 2">
+    <call script="@status"/>
+    <code>i = i + 1; hp = hp - 1;</code>
+  </while>
+  <choice text="Pick">
+    <option text="A"><call script="@status"/></option>
+  </choice>
+  <end/>
+</script>
+</module>"#,
+        )]);
+        let compile = |inline_small_calls| {
+            sl_compiler::compile_artifact_from_xml_map_with_options(
+                &files,
+                None,
+                sl_compiler::CompileOptions {
+                    inline_small_calls,
+                    ..sl_compiler::CompileOptions::default()
+                },
+            )
+            .expect("compile artifact")
+        };
+        let collect = |engine: &mut ScriptLangEngine| {
+            let mut outputs = Vec::new();
+            loop {
+                let output = engine.next_output().expect("next should pass");
+                if let EngineOutput::Choices { items, .. } = &output {
+                    engine.choose(items[0].index).expect("choose");
+                }
+                let done = matches!(output, EngineOutput::End);
+                outputs.push(output);
+                if done {
+                    return outputs;
+                }
+            }
+        };
+
+        let plain = compile(false);
+        let inlined = compile(true);
+        assert_ne!(plain.scripts, inlined.scripts);
+        assert_ne!(plain.compiler_version, inlined.compiler_version);
+
+        let mut plain_engine = engine_from_artifact(plain);
+        let mut inlined_engine = engine_from_artifact(inlined.clone());
+        let plain_outputs = collect(&mut plain_engine);
+        assert_eq!(plain_outputs, collect(&mut inlined_engine));
+        assert!(plain_outputs.contains(&EngineOutput::Text {
+            text: "hp=1".to_string(),
+            tag: None,
+            speaker: None,
+        }));
+
+        let mut snapshot_engine = engine_from_artifact(compile(false));
+        while !matches!(
+            snapshot_engine.next_output().expect("next should pass"),
+            EngineOutput::Choices { .. }
+        ) {}
+        let snapshot = snapshot_engine.snapshot().expect("snapshot");
+        let mut resumed = engine_from_artifact(inlined);
+        let error = resumed
+            .resume(snapshot)
+            .expect_err("snapshot from non-inlined layout should be rejected");
+        assert_eq!(error.code, "SNAPSHOT_COMPILER_VERSION");
+    }
+
     #[test]
     pub(super) fn runtime_errors_cover_call_argument_and_return_target_paths() {
         let mut call_missing_target = engine_from_sources(map(&[(
@@ -454,6 +548,9 @@ mod callstack_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::new(),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let error = call_missing_target
             .execute_call(&lit("missing"), &[])
@@ -480,6 +577,9 @@ mod callstack_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::from([("dst".to_string(), ScriptType::Script)]),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let error = call_empty_target
             .execute_call(&var("dst"), &[])
@@ -506,6 +606,9 @@ mod callstack_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::from([("dst".to_string(), ScriptType::Script)]),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let error = call_bad_type
             .execute_call(&var("dst"), &[])
@@ -532,6 +635,9 @@ mod callstack_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::from([("dst".to_string(), ScriptType::Script)]),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let error = call_non_string_target
             .execute_call(&var("dst"), &[])
@@ -558,6 +664,9 @@ mod callstack_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::new(),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let error = call_missing_var_target
             .execute_call(&var("dst"), &[])
@@ -606,6 +715,9 @@ mod callstack_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::new(),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let error = return_target_missing
             .execute_goto(&lit("missing"), &[])
@@ -632,6 +744,9 @@ mod callstack_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::from([("dst".to_string(), ScriptType::Script)]),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let error = return_empty_target
             .execute_goto(&var("dst"), &[])
@@ -658,6 +773,9 @@ mod callstack_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::from([("dst".to_string(), ScriptType::Script)]),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let error = return_bad_type
             .execute_goto(&var("dst"), &[])
@@ -694,6 +812,9 @@ mod callstack_tests {
                 ref_bindings: BTreeMap::new(),
             }),
             var_types: BTreeMap::new(),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         engine.finish_frame(1).expect("finish should pass");
         assert!(engine.ended);
@@ -718,6 +839,9 @@ mod callstack_tests {
                 script_root: true,
                 return_continuation: None,
                 var_types: BTreeMap::from([("target".to_string(), number_ty.clone())]),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
             RuntimeFrame {
                 frame_id: 1,
@@ -732,6 +856,9 @@ mod callstack_tests {
                     ref_bindings: BTreeMap::from([("missing".to_string(), "target".to_string())]),
                 }),
                 var_types: BTreeMap::new(),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
         ];
         let error = engine
@@ -781,6 +908,9 @@ mod callstack_tests {
                 script_root: true,
                 return_continuation: None,
                 var_types: BTreeMap::from([("caller".to_string(), number_ty.clone())]),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
             RuntimeFrame {
                 frame_id: 11,
@@ -795,6 +925,9 @@ mod callstack_tests {
                     ref_bindings: BTreeMap::from([("x".to_string(), "caller".to_string())]),
                 }),
                 var_types: BTreeMap::from([("x".to_string(), number_ty.clone())]),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
         ];
         engine
@@ -816,6 +949,9 @@ mod callstack_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::new(),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         engine
             .execute_return("return")
@@ -836,6 +972,9 @@ mod callstack_tests {
                 ref_bindings: BTreeMap::new(),
             }),
             var_types: BTreeMap::new(),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         engine
             .execute_return("return")
@@ -853,6 +992,9 @@ mod callstack_tests {
                 script_root: true,
                 return_continuation: None,
                 var_types: BTreeMap::from([("caller".to_string(), number_ty.clone())]),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
             RuntimeFrame {
                 frame_id: 21,
@@ -867,6 +1009,9 @@ mod callstack_tests {
                     ref_bindings: BTreeMap::from([("x".to_string(), "caller".to_string())]),
                 }),
                 var_types: BTreeMap::from([("x".to_string(), number_ty)]),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
         ];
         engine
@@ -914,6 +1059,9 @@ mod callstack_tests {
                         name: "int".to_string(),
                     },
                 )]),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
             RuntimeFrame {
                 frame_id: 31,
@@ -928,6 +1076,9 @@ mod callstack_tests {
                     ref_bindings: BTreeMap::from([("x".to_string(), "caller".to_string())]),
                 }),
                 var_types: BTreeMap::new(),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
         ];
 
@@ -980,6 +1131,9 @@ mod callstack_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::new(),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         no_inherited
             .execute_goto(&lit("next.next"), &[])
@@ -1008,6 +1162,9 @@ mod callstack_tests {
                 script_root: true,
                 return_continuation: None,
                 var_types: BTreeMap::new(),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
             RuntimeFrame {
                 frame_id: 51,
@@ -1018,6 +1175,9 @@ mod callstack_tests {
                 script_root: false,
                 return_continuation: None,
                 var_types: BTreeMap::new(),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
         ];
         let root_index = root_lookup
@@ -1116,6 +1276,9 @@ mod callstack_tests {
                     name: "int".to_string(),
                 },
             )]),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let error = tail
             .execute_call(
@@ -1156,6 +1319,9 @@ mod callstack_tests {
                     name: "int".to_string(),
                 },
             )]),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         tail_ok
             .execute_call(
@@ -1338,6 +1504,9 @@ mod callstack_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::new(),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let error = missing_group
             .execute_call(&lit("main"), &[])
@@ -1347,12 +1516,7 @@ mod callstack_tests {
         let mut ref_read_error = engine_from_sources(map(&[
             (
                 "main.script.xml",
-                r#"
-    <!-- import callee from callee.xml -->
-    <script name="main">
-      <call script="@callee.callee" args="ref:missing"/>
-    </script>
-    "#,
+                r#"<script name="main"><text>main</text></script>"#,
             ),
             (
                 "callee.script.xml",
@@ -1361,7 +1525,13 @@ mod callstack_tests {
         ]));
         ref_read_error.start("main.main", None).expect("start");
         let error = ref_read_error
-            .next_output()
+            .execute_call(
+                &lit("callee.callee"),
+                &[sl_core::CallArgument {
+                    value_expr: "missing".to_string(),
+                    is_ref: true,
+                }],
+            )
             .expect_err("ref read should fail");
         assert_eq!(error.code, "ENGINE_VAR_READ");
 
@@ -1420,6 +1590,9 @@ mod callstack_tests {
                     name: "string".to_string(),
                 },
             )]),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let error = tail_scope_error
             .execute_call(
@@ -1501,6 +1674,9 @@ mod callstack_tests {
                         name: "int".to_string(),
                     },
                 )]),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
             RuntimeFrame {
                 frame_id: 11,
@@ -1520,6 +1696,9 @@ mod callstack_tests {
                         name: "int".to_string(),
                     },
                 )]),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
         ];
         let error = return_write_error
@@ -1558,6 +1737,9 @@ mod callstack_tests {
                         name: "int".to_string(),
                     },
                 )]),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
             RuntimeFrame {
                 frame_id: 31,
@@ -1577,6 +1759,9 @@ mod callstack_tests {
                         name: "int".to_string(),
                     },
                 )]),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
         ];
         let error = target_return_write_error
@@ -1587,7 +1772,7 @@ mod callstack_tests {
         let mut return_target_type_error = engine_from_sources(map(&[
             (
                 "main.script.xml",
-                r#"<script name="main"><goto script="@next.next" args="'bad'"/></script>"#,
+                r#"<script name="main"><goto script="@next.next" args="'bad' + ''"/></script>"#,
             ),
             (
                 "next.script.xml",
@@ -1648,12 +1833,12 @@ mod callstack_tests {
         engine.start("battle.main", None).expect("start");
 
         let explicit = engine
-            .resolve_target_script(&lit("battle.next"), "ERR", "err")
+            .resolve_target_script(&lit("battle.next"), ErrorCode::EngineCallTargetEmpty, "err")
             .expect("explicit qualified target should stay as is");
         assert_eq!(explicit, "battle.next");
 
         let short_literal = engine
-            .resolve_target_script(&lit("next"), "ERR", "err")
+            .resolve_target_script(&lit("next"), ErrorCode::EngineCallTargetEmpty, "err")
             .expect("short literal should stay as-is");
         assert_eq!(short_literal, "next");
 
@@ -1663,7 +1848,7 @@ mod callstack_tests {
         )]));
         plain_engine.start("main.main", None).expect("start");
         let plain = plain_engine
-            .resolve_target_script(&lit("next"), "ERR", "err")
+            .resolve_target_script(&lit("next"), ErrorCode::EngineCallTargetEmpty, "err")
             .expect("short literal should remain unqualified");
         assert_eq!(plain, "next");
 
@@ -1672,7 +1857,7 @@ mod callstack_tests {
             r#"<script name="main"><text>x</text></script>"#,
         )]));
         let idle = idle_engine
-            .resolve_target_script(&lit("next"), "ERR", "err")
+            .resolve_target_script(&lit("next"), ErrorCode::EngineCallTargetEmpty, "err")
             .expect("target resolution without active frame should keep literal");
         assert_eq!(idle, "next");
     }
@@ -1694,10 +1879,13 @@ mod callstack_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::from([("dst".to_string(), ScriptType::Script)]),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
 
         let target = engine
-            .resolve_target_script(&var("dst"), "ERR", "err")
+            .resolve_target_script(&var("dst"), ErrorCode::EngineCallTargetEmpty, "err")
             .expect("short variable target should pass");
         assert_eq!(target, "next");
     }
@@ -1720,9 +1908,12 @@ mod callstack_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::from([("dst".to_string(), ScriptType::Script)]),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let value1 = engine1
-            .resolve_target_script(&var("dst"), "ERR", "err")
+            .resolve_target_script(&var("dst"), ErrorCode::EngineCallTargetEmpty, "err")
             .expect("empty module name pattern should pass resolution");
         assert_eq!(value1, ".script");
 
@@ -1742,9 +1933,12 @@ mod callstack_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::from([("dst".to_string(), ScriptType::Script)]),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let value2 = engine2
-            .resolve_target_script(&var("dst"), "ERR", "err")
+            .resolve_target_script(&var("dst"), ErrorCode::EngineCallTargetEmpty, "err")
             .expect("empty local name pattern should pass resolution");
         assert_eq!(value2, "module.");
 
@@ -1767,9 +1961,12 @@ mod callstack_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::from([("dst".to_string(), ScriptType::Script)]),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let result3 = engine3
-            .resolve_target_script(&var("dst"), "ERR", "err")
+            .resolve_target_script(&var("dst"), ErrorCode::EngineCallTargetEmpty, "err")
             .expect("multi-level namespace target should pass");
         assert_eq!(result3, "module.script.nested");
 
@@ -1792,9 +1989,12 @@ mod callstack_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::from([("dst".to_string(), ScriptType::Script)]),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let value4 = engine4
-            .resolve_target_script(&var("dst"), "ERR", "err")
+            .resolve_target_script(&var("dst"), ErrorCode::EngineCallTargetEmpty, "err")
             .expect("digit-first module segment should pass resolution");
         assert_eq!(value4, "123test.script");
 
@@ -1817,9 +2017,12 @@ mod callstack_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::from([("dst".to_string(), ScriptType::Script)]),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let result = engine5
-            .resolve_target_script(&var("dst"), "ERR", "err")
+            .resolve_target_script(&var("dst"), ErrorCode::EngineCallTargetEmpty, "err")
             .expect("valid qualified name with hyphen should succeed");
         assert_eq!(result, "my-module.my-script");
 
@@ -1842,9 +2045,12 @@ mod callstack_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::from([("dst".to_string(), ScriptType::Script)]),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let value5 = engine6
-            .resolve_target_script(&var("dst"), "ERR", "err")
+            .resolve_target_script(&var("dst"), ErrorCode::EngineCallTargetEmpty, "err")
             .expect("invalid identifier characters should pass resolution");
         assert_eq!(value5, "module.invalid$name");
 
@@ -1864,9 +2070,12 @@ mod callstack_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::from([("dst".to_string(), ScriptType::Script)]),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let value6 = engine7
-            .resolve_target_script(&var("dst"), "ERR", "err")
+            .resolve_target_script(&var("dst"), ErrorCode::EngineCallTargetEmpty, "err")
             .expect("empty segment should pass resolution");
         assert_eq!(value6, "foo..bar");
     }
@@ -1920,6 +2129,9 @@ mod callstack_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::from([("dst".to_string(), ScriptType::Script)]),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let cross_module_error = cross_module
             .execute_call(&lit("shared.hidden"), &[])
@@ -1962,6 +2174,9 @@ mod callstack_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::from([("dst".to_string(), ScriptType::Script)]),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         dynamic_cross_module
             .execute_call(&var("dst"), &[])
@@ -2013,6 +2228,9 @@ mod callstack_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::from([("dst".to_string(), ScriptType::Script)]),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         engine
             .execute_call(&var("dst"), &[])
@@ -2060,6 +2278,9 @@ mod callstack_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::from([("dst".to_string(), ScriptType::Script)]),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         engine
             .execute_goto(&var("dst"), &[])
@@ -2131,6 +2352,7 @@ mod callstack_tests {
             module_name: None, // This is the key - module_name is None
             local_script_name: Some("secret".to_string()),
             kind: sl_core::ScriptKind::Goto,
+            tags: Vec::new(),
             params: vec![],
             root_group_id: "g1".to_string(),
             groups: Default::default(),
@@ -2139,6 +2361,8 @@ mod callstack_tests {
             visible_module_consts: Default::default(),
             visible_globals: vec![],
             invoke_all_functions: Default::default(),
+            host_functions: Vec::new(),
+            node_labels: BTreeMap::new(),
         };
 
         // Directly call validate - should hit line 20 because module_name is None
@@ -2194,6 +2418,7 @@ mod callstack_tests {
             module_name: Some("lib".to_string()),
             local_script_name: Some("secret".to_string()),
             kind: sl_core::ScriptKind::Goto,
+            tags: Vec::new(),
             params: vec![],
             root_group_id: "g2".to_string(),
             groups: Default::default(),
@@ -2202,6 +2427,8 @@ mod callstack_tests {
             visible_module_consts: Default::default(),
             visible_globals: vec![],
             invoke_all_functions: Default::default(),
+            host_functions: Vec::new(),
+            node_labels: BTreeMap::new(),
         };
 
         // Now resolve_current_module_name() should return None (because main is not in scripts)
@@ -2316,6 +2543,9 @@ mod callstack_tests {
             script_root: true,
             return_continuation: Some(invalid_continuation),
             var_types: BTreeMap::new(),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
 
         // Now execute_return should hit line 397-400 and call end_execution
@@ -2381,6 +2611,9 @@ mod callstack_tests {
             script_root: false, // Not a root frame!
             return_continuation: None,
             var_types: BTreeMap::new(),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
 
         let error = engine
@@ -2420,6 +2653,9 @@ mod callstack_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::new(),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
 
         let error = engine
@@ -2480,6 +2716,9 @@ mod callstack_tests {
             script_root: true,
             return_continuation: Some(continuation),
             var_types: BTreeMap::new(),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
 
         let error = engine
@@ -2529,6 +2768,9 @@ mod callstack_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::new(),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
 
         // Try to goto a private script in a different module (lib.secret)
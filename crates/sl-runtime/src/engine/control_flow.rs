@@ -5,14 +5,14 @@ impl ScriptLangEngine {
     pub(super) fn execute_break(&mut self) -> Result<(), ScriptLangError> {
         let while_body_index = self.find_nearest_while_body_frame_index().ok_or_else(|| {
             ScriptLangError::new(
-                "ENGINE_WHILE_CONTROL_TARGET_MISSING",
+                ErrorCode::EngineWhileControlTargetMissing,
                 "No target <while> frame found for <break>.",
             )
         })?;
 
         if while_body_index == 0 {
             return Err(ScriptLangError::new(
-                "ENGINE_WHILE_CONTROL_TARGET_MISSING",
+                ErrorCode::EngineWhileControlTargetMissing,
                 "No owning while frame found.",
             ));
         }
@@ -22,26 +22,27 @@ impl ScriptLangEngine {
         let (_, group) = self.lookup_group(&while_owner.group_id)?;
         let Some(ScriptNode::While { .. }) = group.nodes.get(while_owner.node_index) else {
             return Err(ScriptLangError::new(
-                "ENGINE_WHILE_CONTROL_TARGET_MISSING",
+                ErrorCode::EngineWhileControlTargetMissing,
                 "Owning while node is missing.",
             ));
         };
 
         self.frames.truncate(while_body_index);
         self.frames[while_owner_index].node_index += 1;
+        self.frames[while_owner_index].while_iterations = 0;
         Ok(())
     }
 
     pub(super) fn execute_continue_while(&mut self) -> Result<(), ScriptLangError> {
         let while_body_index = self.find_nearest_while_body_frame_index().ok_or_else(|| {
             ScriptLangError::new(
-                "ENGINE_WHILE_CONTROL_TARGET_MISSING",
+                ErrorCode::EngineWhileControlTargetMissing,
                 "No target <while> frame found for <continue>.",
             )
         })?;
         if while_body_index == 0 {
             return Err(ScriptLangError::new(
-                "ENGINE_WHILE_CONTROL_TARGET_MISSING",
+                ErrorCode::EngineWhileControlTargetMissing,
                 "No owning while frame found.",
             ));
         }
@@ -54,7 +55,7 @@ impl ScriptLangEngine {
         let Some((choice_frame_index, choice_node_index)) = self.find_choice_continue_context()?
         else {
             return Err(ScriptLangError::new(
-                "ENGINE_CHOICE_CONTINUE_TARGET_MISSING",
+                ErrorCode::EngineChoiceContinueTargetMissing,
                 "No target <choice> node found for option <continue>.",
             ));
         };
@@ -152,7 +153,7 @@ mod control_flow_tests {
         let error = resumed
             .resume(snapshot)
             .expect_err("missing group in snapshot should fail");
-        assert_eq!(error.code, "ENGINE_GROUP_NOT_FOUND");
+        assert_eq!(error.code, "SNAPSHOT_GROUP_MISSING");
 
         let mut return_arg_unknown = engine_from_sources(map(&[
             (
@@ -198,6 +199,9 @@ mod control_flow_tests {
                 script_root: true,
                 return_continuation: None,
                 var_types: BTreeMap::new(),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
             RuntimeFrame {
                 frame_id: 2,
@@ -208,6 +212,9 @@ mod control_flow_tests {
                 script_root: false,
                 return_continuation: None,
                 var_types: BTreeMap::new(),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
         ];
 
@@ -259,6 +266,9 @@ mod control_flow_tests {
                 script_root: true,
                 return_continuation: None,
                 var_types: BTreeMap::new(),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
             RuntimeFrame {
                 frame_id: 2,
@@ -269,6 +279,9 @@ mod control_flow_tests {
                 script_root: false,
                 return_continuation: None,
                 var_types: BTreeMap::new(),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
         ];
         let error = break_engine
@@ -289,6 +302,9 @@ mod control_flow_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::new(),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let error = continue_engine
             .find_choice_continue_context()
@@ -299,4 +315,41 @@ mod control_flow_tests {
             .expect_err("continue choice should surface lookup error");
         assert_eq!(error.code, "ENGINE_GROUP_NOT_FOUND");
     }
+
+    #[test]
+    fn repeat_runs_body_once_before_checking_until_and_supports_break_continue() {
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <temp name="n" type="int">0</temp>
+      <repeat until="true"><text>once</text></repeat>
+      <repeat until="true">
+        <if when="n == 0"><continue/></if>
+        <text>skipped</text>
+      </repeat>
+      <repeat until="n >= 5">
+        <code>n = n + 1;</code>
+        <if when="n == 2"><continue/></if>
+        <if when="n == 4"><break/></if>
+        <text>n=${n}</text>
+      </repeat>
+      <text>done ${n}</text>
+      <end/>
+    </script>
+    "#,
+        )]));
+        engine.start("main", None).expect("start");
+        let mut rendered = Vec::new();
+        loop {
+            match engine.next_output().expect("output") {
+                EngineOutput::Text { text, .. } => rendered.push(text),
+                other => {
+                    assert_eq!(other, EngineOutput::End, "unexpected output");
+                    break;
+                }
+            }
+        }
+        assert_eq!(rendered, vec!["once", "n=1", "n=3", "done 4"]);
+    }
 }
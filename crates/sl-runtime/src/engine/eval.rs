@@ -1,13 +1,16 @@
-use super::lifecycle::ScopeInit;
+use super::lifecycle::{ScopeInit, ScriptFunctionTable};
 use super::once_state::BindingOwner;
+use super::scope::{is_internal_var_name, reserved_var_write_error};
 use super::*;
+use sl_core::FunctionDecl;
 
+/// Matches `${expr}` interpolations, or an escaped `\${` delimiter (no capture group 1).
 fn text_interpolation_regex() -> &'static Regex {
     static REGEX: OnceLock<Regex> = OnceLock::new();
-    REGEX.get_or_init(|| Regex::new(r"\$\{([^{}]+)\}").expect("template regex must compile"))
+    REGEX.get_or_init(|| Regex::new(r"\\\$\{|\$\{([^{}]+)\}").expect("template regex must compile"))
 }
 
-const INVOKE_ERROR_PREFIX: &str = "__sl_err:";
+pub(super) const INVOKE_ERROR_PREFIX: &str = "__sl_err:";
 
 fn parse_invoke_runtime_error(message: &str) -> Option<ScriptLangError> {
     let marker = message.find(INVOKE_ERROR_PREFIX)?;
@@ -23,7 +26,7 @@ fn parse_invoke_runtime_error(message: &str) -> Option<ScriptLangError> {
 }
 
 fn map_rhai_error(
-    default_code: &str,
+    default_code: ErrorCode,
     default_message: String,
     error: Box<EvalAltResult>,
 ) -> ScriptLangError {
@@ -31,9 +34,128 @@ fn map_rhai_error(
     if let Some(mapped) = parse_invoke_runtime_error(&rendered) {
         return mapped;
     }
+    if let EvalAltResult::ErrorArithmetic(message, _) = error.unwrap_inner() {
+        let code = if message.starts_with("Division by zero") {
+            ErrorCode::EngineDivByZero
+        } else {
+            ErrorCode::EngineArithmetic
+        };
+        return ScriptLangError::new(code, default_message);
+    }
     ScriptLangError::new(default_code, default_message)
 }
 
+/// Name the offending source in arithmetic errors so `when`/`<code>` failures are locatable.
+fn attach_arithmetic_source(mut error: ScriptLangError, source: &str) -> ScriptLangError {
+    if matches!(
+        error.kind(),
+        ErrorCode::EngineDivByZero | ErrorCode::EngineArithmetic
+    ) {
+        error.message = format!("{} (in \"{}\")", error.message, source.trim());
+    }
+    error
+}
+
+/// Whether `code` (from [`code_without_literals`]) mentions `name` as a root identifier, not as a
+/// `.field` or inside a longer identifier.
+fn references_identifier(code: &str, name: &str) -> bool {
+    let is_ident_char = |ch: char| ch.is_ascii_alphanumeric() || ch == '_';
+    code.match_indices(name).any(|(start, _)| {
+        let before = code[..start].chars().next_back();
+        let after = code[start + name.len()..].chars().next();
+        before.is_none_or(|ch| !is_ident_char(ch) && ch != '.')
+            && after.is_none_or(|ch| !is_ident_char(ch))
+    })
+}
+
+/// `source` with string/char literals and comments blanked to spaces, keeping the `${...}` code
+/// of backtick strings, so identifier scans only see code.
+fn code_without_literals(source: &str) -> String {
+    let mut out = String::with_capacity(source.len());
+    // Open backtick strings, innermost last, each with the brace depth of its current `${...}`.
+    let mut templates: Vec<usize> = Vec::new();
+    let mut in_template_text = false;
+    let mut chars = source.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if in_template_text {
+            match ch {
+                '`' => {
+                    templates.pop();
+                    in_template_text = false;
+                }
+                '$' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    out.push(' ');
+                    in_template_text = false;
+                }
+                _ => {}
+            }
+            out.push(' ');
+            continue;
+        }
+        match ch {
+            '"' | '\'' => {
+                out.push(' ');
+                while let Some(next) = chars.next() {
+                    out.push(' ');
+                    if next == '\\' {
+                        if chars.next().is_some() {
+                            out.push(' ');
+                        }
+                    } else if next == ch {
+                        break;
+                    }
+                }
+            }
+            '`' => {
+                templates.push(0);
+                in_template_text = true;
+                out.push(' ');
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                    out.push(' ');
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                out.push_str("  ");
+                let mut previous = ' ';
+                for next in chars.by_ref() {
+                    out.push(' ');
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            '{' => {
+                if let Some(depth) = templates.last_mut() {
+                    *depth += 1;
+                }
+                out.push(ch);
+            }
+            '}' => match templates.last_mut() {
+                Some(0) => {
+                    in_template_text = true;
+                    out.push(' ');
+                }
+                Some(depth) => {
+                    *depth -= 1;
+                    out.push(ch);
+                }
+                None => out.push(ch),
+            },
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
 fn collect_top_level_let_bindings(source: &str) -> BTreeSet<String> {
     fn is_ident_start(ch: char) -> bool {
         ch.is_ascii_alphabetic() || ch == '_'
@@ -181,7 +303,7 @@ impl ScriptLangEngine {
     ) -> Result<ScopeInit, ScriptLangError> {
         let script = self.scripts.get(script_name).ok_or_else(|| {
             ScriptLangError::new(
-                "ENGINE_SCRIPT_NOT_FOUND",
+                ErrorCode::EngineScriptNotFound,
                 format!("Script \"{}\" not found.", script_name),
             )
         })?;
@@ -195,7 +317,7 @@ impl ScriptLangEngine {
                 value
             } else if matches!(param.r#type, ScriptType::Enum { .. }) {
                 return Err(ScriptLangError::new(
-                    "ENGINE_CALL_ARG_MISSING",
+                    ErrorCode::EngineCallArgMissing,
                     format!(
                         "Call argument \"{}\" is required for enum parameter.",
                         param.name
@@ -210,7 +332,7 @@ impl ScriptLangEngine {
                 .expect("script scope types should contain all declared params");
             if !is_type_compatible(&value, expected_type) {
                 return Err(ScriptLangError::new(
-                    "ENGINE_TYPE_MISMATCH",
+                    ErrorCode::EngineTypeMismatch,
                     format!(
                         "Call argument \"{}\" does not match declared type.",
                         param.name
@@ -222,7 +344,7 @@ impl ScriptLangEngine {
 
         if let Some((name, _)) = arg_values.into_iter().next() {
             return Err(ScriptLangError::new(
-                "ENGINE_CALL_ARG_UNKNOWN",
+                ErrorCode::EngineCallArgUnknown,
                 format!(
                     "Call argument \"{}\" is not declared in target script.",
                     name
@@ -240,24 +362,78 @@ impl ScriptLangEngine {
             let full = captures
                 .get(0)
                 .expect("capture group 0 must exist for each regex capture");
-            let expr = captures
-                .get(1)
-                .expect("capture group 1 must exist for each regex capture");
             output.push_str(&template[last_index..full.start()]);
-            let value = self.execute_rhai(expr.as_str(), true, "text interpolation")?;
-            output.push_str(&slvalue_to_text(&value));
             last_index = full.end();
+            let Some(expr) = captures.get(1) else {
+                output.push_str("${");
+                continue;
+            };
+            let value = self.execute_rhai_dynamic(expr.as_str(), true, "text interpolation")?;
+            let rendered = if value.is_unit() {
+                String::new()
+            } else {
+                slvalue_to_text(&dynamic_to_slvalue(value, self.int_precision_policy)?)
+            };
+            if self.strict_text && rendered.is_empty() {
+                return Err(ScriptLangError::new(
+                    ErrorCode::EngineTextEmptyInterp,
+                    format!(
+                        "Text interpolation \"${{{}}}\" rendered an empty value.",
+                        expr.as_str()
+                    ),
+                ));
+            }
+            output.push_str(&rendered);
         }
         output.push_str(&template[last_index..]);
         Ok(output)
     }
 
+    /// Evaluate a `<text expr="...">` line; the result is shown as-is, without interpolation.
+    pub(super) fn render_text_expression(&mut self, expr: &str) -> Result<String, ScriptLangError> {
+        let value = self.execute_rhai_dynamic(expr, true, "text expression")?;
+        if value.is_unit() {
+            return Ok(String::new());
+        }
+        Ok(slvalue_to_text(&dynamic_to_slvalue(
+            value,
+            self.int_precision_policy,
+        )?))
+    }
+
+    /// Render narration shown to the player, then run it through the host text filter.
+    pub(super) fn render_narration(&mut self, template: &str) -> Result<String, ScriptLangError> {
+        let rendered = self.render_text(template)?;
+        Ok(self.apply_text_filter(rendered))
+    }
+
+    pub(super) fn apply_text_filter(&self, text: String) -> String {
+        match &self.text_filter {
+            Some(filter) => filter(&text),
+            None => text,
+        }
+    }
+
+    /// Resolve the template of a `<text key="...">` node from the locale table.
+    pub(super) fn resolve_locale_text(&self, key: &str) -> Result<String, ScriptLangError> {
+        if let Some(template) = self.locale.as_ref().and_then(|locale| locale.get(key)) {
+            return Ok(template.clone());
+        }
+        if self.locale_fallback_to_key {
+            return Ok(key.to_string());
+        }
+        Err(ScriptLangError::new(
+            ErrorCode::EngineLocaleKeyMissing,
+            format!("Locale key \"{}\" is not defined.", key),
+        ))
+    }
+
     pub(super) fn eval_boolean(&mut self, expr: &str) -> Result<bool, ScriptLangError> {
         let value = self.eval_expression(expr)?;
         match value {
             SlValue::Bool(value) => Ok(value),
             _ => Err(ScriptLangError::new(
-                "ENGINE_BOOLEAN_EXPECTED",
+                ErrorCode::EngineBooleanExpected,
                 format!("Expression \"{}\" must evaluate to boolean.", expr),
             )),
         }
@@ -267,6 +443,12 @@ impl ScriptLangEngine {
         self.execute_rhai(code, false, "code").map(|_| ())
     }
 
+    /// Run macro-emitted code, which may update the `__sl_` vars its macro declared.
+    pub(super) fn run_internal_code(&mut self, code: &str) -> Result<(), ScriptLangError> {
+        self.execute_rhai_dynamic_with_access(code, false, "code", true)
+            .map(|_| ())
+    }
+
     pub(super) fn eval_expression(&mut self, expr: &str) -> Result<SlValue, ScriptLangError> {
         self.execute_rhai(expr, true, "expression")
     }
@@ -284,13 +466,6 @@ impl ScriptLangEngine {
         expr: &str,
         _module_name: &str,
     ) -> Result<SlValue, ScriptLangError> {
-        if !self.host_functions.names().is_empty() {
-            return Err(ScriptLangError::new(
-                "ENGINE_HOST_FUNCTION_UNSUPPORTED",
-                "Host function invocation is not yet supported in this runtime build.",
-            ));
-        }
-
         let mut namespace_values: BTreeMap<String, BTreeMap<String, SlValue>> = BTreeMap::new();
         for (qualified_name, value) in &self.module_vars_value {
             let Some(decl) = self.module_var_declarations.get(qualified_name) else {
@@ -325,7 +500,7 @@ impl ScriptLangEngine {
             scope.push_dynamic(name.clone(), slvalue_to_dynamic(value));
         }
 
-        let result = self.eval_rhai_source_with_cache(
+        let result = self.eval_initializer_rhai(
             &mut scope,
             &format!("({})", expr),
             "Module global initializer eval failed",
@@ -334,10 +509,10 @@ impl ScriptLangEngine {
             let after_dynamic = scope
                 .get_value::<Dynamic>(&name)
                 .expect("scope should still contain global snapshot bindings");
-            let after = dynamic_to_slvalue(after_dynamic)?;
+            let after = dynamic_to_slvalue(after_dynamic, self.int_precision_policy)?;
             if after != before {
                 return Err(ScriptLangError::new(
-                    "ENGINE_GLOBAL_READONLY",
+                    ErrorCode::EngineGlobalReadonly,
                     format!(
                         "global data \"{}\" is readonly and cannot be mutated.",
                         name
@@ -354,13 +529,6 @@ impl ScriptLangEngine {
         expr: &str,
         _module_name: &str,
     ) -> Result<SlValue, ScriptLangError> {
-        if !self.host_functions.names().is_empty() {
-            return Err(ScriptLangError::new(
-                "ENGINE_HOST_FUNCTION_UNSUPPORTED",
-                "Host function invocation is not yet supported in this runtime build.",
-            ));
-        }
-
         let mut namespace_values: BTreeMap<String, BTreeMap<String, SlValue>> = BTreeMap::new();
         for (qualified_name, value) in &self.module_consts_value {
             let Some(decl) = self.module_const_declarations.get(qualified_name) else {
@@ -386,7 +554,7 @@ impl ScriptLangEngine {
             scope.push_dynamic(name.clone(), slvalue_to_dynamic(value));
         }
 
-        let result = self.eval_rhai_source_with_cache(
+        let result = self.eval_initializer_rhai(
             &mut scope,
             &format!("({})", expr),
             "Module const initializer eval failed",
@@ -396,10 +564,10 @@ impl ScriptLangEngine {
             let after_dynamic = scope
                 .get_value::<Dynamic>(&name)
                 .expect("scope should still contain global snapshot bindings");
-            let after = dynamic_to_slvalue(after_dynamic)?;
+            let after = dynamic_to_slvalue(after_dynamic, self.int_precision_policy)?;
             if after != before {
                 return Err(ScriptLangError::new(
-                    "ENGINE_GLOBAL_READONLY",
+                    ErrorCode::EngineGlobalReadonly,
                     format!(
                         "global data \"{}\" is readonly and cannot be mutated.",
                         name
@@ -411,18 +579,24 @@ impl ScriptLangEngine {
         result
     }
 
+    /// `function_table` indexes [`Self::function_tables`]; the prelude marker naming it keeps the
+    /// source, and so the cache key, distinct per table.
     fn get_or_compile_rhai_ast(
         &mut self,
         source: &str,
         context: &str,
+        function_table: Option<usize>,
     ) -> Result<&rhai::AST, ScriptLangError> {
         if !self.rhai_ast_cache.contains_key(source) {
-            let ast = self.rhai_engine.compile(source).map_err(|error| {
+            let mut ast = self.rhai_engine.compile(source).map_err(|error| {
                 ScriptLangError::new(
-                    "ENGINE_EVAL_ERROR",
+                    ErrorCode::EngineEvalError,
                     format!("{}: compile failed: {}", context, error),
                 )
             })?;
+            if let Some(table) = function_table {
+                ast = self.function_tables[table].merge(&ast);
+            }
             self.rhai_ast_cache.insert(source.to_string(), ast);
             #[cfg(test)]
             {
@@ -435,23 +609,66 @@ impl ScriptLangEngine {
             .expect("compiled Rhai AST should be cached"))
     }
 
-    fn eval_rhai_source_with_cache(
+    /// Module `<var>`/`<const>` initializers run outside any script, so no `<uses>` set grants
+    /// them host functions: every one is hidden, and calling a registered one fails with
+    /// `ENGINE_HOST_FUNCTION_UNSUPPORTED`.
+    fn eval_initializer_rhai(
         &mut self,
         scope: &mut Scope<'_>,
         source: &str,
         context: &str,
     ) -> Result<SlValue, ScriptLangError> {
-        let ast = self.get_or_compile_rhai_ast(source, context)?.clone();
+        self.visible_host_functions.borrow_mut().clear();
+        let ast = self.get_or_compile_rhai_ast(source, context, None)?.clone();
+        let value = self
+            .rhai_engine
+            .eval_ast_with_scope::<Dynamic>(scope, &ast)
+            .map_err(|error| {
+                let host_name = match error.unwrap_inner() {
+                    EvalAltResult::ErrorFunctionNotFound(signature, _) => self
+                        .host_functions
+                        .names()
+                        .iter()
+                        .find(|name| signature.split(" (").next() == Some(name.as_str())),
+                    _ => None,
+                };
+                match host_name {
+                    Some(name) => ScriptLangError::new(
+                        ErrorCode::EngineHostFunctionUnsupported,
+                        format!(
+                            "Module initializers cannot call host function \"{}\".",
+                            name
+                        ),
+                    ),
+                    None => map_rhai_error(
+                        ErrorCode::EngineEvalError,
+                        format!("{}: {}", context, error),
+                        error,
+                    ),
+                }
+            })?;
+        dynamic_to_slvalue(value, self.int_precision_policy)
+    }
+
+    fn eval_rhai_dynamic_with_cache(
+        &mut self,
+        scope: &mut Scope<'_>,
+        source: &str,
+        context: &str,
+        function_table: Option<usize>,
+    ) -> Result<Dynamic, ScriptLangError> {
+        let ast = self
+            .get_or_compile_rhai_ast(source, context, function_table)?
+            .clone();
         self.rhai_engine
             .eval_ast_with_scope::<Dynamic>(scope, &ast)
             .map_err(|error| {
                 map_rhai_error(
-                    "ENGINE_EVAL_ERROR",
+                    ErrorCode::EngineEvalError,
                     format!("{}: {}", context, error),
                     error,
                 )
             })
-            .and_then(dynamic_to_slvalue)
     }
 
     fn run_rhai_source_with_cache(
@@ -459,13 +676,16 @@ impl ScriptLangEngine {
         scope: &mut Scope<'_>,
         source: &str,
         context: &str,
+        function_table: Option<usize>,
     ) -> Result<(), ScriptLangError> {
-        let ast = self.get_or_compile_rhai_ast(source, context)?.clone();
+        let ast = self
+            .get_or_compile_rhai_ast(source, context, function_table)?
+            .clone();
         self.rhai_engine
             .run_ast_with_scope(scope, &ast)
             .map_err(|error| {
                 map_rhai_error(
-                    "ENGINE_EVAL_ERROR",
+                    ErrorCode::EngineEvalError,
                     format!("{}: {}", context, error),
                     error,
                 )
@@ -492,17 +712,39 @@ impl ScriptLangEngine {
         is_expression: bool,
         context: &str,
     ) -> Result<SlValue, ScriptLangError> {
+        self.execute_rhai_dynamic(script, is_expression, context)
+            .and_then(|value| dynamic_to_slvalue(value, self.int_precision_policy))
+    }
+
+    /// Like [`Self::execute_rhai_with_mode`], but returns the raw Rhai result so callers can
+    /// inspect values (such as `()`) that have no `SlValue` representation.
+    pub(super) fn execute_rhai_dynamic(
+        &mut self,
+        script: &str,
+        is_expression: bool,
+        context: &str,
+    ) -> Result<Dynamic, ScriptLangError> {
+        self.execute_rhai_dynamic_with_access(script, is_expression, context, false)
+    }
+
+    fn execute_rhai_dynamic_with_access(
+        &mut self,
+        script: &str,
+        is_expression: bool,
+        context: &str,
+        allow_internal_writes: bool,
+    ) -> Result<Dynamic, ScriptLangError> {
         let script_name = self.resolve_current_script_name().unwrap_or_default();
         let script_decl = self.scripts.get(&script_name).ok_or_else(|| {
-            ScriptLangError::new("ENGINE_SCRIPT_MISSING", "Current script missing.")
+            ScriptLangError::new(ErrorCode::EngineScriptMissing, "Current script missing.")
         })?;
 
-        if !self.host_functions.names().is_empty() {
-            return Err(ScriptLangError::new(
-                "ENGINE_HOST_FUNCTION_UNSUPPORTED",
-                "Host function invocation is not yet supported in this runtime build.",
-            ));
-        }
+        *self.visible_host_functions.borrow_mut() =
+            script_decl.host_functions.iter().cloned().collect();
+        self.node_visits
+            .borrow_mut()
+            .current_script
+            .clone_from(&script_name);
 
         let (mutable_bindings, mutable_order) = self.collect_mutable_bindings();
         let visible_globals = script_decl.visible_globals.clone();
@@ -526,7 +768,7 @@ impl ScriptLangEngine {
                 .cloned()
                 .ok_or_else(|| {
                     ScriptLangError::new(
-                        "ENGINE_MODULE_GLOBAL_MISSING",
+                        ErrorCode::EngineModuleGlobalMissing,
                         format!(
                             "Module global \"{}\" is not initialized.",
                             decl.qualified_name
@@ -545,7 +787,7 @@ impl ScriptLangEngine {
                 .cloned()
                 .ok_or_else(|| {
                     ScriptLangError::new(
-                        "ENGINE_MODULE_CONST_MISSING",
+                        ErrorCode::EngineModuleConstMissing,
                         format!(
                             "Module const \"{}\" is not initialized.",
                             decl.qualified_name
@@ -578,6 +820,10 @@ impl ScriptLangEngine {
             scope.push_dynamic(name, slvalue_to_dynamic(value));
         }
 
+        let function_table = self
+            .function_table_by_script
+            .get(&script_name)
+            .map(|split| split.table);
         let mut code_let_bindings = BTreeSet::new();
         let source = {
             let prelude = self.get_or_build_module_prelude(&script_name)?;
@@ -592,28 +838,31 @@ impl ScriptLangEngine {
         };
 
         let run_result = if is_expression {
-            self.eval_rhai_source_with_cache(
+            self.eval_rhai_dynamic_with_cache(
                 &mut scope,
                 &source,
                 &format!("{} expression eval failed", context),
+                function_table,
             )
         } else {
             self.run_rhai_source_with_cache(
                 &mut scope,
                 &source,
                 &format!("{} code eval failed", context),
+                function_table,
             )
-            .map(|_| SlValue::Bool(true))
-        };
+            .map(|_| Dynamic::TRUE)
+        }
+        .map_err(|error| attach_arithmetic_source(error, script));
 
         for (name, before) in global_snapshot {
             let after_dynamic = scope
                 .get_value::<Dynamic>(&name)
                 .expect("scope should still contain visible globals");
-            let after = dynamic_to_slvalue(after_dynamic)?;
+            let after = dynamic_to_slvalue(after_dynamic, self.int_precision_policy)?;
             if after != before {
                 return Err(ScriptLangError::new(
-                    "ENGINE_GLOBAL_READONLY",
+                    ErrorCode::EngineGlobalReadonly,
                     format!(
                         "global data \"{}\" is readonly and cannot be mutated.",
                         name
@@ -626,7 +875,15 @@ impl ScriptLangEngine {
             let after_dynamic = scope
                 .get_value::<Dynamic>(&name)
                 .expect("scope should still contain mutable bindings");
-            let after = dynamic_to_slvalue(after_dynamic)?;
+            let after = dynamic_to_slvalue(after_dynamic, self.int_precision_policy)?;
+            if is_internal_var_name(&name) {
+                if allow_internal_writes {
+                    self.assign_variable(&name, after)?;
+                } else if after != mutable_bindings[&name].value {
+                    return Err(reserved_var_write_error(&name));
+                }
+                continue;
+            }
             self.write_variable(&name, after)?;
         }
 
@@ -642,7 +899,7 @@ impl ScriptLangEngine {
                 let Some(after_dynamic) = scope.get_value::<Dynamic>(&name) else {
                     continue;
                 };
-                let after = dynamic_to_slvalue(after_dynamic)?;
+                let after = dynamic_to_slvalue(after_dynamic, self.int_precision_policy)?;
                 frame.scope.insert(name.clone(), after);
                 frame.var_types.remove(&name);
             }
@@ -652,10 +909,10 @@ impl ScriptLangEngine {
             let after_dynamic = scope
                 .get_value::<Dynamic>(&symbol)
                 .expect("scope should still contain module global namespace symbols");
-            let after = dynamic_to_slvalue(after_dynamic)?;
+            let after = dynamic_to_slvalue(after_dynamic, self.int_precision_policy)?;
             let SlValue::Map(entries) = after else {
                 return Err(ScriptLangError::new(
-                    "ENGINE_MODULE_GLOBAL_NAMESPACE_TYPE",
+                    ErrorCode::EngineModuleGlobalNamespaceType,
                     format!(
                         "Module global namespace \"{}\" is not a map value.",
                         namespace
@@ -669,7 +926,7 @@ impl ScriptLangEngine {
                     let declared_type =
                         self.module_vars_type.get(&qualified_name).ok_or_else(|| {
                             ScriptLangError::new(
-                                "ENGINE_MODULE_GLOBAL_DECL_MISSING",
+                                ErrorCode::EngineModuleGlobalDeclMissing,
                                 format!(
                                     "Module global \"{}\" declaration is missing.",
                                     qualified_name
@@ -678,7 +935,7 @@ impl ScriptLangEngine {
                         })?;
                     if !is_type_compatible(&value, declared_type) {
                         return Err(ScriptLangError::new(
-                            "ENGINE_TYPE_MISMATCH",
+                            ErrorCode::EngineTypeMismatch,
                             format!(
                                 "Module global \"{}\" does not match declared type.",
                                 qualified_name
@@ -697,7 +954,7 @@ impl ScriptLangEngine {
                         .expect("module_consts_value should contain all declared consts");
                     if value != before {
                         return Err(ScriptLangError::new(
-                            "ENGINE_CONST_READONLY",
+                            ErrorCode::EngineConstReadonly,
                             format!(
                                 "Module const \"{}\" is readonly and cannot be mutated.",
                                 qualified_name
@@ -734,21 +991,39 @@ impl ScriptLangEngine {
         let Some(script_decl) = self.scripts.get(script_name) else {
             return Ok(String::new());
         };
+        let functions = &script_decl.invoke_all_functions;
         let visible_globals = &script_decl.visible_globals;
         let mut out = String::new();
+        let mut table_functions = BTreeSet::new();
+        if let Some(split) = self.function_table_by_script.get(script_name) {
+            // Only the functions left out of the table are declared here.
+            out.push_str(&format!("// function table {}\n", split.table));
+            if split.prelude_functions.is_empty() {
+                return Ok(out);
+            }
+            table_functions = functions
+                .keys()
+                .filter(|name| !split.prelude_functions.contains(*name))
+                .cloned()
+                .collect();
+        }
         out.push_str("let invoke = |name, args| {\n");
         out.push_str(
             "throw \"__sl_err:ENGINE_INVOKE_TARGET_NOT_FOUND:Invoke target not found.\" + name;\n",
         );
         out.push_str("};\n");
-        for (qualified_name, decl) in &self.invoke_all_functions {
+        let closures = functions
+            .iter()
+            .filter(|(name, _)| !table_functions.contains(*name))
+            .collect::<Vec<_>>();
+        for (qualified_name, decl) in &closures {
             let rhai_name = self
                 .invoke_function_symbols
-                .get(qualified_name)
+                .get(*qualified_name)
                 .cloned()
                 .ok_or_else(|| {
                     ScriptLangError::new(
-                        "ENGINE_MODULE_FUNCTION_SYMBOL_MISSING",
+                        ErrorCode::EngineModuleFunctionSymbolMissing,
                         format!(
                             "Missing Rhai function symbol mapping for \"{}\".",
                             qualified_name
@@ -770,11 +1045,11 @@ impl ScriptLangEngine {
             out.push_str(&slvalue_to_rhai_literal(&default_value));
             out.push_str("\n};\n");
         }
-        for (qualified_name, decl) in &self.invoke_all_functions {
+        for (qualified_name, decl) in &closures {
             // invoke_function_symbols is always populated in lifecycle.rs for all invoke_all_functions
             let rhai_name = self
                 .invoke_function_symbols
-                .get(qualified_name)
+                .get(*qualified_name)
                 .cloned()
                 .expect(
                     "invoke_function_symbols should contain mapping for all invoke_all_functions",
@@ -806,15 +1081,150 @@ impl ScriptLangEngine {
         }
 
         out.push_str("invoke = |name, args| {\n");
+        self.push_invoke_dispatch(&mut out, functions, |name| table_functions.contains(name));
+        out.push_str("};\n");
+
+        Ok(out)
+    }
+
+    /// Build `behavior.function_table` for every script from the functions it sees: each becomes
+    /// a Rhai `fn` that declares the visible global data it references as locals, plus an
+    /// `invoke` dispatcher. Scripts seeing the same functions share one compiled table.
+    pub(super) fn build_function_tables(&mut self) -> Result<(), ScriptLangError> {
+        let mut tables = Vec::new();
+        let mut table_by_source = HashMap::new();
+        let mut table_by_script = HashMap::new();
+        for (script_name, script) in &self.scripts {
+            let prelude_functions = self.prelude_only_functions(&script.invoke_all_functions);
+            let source = self.function_table_source(script, &prelude_functions);
+            let table = match table_by_source.get(&source) {
+                Some(table) => *table,
+                None => {
+                    let ast = self.rhai_engine.compile(&source).map_err(|error| {
+                        ScriptLangError::new(
+                            ErrorCode::EngineEvalError,
+                            format!("function table compile failed: {}", error),
+                        )
+                    })?;
+                    tables.push(ast);
+                    table_by_source.insert(source, tables.len() - 1);
+                    tables.len() - 1
+                }
+            };
+            table_by_script.insert(
+                script_name.clone(),
+                ScriptFunctionTable {
+                    table,
+                    prelude_functions,
+                },
+            );
+        }
+        self.function_tables = tables;
+        self.function_table_by_script = table_by_script;
+        self.module_prelude_by_script.clear();
+        Ok(())
+    }
+
+    /// Functions that stay prelude closures under `function_table`: Rhai `fn` bodies cannot see
+    /// the caller's scope, where module globals live, nor closures, so this covers those reading
+    /// module globals plus every function calling one of them directly or through `invoke`.
+    fn prelude_only_functions(
+        &self,
+        functions: &BTreeMap<String, FunctionDecl>,
+    ) -> BTreeSet<String> {
+        let codes = functions
+            .iter()
+            .map(|(name, decl)| (name, code_without_literals(&decl.code)))
+            .collect::<Vec<_>>();
+        let module_globals = module_namespace_symbol("");
+        let mut out = codes
+            .iter()
+            .filter(|(_, code)| code.contains(&module_globals))
+            .map(|(name, _)| (*name).clone())
+            .collect::<BTreeSet<_>>();
+        loop {
+            let callers = codes
+                .iter()
+                .filter(|(name, code)| {
+                    !out.is_empty()
+                        && !out.contains(*name)
+                        && (references_identifier(code, "invoke")
+                            || out.iter().any(|callee| {
+                                self.invoke_function_symbols
+                                    .get(callee)
+                                    .is_some_and(|symbol| references_identifier(code, symbol))
+                            }))
+                })
+                .map(|(name, _)| (*name).clone())
+                .collect::<Vec<_>>();
+            if callers.is_empty() {
+                return out;
+            }
+            out.extend(callers);
+        }
+    }
+
+    /// Source of one script's function table; `invoke` joins it only when no function was left
+    /// to the prelude, which then defines `invoke` as a closure instead.
+    fn function_table_source(
+        &self,
+        script: &ScriptIr,
+        prelude_functions: &BTreeSet<String>,
+    ) -> String {
+        let mut out = String::new();
+        for (qualified_name, decl) in &script.invoke_all_functions {
+            if prelude_functions.contains(qualified_name) {
+                continue;
+            }
+            let code = code_without_literals(&decl.code);
+            let params = decl
+                .params
+                .iter()
+                .map(|param| param.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            out.push_str(&format!(
+                "fn {}({}) {{\n",
+                self.invoke_function_symbols[qualified_name], params
+            ));
+            for global_symbol in &script.visible_globals {
+                if let Some(value) = self.global_data.get(global_symbol) {
+                    if references_identifier(&code, global_symbol) {
+                        out.push_str(&format!(
+                            "let {} = {};\n",
+                            global_symbol,
+                            slvalue_to_rhai_literal(value)
+                        ));
+                    }
+                }
+            }
+            out.push_str(&decl.code);
+            out.push_str("\n}\n");
+        }
+        if prelude_functions.is_empty() {
+            out.push_str("fn invoke(name, args) {\n");
+            self.push_invoke_dispatch(&mut out, &script.invoke_all_functions, |_| true);
+            out.push_str("}\n");
+        }
+        out
+    }
+
+    /// Body of `invoke(name, args)`; table functions (`direct_call`) are called directly by name,
+    /// prelude closures through `call(...)`.
+    fn push_invoke_dispatch(
+        &self,
+        out: &mut String,
+        functions: &BTreeMap<String, FunctionDecl>,
+        direct_call: impl Fn(&str) -> bool,
+    ) {
         out.push_str("if type_of(name) != \"string\" || !name.starts_with(\"*\") {\n");
         out.push_str("throw \"__sl_err:ENGINE_INVOKE_TARGET_VAR_TYPE:invoke(fnVar, [args]) requires fnVar to hold a *function reference.\";\n");
         out.push_str("}\n");
         out.push_str("if type_of(args) != \"array\" {\n");
         out.push_str("throw \"__sl_err:ENGINE_INVOKE_ARGS_NOT_ARRAY:invoke(name, [args]) requires args to be an array.\";\n");
         out.push_str("}\n");
-        for qualified_name in self.invoke_all_functions.keys() {
+        for (qualified_name, decl) in functions {
             // Safety: invoke_function_symbols is always populated for all invoke_all_functions keys in lifecycle.rs
-            let decl = self.invoke_all_functions.get(qualified_name).unwrap();
             let target_symbol = self.invoke_function_symbols.get(qualified_name).unwrap();
             out.push_str("if name == \"*");
             out.push_str(qualified_name);
@@ -826,28 +1236,22 @@ impl ScriptLangEngine {
             out.push_str(qualified_name);
             out.push_str(" received unexpected arg count.\";\n");
             out.push_str("}\n");
+            let args = (0..decl.params.len())
+                .map(|index| format!("args[{index}]"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let call = match (direct_call(qualified_name), args.is_empty()) {
+                (true, _) => format!("{}({})", target_symbol, args),
+                (false, true) => format!("call({})", target_symbol),
+                (false, false) => format!("call({}, {})", target_symbol, args),
+            };
             out.push_str("return ");
-            out.push_str("call(");
-            out.push_str(target_symbol);
-            if decl.params.is_empty() {
-                out.push_str(")\n}\n");
-            } else {
-                out.push_str(", ");
-                out.push_str(
-                    &(0..decl.params.len())
-                        .map(|index| format!("args[{index}]"))
-                        .collect::<Vec<_>>()
-                        .join(", "),
-                );
-                out.push_str(")\n}\n");
-            }
+            out.push_str(&call);
+            out.push_str("\n}\n");
         }
         out.push_str(
             "throw \"__sl_err:ENGINE_INVOKE_TARGET_NOT_FOUND:Invoke target not found.\" + name;\n",
         );
-        out.push_str("};\n");
-
-        Ok(out)
     }
 
     #[cfg(test)]
@@ -938,6 +1342,214 @@ mod eval_tests {
         assert_eq!(parsed.message, "boom");
     }
 
+    #[test]
+    pub(super) fn render_text_treats_escaped_delimiter_as_literal() {
+        let mut engine = engine_from_sources(map(&[(
+            "main.xml",
+            r#"<module name="main" export="script:main">
+<script name="main"><text>Price: \${5}</text><text>\${x} costs ${2 + 3}</text></script>
+</module>"#,
+        )]));
+        engine.start("main.main", None).expect("start");
+        assert_eq!(
+            engine.next_output().expect("next"),
+            EngineOutput::Text {
+                text: "Price: ${5}".to_string(),
+                tag: None,
+                speaker: None,
+            }
+        );
+        assert_eq!(
+            engine.next_output().expect("next"),
+            EngineOutput::Text {
+                text: "${x} costs 5".to_string(),
+                tag: None,
+                speaker: None,
+            }
+        );
+    }
+
+    #[test]
+    pub(super) fn render_text_strict_mode_rejects_empty_interpolation() {
+        let files = map(&[(
+            "main.script.xml",
+            r##"
+    <script name="main">
+      <temp name="stats" type="#{int}">#{hp: 3}</temp>
+      <text>hp=${stats.hp} mp=[${stats.mp}]</text>
+    </script>
+    "##,
+        )]);
+
+        let mut lenient = engine_from_sources(files.clone());
+        lenient.start("main", None).expect("start");
+        assert!(matches!(
+            lenient.next_output().expect("next"),
+            EngineOutput::Text { text, .. } if text == "hp=3 mp=[]"
+        ));
+
+        let mut strict = engine_from_sources(files);
+        strict.strict_text = true;
+        strict.start("main", None).expect("start");
+        let error = strict
+            .next_output()
+            .expect_err("empty interpolation should fail in strict mode");
+        assert_eq!(error.code, "ENGINE_TEXT_EMPTY_INTERP");
+        assert!(error.message.contains("${stats.mp}"));
+        assert_eq!(
+            strict
+                .render_text("${stats.hp}")
+                .expect("non-empty renders"),
+            "3"
+        );
+    }
+
+    #[test]
+    pub(super) fn keyed_text_resolves_from_locale_table() {
+        let files = map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <temp name="name" type="string">"Ann"</temp>
+      <text key="intro.greeting" tag="intro"/>
+      <text key="intro.missing"/>
+    </script>
+    "#,
+        )]);
+
+        let mut engine = engine_from_sources(files.clone());
+        engine.locale = Some(BTreeMap::from([(
+            "intro.greeting".to_string(),
+            "Hello, ${name}!".to_string(),
+        )]));
+        engine.start("main", None).expect("start");
+        assert!(matches!(
+            engine.next_output().expect("next"),
+            EngineOutput::Text { text, tag, .. } if text == "Hello, Ann!" && tag.as_deref() == Some("intro")
+        ));
+        let error = engine
+            .next_output()
+            .expect_err("missing locale key should fail");
+        assert_eq!(error.code, "ENGINE_LOCALE_KEY_MISSING");
+        assert!(error.message.contains("intro.missing"));
+
+        let mut fallback = engine_from_sources(files);
+        fallback.locale_fallback_to_key = true;
+        fallback.start("main", None).expect("start");
+        assert!(matches!(
+            fallback.next_output().expect("next"),
+            EngineOutput::Text { text, .. } if text == "intro.greeting"
+        ));
+        assert!(matches!(
+            fallback.next_output().expect("next"),
+            EngineOutput::Text { text, .. } if text == "intro.missing"
+        ));
+    }
+
+    #[test]
+    pub(super) fn code_block_rechecks_rhai_coerced_values_against_declared_types() {
+        for code in [r#"n = n + "x";"#, "n = n * 1.5;"] {
+            let mut engine = engine_from_sources(map(&[(
+                "main.script.xml",
+                &format!(
+                    r#"
+    <script name="main">
+      <temp name="n" type="int">1</temp>
+      <code>{code}</code>
+      <text>${{n}}</text>
+      <end/>
+    </script>
+    "#
+                ),
+            )]));
+            engine.start("main", None).expect("start");
+            let error = engine
+                .next_output()
+                .expect_err("coerced int should be rejected");
+            assert_eq!(error.code, "ENGINE_TYPE_MISMATCH", "code: {code}");
+        }
+    }
+
+    #[test]
+    pub(super) fn nested_object_defaults_allow_deep_writes_without_initializer() {
+        let files = map(&[(
+            "main.xml",
+            r#"
+<module name="main" export="script:main">
+  <type name="Stats">
+    <field name="hp" type="int"/>
+  </type>
+  <type name="Player">
+    <field name="name" type="string"/>
+    <field name="stats" type="Stats"/>
+    <field name="items" type="Stats[]"/>
+  </type>
+  <script name="main">
+    <temp name="p" type="Player"/>
+    <code>p.stats.hp = 5;</code>
+    <text>${p.stats.hp}/${p.items.len()}/${p.name == ""}</text>
+    <end/>
+  </script>
+</module>
+"#,
+        )]);
+        let mut engine = engine_from_sources(files);
+        engine.start("main.main", None).expect("start");
+        assert!(matches!(
+            engine.next_output().expect("text"),
+            EngineOutput::Text { text, .. } if text == "5/0/true"
+        ));
+    }
+
+    #[test]
+    pub(super) fn division_by_zero_maps_to_dedicated_error_code() {
+        let mut in_condition = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <if when="1 / 0 > 0"><text>never</text></if>
+      <end/>
+    </script>
+    "#,
+        )]));
+        in_condition.start("main", None).expect("start");
+        let error = in_condition
+            .next_output()
+            .expect_err("division by zero in condition should fail");
+        assert_eq!(error.code, "ENGINE_DIV_BY_ZERO");
+        assert!(error.message.contains("1 / 0 > 0"));
+
+        let mut in_code = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <temp name="count" type="int">0</temp>
+      <code>let ratio = 10 / count;</code>
+      <end/>
+    </script>
+    "#,
+        )]));
+        in_code.start("main", None).expect("start");
+        let error = in_code
+            .next_output()
+            .expect_err("division by zero in code should fail");
+        assert_eq!(error.code, "ENGINE_DIV_BY_ZERO");
+        assert!(error.message.contains("10 / count"));
+
+        let mut overflow = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <code>let big = 9223372036854775807 + 1;</code>
+      <end/>
+    </script>
+    "#,
+        )]));
+        overflow.start("main", None).expect("start");
+        let error = overflow.next_output().expect_err("overflow should fail");
+        assert_eq!(error.code, "ENGINE_ARITHMETIC");
+    }
+
     #[test]
     pub(super) fn collect_top_level_let_bindings_ignores_nested_strings_and_comments() {
         let source = r#"
@@ -1214,10 +1826,12 @@ let public = 3;
             ),
         ]));
         let dynamic = slvalue_to_dynamic(&value);
-        let roundtrip = dynamic_to_slvalue(dynamic).expect("from dynamic");
+        let roundtrip =
+            dynamic_to_slvalue(dynamic, IntPrecisionPolicy::Error).expect("from dynamic");
         assert_eq!(roundtrip, value);
 
-        let unsupported = dynamic_to_slvalue(Dynamic::UNIT).expect_err("unsupported type");
+        let unsupported = dynamic_to_slvalue(Dynamic::from_char('a'), IntPrecisionPolicy::Error)
+            .expect_err("unsupported type");
         assert_eq!(unsupported.code, "ENGINE_VALUE_UNSUPPORTED");
 
         let literal = slvalue_to_rhai_literal(&SlValue::Map(BTreeMap::from([(
@@ -1250,10 +1864,11 @@ let public = 3;
             r#"
     <script name="main">
       <temp name="hp" type="int">1</temp>
-      <input var="hp" text="bad"/>
+      <input var="hp" text="bad" format="json"/>
     </script>
     "#,
         )]));
+        rewrite_input_nodes(&mut input_type, "hp", InputFormat::Text);
         input_type.start("main", None).expect("start");
         let error = input_type
             .next_output()
@@ -1283,12 +1898,12 @@ let public = 3;
             r#"
 <script name="main">
   <temp name="count" type="int">1</temp>
-  <code>count = count + 1;</code>
+  <code>count = count + ext_fn();</code>
 </script>
 "#,
         )]);
         let compiled = compile_project_from_sources(files);
-        let mut host_unsupported = ScriptLangEngine::new(ScriptLangEngineOptions {
+        let mut host_hidden = ScriptLangEngine::new(ScriptLangEngineOptions {
             scripts: compiled.scripts,
             global_data: compiled.global_data,
             module_var_declarations: compiled.module_var_declarations,
@@ -1299,16 +1914,140 @@ let public = 3;
                 names: vec!["ext_fn".to_string()],
             })),
             random_seed: Some(1),
-            random_sequence: None,
-            random_sequence_index: None,
             compiler_version: Some(DEFAULT_COMPILER_VERSION.to_string()),
+            ..Default::default()
         })
         .expect("engine should build");
-        host_unsupported.start("main", None).expect("start");
-        let error = host_unsupported
+        host_hidden.start("main", None).expect("start");
+        let error = host_hidden
             .next_output()
-            .expect_err("host functions unsupported");
-        assert_eq!(error.code, "ENGINE_HOST_FUNCTION_UNSUPPORTED");
+            .expect_err("host function without <uses> should be unknown");
+        assert_eq!(error.code, "ENGINE_EVAL_ERROR");
+    }
+
+    #[test]
+    pub(super) fn host_functions_are_only_callable_from_scripts_that_declare_uses() {
+        let compiled = compile_project_from_sources(map(&[
+            (
+                "dice.script.xml",
+                r#"
+<script name="dice">
+  <uses functions="roll, fumble"/>
+  <temp name="total" type="int">roll(2, 6)</temp>
+  <text>${total}</text>
+  <code>fumble();</code>
+</script>
+"#,
+            ),
+            (
+                "plain.script.xml",
+                r#"
+<script name="plain">
+  <temp name="total" type="int">roll(2, 6)</temp>
+</script>
+"#,
+            ),
+        ]));
+        let registry = FnHostFunctionRegistry::new(BTreeMap::new())
+            .with_function("roll", |args: &[SlValue]| match args {
+                [SlValue::Number(count), SlValue::Number(sides)] => {
+                    Ok(SlValue::Number(count * sides))
+                }
+                _ => Err(ScriptLangError::new("DICE_ARGS", "roll(count, sides)")),
+            })
+            .with_function("fumble", |_args: &[SlValue]| {
+                Err(ScriptLangError::new("DICE_FUMBLE", "Rolled a one."))
+            });
+        let mut engine = ScriptLangEngine::new(ScriptLangEngineOptions {
+            scripts: compiled.scripts.clone(),
+            host_functions: Some(Arc::new(registry)),
+            ..Default::default()
+        })
+        .expect("engine should build");
+
+        engine.start("dice", None).expect("start");
+        assert!(matches!(
+            engine.next_output().expect("text"),
+            EngineOutput::Text { text, .. } if text == "12"
+        ));
+        let error = engine.next_output().expect_err("host error");
+        assert_eq!(error.code, "DICE_FUMBLE");
+        assert_eq!(error.message, "Rolled a one.");
+
+        engine.start("plain", None).expect("start");
+        let error = engine
+            .next_output()
+            .expect_err("roll is not visible to plain");
+        assert_eq!(error.code, "ENGINE_EVAL_ERROR");
+        assert!(error.message.contains("roll"), "{}", error.message);
+
+        let missing = ScriptLangEngine::new(ScriptLangEngineOptions {
+            scripts: compiled.scripts,
+            ..Default::default()
+        })
+        .err()
+        .expect("unregistered <uses> entry should fail");
+        assert_eq!(missing.code, "ENGINE_HOST_FUNCTION_MISSING");
+    }
+
+    #[test]
+    pub(super) fn logical_operators_skip_the_right_operand_once_the_left_decides() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let compiled = compile_project_from_sources(map(&[(
+            "main.script.xml",
+            r##"
+<script name="main">
+  <uses functions="tick"/>
+  <temp name="stats" type="#{int}">#{}</temp>
+  <if when="false AND tick()"><text>and</text></if>
+  <if when="true || tick()"><text>or</text></if>
+  <if when="stats.contains('hp') AND stats.hp > 0"><text>alive</text></if>
+  <text>${false AND tick()} ${true || tick()}</text>
+  <choice text="Pick">
+    <option text="hidden" when="false AND tick()"><text>x</text></option>
+    <option text="shown" when="true || tick()"><text>y</text></option>
+  </choice>
+  <text>${tick()}</text>
+  <end/>
+</script>
+"##,
+        )]));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let registry = FnHostFunctionRegistry::new(BTreeMap::new()).with_function(
+            "tick",
+            move |_args: &[SlValue]| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok(SlValue::Bool(true))
+            },
+        );
+        let mut engine = ScriptLangEngine::new(ScriptLangEngineOptions {
+            scripts: compiled.scripts,
+            host_functions: Some(Arc::new(registry)),
+            ..Default::default()
+        })
+        .expect("engine should build");
+
+        engine.start("main", None).expect("start");
+        let mut rendered = Vec::new();
+        loop {
+            match engine.next_output().expect("output") {
+                EngineOutput::Text { text, .. } => rendered.push(text),
+                EngineOutput::Choices { items, .. } => {
+                    rendered.extend(items.iter().map(|item| format!("[{}]", item.text)));
+                    engine.choose(0).expect("choose");
+                }
+                EngineOutput::End => break,
+                _ => {}
+            }
+        }
+        assert_eq!(rendered, vec!["or", "false true", "[shown]", "y", "true"]);
+        assert_eq!(
+            calls.load(Ordering::SeqCst),
+            1,
+            "only the final tick() runs"
+        );
     }
 
     #[test]
@@ -1428,7 +2167,7 @@ let public = 3;
         let host_blocked_files = map(&[
             (
                 "shared.xml",
-                r#"<module name="shared" export="var:hp"><var name="hp" type="int">1</var></module>"#,
+                r#"<module name="shared" export="var:hp"><var name="hp" type="int">ext_fn(1)</var></module>"#,
             ),
             (
                 "main.script.xml",
@@ -1450,15 +2189,14 @@ let public = 3;
                 names: vec!["ext_fn".to_string()],
             })),
             random_seed: Some(1),
-            random_sequence: None,
-            random_sequence_index: None,
-            compiler_version: None,
+            ..Default::default()
         })
         .expect("engine");
         let error = host_blocked
             .start("main.main", None)
-            .expect_err("initializer should reject host function mode");
+            .expect_err("initializer should reject host function calls");
         assert_eq!(error.code, "ENGINE_HOST_FUNCTION_UNSUPPORTED");
+        assert!(error.message.contains("ext_fn"), "{}", error.message);
 
         let mut initializer_engine = engine_from_sources_with_global_data(
             map(&[
@@ -1741,6 +2479,7 @@ let public = 3;
                 },
                 initial_value_expr: None,
                 location: sl_core::SourceSpan::synthetic(),
+                session: false,
             },
         );
         let aliases = map_helpers_engine.collect_bundle_module_short_aliases("shared");
@@ -1763,6 +2502,7 @@ let public = 3;
                     },
                     initial_value_expr: None,
                     location: sl_core::SourceSpan::synthetic(),
+                    session: false,
                 },
             ),
             (
@@ -1777,6 +2517,7 @@ let public = 3;
                     },
                     initial_value_expr: Some("1".to_string()),
                     location: sl_core::SourceSpan::synthetic(),
+                    session: false,
                 },
             ),
         ]);
@@ -1871,7 +2612,7 @@ let public = 3;
 <module name="main" export="script:main;var:hp">
   <var name="hp" type="int">7</var>
   <script name="main">
-    <code>hp = ();</code>
+    <code>hp = Fn("f");</code>
   </script>
 </module>
 "#,
@@ -1951,6 +2692,9 @@ let public = 3;
             script_root: false,
             return_continuation: None,
             var_types: BTreeMap::new(),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let error = engine
             .execute_continue_while()
@@ -1978,6 +2722,9 @@ let public = 3;
                 script_root: true,
                 return_continuation: None,
                 var_types: BTreeMap::new(),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
             RuntimeFrame {
                 frame_id: 2,
@@ -1988,6 +2735,9 @@ let public = 3;
                 script_root: false,
                 return_continuation: None,
                 var_types: BTreeMap::new(),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
         ];
         let error = engine
@@ -2048,6 +2798,61 @@ let public = 3;
         assert!(matches!(output, EngineOutput::Text { text, .. } if text == "11"));
     }
 
+    #[test]
+    pub(super) fn prelude_inlines_global_strings_with_special_characters() {
+        let motto = "say \"hi\"\nC:\\saves\\slot1\t${not_interpolated}";
+        let mut engine = engine_from_sources_with_global_data(
+            map(&[
+                (
+                    "shared.xml",
+                    r#"
+<module name="shared" export="function:motto;function:label">
+  <function name="motto" return_type="string">
+    return game.motto;
+  </function>
+  <function name="label" return_type="int">
+    return game.labels["line one"];
+  </function>
+</module>
+"#,
+                ),
+                (
+                    "main.script.xml",
+                    r#"
+<!-- import shared from shared.xml -->
+<script name="main">
+  <temp name="text" type="string">""</temp>
+  <temp name="label" type="int">0</temp>
+  <code>text = shared.motto(); label = shared.label();</code>
+  <text>${text}|${label}</text>
+</script>
+"#,
+                ),
+            ]),
+            BTreeMap::from([(
+                "game".to_string(),
+                SlValue::Map(BTreeMap::from([
+                    ("motto".to_string(), SlValue::String(motto.to_string())),
+                    (
+                        "labels".to_string(),
+                        SlValue::Map(BTreeMap::from([(
+                            "line one".to_string(),
+                            SlValue::Number(7.0),
+                        )])),
+                    ),
+                ])),
+            )]),
+            &["game"],
+        );
+
+        engine.start("main", None).expect("start");
+        let output = engine.next_output().expect("text");
+        assert!(
+            matches!(output, EngineOutput::Text { ref text, .. } if *text == format!("{motto}|7")),
+            "unexpected output: {output:?}"
+        );
+    }
+
     #[test]
     pub(super) fn eval_conversion_and_prelude_error_branches_are_covered() {
         let mut initializer_unit = engine_from_sources_with_global_data(
@@ -2065,7 +2870,7 @@ let public = 3;
         );
         initializer_unit.start("main", None).expect("start");
         let error = initializer_unit
-            .eval_module_global_initializer("{ game = (); 1 }", "shared")
+            .eval_module_global_initializer("{ game = Fn(\"f\"); 1 }", "shared")
             .expect_err("initializer should reject unsupported global value type");
         assert_eq!(error.code, "ENGINE_VALUE_UNSUPPORTED");
 
@@ -2073,7 +2878,7 @@ let public = 3;
             map(&[(
                 "main.script.xml",
                 r#"
-    <script name="main"><code>game = ();</code></script>
+    <script name="main"><code>game = Fn("f");</code></script>
     "#,
             )]),
             BTreeMap::from([(
@@ -2090,7 +2895,7 @@ let public = 3;
 
         let mut mutable_unit = engine_from_sources(map(&[(
             "main.script.xml",
-            r#"<script name="main"><temp name="x" type="int">1</temp><code>x = ();</code></script>"#,
+            r#"<script name="main"><temp name="x" type="int">1</temp><code>x = Fn("f");</code></script>"#,
         )]));
         mutable_unit.start("main", None).expect("start");
         let error = mutable_unit
@@ -2117,7 +2922,7 @@ let public = 3;
                 "main.script.xml",
                 r#"
     <!-- import shared from shared.xml -->
-    <script name="main"><code>__sl_module_ns_shared = ();</code></script>
+    <script name="main"><code>__sl_module_ns_shared = Fn("f");</code></script>
     "#,
             ),
         ]));
@@ -2136,7 +2941,7 @@ let public = 3;
                 "main.script.xml",
                 r#"
 	    <!-- import shared from shared.xml -->
-	    <script name="main"><code>shared.hp = ();</code></script>
+	    <script name="main"><code>shared.hp = Fn("f");</code></script>
 	    "#,
             ),
         ]));
@@ -2232,7 +3037,7 @@ let public = 3;
         let error = engine
             .eval_module_const_initializer("1 / 0", "main")
             .expect_err("division by zero should fail");
-        assert_eq!(error.code, "ENGINE_EVAL_ERROR");
+        assert_eq!(error.code, "ENGINE_DIV_BY_ZERO");
     }
 
     #[test]
@@ -2273,7 +3078,6 @@ let public = 3;
 
     #[test]
     pub(super) fn eval_module_const_initializer_rejects_host_functions() {
-        // Test line 208: when host_functions is not empty, return error
         let mut engine = engine_from_sources(map(&[(
             "main.xml",
             r#"<module name="main" export="script:main;const:base">
@@ -2281,14 +3085,74 @@ let public = 3;
   <script name="main"><text>ok</text></script>
 </module>"#,
         )]));
-        // Register a host function to trigger the error path
         engine.host_functions = Arc::new(TestRegistry {
             names: vec!["test_func".to_string()],
         });
         let error = engine
-            .eval_module_const_initializer("base + 1", "main")
-            .expect_err("host functions should cause error");
+            .eval_module_const_initializer("7 + test_func()", "main")
+            .expect_err("host function calls should cause error");
         assert_eq!(error.code, "ENGINE_HOST_FUNCTION_UNSUPPORTED");
+        assert_eq!(
+            engine
+                .eval_module_const_initializer("\"test_func\" + 1", "main")
+                .expect("names outside a call are not host calls"),
+            SlValue::String("test_func1".to_string())
+        );
+        assert_eq!(
+            engine
+                .eval_module_const_initializer("\"test_func(2)\"", "main")
+                .expect("a call inside a string literal is not a host call"),
+            SlValue::String("test_func(2)".to_string())
+        );
+    }
+
+    #[test]
+    pub(super) fn module_initializers_run_while_host_functions_are_registered() {
+        let compiled = compile_project_from_sources(map(&[
+            (
+                "shared.xml",
+                r#"<module name="shared" export="var:hp;const:max_hp">
+  <const name="max_hp" type="int">10</const>
+  <var name="hp" type="int">max_hp - 1</var>
+</module>"#,
+            ),
+            (
+                "main.script.xml",
+                r#"
+<!-- import shared from shared.xml -->
+<script name="main">
+  <uses functions="heal"/>
+  <code>shared.hp = heal(shared.hp);</code>
+  <text>${shared.hp}/${shared.max_hp}</text>
+</script>
+"#,
+            ),
+        ]));
+        let registry = FnHostFunctionRegistry::new(BTreeMap::new()).with_function(
+            "heal",
+            |args: &[SlValue]| match args {
+                [SlValue::Number(hp)] => Ok(SlValue::Number(hp + 1.0)),
+                _ => Err(ScriptLangError::new("HEAL_ARGS", "heal(hp)")),
+            },
+        );
+        let mut engine = ScriptLangEngine::new(ScriptLangEngineOptions {
+            scripts: compiled.scripts,
+            global_data: compiled.global_data,
+            module_var_declarations: compiled.module_var_declarations,
+            module_var_init_order: compiled.module_var_init_order,
+            module_const_declarations: compiled.module_const_declarations,
+            module_const_init_order: compiled.module_const_init_order,
+            host_functions: Some(Arc::new(registry)),
+            ..Default::default()
+        })
+        .expect("engine should build");
+        engine
+            .start("main", None)
+            .expect("initializers should not be blocked");
+        assert!(matches!(
+            engine.next_output().expect("text"),
+            EngineOutput::Text { text, .. } if text == "10/10"
+        ));
     }
 
     #[test]
@@ -2427,20 +3291,25 @@ let public = 3;
   <script name="main"><text>ok</text></script>
 </module>"#,
         )]));
-        // Manually inject a non-dotted key into invoke_all_functions
-        engine.invoke_all_functions.insert(
-            "nodots_func".to_string(),
-            FunctionDecl {
-                name: "nodots_func".to_string(),
-                params: vec![],
-                return_binding: FunctionReturn {
-                    r#type: sl_core::ScriptType::Script,
+        // Manually inject a non-dotted key into the script's invoke_all_functions
+        engine
+            .scripts
+            .get_mut("main.main")
+            .expect("main script")
+            .invoke_all_functions
+            .insert(
+                "nodots_func".to_string(),
+                FunctionDecl {
+                    name: "nodots_func".to_string(),
+                    params: vec![],
+                    return_binding: FunctionReturn {
+                        r#type: sl_core::ScriptType::Script,
+                        location: sl_core::SourceSpan::synthetic(),
+                    },
+                    code: "true".to_string(),
                     location: sl_core::SourceSpan::synthetic(),
                 },
-                code: "true".to_string(),
-                location: sl_core::SourceSpan::synthetic(),
-            },
-        );
+            );
         // The script execution should still work
         engine.start("main.main", None).expect("start");
         let output = engine.next_output();
@@ -2877,6 +3746,168 @@ let public = 3;
         assert_eq!(error.code, "ENGINE_EVAL_ERROR");
     }
 
+    #[test]
+    fn function_table_matches_prelude_results() {
+        let files = map(&[
+            (
+                "shared.xml",
+                r#"<module name="shared" export="function:twice,fact,label">
+  <function name="twice" args="int:x" return_type="int">return x * 2;</function>
+  <function name="fact" args="int:n" return_type="int">
+    if n LTE 1 { return 1; }
+    return n * fact(n - 1);
+  </function>
+  <function name="label" args="int:x" return_type="string">
+    return "n=" + twice(x) + "/" + bonus;
+  </function>
+</module>"#,
+            ),
+            (
+                "main.xml",
+                r#"<!-- import shared from shared.xml -->
+<module name="main" export="script:main">
+  <script name="main">
+    <function name="triple" args="int:x" return_type="int">return x * 3;</function>
+    <temp name="pick" type="function">*shared.twice</temp>
+    <temp name="total" type="int">0</temp>
+    <code>total = shared.twice(4) + triple(2) + invoke(pick, [5]);</code>
+    <text>${total} ${shared.fact(5)} ${shared.label(3)}</text>
+    <code>invoke(pick, [1, 2]);</code>
+  </script>
+</module>"#,
+            ),
+        ]);
+        let run = |function_table: bool| {
+            let mut engine = engine_from_sources_with_global_data(
+                files.clone(),
+                BTreeMap::from([("bonus".to_string(), SlValue::Number(7.0))]),
+                &["bonus"],
+            );
+            if function_table {
+                engine.build_function_tables().expect("function table");
+            }
+            engine.start("main.main", None).expect("start");
+            engine
+                .outputs()
+                .map(|output| match output {
+                    Ok(EngineOutput::Text { text, .. }) => text,
+                    Ok(other) => format!("{:?}", other),
+                    Err(error) => error.code,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let expected = vec![
+            "24 120 n=6/7".to_string(),
+            "ENGINE_INVOKE_ARG_COUNT_MISMATCH".to_string(),
+        ];
+        assert_eq!(run(false), expected);
+        assert_eq!(run(true), expected);
+    }
+
+    fn outputs_with_and_without_function_table(
+        files: BTreeMap<String, String>,
+        entry: &str,
+    ) -> [Vec<String>; 2] {
+        [false, true].map(|function_table| {
+            let mut engine = engine_from_sources(files.clone());
+            if function_table {
+                engine.build_function_tables().expect("function table");
+            }
+            engine.start(entry, None).expect("start");
+            engine
+                .outputs()
+                .map(|output| match output {
+                    Ok(EngineOutput::Text { text, .. }) => text,
+                    Ok(other) => format!("{:?}", other),
+                    Err(error) => error.code,
+                })
+                .collect()
+        })
+    }
+
+    #[test]
+    fn function_table_leaves_module_global_functions_to_the_prelude() {
+        let files = map(&[(
+            "main.xml",
+            r#"<module name="main" export="script:main">
+  <var name="hp" type="int">3</var>
+  <function name="heal" return_type="int">return hp + 1;</function>
+  <function name="healTwice" return_type="int">return heal() + heal();</function>
+  <function name="twice" args="int:x" return_type="int">return x * 2;</function>
+  <function name="viaInvoke" args="function:f" return_type="int">return invoke(f, [4]);</function>
+  <function name="quoted" return_type="string">return "hp " + twice(1);</function>
+  <script name="main">
+    <temp name="pickHeal" type="function">*main.heal</temp>
+    <temp name="pickTwice" type="function">*main.twice</temp>
+    <code>hp = 5;</code>
+    <text>${heal()} ${healTwice()} ${twice(3)} ${viaInvoke(pickTwice)} ${quoted()}</text>
+    <text>${invoke(pickHeal, [])} ${invoke(pickTwice, [2])}</text>
+    <end/>
+  </script>
+</module>"#,
+        )]);
+        let [prelude, table] = outputs_with_and_without_function_table(files.clone(), "main.main");
+        assert_eq!(prelude, vec!["6 12 6 8 hp 2", "6 4", "End"]);
+        assert_eq!(table, prelude);
+
+        let mut engine = engine_from_sources(files);
+        engine.build_function_tables().expect("function table");
+        let split = &engine.function_table_by_script["main.main"];
+        assert_eq!(
+            split.prelude_functions,
+            ["heal", "healTwice", "viaInvoke"]
+                .into_iter()
+                .flat_map(|name| [name.to_string(), format!("main.{name}")])
+                .collect::<BTreeSet<_>>()
+        );
+    }
+
+    #[test]
+    fn function_table_scopes_functions_per_script() {
+        let files = map(&[(
+            "main.xml",
+            r#"<module name="main" export="script:main,other">
+  <script name="main">
+    <function name="label" return_type="string">return "main";</function>
+    <text>${label()}</text>
+    <call script="@other"/>
+    <end/>
+  </script>
+  <script name="other" kind="call">
+    <function name="label" return_type="string">return "other";</function>
+    <text>${label()}</text>
+    <return/>
+  </script>
+</module>"#,
+        )]);
+        let [prelude, table] = outputs_with_and_without_function_table(files.clone(), "main.main");
+        assert_eq!(prelude, vec!["main", "other", "End"]);
+        assert_eq!(table, prelude);
+
+        let mut engine = engine_from_sources(files);
+        engine.build_function_tables().expect("function table");
+        assert_ne!(
+            engine.function_table_by_script["main.main"].table,
+            engine.function_table_by_script["main.other"].table
+        );
+    }
+
+    #[test]
+    fn references_identifier_skips_literals_and_comments() {
+        let code = code_without_literals(
+            r#"let a = "bonus \" bonus"; let b = 'b'; // bonus
+/* bonus */ let c = `bonus ${ #{ x: bonus }.x } bonus`; gold"#,
+        );
+        assert_eq!(code.matches("bonus").count(), 1);
+        assert!(references_identifier(&code, "bonus"));
+        assert!(references_identifier(&code, "gold"));
+        assert!(!references_identifier(
+            &code_without_literals(r#"return "bonus: " + `${1} bonus`;"#),
+            "bonus"
+        ));
+    }
+
     #[test]
     pub(super) fn invoke_prelude_supports_star_targets_and_module_short_alias() {
         let files = map(&[
@@ -3430,8 +4461,7 @@ let public = 3;
         let mut engine = engine_from_sources(files);
         engine.start("main.main", None).expect("start");
 
-        // Try to run invalid Rhai code via run_code - this triggers compile error at line 464
-        // eval_expression uses eval_rhai_source_with_cache (line 445), not line 464
+        // Invalid Rhai run via run_code fails while compiling the cached AST.
         let result = engine.run_code("this is not valid rhai @#$");
         assert!(result.is_err());
         let error = result.unwrap_err();
@@ -3542,8 +4572,11 @@ let public = 3;
         // Directly call execute_rhai with a manipulated scope containing unsupported type
         // We need to bypass the normal scope construction
         // Instead, let's test dynamic_to_slvalue directly with unsupported types
-        let unit_dynamic = Dynamic::UNIT;
-        let result = crate::helpers::rhai_bridge::dynamic_to_slvalue(unit_dynamic);
+        let unit_dynamic = Dynamic::from_char('a');
+        let result = crate::helpers::rhai_bridge::dynamic_to_slvalue(
+            unit_dynamic,
+            IntPrecisionPolicy::Error,
+        );
         assert!(result.is_err());
         assert_eq!(result.unwrap_err().code, "ENGINE_VALUE_UNSUPPORTED");
     }
@@ -3556,15 +4589,19 @@ let public = 3;
 
         // Test with various unsupported Rhai types
         let char_dynamic = Dynamic::from_char('a');
-        let result = crate::helpers::rhai_bridge::dynamic_to_slvalue(char_dynamic);
+        let result = crate::helpers::rhai_bridge::dynamic_to_slvalue(
+            char_dynamic,
+            IntPrecisionPolicy::Error,
+        );
         assert!(result.is_err());
         assert_eq!(result.unwrap_err().code, "ENGINE_VALUE_UNSUPPORTED");
 
-        // Test with unit type
-        let unit_dynamic = Dynamic::UNIT;
-        let result = crate::helpers::rhai_bridge::dynamic_to_slvalue(unit_dynamic);
-        assert!(result.is_err());
-        assert_eq!(result.unwrap_err().code, "ENGINE_VALUE_UNSUPPORTED");
+        // Unit is null rather than unsupported
+        let result = crate::helpers::rhai_bridge::dynamic_to_slvalue(
+            Dynamic::UNIT,
+            IntPrecisionPolicy::Error,
+        );
+        assert_eq!(result.expect("unit"), SlValue::Null);
     }
 
     #[test]
@@ -3574,7 +4611,7 @@ let public = 3;
             "main.xml",
             r#"<module name="main" export="script:main">
   <script name="main">
-    <code>let x = ();</code>
+    <code>let x = Fn("f");</code>
     <text>done</text>
   </script>
 </module>"#,
@@ -3613,11 +4650,42 @@ let public = 3;
         engine.start("main.main", None).expect("start");
         // Try to set global data to UNIT in module const initializer
         let error = engine
-            .eval_module_const_initializer("{ game = (); base }", "main")
+            .eval_module_const_initializer("{ game = Fn(\"f\"); base }", "main")
             .expect_err("global data UNIT conversion should fail");
         assert_eq!(
             error.code, "ENGINE_VALUE_UNSUPPORTED",
             "Error should be unsupported value type when converting UNIT global data"
         );
     }
+
+    #[test]
+    fn int_precision_policy_controls_integers_beyond_two_pow_53() {
+        let story = map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <temp name="big" type="int">9007199254740992 + 1</temp>
+      <text>${big}</text>
+      <end/>
+    </script>
+    "#,
+        )]);
+        let render = |policy: IntPrecisionPolicy| -> Result<String, ScriptLangError> {
+            let mut engine = engine_from_sources(story.clone());
+            engine.int_precision_policy = policy;
+            engine.start("main", None)?;
+            Ok(output_text(engine.next_output()?).expect("text expected"))
+        };
+
+        let error = render(IntPrecisionPolicy::Error).expect_err("precision loss");
+        assert_eq!(error.code, "ENGINE_INT_PRECISION");
+        assert_eq!(
+            render(IntPrecisionPolicy::Saturate).expect("saturate"),
+            "9007199254740991"
+        );
+        assert_eq!(
+            render(IntPrecisionPolicy::Wrap).expect("wrap"),
+            "-9007199254740991"
+        );
+    }
 }
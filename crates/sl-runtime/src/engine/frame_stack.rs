@@ -1,4 +1,6 @@
-use super::lifecycle::{CompletionKind, PendingBoundary, RuntimeFrame, RuntimeRandomState};
+use super::lifecycle::{
+    CompletionKind, PendingBoundary, PendingChoiceOption, RuntimeFrame, RuntimeRandomState,
+};
 use super::*;
 
 impl ScriptLangEngine {
@@ -6,6 +8,7 @@ impl ScriptLangEngine {
         self.frames.clear();
         self.pending_boundary = None;
         self.waiting_choice = false;
+        self.last_choice = None;
         self.ended = false;
         self.frame_counter = 1;
         self.seeded_rng_state = self.initial_random_seed;
@@ -16,19 +19,32 @@ impl ScriptLangEngine {
             },
             None => RuntimeRandomState::Seeded(self.initial_random_seed),
         };
-        self.module_vars_value.clear();
+        self.random_override.borrow_mut().index = 0;
+        self.retain_session_module_vars();
         self.module_consts_value.clear();
     }
 
+    /// Drop every module global value except `session` vars, which outlive a restart.
+    pub(super) fn retain_session_module_vars(&mut self) {
+        let declarations = &self.module_var_declarations;
+        self.module_vars_value.retain(|qualified_name, _| {
+            declarations
+                .get(qualified_name)
+                .is_some_and(|decl| decl.session)
+        });
+    }
+
     pub(super) fn boundary_output(&self, boundary: &PendingBoundary) -> EngineOutput {
         match boundary {
             PendingBoundary::Choice {
+                frame_id,
                 options,
                 prompt_text,
                 ..
             } => EngineOutput::Choices {
                 items: options.iter().map(|option| option.item.clone()).collect(),
                 prompt_text: prompt_text.clone(),
+                default_index: self.choice_default_index(*frame_id, options),
             },
             PendingBoundary::Input {
                 prompt_text,
@@ -43,17 +59,39 @@ impl ScriptLangEngine {
         }
     }
 
+    /// Position of the choice's `timeout_default` option among the visible options, if shown.
+    pub(super) fn choice_default_index(
+        &self,
+        frame_id: u64,
+        options: &[PendingChoiceOption],
+    ) -> Option<usize> {
+        let frame = &self.frames[self.find_frame_index(frame_id)?];
+        let (_, group) = self.lookup_group(&frame.group_id).ok()?;
+        let Some(ScriptNode::Choice {
+            timeout_default_option_id: Some(default_option_id),
+            ..
+        }) = group.nodes.get(frame.node_index)
+        else {
+            return None;
+        };
+        options
+            .iter()
+            .position(|option| &option.item.id == default_option_id && option.item.enabled)
+    }
+
     #[cfg(test)]
     pub(super) fn top_frame_id(&self) -> Result<u64, ScriptLangError> {
         self.frames
             .last()
             .map(|frame| frame.frame_id)
-            .ok_or_else(|| ScriptLangError::new("ENGINE_NO_FRAME", "No runtime frame available."))
+            .ok_or_else(|| {
+                ScriptLangError::new(ErrorCode::EngineNoFrame, "No runtime frame available.")
+            })
     }
 
     pub(super) fn bump_top_node_index(&mut self, amount: usize) -> Result<(), ScriptLangError> {
         let frame = self.frames.last_mut().ok_or_else(|| {
-            ScriptLangError::new("ENGINE_NO_FRAME", "No runtime frame available.")
+            ScriptLangError::new(ErrorCode::EngineNoFrame, "No runtime frame available.")
         })?;
         frame.node_index += amount;
         Ok(())
@@ -71,21 +109,21 @@ impl ScriptLangEngine {
     ) -> Result<(&str, &sl_core::ImplicitGroup), ScriptLangError> {
         let lookup = self.group_lookup.get(group_id).ok_or_else(|| {
             ScriptLangError::new(
-                "ENGINE_GROUP_NOT_FOUND",
+                ErrorCode::EngineGroupNotFound,
                 format!("Group \"{}\" not found.", group_id),
             )
         })?;
 
         let script = self.scripts.get(&lookup.script_name).ok_or_else(|| {
             ScriptLangError::new(
-                "ENGINE_SCRIPT_NOT_FOUND",
+                ErrorCode::EngineScriptNotFound,
                 format!("Script \"{}\" not found.", lookup.script_name),
             )
         })?;
 
         let group = script.groups.get(&lookup.group_id).ok_or_else(|| {
             ScriptLangError::new(
-                "ENGINE_GROUP_NOT_FOUND",
+                ErrorCode::EngineGroupNotFound,
                 format!("Group \"{}\" missing.", group_id),
             )
         })?;
@@ -109,6 +147,9 @@ impl ScriptLangEngine {
             script_root: true,
             return_continuation,
             var_types,
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         });
         self.frame_counter += 1;
     }
@@ -120,7 +161,7 @@ impl ScriptLangEngine {
     ) -> Result<(), ScriptLangError> {
         if !self.group_lookup.contains_key(group_id) {
             return Err(ScriptLangError::new(
-                "ENGINE_GROUP_NOT_FOUND",
+                ErrorCode::EngineGroupNotFound,
                 format!("Group \"{}\" not found.", group_id),
             ));
         }
@@ -134,6 +175,9 @@ impl ScriptLangEngine {
             script_root: false,
             return_continuation: None,
             var_types: BTreeMap::new(),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         });
         self.frame_counter += 1;
         Ok(())
@@ -151,7 +195,7 @@ impl ScriptLangEngine {
         let Some(continuation) = frame.return_continuation else {
             let (script_name, _) = self.lookup_group(&frame.group_id)?;
             return Err(ScriptLangError::new(
-                "ENGINE_EXPLICIT_END_REQUIRED",
+                ErrorCode::EngineExplicitEndRequired,
                 format!(
                     "Script \"{}\" reached natural end. Goto scripts must terminate with explicit <end/>.",
                     script_name
@@ -167,7 +211,7 @@ impl ScriptLangEngine {
         for (callee_var, caller_path) in continuation.ref_bindings {
             let value = frame.scope.get(&callee_var).cloned().ok_or_else(|| {
                 ScriptLangError::new(
-                    "ENGINE_REF_VALUE_MISSING",
+                    ErrorCode::EngineRefValueMissing,
                     format!("Missing ref value \"{}\" in callee scope.", callee_var),
                 )
             })?;
@@ -190,6 +234,7 @@ mod frame_stack_tests {
             EngineOutput::Debug { .. } => "debug",
             EngineOutput::Choices { .. } => "choices",
             EngineOutput::Input { .. } => "input",
+            EngineOutput::Checkpoint { .. } => "checkpoint",
             EngineOutput::End => "end",
         }
     }
@@ -204,7 +249,7 @@ mod frame_stack_tests {
 
     fn choice_pending_parts(
         pending: PendingBoundary,
-    ) -> Option<(Vec<super::lifecycle::PendingChoiceOption>, Option<String>)> {
+    ) -> Option<(Vec<PendingChoiceOption>, Option<String>)> {
         match pending {
             PendingBoundary::Choice {
                 options,
@@ -250,7 +295,8 @@ mod frame_stack_tests {
         assert_eq!(
             output_kind(&EngineOutput::Text {
                 text: "x".to_string(),
-                tag: None
+                tag: None,
+                speaker: None,
             }),
             "text"
         );
@@ -340,6 +386,9 @@ mod frame_stack_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::new(),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let error = engine
             .finish_frame(1)
@@ -390,6 +439,9 @@ mod frame_stack_tests {
                 script_root: true,
                 return_continuation: None,
                 var_types: BTreeMap::from([("dst".to_string(), number_ty.clone())]),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
             RuntimeFrame {
                 frame_id: 11,
@@ -404,6 +456,9 @@ mod frame_stack_tests {
                     ref_bindings: BTreeMap::from([("src".to_string(), "dst".to_string())]),
                 }),
                 var_types: BTreeMap::from([("src".to_string(), number_ty)]),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
         ];
         engine
@@ -436,6 +491,9 @@ mod frame_stack_tests {
                         name: "int".to_string(),
                     },
                 )]),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
             RuntimeFrame {
                 frame_id: 21,
@@ -460,6 +518,9 @@ mod frame_stack_tests {
                         name: "int".to_string(),
                     },
                 )]),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
         ];
         let error = engine
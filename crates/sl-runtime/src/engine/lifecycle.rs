@@ -1,8 +1,10 @@
+use super::once_state::validate_once_state_keys;
 use super::*;
-use sl_core::FunctionDecl;
 
 pub const DEFAULT_COMPILER_VERSION: &str = "player";
 pub const SNAPSHOT_SCHEMA: &str = "snapshot";
+/// Maximum number of pre-action snapshots kept when `history` is enabled.
+pub(super) const HISTORY_CAPACITY: usize = 32;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RandomStateView {
@@ -16,6 +18,61 @@ pub(super) enum RuntimeRandomState {
     Sequence { values: Vec<u32>, index: usize },
 }
 
+/// Forced `random(n)` results consumed before the regular random state.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct RandomOverride {
+    pub(super) values: Vec<u32>,
+    pub(super) index: usize,
+}
+
+/// Visit counts of labeled nodes, shared with the `sl_visited` builtin.
+#[derive(Debug, Default)]
+pub(super) struct NodeVisits {
+    /// `label -> node id` of every script declaring labels.
+    pub(super) labels_by_script: HashMap<String, BTreeMap<String, String>>,
+    pub(super) labeled_nodes: BTreeSet<String>,
+    /// Script whose Rhai code is being evaluated; labels resolve against it.
+    pub(super) current_script: String,
+    /// Executions by labeled node id.
+    pub(super) counts: BTreeMap<String, usize>,
+}
+
+impl NodeVisits {
+    pub(super) fn from_scripts(scripts: &BTreeMap<String, ScriptIr>) -> Self {
+        let labels_by_script = scripts
+            .values()
+            .filter(|script| !script.node_labels.is_empty())
+            .map(|script| (script.script_name.clone(), script.node_labels.clone()))
+            .collect::<HashMap<_, _>>();
+        let labeled_nodes = labels_by_script
+            .values()
+            .flat_map(|labels| labels.values().cloned())
+            .collect();
+        Self {
+            labels_by_script,
+            labeled_nodes,
+            ..Self::default()
+        }
+    }
+
+    fn count(&self, label: &str) -> Result<INT, Box<EvalAltResult>> {
+        let node_id = self
+            .labels_by_script
+            .get(&self.current_script)
+            .and_then(|labels| labels.get(label))
+            .ok_or_else(|| {
+                Box::new(EvalAltResult::ErrorRuntime(
+                    Dynamic::from(format!(
+                        "sl_visited(label) found no label \"{}\" in script \"{}\".",
+                        label, self.current_script
+                    )),
+                    Position::NONE,
+                ))
+            })?;
+        Ok(self.counts.get(node_id).copied().unwrap_or(0) as INT)
+    }
+}
+
 pub trait HostFunctionRegistry: Send + Sync {
     fn call(&self, name: &str, args: &[SlValue]) -> Result<SlValue, ScriptLangError>;
     fn names(&self) -> &[String];
@@ -29,7 +86,7 @@ pub struct EmptyHostFunctionRegistry {
 impl HostFunctionRegistry for EmptyHostFunctionRegistry {
     fn call(&self, _name: &str, _args: &[SlValue]) -> Result<SlValue, ScriptLangError> {
         Err(ScriptLangError::new(
-            "ENGINE_HOST_FUNCTION_MISSING",
+            ErrorCode::EngineHostFunctionMissing,
             "Host function registry is empty.",
         ))
     }
@@ -39,7 +96,65 @@ impl HostFunctionRegistry for EmptyHostFunctionRegistry {
     }
 }
 
-#[derive(Clone)]
+pub type HostFunction = Box<dyn Fn(&[SlValue]) -> Result<SlValue, ScriptLangError> + Send + Sync>;
+
+/// Post-processes rendered narration (text, choice items, prompts) before it is emitted.
+pub type TextFilter = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+/// Host function registry backed by a map of closures, dispatching calls by name.
+pub struct FnHostFunctionRegistry {
+    functions: BTreeMap<String, HostFunction>,
+    names: Vec<String>,
+}
+
+impl FnHostFunctionRegistry {
+    pub fn new(functions: BTreeMap<String, HostFunction>) -> Self {
+        let names = functions.keys().cloned().collect();
+        Self { functions, names }
+    }
+
+    pub fn with_function(
+        mut self,
+        name: impl Into<String>,
+        function: impl Fn(&[SlValue]) -> Result<SlValue, ScriptLangError> + Send + Sync + 'static,
+    ) -> Self {
+        self.functions.insert(name.into(), Box::new(function));
+        self.names = self.functions.keys().cloned().collect();
+        self
+    }
+}
+
+impl std::fmt::Debug for FnHostFunctionRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnHostFunctionRegistry")
+            .field("names", &self.names)
+            .finish()
+    }
+}
+
+impl HostFunctionRegistry for FnHostFunctionRegistry {
+    fn call(&self, name: &str, args: &[SlValue]) -> Result<SlValue, ScriptLangError> {
+        let function = self.functions.get(name).ok_or_else(|| {
+            ScriptLangError::new(
+                ErrorCode::EngineHostFunctionMissing,
+                format!("Host function \"{}\" is not registered.", name),
+            )
+        })?;
+        function(args)
+    }
+
+    fn names(&self) -> &[String] {
+        &self.names
+    }
+}
+
+/// Engine construction options.
+///
+/// Every field has a neutral default (empty project, no limits override, `trim_input` on), so
+/// embedders can set only what they need and stay source-compatible as options are added:
+/// `ScriptLangEngineOptions { scripts, ..Default::default() }`. Tunables live in the nested
+/// [`EngineLimits`] and [`EngineBehavior`] groups, which default the same way.
+#[derive(Clone, Default)]
 pub struct ScriptLangEngineOptions {
     pub scripts: BTreeMap<String, ScriptIr>,
     pub global_data: BTreeMap<String, SlValue>,
@@ -52,6 +167,110 @@ pub struct ScriptLangEngineOptions {
     pub random_sequence: Option<Vec<u32>>,
     pub random_sequence_index: Option<usize>,
     pub compiler_version: Option<String>,
+    pub initial_once_state: Option<BTreeMap<String, BTreeSet<String>>>,
+    pub clock: Option<i64>,
+    pub host_constants: BTreeMap<String, SlValue>,
+    /// Values `random(n)` returns first (each taken `% n`), in order; once they run out draws
+    /// fall back to `random_sequence`/`random_seed`. The consumed count is kept in snapshots.
+    pub random_override: Option<Vec<u32>>,
+    pub limits: EngineLimits,
+    pub behavior: EngineBehavior,
+}
+
+/// Resource caps of a session (`ScriptLangEngineOptions::limits`); all off by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EngineLimits {
+    /// Rhai sandbox limits; `None` applies [`RhaiResourceLimits::default`].
+    pub rhai: Option<RhaiResourceLimits>,
+    /// Split rendered `<text>` longer than this many characters into sequential page outputs.
+    pub max_text_chars: Option<usize>,
+    /// Default per-loop iteration cap for `<while>` nodes without a `max` attribute.
+    pub max_while_iterations: Option<usize>,
+}
+
+/// Switches for how a session runs and renders (`ScriptLangEngineOptions::behavior`).
+#[derive(Clone)]
+pub struct EngineBehavior {
+    pub track_coverage: bool,
+    pub trim_input: bool,
+    pub strict_text: bool,
+    pub locale: Option<BTreeMap<String, String>>,
+    pub locale_fallback_to_key: bool,
+    pub text_filter: Option<TextFilter>,
+    pub history: bool,
+    /// Compile `<function>`s once into real Rhai functions instead of re-declaring them as
+    /// closures before every evaluation; their bodies must not touch module globals.
+    pub function_table: bool,
+    /// How `choose(index)` treats an index past the end of the pending option list.
+    pub choice_index_policy: ChoiceIndexPolicy,
+    /// What happens when a Rhai integer leaves the range `f64` numbers hold exactly.
+    pub int_precision_policy: IntPrecisionPolicy,
+}
+
+impl Default for EngineBehavior {
+    fn default() -> Self {
+        Self {
+            track_coverage: false,
+            trim_input: true,
+            strict_text: false,
+            locale: None,
+            locale_fallback_to_key: false,
+            text_filter: None,
+            history: false,
+            function_table: false,
+            choice_index_policy: ChoiceIndexPolicy::Strict,
+            int_precision_policy: IntPrecisionPolicy::Error,
+        }
+    }
+}
+
+/// Handling of stale `choose(index)` calls, made against an option list that changed since it
+/// was offered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChoiceIndexPolicy {
+    /// Use the options as offered: an out-of-range index fails with `ENGINE_CHOICE_INDEX` and a
+    /// disabled option with `ENGINE_CHOICE_DISABLED`, keeping the choice pending.
+    #[default]
+    Strict,
+    /// Re-check each offered option's visibility and `enabled_when` at `choose` time and reject a
+    /// target that is out of range, now hidden (`ENGINE_CHOICE_STALE`) or disabled.
+    RejectStale,
+    /// Re-check like `RejectStale`, but take the offered option nearest to the target that is
+    /// still visible and enabled; `choose` returns the option taken.
+    Clamp,
+}
+
+/// Conversion of Rhai integers (`i64`) outside `MIN_EXACT_INT..=MAX_EXACT_INT` to ScriptLang
+/// numbers (`f64`), where they would otherwise silently lose precision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IntPrecisionPolicy {
+    /// Fail the evaluation with `ENGINE_INT_PRECISION`.
+    #[default]
+    Error,
+    /// Clamp to the nearest bound of the exact range.
+    Saturate,
+    /// Keep the low 54 bits, wrapping around like a 54-bit two's complement integer.
+    Wrap,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RhaiResourceLimits {
+    pub max_string_size: usize,
+    pub max_array_size: usize,
+    pub max_map_size: usize,
+    /// Per-evaluation operation budget; `0` disables the limit (the behavior before it existed).
+    pub max_operations: u64,
+}
+
+impl Default for RhaiResourceLimits {
+    fn default() -> Self {
+        Self {
+            max_string_size: 1_048_576,
+            max_array_size: 100_000,
+            max_map_size: 100_000,
+            max_operations: 1_000_000,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -71,6 +290,26 @@ pub(super) struct RuntimeFrame {
     pub(super) script_root: bool,
     pub(super) return_continuation: Option<ContinuationFrame>,
     pub(super) var_types: BTreeMap<String, ScriptType>,
+    /// Pages of the current `<text>` node already emitted when `max_text_chars` paginates it.
+    pub(super) text_page: usize,
+    /// Pages of the current `<text>` node still to emit, rendered once with its first page.
+    pub(super) text_pages: Option<PendingTextPages>,
+    /// Body entries of the `<while>` at `node_index` so far; reset when the loop exits.
+    pub(super) while_iterations: usize,
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct PendingTextPages {
+    pub(super) remaining: VecDeque<String>,
+    pub(super) speaker: Option<String>,
+}
+
+/// A script's part of `behavior.function_table`: the compiled table it evaluates with, plus the
+/// functions left to the closure prelude because Rhai `fn`s cannot reach module globals.
+#[derive(Debug, Clone)]
+pub(super) struct ScriptFunctionTable {
+    pub(super) table: usize,
+    pub(super) prelude_functions: BTreeSet<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -103,6 +342,38 @@ pub(super) struct GroupLookup {
     pub(super) group_id: String,
 }
 
+/// Index every group id to the script that owns it; a module script wins over a legacy
+/// script that declares the same group id.
+pub(super) fn build_group_lookup(
+    scripts: &BTreeMap<String, ScriptIr>,
+) -> HashMap<String, GroupLookup> {
+    let mut group_lookup: HashMap<String, GroupLookup> = HashMap::new();
+    for (script_name, script) in scripts {
+        for group_id in script.groups.keys() {
+            let should_replace = match group_lookup.get(group_id) {
+                None => true,
+                Some(existing_lookup) => {
+                    scripts
+                        .get(&existing_lookup.script_name)
+                        .is_none_or(|existing_script| {
+                            script.module_name.is_some() || existing_script.module_name.is_none()
+                        })
+                }
+            };
+            if should_replace {
+                group_lookup.insert(
+                    group_id.clone(),
+                    GroupLookup {
+                        script_name: script_name.clone(),
+                        group_id: group_id.clone(),
+                    },
+                );
+            }
+        }
+    }
+    group_lookup
+}
+
 pub(super) type ScopeInit = (BTreeMap<String, SlValue>, BTreeMap<String, ScriptType>);
 
 fn normalize_script_builtin_arg(raw: &str) -> Option<String> {
@@ -117,9 +388,273 @@ fn normalize_script_builtin_arg(raw: &str) -> Option<String> {
     Some(normalized.to_string())
 }
 
+/// Args of the numeric builtins: kept as ints when every arg is an int, otherwise widened to
+/// floats so the result type follows the inputs.
+enum NumericBuiltinArgs {
+    Int(Vec<INT>),
+    Float(Vec<FLOAT>),
+}
+
+fn numeric_builtin_args(
+    signature: &str,
+    args: &[Dynamic],
+) -> Result<NumericBuiltinArgs, Box<EvalAltResult>> {
+    if args.iter().all(Dynamic::is_int) {
+        return Ok(NumericBuiltinArgs::Int(
+            args.iter().filter_map(|arg| arg.as_int().ok()).collect(),
+        ));
+    }
+    args.iter()
+        .map(|arg| {
+            arg.as_float()
+                .or_else(|_| arg.as_int().map(|value| value as FLOAT))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(NumericBuiltinArgs::Float)
+        .map_err(|_| {
+            Box::new(EvalAltResult::ErrorRuntime(
+                Dynamic::from(format!("{} expects numeric arguments.", signature)),
+                Position::NONE,
+            ))
+        })
+}
+
+fn numeric_min_max(a: Dynamic, b: Dynamic, max: bool) -> Result<Dynamic, Box<EvalAltResult>> {
+    let signature = if max { "sl_max(a, b)" } else { "sl_min(a, b)" };
+    Ok(match numeric_builtin_args(signature, &[a, b])? {
+        NumericBuiltinArgs::Int(values) if max => Dynamic::from_int(values[0].max(values[1])),
+        NumericBuiltinArgs::Int(values) => Dynamic::from_int(values[0].min(values[1])),
+        NumericBuiltinArgs::Float(values) if max => Dynamic::from_float(values[0].max(values[1])),
+        NumericBuiltinArgs::Float(values) => Dynamic::from_float(values[0].min(values[1])),
+    })
+}
+
+fn numeric_clamp(value: Dynamic, lo: Dynamic, hi: Dynamic) -> Result<Dynamic, Box<EvalAltResult>> {
+    const SIGNATURE: &str = "sl_clamp(value, lo, hi)";
+    let inverted = || {
+        Box::new(EvalAltResult::ErrorRuntime(
+            Dynamic::from(format!("{} expects lo <= hi.", SIGNATURE)),
+            Position::NONE,
+        ))
+    };
+    Ok(match numeric_builtin_args(SIGNATURE, &[value, lo, hi])? {
+        NumericBuiltinArgs::Int(values) => {
+            if values[1] > values[2] {
+                return Err(inverted());
+            }
+            Dynamic::from_int(values[0].clamp(values[1], values[2]))
+        }
+        NumericBuiltinArgs::Float(values) => {
+            if values[1].is_nan() || values[2].is_nan() || values[1] > values[2] {
+                return Err(inverted());
+            }
+            Dynamic::from_float(values[0].clamp(values[1], values[2]))
+        }
+    })
+}
+
+/// Name of a value's kind in ScriptLang type vocabulary (`int`, `float`, `boolean`, `string`,
+/// `array`, `map`); anything else reports the Rhai type name.
+fn type_of(value: Dynamic) -> ImmutableString {
+    let name = if value.is_int() {
+        "int"
+    } else if value.is_float() {
+        "float"
+    } else if value.is_bool() {
+        "boolean"
+    } else if value.is_string() {
+        "string"
+    } else if value.is_array() {
+        "array"
+    } else if value.is_map() {
+        "map"
+    } else if value.is_unit() {
+        "null"
+    } else {
+        value.type_name()
+    };
+    name.into()
+}
+
+/// Shared truthy/falsy vocabulary of `format="boolean"` input and `sl_to_bool`.
+pub(super) fn parse_bool_token(text: &str) -> Option<bool> {
+    match text.trim().to_ascii_lowercase().as_str() {
+        "true" | "yes" | "y" | "on" | "1" => Some(true),
+        "false" | "no" | "n" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+fn to_bool(value: Dynamic) -> Result<bool, Box<EvalAltResult>> {
+    let converted = if let Ok(flag) = value.as_bool() {
+        Some(flag)
+    } else if let Ok(number) = value.as_int() {
+        Some(number != 0)
+    } else if let Ok(number) = value.as_float() {
+        (!number.is_nan()).then_some(number != 0.0)
+    } else if let Some(text) = value.read_lock::<ImmutableString>() {
+        parse_bool_token(text.as_str())
+    } else {
+        None
+    };
+    converted.ok_or_else(|| {
+        Box::new(EvalAltResult::ErrorRuntime(
+            Dynamic::from(format!(
+                "{}ENGINE_CONVERT_ERROR:sl_to_bool(x) cannot convert {} \"{}\" to bool.",
+                super::eval::INVOKE_ERROR_PREFIX,
+                value.type_name(),
+                value
+            )),
+            Position::NONE,
+        ))
+    })
+}
+
+fn split_string(text: ImmutableString, separator: ImmutableString) -> rhai::Array {
+    if text.is_empty() {
+        return rhai::Array::new();
+    }
+    if separator.is_empty() {
+        return text
+            .chars()
+            .map(|ch| Dynamic::from(ch.to_string()))
+            .collect();
+    }
+    text.split(separator.as_str())
+        .map(|part| Dynamic::from(part.to_string()))
+        .collect()
+}
+
+fn join_strings(
+    items: rhai::Array,
+    separator: ImmutableString,
+) -> Result<ImmutableString, Box<EvalAltResult>> {
+    let mut parts = Vec::with_capacity(items.len());
+    for (index, item) in items.iter().enumerate() {
+        let Some(part) = item.read_lock::<ImmutableString>() else {
+            return Err(Box::new(EvalAltResult::ErrorRuntime(
+                Dynamic::from(format!(
+                    "{}ENGINE_CONVERT_ERROR:sl_join(arr, sep) expects string elements, but item {} is {} \"{}\".",
+                    super::eval::INVOKE_ERROR_PREFIX,
+                    index,
+                    item.type_name(),
+                    item
+                )),
+                Position::NONE,
+            )));
+        };
+        parts.push(part.to_string());
+    }
+    Ok(parts.join(separator.as_str()).into())
+}
+
+fn replace_string(
+    text: ImmutableString,
+    from: ImmutableString,
+    to: ImmutableString,
+) -> ImmutableString {
+    if from.is_empty() {
+        return text;
+    }
+    text.replace(from.as_str(), to.as_str()).into()
+}
+
+fn pluralize(
+    count: Dynamic,
+    singular: Dynamic,
+    plural: Dynamic,
+) -> Result<Dynamic, Box<EvalAltResult>> {
+    let is_one = match numeric_builtin_args("sl_pluralize(n, singular, plural)", &[count])? {
+        NumericBuiltinArgs::Int(values) => values[0] == 1,
+        NumericBuiltinArgs::Float(values) => values[0] == 1.0,
+    };
+    Ok(if is_one { singular } else { plural })
+}
+
+/// Both branches are already evaluated when this runs; only the selection is conditional.
+fn select_if(
+    condition: Dynamic,
+    then_value: Dynamic,
+    else_value: Dynamic,
+) -> Result<Dynamic, Box<EvalAltResult>> {
+    let flag = condition.as_bool().map_err(|type_name| {
+        Box::new(EvalAltResult::ErrorRuntime(
+            Dynamic::from(format!(
+                "{}ENGINE_CONVERT_ERROR:sl_if(cond, a, b) expects a boolean condition, got {} \"{}\".",
+                super::eval::INVOKE_ERROR_PREFIX,
+                type_name,
+                condition
+            )),
+            Position::NONE,
+        ))
+    })?;
+    Ok(if flag { then_value } else { else_value })
+}
+
+/// Highest argument count a host function can be called with from script code.
+const MAX_HOST_FUNCTION_ARGS: usize = 8;
+
+/// Surface a host function failure through Rhai while keeping its error code.
+fn host_function_error(error: ScriptLangError) -> Box<EvalAltResult> {
+    Box::new(EvalAltResult::ErrorRuntime(
+        Dynamic::from(format!(
+            "{}{}:{}",
+            super::eval::INVOKE_ERROR_PREFIX,
+            error.code,
+            error.message
+        )),
+        Position::NONE,
+    ))
+}
+
+/// Register every host function for each arity up to `MAX_HOST_FUNCTION_ARGS`.
+///
+/// A call only reaches the registry while its name is in `visible` (the `<uses>` set of the
+/// script being evaluated); otherwise it fails exactly like an unknown function.
+fn register_host_functions(
+    rhai_engine: &mut Engine,
+    registry: &Arc<dyn HostFunctionRegistry>,
+    visible: &Rc<RefCell<BTreeSet<String>>>,
+    int_precision_policy: IntPrecisionPolicy,
+) {
+    for name in registry.names() {
+        for arity in 0..=MAX_HOST_FUNCTION_ARGS {
+            let registry = Arc::clone(registry);
+            let visible = Rc::clone(visible);
+            let function_name = name.clone();
+            rhai_engine.register_raw_fn(
+                name.as_str(),
+                vec![std::any::TypeId::of::<Dynamic>(); arity],
+                move |_context, args| -> Result<Dynamic, Box<EvalAltResult>> {
+                    if !visible.borrow().contains(&function_name) {
+                        return Err(Box::new(EvalAltResult::ErrorFunctionNotFound(
+                            format!("{} ({} args)", function_name, args.len()),
+                            Position::NONE,
+                        )));
+                    }
+                    // Rhai may pass a variable first argument by reference, so taking it would
+                    // clear the caller's variable.
+                    let values = args
+                        .iter()
+                        .map(|arg| dynamic_to_slvalue((*arg).clone(), int_precision_policy))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(host_function_error)?;
+                    registry
+                        .call(&function_name, &values)
+                        .map(|value| slvalue_to_dynamic(&value))
+                        .map_err(host_function_error)
+                },
+            );
+        }
+    }
+}
+
 pub struct ScriptLangEngine {
     pub(super) scripts: BTreeMap<String, ScriptIr>,
     pub(super) host_functions: Arc<dyn HostFunctionRegistry>,
+    /// Host functions callable by the Rhai code currently being evaluated.
+    pub(super) visible_host_functions: Rc<RefCell<BTreeSet<String>>>,
+    pub(super) node_visits: Rc<RefCell<NodeVisits>>,
     pub(super) compiler_version: String,
     pub(super) group_lookup: HashMap<String, GroupLookup>,
     pub(super) global_data: BTreeMap<String, SlValue>,
@@ -130,9 +665,12 @@ pub struct ScriptLangEngine {
     pub(super) module_vars_value: BTreeMap<String, SlValue>,
     pub(super) module_vars_type: BTreeMap<String, ScriptType>,
     pub(super) module_consts_value: BTreeMap<String, SlValue>,
-    pub(super) invoke_all_functions: BTreeMap<String, FunctionDecl>,
     pub(super) invoke_function_symbols: BTreeMap<String, String>,
     pub(super) module_prelude_by_script: HashMap<String, String>,
+    /// Distinct `behavior.function_table` ASTs, shared by scripts that see the same functions.
+    pub(super) function_tables: Vec<rhai::AST>,
+    /// Scripts missing here (all of them unless `function_table` is on) use the full prelude.
+    pub(super) function_table_by_script: HashMap<String, ScriptFunctionTable>,
     pub(super) rhai_ast_cache: HashMap<String, rhai::AST>,
     #[cfg(test)]
     pub(super) rhai_compile_count: usize,
@@ -140,6 +678,7 @@ pub struct ScriptLangEngine {
     pub(super) initial_random_sequence: Option<Vec<u32>>,
     pub(super) rhai_engine: Engine,
     pub(super) shared_rng_state: Rc<RefCell<RuntimeRandomState>>,
+    pub(super) random_override: Rc<RefCell<RandomOverride>>,
 
     pub(super) frames: Vec<RuntimeFrame>,
     pub(super) pending_boundary: Option<PendingBoundary>,
@@ -148,12 +687,43 @@ pub struct ScriptLangEngine {
     pub(super) frame_counter: u64,
     pub(super) seeded_rng_state: u32,
     pub(super) once_state_by_script: BTreeMap<String, BTreeSet<String>>,
+    pub(super) global_once_state: BTreeSet<String>,
+    pub(super) select_count_by_script: BTreeMap<String, BTreeMap<String, u32>>,
+    pub(super) coverage: Option<BTreeMap<String, usize>>,
+    pub(super) trim_input: bool,
+    pub(super) clock: Rc<Cell<i64>>,
+    pub(super) strict_text: bool,
+    pub(super) locale: Option<BTreeMap<String, String>>,
+    pub(super) locale_fallback_to_key: bool,
+    pub(super) text_filter: Option<TextFilter>,
+    pub(super) entry_script: String,
+    pub(super) entry_args: BTreeMap<String, SlValue>,
+    pub(super) history: Option<VecDeque<Snapshot>>,
+    pub(super) max_text_chars: Option<usize>,
+    pub(super) max_while_iterations: Option<usize>,
+    pub(super) choice_index_policy: ChoiceIndexPolicy,
+    pub(super) int_precision_policy: IntPrecisionPolicy,
+    pub(super) last_choice: Option<ChoiceItem>,
 }
 
 impl ScriptLangEngine {
     pub fn new(options: ScriptLangEngineOptions) -> Result<Self, ScriptLangError> {
-        const RESERVED_HOST_BUILTINS: [&str; 5] = [
+        const RESERVED_HOST_BUILTINS: [&str; 19] = [
             "random",
+            "sl_now",
+            "sl_clamp",
+            "sl_min",
+            "sl_max",
+            "sl_to_bool",
+            "sl_typeof",
+            "sl_split",
+            "sl_join",
+            "sl_trim",
+            "sl_replace",
+            "sl_pluralize",
+            "sl_if",
+            "sl_or",
+            "sl_visited",
             "invoke",
             "enum_to_string",
             "is_call_kind_script",
@@ -169,42 +739,51 @@ impl ScriptLangEngine {
             .any(|name| RESERVED_HOST_BUILTINS.contains(&name.as_str()))
         {
             return Err(ScriptLangError::new(
-                "ENGINE_HOST_FUNCTION_RESERVED",
+                ErrorCode::EngineHostFunctionReserved,
                 "hostFunctions cannot register reserved builtin names.",
             ));
         }
 
-        let mut group_lookup: HashMap<String, GroupLookup> = HashMap::new();
+        if options.limits.max_text_chars == Some(0) {
+            return Err(ScriptLangError::new(
+                ErrorCode::EngineMaxTextCharsInvalid,
+                "maxTextChars must be greater than 0.",
+            ));
+        }
+
+        if options.limits.max_while_iterations == Some(0) {
+            return Err(ScriptLangError::new(
+                ErrorCode::EngineMaxWhileIterationsInvalid,
+                "maxWhileIterations must be greater than 0.",
+            ));
+        }
+
+        let once_state_by_script = options.initial_once_state.unwrap_or_default();
+        validate_once_state_keys(&once_state_by_script)?;
+
+        let group_lookup = build_group_lookup(&options.scripts);
 
-        let mut invoke_all_functions = BTreeMap::new();
         let mut invoke_function_symbols = BTreeMap::new();
 
         for (script_name, script) in &options.scripts {
             if script.visible_functions.contains_key("invoke") {
                 return Err(ScriptLangError::new(
-                    "ENGINE_MODULE_FUNCTION_RESERVED",
+                    ErrorCode::EngineModuleFunctionReserved,
                     "Module function name \"invoke\" is reserved for runtime builtin.",
                 ));
             }
-            for group_id in script.groups.keys() {
-                let should_replace = match group_lookup.get(group_id) {
-                    None => true,
-                    Some(existing_lookup) => options
-                        .scripts
-                        .get(&existing_lookup.script_name)
-                        .is_none_or(|existing_script| {
-                            script.module_name.is_some() || existing_script.module_name.is_none()
-                        }),
-                };
-                if should_replace {
-                    group_lookup.insert(
-                        group_id.clone(),
-                        GroupLookup {
-                            script_name: script_name.clone(),
-                            group_id: group_id.clone(),
-                        },
-                    );
-                }
+            if let Some(missing) = script
+                .host_functions
+                .iter()
+                .find(|name| !host_functions.names().contains(name))
+            {
+                return Err(ScriptLangError::new(
+                    ErrorCode::EngineHostFunctionMissing,
+                    format!(
+                        "Script \"{}\" uses host function \"{}\", which is not registered.",
+                        script_name, missing
+                    ),
+                ));
             }
             for function_name in script.visible_functions.keys() {
                 if host_functions
@@ -213,7 +792,7 @@ impl ScriptLangEngine {
                     .any(|name| name == function_name)
                 {
                     return Err(ScriptLangError::new(
-                        "ENGINE_HOST_FUNCTION_CONFLICT",
+                        ErrorCode::EngineHostFunctionConflict,
                         format!(
                             "hostFunctions cannot register \"{}\" because it conflicts with module function.",
                             function_name
@@ -227,7 +806,7 @@ impl ScriptLangEngine {
                 let symbol = rhai_function_symbol(function_name);
                 if let Some(existing) = symbol_to_public.get(&symbol) {
                     return Err(ScriptLangError::new(
-                        "ENGINE_MODULE_FUNCTION_SYMBOL_CONFLICT",
+                        ErrorCode::EngineModuleFunctionSymbolConflict,
                         format!(
                             "Module function \"{}\" conflicts with \"{}\" after Rhai symbol normalization.",
                             function_name, existing
@@ -237,16 +816,12 @@ impl ScriptLangEngine {
                 symbol_to_public.insert(symbol.clone(), function_name.clone());
             }
 
-            for (qualified_name, decl) in &script.invoke_all_functions {
-                if !invoke_all_functions.contains_key(qualified_name) {
-                    invoke_all_functions.insert(qualified_name.clone(), decl.clone());
-                }
+            for qualified_name in script.invoke_all_functions.keys() {
+                invoke_function_symbols
+                    .entry(qualified_name.clone())
+                    .or_insert_with(|| rhai_function_symbol(qualified_name));
             }
         }
-        for qualified_name in invoke_all_functions.keys() {
-            let symbol = rhai_function_symbol(qualified_name);
-            invoke_function_symbols.insert(qualified_name.clone(), symbol);
-        }
         let call_kind_scripts = options
             .scripts
             .iter()
@@ -267,9 +842,21 @@ impl ScriptLangEngine {
             },
             None => RuntimeRandomState::Seeded(initial_random_seed),
         }));
+        let rhai_limits = options.limits.rhai.unwrap_or_default();
+        // `&&`/`||` keep Rhai's short-circuit semantics: `when` guards such as
+        // `m.contains("hp") AND m.hp > 0` rely on the right operand being skipped.
         let mut rhai_engine = Engine::new();
         rhai_engine.set_strict_variables(false);
+        rhai_engine.set_max_string_size(rhai_limits.max_string_size);
+        rhai_engine.set_max_array_size(rhai_limits.max_array_size);
+        rhai_engine.set_max_map_size(rhai_limits.max_map_size);
+        rhai_engine.set_max_operations(rhai_limits.max_operations);
+        let random_override = Rc::new(RefCell::new(RandomOverride {
+            values: options.random_override.unwrap_or_default(),
+            index: 0,
+        }));
         let rng_for_builtin = Rc::clone(&shared_rng_state);
+        let override_for_builtin = Rc::clone(&random_override);
         rhai_engine.register_fn(
             "random",
             move |bound: INT| -> Result<INT, Box<EvalAltResult>> {
@@ -279,6 +866,11 @@ impl ScriptLangEngine {
                         Position::NONE,
                     )));
                 }
+                let mut forced = override_for_builtin.borrow_mut();
+                if let Some(value) = forced.values.get(forced.index).copied() {
+                    forced.index += 1;
+                    return Ok((value % (bound as u32)) as INT);
+                }
                 let mut state = rng_for_builtin.borrow_mut();
                 let value = match &mut *state {
                     RuntimeRandomState::Seeded(seed_state) => {
@@ -297,6 +889,51 @@ impl ScriptLangEngine {
                 Ok(value as INT)
             },
         );
+        let visible_host_functions = Rc::new(RefCell::new(BTreeSet::new()));
+        register_host_functions(
+            &mut rhai_engine,
+            &host_functions,
+            &visible_host_functions,
+            options.behavior.int_precision_policy,
+        );
+        let clock = Rc::new(Cell::new(options.clock.unwrap_or(0)));
+        let clock_for_builtin = Rc::clone(&clock);
+        rhai_engine.register_fn("sl_now", move || -> INT { clock_for_builtin.get() });
+        rhai_engine.register_fn("sl_clamp", numeric_clamp);
+        rhai_engine.register_fn("sl_min", |a: Dynamic, b: Dynamic| {
+            numeric_min_max(a, b, false)
+        });
+        rhai_engine.register_fn("sl_max", |a: Dynamic, b: Dynamic| {
+            numeric_min_max(a, b, true)
+        });
+        rhai_engine.register_fn("sl_to_bool", to_bool);
+        rhai_engine.register_fn("sl_typeof", type_of);
+        rhai_engine.register_fn("sl_split", split_string);
+        rhai_engine.register_fn("sl_join", join_strings);
+        rhai_engine.register_fn("sl_trim", |text: ImmutableString| -> ImmutableString {
+            text.trim().into()
+        });
+        rhai_engine.register_fn("sl_replace", replace_string);
+        rhai_engine.register_fn("sl_pluralize", pluralize);
+        rhai_engine.register_fn("sl_if", select_if);
+        rhai_engine.register_fn(
+            "sl_or",
+            |value: Dynamic, fallback: Dynamic| {
+                if value.is_unit() {
+                    fallback
+                } else {
+                    value
+                }
+            },
+        );
+        let node_visits = Rc::new(RefCell::new(NodeVisits::from_scripts(&options.scripts)));
+        let node_visits_for_builtin = Rc::clone(&node_visits);
+        rhai_engine.register_fn(
+            "sl_visited",
+            move |label: ImmutableString| -> Result<INT, Box<EvalAltResult>> {
+                node_visits_for_builtin.borrow().count(label.as_str())
+            },
+        );
         rhai_engine.register_fn(
             "enum_to_string",
             |value: ImmutableString| -> ImmutableString { value },
@@ -320,14 +957,35 @@ impl ScriptLangEngine {
             .iter()
             .map(|(qualified_name, decl)| (qualified_name.clone(), decl.r#type.clone()))
             .collect();
-        Ok(Self {
-            scripts: options.scripts,
+        let mut scripts = options.scripts;
+        let mut global_data = options.global_data;
+        for (name, value) in options.host_constants {
+            if global_data.contains_key(&name) {
+                return Err(ScriptLangError::new(
+                    ErrorCode::EngineHostConstantConflict,
+                    format!(
+                        "Host constant \"{}\" conflicts with global data of the same name.",
+                        name
+                    ),
+                ));
+            }
+            for script in scripts.values_mut() {
+                if !script.visible_globals.contains(&name) {
+                    script.visible_globals.push(name.clone());
+                }
+            }
+            global_data.insert(name, value);
+        }
+        let mut engine = Self {
+            scripts,
             host_functions,
+            visible_host_functions,
+            node_visits,
             compiler_version: options
                 .compiler_version
                 .unwrap_or_else(|| DEFAULT_COMPILER_VERSION.to_string()),
             group_lookup,
-            global_data: options.global_data,
+            global_data,
             module_var_declarations: options.module_var_declarations,
             module_var_init_order: options.module_var_init_order,
             module_const_declarations: options.module_const_declarations,
@@ -335,9 +993,10 @@ impl ScriptLangEngine {
             module_vars_value: BTreeMap::new(),
             module_vars_type,
             module_consts_value: BTreeMap::new(),
-            invoke_all_functions,
             invoke_function_symbols,
             module_prelude_by_script: HashMap::new(),
+            function_tables: Vec::new(),
+            function_table_by_script: HashMap::new(),
             rhai_ast_cache: HashMap::new(),
             #[cfg(test)]
             rhai_compile_count: 0,
@@ -345,14 +1004,36 @@ impl ScriptLangEngine {
             initial_random_sequence,
             rhai_engine,
             shared_rng_state,
+            random_override,
             frames: Vec::new(),
             pending_boundary: None,
             waiting_choice: false,
             ended: false,
             frame_counter: 1,
             seeded_rng_state: initial_random_seed,
-            once_state_by_script: BTreeMap::new(),
-        })
+            once_state_by_script,
+            global_once_state: BTreeSet::new(),
+            select_count_by_script: BTreeMap::new(),
+            coverage: options.behavior.track_coverage.then(BTreeMap::new),
+            trim_input: options.behavior.trim_input,
+            clock,
+            strict_text: options.behavior.strict_text,
+            locale: options.behavior.locale,
+            locale_fallback_to_key: options.behavior.locale_fallback_to_key,
+            text_filter: options.behavior.text_filter,
+            entry_script: String::new(),
+            entry_args: BTreeMap::new(),
+            history: options.behavior.history.then(VecDeque::new),
+            max_text_chars: options.limits.max_text_chars,
+            max_while_iterations: options.limits.max_while_iterations,
+            choice_index_policy: options.behavior.choice_index_policy,
+            int_precision_policy: options.behavior.int_precision_policy,
+            last_choice: None,
+        };
+        if options.behavior.function_table {
+            engine.build_function_tables()?;
+        }
+        Ok(engine)
     }
 
     pub fn random_state_snapshot(&self) -> RandomStateView {
@@ -365,6 +1046,24 @@ impl ScriptLangEngine {
         }
     }
 
+    /// Seeded generator state, as `snapshot()` records it.
+    pub fn rng_state(&self) -> u32 {
+        self.current_seeded_rng_state()
+    }
+
+    /// Continue the seeded generator from `seed`, e.g. for a "reroll".
+    ///
+    /// Later `random(n)` draws follow `seed`, so the run no longer matches the sequence of the
+    /// original seed; the next `snapshot()` records the new state. `start` still resets to the
+    /// original seed. With `random_sequence`, draws keep using the sequence and only the seeded
+    /// fallback state changes.
+    pub fn reseed(&mut self, seed: u32) {
+        self.seeded_rng_state = seed;
+        if let RuntimeRandomState::Seeded(state) = &mut *self.shared_rng_state.borrow_mut() {
+            *state = seed;
+        }
+    }
+
     pub(super) fn current_seeded_rng_state(&self) -> u32 {
         match &*self.shared_rng_state.borrow() {
             RuntimeRandomState::Seeded(state) => *state,
@@ -376,27 +1075,43 @@ impl ScriptLangEngine {
         &self.compiler_version
     }
 
+    /// Names of the loaded scripts declaring `tag` in their `tags` attribute, in name order.
+    pub fn scripts_with_tag(&self, tag: &str) -> Vec<&str> {
+        self.scripts
+            .iter()
+            .filter(|(_, script)| script.tags.iter().any(|item| item == tag))
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
     pub fn waiting_choice(&self) -> bool {
         self.waiting_choice
     }
 
+    pub fn coverage(&self) -> BTreeMap<String, usize> {
+        self.coverage.clone().unwrap_or_default()
+    }
+
     pub fn start(
         &mut self,
         entry_script_name: &str,
         entry_args: Option<BTreeMap<String, SlValue>>,
     ) -> Result<(), ScriptLangError> {
         self.reset();
+        if let Some(history) = self.history.as_mut() {
+            history.clear();
+        }
         self.initialize_module_consts()?;
         self.initialize_module_vars()?;
         let Some(script) = self.scripts.get(entry_script_name) else {
             return Err(ScriptLangError::new(
-                "ENGINE_SCRIPT_NOT_FOUND",
+                ErrorCode::EngineScriptNotFound,
                 format!("Entry script \"{}\" is not registered.", entry_script_name),
             ));
         };
         if script.access == AccessLevel::Private {
             return Err(ScriptLangError::new(
-                "ENGINE_ENTRY_SCRIPT_PRIVATE",
+                ErrorCode::EngineEntryScriptPrivate,
                 format!(
                     "Entry script \"{}\" is private and cannot be started by host.",
                     entry_script_name
@@ -405,26 +1120,67 @@ impl ScriptLangEngine {
         }
         if script.kind != ScriptKind::Goto {
             return Err(ScriptLangError::new(
-                "ENGINE_ENTRY_SCRIPT_KIND",
+                ErrorCode::EngineEntryScriptKind,
                 format!("Entry script \"{}\" must be goto kind.", entry_script_name),
             ));
         }
         let root_group_id = script.root_group_id.clone();
+        let entry_args = entry_args.unwrap_or_default();
         let (scope, var_types) =
-            self.create_script_root_scope(entry_script_name, entry_args.unwrap_or_default())?;
+            self.create_script_root_scope(entry_script_name, entry_args.clone())?;
         self.push_root_frame(&root_group_id, scope, None, var_types);
+        self.entry_script = entry_script_name.to_string();
+        self.entry_args = entry_args;
+        Ok(())
+    }
+
+    /// Debug-only: reset and resume at an arbitrary group of `script_name` with `scope` as its
+    /// variables. Not for production use: it skips entry/access checks, argument validation and
+    /// declared variable types, and leaves no caller to return to once the group finishes.
+    #[cfg(any(test, feature = "debug-goto"))]
+    pub fn goto(
+        &mut self,
+        script_name: &str,
+        group_id: &str,
+        scope: BTreeMap<String, SlValue>,
+    ) -> Result<(), ScriptLangError> {
+        self.reset();
+        self.initialize_module_consts()?;
+        self.initialize_module_vars()?;
+        let (owner, _) = self.lookup_group(group_id)?;
+        let Some(script) = self.scripts.get(script_name) else {
+            return Err(ScriptLangError::new(
+                ErrorCode::EngineScriptNotFound,
+                format!("Script \"{}\" is not registered.", script_name),
+            ));
+        };
+        if !script.groups.contains_key(group_id) {
+            return Err(ScriptLangError::new(
+                ErrorCode::EngineGotoGroupMismatch,
+                format!(
+                    "Group \"{}\" belongs to script \"{}\", not \"{}\".",
+                    group_id, owner, script_name
+                ),
+            ));
+        }
+        self.push_root_frame(group_id, scope, None, BTreeMap::new());
+        self.entry_script = script_name.to_string();
+        self.entry_args = BTreeMap::new();
         Ok(())
     }
 
     pub(super) fn initialize_module_vars(&mut self) -> Result<(), ScriptLangError> {
-        self.module_vars_value.clear();
+        self.retain_session_module_vars();
         for qualified_name in self.module_var_init_order.clone() {
+            if self.module_vars_value.contains_key(&qualified_name) {
+                continue;
+            }
             let decl = self
                 .module_var_declarations
                 .get(&qualified_name)
                 .cloned()
                 .ok_or_else(|| ScriptLangError::new(
-                        "ENGINE_MODULE_GLOBAL_DECL_MISSING",
+                        ErrorCode::EngineModuleGlobalDeclMissing,
                         format!(
                             "Module global \"{}\" is present in init order but missing from declarations.",
                             qualified_name
@@ -435,7 +1191,7 @@ impl ScriptLangEngine {
                 value = self.eval_module_global_initializer(expr, &decl.namespace)?;
             } else if matches!(decl.r#type, ScriptType::Enum { .. }) {
                 return Err(ScriptLangError::new(
-                    "ENGINE_ENUM_INIT_REQUIRED",
+                    ErrorCode::EngineEnumInitRequired,
                     format!(
                         "Module global \"{}\" with enum type requires explicit Type.Member initializer.",
                         qualified_name
@@ -444,7 +1200,7 @@ impl ScriptLangEngine {
             }
             if !is_type_compatible(&value, &decl.r#type) {
                 return Err(ScriptLangError::new(
-                    "ENGINE_TYPE_MISMATCH",
+                    ErrorCode::EngineTypeMismatch,
                     format!(
                         "Module global \"{}\" does not match declared type.",
                         qualified_name
@@ -474,7 +1230,7 @@ impl ScriptLangEngine {
                 .cloned()
                 .ok_or_else(|| {
                     ScriptLangError::new(
-                        "ENGINE_MODULE_CONST_DECL_MISSING",
+                        ErrorCode::EngineModuleConstDeclMissing,
                         format!(
                             "Module const \"{}\" is present in init order but missing from declarations.",
                             qualified_name
@@ -486,7 +1242,7 @@ impl ScriptLangEngine {
                 value = self.eval_module_const_initializer(expr, &decl.namespace)?;
             } else if matches!(decl.r#type, ScriptType::Enum { .. }) {
                 return Err(ScriptLangError::new(
-                    "ENGINE_ENUM_INIT_REQUIRED",
+                    ErrorCode::EngineEnumInitRequired,
                     format!(
                         "Module const \"{}\" with enum type requires explicit Type.Member initializer.",
                         qualified_name
@@ -495,7 +1251,7 @@ impl ScriptLangEngine {
             }
             if !is_type_compatible(&value, &decl.r#type) {
                 return Err(ScriptLangError::new(
-                    "ENGINE_TYPE_MISMATCH",
+                    ErrorCode::EngineTypeMismatch,
                     format!(
                         "Module const \"{}\" does not match declared type.",
                         qualified_name
@@ -528,6 +1284,7 @@ mod lifecycle_tests {
             EngineOutput::Debug { .. } => "debug",
             EngineOutput::Choices { .. } => "choices",
             EngineOutput::Input { .. } => "input",
+            EngineOutput::Checkpoint { .. } => "checkpoint",
             EngineOutput::End => "end",
         }
     }
@@ -540,6 +1297,64 @@ mod lifecycle_tests {
         assert_eq!(kind, "debug");
     }
 
+    #[test]
+    fn scripts_with_tag_filters_loaded_scripts() {
+        let bundle = sl_compiler::compile_project_bundle_from_xml_map(&map(&[(
+            "main.xml",
+            r#"<module name="main" export="script:main,fight">
+<script name="main" tags="hub"><end/></script>
+<script name="fight" tags="combat,hub"><end/></script>
+</module>"#,
+        )]))
+        .expect("compile");
+        let engine = ScriptLangEngine::new(ScriptLangEngineOptions {
+            scripts: bundle.scripts,
+            ..Default::default()
+        })
+        .expect("new engine");
+        assert_eq!(
+            engine.scripts_with_tag("hub"),
+            vec!["main.fight", "main.main"]
+        );
+        assert_eq!(engine.scripts_with_tag("combat"), vec!["main.fight"]);
+        assert!(engine.scripts_with_tag("shop").is_empty());
+    }
+
+    #[test]
+    fn default_options_build_a_runnable_engine() {
+        let defaults = ScriptLangEngineOptions::default();
+        assert!(defaults.behavior.trim_input);
+        assert!(defaults.scripts.is_empty());
+        assert_eq!(defaults.limits, EngineLimits::default());
+        assert!(defaults.limits.rhai.is_none());
+
+        let compiled = compile_project_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <temp name="name" type="string">""</temp>
+      <input var="name" text="Name?"/>
+      <text>hi ${name}</text>
+    </script>
+    "#,
+        )]));
+        let mut engine = ScriptLangEngine::new(ScriptLangEngineOptions {
+            scripts: compiled.scripts,
+            ..Default::default()
+        })
+        .expect("new engine");
+        engine.start("main", None).expect("start");
+        assert!(matches!(
+            engine.next_output().expect("input"),
+            EngineOutput::Input { .. }
+        ));
+        engine.submit_input("  Bob  ").expect("submit");
+        assert!(matches!(
+            engine.next_output().expect("text"),
+            EngineOutput::Text { text, .. } if text == "hi Bob"
+        ));
+    }
+
     fn random_state_kind(view: &RandomStateView) -> &'static str {
         match view {
             RandomStateView::Seeded { .. } => "seeded",
@@ -547,6 +1362,130 @@ mod lifecycle_tests {
         }
     }
 
+    #[test]
+    pub(super) fn fn_host_function_registry_dispatches_by_name() {
+        let registry = FnHostFunctionRegistry::new(BTreeMap::from([(
+            "double".to_string(),
+            Box::new(|args: &[SlValue]| match args {
+                [SlValue::Number(value)] => Ok(SlValue::Number(value * 2.0)),
+                _ => Err(ScriptLangError::new("TEST_ARGS", "expected one number")),
+            }) as HostFunction,
+        )]))
+        .with_function("greet", |args: &[SlValue]| {
+            Ok(SlValue::String(format!("hi {}", args.len())))
+        });
+
+        assert_eq!(
+            registry.names(),
+            ["double".to_string(), "greet".to_string()]
+        );
+        assert_eq!(
+            registry
+                .call("double", &[SlValue::Number(2.5)])
+                .expect("double"),
+            SlValue::Number(5.0)
+        );
+        assert_eq!(
+            registry
+                .call("greet", &[SlValue::Bool(true)])
+                .expect("greet"),
+            SlValue::String("hi 1".to_string())
+        );
+        assert_eq!(
+            registry
+                .call("double", &[])
+                .expect_err("bad args should fail")
+                .code,
+            "TEST_ARGS"
+        );
+        assert_eq!(
+            registry
+                .call("missing", &[])
+                .expect_err("unknown name should fail")
+                .code,
+            "ENGINE_HOST_FUNCTION_MISSING"
+        );
+
+        let compiled = compile_project_from_sources(map(&[(
+            "main.script.xml",
+            r#"<script name="main"><text>Hi</text></script>"#,
+        )]));
+        let build = |registry: FnHostFunctionRegistry| {
+            ScriptLangEngine::new(ScriptLangEngineOptions {
+                scripts: compiled.scripts.clone(),
+                global_data: compiled.global_data.clone(),
+                module_var_declarations: compiled.module_var_declarations.clone(),
+                module_var_init_order: compiled.module_var_init_order.clone(),
+                module_const_declarations: compiled.module_const_declarations.clone(),
+                module_const_init_order: compiled.module_const_init_order.clone(),
+                host_functions: Some(Arc::new(registry)),
+                ..Default::default()
+            })
+        };
+        let engine = build(registry).expect("engine should accept closure registry");
+        assert_eq!(
+            engine
+                .host_functions
+                .call("double", &[SlValue::Number(1.0)])
+                .expect("double"),
+            SlValue::Number(2.0)
+        );
+        let error = build(
+            FnHostFunctionRegistry::new(BTreeMap::new())
+                .with_function("random", |_: &[SlValue]| Ok(SlValue::Bool(true))),
+        )
+        .err()
+        .expect("reserved name should fail");
+        assert_eq!(error.code, "ENGINE_HOST_FUNCTION_RESERVED");
+    }
+
+    #[test]
+    pub(super) fn fn_host_function_registry_closures_run_from_uses_and_code() {
+        let compiled = compile_project_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+<script name="main">
+  <uses functions="double, greet"/>
+  <temp name="n" type="int">1</temp>
+  <code>n = double(n + 2).to_int();</code>
+  <text>${n} ${greet(n, "x")}</text>
+  <end/>
+</script>
+"#,
+        )]));
+        let registry = FnHostFunctionRegistry::new(BTreeMap::from([(
+            "double".to_string(),
+            Box::new(|args: &[SlValue]| match args {
+                [SlValue::Number(value)] => Ok(SlValue::Number(value * 2.0)),
+                _ => Err(ScriptLangError::new("TEST_ARGS", "expected one number")),
+            }) as HostFunction,
+        )]))
+        .with_function("greet", |args: &[SlValue]| match args {
+            [SlValue::Number(value), SlValue::String(name)] => {
+                Ok(SlValue::String(format!("hi {} {}", name, value)))
+            }
+            _ => Err(ScriptLangError::new(
+                "TEST_ARGS",
+                "expected number and string",
+            )),
+        });
+        let mut engine = ScriptLangEngine::new(ScriptLangEngineOptions {
+            scripts: compiled.scripts,
+            host_functions: Some(Arc::new(registry)),
+            ..Default::default()
+        })
+        .expect("engine should build");
+        engine.start("main", None).expect("start");
+
+        assert_eq!(
+            output_text(engine.next_output().expect("next")).as_deref(),
+            Some("6 hi x 6")
+        );
+        // `n` was passed as the first argument of `greet` and must keep its value.
+        assert_eq!(engine.read_variable("n").expect("n"), SlValue::Number(6.0));
+        assert_eq!(output_kind(&engine.next_output().expect("next")), "end");
+    }
+
     #[test]
     pub(super) fn new_rejects_reserved_host_function_name_random() {
         let files = map(&[(
@@ -565,9 +1504,8 @@ mod lifecycle_tests {
                 names: vec!["random".to_string()],
             })),
             random_seed: Some(1),
-            random_sequence: None,
-            random_sequence_index: None,
             compiler_version: Some(DEFAULT_COMPILER_VERSION.to_string()),
+            ..Default::default()
         });
         assert!(result.is_err());
         let error = result.err().expect("reserved random name should fail");
@@ -592,9 +1530,8 @@ mod lifecycle_tests {
                 names: vec!["invoke".to_string()],
             })),
             random_seed: Some(1),
-            random_sequence: None,
-            random_sequence_index: None,
             compiler_version: Some(DEFAULT_COMPILER_VERSION.to_string()),
+            ..Default::default()
         });
         assert!(result.is_err());
         let error = result.err().expect("reserved invoke name should fail");
@@ -619,9 +1556,8 @@ mod lifecycle_tests {
                 names: vec!["enum_to_string".to_string()],
             })),
             random_seed: Some(1),
-            random_sequence: None,
-            random_sequence_index: None,
             compiler_version: Some(DEFAULT_COMPILER_VERSION.to_string()),
+            ..Default::default()
         });
         assert!(result.is_err());
         let error = result
@@ -648,9 +1584,8 @@ mod lifecycle_tests {
                 names: vec!["is_call_kind_script".to_string()],
             })),
             random_seed: Some(1),
-            random_sequence: None,
-            random_sequence_index: None,
             compiler_version: Some(DEFAULT_COMPILER_VERSION.to_string()),
+            ..Default::default()
         });
         assert!(result.is_err());
         let error = result
@@ -677,9 +1612,8 @@ mod lifecycle_tests {
                 names: vec!["is_goto_kind_script".to_string()],
             })),
             random_seed: Some(1),
-            random_sequence: None,
-            random_sequence_index: None,
             compiler_version: Some(DEFAULT_COMPILER_VERSION.to_string()),
+            ..Default::default()
         });
         assert!(result.is_err());
         let error = result
@@ -721,9 +1655,8 @@ mod lifecycle_tests {
                 names: vec!["shared.addWithGameBonus".to_string()],
             })),
             random_seed: Some(1),
-            random_sequence: None,
-            random_sequence_index: None,
             compiler_version: Some(DEFAULT_COMPILER_VERSION.to_string()),
+            ..Default::default()
         });
         assert!(result.is_err());
         let error = result
@@ -751,11 +1684,9 @@ mod lifecycle_tests {
             module_var_init_order: compiled.module_var_init_order,
             module_const_declarations: compiled.module_const_declarations,
             module_const_init_order: compiled.module_const_init_order,
-            host_functions: None,
             random_seed: Some(1),
-            random_sequence: None,
-            random_sequence_index: None,
             compiler_version: Some(DEFAULT_COMPILER_VERSION.to_string()),
+            ..Default::default()
         });
         assert!(result.is_err());
         let error = result
@@ -805,11 +1736,9 @@ mod lifecycle_tests {
             module_var_init_order: compiled.module_var_init_order,
             module_const_declarations: compiled.module_const_declarations,
             module_const_init_order: compiled.module_const_init_order,
-            host_functions: None,
             random_seed: Some(1),
-            random_sequence: None,
-            random_sequence_index: None,
             compiler_version: Some(DEFAULT_COMPILER_VERSION.to_string()),
+            ..Default::default()
         });
         let error = result
             .err()
@@ -818,50 +1747,323 @@ mod lifecycle_tests {
     }
 
     #[test]
-    pub(super) fn random_function_success_and_registry_call_path_are_covered() {
-        let files = map(&[(
+    pub(super) fn random_function_success_and_registry_call_path_are_covered() {
+        let files = map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <temp name="n" type="int">random(5)</temp>
+      <text>${n}</text>
+    </script>
+    "#,
+        )]);
+        let mut engine = engine_from_sources(files);
+        engine.start("main", None).expect("start");
+        let output = engine.next_output().expect("next");
+        assert_eq!(output_kind(&output), "text");
+
+        let registry = TestRegistry {
+            names: vec!["ok".to_string()],
+        };
+        let value = registry.call("ok", &[]).expect("call should succeed");
+        assert_eq!(value, SlValue::Bool(true));
+    }
+
+    #[test]
+    pub(super) fn enum_builtin_functions_are_available() {
+        let files = map(&[(
+            "main.xml",
+            r#"
+    <module name="main" export="script:main;enum:State">
+      <enum name="State">
+        <member name="Idle"/>
+        <member name="Run"/>
+      </enum>
+      <script name="main">
+        <temp name="state" type="State">State.Run</temp>
+        <temp name="label" type="string">enum_to_string(state)</temp>
+        <text>${label}</text>
+      </script>
+    </module>
+    "#,
+        )]);
+        let mut engine = engine_from_sources(files);
+        engine.start("main.main", None).expect("start");
+        let output = engine.next_output().expect("next");
+        assert!(matches!(output, EngineOutput::Text { text, .. } if text == "Run"));
+    }
+
+    #[test]
+    pub(super) fn numeric_clamp_min_max_builtins_follow_input_types() {
+        let files = map(&[
+            (
+                "shared.xml",
+                r#"<module name="shared" export="function:cap">
+  <function name="cap" args="int:hp" return_type="int">
+    return sl_clamp(hp, 0, 100);
+  </function>
+</module>"#,
+            ),
+            (
+                "main.xml",
+                r#"<!-- import shared from shared.xml -->
+<module name="main" export="script:main">
+  <script name="main">
+    <temp name="hp" type="int">140</temp>
+    <code>hp = sl_clamp(hp, 0, 100);</code>
+    <text>${hp},${sl_clamp(-5, 0, 100)},${sl_clamp(42, 0, 100)},${shared.cap(250)}</text>
+    <text>${sl_min(3, 7)},${sl_max(3, 7)},${sl_min(2, 0.5)},${sl_max(1.5, 1)},${sl_clamp(1.5, 0, 1)}</text>
+    <text>${sl_clamp(1, 10, 0)}</text>
+  </script>
+</module>"#,
+            ),
+        ]);
+        let mut engine = engine_from_sources(files);
+        engine.start("main.main", None).expect("start");
+        assert!(matches!(
+            engine.next_output().expect("next"),
+            EngineOutput::Text { text, .. } if text == "100,0,42,100"
+        ));
+        assert!(matches!(
+            engine.next_output().expect("next"),
+            EngineOutput::Text { text, .. } if text == "3,7,0.5,1.5,1"
+        ));
+        let error = engine
+            .next_output()
+            .expect_err("inverted bounds should fail");
+        assert!(error.message.contains("expects lo <= hi"));
+
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"<script name="main"><text>${sl_max("a", 1)}</text></script>"#,
+        )]));
+        engine.start("main", None).expect("start");
+        let error = engine
+            .next_output()
+            .expect_err("non-numeric arg should fail");
+        assert!(error
+            .message
+            .contains("sl_max(a, b) expects numeric arguments."));
+    }
+
+    #[test]
+    pub(super) fn to_bool_builtin_converts_numbers_and_tokens() {
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <temp name="answer" type="string">"Yes"</temp>
+      <text>${sl_to_bool(answer)},${sl_to_bool("off")},${sl_to_bool(" 1 ")},${sl_to_bool(0)},${sl_to_bool(-2)},${sl_to_bool(0.5)},${sl_to_bool(false)}</text>
+      <text>${sl_to_bool("perhaps")}</text>
+    </script>
+    "#,
+        )]));
+        engine.start("main", None).expect("start");
+        assert!(matches!(
+            engine.next_output().expect("next"),
+            EngineOutput::Text { text, .. } if text == "true,false,true,false,true,true,false"
+        ));
+        let error = engine
+            .next_output()
+            .expect_err("ambiguous string should fail");
+        assert_eq!(error.code, "ENGINE_CONVERT_ERROR");
+        assert!(error.message.contains("\"perhaps\""));
+    }
+
+    #[test]
+    fn string_builtins_split_join_trim_and_replace() {
+        let mut engine = engine_from_sources(map(&[(
             "main.script.xml",
-            r#"
+            r##"
     <script name="main">
-      <temp name="n" type="int">random(5)</temp>
-      <text>${n}</text>
+      <temp name="csv" type="string">"a,b,,c"</temp>
+      <temp name="parts" type="string[]">sl_split(csv, ",")</temp>
+      <text>${parts.len()}:${sl_join(parts, "|")}</text>
+      <text>${sl_join(sl_split("abc", ""), "-")},${sl_split("", ",").len()},${sl_join([], "+")}</text>
+      <text>[${sl_trim("  Rin  ")}]</text>
+      <text>${sl_replace("a cat and a cat", "cat", "dog")},${sl_replace("same", "", "x")}</text>
+      <text>${sl_join(["a", 1], ",")}</text>
     </script>
-    "#,
-        )]);
-        let mut engine = engine_from_sources(files);
+    "##,
+        )]));
         engine.start("main", None).expect("start");
-        let output = engine.next_output().expect("next");
-        assert_eq!(output_kind(&output), "text");
+        for expected in ["4:a|b||c", "a-b-c,0,", "[Rin]", "a dog and a dog,same"] {
+            assert!(matches!(
+                engine.next_output().expect("next"),
+                EngineOutput::Text { text, .. } if text == expected
+            ));
+        }
+        let error = engine
+            .next_output()
+            .expect_err("non-string join element should fail");
+        assert_eq!(error.code, "ENGINE_CONVERT_ERROR");
+        assert!(error.message.contains("item 1"));
+    }
 
-        let registry = TestRegistry {
-            names: vec!["ok".to_string()],
-        };
-        let value = registry.call("ok", &[]).expect("call should succeed");
-        assert_eq!(value, SlValue::Bool(true));
+    #[test]
+    fn null_literal_and_sl_or_handle_missing_values() {
+        let profile = sl_core::slvalue_from_json(serde_json::json!({"name": "Rin", "nick": null}));
+        let mut engine = engine_from_sources_with_global_data(
+            map(&[(
+                "main.script.xml",
+                r##"
+    <script name="main">
+      <text>[${null}][${profile.nick}]</text>
+      <text>${sl_or(null, "guest")},${sl_or(profile.name, "anon")},${sl_or(profile.nick, "anon")},${sl_typeof(null)}</text>
+      <temp name="label" type="string">sl_or(profile.nick, profile.name)</temp>
+      <text>${profile.nick == null},${profile.name == null},${5 == null},${label}</text>
+      <if when="sl_or(profile.nick, '') == ''"><text>no nick</text></if>
+    </script>
+    "##,
+            )]),
+            BTreeMap::from([("profile".to_string(), profile)]),
+            &["profile"],
+        );
+        engine.start("main", None).expect("start");
+        for expected in [
+            "[][]",
+            "guest,Rin,anon,null",
+            "true,false,false,Rin",
+            "no nick",
+        ] {
+            let out = engine.next_output();
+            assert!(
+                matches!(&out, Ok(EngineOutput::Text { text, .. }) if text == expected),
+                "{out:?}"
+            );
+        }
     }
 
     #[test]
-    pub(super) fn enum_builtin_functions_are_available() {
+    fn sl_visited_counts_labeled_node_entries_across_snapshots() {
         let files = map(&[(
-            "main.xml",
-            r#"
-    <module name="main" export="script:main;enum:State">
-      <enum name="State">
-        <member name="Idle"/>
-        <member name="Run"/>
-      </enum>
-      <script name="main">
-        <temp name="state" type="State">State.Run</temp>
-        <temp name="label" type="string">enum_to_string(state)</temp>
-        <text>${label}</text>
-      </script>
-    </module>
-    "#,
+            "main.script.xml",
+            r##"
+    <script name="main">
+      <temp name="i" type="int">0</temp>
+      <while when="i LT 3">
+        <code>i = i + 1;</code>
+        <group label="forest">
+          <if when="sl_visited('forest') == 1"><text>A dark forest.</text></if>
+          <if when="sl_visited('forest') > 1"><text>The forest again (${sl_visited("forest")}).</text></if>
+          <choice text="Go on?">
+            <option text="Yes"><text>${sl_visited("forest")} visits</text></option>
+          </choice>
+        </group>
+      </while>
+    </script>
+    "##,
         )]);
-        let mut engine = engine_from_sources(files);
-        engine.start("main.main", None).expect("start");
-        let output = engine.next_output().expect("next");
-        assert!(matches!(output, EngineOutput::Text { text, .. } if text == "Run"));
+        let expect_text = |engine: &mut ScriptLangEngine, expected: &str| {
+            let out = engine.next_output();
+            assert!(
+                matches!(&out, Ok(EngineOutput::Text { text, .. }) if text == expected),
+                "{out:?}"
+            );
+        };
+        let mut engine = engine_from_sources(files.clone());
+        engine.start("main", None).expect("start");
+        expect_text(&mut engine, "A dark forest.");
+        assert!(matches!(
+            engine.next_output().expect("next"),
+            EngineOutput::Choices { .. }
+        ));
+        engine.choose(0).expect("choose");
+        expect_text(&mut engine, "1 visits");
+        expect_text(&mut engine, "The forest again (2).");
+        assert!(matches!(
+            engine.next_output().expect("next"),
+            EngineOutput::Choices { .. }
+        ));
+
+        let snapshot = engine.snapshot().expect("snapshot");
+        assert_eq!(
+            snapshot.visit_counts.values().copied().collect::<Vec<_>>(),
+            [2]
+        );
+        let mut resumed = engine_from_sources(files);
+        resumed.resume(snapshot).expect("resume");
+        resumed.choose(0).expect("choose");
+        expect_text(&mut resumed, "2 visits");
+        expect_text(&mut resumed, "The forest again (3).");
+
+        let mut unknown = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"<script name="main"><text>${sl_visited("cave")}</text></script>"#,
+        )]));
+        unknown.start("main", None).expect("start");
+        let error = unknown
+            .next_output()
+            .expect_err("unknown label should fail");
+        assert!(error.message.contains("cave"), "{}", error.message);
+    }
+
+    #[test]
+    fn pluralize_and_if_builtins_select_values() {
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r##"
+    <script name="main">
+      <temp name="count" type="int">1</temp>
+      <text>${count} ${sl_pluralize(count, "item", "items")}</text>
+      <code>count = 3;</code>
+      <text>${count} ${sl_pluralize(count, "item", "items")},${sl_pluralize(0, "item", "items")},${sl_pluralize(1.0, "item", "items")}</text>
+      <text>${sl_if(count > 2, "many", "few")},${sl_if(false, "x", 7)},${sl_typeof(sl_if(true, 1.5, "s"))}</text>
+      <temp name="label" type="string">sl_if(count == 3, "three", "other")</temp>
+      <text>${label}</text>
+      <text>${sl_if(1, "a", "b")}</text>
+    </script>
+    "##,
+        )]));
+        engine.start("main", None).expect("start");
+        for expected in ["1 item", "3 items,items,item", "many,7,float", "three"] {
+            assert!(matches!(
+                engine.next_output().expect("next"),
+                EngineOutput::Text { text, .. } if text == expected
+            ));
+        }
+        let error = engine
+            .next_output()
+            .expect_err("non-boolean condition should fail");
+        assert_eq!(error.code, "ENGINE_CONVERT_ERROR");
+
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"<script name="main"><text>${sl_pluralize("one", "a", "b")}</text></script>"#,
+        )]));
+        engine.start("main", None).expect("start");
+        let error = engine
+            .next_output()
+            .expect_err("non-numeric count should fail");
+        assert!(error.message.contains("expects numeric arguments"));
+    }
+
+    #[test]
+    fn typeof_builtin_names_each_value_kind() {
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r##"
+    <script name="main">
+      <temp name="count" type="int">3</temp>
+      <temp name="ratio" type="float">0.5</temp>
+      <temp name="flag" type="boolean">true</temp>
+      <temp name="label" type="string">"x"</temp>
+      <temp name="items" type="int[]">[1, 2]</temp>
+      <temp name="bag" type="#{int}">#{}</temp>
+      <text>${sl_typeof(count)},${sl_typeof(ratio)},${sl_typeof(flag)},${sl_typeof(label)},${sl_typeof(items)},${sl_typeof(bag)}</text>
+      <if when="sl_typeof(items[0]) == 'int'"><text>numeric</text></if>
+    </script>
+    "##,
+        )]));
+        engine.start("main", None).expect("start");
+        assert!(matches!(
+            engine.next_output().expect("next"),
+            EngineOutput::Text { text, .. } if text == "int,float,boolean,string,array,map"
+        ));
+        assert!(matches!(
+            engine.next_output().expect("next"),
+            EngineOutput::Text { text, .. } if text == "numeric"
+        ));
     }
 
     #[test]
@@ -917,11 +2119,11 @@ mod lifecycle_tests {
             module_var_init_order: compiled.module_var_init_order,
             module_const_declarations: compiled.module_const_declarations,
             module_const_init_order: compiled.module_const_init_order,
-            host_functions: None,
             random_seed: Some(1),
             random_sequence: Some(vec![12, 3, 1]),
             random_sequence_index: Some(0),
             compiler_version: Some(DEFAULT_COMPILER_VERSION.to_string()),
+            ..Default::default()
         })
         .expect("new engine");
         engine.start("main", None).expect("start");
@@ -932,25 +2134,86 @@ mod lifecycle_tests {
             a,
             EngineOutput::Text {
                 text: "2".to_string(),
-                tag: None
+                tag: None,
+                speaker: None,
             }
         );
         assert_eq!(
             b,
             EngineOutput::Text {
                 text: "3".to_string(),
-                tag: None
+                tag: None,
+                speaker: None,
             }
         );
         assert_eq!(
             c,
             EngineOutput::Text {
                 text: "1".to_string(),
-                tag: None
+                tag: None,
+                speaker: None,
             }
         );
     }
 
+    #[test]
+    fn random_override_forces_branches_and_survives_resume() {
+        let files = map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <temp name="roll" type="int">random(6)</temp>
+      <if when="roll == 5">
+        <text>crit</text>
+        <else><text>miss</text></else>
+      </if>
+      <choice text="Again">
+        <option text="Roll">
+          <temp name="second" type="int">random(100)</temp>
+          <text>${second}</text>
+          <temp name="third" type="int">random(100)</temp>
+          <text>${third}</text>
+        </option>
+      </choice>
+    </script>
+    "#,
+        )]);
+        let compiled = compile_project_from_sources(files);
+        let build = |random_override: Vec<u32>| {
+            ScriptLangEngine::new(ScriptLangEngineOptions {
+                scripts: compiled.scripts.clone(),
+                random_seed: Some(1),
+                random_override: Some(random_override),
+                compiler_version: Some(DEFAULT_COMPILER_VERSION.to_string()),
+                ..Default::default()
+            })
+            .expect("new engine")
+        };
+        let next_text = |engine: &mut ScriptLangEngine| {
+            output_text(engine.next_output().expect("next")).expect("text expected")
+        };
+
+        let mut engine = build(vec![11, 42]);
+        engine.start("main", None).expect("start");
+        assert_eq!(next_text(&mut engine), "crit");
+        assert!(matches!(
+            engine.next_output().expect("choices"),
+            EngineOutput::Choices { .. }
+        ));
+        let snapshot = engine.snapshot().expect("snapshot");
+        assert_eq!(snapshot.random_override_index, Some(1));
+
+        let mut resumed = build(vec![11, 42]);
+        resumed.resume(snapshot).expect("resume");
+        resumed.choose(0).expect("choose");
+        assert_eq!(next_text(&mut resumed), "42");
+
+        // Once the override runs out, draws continue from the seeded generator.
+        let mut seeded_state = 1u32;
+        let first_seeded = next_random_bounded(&mut seeded_state, 100);
+        assert_eq!(next_text(&mut resumed), first_seeded.to_string());
+    }
+
     #[test]
     pub(super) fn random_sequence_returns_zero_after_exhausted() {
         let files = map(&[(
@@ -974,11 +2237,11 @@ mod lifecycle_tests {
             module_var_init_order: compiled.module_var_init_order,
             module_const_declarations: compiled.module_const_declarations,
             module_const_init_order: compiled.module_const_init_order,
-            host_functions: None,
             random_seed: Some(1),
             random_sequence: Some(vec![5]),
             random_sequence_index: Some(0),
             compiler_version: Some(DEFAULT_COMPILER_VERSION.to_string()),
+            ..Default::default()
         })
         .expect("new engine");
         engine.start("main", None).expect("start");
@@ -989,21 +2252,24 @@ mod lifecycle_tests {
             a,
             EngineOutput::Text {
                 text: "5".to_string(),
-                tag: None
+                tag: None,
+                speaker: None,
             }
         );
         assert_eq!(
             b,
             EngineOutput::Text {
                 text: "0".to_string(),
-                tag: None
+                tag: None,
+                speaker: None,
             }
         );
         assert_eq!(
             c,
             EngineOutput::Text {
                 text: "0".to_string(),
-                tag: None
+                tag: None,
+                speaker: None,
             }
         );
     }
@@ -1036,11 +2302,11 @@ mod lifecycle_tests {
             module_var_init_order: compiled.module_var_init_order,
             module_const_declarations: compiled.module_const_declarations,
             module_const_init_order: compiled.module_const_init_order,
-            host_functions: None,
             random_seed: Some(9),
             random_sequence: Some(vec![12, 3]),
             random_sequence_index: Some(1),
             compiler_version: Some(DEFAULT_COMPILER_VERSION.to_string()),
+            ..Default::default()
         })
         .expect("new");
         sequence.start("main", None).expect("start");
@@ -1056,6 +2322,223 @@ mod lifecycle_tests {
         assert_eq!(sequence.current_seeded_rng_state(), 9);
     }
 
+    #[test]
+    fn reseed_switches_draws_to_new_seed_and_is_snapshotted() {
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <choice text="Roll">
+        <option text="go">
+          <temp name="a" type="int">random(1000)</temp>
+          <temp name="b" type="int">random(1000)</temp>
+          <text>${a},${b}</text>
+        </option>
+      </choice>
+    </script>
+    "#,
+        )]));
+        engine.start("main", None).expect("start");
+        assert!(matches!(
+            engine.next_output().expect("choices"),
+            EngineOutput::Choices { .. }
+        ));
+        engine.reseed(77);
+        assert_eq!(engine.rng_state(), 77);
+        assert_eq!(engine.snapshot().expect("snapshot").rng_state, 77);
+
+        let mut state = 77;
+        let expected = format!(
+            "{},{}",
+            next_random_bounded(&mut state, 1000),
+            next_random_bounded(&mut state, 1000)
+        );
+        engine.choose(0).expect("choose");
+        assert!(matches!(
+            engine.next_output().expect("text"),
+            EngineOutput::Text { text, .. } if text == expected
+        ));
+        assert_eq!(engine.rng_state(), state);
+    }
+
+    #[test]
+    pub(super) fn rhai_limits_reject_oversized_array_construction() {
+        let files = map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <temp name="items" type="int[]">[]</temp>
+      <code>for i in 0..10 { items.push(i); }</code>
+      <text>${items.len()}</text>
+    </script>
+    "#,
+        )]);
+        let compiled = compile_project_from_sources(files.clone());
+        let mut limited = ScriptLangEngine::new(ScriptLangEngineOptions {
+            scripts: compiled.scripts,
+            global_data: compiled.global_data,
+            module_var_declarations: compiled.module_var_declarations,
+            module_var_init_order: compiled.module_var_init_order,
+            module_const_declarations: compiled.module_const_declarations,
+            module_const_init_order: compiled.module_const_init_order,
+            host_functions: None,
+            random_seed: Some(1),
+            random_sequence: None,
+            random_sequence_index: None,
+            compiler_version: None,
+            initial_once_state: None,
+            clock: None,
+            host_constants: BTreeMap::new(),
+            random_override: None,
+            limits: EngineLimits {
+                rhai: Some(RhaiResourceLimits {
+                    max_array_size: 4,
+                    ..RhaiResourceLimits::default()
+                }),
+                ..EngineLimits::default()
+            },
+            behavior: EngineBehavior::default(),
+        })
+        .expect("new engine");
+        limited.start("main", None).expect("start");
+        let error = limited
+            .next_output()
+            .expect_err("oversized array should fail");
+        assert_eq!(error.code, "ENGINE_EVAL_ERROR");
+
+        let mut default_limits = engine_from_sources(files);
+        default_limits.start("main", None).expect("start");
+        assert_eq!(
+            default_limits.next_output().expect("text"),
+            EngineOutput::Text {
+                text: "10".to_string(),
+                tag: None,
+                speaker: None,
+            }
+        );
+    }
+
+    #[test]
+    pub(super) fn sl_now_returns_injected_clock_and_survives_resume() {
+        let files = map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <text>now=${sl_now()}</text>
+      <choice text="Pick">
+        <option text="A"><text>later=${sl_now()}</text></option>
+      </choice>
+    </script>
+    "#,
+        )]);
+        let engine_with_clock = |clock: Option<i64>| {
+            let compiled = compile_project_from_sources(files.clone());
+            ScriptLangEngine::new(ScriptLangEngineOptions {
+                scripts: compiled.scripts,
+                global_data: compiled.global_data,
+                module_var_declarations: compiled.module_var_declarations,
+                module_var_init_order: compiled.module_var_init_order,
+                module_const_declarations: compiled.module_const_declarations,
+                module_const_init_order: compiled.module_const_init_order,
+                random_seed: Some(1),
+                clock,
+                ..Default::default()
+            })
+            .expect("new engine")
+        };
+        let text = |text: &str| EngineOutput::Text {
+            text: text.to_string(),
+            tag: None,
+            speaker: None,
+        };
+
+        let mut engine = engine_with_clock(Some(1_700_000_000));
+        engine.start("main", None).expect("start");
+        assert_eq!(engine.next_output().expect("text"), text("now=1700000000"));
+        assert!(matches!(
+            engine.next_output().expect("choices"),
+            EngineOutput::Choices { .. }
+        ));
+        let snapshot = engine.snapshot().expect("snapshot");
+        assert_eq!(snapshot.clock, Some(1_700_000_000));
+
+        let mut resumed = engine_with_clock(Some(5));
+        resumed.resume(snapshot).expect("resume");
+        resumed.choose(0).expect("choose");
+        assert_eq!(
+            resumed.next_output().expect("text"),
+            text("later=1700000000")
+        );
+
+        let mut unset = engine_with_clock(None);
+        unset.start("main", None).expect("start");
+        assert_eq!(unset.next_output().expect("text"), text("now=0"));
+    }
+
+    #[test]
+    pub(super) fn initial_once_state_skips_pre_seeded_once_text() {
+        let files = map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <text once="true">Intro</text>
+      <text>After</text>
+    </script>
+    "#,
+        )]);
+        let compiled = compile_project_from_sources(files);
+        let once_text_id = compiled
+            .scripts
+            .get("main.main")
+            .expect("main script")
+            .groups
+            .values()
+            .flat_map(|group| group.nodes.iter())
+            .find_map(|node| match node {
+                ScriptNode::Text { id, once: true, .. } => Some(id.clone()),
+                _ => None,
+            })
+            .expect("once text node");
+        let build = |initial_once_state| {
+            ScriptLangEngine::new(ScriptLangEngineOptions {
+                scripts: compiled.scripts.clone(),
+                global_data: compiled.global_data.clone(),
+                module_var_declarations: compiled.module_var_declarations.clone(),
+                module_var_init_order: compiled.module_var_init_order.clone(),
+                module_const_declarations: compiled.module_const_declarations.clone(),
+                module_const_init_order: compiled.module_const_init_order.clone(),
+                random_seed: Some(1),
+                initial_once_state,
+                ..Default::default()
+            })
+        };
+
+        let mut engine = build(Some(BTreeMap::from([(
+            "main.main".to_string(),
+            BTreeSet::from([format!("text:{}", once_text_id)]),
+        )])))
+        .expect("engine with pre-seeded once state");
+        engine.start("main.main", None).expect("start");
+        assert_eq!(
+            engine.next_output().expect("text"),
+            EngineOutput::Text {
+                text: "After".to_string(),
+                tag: None,
+                speaker: None,
+            }
+        );
+
+        for invalid_key in ["intro", "text:", "choice:c1"] {
+            let error = build(Some(BTreeMap::from([(
+                "main".to_string(),
+                BTreeSet::from([invalid_key.to_string()]),
+            )])))
+            .err()
+            .expect("invalid once key should fail");
+            assert_eq!(error.code, "ENGINE_ONCE_STATE_INVALID");
+        }
+    }
+
     #[test]
     pub(super) fn new_success_path_initializes_module_and_function_symbols() {
         let files = map(&[
@@ -1086,11 +2569,8 @@ mod lifecycle_tests {
             module_var_init_order: compiled.module_var_init_order,
             module_const_declarations: compiled.module_const_declarations,
             module_const_init_order: compiled.module_const_init_order,
-            host_functions: None,
             random_seed: Some(7),
-            random_sequence: None,
-            random_sequence_index: None,
-            compiler_version: None,
+            ..Default::default()
         })
         .expect("new should succeed");
 
@@ -1225,6 +2705,7 @@ mod lifecycle_tests {
                 },
                 initial_value_expr: None,
                 location: sl_core::SourceSpan::synthetic(),
+                session: false,
             },
         );
         engine.module_var_init_order.clear();
@@ -1306,7 +2787,8 @@ mod lifecycle_tests {
             output,
             EngineOutput::Text {
                 text: "7".to_string(),
-                tag: None
+                tag: None,
+                speaker: None,
             }
         );
     }
@@ -1410,6 +2892,7 @@ mod lifecycle_tests {
                 initial_value_expr: None,
                 access: AccessLevel::Public,
                 location: SourceSpan::synthetic(),
+                session: false,
             },
         );
         engine.module_var_init_order = vec!["main.state".to_string()];
@@ -1517,11 +3000,8 @@ mod lifecycle_tests {
             module_var_init_order: compiled.module_var_init_order,
             module_const_declarations: compiled.module_const_declarations,
             module_const_init_order: compiled.module_const_init_order,
-            host_functions: None,
             random_seed: Some(1),
-            random_sequence: None,
-            random_sequence_index: None,
-            compiler_version: None,
+            ..Default::default()
         })
         .expect("engine should build");
         let error = engine
@@ -1541,4 +3021,115 @@ mod lifecycle_tests {
         let result = normalize_script_builtin_arg("@");
         assert!(result.is_none());
     }
+
+    #[test]
+    fn goto_resumes_at_nested_choice_group_with_supplied_scope() {
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <temp name="gold" type="int">0</temp>
+      <text>intro</text>
+      <if when="gold > 100">
+        <choice text="Spend ${gold}?">
+          <option text="Buy"><text>bought with ${gold}</text><end/></option>
+        </choice>
+      </if>
+    </script>
+    "#,
+        )]));
+        let script = engine.scripts.get("main").expect("main script");
+        let choice_group_id = script
+            .groups
+            .values()
+            .find(|group| {
+                group.group_id != script.root_group_id
+                    && group
+                        .nodes
+                        .iter()
+                        .any(|node| matches!(node, ScriptNode::Choice { .. }))
+            })
+            .map(|group| group.group_id.clone())
+            .expect("choice group");
+
+        engine
+            .goto(
+                "main",
+                &choice_group_id,
+                BTreeMap::from([("gold".to_string(), SlValue::Number(500.0))]),
+            )
+            .expect("goto");
+        assert!(matches!(
+            engine.next_output().expect("choices"),
+            EngineOutput::Choices { items, prompt_text: Some(prompt), .. }
+                if items.len() == 1 && prompt == "Spend 500?"
+        ));
+        engine.choose(0).expect("choose");
+        assert!(matches!(
+            engine.next_output().expect("text"),
+            EngineOutput::Text { text, .. } if text == "bought with 500"
+        ));
+        assert!(matches!(
+            engine.next_output().expect("end"),
+            EngineOutput::End
+        ));
+
+        let error = engine
+            .goto("other", &choice_group_id, BTreeMap::new())
+            .expect_err("unknown script should fail");
+        assert_eq!(error.code, "ENGINE_SCRIPT_NOT_FOUND");
+        let error = engine
+            .goto("main", "missing", BTreeMap::new())
+            .expect_err("unknown group should fail");
+        assert_eq!(error.code, "ENGINE_GROUP_NOT_FOUND");
+    }
+
+    #[test]
+    fn host_constants_are_readable_everywhere_and_readonly() {
+        let compiled = compile_project_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <text>mode=${difficulty}</text>
+      <code>difficulty = "easy";</code>
+      <end/>
+    </script>
+    "#,
+        )]));
+        let build = |global_data: BTreeMap<String, SlValue>| {
+            ScriptLangEngine::new(ScriptLangEngineOptions {
+                scripts: compiled.scripts.clone(),
+                global_data,
+                module_var_declarations: compiled.module_var_declarations.clone(),
+                module_var_init_order: compiled.module_var_init_order.clone(),
+                module_const_declarations: compiled.module_const_declarations.clone(),
+                module_const_init_order: compiled.module_const_init_order.clone(),
+                random_seed: Some(1),
+                host_constants: BTreeMap::from([(
+                    "difficulty".to_string(),
+                    SlValue::String("hard".to_string()),
+                )]),
+                ..Default::default()
+            })
+        };
+
+        let mut engine = build(BTreeMap::new()).expect("engine should build");
+        engine.start("main", None).expect("start");
+        assert!(matches!(
+            engine.next_output().expect("text"),
+            EngineOutput::Text { text, .. } if text == "mode=hard"
+        ));
+        let error = engine
+            .next_output()
+            .expect_err("host constant write should fail");
+        assert_eq!(error.code, "ENGINE_GLOBAL_READONLY");
+
+        let error = build(BTreeMap::from([(
+            "difficulty".to_string(),
+            SlValue::String("normal".to_string()),
+        )]))
+        .err()
+        .expect("name shared with global data should fail");
+        assert_eq!(error.code, "ENGINE_HOST_CONSTANT_CONFLICT");
+    }
 }
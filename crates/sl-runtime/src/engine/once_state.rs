@@ -1,6 +1,60 @@
 use super::*;
 
+pub(super) fn validate_once_state_keys(
+    once_state_by_script: &BTreeMap<String, BTreeSet<String>>,
+) -> Result<(), ScriptLangError> {
+    for (script_name, keys) in once_state_by_script {
+        for key in keys {
+            let id = key
+                .strip_prefix("text:")
+                .or_else(|| key.strip_prefix("option:"));
+            if id.is_none_or(|value| value.is_empty()) {
+                return Err(ScriptLangError::new(
+                    ErrorCode::EngineOnceStateInvalid,
+                    format!(
+                        "Once state key \"{}\" for script \"{}\" must be \"text:<id>\" or \"option:<id>\".",
+                        key, script_name
+                    ),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
 impl ScriptLangEngine {
+    /// Consumed once-keys (`text:<id>` / `option:<id>`) by script name, as `snapshot()` records them.
+    pub fn once_state(&self) -> &BTreeMap<String, BTreeSet<String>> {
+        &self.once_state_by_script
+    }
+
+    /// Forget one consumed once-key so its text or option shows again; returns whether it was set.
+    pub fn clear_once(&mut self, script_name: &str, key: &str) -> bool {
+        let Some(keys) = self.once_state_by_script.get_mut(script_name) else {
+            return false;
+        };
+        let removed = keys.remove(key);
+        if keys.is_empty() {
+            self.once_state_by_script.remove(script_name);
+        }
+        removed
+    }
+
+    /// Consumed once-keys of `scope="global"` nodes, shared by every script.
+    pub fn global_once_state(&self) -> &BTreeSet<String> {
+        &self.global_once_state
+    }
+
+    /// Forget one consumed global once-key; returns whether it was set.
+    pub fn clear_global_once(&mut self, key: &str) -> bool {
+        self.global_once_state.remove(key)
+    }
+
+    /// Selection counts of `max_select` options (`option:<id>`) by script name.
+    pub fn select_counts(&self) -> &BTreeMap<String, BTreeMap<String, u32>> {
+        &self.select_count_by_script
+    }
+
     pub(super) fn is_choice_option_visible(
         &mut self,
         script_name: &str,
@@ -12,11 +66,46 @@ impl ScriptLangEngine {
             }
         }
 
+        if let Some(limit) = option.max_select {
+            return Ok(self.select_count(script_name, &option.id) < limit);
+        }
+
         if !option.once {
             return Ok(true);
         }
 
-        Ok(!self.has_once_state(script_name, &format!("option:{}", option.id)))
+        Ok(!self.has_scoped_once_state(
+            script_name,
+            &format!("option:{}", option.id),
+            option.global_once,
+        ))
+    }
+
+    pub(super) fn is_choice_option_enabled(
+        &mut self,
+        option: &sl_core::ChoiceOption,
+    ) -> Result<bool, ScriptLangError> {
+        match &option.enabled_when_expr {
+            Some(expr) => self.eval_boolean(expr),
+            None => Ok(true),
+        }
+    }
+
+    pub(super) fn select_count(&self, script_name: &str, option_id: &str) -> u32 {
+        self.select_count_by_script
+            .get(script_name)
+            .and_then(|counts| counts.get(&format!("option:{}", option_id)))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub(super) fn record_selection(&mut self, script_name: &str, option_id: &str) {
+        *self
+            .select_count_by_script
+            .entry(script_name.to_string())
+            .or_default()
+            .entry(format!("option:{}", option_id))
+            .or_default() += 1;
     }
 
     pub(super) fn has_once_state(&self, script_name: &str, key: &str) -> bool {
@@ -32,6 +121,22 @@ impl ScriptLangEngine {
             .or_default()
             .insert(key.to_string());
     }
+
+    pub(super) fn has_scoped_once_state(&self, script_name: &str, key: &str, global: bool) -> bool {
+        if global {
+            self.global_once_state.contains(key)
+        } else {
+            self.has_once_state(script_name, key)
+        }
+    }
+
+    pub(super) fn mark_scoped_once_state(&mut self, script_name: &str, key: &str, global: bool) {
+        if global {
+            self.global_once_state.insert(key.to_string());
+        } else {
+            self.mark_once_state(script_name, key);
+        }
+    }
 }
 #[derive(Debug, Clone)]
 pub(crate) struct BindingOwner {
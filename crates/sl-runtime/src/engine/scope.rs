@@ -1,6 +1,35 @@
 use super::*;
 
+/// Prefix of vars injected by compiler macros (for example the `<for>` first-iteration flag).
+pub(super) const INTERNAL_VAR_PREFIX: &str = "__sl_";
+
+pub(super) fn is_internal_var_name(name: &str) -> bool {
+    name.starts_with(INTERNAL_VAR_PREFIX)
+}
+
+pub(super) fn reserved_var_write_error(name: &str) -> ScriptLangError {
+    ScriptLangError::new(
+        ErrorCode::EngineReservedVarWrite,
+        format!(
+            "Variable \"{}\" is reserved for compiler-generated control flow and cannot be assigned.",
+            name
+        ),
+    )
+}
+
 impl ScriptLangEngine {
+    /// Read a variable visible from the current frame, as a `${name}` expression would.
+    pub fn get_variable(&self, name: &str) -> Result<SlValue, ScriptLangError> {
+        self.read_variable(name)
+    }
+
+    /// Assign a variable visible from the current frame between boundaries. Declared types,
+    /// readonly consts/global data and reserved names are enforced; undeclared names fail with
+    /// `ENGINE_VAR_WRITE`.
+    pub fn set_variable(&mut self, name: &str, value: SlValue) -> Result<(), ScriptLangError> {
+        self.write_variable(name, value)
+    }
+
     pub(super) fn resolve_current_script_name(&self) -> Option<String> {
         let top = self.frames.last()?;
         self.group_lookup
@@ -61,7 +90,7 @@ impl ScriptLangEngine {
                 .cloned()
                 .ok_or_else(|| {
                     ScriptLangError::new(
-                        "ENGINE_MODULE_GLOBAL_MISSING",
+                        ErrorCode::EngineModuleGlobalMissing,
                         format!("Module global \"{}\" is not initialized.", qualified_name),
                     )
                 });
@@ -74,7 +103,7 @@ impl ScriptLangEngine {
                 .cloned()
                 .ok_or_else(|| {
                     ScriptLangError::new(
-                        "ENGINE_MODULE_CONST_MISSING",
+                        ErrorCode::EngineModuleConstMissing,
                         format!("Module const \"{}\" is not initialized.", qualified_name),
                     )
                 });
@@ -88,7 +117,7 @@ impl ScriptLangEngine {
         }
 
         Err(ScriptLangError::new(
-            "ENGINE_VAR_READ",
+            ErrorCode::EngineVarRead,
             format!("Variable \"{}\" is not defined.", name),
         ))
     }
@@ -97,13 +126,25 @@ impl ScriptLangEngine {
         &mut self,
         name: &str,
         value: SlValue,
+    ) -> Result<(), ScriptLangError> {
+        if is_internal_var_name(name) {
+            return Err(reserved_var_write_error(name));
+        }
+        self.assign_variable(name, value)
+    }
+
+    /// Write without the reserved-name guard; used for macro-emitted code.
+    pub(super) fn assign_variable(
+        &mut self,
+        name: &str,
+        value: SlValue,
     ) -> Result<(), ScriptLangError> {
         for frame in self.frames.iter_mut().rev() {
             if frame.scope.contains_key(name) {
                 if let Some(declared_type) = frame.var_types.get(name) {
                     if !is_type_compatible(&value, declared_type) {
                         return Err(ScriptLangError::new(
-                            "ENGINE_TYPE_MISMATCH",
+                            ErrorCode::EngineTypeMismatch,
                             format!("Variable \"{}\" does not match declared type.", name),
                         ));
                     }
@@ -122,7 +163,7 @@ impl ScriptLangEngine {
                 .is_some_and(|declared_type| !is_type_compatible(&value, declared_type))
             {
                 return Err(ScriptLangError::new(
-                    "ENGINE_TYPE_MISMATCH",
+                    ErrorCode::EngineTypeMismatch,
                     format!("Variable \"{}\" does not match declared type.", name),
                 ));
             }
@@ -132,7 +173,7 @@ impl ScriptLangEngine {
         if let Some(qualified_name) = self.resolve_module_const_alias(script_name.as_deref(), name)
         {
             return Err(ScriptLangError::new(
-                "ENGINE_CONST_READONLY",
+                ErrorCode::EngineConstReadonly,
                 format!(
                     "Module const \"{}\" is readonly and cannot be mutated.",
                     qualified_name
@@ -141,7 +182,7 @@ impl ScriptLangEngine {
         }
         if self.is_visible_global_data(script_name.as_deref(), name) {
             return Err(ScriptLangError::new(
-                "ENGINE_GLOBAL_READONLY",
+                ErrorCode::EngineGlobalReadonly,
                 format!(
                     "global data \"{}\" is readonly and cannot be mutated.",
                     name
@@ -149,7 +190,7 @@ impl ScriptLangEngine {
             ));
         }
         Err(ScriptLangError::new(
-            "ENGINE_VAR_WRITE",
+            ErrorCode::EngineVarWrite,
             format!("Variable \"{}\" is not defined.", name),
         ))
     }
@@ -158,7 +199,7 @@ impl ScriptLangEngine {
         let parts = parse_ref_path(path);
         if parts.is_empty() {
             return Err(ScriptLangError::new(
-                "ENGINE_REF_PATH",
+                ErrorCode::EngineRefPath,
                 format!("Invalid ref path \"{}\".", path),
             ));
         }
@@ -167,13 +208,13 @@ impl ScriptLangEngine {
         for part in parts.iter().skip(nested_start_index) {
             let SlValue::Map(entries) = current else {
                 return Err(ScriptLangError::new(
-                    "ENGINE_REF_PATH_READ",
+                    ErrorCode::EngineRefPathRead,
                     format!("Cannot resolve path \"{}\".", path),
                 ));
             };
             current = entries.get(part).cloned().ok_or_else(|| {
                 ScriptLangError::new(
-                    "ENGINE_REF_PATH_READ",
+                    ErrorCode::EngineRefPathRead,
                     format!("Cannot resolve path \"{}\".", path),
                 )
             })?;
@@ -185,7 +226,7 @@ impl ScriptLangEngine {
         let parts = parse_ref_path(path);
         if parts.is_empty() {
             return Err(ScriptLangError::new(
-                "ENGINE_REF_PATH",
+                ErrorCode::EngineRefPath,
                 format!("Invalid ref path \"{}\".", path),
             ));
         }
@@ -202,7 +243,7 @@ impl ScriptLangEngine {
         assign_nested_path(&mut root_value, &parts[nested_start_index..], value).map_err(
             |message| {
                 ScriptLangError::new(
-                    "ENGINE_REF_PATH_WRITE",
+                    ErrorCode::EngineRefPathWrite,
                     format!("Cannot resolve write path \"{}\": {}", path, message),
                 )
             },
@@ -236,17 +277,121 @@ mod scope_tests {
     use super::runtime_test_support::*;
     use super::*;
 
+    #[test]
+    pub(super) fn host_set_variable_writes_typed_values_between_boundaries() {
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <temp name="hp" type="int">10</temp>
+      <choice text="Go?">
+        <option text="Yes"><text>hp=${hp}</text></option>
+      </choice>
+    </script>
+    "#,
+        )]));
+        engine.start("main", None).expect("start");
+        engine.next_output().expect("choices");
+        assert_eq!(
+            engine.get_variable("hp").expect("read"),
+            SlValue::Number(10.0)
+        );
+
+        engine
+            .set_variable("hp", SlValue::Number(3.0))
+            .expect("typed write should pass");
+        let error = engine
+            .set_variable("hp", SlValue::String("low".to_string()))
+            .expect_err("type mismatch should fail");
+        assert_eq!(error.code, "ENGINE_TYPE_MISMATCH");
+
+        engine.choose(0).expect("choose");
+        assert!(matches!(
+            engine.next_output().expect("text"),
+            EngineOutput::Text { text, .. } if text == "hp=3"
+        ));
+    }
+
+    #[test]
+    pub(super) fn int_keyed_maps_validate_keys_and_support_lookups() {
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r##"
+    <script name="main">
+      <temp name="names" type="#{int=>string}">#{"1": "Rin", "2": "Kai"}</temp>
+      <temp name="id" type="int">2</temp>
+      <text>${names[id.to_string()]}</text>
+      <code>names[(id + 1).to_string()] = "Mio";</code>
+      <text>${names["3"]}</text>
+    </script>
+    "##,
+        )]));
+        engine.start("main", None).expect("start");
+        for expected in ["Kai", "Mio"] {
+            assert!(matches!(
+                engine.next_output().expect("text"),
+                EngineOutput::Text { text, .. } if text == expected
+            ));
+        }
+
+        let mut bad_key = engine_from_sources(map(&[(
+            "main.script.xml",
+            r##"
+    <script name="main">
+      <temp name="names" type="#{int=>string}">#{"1": "Rin"}</temp>
+      <code>names["one"] = "Kai";</code>
+    </script>
+    "##,
+        )]));
+        bad_key.start("main", None).expect("start");
+        let error = bad_key.next_output().expect_err("non-int key should fail");
+        assert_eq!(error.code, "ENGINE_TYPE_MISMATCH");
+    }
+
+    #[test]
+    pub(super) fn host_set_variable_rejects_undeclared_names() {
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <choice text="Go?">
+        <option text="Yes"><text>ok</text></option>
+      </choice>
+    </script>
+    "#,
+        )]));
+        engine.start("main", None).expect("start");
+        engine.next_output().expect("choices");
+        let error = engine
+            .set_variable("gold", SlValue::Number(1.0))
+            .expect_err("undeclared write should fail");
+        assert_eq!(error.code, "ENGINE_VAR_WRITE");
+        let error = engine
+            .get_variable("gold")
+            .expect_err("undeclared read should fail");
+        assert_eq!(error.code, "ENGINE_VAR_READ");
+    }
+
     #[test]
     pub(super) fn runtime_errors_cover_var_and_ref_path_failures() {
+        // The compiler rejects duplicates with XML_VAR_DUPLICATE; inject one into the IR to keep
+        // the runtime guard covered for hand-built bundles.
         let mut duplicate_var = engine_from_sources(map(&[(
             "main.script.xml",
             r#"
     <script name="main">
       <temp name="x" type="int">1</temp>
-      <temp name="x" type="int">2</temp>
     </script>
     "#,
         )]));
+        for script in duplicate_var.scripts.values_mut() {
+            let root = script
+                .groups
+                .get_mut(&script.root_group_id)
+                .expect("root group");
+            let var_node = root.nodes[0].clone();
+            root.nodes.push(var_node);
+        }
         duplicate_var.start("main", None).expect("start");
         let error = duplicate_var
             .next_output()
@@ -277,6 +422,31 @@ mod scope_tests {
                 || error.code == "ENGINE_EVAL_ERROR"
         );
 
+        let mut loop_flag_write = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <for temps="i:int:0" condition="i LT 3" iteration="i = i + 1;">
+        <text>${i}</text>
+        <code>__sl_for_0_first = true;</code>
+      </for>
+    </script>
+    "#,
+        )]));
+        loop_flag_write.start("main", None).expect("start");
+        assert!(matches!(
+            loop_flag_write.next_output().expect("first text"),
+            EngineOutput::Text { .. }
+        ));
+        let error = loop_flag_write
+            .next_output()
+            .expect_err("user code cannot rewrite the for-loop flag");
+        assert_eq!(error.code, "ENGINE_RESERVED_VAR_WRITE");
+        let error = loop_flag_write
+            .write_variable("__sl_for_0_first", SlValue::Bool(true))
+            .expect_err("direct reserved write should fail");
+        assert_eq!(error.code, "ENGINE_RESERVED_VAR_WRITE");
+
         let mut bad_ref_write = engine_from_sources(map(&[(
             "main.script.xml",
             r#"
@@ -482,7 +652,7 @@ mod scope_tests {
                 r#"
     <!-- import callee from callee.xml -->
     <script name="main">
-      <call script="@callee.callee" args="'str'"/>
+      <call script="@callee.callee" args="'str' + ''"/>
     </script>
     "#,
             ),
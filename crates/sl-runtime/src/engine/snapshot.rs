@@ -1,73 +1,347 @@
 use super::lifecycle::{
-    CompletionKind, PendingBoundary as RuntimePendingBoundary, PendingChoiceOption, RuntimeFrame,
-    RuntimeRandomState,
+    build_group_lookup, CompletionKind, GroupLookup, PendingBoundary as RuntimePendingBoundary,
+    PendingChoiceOption, RuntimeFrame, RuntimeRandomState, HISTORY_CAPACITY,
 };
 use super::*;
 use sl_core::PendingBoundary as SnapshotPendingBoundary;
 
+fn snapshot_pending_boundary(boundary: &RuntimePendingBoundary) -> SnapshotPendingBoundary {
+    match boundary {
+        RuntimePendingBoundary::Choice {
+            node_id,
+            options,
+            prompt_text,
+            ..
+        } => SnapshotPendingBoundary::Choice {
+            node_id: node_id.clone(),
+            items: options.iter().map(|option| option.item.clone()).collect(),
+            prompt_text: prompt_text.clone(),
+            dynamic_bindings: options
+                .iter()
+                .filter_map(|option| {
+                    option
+                        .dynamic_binding
+                        .clone()
+                        .map(|binding| (option.item.id.clone(), binding))
+                })
+                .collect(),
+        },
+        RuntimePendingBoundary::Input {
+            node_id,
+            target_var,
+            prompt_text,
+            default_text,
+            max_length,
+            ..
+        } => SnapshotPendingBoundary::Input {
+            node_id: node_id.clone(),
+            target_var: target_var.clone(),
+            prompt_text: prompt_text.clone(),
+            default_text: default_text.clone(),
+            max_length: *max_length,
+        },
+    }
+}
+
+/// Offline compatibility check of a snapshot against a script bundle, see `inspect_snapshot`.
+#[derive(Debug, Clone)]
+pub struct SnapshotReport {
+    /// Script the session was started from; empty for snapshots that predate the field.
+    pub entry_script: String,
+    /// One-line description of what the snapshot is waiting on, e.g. `choice "n3" (2 options)`.
+    pub pending: String,
+    pub frame_count: usize,
+    pub schema_compatible: bool,
+    pub compiler_version_compatible: bool,
+    pub entry_script_known: bool,
+    /// Every frame's group (not just the top one) exists in the bundle.
+    pub groups_resolvable: bool,
+    /// Every frame's `node_index` fits in its group.
+    pub node_indices_valid: bool,
+    /// The top frame points at an unchanged node of the pending boundary's kind and id.
+    pub pending_node_valid: bool,
+    /// The error `resume` would raise for each failed check, in check order.
+    pub problems: Vec<ScriptLangError>,
+    pub summary: String,
+}
+
+impl SnapshotReport {
+    /// Whether `resume` would accept the snapshot's frames and boundary for this bundle.
+    pub fn is_compatible(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Run the checks `resume` makes against `scripts` without building an engine.
+///
+/// `compiler_version` is the version the resuming engine would be built with (e.g. one carrying
+/// the bundle's optimization fingerprint). Module var values are not checked, since that needs the
+/// bundle's module declarations.
+pub fn inspect_snapshot(
+    snapshot: &Snapshot,
+    scripts: &BTreeMap<String, ScriptIr>,
+    compiler_version: &str,
+) -> SnapshotReport {
+    let group_lookup = build_group_lookup(scripts);
+    let target = SnapshotTarget {
+        scripts,
+        group_lookup: &group_lookup,
+        compiler_version,
+    };
+    let mut problems = Vec::new();
+    let mut passes = |result: Result<(), ScriptLangError>| match result {
+        Ok(()) => true,
+        Err(error) => {
+            problems.push(error);
+            false
+        }
+    };
+    let schema_compatible = passes(target.check_schema(snapshot));
+    let compiler_version_compatible = passes(target.check_compiler_version(snapshot));
+    let entry_script_known = passes(target.check_entry_script(snapshot));
+    let groups_resolvable = passes(target.check_groups(snapshot));
+    let node_indices_valid = groups_resolvable && passes(target.check_node_indices(snapshot));
+    let pending_node_valid = node_indices_valid && passes(target.check_pending_node(snapshot));
+
+    let pending = pending_boundary_summary(&snapshot.pending_boundary);
+    let entry = if snapshot.entry_script.is_empty() {
+        "unknown entry"
+    } else {
+        snapshot.entry_script.as_str()
+    };
+    let verdict = match problems.first() {
+        None => "compatible".to_string(),
+        Some(first) if problems.len() == 1 => format!("incompatible: {}", first),
+        Some(first) => format!("incompatible: {} (+{} more)", first, problems.len() - 1),
+    };
+    SnapshotReport {
+        summary: format!(
+            "{}: {}, {} frame(s); {}",
+            entry,
+            pending,
+            snapshot.runtime_frames.len(),
+            verdict
+        ),
+        entry_script: snapshot.entry_script.clone(),
+        pending,
+        frame_count: snapshot.runtime_frames.len(),
+        schema_compatible,
+        compiler_version_compatible,
+        entry_script_known,
+        groups_resolvable,
+        node_indices_valid,
+        pending_node_valid,
+        problems,
+    }
+}
+
+fn pending_boundary_summary(boundary: &SnapshotPendingBoundary) -> String {
+    match boundary {
+        SnapshotPendingBoundary::Choice { node_id, items, .. } => {
+            format!("choice \"{}\" ({} options)", node_id, items.len())
+        }
+        SnapshotPendingBoundary::Input {
+            node_id,
+            target_var,
+            ..
+        } => format!("input \"{}\" into {}", node_id, target_var),
+        SnapshotPendingBoundary::Checkpoint {
+            node_id,
+            checkpoint_id,
+        } => format!("checkpoint \"{}\" at \"{}\"", checkpoint_id, node_id),
+    }
+}
+
+/// The compiled scripts a snapshot is validated against; shared by `resume` and
+/// `inspect_snapshot` so both accept exactly the same snapshots.
+struct SnapshotTarget<'a> {
+    scripts: &'a BTreeMap<String, ScriptIr>,
+    group_lookup: &'a HashMap<String, GroupLookup>,
+    compiler_version: &'a str,
+}
+
+impl SnapshotTarget<'_> {
+    fn group(&self, group_id: &str) -> Option<&sl_core::ImplicitGroup> {
+        let lookup = self.group_lookup.get(group_id)?;
+        self.scripts
+            .get(&lookup.script_name)?
+            .groups
+            .get(&lookup.group_id)
+    }
+
+    fn check_schema(&self, snapshot: &Snapshot) -> Result<(), ScriptLangError> {
+        if snapshot.schema_version != SNAPSHOT_SCHEMA {
+            return Err(ScriptLangError::new(
+                ErrorCode::SnapshotSchema,
+                format!(
+                    "Unsupported snapshot schema \"{}\".",
+                    snapshot.schema_version
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_compiler_version(&self, snapshot: &Snapshot) -> Result<(), ScriptLangError> {
+        if snapshot.compiler_version != self.compiler_version {
+            return Err(ScriptLangError::new(
+                ErrorCode::SnapshotCompilerVersion,
+                format!(
+                    "Snapshot compiler version \"{}\" does not match engine \"{}\".",
+                    snapshot.compiler_version, self.compiler_version
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_entry_script(&self, snapshot: &Snapshot) -> Result<(), ScriptLangError> {
+        if !snapshot.entry_script.is_empty() && !self.scripts.contains_key(&snapshot.entry_script) {
+            return Err(ScriptLangError::new(
+                ErrorCode::SnapshotEntryScriptUnknown,
+                format!(
+                    "Snapshot entry script \"{}\" is not registered.",
+                    snapshot.entry_script
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// A story edit may have removed a group that a suspended caller frame still points at;
+    /// reject the save up front instead of failing later when that frame resumes.
+    fn check_groups(&self, snapshot: &Snapshot) -> Result<(), ScriptLangError> {
+        if let Some(frame) = snapshot
+            .runtime_frames
+            .iter()
+            .find(|frame| self.group(&frame.group_id).is_none())
+        {
+            return Err(ScriptLangError::new(
+                ErrorCode::SnapshotGroupMissing,
+                format!(
+                    "Snapshot frame {} references group \"{}\" which no longer exists.",
+                    frame.frame_id, frame.group_id
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// `node_index` is the only progress marker resume trusts: nodes before it are never
+    /// re-run, so an index past the group's end cannot be repaired by replaying anything.
+    fn check_node_indices(&self, snapshot: &Snapshot) -> Result<(), ScriptLangError> {
+        for frame in &snapshot.runtime_frames {
+            let Some(group) = self.group(&frame.group_id) else {
+                continue;
+            };
+            if frame.node_index > group.nodes.len() {
+                return Err(ScriptLangError::new(
+                    ErrorCode::SnapshotNodeIndexOob,
+                    format!(
+                        "Snapshot frame {} points at node {} but group \"{}\" has {} nodes.",
+                        frame.frame_id,
+                        frame.node_index,
+                        frame.group_id,
+                        group.nodes.len()
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn check_pending_node(&self, snapshot: &Snapshot) -> Result<(), ScriptLangError> {
+        let top = snapshot.runtime_frames.last().ok_or_else(|| {
+            ScriptLangError::new(
+                ErrorCode::SnapshotEmpty,
+                "Snapshot contains no runtime frames.",
+            )
+        })?;
+        let pending_node_error = || {
+            ScriptLangError::new(
+                ErrorCode::SnapshotPendingBoundary,
+                "Pending node index invalid.",
+            )
+        };
+        let group = self.group(&top.group_id).ok_or_else(pending_node_error)?;
+        let node = group
+            .nodes
+            .get(top.node_index)
+            .ok_or_else(pending_node_error)?;
+        if let (Some(expected), Some(actual)) =
+            (&snapshot.node_hash, group.node_hashes.get(top.node_index))
+        {
+            if expected != actual {
+                return Err(ScriptLangError::new(
+                    ErrorCode::SnapshotNodeChanged,
+                    format!(
+                        "Pending node \"{}\" changed since the snapshot was taken.",
+                        node.id()
+                    ),
+                ));
+            }
+        }
+
+        let (kind, matches) = match (&snapshot.pending_boundary, node) {
+            (SnapshotPendingBoundary::Choice { node_id, .. }, ScriptNode::Choice { id, .. }) => {
+                ("choice", Some(id.as_str() == node_id.as_str()))
+            }
+            (SnapshotPendingBoundary::Choice { .. }, _) => ("choice", None),
+            (SnapshotPendingBoundary::Input { node_id, .. }, ScriptNode::Input { id, .. }) => {
+                ("input", Some(id.as_str() == node_id.as_str()))
+            }
+            (SnapshotPendingBoundary::Input { .. }, _) => ("input", None),
+            (
+                SnapshotPendingBoundary::Checkpoint {
+                    node_id,
+                    checkpoint_id,
+                },
+                ScriptNode::Checkpoint {
+                    id,
+                    checkpoint_id: actual_checkpoint_id,
+                    ..
+                },
+            ) => (
+                "checkpoint",
+                Some(
+                    id.as_str() == node_id.as_str()
+                        && actual_checkpoint_id.as_str() == checkpoint_id.as_str(),
+                ),
+            ),
+            (SnapshotPendingBoundary::Checkpoint { .. }, _) => ("checkpoint", None),
+        };
+        match matches {
+            Some(true) => Ok(()),
+            Some(false) => Err(ScriptLangError::new(
+                ErrorCode::SnapshotPendingBoundary,
+                format!("Snapshot pending {} node mismatch.", kind),
+            )),
+            None => Err(ScriptLangError::new(
+                ErrorCode::SnapshotPendingBoundary,
+                format!("Snapshot pending boundary expects {} node.", kind),
+            )),
+        }
+    }
+}
+
 impl ScriptLangEngine {
     pub fn snapshot(&self) -> Result<Snapshot, ScriptLangError> {
         let Some(boundary) = &self.pending_boundary else {
             return Err(ScriptLangError::new(
-                "SNAPSHOT_NOT_ALLOWED",
+                ErrorCode::SnapshotNotAllowed,
                 "snapshot() is only allowed while waiting for a choice or input.",
             ));
         };
+        self.build_snapshot(snapshot_pending_boundary(boundary))
+    }
 
-        let runtime_frames = self
-            .frames
-            .iter()
-            .map(|frame| SnapshotFrame {
-                frame_id: frame.frame_id,
-                group_id: frame.group_id.clone(),
-                node_index: frame.node_index,
-                scope: frame.scope.clone(),
-                var_types: frame.var_types.clone(),
-                completion: match frame.completion {
-                    CompletionKind::None => SnapshotCompletion::None,
-                    CompletionKind::WhileBody => SnapshotCompletion::WhileBody,
-                    CompletionKind::ResumeAfterChild => SnapshotCompletion::ResumeAfterChild,
-                },
-                script_root: frame.script_root,
-                return_continuation: frame.return_continuation.clone(),
-            })
-            .collect::<Vec<_>>();
-
-        let pending_boundary = match boundary {
-            RuntimePendingBoundary::Choice {
-                node_id,
-                options,
-                prompt_text,
-                ..
-            } => SnapshotPendingBoundary::Choice {
-                node_id: node_id.clone(),
-                items: options.iter().map(|option| option.item.clone()).collect(),
-                prompt_text: prompt_text.clone(),
-                dynamic_bindings: options
-                    .iter()
-                    .filter_map(|option| {
-                        option
-                            .dynamic_binding
-                            .clone()
-                            .map(|binding| (option.item.id.clone(), binding))
-                    })
-                    .collect(),
-            },
-            RuntimePendingBoundary::Input {
-                node_id,
-                target_var,
-                prompt_text,
-                default_text,
-                max_length,
-                ..
-            } => SnapshotPendingBoundary::Input {
-                node_id: node_id.clone(),
-                target_var: target_var.clone(),
-                prompt_text: prompt_text.clone(),
-                default_text: default_text.clone(),
-                max_length: *max_length,
-            },
-        };
+    /// Capture the engine state with `pending_boundary`; the top frame must still point at the
+    /// node the boundary names so `node_hash` matches it.
+    pub(super) fn build_snapshot(
+        &self,
+        pending_boundary: SnapshotPendingBoundary,
+    ) -> Result<Snapshot, ScriptLangError> {
+        let runtime_frames = self.snapshot_frames();
 
         let once_state_by_script = self
             .once_state_by_script
@@ -87,41 +361,127 @@ impl ScriptLangEngine {
             pending_boundary,
             module_vars: self.module_vars_value.clone(),
             once_state_by_script,
+            global_once_state: self.global_once_state.iter().cloned().collect(),
+            select_count_by_script: self.select_count_by_script.clone(),
+            visit_counts: self.node_visits.borrow().counts.clone(),
+            coverage: self.coverage.clone(),
+            clock: Some(self.clock.get()),
+            entry_script: self.entry_script.clone(),
+            entry_args: self.entry_args.clone(),
+            node_hash: self.top_node_hash(),
+            random_override_index: self.random_override_index(),
         })
     }
 
-    pub fn resume(&mut self, snapshot: Snapshot) -> Result<(), ScriptLangError> {
-        if snapshot.schema_version != SNAPSHOT_SCHEMA {
-            return Err(ScriptLangError::new(
-                "SNAPSHOT_SCHEMA",
-                format!(
-                    "Unsupported snapshot schema \"{}\".",
-                    snapshot.schema_version
-                ),
-            ));
+    fn random_override_index(&self) -> Option<usize> {
+        let forced = self.random_override.borrow();
+        (!forced.values.is_empty()).then_some(forced.index)
+    }
+
+    fn top_node_hash(&self) -> Option<String> {
+        let frame = self.frames.last()?;
+        let (_, group) = self.lookup_group(&frame.group_id).ok()?;
+        group.node_hashes.get(frame.node_index).cloned()
+    }
+
+    /// Dump the frame stack, pending boundary, rng state and module vars for diagnostics.
+    ///
+    /// Unlike `snapshot()` this works between boundaries too, e.g. from a host function or while
+    /// a long text run is still being emitted; it cannot be passed to `resume`.
+    pub fn debug_dump(&self) -> DebugState {
+        DebugState {
+            frames: self.snapshot_frames(),
+            pending_boundary: self
+                .pending_boundary
+                .as_ref()
+                .map(snapshot_pending_boundary),
+            rng_state: self.current_seeded_rng_state(),
+            module_vars: self.module_vars_value.clone(),
+            waiting_choice: self.waiting_choice,
+            ended: self.ended,
         }
+    }
 
-        if snapshot.compiler_version != self.compiler_version {
+    fn snapshot_frames(&self) -> Vec<SnapshotFrame> {
+        self.frames
+            .iter()
+            .map(|frame| SnapshotFrame {
+                frame_id: frame.frame_id,
+                group_id: frame.group_id.clone(),
+                node_index: frame.node_index,
+                scope: frame.scope.clone(),
+                var_types: frame.var_types.clone(),
+                completion: match frame.completion {
+                    CompletionKind::None => SnapshotCompletion::None,
+                    CompletionKind::WhileBody => SnapshotCompletion::WhileBody,
+                    CompletionKind::ResumeAfterChild => SnapshotCompletion::ResumeAfterChild,
+                },
+                script_root: frame.script_root,
+                return_continuation: frame.return_continuation.clone(),
+                text_page: (frame.text_page > 0).then_some(frame.text_page),
+                while_iterations: (frame.while_iterations > 0).then_some(frame.while_iterations),
+            })
+            .collect()
+    }
+
+    /// Restore the state captured before the most recent `choose`/`submit_input`.
+    ///
+    /// Requires the engine to be created with `behavior.history` on; the ring keeps up to
+    /// `HISTORY_CAPACITY` boundaries and is cleared by `start`.
+    pub fn rewind(&mut self) -> Result<(), ScriptLangError> {
+        let Some(snapshot) = self.history.as_mut().and_then(VecDeque::pop_back) else {
             return Err(ScriptLangError::new(
-                "SNAPSHOT_COMPILER_VERSION",
-                format!(
-                    "Snapshot compiler version \"{}\" does not match engine \"{}\".",
-                    snapshot.compiler_version, self.compiler_version
-                ),
+                ErrorCode::EngineNoHistory,
+                "No earlier boundary is available to rewind to.",
             ));
+        };
+        self.resume(snapshot)
+    }
+
+    pub(super) fn history_checkpoint(&self) -> Option<Snapshot> {
+        self.history.as_ref()?;
+        self.snapshot().ok()
+    }
+
+    pub(super) fn push_history(&mut self, checkpoint: Option<Snapshot>) {
+        let (Some(history), Some(snapshot)) = (self.history.as_mut(), checkpoint) else {
+            return;
+        };
+        if history.len() == HISTORY_CAPACITY {
+            history.pop_front();
         }
+        history.push_back(snapshot);
+    }
+
+    pub fn resume(&mut self, snapshot: Snapshot) -> Result<(), ScriptLangError> {
+        let target = SnapshotTarget {
+            scripts: &self.scripts,
+            group_lookup: &self.group_lookup,
+            compiler_version: &self.compiler_version,
+        };
+        target.check_schema(&snapshot)?;
+        target.check_compiler_version(&snapshot)?;
+        target.check_entry_script(&snapshot)?;
+        target.check_groups(&snapshot)?;
+        target.check_node_indices(&snapshot)?;
+        target.check_pending_node(&snapshot)?;
 
         self.reset();
         self.initialize_module_consts()?;
+        self.entry_script = snapshot.entry_script;
+        self.entry_args = snapshot.entry_args;
         self.seeded_rng_state = snapshot.rng_state;
         if self.initial_random_sequence.is_none() {
             *self.shared_rng_state.borrow_mut() = RuntimeRandomState::Seeded(snapshot.rng_state);
         }
+        if let Some(index) = snapshot.random_override_index {
+            self.random_override.borrow_mut().index = index;
+        }
 
         for qualified_name in snapshot.module_vars.keys() {
             if !self.module_var_declarations.contains_key(qualified_name) {
                 return Err(ScriptLangError::new(
-                    "SNAPSHOT_MODULE_GLOBAL_UNKNOWN",
+                    ErrorCode::SnapshotModuleGlobalUnknown,
                     format!(
                         "Snapshot contains unknown module global \"{}\".",
                         qualified_name
@@ -139,7 +499,7 @@ impl ScriptLangEngine {
                 .unwrap_or_else(|| default_value_from_type(&decl.r#type));
             if !is_type_compatible(&value, &decl.r#type) {
                 return Err(ScriptLangError::new(
-                    "SNAPSHOT_MODULE_GLOBAL_TYPE",
+                    ErrorCode::SnapshotModuleGlobalType,
                     format!(
                         "Module global \"{}\" from snapshot does not match declared type.",
                         qualified_name
@@ -155,6 +515,15 @@ impl ScriptLangEngine {
             .into_iter()
             .map(|(script, entries)| (script, entries.into_iter().collect()))
             .collect();
+        self.global_once_state = snapshot.global_once_state.into_iter().collect();
+        self.select_count_by_script = snapshot.select_count_by_script;
+        self.node_visits.borrow_mut().counts = snapshot.visit_counts;
+        if let Some(clock) = snapshot.clock {
+            self.clock.set(clock);
+        }
+        if let (Some(coverage), Some(saved)) = (self.coverage.as_mut(), snapshot.coverage) {
+            *coverage = saved;
+        }
 
         self.frames = snapshot
             .runtime_frames
@@ -172,6 +541,9 @@ impl ScriptLangEngine {
                 script_root: frame.script_root,
                 return_continuation: frame.return_continuation,
                 var_types: frame.var_types,
+                text_page: frame.text_page.unwrap_or(0),
+                text_pages: None,
+                while_iterations: frame.while_iterations.unwrap_or(0),
             })
             .collect();
 
@@ -183,58 +555,36 @@ impl ScriptLangEngine {
             .unwrap_or(0)
             + 1;
 
-        let top = self
+        let top_frame_id = self
             .frames
             .last()
-            .ok_or_else(|| {
-                ScriptLangError::new("SNAPSHOT_EMPTY", "Snapshot contains no runtime frames.")
-            })?
-            .clone();
-
-        let node = {
-            let (_, group) = self.lookup_group(&top.group_id)?;
-            let node = group
-                .nodes
-                .get(top.node_index)
-                .ok_or_else(|| {
-                    ScriptLangError::new("SNAPSHOT_PENDING_BOUNDARY", "Pending node index invalid.")
-                })?
-                .clone();
-            node
-        };
+            .map(|frame| frame.frame_id)
+            .expect("validated snapshot has a top frame");
 
-        self.pending_boundary = Some(match snapshot.pending_boundary {
+        // `check_pending_node` already matched the boundary against the node it names.
+        self.pending_boundary = match snapshot.pending_boundary {
             SnapshotPendingBoundary::Choice {
                 node_id,
                 items,
                 prompt_text,
                 dynamic_bindings,
             } => {
-                let ScriptNode::Choice { id, .. } = node else {
-                    return Err(ScriptLangError::new(
-                        "SNAPSHOT_PENDING_BOUNDARY",
-                        "Snapshot pending boundary expects choice node.",
-                    ));
-                };
-                if id != node_id {
-                    return Err(ScriptLangError::new(
-                        "SNAPSHOT_PENDING_BOUNDARY",
-                        "Snapshot pending choice node mismatch.",
-                    ));
-                }
                 self.waiting_choice = true;
-                RuntimePendingBoundary::Choice {
-                    frame_id: top.frame_id,
+                Some(RuntimePendingBoundary::Choice {
+                    frame_id: top_frame_id,
                     node_id,
+                    // Indices are positions among the offered options; renumber in case the
+                    // saved list was edited so `choose` and the re-emitted items agree.
                     options: items
                         .into_iter()
-                        .map(|item| PendingChoiceOption {
+                        .enumerate()
+                        .map(|(index, item)| PendingChoiceOption {
                             dynamic_binding: dynamic_bindings.get(&item.id).cloned(),
-                            item,
+                            item: ChoiceItem { index, ..item },
                         })
                         .collect(),
                     prompt_text,
-                }
+                })
             }
             SnapshotPendingBoundary::Input {
                 node_id,
@@ -243,29 +593,22 @@ impl ScriptLangEngine {
                 default_text,
                 max_length,
             } => {
-                let ScriptNode::Input { id, .. } = node else {
-                    return Err(ScriptLangError::new(
-                        "SNAPSHOT_PENDING_BOUNDARY",
-                        "Snapshot pending boundary expects input node.",
-                    ));
-                };
-                if id != node_id {
-                    return Err(ScriptLangError::new(
-                        "SNAPSHOT_PENDING_BOUNDARY",
-                        "Snapshot pending input node mismatch.",
-                    ));
-                }
                 self.waiting_choice = false;
-                RuntimePendingBoundary::Input {
-                    frame_id: top.frame_id,
+                Some(RuntimePendingBoundary::Input {
+                    frame_id: top_frame_id,
                     node_id,
                     target_var,
                     prompt_text,
                     default_text,
                     max_length,
-                }
+                })
             }
-        });
+            SnapshotPendingBoundary::Checkpoint { .. } => {
+                // The checkpoint itself already ran; continue with the node after it.
+                self.bump_top_node_index(1)?;
+                None
+            }
+        };
 
         Ok(())
     }
@@ -283,6 +626,7 @@ mod snapshot_tests {
             EngineOutput::Debug { .. } => "debug",
             EngineOutput::Choices { .. } => "choices",
             EngineOutput::Input { .. } => "input",
+            EngineOutput::Checkpoint { .. } => "checkpoint",
             EngineOutput::End => "end",
         }
     }
@@ -299,6 +643,7 @@ mod snapshot_tests {
         match pending {
             PendingBoundary::Choice { .. } => "choice",
             PendingBoundary::Input { .. } => "input",
+            PendingBoundary::Checkpoint { .. } => "checkpoint",
         }
     }
 
@@ -306,6 +651,7 @@ mod snapshot_tests {
         match pending {
             PendingBoundary::Choice { node_id, .. } => node_id.clone(),
             PendingBoundary::Input { node_id, .. } => node_id.clone(),
+            PendingBoundary::Checkpoint { node_id, .. } => node_id.clone(),
         }
     }
 
@@ -345,6 +691,164 @@ mod snapshot_tests {
         assert_eq!(output_kind(&next), "text");
     }
 
+    #[test]
+    pub(super) fn rewind_restores_previous_choice_boundary() {
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <choice text="Pick">
+        <option text="A"><text>Alpha</text></option>
+        <option text="B"><text>Beta</text></option>
+      </choice>
+      <end/>
+    </script>
+    "#,
+        )]));
+        engine.start("main", None).expect("start");
+        assert_eq!(
+            engine.rewind().expect_err("history disabled").code,
+            "ENGINE_NO_HISTORY"
+        );
+
+        engine.history = Some(VecDeque::new());
+        assert_eq!(
+            output_kind(&engine.next_output().expect("choices")),
+            "choices"
+        );
+        engine.choose(0).expect("choose");
+        assert!(matches!(
+            engine.next_output().expect("text"),
+            EngineOutput::Text { text, .. } if text == "Alpha"
+        ));
+
+        engine.rewind().expect("rewind");
+        assert_eq!(
+            output_kind(&engine.next_output().expect("choices")),
+            "choices"
+        );
+        engine.choose(1).expect("choose");
+        assert!(matches!(
+            engine.next_output().expect("text"),
+            EngineOutput::Text { text, .. } if text == "Beta"
+        ));
+        engine.rewind().expect("rewind");
+        assert_eq!(
+            engine.rewind().expect_err("history exhausted").code,
+            "ENGINE_NO_HISTORY"
+        );
+
+        for _ in 0..HISTORY_CAPACITY + 1 {
+            engine.push_history(Some(engine.snapshot().expect("snapshot")));
+        }
+        assert_eq!(
+            engine.history.as_ref().map(VecDeque::len),
+            Some(HISTORY_CAPACITY)
+        );
+    }
+
+    #[test]
+    pub(super) fn snapshot_records_entry_script_and_args_across_round_trip() {
+        let files = map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main" args="int:hp">
+      <choice text="Pick">
+        <option text="Go"><text>hp=${hp}</text></option>
+      </choice>
+      <end/>
+    </script>
+    "#,
+        )]);
+        let entry_args = BTreeMap::from([("hp".to_string(), SlValue::Number(7.0))]);
+        let mut engine = engine_from_sources(files.clone());
+        engine
+            .start("main", Some(entry_args.clone()))
+            .expect("start");
+        engine.next_output().expect("choices");
+        let snapshot = engine.snapshot().expect("snapshot");
+        assert_eq!(snapshot.entry_script, "main");
+        assert_eq!(snapshot.entry_args, entry_args);
+
+        let json = serde_json::to_string(&snapshot).expect("serialize");
+        let decoded: Snapshot = serde_json::from_str(&json).expect("deserialize");
+        assert_eq!(decoded, snapshot);
+
+        let mut resumed = engine_from_sources(files.clone());
+        resumed.resume(decoded).expect("resume");
+        let again = resumed.snapshot().expect("snapshot after resume");
+        assert_eq!(again.entry_script, "main");
+        assert_eq!(again.entry_args, entry_args);
+
+        let mut legacy: serde_json::Value = serde_json::from_str(&json).expect("json value");
+        let object = legacy.as_object_mut().expect("snapshot object");
+        object.remove("entryScript");
+        object.remove("entryArgs");
+        let legacy: Snapshot = serde_json::from_value(legacy).expect("legacy snapshot");
+        assert!(legacy.entry_script.is_empty());
+        engine_from_sources(files.clone())
+            .resume(legacy)
+            .expect("legacy snapshot without entry fields should resume");
+
+        let mut unknown = snapshot;
+        unknown.entry_script = "missing".to_string();
+        let error = engine_from_sources(files)
+            .resume(unknown)
+            .expect_err("unknown entry script should fail");
+        assert_eq!(error.code, "SNAPSHOT_ENTRY_SCRIPT_UNKNOWN");
+    }
+
+    #[test]
+    pub(super) fn choice_timeout_default_survives_snapshot_resume() {
+        let files = map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <choice text="Pick" timeout_default="2">
+        <option text="A" when="false"><text>Alpha</text></option>
+        <option text="B"><text>Beta</text></option>
+        <option text="C"><text>Gamma</text></option>
+      </choice>
+      <choice text="Again">
+        <option text="D"><text>Delta</text></option>
+      </choice>
+    </script>
+    "#,
+        )]);
+        let mut engine = engine_from_sources(files.clone());
+        engine.start("main", None).expect("start");
+        assert!(matches!(
+            engine.next_output().expect("choices"),
+            EngineOutput::Choices { items, default_index: Some(1), .. } if items[1].text == "C"
+        ));
+        let snapshot = engine.snapshot().expect("snapshot");
+
+        let mut resumed = engine_from_sources(files);
+        resumed.resume(snapshot).expect("resume");
+        assert!(
+            matches!(
+                resumed.next_output().expect("choices"),
+                EngineOutput::Choices {
+                    default_index: Some(1),
+                    ..
+                }
+            ),
+            "resumed engine should re-emit choices with their default"
+        );
+        resumed.choose(1).expect("choose default");
+        assert!(matches!(
+            resumed.next_output().expect("text"),
+            EngineOutput::Text { text, .. } if text == "Gamma"
+        ));
+        assert!(matches!(
+            resumed.next_output().expect("choices"),
+            EngineOutput::Choices {
+                default_index: None,
+                ..
+            }
+        ));
+    }
+
     #[test]
     pub(super) fn snapshot_and_resume_cover_while_completion_and_once_state() {
         let files = map(&[(
@@ -419,6 +923,275 @@ mod snapshot_tests {
         assert_eq!(error.code, "SNAPSHOT_NOT_ALLOWED");
     }
 
+    #[test]
+    fn resume_rejects_snapshot_whose_caller_frame_group_was_removed() {
+        let files = map(&[(
+            "main.xml",
+            r#"<module name="main" export="script:main">
+<script name="pick" kind="call">
+  <choice text="Pick"><option text="A"><text>A</text></option></choice>
+  <return/>
+</script>
+<script name="main">
+  <if when="true"><call script="@pick"/><text>back</text></if>
+  <end/>
+</script>
+</module>"#,
+        )]);
+        let mut engine = engine_from_sources(files.clone());
+        engine.start("main", None).expect("start");
+        assert_eq!(
+            output_kind(&engine.next_output().expect("choices")),
+            "choices"
+        );
+        let snapshot = engine.snapshot().expect("snapshot");
+        let if_group_id = snapshot.runtime_frames[1].group_id.clone();
+
+        let mut compiled = compile_project_from_sources(files);
+        for script in compiled.scripts.values_mut() {
+            script.groups.remove(&if_group_id);
+        }
+        let mut edited = ScriptLangEngine::new(ScriptLangEngineOptions {
+            scripts: compiled.scripts,
+            module_var_declarations: compiled.module_var_declarations,
+            module_var_init_order: compiled.module_var_init_order,
+            ..Default::default()
+        })
+        .expect("engine should build");
+        let error = edited
+            .resume(snapshot)
+            .expect_err("removed caller group should fail");
+        assert_eq!(error.code, "SNAPSHOT_GROUP_MISSING");
+        assert!(error.message.contains(&if_group_id));
+    }
+
+    #[test]
+    fn resume_continues_at_recorded_node_without_rerunning_earlier_nodes() {
+        let story = map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <temp name="count" type="int">0</temp>
+      <while when="count LT 2">
+        <code>count = count + 1;</code>
+        <text once="true">Intro</text>
+        <choice text="Pick">
+          <option text="Go"><text>${count}</text></option>
+        </choice>
+      </while>
+      <end/>
+    </script>
+    "#,
+        )]);
+        let mut source = engine_from_sources(story.clone());
+        source.start("main", None).expect("start");
+        assert!(matches!(
+            source.next_output().expect("intro"),
+            EngineOutput::Text { text, .. } if text == "Intro"
+        ));
+        assert_eq!(
+            output_kind(&source.next_output().expect("choices")),
+            "choices"
+        );
+        let snapshot = source.snapshot().expect("snapshot");
+        let top = snapshot.runtime_frames.last().expect("top frame");
+        let (_, group) = source.lookup_group(&top.group_id).expect("group");
+        assert!(matches!(
+            group.nodes[top.node_index],
+            ScriptNode::Choice { .. }
+        ));
+
+        let mut resumed = engine_from_sources(story);
+        resumed.resume(snapshot).expect("resume");
+        resumed.choose(0).expect("choose");
+        let mut rendered = Vec::new();
+        loop {
+            match resumed.next_output().expect("output") {
+                EngineOutput::Text { text, .. } => rendered.push(text),
+                EngineOutput::Choices { .. } => {
+                    resumed.choose(0).expect("choose");
+                }
+                other => {
+                    assert_eq!(other, EngineOutput::End, "unexpected output");
+                    break;
+                }
+            }
+        }
+        assert_eq!(rendered, vec!["1", "2"]);
+    }
+
+    #[test]
+    fn inspect_snapshot_reports_compatibility_without_an_engine() {
+        let story = map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <text>Intro</text>
+      <choice text="Pick">
+        <option text="A"><text>A</text></option>
+        <option text="B"><text>B</text></option>
+      </choice>
+      <end/>
+    </script>
+    "#,
+        )]);
+        let mut engine = engine_from_sources(story);
+        engine.compiler_version = format!("{}+inline-0123456789abcdef", DEFAULT_COMPILER_VERSION);
+        engine.start("main", None).expect("start");
+        engine.next_output().expect("intro");
+        engine.next_output().expect("choices");
+        let snapshot = engine.snapshot().expect("snapshot");
+
+        let report = inspect_snapshot(&snapshot, &engine.scripts, &engine.compiler_version);
+        assert!(report.is_compatible(), "{}", report.summary);
+        assert!(report.schema_compatible && report.compiler_version_compatible);
+        assert!(report.entry_script_known && report.groups_resolvable);
+        assert!(report.node_indices_valid && report.pending_node_valid);
+        assert_eq!(report.entry_script, snapshot.entry_script);
+        assert_eq!(report.frame_count, snapshot.runtime_frames.len());
+        assert!(report.pending.starts_with("choice ") && report.pending.ends_with("(2 options)"));
+        assert!(
+            report.summary.ends_with("; compatible"),
+            "{}",
+            report.summary
+        );
+
+        let report = inspect_snapshot(&snapshot, &engine.scripts, DEFAULT_COMPILER_VERSION);
+        assert!(!report.compiler_version_compatible);
+        assert_eq!(report.problems[0].code, "SNAPSHOT_COMPILER_VERSION");
+
+        let mut orphaned = snapshot;
+        orphaned
+            .runtime_frames
+            .last_mut()
+            .expect("top frame")
+            .group_id = "removed.group".to_string();
+        let report = inspect_snapshot(&orphaned, &engine.scripts, &engine.compiler_version);
+        assert!(!report.is_compatible());
+        assert!(report.schema_compatible && report.compiler_version_compatible);
+        assert!(!report.groups_resolvable);
+        assert!(!report.node_indices_valid && !report.pending_node_valid);
+        assert_eq!(
+            report
+                .problems
+                .iter()
+                .map(|error| error.code.as_str())
+                .collect::<Vec<_>>(),
+            vec!["SNAPSHOT_GROUP_MISSING"]
+        );
+        assert!(report
+            .summary
+            .contains("incompatible: SNAPSHOT_GROUP_MISSING"));
+        let error = engine.resume(orphaned).expect_err("resume agrees");
+        assert_eq!(error.code, report.problems[0].code);
+    }
+
+    #[test]
+    fn checkpoint_snapshot_resumes_at_the_following_node() {
+        let story = map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <temp name="hp" type="int">1</temp>
+      <text>Before</text>
+      <code>hp = hp + 1;</code>
+      <checkpoint id="camp"/>
+      <text>After ${hp}</text>
+      <end/>
+    </script>
+    "#,
+        )]);
+        let mut source = engine_from_sources(story.clone());
+        source.start("main", None).expect("start");
+        let outputs = source
+            .outputs()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("outputs");
+        assert_eq!(
+            outputs.iter().map(output_kind).collect::<Vec<_>>(),
+            vec!["text", "checkpoint", "text", "end"]
+        );
+        let (id, snapshot) = match &outputs[1] {
+            EngineOutput::Checkpoint { id, snapshot } => Some((id, snapshot)),
+            _ => None,
+        }
+        .expect("checkpoint expected");
+        assert_eq!(id, "camp");
+        assert_eq!(pending_kind(&snapshot.pending_boundary), "checkpoint");
+        assert_eq!(
+            source.snapshot().expect_err("ended engine").code,
+            "SNAPSHOT_NOT_ALLOWED"
+        );
+
+        let json = serde_json::to_string(snapshot.as_ref()).expect("serialize");
+        let persisted: Snapshot = serde_json::from_str(&json).expect("deserialize");
+        let mut resumed = engine_from_sources(story.clone());
+        resumed.resume(persisted.clone()).expect("resume");
+        assert!(matches!(
+            resumed.next_output().expect("after"),
+            EngineOutput::Text { text, .. } if text == "After 2"
+        ));
+        assert_eq!(output_kind(&resumed.next_output().expect("end")), "end");
+
+        let mut renamed = persisted;
+        renamed.pending_boundary = PendingBoundary::Checkpoint {
+            node_id: pending_node_id(&renamed.pending_boundary),
+            checkpoint_id: "other".to_string(),
+        };
+        let error = engine_from_sources(story)
+            .resume(renamed)
+            .expect_err("checkpoint id mismatch");
+        assert_eq!(error.code, "SNAPSHOT_PENDING_BOUNDARY");
+    }
+
+    #[test]
+    fn resume_rejects_snapshot_whose_pending_node_was_edited() {
+        let story = |option_text: &str, intro: &str| {
+            map(&[(
+                "main.script.xml",
+                format!(
+                    r#"
+    <script name="main">
+      <text>{}</text>
+      <choice text="Pick">
+        <option text="{}"><text>A</text></option>
+      </choice>
+    </script>
+    "#,
+                    intro, option_text
+                )
+                .as_str(),
+            )])
+        };
+
+        let mut base = engine_from_sources(story("Open", "Hi"));
+        base.start("main", None).expect("start");
+        base.next_output().expect("text");
+        assert_eq!(
+            output_kind(&base.next_output().expect("choices")),
+            "choices"
+        );
+        let snapshot = base.snapshot().expect("snapshot");
+        assert!(snapshot.node_hash.is_some());
+
+        let mut unrelated_edit = engine_from_sources(story("Open", "Hello there"));
+        unrelated_edit
+            .resume(snapshot.clone())
+            .expect("edits elsewhere should still resume");
+
+        let mut edited = engine_from_sources(story("Break down the door", "Hi"));
+        let error = edited
+            .resume(snapshot.clone())
+            .expect_err("edited pending node should fail");
+        assert_eq!(error.code, "SNAPSHOT_NODE_CHANGED");
+
+        let mut legacy = snapshot;
+        legacy.node_hash = None;
+        edited
+            .resume(legacy)
+            .expect("snapshots without a node hash skip the check");
+    }
+
     #[test]
     pub(super) fn resume_validates_schema_and_compiler_version() {
         let sources = map(&[(
@@ -527,9 +1300,19 @@ mod snapshot_tests {
             .last_mut()
             .expect("snapshot should contain frame");
         frame.node_index = 9999;
+        let error = fresh
+            .resume(bad_index.clone())
+            .expect_err("out-of-range node index should fail");
+        assert_eq!(error.code, "SNAPSHOT_NODE_INDEX_OOB");
+
+        let frame = bad_index
+            .runtime_frames
+            .last_mut()
+            .expect("snapshot should contain frame");
+        frame.node_index = 1;
         let error = fresh
             .resume(bad_index)
-            .expect_err("invalid pending node index should fail");
+            .expect_err("index at group end has no pending node");
         assert_eq!(error.code, "SNAPSHOT_PENDING_BOUNDARY");
     }
 
@@ -708,6 +1491,112 @@ mod snapshot_tests {
         assert!(matches!(text, EngineOutput::Text { text, .. } if text == "15"));
     }
 
+    #[test]
+    fn session_module_vars_survive_restart_and_snapshot() {
+        let files = map(&[(
+            "main.xml",
+            r#"<module name="main" export="script:hub,shop">
+  <var name="visits" type="int" session="true">0</var>
+  <var name="coins" type="int">5</var>
+  <script name="hub">
+    <code>visits = visits + 1; coins = coins + 1;</code>
+    <text>hub ${visits} ${coins}</text>
+    <choice text="Go">
+      <option text="A"><end/></option>
+    </choice>
+  </script>
+  <script name="shop">
+    <text>shop ${visits} ${coins}</text>
+    <end/>
+  </script>
+</module>"#,
+        )]);
+        let next_text = |engine: &mut ScriptLangEngine| {
+            output_text(engine.next_output().expect("next")).expect("text expected")
+        };
+
+        let mut engine = engine_from_sources(files.clone());
+        engine.start("main.hub", None).expect("start");
+        assert_eq!(next_text(&mut engine), "hub 1 6");
+        engine.start("main.shop", None).expect("restart");
+        assert_eq!(next_text(&mut engine), "shop 1 5");
+        engine.start("main.hub", None).expect("restart");
+        assert_eq!(next_text(&mut engine), "hub 2 6");
+        assert_eq!(
+            output_kind(&engine.next_output().expect("choice")),
+            "choices"
+        );
+
+        let snapshot = engine.snapshot().expect("snapshot");
+        assert_eq!(
+            snapshot.module_vars.get("main.visits"),
+            Some(&SlValue::Number(2.0))
+        );
+        let mut resumed = engine_from_sources(files);
+        resumed.resume(snapshot).expect("resume");
+        resumed
+            .start("main.shop", None)
+            .expect("restart after resume");
+        assert_eq!(next_text(&mut resumed), "shop 2 5");
+    }
+
+    #[test]
+    fn debug_dump_reports_live_frames_between_boundaries() {
+        let files = map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <temp name="hp" type="int">3</temp>
+      <text>first</text>
+      <text>second</text>
+      <choice text="Go">
+        <option text="A"><end/></option>
+      </choice>
+    </script>
+    "#,
+        )]);
+        let mut engine = engine_from_sources(files);
+        engine.start("main", None).expect("start");
+        assert_eq!(output_kind(&engine.next_output().expect("text")), "text");
+        assert!(engine.snapshot().is_err());
+
+        let dump = engine.debug_dump();
+        assert_eq!(dump.frames.len(), 1);
+        let frame = &dump.frames[0];
+        assert!(frame.script_root);
+        assert_eq!(frame.node_index, 2);
+        assert_eq!(frame.scope.get("hp"), Some(&SlValue::Number(3.0)));
+        assert_eq!(
+            frame.var_types.get("hp"),
+            Some(&ScriptType::Primitive {
+                name: "int".to_string(),
+            })
+        );
+        assert_eq!(frame.completion, SnapshotCompletion::None);
+        assert!(dump.pending_boundary.is_none());
+        assert!(!dump.waiting_choice);
+        assert!(!dump.ended);
+        let json = serde_json::to_value(&dump).expect("debug state should serialize");
+        assert_eq!(json["frames"][0]["nodeIndex"], 2);
+        assert_eq!(json["pendingBoundary"], serde_json::Value::Null);
+
+        assert_eq!(output_kind(&engine.next_output().expect("text")), "text");
+        assert_eq!(
+            output_kind(&engine.next_output().expect("choice")),
+            "choices"
+        );
+        let dump = engine.debug_dump();
+        assert!(matches!(
+            dump.pending_boundary,
+            Some(PendingBoundary::Choice { ref items, .. }) if items.len() == 1
+        ));
+        assert!(dump.waiting_choice);
+        assert_eq!(
+            dump.frames,
+            engine.snapshot().expect("snapshot").runtime_frames
+        );
+    }
+
     #[test]
     pub(super) fn snapshot_does_not_store_module_consts_and_resume_rebuilds_them() {
         let files = map(&[(
@@ -936,11 +1825,11 @@ mod snapshot_tests {
             module_var_init_order: compiled.module_var_init_order.clone(),
             module_const_declarations: compiled.module_const_declarations.clone(),
             module_const_init_order: compiled.module_const_init_order.clone(),
-            host_functions: None,
             random_seed: Some(1),
             random_sequence: Some(vec![7, 9]),
             random_sequence_index: Some(0),
             compiler_version: Some(DEFAULT_COMPILER_VERSION.to_string()),
+            ..Default::default()
         })
         .expect("source engine");
         source.start("main", None).expect("start");
@@ -957,11 +1846,11 @@ mod snapshot_tests {
             module_var_init_order: compiled.module_var_init_order,
             module_const_declarations: compiled.module_const_declarations,
             module_const_init_order: compiled.module_const_init_order,
-            host_functions: None,
             random_seed: Some(1),
             random_sequence: Some(vec![7, 9]),
             random_sequence_index: Some(0),
             compiler_version: Some(DEFAULT_COMPILER_VERSION.to_string()),
+            ..Default::default()
         })
         .expect("target engine");
         target.resume(snapshot).expect("resume");
@@ -1,4 +1,4 @@
-use super::lifecycle::{CompletionKind, PendingBoundary, PendingChoiceOption};
+use super::lifecycle::{CompletionKind, PendingBoundary, PendingChoiceOption, PendingTextPages};
 use super::*;
 
 enum PlannedNode {
@@ -8,15 +8,24 @@ enum PlannedNode {
     Text {
         script_name: String,
         value: String,
+        key: Option<String>,
+        expr: Option<String>,
         tag: Option<String>,
+        speaker: Option<String>,
         once: bool,
+        global_once: bool,
         id: String,
     },
     Debug {
         value: String,
     },
+    Checkpoint {
+        node_id: String,
+        checkpoint_id: String,
+    },
     Code {
         code: String,
+        internal: bool,
     },
     Var {
         declaration: sl_core::VarDeclaration,
@@ -27,8 +36,11 @@ enum PlannedNode {
         else_group_id: Option<String>,
     },
     While {
+        id: String,
         when_expr: String,
         body_group_id: String,
+        max_iterations: Option<usize>,
+        location: sl_core::SourceSpan,
     },
     Choice {
         script_name: String,
@@ -41,6 +53,7 @@ enum PlannedNode {
         target_var: String,
         prompt_text: String,
         max_length: Option<usize>,
+        format: InputFormat,
     },
     Call {
         target_script: ScriptTarget,
@@ -51,6 +64,10 @@ enum PlannedNode {
         args: Vec<sl_core::CallArgument>,
     },
     End,
+    Fail {
+        code: String,
+        message: String,
+    },
     Return {
         node_id: String,
     },
@@ -60,12 +77,65 @@ enum PlannedNode {
     },
 }
 
+/// Split `text` into pages of at most `max_chars` characters, breaking at the last line break,
+/// else the last whitespace, that fits; a single word longer than a page is split mid-word.
+fn paginate_text(text: &str, max_chars: usize) -> Vec<String> {
+    let mut pages = Vec::new();
+    let mut rest = text;
+    while rest.chars().count() > max_chars {
+        let limit = rest
+            .char_indices()
+            .nth(max_chars)
+            .map(|(index, _)| index)
+            .unwrap_or(rest.len());
+        let window = &rest[..limit];
+        let split = window
+            .rfind('\n')
+            .or_else(|| window.rfind(char::is_whitespace))
+            .filter(|index| !window[..*index].trim().is_empty());
+        let (page, next) = match split {
+            Some(index) => (&rest[..index], &rest[index..]),
+            None => (window, &rest[limit..]),
+        };
+        pages.push(page.trim_end().to_string());
+        rest = next.trim_start();
+    }
+    if !rest.is_empty() || pages.is_empty() {
+        pages.push(rest.to_string());
+    }
+    pages
+}
+
 impl ScriptLangEngine {
     fn bump_top_node_index_infallible(&mut self, amount: usize) {
         self.bump_top_node_index(amount)
             .expect("top frame should exist while stepping");
     }
 
+    /// Count an executed node for coverage and, when it is labeled, for `sl_visited`.
+    fn record_coverage(&mut self, group_id: &str, node_index: usize) {
+        if self.coverage.is_none() && self.node_visits.borrow().labeled_nodes.is_empty() {
+            return;
+        }
+        let Some(node) = self
+            .group_lookup
+            .get(group_id)
+            .and_then(|lookup| self.scripts.get(&lookup.script_name))
+            .and_then(|script| script.groups.get(group_id))
+            .and_then(|group| group.nodes.get(node_index))
+        else {
+            return;
+        };
+        let node_id = node.id();
+        let mut visits = self.node_visits.borrow_mut();
+        if visits.labeled_nodes.contains(node_id) {
+            *visits.counts.entry(node_id.to_string()).or_insert(0) += 1;
+        }
+        if let Some(coverage) = self.coverage.as_mut() {
+            *coverage.entry(node_id.to_string()).or_insert(0) += 1;
+        }
+    }
+
     fn top_frame_state(&self) -> Option<(u64, String, usize)> {
         self.frames
             .last()
@@ -88,21 +158,38 @@ impl ScriptLangEngine {
         let planned = match &group.nodes[top_node_index] {
             ScriptNode::Text {
                 value,
+                key,
+                expr,
                 tag,
+                speaker,
                 once,
+                global_once,
                 id,
                 ..
             } => PlannedNode::Text {
                 script_name: script_name.to_string(),
                 value: value.clone(),
+                key: key.clone(),
+                expr: expr.clone(),
                 tag: tag.clone(),
+                speaker: speaker.clone(),
                 once: *once,
+                global_once: *global_once,
                 id: id.clone(),
             },
             ScriptNode::Debug { value, .. } => PlannedNode::Debug {
                 value: value.clone(),
             },
-            ScriptNode::Code { code, .. } => PlannedNode::Code { code: code.clone() },
+            ScriptNode::Checkpoint {
+                id, checkpoint_id, ..
+            } => PlannedNode::Checkpoint {
+                node_id: id.clone(),
+                checkpoint_id: checkpoint_id.clone(),
+            },
+            ScriptNode::Code { code, internal, .. } => PlannedNode::Code {
+                code: code.clone(),
+                internal: *internal,
+            },
             ScriptNode::Var { declaration, .. } => PlannedNode::Var {
                 declaration: declaration.clone(),
             },
@@ -117,12 +204,17 @@ impl ScriptLangEngine {
                 else_group_id: else_group_id.clone(),
             },
             ScriptNode::While {
+                id,
                 when_expr,
                 body_group_id,
-                ..
+                max_iterations,
+                location,
             } => PlannedNode::While {
+                id: id.clone(),
                 when_expr: when_expr.clone(),
                 body_group_id: body_group_id.clone(),
+                max_iterations: max_iterations.or(self.max_while_iterations),
+                location: location.clone(),
             },
             ScriptNode::Choice {
                 id,
@@ -140,12 +232,14 @@ impl ScriptLangEngine {
                 target_var,
                 prompt_text,
                 max_length,
+                format,
                 ..
             } => PlannedNode::Input {
                 id: id.clone(),
                 target_var: target_var.clone(),
                 prompt_text: prompt_text.clone(),
                 max_length: *max_length,
+                format: *format,
             },
             ScriptNode::Call {
                 target_script,
@@ -164,6 +258,10 @@ impl ScriptLangEngine {
                 args: args.clone(),
             },
             ScriptNode::End { .. } => PlannedNode::End,
+            ScriptNode::Fail { code, message, .. } => PlannedNode::Fail {
+                code: code.clone(),
+                message: message.clone(),
+            },
             ScriptNode::Return { id, .. } => PlannedNode::Return {
                 node_id: id.clone(),
             },
@@ -175,6 +273,28 @@ impl ScriptLangEngine {
         Ok(planned)
     }
 
+    /// Iterate `next_output` results until the engine ends or reaches a choice/input boundary.
+    ///
+    /// The boundary (or `End`) is yielded as the last item: the iterator never advances past a
+    /// boundary, so call `choose`/`submit_input` and then `outputs()` again. An error is also
+    /// yielded as the last item. Checkpoints are yielded without stopping.
+    pub fn outputs(&mut self) -> impl Iterator<Item = Result<EngineOutput, ScriptLangError>> + '_ {
+        let mut finished = false;
+        std::iter::from_fn(move || {
+            if finished {
+                return None;
+            }
+            let output = self.next_output();
+            finished = !matches!(
+                output,
+                Ok(EngineOutput::Text { .. }
+                    | EngineOutput::Debug { .. }
+                    | EngineOutput::Checkpoint { .. })
+            );
+            Some(output)
+        })
+    }
+
     pub fn next_output(&mut self) -> Result<EngineOutput, ScriptLangError> {
         if let Some(boundary) = &self.pending_boundary {
             return Ok(self.boundary_output(boundary));
@@ -194,17 +314,68 @@ impl ScriptLangEngine {
             };
 
             let planned_node = self.plan_node(top_frame_id, &top_group_id, top_node_index)?;
+            // Later pages of a paginated `<text>` belong to the execution counted on its first.
+            let continues_text = self.frames.last().is_some_and(|frame| frame.text_page > 0);
+            if !matches!(planned_node, PlannedNode::FinishFrame { .. }) && !continues_text {
+                self.record_coverage(&top_group_id, top_node_index);
+            }
             if let Some(output) = self.execute_planned_node(top_frame_id, planned_node)? {
                 return Ok(output);
             }
         }
 
         Err(ScriptLangError::new(
-            "ENGINE_GUARD_EXCEEDED",
+            ErrorCode::EngineGuardExceeded,
             "Execution guard exceeded 10000 iterations.",
         ))
     }
 
+    /// Render a `<text>` node and its speaker, returning its first page still to emit.
+    ///
+    /// With `max_text_chars`, the later pages are cached on the top frame so each page comes from
+    /// this one render. A frame resumed mid-text starts at its `text_page`.
+    fn render_text_node(
+        &mut self,
+        value: &str,
+        key: Option<&str>,
+        expr: Option<&str>,
+        speaker: Option<String>,
+    ) -> Result<(String, Option<String>), ScriptLangError> {
+        let rendered = match (expr, key) {
+            (Some(expr), _) => {
+                let text = self.render_text_expression(expr)?;
+                self.apply_text_filter(text)
+            }
+            (None, Some(key)) => {
+                let template = self.resolve_locale_text(key)?;
+                self.render_narration(&template)?
+            }
+            (None, None) => self.render_narration(value)?,
+        };
+        let speaker = speaker
+            .map(|speaker| self.render_text(&speaker))
+            .transpose()?;
+        let Some(max_chars) = self.max_text_chars else {
+            return Ok((rendered, speaker));
+        };
+        let frame = self
+            .frames
+            .last_mut()
+            .expect("top frame should exist while stepping");
+        let mut pages = VecDeque::from(paginate_text(&rendered, max_chars));
+        pages.drain(..frame.text_page.min(pages.len() - 1));
+        let first = pages
+            .pop_front()
+            .expect("pagination yields at least one page");
+        if !pages.is_empty() {
+            frame.text_pages = Some(PendingTextPages {
+                remaining: pages,
+                speaker: speaker.clone(),
+            });
+        }
+        Ok((first, speaker))
+    }
+
     fn execute_planned_node(
         &mut self,
         top_frame_id: u64,
@@ -218,25 +389,68 @@ impl ScriptLangEngine {
             PlannedNode::Text {
                 script_name,
                 value,
+                key,
+                expr,
                 tag,
+                speaker,
                 once,
+                global_once,
                 id,
             } => {
-                if once && self.has_once_state(&script_name, &format!("text:{}", id)) {
-                    self.bump_top_node_index_infallible(1);
-                    return Ok(None);
+                let cached_page = self
+                    .frames
+                    .last_mut()
+                    .expect("top frame should exist while stepping")
+                    .text_pages
+                    .as_mut()
+                    .and_then(|pages| {
+                        let text = pages.remaining.pop_front()?;
+                        Some((text, pages.speaker.clone()))
+                    });
+                let (rendered, speaker) = match cached_page {
+                    Some(page) => page,
+                    None => {
+                        if once
+                            && self.has_scoped_once_state(
+                                &script_name,
+                                &format!("text:{}", id),
+                                global_once,
+                            )
+                        {
+                            self.bump_top_node_index_infallible(1);
+                            return Ok(None);
+                        }
+                        self.render_text_node(&value, key.as_deref(), expr.as_deref(), speaker)?
+                    }
+                };
+                let frame = self
+                    .frames
+                    .last_mut()
+                    .expect("top frame should exist while stepping");
+                if frame
+                    .text_pages
+                    .as_ref()
+                    .is_some_and(|pages| !pages.remaining.is_empty())
+                {
+                    frame.text_page += 1;
+                    return Ok(Some(EngineOutput::Text {
+                        text: rendered,
+                        tag,
+                        speaker,
+                    }));
                 }
-
-                let rendered = self.render_text(&value)?;
+                frame.text_pages = None;
+                frame.text_page = 0;
                 self.bump_top_node_index_infallible(1);
 
                 if once {
-                    self.mark_once_state(&script_name, &format!("text:{}", id));
+                    self.mark_scoped_once_state(&script_name, &format!("text:{}", id), global_once);
                 }
 
                 Ok(Some(EngineOutput::Text {
                     text: rendered,
                     tag,
+                    speaker,
                 }))
             }
             PlannedNode::Debug { value } => {
@@ -244,8 +458,27 @@ impl ScriptLangEngine {
                 self.bump_top_node_index_infallible(1);
                 Ok(Some(EngineOutput::Debug { text: rendered }))
             }
-            PlannedNode::Code { code } => {
-                self.run_code(&code)?;
+            PlannedNode::Checkpoint {
+                node_id,
+                checkpoint_id,
+            } => {
+                // Taken before advancing so the snapshot's node hash names the checkpoint.
+                let snapshot = self.build_snapshot(sl_core::PendingBoundary::Checkpoint {
+                    node_id,
+                    checkpoint_id: checkpoint_id.clone(),
+                })?;
+                self.bump_top_node_index_infallible(1);
+                Ok(Some(EngineOutput::Checkpoint {
+                    id: checkpoint_id,
+                    snapshot: Box::new(snapshot),
+                }))
+            }
+            PlannedNode::Code { code, internal } => {
+                if internal {
+                    self.run_internal_code(&code)?;
+                } else {
+                    self.run_code(&code)?;
+                }
                 self.bump_top_node_index_infallible(1);
                 Ok(None)
             }
@@ -264,24 +497,41 @@ impl ScriptLangEngine {
                 if condition {
                     self.push_group_frame(&then_group_id, CompletionKind::ResumeAfterChild)
                         .expect("compiler should emit existing then group");
-                } else {
-                    let else_group_id =
-                        else_group_id.expect("compiler should always synthesize an else group id");
+                } else if let Some(else_group_id) = else_group_id {
                     self.push_group_frame(&else_group_id, CompletionKind::ResumeAfterChild)
                         .expect("compiler should emit existing else group");
                 }
                 Ok(None)
             }
             PlannedNode::While {
+                id,
                 when_expr,
                 body_group_id,
+                max_iterations,
+                location,
             } => {
                 let condition = self.eval_boolean(&when_expr)?;
+                let frame = self
+                    .frames
+                    .last_mut()
+                    .expect("top frame should exist while stepping");
                 if condition {
+                    if let Some(max) = max_iterations.filter(|max| frame.while_iterations >= *max) {
+                        return Err(ScriptLangError::with_span(
+                            ErrorCode::EngineWhileLimitExceeded,
+                            format!(
+                                "<while> \"{}\" exceeded its limit of {} iterations.",
+                                id, max
+                            ),
+                            location,
+                        ));
+                    }
+                    frame.while_iterations += 1;
                     self.push_group_frame(&body_group_id, CompletionKind::WhileBody)
                         .expect("compiler should emit existing while body group");
                 } else {
-                    self.bump_top_node_index_infallible(1);
+                    frame.while_iterations = 0;
+                    frame.node_index += 1;
                 }
                 Ok(None)
             }
@@ -296,7 +546,15 @@ impl ScriptLangEngine {
                 target_var,
                 prompt_text,
                 max_length,
-            } => self.execute_input_node(top_frame_id, &id, &target_var, &prompt_text, max_length),
+                format,
+            } => self.execute_input_node(
+                top_frame_id,
+                &id,
+                &target_var,
+                &prompt_text,
+                max_length,
+                format,
+            ),
             PlannedNode::Call {
                 target_script,
                 args,
@@ -315,6 +573,10 @@ impl ScriptLangEngine {
                 self.execute_end();
                 Ok(None)
             }
+            PlannedNode::Fail { code, message } => {
+                let message = self.render_text(&message)?;
+                Err(ScriptLangError::new(format!("USER_{}", code), message))
+            }
             PlannedNode::Return { node_id } => {
                 self.execute_return(&node_id)?;
                 Ok(None)
@@ -343,6 +605,7 @@ impl ScriptLangEngine {
     ) -> Result<Option<EngineOutput>, ScriptLangError> {
         let mut visible_regular = Vec::<PendingChoiceOption>::new();
         let mut visible_fall_over = None;
+        let mut visible_pinned = Vec::<PendingChoiceOption>::new();
         let mut dynamic_block_ordinal = 0usize;
 
         for entry in entries {
@@ -350,13 +613,19 @@ impl ScriptLangEngine {
                 ChoiceEntry::Static { option } => {
                     if option.fall_over {
                         let visible = !option.once
-                            || !self.has_once_state(script_name, &format!("option:{}", option.id));
+                            || !self.has_scoped_once_state(
+                                script_name,
+                                &format!("option:{}", option.id),
+                                option.global_once,
+                            );
                         if visible {
                             visible_fall_over = Some(PendingChoiceOption {
                                 item: ChoiceItem {
                                     index: 0,
                                     id: option.id.clone(),
-                                    text: self.render_text(&option.text)?,
+                                    text: self.render_narration(&option.text)?,
+                                    scene: option.scene.clone(),
+                                    enabled: true,
                                 },
                                 dynamic_binding: None,
                             });
@@ -365,11 +634,19 @@ impl ScriptLangEngine {
                     }
 
                     if self.is_choice_option_visible(script_name, option)? {
-                        visible_regular.push(PendingChoiceOption {
+                        let enabled = self.is_choice_option_enabled(option)?;
+                        let visible = if option.pinned {
+                            &mut visible_pinned
+                        } else {
+                            &mut visible_regular
+                        };
+                        visible.push(PendingChoiceOption {
                             item: ChoiceItem {
                                 index: 0,
                                 id: option.id.clone(),
-                                text: self.render_text(&option.text)?,
+                                text: self.render_narration(&option.text)?,
+                                scene: option.scene.clone(),
+                                enabled,
                             },
                             dynamic_binding: None,
                         });
@@ -379,7 +656,7 @@ impl ScriptLangEngine {
                     let array_value = self.eval_expression(&block.array_expr)?;
                     let SlValue::Array(items) = array_value else {
                         return Err(ScriptLangError::new(
-                            "ENGINE_CHOICE_ARRAY_NOT_ARRAY",
+                            ErrorCode::EngineChoiceArrayNotArray,
                             format!(
                                 "dynamic-options array expression \"{}\" must evaluate to array.",
                                 block.array_expr
@@ -403,6 +680,8 @@ impl ScriptLangEngine {
                                     node_id, dynamic_block_ordinal, element_index
                                 ),
                                 text: rendered_text,
+                                scene: None,
+                                enabled: true,
                             },
                             dynamic_binding: Some(PendingDynamicChoiceBinding {
                                 group_id: block.template.group_id.clone(),
@@ -419,11 +698,12 @@ impl ScriptLangEngine {
             }
         }
 
-        let visible_options = if visible_regular.is_empty() {
+        let mut visible_options = if visible_regular.is_empty() {
             visible_fall_over.into_iter().collect::<Vec<_>>()
         } else {
             visible_regular
         };
+        visible_options.extend(visible_pinned);
 
         if visible_options.is_empty() {
             self.bump_top_node_index_infallible(1);
@@ -439,7 +719,8 @@ impl ScriptLangEngine {
             .map(|option| option.item.clone())
             .collect::<Vec<_>>();
 
-        let rendered_prompt = Some(self.render_text(prompt_text)?);
+        let rendered_prompt = Some(self.render_narration(prompt_text)?);
+        let default_index = self.choice_default_index(top_frame_id, &pending_options);
         self.pending_boundary = Some(PendingBoundary::Choice {
             frame_id: top_frame_id,
             node_id: node_id.to_string(),
@@ -450,6 +731,7 @@ impl ScriptLangEngine {
         Ok(Some(EngineOutput::Choices {
             items,
             prompt_text: rendered_prompt,
+            default_index,
         }))
     }
 
@@ -460,32 +742,45 @@ impl ScriptLangEngine {
         target_var: &str,
         prompt_text: &str,
         max_length: Option<usize>,
+        format: InputFormat,
     ) -> Result<Option<EngineOutput>, ScriptLangError> {
         let current = self.read_path(target_var)?;
-        let SlValue::String(default_text) = current else {
-            return Err(ScriptLangError::new(
-                "ENGINE_INPUT_VAR_TYPE",
-                format!("Input target var \"{}\" must be string.", target_var),
-            ));
+        let default_text = match (format, current) {
+            (InputFormat::Text, SlValue::String(default_text)) => default_text,
+            (InputFormat::Text, _) => {
+                return Err(ScriptLangError::new(
+                    ErrorCode::EngineInputVarType,
+                    format!("Input target var \"{}\" must be string.", target_var),
+                ));
+            }
+            (InputFormat::Json, current) => slvalue_to_json_text(&current),
+            (InputFormat::Boolean, SlValue::Bool(current)) => current.to_string(),
+            (InputFormat::Boolean, _) => {
+                return Err(ScriptLangError::new(
+                    ErrorCode::EngineInputVarType,
+                    format!("Input target var \"{}\" must be boolean.", target_var),
+                ));
+            }
         };
 
+        let prompt_text = self.apply_text_filter(prompt_text.to_string());
         self.pending_boundary = Some(PendingBoundary::Input {
             frame_id: top_frame_id,
             node_id: node_id.to_string(),
             target_var: target_var.to_string(),
-            prompt_text: prompt_text.to_string(),
+            prompt_text: prompt_text.clone(),
             default_text: default_text.clone(),
             max_length,
         });
         self.waiting_choice = false;
         Ok(Some(EngineOutput::Input {
-            prompt_text: prompt_text.to_string(),
+            prompt_text,
             default_text,
             max_length,
         }))
     }
 
-    fn dynamic_choice_when(
+    pub(super) fn dynamic_choice_when(
         &mut self,
         block: &sl_core::DynamicChoiceBlock,
         binding: (&SlValue, usize),
@@ -514,7 +809,7 @@ impl ScriptLangEngine {
             element_value,
             block.index_name.as_deref(),
             element_index,
-            |engine| engine.render_text(&block.template.text),
+            |engine| engine.render_narration(&block.template.text),
         )
     }
 
@@ -588,10 +883,98 @@ mod step_tests {
             EngineOutput::Debug { .. } => "debug",
             EngineOutput::Choices { .. } => "choices",
             EngineOutput::Input { .. } => "input",
+            EngineOutput::Checkpoint { .. } => "checkpoint",
             EngineOutput::End => "end",
         }
     }
 
+    fn coverage_engine(files: BTreeMap<String, String>) -> ScriptLangEngine {
+        let compiled = compile_project_from_sources(files);
+        ScriptLangEngine::new(ScriptLangEngineOptions {
+            scripts: compiled.scripts,
+            global_data: compiled.global_data,
+            module_var_declarations: compiled.module_var_declarations,
+            module_var_init_order: compiled.module_var_init_order,
+            module_const_declarations: compiled.module_const_declarations,
+            module_const_init_order: compiled.module_const_init_order,
+            random_seed: Some(1),
+            behavior: EngineBehavior {
+                track_coverage: true,
+                ..EngineBehavior::default()
+            },
+            ..Default::default()
+        })
+        .expect("engine should build")
+    }
+
+    fn covered_texts(engine: &ScriptLangEngine) -> BTreeMap<String, usize> {
+        let mut by_value = BTreeMap::new();
+        for (node_id, count) in engine.coverage() {
+            let node = engine
+                .scripts
+                .values()
+                .flat_map(|script| script.groups.values())
+                .flat_map(|group| group.nodes.iter())
+                .find(|node| node.id() == node_id)
+                .expect("covered node should exist in IR");
+            if let ScriptNode::Text { value, .. } = node {
+                by_value.insert(value.clone(), count);
+            }
+        }
+        by_value
+    }
+
+    #[test]
+    pub(super) fn coverage_counts_executed_nodes_across_calls_and_snapshots() {
+        let files = map(&[(
+            "main.xml",
+            r#"<module name="main" export="script:main">
+<script name="helper" kind="call"><text>helper</text></script>
+<script name="main">
+  <temp name="i" type="int">0</temp>
+  <while when="i LT 2">
+    <call script="@helper"/>
+    <code>i = i + 1;</code>
+  </while>
+  <if when="i == 2"><text>then</text><else><text>else</text></else></if>
+  <choice text="Pick">
+    <option text="A"><text>picked A</text></option>
+    <option text="B"><text>picked B</text></option>
+  </choice>
+  <end/>
+</script>
+</module>"#,
+        )]);
+
+        let mut plain = engine_from_sources(files.clone());
+        plain.start("main.main", None).expect("start");
+        drive_engine_to_end(&mut plain);
+        assert!(plain.coverage().is_empty());
+
+        let mut engine = coverage_engine(files.clone());
+        engine.start("main.main", None).expect("start");
+        while !matches!(
+            engine.next_output().expect("next should pass"),
+            EngineOutput::Choices { .. }
+        ) {}
+        assert_eq!(
+            covered_texts(&engine),
+            BTreeMap::from([("helper".to_string(), 2), ("then".to_string(), 1)])
+        );
+        let snapshot = engine.snapshot().expect("snapshot");
+        assert!(snapshot.coverage.is_some());
+
+        let mut resumed = coverage_engine(files);
+        resumed.resume(snapshot).expect("resume");
+        assert_eq!(resumed.coverage(), engine.coverage());
+        resumed.choose(1).expect("choose");
+        drive_engine_to_end(&mut resumed);
+        let texts = covered_texts(&resumed);
+        assert_eq!(texts.get("picked B"), Some(&1));
+        assert_eq!(texts.get("picked A"), None);
+        assert_eq!(texts.get("else"), None);
+    }
+
     #[test]
     fn output_kind_supports_debug_variant() {
         let kind = output_kind(&EngineOutput::Debug {
@@ -686,7 +1069,7 @@ mod step_tests {
         let first = engine.next_output().expect("next");
         assert!(matches!(
             first,
-            EngineOutput::Text { text, tag } if text == "sfx/open.ogg" && tag == Some("sound".to_string())
+            EngineOutput::Text { text, tag, .. } if text == "sfx/open.ogg" && tag == Some("sound".to_string())
         ));
     }
 
@@ -701,7 +1084,29 @@ mod step_tests {
         let first = engine.next_output().expect("next");
         assert!(matches!(
             first,
-            EngineOutput::Text { text, tag } if text == "plain" && tag.is_none()
+            EngineOutput::Text { text, tag, .. } if text == "plain" && tag.is_none()
+        ));
+    }
+
+    #[test]
+    fn next_text_renders_optional_speaker() {
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"<script name="main"><temp name="who" type="string">"Ann"</temp><text speaker="${who}">hi</text><text speaker="  ">narration</text></script>"#,
+        )]));
+        engine.start("main", None).expect("start");
+
+        assert_eq!(
+            engine.next_output().expect("next"),
+            EngineOutput::Text {
+                text: "hi".to_string(),
+                tag: None,
+                speaker: Some("Ann".to_string()),
+            }
+        );
+        assert!(matches!(
+            engine.next_output().expect("next"),
+            EngineOutput::Text { text, speaker, .. } if text == "narration" && speaker.is_none()
         ));
     }
 
@@ -838,6 +1243,9 @@ mod step_tests {
                 EngineOutput::Debug { text } => {
                     println!("Debug: {}", text);
                 }
+                EngineOutput::Checkpoint { id, .. } => {
+                    println!("Checkpoint: {}", id);
+                }
                 EngineOutput::Choices { items, .. } => {
                     println!("Choices: {} items", items.len());
                     hit_choices = true;
@@ -880,6 +1288,23 @@ mod step_tests {
         assert!(matches!(output, EngineOutput::Text { text, .. } if text == "weak"));
     }
 
+    #[test]
+    pub(super) fn if_without_else_advances_when_condition_false() {
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <if when="false"><text>skipped</text></if>
+      <text>after</text>
+    </script>
+    "#,
+        )]));
+        engine.start("main", None).expect("start");
+
+        let output = engine.next_output().expect("next should pass");
+        assert!(matches!(output, EngineOutput::Text { text, .. } if text == "after"));
+    }
+
     #[test]
     pub(super) fn while_loop_condition_false_covered() {
         // Test while loop when condition is initially false
@@ -1002,6 +1427,137 @@ mod step_tests {
         assert!(matches!(output, EngineOutput::Text { text, .. } if text == "after"));
     }
 
+    #[test]
+    fn text_expr_emits_the_computed_value() {
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <temp name="greetings" type="string[]">["Hi", "Yo", "Hey"]</temp>
+      <temp name="idx" type="int">2</temp>
+      <text expr="greetings[idx]" tag="npc"/>
+      <text expr="idx * 10"/>
+    </script>
+    "#,
+        )]));
+        engine.start("main", None).expect("start");
+        assert_eq!(
+            engine.next_output().expect("computed string"),
+            EngineOutput::Text {
+                text: "Hey".to_string(),
+                tag: Some("npc".to_string()),
+                speaker: None,
+            }
+        );
+        assert!(matches!(
+            engine.next_output().expect("computed number"),
+            EngineOutput::Text { text, .. } if text == "20"
+        ));
+    }
+
+    #[test]
+    fn comments_are_exported_in_ir_but_never_executed() {
+        let files = map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <comment>Opening beat; keep it short.</comment>
+      <text>Hello</text>
+      <choice text="Pick">
+        <option text="Go"><comment>TODO: branch</comment><text>went</text></option>
+      </choice>
+      <end/>
+      <comment>trailing note</comment>
+    </script>
+    "#,
+        )]);
+        let compiled = compile_project_from_sources(files.clone());
+        let script = &compiled.scripts["main.main"];
+        let root = &script.groups[&script.root_group_id];
+        assert_eq!(root.nodes.len(), 3);
+        let notes = root
+            .comments
+            .iter()
+            .map(|comment| (comment.node_index, comment.text.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            notes,
+            [(0, "Opening beat; keep it short."), (3, "trailing note")]
+        );
+        assert!(script
+            .groups
+            .values()
+            .any(|group| group.comments.iter().any(|c| c.text == "TODO: branch")));
+
+        let mut engine = engine_from_sources(files);
+        engine.start("main", None).expect("start");
+        assert!(matches!(
+            engine.next_output().expect("text"),
+            EngineOutput::Text { text, .. } if text == "Hello"
+        ));
+        assert!(matches!(
+            engine.next_output().expect("choices"),
+            EngineOutput::Choices { .. }
+        ));
+        let snapshot = engine.snapshot().expect("snapshot");
+        assert_eq!(snapshot.runtime_frames[0].node_index, 1);
+        engine.choose(0).expect("choose");
+        assert!(matches!(
+            engine.next_output().expect("text"),
+            EngineOutput::Text { text, .. } if text == "went"
+        ));
+        assert_eq!(engine.next_output().expect("end"), EngineOutput::End);
+    }
+
+    #[test]
+    fn while_max_iterations_fails_before_the_global_guard() {
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <while when="true" max="50">
+        <continue/>
+      </while>
+    </script>
+    "#,
+        )]));
+        engine.start("main", None).expect("start");
+        let error = engine.next_output().expect_err("loop limit should trip");
+        assert_eq!(error.code, "ENGINE_WHILE_LIMIT_EXCEEDED");
+        assert!(error.message.contains("50 iterations"));
+        assert!(error.span.is_some());
+
+        // The count spans text boundaries and restarts each time the loop is entered again.
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <temp name="outer" type="int">0</temp>
+      <while when="outer LT 3">
+        <temp name="inner" type="int">0</temp>
+        <while when="inner LT 2">
+          <code>inner += 1;</code>
+        </while>
+        <code>outer += 1;</code>
+      </while>
+      <while when="true">
+        <text>tick</text>
+      </while>
+    </script>
+    "#,
+        )]));
+        engine.max_while_iterations = Some(3);
+        engine.start("main", None).expect("start");
+        for _ in 0..3 {
+            assert!(matches!(
+                engine.next_output().expect("tick"),
+                EngineOutput::Text { text, .. } if text == "tick"
+            ));
+        }
+        let error = engine.next_output().expect_err("default limit should trip");
+        assert_eq!(error.code, "ENGINE_WHILE_LIMIT_EXCEEDED");
+    }
+
     #[test]
     pub(super) fn guard_and_choice_error_paths_are_covered() {
         let mut infinite = engine_from_sources(map(&[(
@@ -1114,6 +1670,9 @@ mod step_tests {
                         name: "int".to_string(),
                     },
                 )]),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
         );
         with_choice.pending_boundary = Some(PendingBoundary::Choice {
@@ -1124,6 +1683,8 @@ mod step_tests {
                     index: 0,
                     id: "id0".to_string(),
                     text: "A".to_string(),
+                    scene: None,
+                    enabled: true,
                 },
                 dynamic_binding: None,
             }],
@@ -1224,6 +1785,9 @@ mod step_tests {
                 script_root: true,
                 return_continuation: None,
                 var_types: BTreeMap::new(),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
             RuntimeFrame {
                 frame_id: 2,
@@ -1238,6 +1802,9 @@ mod step_tests {
                     ref_bindings: BTreeMap::from([("x".to_string(), "caller".to_string())]),
                 }),
                 var_types: BTreeMap::new(),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
         ];
         return_engine
@@ -1253,6 +1820,9 @@ mod step_tests {
                 script_root: true,
                 return_continuation: None,
                 var_types: BTreeMap::new(),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
             RuntimeFrame {
                 frame_id: 2,
@@ -1267,6 +1837,9 @@ mod step_tests {
                     ref_bindings: BTreeMap::from([("x".to_string(), "caller".to_string())]),
                 }),
                 var_types: BTreeMap::new(),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
         ];
         return_engine
@@ -1296,6 +1869,9 @@ mod step_tests {
             script_root: false,
             return_continuation: None,
             var_types: BTreeMap::new(),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let error = while_control
             .execute_break()
@@ -1315,6 +1891,9 @@ mod step_tests {
             script_root: false,
             return_continuation: None,
             var_types: BTreeMap::new(),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let error = while_control
             .execute_continue_while()
@@ -1403,6 +1982,9 @@ mod step_tests {
             script_root: false,
             return_continuation: None,
             var_types: BTreeMap::new(),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         });
         let _ = snapshot_engine.snapshot().expect("snapshot should pass");
     }
@@ -1429,6 +2011,9 @@ mod step_tests {
                 script_root: true,
                 return_continuation: None,
                 var_types: BTreeMap::new(),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
             RuntimeFrame {
                 frame_id: 2,
@@ -1443,6 +2028,9 @@ mod step_tests {
                     ref_bindings: BTreeMap::from([("src".to_string(), "dst".to_string())]),
                 }),
                 var_types: BTreeMap::new(),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
         ];
         finisher
@@ -1521,6 +2109,9 @@ mod step_tests {
                 script_root: true,
                 return_continuation: None,
                 var_types: BTreeMap::new(),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
             RuntimeFrame {
                 frame_id: 11,
@@ -1535,6 +2126,9 @@ mod step_tests {
                     ref_bindings: BTreeMap::from([("missing".to_string(), "dst".to_string())]),
                 }),
                 var_types: BTreeMap::new(),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
         ];
         return_skip
@@ -1558,6 +2152,9 @@ mod step_tests {
                 ref_bindings: BTreeMap::from([("missing".to_string(), "dst".to_string())]),
             }),
             var_types: BTreeMap::new(),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         return_skip
             .execute_goto(&lit("next.next"), &[])
@@ -1660,6 +2257,99 @@ mod step_tests {
         assert!(outputs.iter().any(|s| s.contains("done-4")));
     }
 
+    #[test]
+    fn clear_once_lets_once_text_emit_again() {
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"<script name="main"><text once="true">intro</text><text>body</text><end/></script>"#,
+        )]));
+        let run_texts = |engine: &mut ScriptLangEngine| {
+            engine.start("main", None).expect("start");
+            engine
+                .outputs()
+                .map(|output| match output.expect("output") {
+                    EngineOutput::Text { text, .. } => text,
+                    other => output_kind(&other).to_string(),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(run_texts(&mut engine), vec!["intro", "body", "end"]);
+        assert_eq!(run_texts(&mut engine), vec!["body", "end"]);
+        let keys = engine.once_state()["main.main"].clone();
+        assert_eq!(keys.len(), 1);
+        let key = keys.first().expect("once key").clone();
+        assert!(key.starts_with("text:"));
+
+        assert!(!engine.clear_once("main.main", "text:missing"));
+        assert!(!engine.clear_once("main.other", &key));
+        assert!(engine.clear_once("main.main", &key));
+        assert!(engine.once_state().is_empty());
+        assert!(!engine.clear_once("main.main", &key));
+        assert_eq!(run_texts(&mut engine), vec!["intro", "body", "end"]);
+    }
+
+    #[test]
+    fn global_once_nodes_track_state_outside_the_script() {
+        let files = map(&[(
+            "main.script.xml",
+            r#"<script name="main">
+  <text once="true" scope="global">tip</text>
+  <text once="true">intro</text>
+  <choice text="Pick">
+    <option text="Learn" once="true" scope="global"><text>learned</text></option>
+    <option text="Leave"><text>left</text></option>
+  </choice>
+  <end/>
+</script>"#,
+        )]);
+        let run = |engine: &mut ScriptLangEngine| {
+            engine
+                .outputs()
+                .map(|output| match output.expect("output") {
+                    EngineOutput::Text { text, .. } => text,
+                    EngineOutput::Choices { items, .. } => items
+                        .iter()
+                        .map(|item| item.text.clone())
+                        .collect::<Vec<_>>()
+                        .join("|"),
+                    other => output_kind(&other).to_string(),
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut engine = engine_from_sources(files.clone());
+        engine.start("main", None).expect("start");
+        assert_eq!(run(&mut engine), vec!["tip", "intro", "Learn|Leave"]);
+        engine.choose(0).expect("choose learn");
+        assert_eq!(run(&mut engine), vec!["learned", "end"]);
+
+        let global = engine
+            .global_once_state()
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(global.len(), 2);
+        assert!(global.iter().any(|key| key.starts_with("text:")));
+        assert!(global.iter().any(|key| key.starts_with("option:")));
+        assert_eq!(engine.once_state()["main.main"].len(), 1);
+
+        engine.start("main", None).expect("restart");
+        assert_eq!(run(&mut engine), vec!["Leave"]);
+        let snapshot = engine.snapshot().expect("snapshot");
+        assert_eq!(snapshot.global_once_state, global);
+
+        let mut resumed = engine_from_sources(files);
+        resumed.resume(snapshot).expect("resume");
+        assert_eq!(resumed.global_once_state(), engine.global_once_state());
+        let option_key = global
+            .iter()
+            .find(|key| key.starts_with("option:"))
+            .expect("option key");
+        assert!(!resumed.clear_once("main.main", option_key));
+        assert!(resumed.clear_global_once(option_key));
+        assert!(!resumed.clear_global_once(option_key));
+    }
+
     #[test]
     pub(super) fn once_text_skipped_on_revisit() {
         // Test that once text is skipped when revisited (covers step.rs lines 169-170)
@@ -1875,6 +2565,37 @@ mod step_tests {
         assert!(matches!(text, EngineOutput::Text { text, .. } if text == "picked 7"));
     }
 
+    #[test]
+    fn dynamic_options_materialize_one_option_per_array_element() {
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <temp name="items" type="string[]">["Sword", "Shield", "Potion"]</temp>
+      <choice text="Shop">
+        <dynamic-options array="items" item="item">
+          <option text="Buy ${item}"><text>bought ${item}</text></option>
+        </dynamic-options>
+      </choice>
+    </script>
+    "#,
+        )]));
+        engine.start("main", None).expect("start");
+        let items = choice_items(engine.next_output().expect("next")).expect("choices expected");
+        assert_eq!(
+            items
+                .iter()
+                .map(|item| item.text.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Buy Sword", "Buy Shield", "Buy Potion"]
+        );
+        engine.choose(2).expect("choose");
+        assert!(matches!(
+            engine.next_output().expect("text"),
+            EngineOutput::Text { text, .. } if text == "bought Potion"
+        ));
+    }
+
     #[test]
     pub(super) fn nested_dynamic_options_allow_shadowing_and_restore_outer_bindings() {
         let mut engine = engine_from_sources(map(&[(
@@ -1964,10 +2685,11 @@ mod step_tests {
         <option text="F" fall_over="true"><text>fall</text></option>
       </choice>
       <temp name="x" type="int">1</temp>
-      <input var="x" text="input"/>
+      <input var="x" text="input" format="json"/>
     </script>
     "#,
         )]));
+        rewrite_input_nodes(&mut engine, "x", InputFormat::Text);
         engine.start("main", None).expect("start");
 
         let first = engine.next_output().expect("choice 1");
@@ -2035,6 +2757,9 @@ mod step_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::new(),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let error = missing_group
             .next_output()
@@ -2061,6 +2786,9 @@ mod step_tests {
                 script_root: true,
                 return_continuation: None,
                 var_types: BTreeMap::new(),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
             RuntimeFrame {
                 frame_id: 11,
@@ -2075,6 +2803,9 @@ mod step_tests {
                     ref_bindings: BTreeMap::from([("src".to_string(), "dst".to_string())]),
                 }),
                 var_types: BTreeMap::new(),
+                text_page: 0,
+                text_pages: None,
+                while_iterations: 0,
             },
         ];
         let error = finish_error
@@ -2154,8 +2885,9 @@ mod step_tests {
 
         let mut input_read_error = engine_from_sources(map(&[(
             "main.script.xml",
-            r#"<script name="main"><input var="missing" text="input"/></script>"#,
+            r#"<script name="main"><temp name="x" type="string">""</temp><input var="x" text="input"/></script>"#,
         )]));
+        rewrite_input_nodes(&mut input_read_error, "missing", InputFormat::Text);
         input_read_error.start("main", None).expect("start");
         let error = input_read_error
             .next_output()
@@ -2187,6 +2919,9 @@ mod step_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::new(),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let error = break_error
             .next_output()
@@ -2226,6 +2961,9 @@ mod step_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::new(),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let error = continue_while_error
             .next_output()
@@ -2265,6 +3003,9 @@ mod step_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::new(),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
         let error = continue_choice_error
             .next_output()
@@ -2358,6 +3099,9 @@ mod step_tests {
             script_root: true,
             return_continuation: None,
             var_types: BTreeMap::new(),
+            text_page: 0,
+            text_pages: None,
+            while_iterations: 0,
         }];
 
         // This should trigger the error path in execute_return
@@ -2371,4 +3115,316 @@ mod step_tests {
             .expect_err("execute_return should fail with missing group");
         assert_eq!(error.code, "ENGINE_GROUP_NOT_FOUND");
     }
+
+    #[test]
+    fn text_filter_transforms_narration_but_not_values() {
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <temp name="name" type="string">"ann"</temp>
+      <text>hello ${name}</text>
+      <choice text="pick for ${name}">
+        <option text="greet ${name}">
+          <text>${name == "ann"}</text>
+        </option>
+      </choice>
+      <end/>
+    </script>
+    "#,
+        )]));
+        engine.text_filter = Some(Arc::new(|text: &str| text.to_uppercase()));
+        engine.start("main", None).expect("start");
+        assert!(matches!(
+            engine.next_output().expect("text"),
+            EngineOutput::Text { text, .. } if text == "HELLO ANN"
+        ));
+        assert!(matches!(
+            engine.next_output().expect("choices"),
+            EngineOutput::Choices { items, prompt_text: Some(prompt), .. }
+                if items[0].text == "GREET ANN" && prompt == "PICK FOR ANN"
+        ));
+        engine.choose(0).expect("choose");
+        assert!(matches!(
+            engine.next_output().expect("text"),
+            EngineOutput::Text { text, .. } if text == "TRUE"
+        ));
+        assert_eq!(
+            engine.read_variable("name").expect("name"),
+            SlValue::String("ann".to_string())
+        );
+    }
+
+    #[test]
+    fn max_text_chars_paginates_text_and_persists_page_cursor() {
+        assert_eq!(paginate_text("abcdefgh", 3), vec!["abc", "def", "gh"]);
+        assert_eq!(paginate_text("one\ntwo three", 9), vec!["one", "two three"]);
+
+        let files = map(&[(
+            "main.script.xml",
+            r#"<script name="main"><temp name="name" type="string">""</temp><text once="true">The quick brown fox jumps over the lazy dog</text><input var="name" text="Name"/><text>after</text><end/></script>"#,
+        )]);
+        let next_text = |engine: &mut ScriptLangEngine| {
+            output_text(engine.next_output().expect("next")).expect("text expected")
+        };
+        let mut engine = engine_from_sources(files.clone());
+        engine.max_text_chars = Some(12);
+        engine.start("main", None).expect("start");
+        assert_eq!(next_text(&mut engine), "The quick");
+        assert_eq!(next_text(&mut engine), "brown fox");
+        assert_eq!(engine.frames.last().expect("frame").text_page, 2);
+        assert!(engine.once_state_by_script.is_empty());
+        assert_eq!(next_text(&mut engine), "jumps over");
+        assert_eq!(next_text(&mut engine), "the lazy dog");
+        assert!(!engine.once_state_by_script.is_empty());
+        assert_eq!(engine.frames.last().expect("frame").text_page, 0);
+        assert!(matches!(
+            engine.next_output().expect("input"),
+            EngineOutput::Input { .. }
+        ));
+
+        // snapshot() only runs at a boundary, where the cursor is back at 0; it still round-trips.
+        let mut snapshot = engine.snapshot().expect("snapshot");
+        assert_eq!(snapshot.runtime_frames[0].text_page, None);
+        snapshot.runtime_frames[0].text_page = Some(1);
+        let mut resumed = engine_from_sources(files);
+        resumed.max_text_chars = Some(12);
+        resumed.resume(snapshot).expect("resume");
+        assert_eq!(resumed.frames.last().expect("frame").text_page, 1);
+        resumed.frames.last_mut().expect("frame").text_page = 0;
+        resumed.submit_input("Ann").expect("submit");
+        assert_eq!(next_text(&mut resumed), "after");
+    }
+
+    #[test]
+    fn max_text_chars_renders_paginated_text_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let compiled = compile_project_from_sources(map(&[(
+            "main.script.xml",
+            r##"<script name="main"><uses functions="seq"/><text label="long">Ticket ${seq()} for the quick brown fox</text><text>${sl_visited("long")}</text><end/></script>"##,
+        )]));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let registry = FnHostFunctionRegistry::new(BTreeMap::new()).with_function(
+            "seq",
+            move |_args: &[SlValue]| {
+                let next = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                Ok(SlValue::String(next.to_string()))
+            },
+        );
+        let mut engine = ScriptLangEngine::new(ScriptLangEngineOptions {
+            scripts: compiled.scripts,
+            host_functions: Some(Arc::new(registry)),
+            behavior: EngineBehavior {
+                track_coverage: true,
+                ..EngineBehavior::default()
+            },
+            ..Default::default()
+        })
+        .expect("engine should build");
+        engine.max_text_chars = Some(12);
+        engine.start("main", None).expect("start");
+
+        let mut pages = Vec::new();
+        while let Some(text) = output_text(engine.next_output().expect("next")) {
+            pages.push(text);
+        }
+        assert_eq!(
+            pages,
+            vec!["Ticket 1", "for the", "quick brown", "fox", "1"]
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            covered_texts(&engine)["Ticket ${seq()} for the quick brown fox"],
+            1
+        );
+    }
+
+    #[test]
+    fn outputs_iterates_until_boundary_or_end() {
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <text>one</text>
+      <text>two</text>
+      <choice text="Pick">
+        <option text="A"><text>three</text></option>
+      </choice>
+      <end/>
+    </script>
+    "#,
+        )]));
+        engine.start("main", None).expect("start");
+        let first_run = engine
+            .outputs()
+            .collect::<Result<Vec<_>, _>>()
+            .expect("outputs");
+        assert_eq!(first_run.len(), 3);
+        assert!(matches!(&first_run[0], EngineOutput::Text { text, .. } if text == "one"));
+        assert!(matches!(&first_run[1], EngineOutput::Text { text, .. } if text == "two"));
+        assert!(matches!(&first_run[2], EngineOutput::Choices { .. }));
+
+        engine.choose(0).expect("choose");
+        let texts = engine
+            .outputs()
+            .filter_map(|output| match output.expect("output") {
+                EngineOutput::Text { text, .. } => Some(text),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["three".to_string()]);
+        assert!(matches!(
+            engine.outputs().next(),
+            Some(Ok(EngineOutput::End))
+        ));
+    }
+
+    #[test]
+    fn fail_node_surfaces_prefixed_author_error_code() {
+        let mut engine = engine_from_sources(map(&[(
+            "main.script.xml",
+            r#"<script name="main"><temp name="hp" type="int">-1</temp><text>before</text><if when="hp LT 0"><fail code="NEGATIVE_HP" message="hp is ${hp}"/></if><end/></script>"#,
+        )]));
+        engine.start("main", None).expect("start");
+        assert!(matches!(
+            engine.next_output().expect("text"),
+            EngineOutput::Text { text, .. } if text == "before"
+        ));
+        let error = engine.next_output().expect_err("fail should error");
+        assert_eq!(error.code, "USER_NEGATIVE_HP");
+        assert_eq!(error.message, "hp is -1");
+    }
+
+    #[test]
+    fn script_local_function_is_callable_only_from_its_script() {
+        let sources = map(&[
+            (
+                "main.script.xml",
+                r#"
+    <script name="main">
+      <function name="double" args="int:x" return_type="int">return x * 2;</function>
+      <text>${double(21)}</text>
+      <end/>
+    </script>
+    "#,
+            ),
+            (
+                "other.script.xml",
+                r#"
+    <script name="other">
+      <text>${double(21)}</text>
+      <end/>
+    </script>
+    "#,
+            ),
+        ]);
+        let mut engine = engine_from_sources(sources.clone());
+        engine.start("main", None).expect("start");
+        assert!(matches!(
+            engine.next_output().expect("text"),
+            EngineOutput::Text { text, .. } if text == "42"
+        ));
+
+        let mut engine = engine_from_sources(sources);
+        engine.start("other", None).expect("start");
+        assert!(engine.next_output().is_err());
+    }
+
+    #[test]
+    fn pinned_option_is_appended_while_fall_over_only_fills_empty_choices() {
+        let files = map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main" args="boolean:open">
+      <choice text="Pick">
+        <option text="Buy" when="open"><text>buy</text></option>
+        <option text="Wait" fall_over="true"><text>wait</text></option>
+        <option text="Leave" pinned="true"><text>leave</text></option>
+      </choice>
+      <end/>
+    </script>
+    "#,
+        )]);
+        let choice_texts = |open: bool| {
+            let mut engine = engine_from_sources(files.clone());
+            engine
+                .start(
+                    "main",
+                    Some(BTreeMap::from([("open".to_string(), SlValue::Bool(open))])),
+                )
+                .expect("start");
+            choice_items(engine.next_output().expect("next"))
+                .expect("choices expected")
+                .into_iter()
+                .map(|item| (item.index, item.text))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            choice_texts(true),
+            vec![(0, "Buy".to_string()), (1, "Leave".to_string())]
+        );
+        assert_eq!(
+            choice_texts(false),
+            vec![(0, "Wait".to_string()), (1, "Leave".to_string())]
+        );
+
+        let mut engine = engine_from_sources(files);
+        engine
+            .start(
+                "main",
+                Some(BTreeMap::from([("open".to_string(), SlValue::Bool(true))])),
+            )
+            .expect("start");
+        engine.next_output().expect("choices");
+        engine.choose(1).expect("choose pinned");
+        assert!(matches!(
+            engine.next_output().expect("text"),
+            EngineOutput::Text { text, .. } if text == "leave"
+        ));
+    }
+
+    #[test]
+    fn option_when_is_evaluated_once_per_option_per_presentation() {
+        let files = map(&[(
+            "main.script.xml",
+            r#"
+    <script name="main">
+      <choice text="Pick">
+        <option text="A" when="random(10) >= 0"><text>a</text></option>
+        <option text="B" when="random(10) >= 0"><text>b</text></option>
+        <option text="Leave" fall_over="true"><text>leave</text></option>
+      </choice>
+      <end/>
+    </script>
+    "#,
+        )]);
+        // `None` when the engine is not drawing from a random sequence.
+        let sequence_index = |engine: &ScriptLangEngine| match engine.random_state_snapshot() {
+            RandomStateView::Sequence { index, .. } => Some(index),
+            RandomStateView::Seeded { .. } => None,
+        };
+
+        let mut engine = engine_from_sources(files.clone());
+        engine.initial_random_sequence = Some(vec![1; 8]);
+        engine.start("main", None).expect("start");
+        assert!(matches!(
+            engine.next_output().expect("choices"),
+            EngineOutput::Choices { items, .. } if items.len() == 2
+        ));
+        assert_eq!(sequence_index(&engine), Some(2));
+
+        engine
+            .next_output()
+            .expect("pending choices are re-emitted");
+        let snapshot = engine.snapshot().expect("snapshot");
+        let mut resumed = engine_from_sources(files);
+        resumed.initial_random_sequence = Some(vec![1; 8]);
+        resumed.resume(snapshot).expect("resume");
+        resumed.next_output().expect("resumed choices");
+        assert_eq!(sequence_index(&engine), Some(2));
+        assert_eq!(sequence_index(&resumed), Some(0));
+    }
 }
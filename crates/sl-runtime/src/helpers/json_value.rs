@@ -0,0 +1,52 @@
+use serde_json::Value as JsonValue;
+use sl_core::{slvalue_from_json, slvalue_to_json, ErrorCode, ScriptLangError, SlValue};
+
+pub(crate) fn parse_json_input(text: &str) -> Result<SlValue, ScriptLangError> {
+    serde_json::from_str::<JsonValue>(text)
+        .map(slvalue_from_json)
+        .map_err(|error| {
+            ScriptLangError::new(
+                ErrorCode::EngineInputJsonInvalid,
+                format!("Input is not valid JSON: {}", error),
+            )
+        })
+}
+
+pub(crate) fn slvalue_to_json_text(value: &SlValue) -> String {
+    slvalue_to_json(value).to_string()
+}
+
+#[cfg(test)]
+mod json_value_tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    #[test]
+    fn json_input_round_trips_structured_values() {
+        let value = parse_json_input(r#"{"hp": 3, "tags": ["a", true], "none": null}"#)
+            .expect("json should parse");
+        assert_eq!(
+            value,
+            SlValue::Map(BTreeMap::from([
+                ("hp".to_string(), SlValue::Number(3.0)),
+                ("none".to_string(), SlValue::Null),
+                (
+                    "tags".to_string(),
+                    SlValue::Array(vec![SlValue::String("a".to_string()), SlValue::Bool(true),])
+                ),
+            ]))
+        );
+        assert_eq!(
+            slvalue_to_json_text(&SlValue::Array(vec![SlValue::Number(1.5)])),
+            "[1.5]"
+        );
+        assert_eq!(
+            slvalue_to_json_text(&value),
+            r#"{"hp":3,"none":null,"tags":["a",true]}"#
+        );
+
+        let error = parse_json_input("{hp:").expect_err("malformed json should fail");
+        assert_eq!(error.code, "ENGINE_INPUT_JSON_INVALID");
+    }
+}
@@ -1,2 +1,3 @@
+pub(crate) mod json_value;
 pub(crate) mod rhai_bridge;
 pub(crate) mod value_path;
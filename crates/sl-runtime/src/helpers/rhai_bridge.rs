@@ -1,7 +1,14 @@
 use std::collections::BTreeMap;
 
 use rhai::{Array, Dynamic, ImmutableString, Map, FLOAT, INT};
-use sl_core::{ScriptLangError, ScriptType, SlValue};
+use sl_core::{ErrorCode, ScriptLangError, ScriptType, SlValue};
+
+use crate::IntPrecisionPolicy;
+
+/// Largest integer whose neighbours are also exactly representable as `f64` (2^53 - 1).
+pub(crate) const MAX_EXACT_INT: INT = (1 << 53) - 1;
+/// Smallest integer of the 54-bit two's complement range `IntPrecisionPolicy::Wrap` maps into.
+pub(crate) const MIN_EXACT_INT: INT = -(1 << 53);
 
 pub(crate) fn slvalue_to_text(value: &SlValue) -> String {
     match value {
@@ -15,6 +22,7 @@ pub(crate) fn slvalue_to_text(value: &SlValue) -> String {
         }
         SlValue::String(value) => value.clone(),
         SlValue::Array(_) | SlValue::Map(_) => format!("{:?}", value),
+        SlValue::Null => String::new(),
     }
 }
 
@@ -63,15 +71,48 @@ pub(crate) fn slvalue_to_dynamic_with_type(value: &SlValue, ty: Option<&ScriptTy
             }
             Dynamic::from_map(map)
         }
+        SlValue::Null => Dynamic::UNIT,
+    }
+}
+
+/// Convert a Rhai integer to a ScriptLang number, applying `policy` when `f64` cannot hold it
+/// exactly.
+pub(crate) fn int_to_number(
+    value: INT,
+    policy: IntPrecisionPolicy,
+) -> Result<f64, ScriptLangError> {
+    if (MIN_EXACT_INT..=MAX_EXACT_INT).contains(&value) {
+        return Ok(value as f64);
+    }
+    match policy {
+        IntPrecisionPolicy::Error => Err(ScriptLangError::new(
+            ErrorCode::EngineIntPrecision,
+            format!(
+                "Integer {} is outside the range numbers hold exactly ({}..={}).",
+                value, MIN_EXACT_INT, MAX_EXACT_INT
+            ),
+        )),
+        IntPrecisionPolicy::Saturate => Ok(value.clamp(MIN_EXACT_INT, MAX_EXACT_INT) as f64),
+        // Shifting the low 54 bits to the top and back sign-extends them.
+        IntPrecisionPolicy::Wrap => Ok(((value << 10) >> 10) as f64),
     }
 }
 
-pub(crate) fn dynamic_to_slvalue(value: Dynamic) -> Result<SlValue, ScriptLangError> {
+pub(crate) fn dynamic_to_slvalue(
+    value: Dynamic,
+    int_policy: IntPrecisionPolicy,
+) -> Result<SlValue, ScriptLangError> {
+    if value.is_unit() {
+        return Ok(SlValue::Null);
+    }
     if value.is::<bool>() {
         return Ok(SlValue::Bool(value.cast::<bool>()));
     }
     if value.is::<INT>() {
-        return Ok(SlValue::Number(value.cast::<INT>() as f64));
+        return Ok(SlValue::Number(int_to_number(
+            value.cast::<INT>(),
+            int_policy,
+        )?));
     }
     if value.is::<FLOAT>() {
         return Ok(SlValue::Number(value.cast::<FLOAT>()));
@@ -83,7 +124,7 @@ pub(crate) fn dynamic_to_slvalue(value: Dynamic) -> Result<SlValue, ScriptLangEr
         let array = value.cast::<Array>();
         let mut out = Vec::with_capacity(array.len());
         for item in array {
-            out.push(dynamic_to_slvalue(item)?);
+            out.push(dynamic_to_slvalue(item, int_policy)?);
         }
         return Ok(SlValue::Array(out));
     }
@@ -91,13 +132,13 @@ pub(crate) fn dynamic_to_slvalue(value: Dynamic) -> Result<SlValue, ScriptLangEr
         let map = value.cast::<Map>();
         let mut out = BTreeMap::new();
         for (key, value) in map {
-            out.insert(key.to_string(), dynamic_to_slvalue(value)?);
+            out.insert(key.to_string(), dynamic_to_slvalue(value, int_policy)?);
         }
         return Ok(SlValue::Map(out));
     }
 
     Err(ScriptLangError::new(
-        "ENGINE_VALUE_UNSUPPORTED",
+        ErrorCode::EngineValueUnsupported,
         "Unsupported Rhai value type.",
     ))
 }
@@ -112,7 +153,7 @@ pub(crate) fn slvalue_to_rhai_literal(value: &SlValue) -> String {
                 value.to_string()
             }
         }
-        SlValue::String(value) => format!("\"{}\"", value.replace('"', "\\\"")),
+        SlValue::String(value) => rhai_string_literal(value),
         SlValue::Array(values) => format!(
             "[{}]",
             values
@@ -124,14 +165,109 @@ pub(crate) fn slvalue_to_rhai_literal(value: &SlValue) -> String {
         SlValue::Map(values) => {
             let entries = values
                 .iter()
-                .map(|(key, value)| format!("{}: {}", key, slvalue_to_rhai_literal(value)))
+                .map(|(key, value)| {
+                    format!("{}: {}", rhai_map_key(key), slvalue_to_rhai_literal(value))
+                })
                 .collect::<Vec<_>>()
                 .join(", ");
             format!("#{{{}}}", entries)
         }
+        SlValue::Null => "()".to_string(),
+    }
+}
+
+/// Quote `value` as a Rhai string literal, escaping everything the lexer would otherwise interpret.
+fn rhai_string_literal(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            ch if ch.is_control() => out.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => out.push(ch),
+        }
     }
+    out.push('"');
+    out
 }
 
+/// Object-map keys stay bare when they are plain identifiers; anything else is written as a
+/// string literal, which Rhai accepts in `#{...}`.
+fn rhai_map_key(key: &str) -> String {
+    let mut chars = key.chars();
+    let is_identifier = chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+        && !RHAI_RESERVED_KEYS.contains(&key);
+    if is_identifier {
+        key.to_string()
+    } else {
+        rhai_string_literal(key)
+    }
+}
+
+/// Rhai keywords and reserved words, which the parser rejects as bare map keys.
+const RHAI_RESERVED_KEYS: &[&str] = &[
+    "as",
+    "async",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "const",
+    "continue",
+    "default",
+    "do",
+    "else",
+    "exit",
+    "export",
+    "false",
+    "fn",
+    "for",
+    "global",
+    "go",
+    "goto",
+    "if",
+    "import",
+    "in",
+    "is",
+    "let",
+    "loop",
+    "match",
+    "module",
+    "new",
+    "nil",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "shared",
+    "spawn",
+    "static",
+    "super",
+    "switch",
+    "sync",
+    "this",
+    "thread",
+    "throw",
+    "true",
+    "try",
+    "until",
+    "use",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
 #[cfg(test)]
 mod rhai_bridge_tests {
     use super::*;
@@ -158,7 +294,8 @@ mod rhai_bridge_tests {
             "k".to_string(),
             SlValue::Array(vec![SlValue::Bool(false)]),
         )])));
-        let roundtrip = dynamic_to_slvalue(dynamic_map).expect("roundtrip");
+        let roundtrip =
+            dynamic_to_slvalue(dynamic_map, IntPrecisionPolicy::Error).expect("roundtrip");
         assert_eq!(
             roundtrip,
             SlValue::Map(BTreeMap::from([(
@@ -180,6 +317,34 @@ mod rhai_bridge_tests {
         );
     }
 
+    #[test]
+    fn rhai_literals_escape_special_characters_and_quote_non_identifier_keys() {
+        let text = "line1\nsays \"hi\"\tpath C:\\dir\r\u{1}end ${x}";
+        assert_eq!(
+            slvalue_to_rhai_literal(&SlValue::String(text.to_string())),
+            "\"line1\\nsays \\\"hi\\\"\\tpath C:\\\\dir\\r\\u0001end ${x}\""
+        );
+
+        let value = SlValue::Map(BTreeMap::from([
+            ("plain_key".to_string(), SlValue::String(text.to_string())),
+            ("with space".to_string(), SlValue::Number(1.0)),
+            ("a\"b".to_string(), SlValue::Number(2.0)),
+            ("2d".to_string(), SlValue::Number(3.0)),
+            ("if".to_string(), SlValue::Bool(true)),
+        ]));
+        let literal = slvalue_to_rhai_literal(&value);
+        assert!(literal.contains("plain_key: "));
+        assert!(literal.contains("\"with space\": 1"));
+
+        let evaluated = rhai::Engine::new()
+            .eval::<Dynamic>(&literal)
+            .expect("literal should be valid rhai");
+        assert_eq!(
+            dynamic_to_slvalue(evaluated, IntPrecisionPolicy::Error).expect("roundtrip"),
+            value
+        );
+    }
+
     #[test]
     fn slvalue_to_dynamic_with_type_int_uses_rhai_int() {
         let int_ty = ScriptType::Primitive {
@@ -213,11 +378,13 @@ mod rhai_bridge_tests {
     fn dynamic_to_slvalue_array_recursive_covered() {
         let arr = Array::from([Dynamic::from_array(Array::from([Dynamic::from_bool(true)]))]);
         let dynamic = Dynamic::from_array(arr);
-        let result = dynamic_to_slvalue(dynamic).expect("array recursive");
+        let result =
+            dynamic_to_slvalue(dynamic, IntPrecisionPolicy::Error).expect("array recursive");
         assert!(matches!(result, SlValue::Array(vec) if vec.len() == 1));
 
-        let bad = Dynamic::from_array(Array::from([Dynamic::UNIT]));
-        let error = dynamic_to_slvalue(bad).expect_err("nested unsupported array value");
+        let bad = Dynamic::from_array(Array::from([Dynamic::from('x')]));
+        let error = dynamic_to_slvalue(bad, IntPrecisionPolicy::Error)
+            .expect_err("nested unsupported array value");
         assert_eq!(error.code, "ENGINE_VALUE_UNSUPPORTED");
     }
 
@@ -229,19 +396,68 @@ mod rhai_bridge_tests {
             Dynamic::from_array(Array::from([Dynamic::from_bool(false)])),
         );
         let dynamic = Dynamic::from_map(map);
-        let result = dynamic_to_slvalue(dynamic).expect("map recursive");
+        let result = dynamic_to_slvalue(dynamic, IntPrecisionPolicy::Error).expect("map recursive");
         assert!(matches!(result, SlValue::Map(m) if m.contains_key("arr")));
 
         let mut bad = Map::new();
-        bad.insert("bad".into(), Dynamic::UNIT);
-        let error =
-            dynamic_to_slvalue(Dynamic::from_map(bad)).expect_err("nested unsupported map value");
+        bad.insert("bad".into(), Dynamic::from('x'));
+        let error = dynamic_to_slvalue(Dynamic::from_map(bad), IntPrecisionPolicy::Error)
+            .expect_err("nested unsupported map value");
         assert_eq!(error.code, "ENGINE_VALUE_UNSUPPORTED");
     }
 
     #[test]
     fn dynamic_to_slvalue_error_covered() {
-        let result = dynamic_to_slvalue(Dynamic::UNIT);
+        let result = dynamic_to_slvalue(Dynamic::from('x'), IntPrecisionPolicy::Error);
         assert!(result.is_err());
+        assert_eq!(
+            dynamic_to_slvalue(Dynamic::UNIT, IntPrecisionPolicy::Error).expect("unit"),
+            SlValue::Null
+        );
+        assert!(slvalue_to_dynamic(&SlValue::Null).is_unit());
+        assert_eq!(slvalue_to_text(&SlValue::Null), "");
+        assert_eq!(slvalue_to_rhai_literal(&SlValue::Null), "()");
+    }
+
+    #[test]
+    fn int_to_number_applies_policy_outside_exact_range() {
+        let above = (1 << 53) + 1;
+        for value in [MIN_EXACT_INT, MAX_EXACT_INT, -42] {
+            for policy in [
+                IntPrecisionPolicy::Error,
+                IntPrecisionPolicy::Saturate,
+                IntPrecisionPolicy::Wrap,
+            ] {
+                assert_eq!(int_to_number(value, policy).expect("exact"), value as f64);
+            }
+        }
+
+        let error = int_to_number(above, IntPrecisionPolicy::Error).expect_err("too large");
+        assert_eq!(error.code, "ENGINE_INT_PRECISION");
+        assert_eq!(
+            int_to_number(above, IntPrecisionPolicy::Saturate).expect("saturate"),
+            MAX_EXACT_INT as f64
+        );
+        assert_eq!(
+            int_to_number(-above - 1, IntPrecisionPolicy::Saturate).expect("saturate"),
+            MIN_EXACT_INT as f64
+        );
+        assert_eq!(
+            int_to_number(above, IntPrecisionPolicy::Wrap).expect("wrap"),
+            (MIN_EXACT_INT + 1) as f64
+        );
+        assert_eq!(
+            int_to_number(INT::MAX, IntPrecisionPolicy::Wrap).expect("wrap"),
+            -1.0
+        );
+
+        let nested = Dynamic::from_array(Array::from([Dynamic::from_int(above)]));
+        let error = dynamic_to_slvalue(nested.clone(), IntPrecisionPolicy::Error)
+            .expect_err("nested too large");
+        assert_eq!(error.code, "ENGINE_INT_PRECISION");
+        assert_eq!(
+            dynamic_to_slvalue(nested, IntPrecisionPolicy::Saturate).expect("saturate"),
+            SlValue::Array(vec![SlValue::Number(MAX_EXACT_INT as f64)])
+        );
     }
 }
@@ -21,6 +21,11 @@ pub enum SlTestExampleError {
         path: PathBuf,
         source: serde_json::Error,
     },
+    #[error("Failed to parse game data {path}: {source}")]
+    ParseGameData {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
     #[error("Invalid testcase schema version \"{found}\", expected \"{expected}\".")]
     InvalidSchemaVersion { expected: String, found: String },
     #[error("No .xml files under {path}.")]
@@ -1,11 +1,13 @@
+use std::collections::BTreeMap;
 use std::path::Path;
 
 use sl_api::{
     compile_artifact_from_xml_map, create_engine_from_artifact, CreateEngineFromArtifactOptions,
+    EngineTuning,
 };
 use sl_runtime::DEFAULT_COMPILER_VERSION;
 
-use crate::source::{read_scripts_xml_from_dir, read_test_case};
+use crate::source::{read_game_data, read_scripts_xml_from_dir, read_test_case};
 use crate::{ExpectedEvent, SlTestExampleError, TestAction, TestCase};
 
 const MAX_STEPS: usize = 5_000;
@@ -21,6 +23,9 @@ pub fn run_case(example_dir: &Path, case: &TestCase) -> Result<RunReport, SlTest
     let scripts_xml = read_scripts_xml_from_dir(example_dir)?;
     let artifact = compile_artifact_from_xml_map(&scripts_xml, Some(case.entry_script.clone()))
         .map_err(SlTestExampleError::Engine)?;
+    let host_constants = read_game_data(example_dir)?
+        .map(|game| BTreeMap::from([("game".to_string(), game)]))
+        .unwrap_or_default();
     let mut engine = create_engine_from_artifact(CreateEngineFromArtifactOptions {
         artifact,
         entry_args: None,
@@ -29,6 +34,10 @@ pub fn run_case(example_dir: &Path, case: &TestCase) -> Result<RunReport, SlTest
         random_sequence: None,
         random_sequence_index: None,
         compiler_version: Some(DEFAULT_COMPILER_VERSION.to_string()),
+        tuning: EngineTuning {
+            host_constants,
+            ..EngineTuning::default()
+        },
     })
     .map_err(SlTestExampleError::Engine)?;
 
@@ -38,11 +47,13 @@ pub fn run_case(example_dir: &Path, case: &TestCase) -> Result<RunReport, SlTest
     for step in 1..=MAX_STEPS {
         let output = engine.next_output().map_err(SlTestExampleError::Engine)?;
         match output {
-            sl_core::EngineOutput::Text { text, tag } => {
+            sl_core::EngineOutput::Text { text, tag, .. } => {
                 observed_events.push(ExpectedEvent::Text { text, tag });
             }
-            sl_core::EngineOutput::Debug { .. } => {}
-            sl_core::EngineOutput::Choices { items, prompt_text } => {
+            sl_core::EngineOutput::Debug { .. } | sl_core::EngineOutput::Checkpoint { .. } => {}
+            sl_core::EngineOutput::Choices {
+                items, prompt_text, ..
+            } => {
                 let choices = items.into_iter().map(|item| item.text).collect();
                 observed_events.push(ExpectedEvent::Choices {
                     prompt_text,
@@ -2,6 +2,7 @@ use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
+use sl_core::{slvalue_from_json, SlValue};
 use walkdir::WalkDir;
 
 use crate::{SlTestExampleError, TestCase, TESTCASE_SCHEMA};
@@ -51,6 +52,22 @@ pub fn read_scripts_xml_from_dir(
     Ok(scripts)
 }
 
+/// Optional `game.json` of an example, which the runner exposes to scripts as the host constant
+/// `game`.
+pub fn read_game_data(example_dir: &Path) -> Result<Option<SlValue>, SlTestExampleError> {
+    let path = example_dir.join("game.json");
+    if !path.is_file() {
+        return Ok(None);
+    }
+    let raw = fs::read_to_string(&path).map_err(|source| SlTestExampleError::ReadFile {
+        path: path.clone(),
+        source,
+    })?;
+    let parsed = serde_json::from_str(&raw)
+        .map_err(|source| SlTestExampleError::ParseGameData { path, source })?;
+    Ok(Some(slvalue_from_json(parsed)))
+}
+
 pub fn read_test_case(case_path: &Path) -> Result<TestCase, SlTestExampleError> {
     let raw = fs::read_to_string(case_path).map_err(|source| SlTestExampleError::ReadFile {
         path: case_path.to_path_buf(),
@@ -305,3 +305,13 @@ fn example_48_sub_module_complex_matches_testcase() {
 fn example_49_submodule_parent_visible_matches_testcase() {
     assert_example("49-submodule-parent-visible");
 }
+
+#[test]
+fn example_50_json_global_escapes_matches_testcase() {
+    assert_example("50-json-global-escapes");
+}
+
+#[test]
+fn example_51_json_null_fallback_matches_testcase() {
+    assert_example("51-json-null-fallback");
+}